@@ -34,7 +34,7 @@ use frame_support::{
 use pallet_transaction_payment::Multiplier;
 use sp_runtime::{
 	testing::H256,
-	traits::{BlakeTwo256, ConstU32, ConstU64, ConstU8},
+	traits::{BlakeTwo256, ConstBool, ConstU32, ConstU64, ConstU8},
 	FixedPointNumber, Perquintill, StateVersion,
 };
 
@@ -170,6 +170,8 @@ impl pallet_bridge_grandpa::Config for TestRuntime {
 	type MaxFreeHeadersPerBlock = ConstU32<4>;
 	type FreeHeadersInterval = ConstU32<1_024>;
 	type HeadersToKeep = ConstU32<8>;
+	type ArchivePrunedHeaders = ConstBool<false>;
+	type AuthoritySetChangesToKeep = ConstU32<0>;
 	type WeightInfo = pallet_bridge_grandpa::weights::BridgeWeight<TestRuntime>;
 }
 
@@ -215,6 +217,9 @@ impl pallet_bridge_relayers::Config for TestRuntime {
 	type StakeAndSlash = TestStakeAndSlash;
 	type WeightInfo = ();
 	type LaneId = TestLaneIdType;
+	type MaxInvalidSubmissions = ConstU32<8>;
+	type PenaltyDuration = ConstU32<16>;
+	type MaxDeliveredMessagesRecords = ConstU32<16>;
 }
 
 /// Dummy message dispatcher.
@@ -86,13 +86,13 @@ pub type ImportedCommitment<T, I> = bp_beefy::ImportedCommitment<
 >;
 
 /// Some high level info about the imported commitments.
-#[derive(codec::Encode, codec::Decode, scale_info::TypeInfo)]
+#[derive(codec::Encode, codec::Decode, scale_info::TypeInfo, sp_runtime::RuntimeDebug)]
 pub struct ImportedCommitmentsInfoData<BlockNumber> {
 	/// Best known block number, provided in a BEEFY commitment. However this is not
 	/// the best proven block. The best proven block is this block's parent.
-	best_block_number: BlockNumber,
+	pub best_block_number: BlockNumber,
 	/// The head of the `ImportedBlockNumbers` ring buffer.
-	next_block_number_index: u32,
+	pub next_block_number_index: u32,
 }
 
 #[frame_support::pallet(dev_mode)]
@@ -104,6 +104,10 @@ pub mod pallet {
 
 	#[pallet::config]
 	pub trait Config<I: 'static = ()>: frame_system::Config {
+		/// The overarching event type.
+		type RuntimeEvent: From<Event<Self, I>>
+			+ IsType<<Self as frame_system::Config>::RuntimeEvent>;
+
 		/// The upper bound on the number of requests allowed by the pallet.
 		///
 		/// A request refers to an action which writes a header to storage.
@@ -271,6 +275,8 @@ pub mod pallet {
 				commitment.commitment.block_number,
 			);
 
+			Self::deposit_event(Event::CommitmentImported { commitment });
+
 			Ok(())
 		}
 	}
@@ -355,6 +361,16 @@ pub mod pallet {
 		}
 	}
 
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config<I>, I: 'static = ()> {
+		/// BEEFY commitment has been imported and its block is the new best known block.
+		CommitmentImported {
+			/// The imported signed commitment.
+			commitment: BridgedBeefySignedCommitment<T, I>,
+		},
+	}
+
 	#[pallet::error]
 	pub enum Error<T, I = ()> {
 		/// The pallet has not been initialized yet.
@@ -406,6 +422,25 @@ pub mod pallet {
 	}
 }
 
+impl<T: Config<I>, I: 'static> Pallet<T, I>
+where
+	<T as frame_system::Config>::RuntimeEvent: TryInto<Event<T, I>>,
+{
+	/// Get the BEEFY commitments accepted in the current block.
+	pub fn synced_headers_beefy_info() -> Vec<BridgedBeefySignedCommitment<T, I>> {
+		frame_system::Pallet::<T>::read_events_no_consensus()
+			.filter_map(|event| {
+				if let Event::<T, I>::CommitmentImported { commitment } =
+					event.event.try_into().ok()?
+				{
+					return Some(commitment)
+				}
+				None
+			})
+			.collect()
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -567,6 +602,30 @@ mod tests {
 		})
 	}
 
+	#[test]
+	fn synced_headers_beefy_info_returns_commitments_imported_in_current_block() {
+		run_test_with_initialize(3, || {
+			next_block();
+			let chain = ChainBuilder::new(3)
+				.append_finalized_header() // 1
+				.append_default_headers(2) // 2..3
+				.append_finalized_header(); // 4
+			import_header_chain(chain.to_chain());
+
+			let synced = Pallet::<TestRuntime>::synced_headers_beefy_info();
+			assert_eq!(
+				synced.iter().map(|c| c.commitment.block_number).collect::<Vec<_>>(),
+				vec![1, 4],
+			);
+			assert_eq!(Some(&synced[1]), chain.header(4).commitment.as_ref());
+
+			// events of the previous block are not returned
+			frame_system::Pallet::<TestRuntime>::reset_events();
+			next_block();
+			assert!(Pallet::<TestRuntime>::synced_headers_beefy_info().is_empty());
+		})
+	}
+
 	#[test]
 	fn commitment_pruning_works() {
 		run_test_with_initialize(3, || {
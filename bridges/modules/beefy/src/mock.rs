@@ -63,7 +63,7 @@ construct_runtime! {
 	pub enum TestRuntime
 	{
 		System: frame_system::{Pallet, Call, Config<T>, Storage, Event<T>},
-		Beefy: beefy::{Pallet},
+		Beefy: beefy::{Pallet, Event<T>},
 	}
 }
 
@@ -73,6 +73,7 @@ impl frame_system::Config for TestRuntime {
 }
 
 impl beefy::Config for TestRuntime {
+	type RuntimeEvent = RuntimeEvent;
 	type MaxRequests = frame_support::traits::ConstU32<16>;
 	type BridgedChain = TestBridgedChain;
 	type CommitmentsToKeep = frame_support::traits::ConstU32<16>;
@@ -105,6 +106,8 @@ impl Chain for TestBridgedChain {
 }
 
 impl ChainWithBeefy for TestBridgedChain {
+	const WITH_CHAIN_BEEFY_PALLET_NAME: &'static str = "Beefy";
+
 	type CommitmentHasher = Keccak256;
 	type MmrHashing = Keccak256;
 	type MmrHash = <Keccak256 as Hash>::Output;
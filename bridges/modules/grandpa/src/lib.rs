@@ -38,12 +38,16 @@
 pub use storage_types::StoredAuthoritySet;
 
 use bp_header_chain::{
-	justification::GrandpaJustification, AuthoritySet, ChainWithGrandpa, GrandpaConsensusLogReader,
-	HeaderChain, InitializationData, StoredHeaderData, StoredHeaderDataBuilder,
-	StoredHeaderGrandpaInfo,
+	archive::{ArchivedHeader, ArchivedHeaderProof, ArchivedHeadersMmr},
+	justification::GrandpaJustification,
+	AuthoritySet, ChainWithGrandpa, GrandpaConsensusLogReader, HeaderChain, InitializationData,
+	StoredHeaderData, StoredHeaderDataBuilder, StoredHeaderGrandpaInfo,
 };
 use bp_runtime::{BlockNumberOf, HashOf, HasherOf, HeaderId, HeaderOf, OwnedBridgeModule};
-use frame_support::{dispatch::PostDispatchInfo, ensure, DefaultNoBound};
+use codec::MaxEncodedLen;
+use frame_support::{
+	dispatch::PostDispatchInfo, ensure, traits::Get, weights::Weight, DefaultNoBound,
+};
 use sp_consensus_grandpa::{AuthorityList, SetId};
 use sp_runtime::{
 	traits::{Header as HeaderT, Zero},
@@ -87,6 +91,9 @@ pub type BridgedHeader<T, I> = HeaderOf<<T as Config<I>>::BridgedChain>;
 /// Header data of the bridged chain that is stored at this chain by this pallet.
 pub type BridgedStoredHeaderData<T, I> =
 	StoredHeaderData<BridgedBlockNumber<T, I>, BridgedBlockHash<T, I>>;
+/// Proof that the bridged chain header has been archived by this pallet.
+pub type BridgedArchivedHeaderProof<T, I> =
+	ArchivedHeaderProof<BridgedBlockNumber<T, I>, BridgedBlockHash<T, I>>;
 
 #[frame_support::pallet]
 pub mod pallet {
@@ -140,6 +147,27 @@ pub mod pallet {
 		#[pallet::constant]
 		type HeadersToKeep: Get<u32>;
 
+		/// If `true`, headers that are pruned from the storage are archived.
+		///
+		/// Archived headers are appended to the Merkle Mountain Range, of which only peaks
+		/// are kept in the storage. So the state root of the archived header may still be
+		/// verified (see [`Pallet::archived_header_state_root`]) if the caller provides a
+		/// proof, built from `HeaderArchived` events. In addition, finality proofs of recent
+		/// mandatory headers are kept in the storage, so that the history of authority set
+		/// changes may be exported (see [`Pallet::authority_set_changes`]).
+		#[pallet::constant]
+		type ArchivePrunedHeaders: Get<bool>;
+
+		/// Maximal number of authority set changes to keep in the storage, if
+		/// `ArchivePrunedHeaders` is `true`.
+		///
+		/// Like `HeadersToKeep`, the setting does not relate to set ids - we will simply keep
+		/// finality proofs of that many latest authority set changes.
+		///
+		/// Incautious change of this constant may lead to orphan entries in the runtime storage.
+		#[pallet::constant]
+		type AuthoritySetChangesToKeep: Get<u32>;
+
 		/// Weights gathered through benchmarking.
 		type WeightInfo: WeightInfoExt;
 	}
@@ -175,7 +203,7 @@ pub mod pallet {
 		#[pallet::weight(T::WeightInfo::submit_finality_proof_weight(
 			justification.commit.precommits.len().saturated_into(),
 			justification.votes_ancestries.len().saturated_into(),
-		))]
+		).saturating_add(Pallet::<T, I>::archive_weight()))]
 		#[allow(deprecated)]
 		#[deprecated(
 			note = "`submit_finality_proof` will be removed in May 2024. Use `submit_finality_proof_ex` instead."
@@ -279,7 +307,7 @@ pub mod pallet {
 		#[pallet::weight(T::WeightInfo::submit_finality_proof_weight(
 			justification.commit.precommits.len().saturated_into(),
 			justification.votes_ancestries.len().saturated_into(),
-		))]
+		).saturating_add(Pallet::<T, I>::archive_weight()))]
 		pub fn submit_finality_proof_ex(
 			origin: OriginFor<T>,
 			finality_target: Box<BridgedHeader<T, I>>,
@@ -310,6 +338,9 @@ pub mod pallet {
 
 			let maybe_new_authority_set =
 				try_enact_authority_change::<T, I>(&finality_target, set_id)?;
+			if let Some(ref new_authority_set) = maybe_new_authority_set {
+				archive_authority_set_change::<T, I>(set_id, &justification, new_authority_set);
+			}
 			let may_refund_call_fee = may_refund_call_fee::<T, I>(
 				&finality_target,
 				&justification,
@@ -345,7 +376,8 @@ pub mod pallet {
 			let pre_dispatch_weight = T::WeightInfo::submit_finality_proof(
 				justification.commit.precommits.len().saturated_into(),
 				justification.votes_ancestries.len().saturated_into(),
-			);
+			)
+			.saturating_add(Self::archive_weight());
 			let actual_weight = pre_dispatch_weight
 				.set_proof_size(pre_dispatch_weight.proof_size().saturating_sub(unused_proof_size));
 
@@ -449,6 +481,47 @@ pub mod pallet {
 		MaxValues = MaybeHeadersToKeep<T, I>,
 	>;
 
+	/// Merkle Mountain Range of headers that have been pruned from the `ImportedHeaders`.
+	///
+	/// It is only updated if `Config::ArchivePrunedHeaders` is `true`.
+	#[pallet::storage]
+	pub type ArchivedHeaders<T: Config<I>, I: 'static = ()> =
+		StorageValue<_, ArchivedHeadersMmr<BridgedBlockHash<T, I>>, ValueQuery>;
+
+	/// Finality proofs of imported mandatory headers, mapped by the id of the authority set that
+	/// has been changed by the header.
+	///
+	/// It is only updated if `Config::ArchivePrunedHeaders` is `true`. At most
+	/// `Config::AuthoritySetChangesToKeep` latest changes are kept. Authority set changes,
+	/// made by the `force_set_pallet_state` call, have no finality proofs and are not stored here.
+	#[pallet::storage]
+	#[pallet::unbounded]
+	pub type AuthoritySetChanges<T: Config<I>, I: 'static = ()> = StorageMap<
+		Hasher = Identity,
+		Key = SetId,
+		Value = StoredHeaderGrandpaInfo<BridgedHeader<T, I>>,
+		QueryKind = OptionQuery,
+		OnEmpty = GetDefault,
+		MaxValues = MaybeAuthoritySetChangesToKeep<T, I>,
+	>;
+
+	/// A ring buffer of ids of the authority sets, whose changes are stored in the
+	/// `AuthoritySetChanges`. Ordered by the insertion time.
+	#[pallet::storage]
+	pub(super) type AuthoritySetChangesIds<T: Config<I>, I: 'static = ()> = StorageMap<
+		Hasher = Identity,
+		Key = u32,
+		Value = SetId,
+		QueryKind = OptionQuery,
+		OnEmpty = GetDefault,
+		MaxValues = MaybeAuthoritySetChangesToKeep<T, I>,
+	>;
+
+	/// Current `AuthoritySetChangesIds` ring buffer position.
+	#[pallet::storage]
+	pub(super) type AuthoritySetChangesPointer<T: Config<I>, I: 'static = ()> =
+		StorageValue<_, u32, ValueQuery>;
+
 	/// The current GRANDPA Authority set.
 	#[pallet::storage]
 	pub type CurrentAuthoritySet<T: Config<I>, I: 'static = ()> =
@@ -509,6 +582,15 @@ pub mod pallet {
 			/// The Grandpa info associated to the new best finalized header.
 			grandpa_info: StoredHeaderGrandpaInfo<BridgedHeader<T, I>>,
 		},
+		/// Header has been pruned from the storage and appended to the archive.
+		HeaderArchived {
+			/// Number of the archived header.
+			number: BridgedBlockNumber<T, I>,
+			/// Hash of the archived header.
+			hash: BridgedBlockHash<T, I>,
+			/// Index of the header leaf in the archive Merkle Mountain Range.
+			leaf_index: u64,
+		},
 	}
 
 	#[pallet::error]
@@ -715,8 +797,70 @@ pub mod pallet {
 		<ImportedHashesPointer<T, I>>::put((index + 1) % T::HeadersToKeep::get());
 		if let Ok(hash) = pruning {
 			log::debug!(target: LOG_TARGET, "Pruning old header: {:?}.", hash);
-			<ImportedHeaders<T, I>>::remove(hash);
+			let pruned_header = <ImportedHeaders<T, I>>::take(hash);
+			if let Some(pruned_header) = pruned_header.filter(|_| T::ArchivePrunedHeaders::get()) {
+				archive_header::<T, I>(pruned_header, hash);
+			}
+		}
+	}
+
+	/// Append pruned header to the archive.
+	fn archive_header<T: Config<I>, I: 'static>(
+		header: BridgedStoredHeaderData<T, I>,
+		hash: BridgedBlockHash<T, I>,
+	) {
+		let archived_header =
+			ArchivedHeader { number: header.number, hash, state_root: header.state_root };
+		let leaf = archived_header.leaf_hash::<BridgedBlockHasher<T, I>>();
+		let leaf_index =
+			<ArchivedHeaders<T, I>>::mutate(|mmr| mmr.append::<BridgedBlockHasher<T, I>>(leaf));
+
+		log::debug!(
+			target: LOG_TARGET,
+			"Archived header {:?} at MMR leaf {}.",
+			hash,
+			leaf_index,
+		);
+
+		Pallet::<T, I>::deposit_event(Event::HeaderArchived {
+			number: header.number,
+			hash,
+			leaf_index,
+		});
+	}
+
+	/// Save the finality proof of the mandatory header, which has changed the authority set with
+	/// `set_id` id, if archival is enabled. The oldest saved proof is pruned if there are already
+	/// `Config::AuthoritySetChangesToKeep` proofs in the storage.
+	fn archive_authority_set_change<T: Config<I>, I: 'static>(
+		set_id: SetId,
+		justification: &GrandpaJustification<BridgedHeader<T, I>>,
+		new_authority_set: &AuthoritySet,
+	) {
+		let changes_to_keep = T::AuthoritySetChangesToKeep::get();
+		if !T::ArchivePrunedHeaders::get() || changes_to_keep == 0 {
+			return
+		}
+
+		let index = <AuthoritySetChangesPointer<T, I>>::get();
+		if let Ok(pruned_set_id) = <AuthoritySetChangesIds<T, I>>::try_get(index) {
+			log::debug!(
+				target: LOG_TARGET,
+				"Pruning finality proof of the authority set {} change.",
+				pruned_set_id,
+			);
+			<AuthoritySetChanges<T, I>>::remove(pruned_set_id);
 		}
+		<AuthoritySetChangesIds<T, I>>::insert(index, set_id);
+		<AuthoritySetChangesPointer<T, I>>::put((index + 1) % changes_to_keep);
+
+		<AuthoritySetChanges<T, I>>::insert(
+			set_id,
+			StoredHeaderGrandpaInfo {
+				finality_proof: justification.clone(),
+				new_verification_context: Some(new_authority_set.clone()),
+			},
+		);
 	}
 
 	/// Since this writes to storage with no real checks this should only be used in functions that
@@ -759,6 +903,17 @@ pub mod pallet {
 		}
 	}
 
+	/// Adapter for using `Config::AuthoritySetChangesToKeep` as `MaxValues` bound in our storage
+	/// maps.
+	pub struct MaybeAuthoritySetChangesToKeep<T, I>(PhantomData<(T, I)>);
+
+	// this implementation is required to use the struct as `MaxValues`
+	impl<T: Config<I>, I: 'static> Get<Option<u32>> for MaybeAuthoritySetChangesToKeep<T, I> {
+		fn get() -> Option<u32> {
+			Some(T::AuthoritySetChangesToKeep::get())
+		}
+	}
+
 	/// Initialize pallet so that it is ready for inserting new header.
 	///
 	/// The function makes sure that the new insertion will cause the pruning of some old header.
@@ -781,6 +936,54 @@ pub mod pallet {
 	}
 }
 
+impl<T: Config<I>, I: 'static> Pallet<T, I> {
+	/// Returns state root of the archived header, if `proof` proves that the header has been
+	/// archived by the pallet.
+	pub fn archived_header_state_root(
+		proof: &BridgedArchivedHeaderProof<T, I>,
+	) -> Option<BridgedBlockHash<T, I>> {
+		ArchivedHeaders::<T, I>::get()
+			.verify_header::<BridgedBlockHasher<T, I>, _>(proof)
+			.then_some(proof.header.state_root)
+	}
+
+	/// Returns finality proofs of archived authority set changes, starting from the change of the
+	/// set with `from_set_id` id. At most `limit` proofs are returned.
+	pub fn authority_set_changes(
+		from_set_id: SetId,
+		limit: u32,
+	) -> Vec<StoredHeaderGrandpaInfo<BridgedHeader<T, I>>> {
+		let mut set_ids: Vec<_> = AuthoritySetChangesIds::<T, I>::iter_values()
+			.filter(|set_id| *set_id >= from_set_id)
+			.collect();
+		set_ids.sort_unstable();
+		set_ids.dedup();
+		set_ids
+			.into_iter()
+			.filter_map(|set_id| AuthoritySetChanges::<T, I>::get(set_id))
+			.take(limit as usize)
+			.collect()
+	}
+
+	/// Extra weight of the header import, spent on the archival.
+	fn archive_weight() -> Weight {
+		if !T::ArchivePrunedHeaders::get() {
+			return Weight::zero()
+		}
+
+		// read and write the MMR peaks, read and write the pointer and the entry of the
+		// authority set changes ring buffer, remove the pruned authority set change and write
+		// the new one. The new change is written, not read, so its size is not a part of the
+		// proof
+		let proof_size = ArchivedHeadersMmr::<BridgedBlockHash<T, I>>::max_encoded_len()
+			.saturating_add(u32::max_encoded_len())
+			.saturating_add(SetId::max_encoded_len());
+		T::DbWeight::get()
+			.reads_writes(3, 5)
+			.saturating_add(Weight::from_parts(0, proof_size as u64))
+	}
+}
+
 impl<T: Config<I>, I: 'static> Pallet<T, I>
 where
 	<T as frame_system::Config>::RuntimeEvent: TryInto<Event<T, I>>,
@@ -831,7 +1034,7 @@ mod tests {
 		run_test, test_header, FreeHeadersInterval, RuntimeEvent as TestEvent, RuntimeOrigin,
 		System, TestBridgedChain, TestHeader, TestNumber, TestRuntime, MAX_BRIDGED_AUTHORITIES,
 	};
-	use bp_header_chain::BridgeGrandpaCall;
+	use bp_header_chain::{archive::build_mmr_proof, BridgeGrandpaCall};
 	use bp_runtime::BasicOperatingMode;
 	use bp_test_utils::{
 		authority_list, generate_owned_bridge_module_tests, make_default_justification,
@@ -1658,6 +1861,136 @@ mod tests {
 		})
 	}
 
+	#[test]
+	fn should_archive_pruned_headers() {
+		run_test(|| {
+			mock::ArchivePrunedHeaders::set(true);
+			initialize_substrate_bridge();
+			for number in 1..=8 {
+				assert_ok!(submit_finality_proof(number));
+				next_block();
+			}
+
+			// headers 0..=3 are pruned, because we only keep 5 headers
+			let archived_headers: Vec<_> = (0..=3)
+				.map(|number| {
+					let header = test_header(number);
+					ArchivedHeader { number, hash: header.hash(), state_root: header.state_root }
+				})
+				.collect();
+			let leaves: Vec<_> = archived_headers
+				.iter()
+				.map(|header| header.leaf_hash::<BridgedBlockHasher<TestRuntime, ()>>())
+				.collect();
+			assert_eq!(ArchivedHeaders::<TestRuntime>::get().leaves, 4);
+
+			for (leaf_index, header) in archived_headers.into_iter().enumerate() {
+				let leaf_index = leaf_index as u64;
+				assert!(System::events().iter().any(|event| event.event ==
+					TestEvent::Grandpa(Event::HeaderArchived {
+						number: header.number,
+						hash: header.hash,
+						leaf_index,
+					})));
+
+				let mut proof = ArchivedHeaderProof {
+					header: header.clone(),
+					leaf_index,
+					siblings: build_mmr_proof::<BridgedBlockHasher<TestRuntime, ()>>(
+						&leaves, leaf_index,
+					)
+					.unwrap(),
+				};
+				assert_eq!(
+					Pallet::<TestRuntime>::archived_header_state_root(&proof),
+					Some(header.state_root)
+				);
+
+				// proof of the header with other state root is rejected
+				proof.header.state_root = [42u8; 32].into();
+				assert_eq!(Pallet::<TestRuntime>::archived_header_state_root(&proof), None);
+			}
+		})
+	}
+
+	#[test]
+	fn should_not_archive_pruned_headers_if_archival_is_disabled() {
+		run_test(|| {
+			initialize_substrate_bridge();
+			for number in 1..=6 {
+				assert_ok!(submit_finality_proof(number));
+				next_block();
+			}
+
+			assert!(!ImportedHeaders::<TestRuntime, ()>::contains_key(test_header(0).hash()));
+			assert_eq!(ArchivedHeaders::<TestRuntime>::get(), Default::default());
+		})
+	}
+
+	#[test]
+	fn should_archive_authority_set_changes() {
+		run_test(|| {
+			mock::ArchivePrunedHeaders::set(true);
+			initialize_substrate_bridge();
+
+			let mut header = test_header(2);
+			header.digest = change_log(0);
+			let justification = make_default_justification(&header);
+			assert_ok!(Pallet::<TestRuntime>::submit_finality_proof_ex(
+				RuntimeOrigin::signed(1),
+				Box::new(header),
+				justification.clone(),
+				TEST_GRANDPA_SET_ID,
+				false,
+			));
+
+			assert_eq!(
+				Pallet::<TestRuntime>::authority_set_changes(0, 16),
+				vec![StoredHeaderGrandpaInfo {
+					finality_proof: justification,
+					new_verification_context: Some(
+						<CurrentAuthoritySet<TestRuntime>>::get().into()
+					),
+				}]
+			);
+			assert_eq!(Pallet::<TestRuntime>::authority_set_changes(0, 0), vec![]);
+			assert_eq!(
+				Pallet::<TestRuntime>::authority_set_changes(TEST_GRANDPA_SET_ID + 1, 16),
+				vec![]
+			);
+		})
+	}
+
+	#[test]
+	fn should_prune_archived_authority_set_changes() {
+		run_test(|| {
+			mock::ArchivePrunedHeaders::set(true);
+			initialize_substrate_bridge();
+
+			// we keep 2 authority set changes
+			for (number, set_id) in (1..=3).zip(TEST_GRANDPA_SET_ID..) {
+				assert_ok!(submit_mandatory_finality_proof(number, set_id));
+				next_block();
+			}
+
+			assert!(!AuthoritySetChanges::<TestRuntime>::contains_key(TEST_GRANDPA_SET_ID));
+			assert_eq!(AuthoritySetChanges::<TestRuntime>::iter().count(), 2);
+			let changes = Pallet::<TestRuntime>::authority_set_changes(0, 16);
+			assert_eq!(
+				changes
+					.iter()
+					.map(|change| change.finality_proof.commit.target_number)
+					.collect::<Vec<_>>(),
+				vec![2, 3],
+			);
+			assert_eq!(
+				Pallet::<TestRuntime>::authority_set_changes(TEST_GRANDPA_SET_ID + 2, 16),
+				changes[1..].to_vec(),
+			);
+			assert_eq!(Pallet::<TestRuntime>::authority_set_changes(0, 1), changes[..1].to_vec());
+		})
+	}
+
 	#[test]
 	fn storage_keys_computed_properly() {
 		assert_eq!(
@@ -52,6 +52,8 @@ parameter_types! {
 	pub const MaxFreeHeadersPerBlock: u32 = 2;
 	pub const FreeHeadersInterval: u32 = 32;
 	pub const HeadersToKeep: u32 = 5;
+	pub static ArchivePrunedHeaders: bool = false;
+	pub const AuthoritySetChangesToKeep: u32 = 2;
 }
 
 impl grandpa::Config for TestRuntime {
@@ -60,6 +62,8 @@ impl grandpa::Config for TestRuntime {
 	type MaxFreeHeadersPerBlock = MaxFreeHeadersPerBlock;
 	type FreeHeadersInterval = FreeHeadersInterval;
 	type HeadersToKeep = HeadersToKeep;
+	type ArchivePrunedHeaders = ArchivePrunedHeaders;
+	type AuthoritySetChangesToKeep = AuthoritySetChangesToKeep;
 	type WeightInfo = ();
 }
 
@@ -51,7 +51,7 @@ use scale_info::TypeInfo;
 use sp_core::H256;
 use sp_runtime::{
 	testing::Header as SubstrateHeader,
-	traits::{BlakeTwo256, ConstU32},
+	traits::{BlakeTwo256, ConstBool, ConstU32},
 	BuildStorage, StateVersion,
 };
 use std::{collections::VecDeque, ops::RangeInclusive};
@@ -181,6 +181,8 @@ impl pallet_bridge_grandpa::Config for TestRuntime {
 	type MaxFreeHeadersPerBlock = ConstU32<4>;
 	type FreeHeadersInterval = ConstU32<1_024>;
 	type HeadersToKeep = ConstU32<8>;
+	type ArchivePrunedHeaders = ConstBool<false>;
+	type AuthoritySetChangesToKeep = ConstU32<0>;
 	type WeightInfo = pallet_bridge_grandpa::weights::BridgeWeight<TestRuntime>;
 }
 
@@ -18,7 +18,9 @@ use bp_header_chain::ChainWithGrandpa;
 use bp_polkadot_core::parachains::ParaId;
 use bp_runtime::{Chain, ChainId, Parachain};
 use frame_support::{
-	construct_runtime, derive_impl, parameter_types, traits::ConstU32, weights::Weight,
+	construct_runtime, derive_impl, parameter_types,
+	traits::{ConstBool, ConstU32},
+	weights::Weight,
 };
 use sp_runtime::{
 	testing::H256,
@@ -189,6 +191,8 @@ impl pallet_bridge_grandpa::Config<pallet_bridge_grandpa::Instance1> for TestRun
 	type MaxFreeHeadersPerBlock = ConstU32<2>;
 	type FreeHeadersInterval = FreeHeadersInterval;
 	type HeadersToKeep = HeadersToKeep;
+	type ArchivePrunedHeaders = ConstBool<false>;
+	type AuthoritySetChangesToKeep = ConstU32<0>;
 	type WeightInfo = ();
 }
 
@@ -198,6 +202,8 @@ impl pallet_bridge_grandpa::Config<pallet_bridge_grandpa::Instance2> for TestRun
 	type MaxFreeHeadersPerBlock = ConstU32<2>;
 	type FreeHeadersInterval = FreeHeadersInterval;
 	type HeadersToKeep = HeadersToKeep;
+	type ArchivePrunedHeaders = ConstBool<false>;
+	type AuthoritySetChangesToKeep = ConstU32<0>;
 	type WeightInfo = ();
 }
 
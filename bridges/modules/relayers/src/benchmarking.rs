@@ -133,5 +133,14 @@ benchmarks_instance_pallet! {
 		assert_eq!(RelayerRewards::<T, I>::get(relayer, &account_params), Some(One::one()));
 	}
 
+	// Benchmark `set_delivery_reward_share` call.
+	set_delivery_reward_share {
+		let lane = T::bench_lane_id();
+		let share = Some(Perbill::from_percent(50));
+	}: _(RawOrigin::Root, lane, share)
+	verify {
+		assert_eq!(DeliveryRewardShares::<T, I>::get(lane), share);
+	}
+
 	impl_benchmark_test_suite!(Pallet, crate::mock::new_test_ext(), crate::mock::TestRuntime)
 }
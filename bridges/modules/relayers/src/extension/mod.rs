@@ -22,9 +22,11 @@
 //! - bump priority of messages delivery and confirmation transactions, signed by the registered
 //!   relayers.
 
-use crate::{Config as RelayersConfig, Pallet as RelayersPallet, WeightInfoExt, LOG_TARGET};
+use crate::{
+	Config as RelayersConfig, Pallet as RelayersPallet, WeightInfo, WeightInfoExt, LOG_TARGET,
+};
 
-use bp_messages::{ChainWithMessages, MessageNonce};
+use bp_messages::{ChainWithMessages, MessageNonce, MessagesCallInfo};
 use bp_relayers::{
 	ExplicitOrAccountParams, ExtensionCallData, ExtensionCallInfo, ExtensionConfig,
 	RewardsAccountOwner, RewardsAccountParams,
@@ -34,6 +36,7 @@ use codec::{Decode, Encode};
 use frame_support::{
 	dispatch::{DispatchInfo, PostDispatchInfo},
 	pallet_prelude::TransactionSource,
+	traits::Get,
 	weights::Weight,
 	CloneNoBound, DefaultNoBound, EqNoBound, PartialEqNoBound, RuntimeDebugNoBound,
 };
@@ -100,6 +103,9 @@ impl<AccountId, RemoteGrandpaChainBlockNumber: Debug, LaneId: Clone + Copy + Deb
 pub enum RelayerAccountAction<AccountId, Reward, LaneId> {
 	/// Do nothing with relayer account.
 	None,
+	/// Refuse to refund the relayer, because it has submitted a transaction with obsolete or
+	/// duplicate proof.
+	Refuse(AccountId, RewardsAccountParams<LaneId>),
 	/// Reward the relayer.
 	Reward(AccountId, RewardsAccountParams<LaneId>, Reward),
 	/// Slash the relayer.
@@ -169,6 +175,37 @@ where
 		Some(bundled_messages)
 	}
 
+	/// Returns weight of the relayers pallet storage accesses, caused by the relayer call.
+	///
+	/// The `RelayerReputations` entry of the relayer is read in `validate` and may be read and
+	/// written in `post_dispatch`, where the penalized relayer is also slashed. The
+	/// `DeliveredMessages` history of every relayer, rewarded by the successful delivery
+	/// confirmation, is read and written by the messages pallet.
+	fn relayers_pallet_weight(
+		reputation_updated: bool,
+		relayer_penalized: bool,
+		rewarded_relayers: MessageNonce,
+	) -> Weight {
+		let db_weight = <R as SystemConfig>::DbWeight::get();
+		let mut weight = db_weight.reads(1);
+		if reputation_updated {
+			weight.saturating_accrue(db_weight.reads_writes(1, 1));
+		}
+		if relayer_penalized {
+			weight.saturating_accrue(
+				<R as RelayersConfig<C::BridgeRelayersPalletInstance>>::WeightInfo::slash_and_deregister(),
+			);
+		}
+		weight.saturating_add(db_weight.reads_writes(rewarded_relayers, rewarded_relayers))
+	}
+
+	/// Returns maximal weight of the relayers pallet storage accesses, caused by the relayer call.
+	fn max_relayers_pallet_weight() -> Weight {
+		let max_rewarded_relayers = <R as BridgeMessagesConfig<C::BridgeMessagesPalletInstance>>::BridgedChain
+			::MAX_UNREWARDED_RELAYERS_IN_CONFIRMATION_TX;
+		Self::relayers_pallet_weight(true, true, max_rewarded_relayers)
+	}
+
 	/// Given post-dispatch information, analyze the outcome of relayer call and return
 	/// actions that need to be performed on relayer account.
 	fn analyze_call_result(
@@ -212,7 +249,9 @@ where
 		let may_slash_relayer = Self::bundled_messages_for_priority_boost(&call_info).is_some();
 		let slash_relayer_if_delivery_result = may_slash_relayer
 			.then(|| RelayerAccountAction::Slash(relayer.clone(), reward_account_params))
-			.unwrap_or(RelayerAccountAction::None);
+			.unwrap_or_else(|| {
+				RelayerAccountAction::Refuse(relayer.clone(), reward_account_params)
+			});
 
 		// We don't refund anything if the transaction has failed.
 		if let Err(e) = result {
@@ -224,10 +263,15 @@ where
 				relayer,
 				e,
 			);
-			return slash_relayer_if_delivery_result
+			return if may_slash_relayer {
+				RelayerAccountAction::Slash(relayer, reward_account_params)
+			} else {
+				RelayerAccountAction::None
+			}
 		}
 
-		// check whether the call has succeeded
+		// check whether the call has succeeded. If it has not updated the runtime storage, it
+		// has brought obsolete or duplicate proofs
 		let mut call_data = ExtensionCallData::default();
 		if !C::check_call_result(&call_info, &mut call_data, &relayer) {
 			return slash_relayer_if_delivery_result
@@ -294,7 +338,7 @@ where
 	type Val = Self::Pre;
 
 	fn weight(&self, _call: &R::RuntimeCall) -> Weight {
-		Weight::zero()
+		Self::max_relayers_pallet_weight()
 	}
 
 	fn validate(
@@ -315,6 +359,19 @@ where
 		// Those calls are only for signed transactions.
 		let relayer = origin.as_system_origin_signer().ok_or(InvalidTransaction::BadSigner)?;
 
+		// we reject transactions of penalized relayers
+		if RelayersPallet::<R, C::BridgeRelayersPalletInstance>::is_penalized(relayer) {
+			log::trace!(
+				target: LOG_TARGET,
+				"{}.{:?}: rejecting transaction of penalized relayer {:?}",
+				Self::IDENTIFIER,
+				parsed_call.messages_call_info().lane_id(),
+				relayer,
+			);
+
+			return Err(InvalidTransaction::Call.into())
+		}
+
 		let data = PreDispatchData { relayer: relayer.clone(), call_info: parsed_call };
 
 		// the following code just plays with transaction priority
@@ -378,17 +435,35 @@ where
 		len: usize,
 		result: &DispatchResult,
 	) -> Result<Weight, TransactionValidityError> {
-		let lane_id = pre.as_ref().map(|p| p.call_info.messages_call_info().lane_id());
+		// unsupported calls do not touch the relayers pallet storage
+		let Some(ref pre_data) = pre else { return Ok(Self::max_relayers_pallet_weight()) };
+		let lane_id = pre_data.call_info.messages_call_info().lane_id();
+		let confirmed_messages = match pre_data.call_info.messages_call_info() {
+			MessagesCallInfo::ReceiveMessagesDeliveryProof(_) =>
+				pre_data.call_info.messages_call_info().bundled_messages().saturating_len(),
+			MessagesCallInfo::ReceiveMessagesProof(_) => 0,
+		};
 		let call_result = Self::analyze_call_result(pre, info, post_info, len, result);
 
-		match call_result {
-			RelayerAccountAction::None => (),
+		let actual_weight = match call_result {
+			RelayerAccountAction::None => Self::relayers_pallet_weight(false, false, 0),
+			RelayerAccountAction::Refuse(relayer, slash_account) => {
+				let relayer_penalized =
+					RelayersPallet::<R, C::BridgeRelayersPalletInstance>::note_invalid_submission(
+						&relayer,
+						slash_account,
+					);
+				Self::relayers_pallet_weight(true, relayer_penalized, 0)
+			},
 			RelayerAccountAction::Reward(relayer, reward_account, reward) => {
 				RelayersPallet::<R, C::BridgeRelayersPalletInstance>::register_relayer_reward(
 					reward_account,
 					&relayer,
 					reward,
 				);
+				RelayersPallet::<R, C::BridgeRelayersPalletInstance>::note_valid_submission(
+					&relayer,
+				);
 
 				log::trace!(
 					target: LOG_TARGET,
@@ -398,15 +473,28 @@ where
 					reward,
 					relayer,
 				);
+
+				// the number of rewarded relayers never exceeds the number of confirmed messages
+				let max_rewarded_relayers = <R as BridgeMessagesConfig<
+					C::BridgeMessagesPalletInstance,
+				>>::BridgedChain::MAX_UNREWARDED_RELAYERS_IN_CONFIRMATION_TX;
+				Self::relayers_pallet_weight(
+					true,
+					false,
+					confirmed_messages.min(max_rewarded_relayers),
+				)
 			},
-			RelayerAccountAction::Slash(relayer, slash_account) =>
+			RelayerAccountAction::Slash(relayer, slash_account) => {
+				// the weight of slashing is a part of the delivery call weight
 				RelayersPallet::<R, C::BridgeRelayersPalletInstance>::slash_and_deregister(
 					&relayer,
 					ExplicitOrAccountParams::Params(slash_account),
-				),
-		}
+				);
+				Self::relayers_pallet_weight(false, false, 0)
+			},
+		};
 
-		Ok(Weight::zero())
+		Ok(Self::max_relayers_pallet_weight().saturating_sub(actual_weight))
 	}
 }
 
@@ -444,7 +532,7 @@ where
 #[cfg(test)]
 mod tests {
 	use super::*;
-	use crate::mock::*;
+	use crate::{mock::*, RelayerReputation, RelayerReputations};
 
 	use bp_header_chain::{StoredHeaderDataBuilder, SubmitFinalityProofInfo};
 	use bp_messages::{
@@ -1203,15 +1291,37 @@ mod tests {
 			PreDispatchData<ThisChainAccountId, BridgedChainBlockNumber, TestLaneIdType>,
 		>,
 		dispatch_result: DispatchResult,
-	) {
-		let post_dispatch_result = TestExtension::post_dispatch_details(
+	) -> Weight {
+		TestExtension::post_dispatch_details(
 			pre_dispatch_data,
 			&dispatch_info(),
 			&post_dispatch_info(),
 			1024,
 			&dispatch_result,
-		);
-		assert_eq!(post_dispatch_result, Ok(Weight::zero()));
+		)
+		.expect("post_dispatch never fails")
+	}
+
+	fn assert_refund_refused(
+		pre_dispatch_data: PreDispatchData<
+			ThisChainAccountId,
+			BridgedChainBlockNumber,
+			TestLaneIdType,
+		>,
+		dispatch_result: DispatchResult,
+	) {
+		// the only change is the increased number of invalid relayer submissions
+		let relayer = pre_dispatch_data.relayer;
+		let reputation = RelayerReputations::<TestRuntime>::get(relayer);
+		assert_storage_noop!({
+			run_post_dispatch(Some(pre_dispatch_data), dispatch_result);
+			assert_eq!(
+				RelayerReputations::<TestRuntime>::get(relayer)
+					.map(|reputation| reputation.invalid_submissions),
+				Some(reputation.map_or(0, |reputation| reputation.invalid_submissions) + 1),
+			);
+			RelayerReputations::<TestRuntime>::set(relayer, reputation);
+		});
 	}
 
 	fn expected_delivery_reward() -> ThisChainBalance {
@@ -1699,76 +1809,59 @@ mod tests {
 				Some(all_finality_pre_dispatch_data()),
 				Err(DispatchError::BadOrigin)
 			));
+			assert_storage_noop!(run_post_dispatch(
+				Some(confirmation_pre_dispatch_data()),
+				Err(DispatchError::BadOrigin)
+			));
 		});
 	}
 
 	#[test]
-	fn post_dispatch_ignores_transaction_that_has_not_updated_relay_chain_state() {
+	fn post_dispatch_refuses_transaction_that_has_not_updated_relay_chain_state() {
 		run_test(|| {
 			initialize_environment(100, 200, 200);
 
-			assert_storage_noop!(run_post_dispatch(Some(all_finality_pre_dispatch_data()), Ok(())));
+			assert_refund_refused(all_finality_pre_dispatch_data(), Ok(()));
 		});
 	}
 
 	#[test]
-	fn post_dispatch_ignores_transaction_that_has_not_updated_parachain_state() {
+	fn post_dispatch_refuses_transaction_that_has_not_updated_parachain_state() {
 		run_test(|| {
 			initialize_environment(200, 100, 200);
 
-			assert_storage_noop!(run_post_dispatch(Some(all_finality_pre_dispatch_data()), Ok(())));
-			assert_storage_noop!(run_post_dispatch(
-				Some(parachain_finality_pre_dispatch_data()),
-				Ok(())
-			));
+			assert_refund_refused(all_finality_pre_dispatch_data(), Ok(()));
+			assert_refund_refused(parachain_finality_pre_dispatch_data(), Ok(()));
 		});
 	}
 
 	#[test]
-	fn post_dispatch_ignores_transaction_that_has_not_delivered_any_messages() {
+	fn post_dispatch_refuses_transaction_that_has_not_delivered_any_messages() {
 		run_test(|| {
 			initialize_environment(200, 200, 100);
 
-			assert_storage_noop!(run_post_dispatch(Some(all_finality_pre_dispatch_data()), Ok(())));
-			assert_storage_noop!(run_post_dispatch(
-				Some(parachain_finality_pre_dispatch_data()),
-				Ok(())
-			));
-			assert_storage_noop!(run_post_dispatch(Some(delivery_pre_dispatch_data()), Ok(())));
+			assert_refund_refused(all_finality_pre_dispatch_data(), Ok(()));
+			assert_refund_refused(parachain_finality_pre_dispatch_data(), Ok(()));
+			assert_refund_refused(delivery_pre_dispatch_data(), Ok(()));
 
-			assert_storage_noop!(run_post_dispatch(
-				Some(all_finality_confirmation_pre_dispatch_data()),
-				Ok(())
-			));
-			assert_storage_noop!(run_post_dispatch(
-				Some(parachain_finality_confirmation_pre_dispatch_data()),
-				Ok(())
-			));
-			assert_storage_noop!(run_post_dispatch(Some(confirmation_pre_dispatch_data()), Ok(())));
+			assert_refund_refused(all_finality_confirmation_pre_dispatch_data(), Ok(()));
+			assert_refund_refused(parachain_finality_confirmation_pre_dispatch_data(), Ok(()));
+			assert_refund_refused(confirmation_pre_dispatch_data(), Ok(()));
 		});
 	}
 
 	#[test]
-	fn post_dispatch_ignores_transaction_that_has_not_delivered_all_messages() {
+	fn post_dispatch_refuses_transaction_that_has_not_delivered_all_messages() {
 		run_test(|| {
 			initialize_environment(200, 200, 150);
 
-			assert_storage_noop!(run_post_dispatch(Some(all_finality_pre_dispatch_data()), Ok(())));
-			assert_storage_noop!(run_post_dispatch(
-				Some(parachain_finality_pre_dispatch_data()),
-				Ok(())
-			));
-			assert_storage_noop!(run_post_dispatch(Some(delivery_pre_dispatch_data()), Ok(())));
+			assert_refund_refused(all_finality_pre_dispatch_data(), Ok(()));
+			assert_refund_refused(parachain_finality_pre_dispatch_data(), Ok(()));
+			assert_refund_refused(delivery_pre_dispatch_data(), Ok(()));
 
-			assert_storage_noop!(run_post_dispatch(
-				Some(all_finality_confirmation_pre_dispatch_data()),
-				Ok(())
-			));
-			assert_storage_noop!(run_post_dispatch(
-				Some(parachain_finality_confirmation_pre_dispatch_data()),
-				Ok(())
-			));
-			assert_storage_noop!(run_post_dispatch(Some(confirmation_pre_dispatch_data()), Ok(())));
+			assert_refund_refused(all_finality_confirmation_pre_dispatch_data(), Ok(()));
+			assert_refund_refused(parachain_finality_confirmation_pre_dispatch_data(), Ok(()));
+			assert_refund_refused(confirmation_pre_dispatch_data(), Ok(()));
 		});
 	}
 
@@ -1916,6 +2009,10 @@ mod tests {
 			assert_eq!(Balances::reserved_balance(relayer_account_at_this_chain()), test_stake);
 			run_post_dispatch(Some(delivery_pre_dispatch_data()), Ok(()));
 			assert_eq!(Balances::reserved_balance(relayer_account_at_this_chain()), 0);
+			assert_eq!(
+				RelayerReputations::<TestRuntime>::get(relayer_account_at_this_chain()),
+				None
+			);
 			assert_eq!(
 				delivery_rewards_account_balance + test_stake,
 				Balances::free_balance(delivery_rewards_account())
@@ -1973,6 +2070,106 @@ mod tests {
 		});
 	}
 
+	#[test]
+	fn post_dispatch_penalizes_relayer_that_has_submitted_too_many_invalid_transactions() {
+		run_test(|| {
+			initialize_environment(200, 200, 100);
+			System::set_block_number(1);
+
+			// first invalid submissions are just refused a refund
+			for _ in 1..MaxInvalidSubmissions::get() {
+				run_post_dispatch(Some(confirmation_pre_dispatch_data()), Ok(()));
+			}
+			assert!(!BridgeRelayers::is_penalized(&relayer_account_at_this_chain()));
+			assert_eq!(
+				ignore_priority(run_validate(message_confirmation_call(200))),
+				Ok(ValidTransaction::default()),
+			);
+
+			// and then the relayer is penalized
+			run_post_dispatch(Some(confirmation_pre_dispatch_data()), Ok(()));
+			assert!(BridgeRelayers::is_penalized(&relayer_account_at_this_chain()));
+			assert_eq!(
+				run_validate(message_confirmation_call(200)),
+				Err(TransactionValidityError::Invalid(InvalidTransaction::Call)),
+			);
+			assert_eq!(
+				run_validate(message_delivery_call(200)),
+				Err(TransactionValidityError::Invalid(InvalidTransaction::Call)),
+			);
+
+			// until the penalty expires
+			System::set_block_number(1 + PenaltyDuration::get());
+			assert!(BridgeRelayers::is_penalized(&relayer_account_at_this_chain()));
+			System::set_block_number(2 + PenaltyDuration::get());
+			assert!(!BridgeRelayers::is_penalized(&relayer_account_at_this_chain()));
+			assert_eq!(
+				ignore_priority(run_validate(message_confirmation_call(200))),
+				Ok(ValidTransaction::default()),
+			);
+		});
+	}
+
+	#[test]
+	fn post_dispatch_resets_invalid_submissions_of_rewarded_relayer() {
+		run_test(|| {
+			initialize_environment(200, 200, 100);
+
+			run_post_dispatch(Some(confirmation_pre_dispatch_data()), Ok(()));
+			assert_eq!(
+				RelayerReputations::<TestRuntime>::get(relayer_account_at_this_chain()),
+				Some(RelayerReputation {
+					invalid_submissions: 1,
+					last_invalid_submission: 0,
+					penalized_till: None
+				}),
+			);
+
+			initialize_environment(200, 200, 200);
+			run_post_dispatch(Some(confirmation_pre_dispatch_data()), Ok(()));
+			assert_eq!(
+				RelayerReputations::<TestRuntime>::get(relayer_account_at_this_chain()),
+				None,
+			);
+		});
+	}
+
+	#[test]
+	fn post_dispatch_refunds_unused_relayers_pallet_weight() {
+		run_test(|| {
+			initialize_environment(200, 200, 200);
+
+			let db_weight = <TestRuntime as SystemConfig>::DbWeight::get();
+			let max_weight = TestExtension::max_relayers_pallet_weight();
+			assert_eq!(TestExtension::default().weight(&message_delivery_call(200)), max_weight);
+
+			// unsupported calls do not touch the relayers pallet
+			assert_eq!(run_post_dispatch(None, Ok(())), max_weight);
+
+			// the reputation is read and updated for the rewarded delivery
+			assert_eq!(
+				run_post_dispatch(Some(delivery_pre_dispatch_data()), Ok(())),
+				max_weight - db_weight.reads(2) - db_weight.writes(1),
+			);
+
+			// and the `DeliveredMessages` of every rewarded relayer are also updated for the
+			// rewarded confirmation
+			let rewarded_relayers = confirmation_pre_dispatch_data()
+				.call_info
+				.messages_call_info()
+				.bundled_messages()
+				.saturating_len()
+				.min(BridgedUnderlyingParachain::MAX_UNREWARDED_RELAYERS_IN_CONFIRMATION_TX);
+			assert_eq!(
+				run_post_dispatch(Some(confirmation_pre_dispatch_data()), Ok(())),
+				max_weight -
+					db_weight.reads(2) -
+					db_weight.writes(1) -
+					db_weight.reads_writes(rewarded_relayers, rewarded_relayers),
+			);
+		});
+	}
+
 	fn run_analyze_call_result(
 		pre_dispatch_data: PreDispatchData<
 			ThisChainAccountId,
@@ -2026,21 +2223,30 @@ mod tests {
 					set_bundled_range_end(all_finality_pre_dispatch_data(), 1_000_000),
 					Ok(())
 				),
-				RelayerAccountAction::None,
+				RelayerAccountAction::Refuse(
+					relayer_account_at_this_chain(),
+					MsgProofsRewardsAccount::get()
+				),
 			);
 			assert_eq!(
 				run_analyze_call_result(
 					set_bundled_range_end(parachain_finality_pre_dispatch_data(), 1_000_000),
 					Ok(())
 				),
-				RelayerAccountAction::None,
+				RelayerAccountAction::Refuse(
+					relayer_account_at_this_chain(),
+					MsgProofsRewardsAccount::get()
+				),
 			);
 			assert_eq!(
 				run_analyze_call_result(
 					set_bundled_range_end(delivery_pre_dispatch_data(), 1_000_000),
 					Ok(())
 				),
-				RelayerAccountAction::None,
+				RelayerAccountAction::Refuse(
+					relayer_account_at_this_chain(),
+					MsgProofsRewardsAccount::get()
+				),
 			);
 		});
 	}
@@ -16,20 +16,34 @@
 
 //! Runtime module that is used to store relayer rewards and (in the future) to
 //! coordinate relations between relayers.
+//!
+//! The pallet also keeps track of relayers reputation:
+//!
+//! - the history of confirmed message deliveries of every relayer. Only the latest
+//!   `Config::MaxDeliveredMessagesRecords` deliveries are kept;
+//!
+//! - the number of consecutive relayer transactions that have been refused a refund, because they
+//!   have brought obsolete or duplicate proofs. Once it reaches `Config::MaxInvalidSubmissions`,
+//!   the relayer is penalized: its bridge transactions are rejected for `Config::PenaltyDuration`
+//!   blocks and its stake (if registered) is slashed. The reputation of relayer that has not
+//!   submitted such transactions for `Config::PenaltyDuration` blocks is forgotten.
 
 #![cfg_attr(not(feature = "std"), no_std)]
 #![warn(missing_docs)]
 
+use bp_messages::MessageNonce;
 use bp_relayers::{
-	ExplicitOrAccountParams, PaymentProcedure, Registration, RelayerRewardsKeyProvider,
-	StakeAndSlash,
+	DeliveryRewardSharesKeyProvider, ExplicitOrAccountParams, PaymentProcedure, Registration,
+	RelayerRewardsKeyProvider, StakeAndSlash,
 };
 pub use bp_relayers::{RewardsAccountOwner, RewardsAccountParams};
-use bp_runtime::StorageDoubleMapKeyProvider;
-use frame_support::fail;
+use bp_runtime::{StorageDoubleMapKeyProvider, StorageMapKeyProvider};
+use codec::{Decode, Encode, MaxEncodedLen};
+use frame_support::{fail, weights::WeightMeter};
+use scale_info::TypeInfo;
 use sp_arithmetic::traits::{AtLeast32BitUnsigned, Zero};
-use sp_runtime::{traits::CheckedSub, Saturating};
-use sp_std::marker::PhantomData;
+use sp_runtime::{traits::CheckedSub, Perbill, RuntimeDebug, Saturating};
+use sp_std::{marker::PhantomData, vec::Vec};
 
 pub use pallet::*;
 pub use payment_adapter::{DeliveryConfirmationPaymentsAdapter, PayRewardFromAccount};
@@ -50,6 +64,42 @@ pub mod weights;
 /// The target that will be used when publishing logs related to this pallet.
 pub const LOG_TARGET: &str = "runtime::bridge-relayers";
 
+/// Reputation of the relayer, based on the transactions it has submitted.
+#[derive(Clone, Copy, Decode, Encode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub struct RelayerReputation<BlockNumber> {
+	/// Number of consecutive relayer transactions that have been refused a refund, because they
+	/// have brought obsolete or duplicate proofs.
+	pub invalid_submissions: u32,
+	/// Block, where the latest of such transactions has been submitted.
+	pub last_invalid_submission: BlockNumber,
+	/// If set, relayer transactions are rejected until this block (inclusive).
+	pub penalized_till: Option<BlockNumber>,
+}
+
+impl<BlockNumber: Copy + Ord + Saturating> RelayerReputation<BlockNumber> {
+	/// Returns true if the relayer is penalized at given block.
+	pub fn is_penalized(&self, now: BlockNumber) -> bool {
+		self.penalized_till.is_some_and(|penalized_till| now <= penalized_till)
+	}
+
+	/// Returns true if the reputation may be forgotten at given block: the relayer is not
+	/// penalized and it has not submitted invalid transactions for `forget_after` blocks.
+	pub fn is_stale(&self, now: BlockNumber, forget_after: BlockNumber) -> bool {
+		!self.is_penalized(now) && self.last_invalid_submission.saturating_add(forget_after) < now
+	}
+}
+
+/// Messages, delivered by the relayer over the lane, which delivery has been confirmed.
+#[derive(Clone, Copy, Decode, Encode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub struct DeliveredMessagesRecord<LaneId, BlockNumber> {
+	/// Lane, where messages have been delivered.
+	pub lane_id: LaneId,
+	/// Number of delivered messages.
+	pub messages: MessageNonce,
+	/// Block, where the delivery has been confirmed.
+	pub confirmed_at: BlockNumber,
+}
+
 #[frame_support::pallet]
 pub mod pallet {
 	use super::*;
@@ -64,6 +114,10 @@ pub mod pallet {
 		<T as Config<I>>::LaneId,
 	>;
 
+	/// `DeliveryRewardSharesKeyProvider` for given configuration.
+	type DeliveryRewardSharesKeyProviderOf<T, I> =
+		DeliveryRewardSharesKeyProvider<<T as Config<I>>::LaneId>;
+
 	#[pallet::config]
 	pub trait Config<I: 'static = ()>: frame_system::Config {
 		/// The overarching event type.
@@ -83,12 +137,35 @@ pub mod pallet {
 		type WeightInfo: WeightInfoExt;
 		/// Lane identifier type.
 		type LaneId: LaneIdType + Send + Sync;
+		/// Number of consecutive relayer transactions that have been refused a refund, after which
+		/// the relayer is penalized.
+		#[pallet::constant]
+		type MaxInvalidSubmissions: Get<u32>;
+		/// Number of blocks during which transactions of the penalized relayer are rejected.
+		///
+		/// It is also the number of blocks after which the reputation of relayer, which has not
+		/// submitted invalid transactions, is forgotten.
+		#[pallet::constant]
+		type PenaltyDuration: Get<BlockNumberFor<Self>>;
+		/// Maximal number of confirmed deliveries, remembered for every relayer. Older records
+		/// are dropped.
+		#[pallet::constant]
+		type MaxDeliveredMessagesRecords: Get<u32>;
 	}
 
 	#[pallet::pallet]
 	#[pallet::storage_version(migration::STORAGE_VERSION)]
 	pub struct Pallet<T, I = ()>(PhantomData<(T, I)>);
 
+	#[pallet::hooks]
+	impl<T: Config<I>, I: 'static> Hooks<BlockNumberFor<T>> for Pallet<T, I> {
+		fn on_idle(now: BlockNumberFor<T>, limit: Weight) -> Weight {
+			let mut meter = WeightMeter::with_limit(limit);
+			Self::remove_stale_reputations(now, &mut meter);
+			meter.consumed()
+		}
+	}
+
 	#[pallet::call]
 	impl<T: Config<I>, I: 'static> Pallet<T, I> {
 		/// Claim accumulated rewards.
@@ -229,6 +306,26 @@ pub mod pallet {
 				},
 			)
 		}
+
+		/// Set the share of the message delivery reward that is paid to relayers that have
+		/// delivered messages over the given lane. The rest is paid to the relayer that has
+		/// confirmed the delivery.
+		///
+		/// If `share` is `None`, the whole reward is paid to the delivery relayers.
+		#[pallet::call_index(3)]
+		#[pallet::weight(T::WeightInfo::set_delivery_reward_share())]
+		pub fn set_delivery_reward_share(
+			origin: OriginFor<T>,
+			lane_id: T::LaneId,
+			share: Option<Perbill>,
+		) -> DispatchResult {
+			ensure_root(origin)?;
+
+			DeliveryRewardShares::<T, I>::set(lane_id, share);
+			Self::deposit_event(Event::<T, I>::DeliveryRewardShareUpdated { lane_id, share });
+
+			Ok(())
+		}
 	}
 
 	impl<T: Config<I>, I: 'static> Pallet<T, I> {
@@ -357,6 +454,143 @@ pub mod pallet {
 			);
 		}
 
+		/// Return share of the message delivery reward that is paid to relayers that have
+		/// delivered messages over the given lane.
+		pub fn delivery_reward_share(lane_id: T::LaneId) -> Perbill {
+			DeliveryRewardShares::<T, I>::get(lane_id).unwrap_or_else(Perbill::one)
+		}
+
+		/// Register messages, delivered by given relayer over given lane.
+		///
+		/// The oldest record is dropped if the delivery history of the relayer is full.
+		pub fn register_delivered_messages(
+			relayer: &T::AccountId,
+			lane_id: T::LaneId,
+			messages: MessageNonce,
+		) {
+			if messages.is_zero() {
+				return
+			}
+
+			let record = DeliveredMessagesRecord {
+				lane_id,
+				messages,
+				confirmed_at: frame_system::Pallet::<T>::block_number(),
+			};
+			DeliveredMessages::<T, I>::mutate(relayer, |history| {
+				if history.is_full() && !history.is_empty() {
+					history.remove(0);
+				}
+				let _ = history.try_push(record);
+			});
+		}
+
+		/// Returns true if transactions of the given relayer are currently rejected.
+		pub fn is_penalized(relayer: &T::AccountId) -> bool {
+			RelayerReputations::<T, I>::get(relayer).is_some_and(|reputation| {
+				reputation.is_penalized(frame_system::Pallet::<T>::block_number())
+			})
+		}
+
+		/// Note that the relayer has submitted a valid transaction, which has been refunded.
+		pub fn note_valid_submission(relayer: &T::AccountId) {
+			let now = frame_system::Pallet::<T>::block_number();
+			RelayerReputations::<T, I>::mutate_exists(relayer, |maybe_reputation| {
+				if let Some(reputation) = maybe_reputation {
+					reputation.invalid_submissions = 0;
+					if !reputation.is_penalized(now) {
+						*maybe_reputation = None;
+					}
+				}
+			});
+		}
+
+		/// Note that the relayer has submitted a transaction with obsolete or duplicate proof,
+		/// which has been refused a refund.
+		///
+		/// If the relayer has submitted too many such transactions in a row, it is penalized. The
+		/// stake of registered relayer is slashed to the `slash_destination`. Returns true if
+		/// the relayer has been penalized.
+		pub fn note_invalid_submission(
+			relayer: &T::AccountId,
+			slash_destination: RewardsAccountParams<T::LaneId>,
+		) -> bool {
+			let now = frame_system::Pallet::<T>::block_number();
+			let mut reputation = RelayerReputations::<T, I>::get(relayer)
+				.filter(|reputation| !reputation.is_stale(now, T::PenaltyDuration::get()))
+				.unwrap_or(RelayerReputation {
+					invalid_submissions: 0,
+					last_invalid_submission: now,
+					penalized_till: None,
+				});
+			reputation.invalid_submissions = reputation.invalid_submissions.saturating_add(1);
+			reputation.last_invalid_submission = now;
+			if reputation.invalid_submissions < T::MaxInvalidSubmissions::get() {
+				RelayerReputations::<T, I>::insert(relayer, reputation);
+				return false
+			}
+
+			let penalized_till = now.saturating_add(T::PenaltyDuration::get());
+			reputation.invalid_submissions = 0;
+			reputation.penalized_till = Some(penalized_till);
+			RelayerReputations::<T, I>::insert(relayer, reputation);
+
+			log::trace!(
+				target: LOG_TARGET,
+				"Relayer {:?} is penalized till {:?} for submitting {} invalid transactions",
+				relayer,
+				penalized_till,
+				T::MaxInvalidSubmissions::get(),
+			);
+			Self::deposit_event(Event::<T, I>::RelayerPenalized {
+				relayer: relayer.clone(),
+				penalized_till,
+			});
+
+			Self::slash_and_deregister(relayer, ExplicitOrAccountParams::Params(slash_destination));
+			true
+		}
+
+		/// Remove reputations of relayers that are not penalized and have not submitted invalid
+		/// transactions for `Config::PenaltyDuration` blocks.
+		///
+		/// Continues after the last reputation checked by the previous call, and starts from the
+		/// beginning again once all reputations were checked.
+		pub(crate) fn remove_stale_reputations(now: BlockNumberFor<T>, meter: &mut WeightMeter) {
+			let db_weight = T::DbWeight::get();
+			if meter.try_consume(db_weight.reads_writes(1, 1)).is_err() {
+				return
+			}
+			let cursor = ReputationsCursor::<T, I>::get();
+			let mut reputations = match &cursor {
+				Some(relayer) => RelayerReputations::<T, I>::iter_from(
+					RelayerReputations::<T, I>::hashed_key_for(relayer),
+				),
+				None => RelayerReputations::<T, I>::iter(),
+			};
+
+			let forget_after = T::PenaltyDuration::get();
+			let mut last = cursor;
+			let mut stale = Vec::new();
+			loop {
+				if meter.try_consume(db_weight.reads_writes(1, 1)).is_err() {
+					break
+				}
+				let Some((relayer, reputation)) = reputations.next() else {
+					last = None;
+					break
+				};
+				if reputation.is_stale(now, forget_after) {
+					stale.push(relayer.clone());
+				}
+				last = Some(relayer);
+			}
+			ReputationsCursor::<T, I>::set(last);
+			for relayer in stale {
+				RelayerReputations::<T, I>::remove(relayer);
+			}
+		}
+
 		/// Return required registration lease.
 		pub(crate) fn required_registration_lease() -> BlockNumberFor<T> {
 			<T::StakeAndSlash as StakeAndSlash<
@@ -434,6 +668,21 @@ pub mod pallet {
 			/// Registration that was removed.
 			registration: Registration<BlockNumberFor<T>, T::Reward>,
 		},
+		/// Share of the message delivery reward, paid to delivery relayers, has been updated.
+		DeliveryRewardShareUpdated {
+			/// Lane, which reward share has been updated.
+			lane_id: T::LaneId,
+			/// New share of the reward, paid to delivery relayers. `None` means that the whole
+			/// reward is paid to delivery relayers.
+			share: Option<Perbill>,
+		},
+		/// Relayer has submitted too many invalid transactions and has been penalized.
+		RelayerPenalized {
+			/// Relayer account that has been penalized.
+			relayer: T::AccountId,
+			/// Relayer transactions are rejected until this block (inclusive).
+			penalized_till: BlockNumberFor<T>,
+		},
 	}
 
 	#[pallet::error]
@@ -485,6 +734,49 @@ pub mod pallet {
 		Registration<BlockNumberFor<T>, T::Reward>,
 		OptionQuery,
 	>;
+
+	/// Share of the message delivery reward that is paid to relayers that have delivered messages
+	/// over the lane. The rest is paid to the relayer that has confirmed the delivery.
+	///
+	/// If there's no entry for the lane, the whole reward is paid to the delivery relayers.
+	#[pallet::storage]
+	pub type DeliveryRewardShares<T: Config<I>, I: 'static = ()> = StorageMap<
+		_,
+		<DeliveryRewardSharesKeyProviderOf<T, I> as StorageMapKeyProvider>::Hasher,
+		<DeliveryRewardSharesKeyProviderOf<T, I> as StorageMapKeyProvider>::Key,
+		<DeliveryRewardSharesKeyProviderOf<T, I> as StorageMapKeyProvider>::Value,
+		OptionQuery,
+	>;
+
+	/// History of the relayer message deliveries, which have been confirmed. Only the latest
+	/// `Config::MaxDeliveredMessagesRecords` deliveries are kept.
+	#[pallet::storage]
+	pub type DeliveredMessages<T: Config<I>, I: 'static = ()> = StorageMap<
+		_,
+		Blake2_128Concat,
+		T::AccountId,
+		BoundedVec<
+			DeliveredMessagesRecord<T::LaneId, BlockNumberFor<T>>,
+			T::MaxDeliveredMessagesRecords,
+		>,
+		ValueQuery,
+	>;
+
+	/// Reputation of relayers that have submitted invalid transactions.
+	#[pallet::storage]
+	pub type RelayerReputations<T: Config<I>, I: 'static = ()> = StorageMap<
+		_,
+		Blake2_128Concat,
+		T::AccountId,
+		RelayerReputation<BlockNumberFor<T>>,
+		OptionQuery,
+	>;
+
+	/// The last relayer whose reputation has been checked by `on_idle`, or `None` if the check
+	/// has not started or has reached the end of the `RelayerReputations` map.
+	#[pallet::storage]
+	pub type ReputationsCursor<T: Config<I>, I: 'static = ()> =
+		StorageValue<_, T::AccountId, OptionQuery>;
 }
 
 #[cfg(test)]
@@ -497,7 +789,10 @@ mod tests {
 	use bp_relayers::RewardsAccountOwner;
 	use frame_support::{
 		assert_noop, assert_ok,
-		traits::fungible::{Inspect, Mutate},
+		traits::{
+			fungible::{Inspect, Mutate},
+			Get,
+		},
 	};
 	use frame_system::{EventRecord, Pallet as System, Phase};
 	use sp_runtime::DispatchError;
@@ -944,4 +1239,171 @@ mod tests {
 			assert!(Pallet::<TestRuntime>::is_registration_active(&REGISTER_RELAYER));
 		});
 	}
+
+	#[test]
+	fn set_delivery_reward_share_requires_root() {
+		run_test(|| {
+			assert_noop!(
+				Pallet::<TestRuntime>::set_delivery_reward_share(
+					RuntimeOrigin::signed(REGULAR_RELAYER),
+					test_lane_id(),
+					Some(Perbill::from_percent(50)),
+				),
+				DispatchError::BadOrigin,
+			);
+		});
+	}
+
+	#[test]
+	fn set_delivery_reward_share_works() {
+		run_test(|| {
+			get_ready_for_events();
+
+			assert_eq!(
+				Pallet::<TestRuntime>::delivery_reward_share(test_lane_id()),
+				Perbill::one()
+			);
+
+			assert_ok!(Pallet::<TestRuntime>::set_delivery_reward_share(
+				RuntimeOrigin::root(),
+				test_lane_id(),
+				Some(Perbill::from_percent(50)),
+			));
+			assert_eq!(
+				Pallet::<TestRuntime>::delivery_reward_share(test_lane_id()),
+				Perbill::from_percent(50)
+			);
+			assert_eq!(
+				System::<TestRuntime>::events().last(),
+				Some(&EventRecord {
+					phase: Phase::Initialization,
+					event: TestEvent::BridgeRelayers(Event::DeliveryRewardShareUpdated {
+						lane_id: test_lane_id(),
+						share: Some(Perbill::from_percent(50)),
+					}),
+					topics: vec![],
+				}),
+			);
+
+			assert_ok!(Pallet::<TestRuntime>::set_delivery_reward_share(
+				RuntimeOrigin::root(),
+				test_lane_id(),
+				None,
+			));
+			assert_eq!(
+				Pallet::<TestRuntime>::delivery_reward_share(test_lane_id()),
+				Perbill::one()
+			);
+		});
+	}
+
+	#[test]
+	fn note_invalid_submission_penalizes_and_slashes_relayer() {
+		run_test(|| {
+			get_ready_for_events();
+
+			assert_ok!(Pallet::<TestRuntime>::register(
+				RuntimeOrigin::signed(REGISTER_RELAYER),
+				150
+			));
+
+			for _ in 1..MaxInvalidSubmissions::get() {
+				Pallet::<TestRuntime>::note_invalid_submission(
+					&REGISTER_RELAYER,
+					test_reward_account_param(),
+				);
+			}
+			assert!(!Pallet::<TestRuntime>::is_penalized(&REGISTER_RELAYER));
+			assert!(Pallet::<TestRuntime>::is_registration_active(&REGISTER_RELAYER));
+
+			assert!(Pallet::<TestRuntime>::note_invalid_submission(
+				&REGISTER_RELAYER,
+				test_reward_account_param(),
+			));
+			assert!(Pallet::<TestRuntime>::is_penalized(&REGISTER_RELAYER));
+			assert!(!Pallet::<TestRuntime>::is_registration_active(&REGISTER_RELAYER));
+			assert_eq!(
+				RelayerReputations::<TestRuntime>::get(REGISTER_RELAYER),
+				Some(RelayerReputation {
+					invalid_submissions: 0,
+					last_invalid_submission: 1,
+					penalized_till: Some(1 + PenaltyDuration::get()),
+				}),
+			);
+			assert!(System::<TestRuntime>::events().contains(&EventRecord {
+				phase: Phase::Initialization,
+				event: TestEvent::BridgeRelayers(Event::RelayerPenalized {
+					relayer: REGISTER_RELAYER,
+					penalized_till: 1 + PenaltyDuration::get(),
+				}),
+				topics: vec![],
+			}));
+		});
+	}
+
+	#[test]
+	fn invalid_submissions_are_forgotten_after_penalty_duration() {
+		run_test(|| {
+			get_ready_for_events();
+
+			Pallet::<TestRuntime>::note_invalid_submission(
+				&REGULAR_RELAYER,
+				test_reward_account_param(),
+			);
+			System::<TestRuntime>::set_block_number(1 + PenaltyDuration::get());
+			Pallet::<TestRuntime>::note_invalid_submission(
+				&REGULAR_RELAYER,
+				test_reward_account_param(),
+			);
+			assert_eq!(
+				RelayerReputations::<TestRuntime>::get(REGULAR_RELAYER)
+					.map(|reputation| reputation.invalid_submissions),
+				Some(2),
+			);
+
+			System::<TestRuntime>::set_block_number(2 + 2 * PenaltyDuration::get());
+			Pallet::<TestRuntime>::note_invalid_submission(
+				&REGULAR_RELAYER,
+				test_reward_account_param(),
+			);
+			assert_eq!(
+				RelayerReputations::<TestRuntime>::get(REGULAR_RELAYER)
+					.map(|reputation| reputation.invalid_submissions),
+				Some(1),
+			);
+		});
+	}
+
+	#[test]
+	fn remove_stale_reputations_works() {
+		run_test(|| {
+			let reputation = |last_invalid_submission, penalized_till| RelayerReputation {
+				invalid_submissions: 1,
+				last_invalid_submission,
+				penalized_till,
+			};
+			let now = 2 + PenaltyDuration::get();
+			RelayerReputations::<TestRuntime>::insert(1, reputation(1, None));
+			RelayerReputations::<TestRuntime>::insert(2, reputation(1, Some(now - 1)));
+			// relayer has recently submitted invalid transaction
+			RelayerReputations::<TestRuntime>::insert(3, reputation(now - 1, None));
+			// relayer is still penalized
+			RelayerReputations::<TestRuntime>::insert(4, reputation(1, Some(now)));
+
+			// the cursor is kept if not all reputations have been checked
+			let step = DbWeight::get().reads_writes(1, 1);
+			let mut meter = WeightMeter::with_limit(step.saturating_mul(3));
+			Pallet::<TestRuntime>::remove_stale_reputations(now, &mut meter);
+			assert!(ReputationsCursor::<TestRuntime>::get().is_some());
+			assert!(RelayerReputations::<TestRuntime>::iter().count() >= 2);
+
+			// and the next call continues from the cursor
+			let mut meter = WeightMeter::new();
+			Pallet::<TestRuntime>::remove_stale_reputations(now, &mut meter);
+			assert_eq!(ReputationsCursor::<TestRuntime>::get(), None);
+			let mut relayers = RelayerReputations::<TestRuntime>::iter_keys().collect::<Vec<_>>();
+			relayers.sort();
+			assert_eq!(relayers, vec![3, 4]);
+		});
+	}
 }
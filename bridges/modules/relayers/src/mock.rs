@@ -37,7 +37,7 @@ use frame_support::{
 use pallet_transaction_payment::Multiplier;
 use sp_core::{ConstU64, ConstU8, H256};
 use sp_runtime::{
-	traits::{BlakeTwo256, ConstU32},
+	traits::{BlakeTwo256, ConstBool, ConstU32},
 	BuildStorage, FixedPointNumber, Perquintill, StateVersion,
 };
 
@@ -189,6 +189,9 @@ parameter_types! {
 	pub const ReserveId: [u8; 8] = *b"brdgrlrs";
 	pub const Stake: ThisChainBalance = 1_000;
 	pub const Lease: ThisChainBlockNumber = 8;
+	pub const MaxInvalidSubmissions: u32 = 3;
+	pub const PenaltyDuration: ThisChainBlockNumber = 10;
+	pub const MaxDeliveredMessagesRecords: u32 = 2;
 	pub const TargetBlockFullness: Perquintill = Perquintill::from_percent(25);
 	pub const TransactionBaseFee: ThisChainBalance = 0;
 	pub const TransactionByteFee: ThisChainBalance = 1;
@@ -241,6 +244,8 @@ impl pallet_bridge_grandpa::Config for TestRuntime {
 	type MaxFreeHeadersPerBlock = ConstU32<4>;
 	type FreeHeadersInterval = ConstU32<1_024>;
 	type HeadersToKeep = ConstU32<8>;
+	type ArchivePrunedHeaders = ConstBool<false>;
+	type AuthoritySetChangesToKeep = ConstU32<0>;
 	type WeightInfo = pallet_bridge_grandpa::weights::BridgeWeight<TestRuntime>;
 }
 
@@ -285,6 +290,9 @@ impl pallet_bridge_relayers::Config for TestRuntime {
 	type StakeAndSlash = TestStakeAndSlash;
 	type WeightInfo = ();
 	type LaneId = TestLaneIdType;
+	type MaxInvalidSubmissions = MaxInvalidSubmissions;
+	type PenaltyDuration = PenaltyDuration;
+	type MaxDeliveredMessagesRecords = MaxDeliveredMessagesRecords;
 }
 
 #[cfg(feature = "runtime-benchmarks")]
@@ -27,7 +27,10 @@ use bp_relayers::{RewardsAccountOwner, RewardsAccountParams};
 use bp_runtime::Chain;
 use frame_support::{sp_runtime::SaturatedConversion, traits::Get};
 use pallet_bridge_messages::LaneIdOf;
-use sp_arithmetic::traits::{Saturating, Zero};
+use sp_arithmetic::{
+	traits::{Saturating, Zero},
+	PerThing,
+};
 use sp_std::{collections::vec_deque::VecDeque, marker::PhantomData, ops::RangeInclusive};
 
 /// Adapter that allows relayers pallet to be used as a delivery+dispatch payment mechanism
@@ -72,18 +75,30 @@ where
 }
 
 // Update rewards to given relayers, optionally rewarding confirmation relayer.
+//
+// The share of `delivery_fee` that is not paid to delivery relayers (see
+// `Pallet::delivery_reward_share`) is paid to the confirmation relayer.
 fn register_relayers_rewards<T: Config<I>, I: 'static>(
 	confirmation_relayer: &T::AccountId,
 	relayers_rewards: RelayersRewards<T::AccountId>,
 	lane_id: RewardsAccountParams<T::LaneId>,
 	delivery_fee: T::Reward,
 ) {
+	let delivery_reward_per_message =
+		Pallet::<T, I>::delivery_reward_share(*lane_id.lane_id()).mul_floor(delivery_fee);
+	let confirmation_reward_per_message = delivery_fee.saturating_sub(delivery_reward_per_message);
+
 	// reward every relayer except `confirmation_relayer`
 	let mut confirmation_relayer_reward = T::Reward::zero();
 	for (relayer, messages) in relayers_rewards {
+		Pallet::<T, I>::register_delivered_messages(&relayer, *lane_id.lane_id(), messages);
+
 		// sane runtime configurations guarantee that the number of messages will be below
 		// `u32::MAX`
-		let relayer_reward = T::Reward::saturated_from(messages).saturating_mul(delivery_fee);
+		let messages = T::Reward::saturated_from(messages);
+		let relayer_reward = messages.saturating_mul(delivery_reward_per_message);
+		confirmation_relayer_reward = confirmation_relayer_reward
+			.saturating_add(messages.saturating_mul(confirmation_reward_per_message));
 
 		if relayer != *confirmation_relayer {
 			Pallet::<T, I>::register_relayer_reward(lane_id, &relayer, relayer_reward);
@@ -104,7 +119,10 @@ fn register_relayers_rewards<T: Config<I>, I: 'static>(
 #[cfg(test)]
 mod tests {
 	use super::*;
-	use crate::{mock::*, RelayerRewards};
+	use crate::{
+		mock::*, DeliveredMessages, DeliveredMessagesRecord, DeliveryRewardShares, RelayerRewards,
+	};
+	use sp_runtime::Perbill;
 
 	const RELAYER_1: ThisChainAccountId = 1;
 	const RELAYER_2: ThisChainAccountId = 2;
@@ -159,4 +177,62 @@ mod tests {
 			);
 		});
 	}
+
+	#[test]
+	fn delivered_messages_are_registered() {
+		run_test(|| {
+			for (block, confirmation_relayer) in [(1, RELAYER_2), (2, RELAYER_3), (3, RELAYER_3)] {
+				System::set_block_number(block);
+				register_relayers_rewards::<TestRuntime, ()>(
+					&confirmation_relayer,
+					relayers_rewards(),
+					test_reward_account_param(),
+					50,
+				);
+			}
+
+			// only the latest `MaxDeliveredMessagesRecords` deliveries are kept
+			let record = |messages, confirmed_at| DeliveredMessagesRecord {
+				lane_id: test_lane_id(),
+				messages,
+				confirmed_at,
+			};
+			assert_eq!(
+				DeliveredMessages::<TestRuntime>::get(RELAYER_1).into_inner(),
+				vec![record(2, 2), record(2, 3)],
+			);
+			assert_eq!(
+				DeliveredMessages::<TestRuntime>::get(RELAYER_2).into_inner(),
+				vec![record(3, 2), record(3, 3)],
+			);
+			assert!(DeliveredMessages::<TestRuntime>::get(RELAYER_3).is_empty());
+		});
+	}
+
+	#[test]
+	fn delivery_reward_is_split_between_delivery_and_confirmation_relayers() {
+		run_test(|| {
+			DeliveryRewardShares::<TestRuntime>::insert(test_lane_id(), Perbill::from_percent(80));
+
+			register_relayers_rewards::<TestRuntime, ()>(
+				&RELAYER_3,
+				relayers_rewards(),
+				test_reward_account_param(),
+				50,
+			);
+
+			assert_eq!(
+				RelayerRewards::<TestRuntime>::get(RELAYER_1, test_reward_account_param()),
+				Some(80)
+			);
+			assert_eq!(
+				RelayerRewards::<TestRuntime>::get(RELAYER_2, test_reward_account_param()),
+				Some(120)
+			);
+			assert_eq!(
+				RelayerRewards::<TestRuntime>::get(RELAYER_3, test_reward_account_param()),
+				Some(50)
+			);
+		});
+	}
 }
@@ -55,6 +55,7 @@ pub trait WeightInfo {
 	fn deregister() -> Weight;
 	fn slash_and_deregister() -> Weight;
 	fn register_relayer_reward() -> Weight;
+	fn set_delivery_reward_share() -> Weight;
 }
 
 /// Weights for `pallet_bridge_relayers` that are generated using one of the Bridge testnets.
@@ -157,6 +158,13 @@ impl<T: frame_system::Config> WeightInfo for BridgeWeight<T> {
 			.saturating_add(T::DbWeight::get().reads(1_u64))
 			.saturating_add(T::DbWeight::get().writes(1_u64))
 	}
+	/// Storage: BridgeRelayers DeliveryRewardShares (r:0 w:1)
+	///
+	/// Not benchmarked yet. The `register_relayer_reward` weight is used instead, because it
+	/// reads and writes a larger storage map entry.
+	fn set_delivery_reward_share() -> Weight {
+		Self::register_relayer_reward()
+	}
 }
 
 // For backwards compatibility and tests
@@ -256,4 +264,11 @@ impl WeightInfo for () {
 			.saturating_add(RocksDbWeight::get().reads(1_u64))
 			.saturating_add(RocksDbWeight::get().writes(1_u64))
 	}
+	/// Storage: BridgeRelayers DeliveryRewardShares (r:0 w:1)
+	///
+	/// Not benchmarked yet. The `register_relayer_reward` weight is used instead, because it
+	/// reads and writes a larger storage map entry.
+	fn set_delivery_reward_share() -> Weight {
+		Self::register_relayer_reward()
+	}
 }
@@ -19,10 +19,15 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 #![warn(missing_docs)]
 
+pub mod storage_keys;
+
 pub use binary_merkle_tree::merkle_root;
 pub use pallet_beefy_mmr::BeefyEcdsaToEthereum;
 pub use pallet_mmr::{
-	primitives::{DataOrHash as MmrDataOrHash, LeafProof as MmrProof},
+	primitives::{
+		DataOrHash as MmrDataOrHash, EncodableOpaqueLeaf as MmrEncodableOpaqueLeaf,
+		Error as MmrError, LeafProof as MmrProof,
+	},
 	verify_leaves_proof as verify_mmr_leaves_proof,
 };
 pub use sp_consensus_beefy::{
@@ -31,18 +36,19 @@ pub use sp_consensus_beefy::{
 	},
 	known_payloads::MMR_ROOT_ID as MMR_ROOT_PAYLOAD_ID,
 	mmr::{BeefyAuthoritySet, MmrLeafVersion},
-	BeefyAuthorityId, Commitment, Payload as BeefyPayload, SignedCommitment, ValidatorSet,
-	ValidatorSetId, BEEFY_ENGINE_ID,
+	BeefyAuthorityId, Commitment, ConsensusLog, Payload as BeefyPayload, SignedCommitment,
+	ValidatorSet, ValidatorSetId, BEEFY_ENGINE_ID,
 };
 
-use bp_runtime::{BasicOperatingMode, BlockNumberOf, Chain, HashOf};
-use codec::{Decode, Encode};
+use bp_header_chain::ConsensusLogReader;
+use bp_runtime::{BasicOperatingMode, BlockNumberOf, Chain, HashOf, UnderlyingChainProvider};
+use codec::{Codec, Decode, Encode};
 use frame_support::Parameter;
 use scale_info::TypeInfo;
 use serde::{Deserialize, Serialize};
 use sp_runtime::{
 	traits::{Convert, MaybeSerializeDeserialize},
-	RuntimeAppPublic, RuntimeDebug,
+	Digest, RuntimeAppPublic, RuntimeDebug,
 };
 use sp_std::prelude::*;
 
@@ -52,6 +58,13 @@ use sp_std::prelude::*;
 /// primitives. Some of types can be configured in low-level pallets, but are constrained
 /// when BEEFY+MMR bundle is used.
 pub trait ChainWithBeefy: Chain {
+	/// Name of the bridge BEEFY pallet (used in `construct_runtime` macro call) that is deployed
+	/// at some other chain to bridge with this `ChainWithBeefy`.
+	///
+	/// We assume that all chains that are bridging with this `ChainWithBeefy` are using
+	/// the same name.
+	const WITH_CHAIN_BEEFY_PALLET_NAME: &'static str;
+
 	/// The hashing algorithm used to compute the digest of the BEEFY commitment.
 	///
 	/// Corresponds to the hashing algorithm, used by `sc_consensus_beefy::BeefyKeystore`.
@@ -93,6 +106,43 @@ pub trait ChainWithBeefy: Chain {
 	type AuthorityIdToMerkleLeaf: Convert<Self::AuthorityId, Vec<u8>>;
 }
 
+impl<T> ChainWithBeefy for T
+where
+	T: Chain + UnderlyingChainProvider,
+	T::Chain: ChainWithBeefy,
+{
+	const WITH_CHAIN_BEEFY_PALLET_NAME: &'static str =
+		<T::Chain as ChainWithBeefy>::WITH_CHAIN_BEEFY_PALLET_NAME;
+
+	type CommitmentHasher = <T::Chain as ChainWithBeefy>::CommitmentHasher;
+	type MmrHashing = <T::Chain as ChainWithBeefy>::MmrHashing;
+	type MmrHash = <T::Chain as ChainWithBeefy>::MmrHash;
+	type BeefyMmrLeafExtra = <T::Chain as ChainWithBeefy>::BeefyMmrLeafExtra;
+	type AuthorityId = <T::Chain as ChainWithBeefy>::AuthorityId;
+	type AuthorityIdToMerkleLeaf = <T::Chain as ChainWithBeefy>::AuthorityIdToMerkleLeaf;
+}
+
+/// A struct that provides helper methods for querying the BEEFY consensus log.
+pub struct BeefyConsensusLogReader<AuthorityId>(sp_std::marker::PhantomData<AuthorityId>);
+
+impl<AuthorityId: Codec> BeefyConsensusLogReader<AuthorityId> {
+	/// Find and return the validator set, enacted by the header with given digest.
+	pub fn find_authorities_change(digest: &Digest) -> Option<ValidatorSet<AuthorityId>> {
+		digest
+			.convert_first(|log| log.consensus_try_to(&BEEFY_ENGINE_ID))
+			.and_then(|log| match log {
+				ConsensusLog::AuthoritiesChange(validator_set) => Some(validator_set),
+				_ => None,
+			})
+	}
+}
+
+impl<AuthorityId: Codec> ConsensusLogReader for BeefyConsensusLogReader<AuthorityId> {
+	fn schedules_authorities_change(digest: &Digest) -> bool {
+		BeefyConsensusLogReader::<AuthorityId>::find_authorities_change(digest).is_some()
+	}
+}
+
 /// BEEFY validator id used by given Substrate chain.
 pub type BeefyAuthorityIdOf<C> = <C as ChainWithBeefy>::AuthorityId;
 /// BEEFY validator set, containing both validator identifiers and the numeric set id.
@@ -0,0 +1,220 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Archive of headers that have been pruned from the header chain storage.
+//!
+//! Pruned headers are appended as leaves to a Merkle Mountain Range (MMR), of which only peaks
+//! are stored on-chain. Anyone who knows all archived headers (e.g. from pallet events) may
+//! build a proof that some header has been archived, which is verified against the peaks.
+
+use codec::{Decode, Encode, MaxEncodedLen};
+use frame_support::{traits::ConstU32, BoundedVec};
+use scale_info::TypeInfo;
+use sp_runtime::{traits::Hash as HashT, RuntimeDebug};
+use sp_std::vec::Vec;
+
+/// Maximal number of MMR peaks. There's a peak for every non-zero bit in the number of MMR
+/// leaves, so it is enough for any `u64` number of leaves.
+pub const MAX_MMR_PEAKS: u32 = 64;
+
+/// Header data that is archived when the header is pruned.
+#[derive(Clone, Decode, Encode, Eq, MaxEncodedLen, PartialEq, RuntimeDebug, TypeInfo)]
+pub struct ArchivedHeader<Number, Hash> {
+	/// Header number.
+	pub number: Number,
+	/// Header hash.
+	pub hash: Hash,
+	/// Header state root.
+	pub state_root: Hash,
+}
+
+impl<Number: Encode, Hash: Encode> ArchivedHeader<Number, Hash> {
+	/// Returns hash of the MMR leaf, representing this header.
+	pub fn leaf_hash<H: HashT<Output = Hash>>(&self) -> Hash {
+		H::hash_of(self)
+	}
+}
+
+/// Proof that the header has been archived.
+#[derive(Clone, Decode, Encode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+pub struct ArchivedHeaderProof<Number, Hash> {
+	/// The archived header.
+	pub header: ArchivedHeader<Number, Hash>,
+	/// Index of the header leaf in the MMR.
+	pub leaf_index: u64,
+	/// Hashes of sibling nodes on the path from the leaf to its peak, ordered from the leaf.
+	pub siblings: Vec<Hash>,
+}
+
+/// Peaks of the Merkle Mountain Range with archived headers.
+#[derive(Clone, Decode, Default, Encode, Eq, MaxEncodedLen, PartialEq, RuntimeDebug, TypeInfo)]
+pub struct ArchivedHeadersMmr<Hash> {
+	/// Number of leaves in the MMR.
+	pub leaves: u64,
+	/// Peaks of the MMR, ordered from the highest (leftmost) to the lowest (rightmost).
+	pub peaks: BoundedVec<Hash, ConstU32<MAX_MMR_PEAKS>>,
+}
+
+impl<Hash: Clone + PartialEq + Encode> ArchivedHeadersMmr<Hash> {
+	/// Appends new leaf to the MMR and returns its index.
+	pub fn append<H: HashT<Output = Hash>>(&mut self, leaf: Hash) -> u64 {
+		let leaf_index = self.leaves;
+
+		// every peak of the same height is merged with the new node, starting from the lowest
+		let mut node = leaf;
+		let mut height = 0;
+		while (self.leaves >> height) & 1 == 1 {
+			let left = self.peaks.pop().expect("there's a peak for every non-zero bit; qed");
+			node = merge::<H>(left, node);
+			height += 1;
+		}
+		self.peaks
+			.try_push(node)
+			.expect("there's a peak for every non-zero bit of `u64` number of leaves; qed");
+		self.leaves = self.leaves.saturating_add(1);
+
+		leaf_index
+	}
+
+	/// Returns true if `leaf` is the leaf with `leaf_index` index in the MMR.
+	pub fn verify<H: HashT<Output = Hash>>(
+		&self,
+		leaf_index: u64,
+		leaf: Hash,
+		siblings: &[Hash],
+	) -> bool {
+		let Some((peak_index, height, first_leaf)) = self.find_peak(leaf_index) else {
+			return false
+		};
+		if siblings.len() != height as usize {
+			return false
+		}
+
+		let mut position = leaf_index - first_leaf;
+		let mut node = leaf;
+		for sibling in siblings {
+			node = if position & 1 == 0 {
+				merge::<H>(node, sibling.clone())
+			} else {
+				merge::<H>(sibling.clone(), node)
+			};
+			position >>= 1;
+		}
+
+		self.peaks.get(peak_index) == Some(&node)
+	}
+
+	/// Returns true if `proof` proves that the header has been archived.
+	pub fn verify_header<H: HashT<Output = Hash>, Number: Encode>(
+		&self,
+		proof: &ArchivedHeaderProof<Number, Hash>,
+	) -> bool {
+		self.verify::<H>(proof.leaf_index, proof.header.leaf_hash::<H>(), &proof.siblings)
+	}
+
+	/// Finds peak that covers leaf with given index. Returns index of the peak, its height and
+	/// index of the first leaf it covers.
+	fn find_peak(&self, leaf_index: u64) -> Option<(usize, u32, u64)> {
+		let mut first_leaf = 0u64;
+		for (peak_index, height) in peak_heights(self.leaves).enumerate() {
+			let peak_leaves = 1u64 << height;
+			if leaf_index < first_leaf + peak_leaves {
+				return Some((peak_index, height, first_leaf))
+			}
+			first_leaf += peak_leaves;
+		}
+		None
+	}
+}
+
+/// Builds the MMR proof for the leaf with given index. All MMR leaves must be provided.
+pub fn build_mmr_proof<H: HashT>(leaves: &[H::Output], leaf_index: u64) -> Option<Vec<H::Output>> {
+	let mut first_leaf = 0usize;
+	for height in peak_heights(leaves.len() as u64) {
+		let peak_leaves = 1usize << height;
+		let leaf_index = leaf_index as usize;
+		if leaf_index < first_leaf + peak_leaves {
+			let mut level = leaves[first_leaf..first_leaf + peak_leaves].to_vec();
+			let mut position = leaf_index - first_leaf;
+			let mut siblings = Vec::with_capacity(height as usize);
+			while level.len() > 1 {
+				siblings.push(level[position ^ 1]);
+				level = level.chunks(2).map(|pair| merge::<H>(pair[0], pair[1])).collect();
+				position >>= 1;
+			}
+			return Some(siblings)
+		}
+		first_leaf += peak_leaves;
+	}
+	None
+}
+
+/// Returns heights of MMR peaks, starting from the highest.
+fn peak_heights(leaves: u64) -> impl Iterator<Item = u32> {
+	(0..u64::BITS).rev().filter(move |height| (leaves >> height) & 1 == 1)
+}
+
+/// Merges two MMR nodes into the parent node.
+fn merge<H: HashT>(left: H::Output, right: H::Output) -> H::Output {
+	H::hash_of(&(left, right))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_runtime::{testing::H256, traits::BlakeTwo256};
+
+	fn leaf(index: u64) -> H256 {
+		BlakeTwo256::hash_of(&index)
+	}
+
+	#[test]
+	fn all_leaves_are_proved() {
+		let mut mmr = ArchivedHeadersMmr::<H256>::default();
+		let mut leaves = Vec::new();
+		for index in 0..37u64 {
+			assert_eq!(mmr.append::<BlakeTwo256>(leaf(index)), index);
+			leaves.push(leaf(index));
+			assert_eq!(mmr.peaks.len() as u32, mmr.leaves.count_ones());
+
+			for proved in 0..=index {
+				let siblings = build_mmr_proof::<BlakeTwo256>(&leaves, proved).unwrap();
+				assert!(mmr.verify::<BlakeTwo256>(proved, leaf(proved), &siblings));
+			}
+		}
+	}
+
+	#[test]
+	fn invalid_proofs_are_rejected() {
+		let mut mmr = ArchivedHeadersMmr::<H256>::default();
+		let leaves: Vec<_> = (0..11u64).map(leaf).collect();
+		for leaf in &leaves {
+			mmr.append::<BlakeTwo256>(*leaf);
+		}
+
+		let siblings = build_mmr_proof::<BlakeTwo256>(&leaves, 5).unwrap();
+		assert!(mmr.verify::<BlakeTwo256>(5, leaf(5), &siblings));
+		// wrong leaf
+		assert!(!mmr.verify::<BlakeTwo256>(5, leaf(6), &siblings));
+		// wrong index
+		assert!(!mmr.verify::<BlakeTwo256>(4, leaf(5), &siblings));
+		// missing sibling
+		assert!(!mmr.verify::<BlakeTwo256>(5, leaf(5), &siblings[1..]));
+		// unknown leaf
+		assert!(!mmr.verify::<BlakeTwo256>(11, leaf(11), &[]));
+		assert_eq!(build_mmr_proof::<BlakeTwo256>(&leaves, 11), None);
+	}
+}
@@ -42,6 +42,7 @@ pub use call_info::{BridgeGrandpaCall, BridgeGrandpaCallOf, SubmitFinalityProofI
 
 mod call_info;
 
+pub mod archive;
 pub mod justification;
 pub mod storage_keys;
 
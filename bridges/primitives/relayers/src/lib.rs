@@ -25,13 +25,13 @@ pub use extension::{
 };
 pub use registration::{ExplicitOrAccountParams, Registration, StakeAndSlash};
 
-use bp_runtime::{ChainId, StorageDoubleMapKeyProvider};
+use bp_runtime::{ChainId, StorageDoubleMapKeyProvider, StorageMapKeyProvider};
 use frame_support::{traits::tokens::Preservation, Blake2_128Concat, Identity};
 use scale_info::TypeInfo;
 use sp_runtime::{
 	codec::{Codec, Decode, Encode, EncodeLike, MaxEncodedLen},
 	traits::AccountIdConversion,
-	TypeId,
+	Perbill, TypeId,
 };
 use sp_std::{fmt::Debug, marker::PhantomData};
 
@@ -179,6 +179,21 @@ where
 	type Value = Reward;
 }
 
+/// Can be used to access the runtime storage key within the `DeliveryRewardShares` map of the
+/// relayers pallet.
+pub struct DeliveryRewardSharesKeyProvider<LaneId>(PhantomData<LaneId>);
+
+impl<LaneId> StorageMapKeyProvider for DeliveryRewardSharesKeyProvider<LaneId>
+where
+	LaneId: Codec + EncodeLike + Send + Sync,
+{
+	const MAP_NAME: &'static str = "DeliveryRewardShares";
+
+	type Hasher = Blake2_128Concat;
+	type Key = LaneId;
+	type Value = Perbill;
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -330,6 +330,11 @@ macro_rules! decl_bridge_finality_runtime_apis {
 					/// runtime method.
 					pub const [<$chain:upper _SYNCED_HEADERS_ $consensus:upper _INFO_METHOD>]: &str =
 						stringify!([<$chain:camel FinalityApi_synced_headers_ $consensus:lower _info>]);
+
+					/// Name of the `<ThisChain>FinalityApi::<consensus>_authority_set_changes`
+					/// runtime method.
+					pub const [<$chain:upper _ $consensus:upper _AUTHORITY_SET_CHANGES_METHOD>]: &str =
+						stringify!([<$chain:camel FinalityApi_ $consensus:lower _authority_set_changes>]);
 				)?
 
 				sp_api::decl_runtime_apis! {
@@ -337,6 +342,9 @@ macro_rules! decl_bridge_finality_runtime_apis {
 					///
 					/// This API is implemented by runtimes that are receiving messages from this chain, not by this
 					/// chain's runtime itself.
+					///
+					/// Version 2 adds the `<consensus>_authority_set_changes` method.
+					#[api_version(2)]
 					pub trait [<$chain:camel FinalityApi>] {
 						/// Returns number and hash of the best finalized header known to the bridge module.
 						fn best_finalized() -> Option<bp_runtime::HeaderId<Hash, BlockNumber>>;
@@ -352,6 +360,18 @@ macro_rules! decl_bridge_finality_runtime_apis {
 							/// Returns the justifications accepted in the current block.
 							fn [<synced_headers_ $consensus:lower _info>](
 							) -> sp_std::vec::Vec<$justification_type>;
+
+							/// Returns the archived finality proofs of authority set changes,
+							/// starting from the change of the set with `from_set_id` id. At most
+							/// `limit` proofs are returned.
+							///
+							/// Proofs are only archived if the bridge module is configured to
+							/// archive pruned headers.
+							#[api_version(2)]
+							fn [<$consensus:lower _authority_set_changes>](
+								from_set_id: u64,
+								limit: u32,
+							) -> sp_std::vec::Vec<$justification_type>;
 						)?
 					}
 				}
@@ -17,6 +17,7 @@
 use crate::calls::UtilityCall;
 
 use crate::SimpleRuntimeVersion;
+use bp_beefy::ChainWithBeefy as ChainWithBeefyBase;
 use bp_header_chain::ChainWithGrandpa as ChainWithGrandpaBase;
 use bp_messages::ChainWithMessages as ChainWithMessagesBase;
 use bp_runtime::{
@@ -106,6 +107,19 @@ pub trait ChainWithGrandpa: Chain + ChainWithGrandpaBase {
 	type KeyOwnerProof: Decode + TypeInfo + Send;
 }
 
+/// Substrate-based chain that is using BEEFY finality from minimal relay-client point of view.
+pub trait ChainWithBeefy: Chain + ChainWithBeefyBase {
+	/// Name of the runtime API method that is returning the BEEFY commitments accepted by the
+	/// `submit_commitment` extrinsic in the queried block.
+	///
+	/// Keep in mind that this method is normally provided by the other chain, which is
+	/// bridged with this chain.
+	const SYNCED_HEADERS_BEEFY_INFO_METHOD: &'static str;
+
+	/// The type of the key owner proof used by the BEEFY pallet.
+	type KeyOwnerProof: Decode + TypeInfo + Send;
+}
+
 /// Substrate-based parachain from minimal relay-client point of view.
 pub trait Parachain: Chain + ParachainBase {}
 
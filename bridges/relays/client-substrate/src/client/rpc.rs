@@ -30,8 +30,8 @@ use crate::{
 	error::{Error, Result},
 	guard::Environment,
 	transaction_stall_timeout, AccountIdOf, AccountKeyPairOf, BalanceOf, BlockNumberOf, Chain,
-	ChainRuntimeVersion, ChainWithGrandpa, ChainWithTransactions, ConnectionParams, HashOf,
-	HeaderIdOf, HeaderOf, NonceOf, SignParam, SignedBlockOf, SimpleRuntimeVersion,
+	ChainRuntimeVersion, ChainWithBeefy, ChainWithGrandpa, ChainWithTransactions, ConnectionParams,
+	HashOf, HeaderIdOf, HeaderOf, NonceOf, SignParam, SignedBlockOf, SimpleRuntimeVersion,
 	TransactionTracker, UnsignedTransaction,
 };
 
@@ -425,7 +425,10 @@ impl<C: Chain> Client<C> for RpcClient<C> {
 		.await
 	}
 
-	async fn subscribe_beefy_finality_justifications(&self) -> Result<Subscription<Bytes>> {
+	async fn subscribe_beefy_finality_justifications(&self) -> Result<Subscription<Bytes>>
+	where
+		C: ChainWithBeefy,
+	{
 		self.subscribe_finality_justifications("BEEFY", move |client| async move {
 			SubstrateBeefyClient::<C>::subscribe_justifications(&*client).await
 		})
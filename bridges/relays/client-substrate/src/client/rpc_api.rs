@@ -16,7 +16,7 @@
 
 //! The most generic Substrate node RPC interface.
 
-use crate::{Chain, ChainWithGrandpa, TransactionStatusOf};
+use crate::{Chain, ChainWithBeefy, ChainWithGrandpa, TransactionStatusOf};
 
 use jsonrpsee::proc_macros::rpc;
 use pallet_transaction_payment_rpc_runtime_api::FeeDetails;
@@ -122,9 +122,8 @@ pub(crate) trait SubstrateGrandpa<C> {
 	async fn subscribe_justifications(&self);
 }
 
-// TODO: Use `ChainWithBeefy` instead of `Chain` after #1606 is merged
 /// RPC methods of Substrate `beefy` namespace, that we are using.
-#[rpc(client, client_bounds(C: Chain), namespace = "beefy")]
+#[rpc(client, client_bounds(C: ChainWithBeefy), namespace = "beefy")]
 pub(crate) trait SubstrateBeefy<C> {
 	/// Subscribe to BEEFY justifications.
 	#[subscription(name = "subscribeJustifications", unsubscribe = "unsubscribeJustifications", item = Bytes)]
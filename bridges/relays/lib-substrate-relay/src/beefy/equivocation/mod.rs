@@ -0,0 +1,400 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Types and functions intended to ease adding of new Substrate -> Substrate
+//! BEEFY equivocation detection pipelines.
+
+mod source;
+mod target;
+
+use crate::{
+	beefy::{
+		equivocation::{
+			source::SubstrateBeefyEquivocationSource, target::SubstrateBeefyEquivocationTarget,
+		},
+		BeefyFinalityProofOf,
+	},
+	TransactionParams,
+};
+
+use async_trait::async_trait;
+use bp_beefy::{BeefyAuthorityIdOf, BeefyAuthoritySetOf, BeefyValidatorSignatureOf};
+use bp_header_chain::FindEquivocations;
+use bp_runtime::{AccountIdOf, BlockNumberOf, HashOf};
+use equivocation_detector::EquivocationDetectionPipeline;
+use finality_relay::FinalityPipeline;
+use pallet_beefy::{Call as BeefyCall, Config as BeefyConfig};
+use relay_substrate_client::{
+	AccountKeyPairOf, CallOf, Chain, ChainWithBeefy, ChainWithTransactions, Client,
+};
+use relay_utils::metrics::MetricsParams;
+use sp_consensus_beefy::{DoubleVotingProof, VoteMessage};
+use sp_core::Pair;
+use sp_runtime::traits::{Block, Header};
+use std::{fmt::Debug, marker::PhantomData};
+
+/// BEEFY equivocation proof of the given chain.
+pub type BeefyEquivocationProofOf<C> =
+	DoubleVotingProof<BlockNumberOf<C>, BeefyAuthorityIdOf<C>, BeefyValidatorSignatureOf<C>>;
+/// The type of the key owner proof, used by the BEEFY pallet of the given chain.
+pub type KeyOwnerProofOf<C> = <C as ChainWithBeefy>::KeyOwnerProof;
+
+/// Substrate -> Substrate BEEFY equivocation detection pipeline.
+#[async_trait]
+pub trait SubstrateBeefyEquivocationDetectionPipeline:
+	'static + Clone + Debug + Send + Sync
+{
+	/// Headers of this chain are submitted to the `TargetChain`.
+	type SourceChain: ChainWithBeefy + ChainWithTransactions;
+	/// Headers of the `SourceChain` are submitted to this chain.
+	type TargetChain: Chain;
+
+	/// How the `report_double_voting` call is built ?
+	type ReportEquivocationCallBuilder: ReportBeefyEquivocationCallBuilder<Self>;
+
+	/// Add relay guards if required.
+	async fn start_relay_guards(
+		source_client: &impl Client<Self::SourceChain>,
+		enable_version_guard: bool,
+	) -> relay_substrate_client::Result<()> {
+		if enable_version_guard {
+			relay_substrate_client::guard::abort_on_spec_version_change(
+				source_client.clone(),
+				source_client.simple_runtime_version().await?.spec_version,
+			);
+		}
+		Ok(())
+	}
+}
+
+/// Adapter that allows a `SubstrateBeefyEquivocationDetectionPipeline` to act as an
+/// `EquivocationDetectionPipeline`.
+#[derive(Clone, Debug)]
+pub struct BeefyEquivocationDetectionPipelineAdapter<P: SubstrateBeefyEquivocationDetectionPipeline>
+{
+	_phantom: PhantomData<P>,
+}
+
+impl<P: SubstrateBeefyEquivocationDetectionPipeline> FinalityPipeline
+	for BeefyEquivocationDetectionPipelineAdapter<P>
+{
+	const SOURCE_NAME: &'static str = P::SourceChain::NAME;
+	const TARGET_NAME: &'static str = P::TargetChain::NAME;
+
+	type Hash = HashOf<P::SourceChain>;
+	type Number = BlockNumberOf<P::SourceChain>;
+	type FinalityProof = BeefyFinalityProofOf<P::SourceChain>;
+}
+
+impl<P: SubstrateBeefyEquivocationDetectionPipeline> EquivocationDetectionPipeline
+	for BeefyEquivocationDetectionPipelineAdapter<P>
+{
+	type TargetNumber = BlockNumberOf<P::TargetChain>;
+	type FinalityVerificationContext = BeefyAuthoritySetOf<P::SourceChain>;
+	type EquivocationProof = BeefyEquivocationProofOf<P::SourceChain>;
+	type EquivocationsFinder = BeefyEquivocationsFinder<P::SourceChain>;
+}
+
+/// Errors that may happen when looking for BEEFY equivocations.
+#[derive(Debug, PartialEq)]
+pub enum BeefyEquivocationsFinderError {
+	/// The synced commitment is signed by the other validator set.
+	UnexpectedValidatorSetId,
+	/// The number of signatures in the synced commitment doesn't match the validator set size.
+	InvalidSignaturesLen,
+}
+
+/// Looks for BEEFY validators that have signed different commitments for the same block.
+pub struct BeefyEquivocationsFinder<C>(PhantomData<C>);
+
+impl<C: ChainWithBeefy>
+	FindEquivocations<BeefyFinalityProofOf<C>, BeefyAuthoritySetOf<C>, BeefyEquivocationProofOf<C>>
+	for BeefyEquivocationsFinder<C>
+{
+	type Error = BeefyEquivocationsFinderError;
+
+	fn find_equivocations(
+		validator_set: &BeefyAuthoritySetOf<C>,
+		synced_proof: &BeefyFinalityProofOf<C>,
+		source_proofs: &[BeefyFinalityProofOf<C>],
+	) -> Result<Vec<BeefyEquivocationProofOf<C>>, Self::Error> {
+		let synced = &synced_proof.signed_commitment;
+		if synced.commitment.validator_set_id != validator_set.id() {
+			return Err(BeefyEquivocationsFinderError::UnexpectedValidatorSetId)
+		}
+		if synced.signatures.len() != validator_set.validators().len() {
+			return Err(BeefyEquivocationsFinderError::InvalidSignaturesLen)
+		}
+
+		let mut equivocations = vec![];
+		for source_proof in source_proofs {
+			let source = &source_proof.signed_commitment;
+			let is_conflicting = source.commitment.block_number == synced.commitment.block_number &&
+				source.commitment.validator_set_id == synced.commitment.validator_set_id &&
+				source.commitment.payload != synced.commitment.payload;
+			if !is_conflicting {
+				continue
+			}
+
+			let votes = validator_set
+				.validators()
+				.iter()
+				.zip(synced.signatures.iter())
+				.zip(source.signatures.iter());
+			for ((id, synced_signature), source_signature) in votes {
+				if let (Some(synced_signature), Some(source_signature)) =
+					(synced_signature, source_signature)
+				{
+					equivocations.push(DoubleVotingProof {
+						first: VoteMessage {
+							commitment: synced.commitment.clone(),
+							id: id.clone(),
+							signature: synced_signature.clone(),
+						},
+						second: VoteMessage {
+							commitment: source.commitment.clone(),
+							id: id.clone(),
+							signature: source_signature.clone(),
+						},
+					});
+				}
+			}
+		}
+
+		Ok(equivocations)
+	}
+}
+
+/// Different ways of building `report_double_voting` calls.
+pub trait ReportBeefyEquivocationCallBuilder<P: SubstrateBeefyEquivocationDetectionPipeline> {
+	/// Build a `report_double_voting` call to be executed on the source chain.
+	fn build_report_equivocation_call(
+		equivocation_proof: BeefyEquivocationProofOf<P::SourceChain>,
+		key_owner_proof: KeyOwnerProofOf<P::SourceChain>,
+	) -> CallOf<P::SourceChain>;
+}
+
+/// Building the `report_double_voting` call when having direct access to the source chain
+/// runtime.
+pub struct DirectReportBeefyEquivocationCallBuilder<P, R> {
+	_phantom: PhantomData<(P, R)>,
+}
+
+impl<P, R> ReportBeefyEquivocationCallBuilder<P> for DirectReportBeefyEquivocationCallBuilder<P, R>
+where
+	P: SubstrateBeefyEquivocationDetectionPipeline,
+	R: frame_system::Config
+		+ BeefyConfig<
+			BeefyId = BeefyAuthorityIdOf<P::SourceChain>,
+			KeyOwnerProof = KeyOwnerProofOf<P::SourceChain>,
+		>,
+	<R::Block as Block>::Header: Header<Number = BlockNumberOf<P::SourceChain>>,
+	CallOf<P::SourceChain>: From<BeefyCall<R>>,
+{
+	fn build_report_equivocation_call(
+		equivocation_proof: BeefyEquivocationProofOf<P::SourceChain>,
+		key_owner_proof: KeyOwnerProofOf<P::SourceChain>,
+	) -> CallOf<P::SourceChain> {
+		BeefyCall::<R>::report_double_voting {
+			equivocation_proof: Box::new(equivocation_proof),
+			key_owner_proof,
+		}
+		.into()
+	}
+}
+
+/// Macro that generates `ReportBeefyEquivocationCallBuilder` implementation for the case where
+/// we only have access to the mocked version of the source chain runtime.
+#[rustfmt::skip]
+#[macro_export]
+macro_rules! generate_report_beefy_equivocation_call_builder {
+	($pipeline:ident, $mocked_builder:ident, $beefy:path, $report_double_voting:path) => {
+		pub struct $mocked_builder;
+
+		impl $crate::beefy::equivocation::ReportBeefyEquivocationCallBuilder<$pipeline>
+			for $mocked_builder
+		{
+			fn build_report_equivocation_call(
+				equivocation_proof: $crate::beefy::equivocation::BeefyEquivocationProofOf<
+					<$pipeline as $crate::beefy::equivocation::SubstrateBeefyEquivocationDetectionPipeline>::SourceChain
+				>,
+				key_owner_proof: $crate::beefy::equivocation::KeyOwnerProofOf<
+					<$pipeline as $crate::beefy::equivocation::SubstrateBeefyEquivocationDetectionPipeline>::SourceChain
+				>,
+			) -> relay_substrate_client::CallOf<
+				<$pipeline as $crate::beefy::equivocation::SubstrateBeefyEquivocationDetectionPipeline>::SourceChain
+			> {
+				bp_runtime::paste::item! {
+					$beefy($report_double_voting {
+						equivocation_proof: Box::new(equivocation_proof),
+						key_owner_proof: key_owner_proof
+					})
+				}
+			}
+		}
+	};
+}
+
+/// Run Substrate-to-Substrate BEEFY equivocations detection loop.
+pub async fn run<P: SubstrateBeefyEquivocationDetectionPipeline>(
+	source_client: impl Client<P::SourceChain>,
+	target_client: impl Client<P::TargetChain>,
+	source_transaction_params: TransactionParams<AccountKeyPairOf<P::SourceChain>>,
+	metrics_params: MetricsParams,
+) -> anyhow::Result<()>
+where
+	AccountIdOf<P::SourceChain>: From<<AccountKeyPairOf<P::SourceChain> as Pair>::Public>,
+{
+	log::info!(
+		target: "bridge",
+		"Starting {} -> {} BEEFY equivocations detection loop",
+		P::SourceChain::NAME,
+		P::TargetChain::NAME,
+	);
+
+	equivocation_detector::run(
+		SubstrateBeefyEquivocationSource::<P, _>::new(
+			source_client.clone(),
+			source_transaction_params,
+		),
+		SubstrateBeefyEquivocationTarget::<P, _, _>::new(source_client, target_client),
+		P::TargetChain::AVERAGE_BLOCK_INTERVAL,
+		metrics_params,
+		futures::future::pending(),
+	)
+	.await
+	.map_err(|e| anyhow::format_err!("{}", e))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::beefy::BeefyFinalityProof;
+	use bp_beefy::{BeefyPayload, Commitment, SignedCommitment, ValidatorSet, MMR_ROOT_PAYLOAD_ID};
+	use codec::Encode;
+	use relay_substrate_client::test_chain::TestChain;
+	use sp_consensus_beefy::{check_double_voting_proof, test_utils::Keyring};
+	use sp_runtime::traits::Keccak256;
+
+	type Validator = Keyring<bp_beefy::EcdsaValidatorId>;
+
+	const VALIDATORS: [Validator; 3] = [Validator::Alice, Validator::Bob, Validator::Charlie];
+
+	fn validator_set() -> BeefyAuthoritySetOf<TestChain> {
+		ValidatorSet::new(VALIDATORS.iter().map(|validator| validator.public()), 0).unwrap()
+	}
+
+	fn commitment(block_number: u32, mmr_root: u8) -> Commitment<u32> {
+		Commitment {
+			payload: BeefyPayload::from_single_entry(MMR_ROOT_PAYLOAD_ID, vec![mmr_root]),
+			block_number,
+			validator_set_id: 0,
+		}
+	}
+
+	/// Returns the proof of `commitment`, signed by the validators at `signers` positions.
+	fn proof(commitment: Commitment<u32>, signers: &[usize]) -> BeefyFinalityProofOf<TestChain> {
+		let signatures = VALIDATORS
+			.iter()
+			.enumerate()
+			.map(|(i, validator)| {
+				signers.contains(&i).then(|| validator.sign(&commitment.encode()))
+			})
+			.collect();
+		BeefyFinalityProof {
+			header_hash: Default::default(),
+			signed_commitment: SignedCommitment { commitment, signatures },
+		}
+	}
+
+	fn find(
+		synced_proof: &BeefyFinalityProofOf<TestChain>,
+		source_proofs: &[BeefyFinalityProofOf<TestChain>],
+	) -> Result<Vec<BeefyEquivocationProofOf<TestChain>>, BeefyEquivocationsFinderError> {
+		BeefyEquivocationsFinder::<TestChain>::find_equivocations(
+			&validator_set(),
+			synced_proof,
+			source_proofs,
+		)
+	}
+
+	#[test]
+	fn find_equivocations_rejects_proofs_of_other_validator_sets() {
+		let mut synced_proof = proof(commitment(10, 1), &[0, 1, 2]);
+		synced_proof.signed_commitment.commitment.validator_set_id = 1;
+
+		assert_eq!(
+			find(&synced_proof, &[]),
+			Err(BeefyEquivocationsFinderError::UnexpectedValidatorSetId)
+		);
+	}
+
+	#[test]
+	fn find_equivocations_rejects_proofs_with_wrong_number_of_signatures() {
+		let mut synced_proof = proof(commitment(10, 1), &[0, 1, 2]);
+		synced_proof.signed_commitment.signatures.pop();
+
+		assert_eq!(
+			find(&synced_proof, &[]),
+			Err(BeefyEquivocationsFinderError::InvalidSignaturesLen)
+		);
+	}
+
+	#[test]
+	fn find_equivocations_ignores_commitments_that_do_not_conflict() {
+		let synced_proof = proof(commitment(10, 1), &[0, 1, 2]);
+		let mut other_set_proof = proof(commitment(10, 2), &[0, 1, 2]);
+		other_set_proof.signed_commitment.commitment.validator_set_id = 1;
+
+		assert_eq!(
+			find(
+				&synced_proof,
+				&[
+					// The same commitment.
+					proof(commitment(10, 1), &[0, 1]),
+					// A commitment for another block.
+					proof(commitment(11, 2), &[0, 1, 2]),
+					// A commitment of another validator set.
+					other_set_proof,
+				]
+			),
+			Ok(vec![])
+		);
+	}
+
+	#[test]
+	fn find_equivocations_reports_validators_that_signed_conflicting_commitments() {
+		let synced_proof = proof(commitment(10, 1), &[0, 1]);
+		let source_proof = proof(commitment(10, 2), &[1, 2]);
+
+		// Only Bob has signed both commitments.
+		let equivocations = find(&synced_proof, &[source_proof.clone()]).unwrap();
+		assert_eq!(equivocations.len(), 1);
+		let equivocation = &equivocations[0];
+		assert_eq!(equivocation.offender_id(), &Validator::Bob.public());
+		assert_eq!(equivocation.first.commitment, synced_proof.signed_commitment.commitment);
+		assert_eq!(equivocation.second.commitment, source_proof.signed_commitment.commitment);
+		assert!(check_double_voting_proof::<_, _, Keccak256>(equivocation));
+
+		// Every conflicting source proof is checked.
+		let equivocations =
+			find(&synced_proof, &[source_proof, proof(commitment(10, 3), &[0, 1, 2])]).unwrap();
+		assert_eq!(
+			equivocations.iter().map(|e| e.offender_id().clone()).collect::<Vec<_>>(),
+			vec![Validator::Bob.public(), Validator::Alice.public(), Validator::Bob.public()],
+		);
+	}
+}
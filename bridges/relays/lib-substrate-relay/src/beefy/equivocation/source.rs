@@ -0,0 +1,152 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Default generic implementation of BEEFY equivocation source for basic Substrate client.
+
+use crate::{
+	beefy::{
+		equivocation::{
+			BeefyEquivocationDetectionPipelineAdapter, BeefyEquivocationProofOf, KeyOwnerProofOf,
+			ReportBeefyEquivocationCallBuilder, SubstrateBeefyEquivocationDetectionPipeline,
+		},
+		source::{beefy_finality_proofs, BeefyFinalityProofsStream},
+	},
+	TransactionParams,
+};
+
+use async_trait::async_trait;
+use bp_runtime::{HashOf, TransactionEra};
+use equivocation_detector::SourceClient;
+use finality_relay::SourceClientBase;
+use relay_substrate_client::{
+	AccountKeyPairOf, Chain, Client, Error, TransactionTracker, UnsignedTransaction,
+};
+use relay_utils::relay_loop::Client as RelayClient;
+use sp_consensus_beefy::OpaqueKeyOwnershipProof;
+
+/// Name of the runtime API method that generates the BEEFY key ownership proof.
+const BEEFY_API_GENERATE_KEY_OWNERSHIP_PROOF_METHOD: &str = "BeefyApi_generate_key_ownership_proof";
+
+/// Substrate node as BEEFY equivocation source.
+pub struct SubstrateBeefyEquivocationSource<
+	P: SubstrateBeefyEquivocationDetectionPipeline,
+	SourceClnt,
+> {
+	client: SourceClnt,
+	transaction_params: TransactionParams<AccountKeyPairOf<P::SourceChain>>,
+}
+
+impl<P: SubstrateBeefyEquivocationDetectionPipeline, SourceClnt: Client<P::SourceChain>>
+	SubstrateBeefyEquivocationSource<P, SourceClnt>
+{
+	/// Create new instance of `SubstrateBeefyEquivocationSource`.
+	pub fn new(
+		client: SourceClnt,
+		transaction_params: TransactionParams<AccountKeyPairOf<P::SourceChain>>,
+	) -> Self {
+		Self { client, transaction_params }
+	}
+
+	/// Generate proof that the offender has been a member of the BEEFY validator set.
+	async fn generate_key_ownership_proof(
+		&self,
+		at: HashOf<P::SourceChain>,
+		equivocation: &BeefyEquivocationProofOf<P::SourceChain>,
+	) -> Result<KeyOwnerProofOf<P::SourceChain>, Error> {
+		let opaque_key_owner_proof = self
+			.client
+			.state_call::<_, Option<OpaqueKeyOwnershipProof>>(
+				at,
+				BEEFY_API_GENERATE_KEY_OWNERSHIP_PROOF_METHOD.into(),
+				(equivocation.set_id(), equivocation.offender_id().clone()),
+			)
+			.await?
+			.ok_or(Error::Custom(format!(
+				"Couldn't get BEEFY key ownership proof from {} at block: {at} \
+				for offender: {:?}, set_id: {}",
+				P::SourceChain::NAME,
+				equivocation.offender_id(),
+				equivocation.set_id(),
+			)))?;
+
+		opaque_key_owner_proof.decode().ok_or(Error::Custom(format!(
+			"Couldn't decode BEEFY key ownership proof generated by {} at block: {at}",
+			P::SourceChain::NAME,
+		)))
+	}
+}
+
+impl<P: SubstrateBeefyEquivocationDetectionPipeline, SourceClnt: Client<P::SourceChain>> Clone
+	for SubstrateBeefyEquivocationSource<P, SourceClnt>
+{
+	fn clone(&self) -> Self {
+		Self { client: self.client.clone(), transaction_params: self.transaction_params.clone() }
+	}
+}
+
+#[async_trait]
+impl<P: SubstrateBeefyEquivocationDetectionPipeline, SourceClnt: Client<P::SourceChain>> RelayClient
+	for SubstrateBeefyEquivocationSource<P, SourceClnt>
+{
+	type Error = Error;
+
+	async fn reconnect(&mut self) -> Result<(), Error> {
+		self.client.reconnect().await
+	}
+}
+
+#[async_trait]
+impl<P: SubstrateBeefyEquivocationDetectionPipeline, SourceClnt: Client<P::SourceChain>>
+	SourceClientBase<BeefyEquivocationDetectionPipelineAdapter<P>>
+	for SubstrateBeefyEquivocationSource<P, SourceClnt>
+{
+	type FinalityProofsStream = BeefyFinalityProofsStream<P::SourceChain>;
+
+	async fn finality_proofs(&self) -> Result<Self::FinalityProofsStream, Error> {
+		beefy_finality_proofs(&self.client).await
+	}
+}
+
+#[async_trait]
+impl<P: SubstrateBeefyEquivocationDetectionPipeline, SourceClnt: Client<P::SourceChain>>
+	SourceClient<BeefyEquivocationDetectionPipelineAdapter<P>>
+	for SubstrateBeefyEquivocationSource<P, SourceClnt>
+{
+	type TransactionTracker = TransactionTracker<P::SourceChain, SourceClnt>;
+
+	async fn report_equivocation(
+		&self,
+		at: HashOf<P::SourceChain>,
+		equivocation: BeefyEquivocationProofOf<P::SourceChain>,
+	) -> Result<Self::TransactionTracker, Self::Error> {
+		let key_owner_proof = self.generate_key_ownership_proof(at, &equivocation).await?;
+
+		let mortality = self.transaction_params.mortality;
+		let call = P::ReportEquivocationCallBuilder::build_report_equivocation_call(
+			equivocation,
+			key_owner_proof,
+		);
+		self.client
+			.submit_and_watch_signed_extrinsic(
+				&self.transaction_params.signer,
+				move |best_block_id, transaction_nonce| {
+					Ok(UnsignedTransaction::new(call.into(), transaction_nonce)
+						.era(TransactionEra::new(best_block_id, mortality)))
+				},
+			)
+			.await
+	}
+}
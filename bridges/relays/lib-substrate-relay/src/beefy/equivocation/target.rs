@@ -0,0 +1,179 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Default generic implementation of BEEFY equivocation target for basic Substrate client.
+//!
+//! The bridge BEEFY pallet only stores the number of the best imported header, so the target
+//! reads hashes of synced headers and validator sets from the source chain.
+
+use crate::beefy::{
+	best_synced_header_id,
+	equivocation::{
+		BeefyEquivocationDetectionPipelineAdapter, SubstrateBeefyEquivocationDetectionPipeline,
+	},
+	validator_set_at, BeefyFinalityProof, BeefyFinalityProofOf,
+};
+
+use async_trait::async_trait;
+use bp_beefy::{BeefyAuthoritySetOf, BeefySignedCommitmentOf};
+use bp_header_chain::HeaderFinalityInfo;
+use bp_runtime::{BlockNumberOf, HashOf};
+use equivocation_detector::TargetClient;
+use relay_substrate_client::{ChainWithBeefy, Client, Error};
+use relay_utils::relay_loop::Client as RelayClient;
+use sp_runtime::traits::Header;
+use std::marker::PhantomData;
+
+/// Substrate node as BEEFY equivocation target.
+pub struct SubstrateBeefyEquivocationTarget<
+	P: SubstrateBeefyEquivocationDetectionPipeline,
+	SourceClnt,
+	TargetClnt,
+> {
+	source_client: SourceClnt,
+	target_client: TargetClnt,
+
+	_phantom: PhantomData<P>,
+}
+
+impl<
+		P: SubstrateBeefyEquivocationDetectionPipeline,
+		SourceClnt: Client<P::SourceChain>,
+		TargetClnt: Client<P::TargetChain>,
+	> SubstrateBeefyEquivocationTarget<P, SourceClnt, TargetClnt>
+{
+	/// Create new instance of `SubstrateBeefyEquivocationTarget`.
+	pub fn new(source_client: SourceClnt, target_client: TargetClnt) -> Self {
+		Self { source_client, target_client, _phantom: Default::default() }
+	}
+
+	/// Return id of the best source header, synced to the target at given target block.
+	async fn best_synced_header_hash_at(
+		&self,
+		at: BlockNumberOf<P::TargetChain>,
+	) -> Result<Option<HashOf<P::SourceChain>>, Error> {
+		Ok(best_synced_header_id::<P::SourceChain, P::TargetChain>(
+			&self.source_client,
+			&self.target_client,
+			self.target_client.header_by_number(at).await?.hash(),
+		)
+		.await?
+		.map(|id| id.hash()))
+	}
+}
+
+impl<
+		P: SubstrateBeefyEquivocationDetectionPipeline,
+		SourceClnt: Client<P::SourceChain>,
+		TargetClnt: Client<P::TargetChain>,
+	> Clone for SubstrateBeefyEquivocationTarget<P, SourceClnt, TargetClnt>
+{
+	fn clone(&self) -> Self {
+		Self {
+			source_client: self.source_client.clone(),
+			target_client: self.target_client.clone(),
+			_phantom: Default::default(),
+		}
+	}
+}
+
+#[async_trait]
+impl<
+		P: SubstrateBeefyEquivocationDetectionPipeline,
+		SourceClnt: Client<P::SourceChain>,
+		TargetClnt: Client<P::TargetChain>,
+	> RelayClient for SubstrateBeefyEquivocationTarget<P, SourceClnt, TargetClnt>
+{
+	type Error = Error;
+
+	async fn reconnect(&mut self) -> Result<(), Error> {
+		self.source_client.reconnect().await?;
+		self.target_client.reconnect().await
+	}
+}
+
+#[async_trait]
+impl<
+		P: SubstrateBeefyEquivocationDetectionPipeline,
+		SourceClnt: Client<P::SourceChain>,
+		TargetClnt: Client<P::TargetChain>,
+	> TargetClient<BeefyEquivocationDetectionPipelineAdapter<P>>
+	for SubstrateBeefyEquivocationTarget<P, SourceClnt, TargetClnt>
+{
+	async fn best_finalized_header_number(
+		&self,
+	) -> Result<BlockNumberOf<P::TargetChain>, Self::Error> {
+		self.target_client.best_finalized_header_number().await
+	}
+
+	async fn best_synced_header_hash(
+		&self,
+		at: BlockNumberOf<P::TargetChain>,
+	) -> Result<Option<HashOf<P::SourceChain>>, Self::Error> {
+		self.best_synced_header_hash_at(at).await
+	}
+
+	async fn finality_verification_context(
+		&self,
+		at: BlockNumberOf<P::TargetChain>,
+	) -> Result<BeefyAuthoritySetOf<P::SourceChain>, Self::Error> {
+		let best_synced_header_hash = self
+			.best_synced_header_hash_at(at)
+			.await?
+			.ok_or(Error::BridgePalletIsNotInitialized)?;
+		validator_set_at(&self.source_client, best_synced_header_hash).await
+	}
+
+	async fn synced_headers_finality_info(
+		&self,
+		at: BlockNumberOf<P::TargetChain>,
+	) -> Result<
+		Vec<
+			HeaderFinalityInfo<
+				BeefyFinalityProofOf<P::SourceChain>,
+				BeefyAuthoritySetOf<P::SourceChain>,
+			>,
+		>,
+		Self::Error,
+	> {
+		let commitments: Vec<BeefySignedCommitmentOf<P::SourceChain>> = self
+			.target_client
+			.state_call(
+				self.target_client.header_by_number(at).await?.hash(),
+				P::SourceChain::SYNCED_HEADERS_BEEFY_INFO_METHOD.to_string(),
+				(),
+			)
+			.await?;
+
+		let mut synced_headers_finality_info = Vec::with_capacity(commitments.len());
+		for signed_commitment in commitments {
+			let header_hash = self
+				.source_client
+				.header_hash_by_number(signed_commitment.commitment.block_number)
+				.await?;
+			// the validator set is read at every synced header, since the commitment may
+			// enact the new validator set
+			let new_verification_context =
+				Some(validator_set_at(&self.source_client, header_hash).await?);
+			synced_headers_finality_info.push(HeaderFinalityInfo {
+				finality_proof: BeefyFinalityProof { header_hash, signed_commitment },
+				new_verification_context,
+			});
+		}
+
+		Ok(synced_headers_finality_info)
+	}
+}
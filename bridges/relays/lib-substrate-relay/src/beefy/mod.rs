@@ -0,0 +1,353 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Types and functions intended to ease adding of new Substrate -> Substrate
+//! BEEFY finality proofs synchronization and equivocation detection pipelines.
+//!
+//! Unlike GRANDPA justifications, BEEFY commitments are not enough to import the finalized
+//! header: the bridge BEEFY pallet also needs the validator set that has signed the commitment
+//! and the MMR leaf of the finalized header along with its proof. All this data is read from
+//! the source chain, so the BEEFY targets are also connected to the source chain.
+
+use crate::{
+	beefy::{source::SubstrateBeefySource, target::SubstrateBeefyTarget},
+	TransactionParams,
+};
+
+use async_trait::async_trait;
+use bp_beefy::{
+	BeefyAuthorityIdOf, BeefyAuthoritySetOf, BeefyConsensusLogReader, BeefyMmrLeafExtraOf,
+	BeefyMmrLeafOf, BeefySignedCommitmentOf, BeefyValidatorSignatureOf, MmrHashOf, MmrProofOf,
+	SignedCommitment,
+};
+use bp_header_chain::FinalityProof;
+use bp_runtime::{HeaderId, HeaderIdOf};
+use finality_relay::{FinalityPipeline, FinalitySyncPipeline, HeadersToRelay};
+use pallet_bridge_beefy::{
+	Call as BridgeBeefyCall, Config as BridgeBeefyConfig, ImportedCommitmentsInfoData,
+};
+use relay_substrate_client::{
+	transaction_stall_timeout, AccountIdOf, AccountKeyPairOf, BlockNumberOf, CallOf, Chain,
+	ChainWithBeefy, ChainWithTransactions, Client, HashOf, HeaderOf, SyncHeader,
+};
+use relay_utils::metrics::MetricsParams;
+use sp_core::Pair;
+use std::{fmt::Debug, marker::PhantomData};
+
+pub mod equivocation;
+pub mod source;
+pub mod target;
+
+/// Name of the runtime API method that returns the current BEEFY validator set.
+const BEEFY_API_VALIDATOR_SET_METHOD: &str = "BeefyApi_validator_set";
+
+/// Default limit of recent finality proofs.
+///
+/// BEEFY is expected to lag behind GRANDPA, but not by more than a session.
+pub(crate) const RECENT_FINALITY_PROOFS_LIMIT: usize = 4096;
+
+/// BEEFY finality proof of the source chain header.
+///
+/// The BEEFY justification only contains the number of the finalized header, so we keep its hash
+/// next to it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BeefyFinalityProof<Hash, Number, Signature> {
+	/// Hash of the finalized header.
+	pub header_hash: Hash,
+	/// Signed commitment, finalizing the header.
+	pub signed_commitment: SignedCommitment<Number, Signature>,
+}
+
+impl<Hash, Number, Signature> FinalityProof<Hash, Number>
+	for BeefyFinalityProof<Hash, Number, Signature>
+where
+	Hash: Copy + Debug + Send + Sync,
+	Number: Copy + Debug + Send + Sync,
+	Signature: Clone + Debug + Send + Sync,
+{
+	fn target_header_hash(&self) -> Hash {
+		self.header_hash
+	}
+
+	fn target_header_number(&self) -> Number {
+		self.signed_commitment.commitment.block_number
+	}
+}
+
+/// BEEFY finality proof of the given chain header.
+pub type BeefyFinalityProofOf<C> =
+	BeefyFinalityProof<HashOf<C>, BlockNumberOf<C>, BeefyValidatorSignatureOf<C>>;
+
+/// Get the id of the best `SourceChain` header known to the bridge BEEFY pallet at the provided
+/// `TargetChain` block.
+pub async fn best_synced_header_id<SourceChain, TargetChain>(
+	source_client: &impl Client<SourceChain>,
+	target_client: &impl Client<TargetChain>,
+	at: HashOf<TargetChain>,
+) -> Result<Option<HeaderIdOf<SourceChain>>, relay_substrate_client::Error>
+where
+	SourceChain: ChainWithBeefy,
+	TargetChain: Chain,
+{
+	let commitments_info: Option<ImportedCommitmentsInfoData<BlockNumberOf<SourceChain>>> =
+		target_client
+			.storage_value(
+				at,
+				bp_beefy::storage_keys::imported_commitments_info_key(
+					SourceChain::WITH_CHAIN_BEEFY_PALLET_NAME,
+				),
+			)
+			.await?;
+	let Some(commitments_info) = commitments_info else { return Ok(None) };
+
+	// the pallet only stores number of the best header, so we read its hash from the source chain
+	let best_block_number = commitments_info.best_block_number;
+	let best_block_hash = source_client.header_hash_by_number(best_block_number).await?;
+	Ok(Some(HeaderId(best_block_number, best_block_hash)))
+}
+
+/// Read the BEEFY validator set that is active at given source chain block.
+async fn validator_set_at<C: ChainWithBeefy>(
+	client: &impl Client<C>,
+	at: HashOf<C>,
+) -> Result<BeefyAuthoritySetOf<C>, relay_substrate_client::Error> {
+	client
+		.state_call::<_, Option<BeefyAuthoritySetOf<C>>>(
+			at,
+			BEEFY_API_VALIDATOR_SET_METHOD.into(),
+			(),
+		)
+		.await?
+		.ok_or_else(|| {
+			relay_substrate_client::Error::Custom(format!(
+				"BEEFY is not enabled at {} block {at:?}",
+				C::NAME,
+			))
+		})
+}
+
+/// Read the BEEFY validator set that has signed the commitment, finalizing given header.
+///
+/// The commitment is normally signed by the set that is active at the finalized header. But
+/// the set is changed by the header that enacts a new set, so we also look at its parent.
+async fn commitment_validator_set<C: ChainWithBeefy>(
+	client: &impl Client<C>,
+	header: &HeaderOf<C>,
+	commitment: &BeefySignedCommitmentOf<C>,
+) -> Result<BeefyAuthoritySetOf<C>, relay_substrate_client::Error> {
+	use sp_runtime::traits::Header;
+
+	let validator_set_id = commitment.commitment.validator_set_id;
+	let validator_set = validator_set_at(client, header.hash()).await?;
+	if validator_set.id() == validator_set_id {
+		return Ok(validator_set)
+	}
+
+	let validator_set = validator_set_at(client, *header.parent_hash()).await?;
+	if validator_set.id() == validator_set_id {
+		return Ok(validator_set)
+	}
+
+	Err(relay_substrate_client::Error::Custom(format!(
+		"Unable to find BEEFY validator set {validator_set_id} that has signed {} header {:?}",
+		C::NAME,
+		header.hash(),
+	)))
+}
+
+/// Substrate -> Substrate BEEFY finality proofs synchronization pipeline.
+#[async_trait]
+pub trait SubstrateBeefySyncPipeline: 'static + Clone + Debug + Send + Sync {
+	/// Headers of this chain are submitted to the `TargetChain`.
+	type SourceChain: ChainWithBeefy;
+	/// Headers of the `SourceChain` are submitted to this chain.
+	type TargetChain: ChainWithTransactions;
+
+	/// How submit commitment call is built?
+	type SubmitCommitmentCallBuilder: SubmitCommitmentCallBuilder<Self>;
+
+	/// Add relay guards if required.
+	async fn start_relay_guards(
+		target_client: &impl Client<Self::TargetChain>,
+		enable_version_guard: bool,
+	) -> relay_substrate_client::Result<()> {
+		if enable_version_guard {
+			relay_substrate_client::guard::abort_on_spec_version_change(
+				target_client.clone(),
+				target_client.simple_runtime_version().await?.spec_version,
+			);
+		}
+		Ok(())
+	}
+}
+
+/// Adapter that allows all `SubstrateBeefySyncPipeline` to act as `FinalitySyncPipeline`.
+#[derive(Clone, Debug)]
+pub struct BeefySyncPipelineAdapter<P: SubstrateBeefySyncPipeline> {
+	_phantom: PhantomData<P>,
+}
+
+impl<P: SubstrateBeefySyncPipeline> FinalityPipeline for BeefySyncPipelineAdapter<P> {
+	const SOURCE_NAME: &'static str = P::SourceChain::NAME;
+	const TARGET_NAME: &'static str = P::TargetChain::NAME;
+
+	type Hash = HashOf<P::SourceChain>;
+	type Number = BlockNumberOf<P::SourceChain>;
+	type FinalityProof = BeefyFinalityProofOf<P::SourceChain>;
+}
+
+impl<P: SubstrateBeefySyncPipeline> FinalitySyncPipeline for BeefySyncPipelineAdapter<P> {
+	type ConsensusLogReader = BeefyConsensusLogReader<BeefyAuthorityIdOf<P::SourceChain>>;
+	type Header = SyncHeader<HeaderOf<P::SourceChain>>;
+}
+
+/// Different ways of building `submit_commitment` calls.
+pub trait SubmitCommitmentCallBuilder<P: SubstrateBeefySyncPipeline> {
+	/// Given the signed commitment, the validator set that has signed it and the MMR leaf of the
+	/// finalized header with its proof, build call of `submit_commitment` function of bridge
+	/// BEEFY module at the target chain.
+	fn build_submit_commitment_call(
+		commitment: BeefySignedCommitmentOf<P::SourceChain>,
+		validator_set: BeefyAuthoritySetOf<P::SourceChain>,
+		mmr_leaf: BeefyMmrLeafOf<P::SourceChain>,
+		mmr_proof: MmrProofOf<P::SourceChain>,
+	) -> CallOf<P::TargetChain>;
+}
+
+/// Building `submit_commitment` call when you have direct access to the target
+/// chain runtime.
+pub struct DirectSubmitBeefyCommitmentCallBuilder<P, R, I> {
+	_phantom: PhantomData<(P, R, I)>,
+}
+
+impl<P, R, I> SubmitCommitmentCallBuilder<P> for DirectSubmitBeefyCommitmentCallBuilder<P, R, I>
+where
+	P: SubstrateBeefySyncPipeline,
+	R: BridgeBeefyConfig<I>,
+	I: 'static,
+	R::BridgedChain: bp_runtime::Chain<
+			BlockNumber = BlockNumberOf<P::SourceChain>,
+			Hash = HashOf<P::SourceChain>,
+		> + bp_beefy::ChainWithBeefy<
+			MmrHash = MmrHashOf<P::SourceChain>,
+			BeefyMmrLeafExtra = BeefyMmrLeafExtraOf<P::SourceChain>,
+			AuthorityId = BeefyAuthorityIdOf<P::SourceChain>,
+		>,
+	CallOf<P::TargetChain>: From<BridgeBeefyCall<R, I>>,
+{
+	fn build_submit_commitment_call(
+		commitment: BeefySignedCommitmentOf<P::SourceChain>,
+		validator_set: BeefyAuthoritySetOf<P::SourceChain>,
+		mmr_leaf: BeefyMmrLeafOf<P::SourceChain>,
+		mmr_proof: MmrProofOf<P::SourceChain>,
+	) -> CallOf<P::TargetChain> {
+		BridgeBeefyCall::<R, I>::submit_commitment {
+			commitment,
+			validator_set,
+			mmr_leaf: Box::new(mmr_leaf),
+			mmr_proof,
+		}
+		.into()
+	}
+}
+
+/// Macro that generates `SubmitCommitmentCallBuilder` implementation for the case when
+/// you only have an access to the mocked version of target chain runtime. In this case you
+/// should provide "name" of the call variant for the bridge BEEFY calls and the "name" of
+/// the variant for the `submit_commitment` call within that first option.
+#[rustfmt::skip]
+#[macro_export]
+macro_rules! generate_submit_commitment_call_builder {
+	($pipeline:ident, $mocked_builder:ident, $bridge_beefy:path, $submit_commitment:path) => {
+		pub struct $mocked_builder;
+
+		impl $crate::beefy::SubmitCommitmentCallBuilder<$pipeline>
+			for $mocked_builder
+		{
+			fn build_submit_commitment_call(
+				commitment: bp_beefy::BeefySignedCommitmentOf<
+					<$pipeline as $crate::beefy::SubstrateBeefySyncPipeline>::SourceChain
+				>,
+				validator_set: bp_beefy::BeefyAuthoritySetOf<
+					<$pipeline as $crate::beefy::SubstrateBeefySyncPipeline>::SourceChain
+				>,
+				mmr_leaf: bp_beefy::BeefyMmrLeafOf<
+					<$pipeline as $crate::beefy::SubstrateBeefySyncPipeline>::SourceChain
+				>,
+				mmr_proof: bp_beefy::MmrProofOf<
+					<$pipeline as $crate::beefy::SubstrateBeefySyncPipeline>::SourceChain
+				>,
+			) -> relay_substrate_client::CallOf<
+				<$pipeline as $crate::beefy::SubstrateBeefySyncPipeline>::TargetChain
+			> {
+				bp_runtime::paste::item! {
+					$bridge_beefy($submit_commitment {
+						commitment,
+						validator_set,
+						mmr_leaf: Box::new(mmr_leaf),
+						mmr_proof,
+					})
+				}
+			}
+		}
+	};
+}
+
+/// Run Substrate-to-Substrate BEEFY finality sync loop.
+pub async fn run<P: SubstrateBeefySyncPipeline>(
+	source_client: impl Client<P::SourceChain>,
+	target_client: impl Client<P::TargetChain>,
+	headers_to_relay: HeadersToRelay,
+	transaction_params: TransactionParams<AccountKeyPairOf<P::TargetChain>>,
+	metrics_params: MetricsParams,
+) -> anyhow::Result<()>
+where
+	AccountIdOf<P::TargetChain>: From<<AccountKeyPairOf<P::TargetChain> as Pair>::Public>,
+{
+	log::info!(
+		target: "bridge",
+		"Starting {} -> {} BEEFY finality proof relay: relaying {:?} headers",
+		P::SourceChain::NAME,
+		P::TargetChain::NAME,
+		headers_to_relay,
+	);
+
+	finality_relay::run(
+		SubstrateBeefySource::<P, _>::new(source_client.clone()),
+		SubstrateBeefyTarget::<P, _, _>::new(
+			source_client,
+			target_client,
+			transaction_params.clone(),
+		),
+		finality_relay::FinalitySyncParams {
+			tick: std::cmp::max(
+				P::SourceChain::AVERAGE_BLOCK_INTERVAL,
+				P::TargetChain::AVERAGE_BLOCK_INTERVAL,
+			),
+			recent_finality_proofs_limit: RECENT_FINALITY_PROOFS_LIMIT,
+			stall_timeout: transaction_stall_timeout(
+				transaction_params.mortality,
+				P::TargetChain::AVERAGE_BLOCK_INTERVAL,
+				relay_utils::STALL_TIMEOUT,
+			),
+			headers_to_relay,
+		},
+		metrics_params,
+		futures::future::pending(),
+	)
+	.await
+	.map_err(|e| anyhow::format_err!("{}", e))
+}
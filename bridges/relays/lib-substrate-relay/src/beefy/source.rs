@@ -0,0 +1,186 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Default generic implementation of BEEFY finality source for basic Substrate client.
+
+use crate::beefy::{
+	BeefyFinalityProof, BeefyFinalityProofOf, BeefySyncPipelineAdapter, SubstrateBeefySyncPipeline,
+};
+
+use async_trait::async_trait;
+use bp_beefy::{BeefyValidatorSignatureOf, BEEFY_ENGINE_ID};
+use codec::Decode;
+use finality_relay::{SourceClient, SourceClientBase};
+use futures::{
+	stream::{unfold, Stream},
+	StreamExt,
+};
+use relay_substrate_client::{
+	BlockNumberOf, BlockWithJustification, ChainWithBeefy, Client, Error, HeaderOf, SyncHeader,
+};
+use relay_utils::relay_loop::Client as RelayClient;
+use sp_consensus_beefy::VersionedFinalityProof;
+use std::{marker::PhantomData, pin::Pin};
+
+/// BEEFY finality proofs stream.
+pub type BeefyFinalityProofsStream<C> = Pin<Box<dyn Stream<Item = BeefyFinalityProofOf<C>> + Send>>;
+
+/// Versioned BEEFY justification of the given chain header.
+type VersionedFinalityProofOf<C> =
+	VersionedFinalityProof<BlockNumberOf<C>, BeefyValidatorSignatureOf<C>>;
+
+/// Subscribe to new BEEFY finality proofs.
+pub async fn beefy_finality_proofs<C: ChainWithBeefy>(
+	client: &impl Client<C>,
+) -> Result<BeefyFinalityProofsStream<C>, Error> {
+	Ok(unfold(
+		(client.subscribe_beefy_finality_justifications().await?, client.clone()),
+		move |(mut subscription, client)| async move {
+			loop {
+				let log_error = |err| {
+					log::error!(
+						target: "bridge",
+						"Failed to read justification target from the {} BEEFY justifications stream: {:?}",
+						C::NAME,
+						err,
+					);
+				};
+
+				let next_justification = subscription.next().await?;
+				let decoded_justification =
+					VersionedFinalityProofOf::<C>::decode(&mut &next_justification[..]);
+				let VersionedFinalityProof::V1(signed_commitment) = match decoded_justification {
+					Ok(j) => j,
+					Err(err) => {
+						log_error(format!("decode failed with error {err:?}"));
+						continue
+					},
+				};
+
+				// BEEFY justifications only contain the number of the finalized header
+				let header_hash = match client
+					.header_hash_by_number(signed_commitment.commitment.block_number)
+					.await
+				{
+					Ok(header_hash) => header_hash,
+					Err(err) => {
+						log_error(format!("failed to read header hash with error {err:?}"));
+						continue
+					},
+				};
+
+				return Some((
+					BeefyFinalityProof { header_hash, signed_commitment },
+					(subscription, client),
+				))
+			}
+		},
+	)
+	.boxed())
+}
+
+/// Read header and its BEEFY justification (if any) from the given Substrate client.
+pub async fn header_and_beefy_finality_proof<C: ChainWithBeefy>(
+	client: &impl Client<C>,
+	number: BlockNumberOf<C>,
+) -> Result<(SyncHeader<HeaderOf<C>>, Option<BeefyFinalityProofOf<C>>), Error> {
+	let header_hash = client.header_hash_by_number(number).await?;
+	let signed_block = client.block_by_hash(header_hash).await?;
+
+	let justification = signed_block
+		.justification(BEEFY_ENGINE_ID)
+		.map(|raw_justification| {
+			VersionedFinalityProofOf::<C>::decode(&mut raw_justification.as_slice())
+		})
+		.transpose()
+		.map_err(Error::ResponseParseFailed)?
+		.map(|VersionedFinalityProof::V1(signed_commitment)| BeefyFinalityProof {
+			header_hash,
+			signed_commitment,
+		});
+
+	Ok((signed_block.header().into(), justification))
+}
+
+/// Substrate node as BEEFY finality source.
+pub struct SubstrateBeefySource<P, SourceClnt> {
+	client: SourceClnt,
+	_phantom: PhantomData<P>,
+}
+
+impl<P: SubstrateBeefySyncPipeline, SourceClnt: Client<P::SourceChain>>
+	SubstrateBeefySource<P, SourceClnt>
+{
+	/// Create new BEEFY finality source using given client.
+	pub fn new(client: SourceClnt) -> Self {
+		SubstrateBeefySource { client, _phantom: Default::default() }
+	}
+
+	/// Returns reference to the underlying RPC client.
+	pub fn client(&self) -> &SourceClnt {
+		&self.client
+	}
+}
+
+impl<P, SourceClnt: Clone> Clone for SubstrateBeefySource<P, SourceClnt> {
+	fn clone(&self) -> Self {
+		SubstrateBeefySource { client: self.client.clone(), _phantom: Default::default() }
+	}
+}
+
+#[async_trait]
+impl<P: SubstrateBeefySyncPipeline, SourceClnt: Client<P::SourceChain>> RelayClient
+	for SubstrateBeefySource<P, SourceClnt>
+{
+	type Error = Error;
+
+	async fn reconnect(&mut self) -> Result<(), Error> {
+		self.client.reconnect().await
+	}
+}
+
+#[async_trait]
+impl<P: SubstrateBeefySyncPipeline, SourceClnt: Client<P::SourceChain>>
+	SourceClientBase<BeefySyncPipelineAdapter<P>> for SubstrateBeefySource<P, SourceClnt>
+{
+	type FinalityProofsStream = BeefyFinalityProofsStream<P::SourceChain>;
+
+	async fn finality_proofs(&self) -> Result<Self::FinalityProofsStream, Error> {
+		beefy_finality_proofs(&self.client).await
+	}
+}
+
+#[async_trait]
+impl<P: SubstrateBeefySyncPipeline, SourceClnt: Client<P::SourceChain>>
+	SourceClient<BeefySyncPipelineAdapter<P>> for SubstrateBeefySource<P, SourceClnt>
+{
+	async fn best_finalized_block_number(&self) -> Result<BlockNumberOf<P::SourceChain>, Error> {
+		// BEEFY finalizes headers that are already finalized by GRANDPA, so there may be no
+		// BEEFY justifications for the best finalized headers yet. Those headers are then
+		// relayed later, when justifications appear
+		self.client.best_finalized_header_number().await
+	}
+
+	async fn header_and_finality_proof(
+		&self,
+		number: BlockNumberOf<P::SourceChain>,
+	) -> Result<
+		(SyncHeader<HeaderOf<P::SourceChain>>, Option<BeefyFinalityProofOf<P::SourceChain>>),
+		Error,
+	> {
+		header_and_beefy_finality_proof(&self.client, number).await
+	}
+}
@@ -0,0 +1,223 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Substrate client as Substrate BEEFY finality proof target.
+
+use crate::{
+	beefy::{
+		best_synced_header_id, commitment_validator_set, BeefyFinalityProof, BeefyFinalityProofOf,
+		BeefySyncPipelineAdapter, SubmitCommitmentCallBuilder, SubstrateBeefySyncPipeline,
+	},
+	TransactionParams,
+};
+
+use async_trait::async_trait;
+use bp_beefy::{BeefyMmrLeafOf, MmrEncodableOpaqueLeaf, MmrError, MmrProofOf};
+use bp_runtime::{BasicOperatingMode, OperatingMode};
+use codec::Decode;
+use finality_relay::TargetClient;
+use relay_substrate_client::{
+	AccountIdOf, AccountKeyPairOf, BlockNumberOf, ChainWithBeefy, Client, Error, HashOf,
+	HeaderIdOf, HeaderOf, SyncHeader, TransactionEra, TransactionTracker, UnsignedTransaction,
+};
+use relay_utils::relay_loop::Client as RelayClient;
+use sp_core::Pair;
+use sp_runtime::traits::Header;
+
+/// Name of the runtime API method that generates MMR proofs.
+const MMR_API_GENERATE_PROOF_METHOD: &str = "MmrApi_generate_proof";
+
+/// Generate the MMR leaf, added by the source chain header with given number, and its proof,
+/// verifiable against the MMR root from the BEEFY commitment, finalizing this header.
+async fn mmr_leaf_and_proof<C: ChainWithBeefy>(
+	client: &impl Client<C>,
+	at: HashOf<C>,
+	block_number: BlockNumberOf<C>,
+) -> Result<(BeefyMmrLeafOf<C>, MmrProofOf<C>), Error> {
+	let (leaves, proof) = client
+		.state_call::<_, Result<(Vec<MmrEncodableOpaqueLeaf>, MmrProofOf<C>), MmrError>>(
+			at,
+			MMR_API_GENERATE_PROOF_METHOD.into(),
+			(vec![block_number], Some(block_number)),
+		)
+		.await?
+		.map_err(|e| {
+			Error::Custom(format!(
+				"Failed to generate MMR proof for {} header {block_number:?}: {e:?}",
+				C::NAME,
+			))
+		})?;
+	let leaf = leaves.into_iter().next().ok_or_else(|| {
+		Error::Custom(format!("Missing MMR leaf of {} header {block_number:?}", C::NAME))
+	})?;
+
+	Ok((BeefyMmrLeafOf::<C>::decode(&mut &leaf.0[..])?, proof))
+}
+
+/// Substrate client as Substrate BEEFY finality target.
+pub struct SubstrateBeefyTarget<P: SubstrateBeefySyncPipeline, SourceClnt, TargetClnt> {
+	source_client: SourceClnt,
+	target_client: TargetClnt,
+	transaction_params: TransactionParams<AccountKeyPairOf<P::TargetChain>>,
+}
+
+impl<
+		P: SubstrateBeefySyncPipeline,
+		SourceClnt: Client<P::SourceChain>,
+		TargetClnt: Client<P::TargetChain>,
+	> SubstrateBeefyTarget<P, SourceClnt, TargetClnt>
+{
+	/// Create new Substrate BEEFY headers target.
+	pub fn new(
+		source_client: SourceClnt,
+		target_client: TargetClnt,
+		transaction_params: TransactionParams<AccountKeyPairOf<P::TargetChain>>,
+	) -> Self {
+		SubstrateBeefyTarget { source_client, target_client, transaction_params }
+	}
+
+	/// Ensure that the bridge BEEFY pallet at target chain is active.
+	pub async fn ensure_pallet_active(&self) -> Result<(), Error> {
+		let pallet_name = P::SourceChain::WITH_CHAIN_BEEFY_PALLET_NAME;
+		let best_header_hash = self.target_client.best_header_hash().await?;
+
+		let is_halted = self
+			.target_client
+			.storage_value::<BasicOperatingMode>(
+				best_header_hash,
+				bp_beefy::storage_keys::pallet_operating_mode_key(pallet_name),
+			)
+			.await?
+			.map(|operating_mode| operating_mode.is_halted())
+			.unwrap_or(false);
+		if is_halted {
+			return Err(Error::BridgePalletIsHalted)
+		}
+
+		let is_initialized = self
+			.target_client
+			.raw_storage_value(
+				best_header_hash,
+				bp_beefy::storage_keys::imported_commitments_info_key(pallet_name),
+			)
+			.await?
+			.is_some();
+		if !is_initialized {
+			return Err(Error::BridgePalletIsNotInitialized)
+		}
+
+		Ok(())
+	}
+}
+
+impl<P: SubstrateBeefySyncPipeline, SourceClnt: Clone, TargetClnt: Clone> Clone
+	for SubstrateBeefyTarget<P, SourceClnt, TargetClnt>
+{
+	fn clone(&self) -> Self {
+		SubstrateBeefyTarget {
+			source_client: self.source_client.clone(),
+			target_client: self.target_client.clone(),
+			transaction_params: self.transaction_params.clone(),
+		}
+	}
+}
+
+#[async_trait]
+impl<
+		P: SubstrateBeefySyncPipeline,
+		SourceClnt: Client<P::SourceChain>,
+		TargetClnt: Client<P::TargetChain>,
+	> RelayClient for SubstrateBeefyTarget<P, SourceClnt, TargetClnt>
+{
+	type Error = Error;
+
+	async fn reconnect(&mut self) -> Result<(), Error> {
+		self.source_client.reconnect().await?;
+		self.target_client.reconnect().await
+	}
+}
+
+#[async_trait]
+impl<
+		P: SubstrateBeefySyncPipeline,
+		SourceClnt: Client<P::SourceChain>,
+		TargetClnt: Client<P::TargetChain>,
+	> TargetClient<BeefySyncPipelineAdapter<P>> for SubstrateBeefyTarget<P, SourceClnt, TargetClnt>
+where
+	AccountIdOf<P::TargetChain>: From<<AccountKeyPairOf<P::TargetChain> as Pair>::Public>,
+{
+	type TransactionTracker = TransactionTracker<P::TargetChain, TargetClnt>;
+
+	async fn best_finalized_source_block_id(&self) -> Result<HeaderIdOf<P::SourceChain>, Error> {
+		// we can't continue to relay finality if target node is out of sync, because
+		// it may have already received (some of) headers that we're going to relay
+		self.target_client.ensure_synced().await?;
+		// we can't relay finality if bridge pallet at target chain is halted
+		self.ensure_pallet_active().await?;
+
+		best_synced_header_id::<P::SourceChain, P::TargetChain>(
+			&self.source_client,
+			&self.target_client,
+			self.target_client.best_header().await?.hash(),
+		)
+		.await?
+		.ok_or(Error::BridgePalletIsNotInitialized)
+	}
+
+	async fn free_source_headers_interval(
+		&self,
+	) -> Result<Option<BlockNumberOf<P::SourceChain>>, Self::Error> {
+		// the bridge BEEFY pallet doesn't support free headers
+		Ok(None)
+	}
+
+	async fn submit_finality_proof(
+		&self,
+		header: SyncHeader<HeaderOf<P::SourceChain>>,
+		proof: BeefyFinalityProofOf<P::SourceChain>,
+		_is_free_execution_expected: bool,
+	) -> Result<Self::TransactionTracker, Error> {
+		let BeefyFinalityProof { header_hash, signed_commitment } = proof;
+
+		// the pallet needs the validator set that has signed the commitment and the MMR leaf of
+		// the finalized header, so let's read them from the source chain
+		let validator_set =
+			commitment_validator_set(&self.source_client, &header, &signed_commitment).await?;
+		let (mmr_leaf, mmr_proof) = mmr_leaf_and_proof(
+			&self.source_client,
+			header_hash,
+			signed_commitment.commitment.block_number,
+		)
+		.await?;
+
+		let mortality = self.transaction_params.mortality;
+		let call = P::SubmitCommitmentCallBuilder::build_submit_commitment_call(
+			signed_commitment,
+			validator_set,
+			mmr_leaf,
+			mmr_proof,
+		);
+		self.target_client
+			.submit_and_watch_signed_extrinsic(
+				&self.transaction_params.signer,
+				move |best_block_id, transaction_nonce| {
+					Ok(UnsignedTransaction::new(call.into(), transaction_nonce)
+						.era(TransactionEra::new(best_block_id, mortality)))
+				},
+			)
+			.await
+	}
+}
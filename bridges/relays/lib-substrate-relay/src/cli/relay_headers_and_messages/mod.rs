@@ -41,7 +41,7 @@ use crate::{
 		bridge::{MessagesCliBridge, MessagesLaneIdOf},
 		DefaultClient, HexLaneId, PrometheusParams,
 	},
-	messages::{MessagesRelayLimits, MessagesRelayParams},
+	messages::{delivery_scheduler::DeliveryScheduler, MessagesRelayLimits, MessagesRelayParams},
 	on_demand::OnDemandRelay,
 	HeadersToRelay, TaggedAccount, TransactionParams,
 };
@@ -161,12 +161,34 @@ where
 		Self { source, target, metrics_params, _phantom_data: Default::default() }
 	}
 
+	/// Returns scheduler of messages delivery transactions, shared by all lanes.
+	///
+	/// There's nothing to schedule if there's a single lane.
+	fn delivery_scheduler(
+		&self,
+		lanes_count: usize,
+	) -> anyhow::Result<Option<DeliveryScheduler<Bridge::MessagesLane, DefaultClient<Target>>>> {
+		if lanes_count < 2 {
+			return Ok(None)
+		}
+
+		DeliveryScheduler::new(
+			self.target.client.clone(),
+			self.target.tx_params.clone(),
+			Target::AVERAGE_BLOCK_INTERVAL / 4,
+			self.metrics_params,
+		)
+		.map(Some)
+		.map_err(|e| anyhow::format_err!("{}", e))
+	}
+
 	/// Returns message relay parameters.
 	fn messages_relay_params(
 		&self,
 		source_to_target_headers_relay: Arc<dyn OnDemandRelay<Source, Target>>,
 		target_to_source_headers_relay: Arc<dyn OnDemandRelay<Target, Source>>,
 		lane_id: MessagesLaneIdOf<Bridge>,
+		delivery_scheduler: Option<DeliveryScheduler<Bridge::MessagesLane, DefaultClient<Target>>>,
 		maybe_limits: Option<MessagesRelayLimits>,
 	) -> MessagesRelayParams<Bridge::MessagesLane, DefaultClient<Source>, DefaultClient<Target>> {
 		MessagesRelayParams {
@@ -177,6 +199,7 @@ where
 			source_to_target_headers_relay: Some(source_to_target_headers_relay),
 			target_to_source_headers_relay: Some(target_to_source_headers_relay),
 			lane_id,
+			delivery_scheduler,
 			limits: maybe_limits,
 			metrics_params: self.metrics_params.clone().disable(),
 		}
@@ -340,6 +363,10 @@ where
 		// Need 2x capacity since we consider both directions for each lane
 		let mut message_relays =
 			Vec::with_capacity(lanes_l2r.len().saturating_add(lanes_r2l.len()));
+		let left_to_right_delivery_scheduler =
+			self.left_to_right().delivery_scheduler(lanes_l2r.len())?;
+		let right_to_left_delivery_scheduler =
+			self.right_to_left().delivery_scheduler(lanes_r2l.len())?;
 		for lane in lanes_l2r {
 			let left_to_right_messages =
 				crate::messages::run::<<Self::L2R as MessagesCliBridge>::MessagesLane, _, _>(
@@ -347,6 +374,7 @@ where
 						left_to_right_on_demand_headers.clone(),
 						right_to_left_on_demand_headers.clone(),
 						lane,
+						left_to_right_delivery_scheduler.clone(),
 						Self::L2R::maybe_messages_limits(),
 					),
 				)
@@ -361,6 +389,7 @@ where
 						right_to_left_on_demand_headers.clone(),
 						left_to_right_on_demand_headers.clone(),
 						lane,
+						right_to_left_delivery_scheduler.clone(),
 						Self::R2L::maybe_messages_limits(),
 					),
 				)
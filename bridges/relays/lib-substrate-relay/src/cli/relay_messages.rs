@@ -18,11 +18,12 @@
 
 use crate::{
 	cli::{bridge::*, chain_schema::*, HexLaneId, PrometheusParams},
-	messages::MessagesRelayParams,
+	messages::{delivery_scheduler::DeliveryScheduler, MessagesRelayParams},
 	TransactionParams,
 };
 
 use async_trait::async_trait;
+use futures::{FutureExt, TryFutureExt};
 use sp_core::Pair;
 use structopt::StructOpt;
 
@@ -38,9 +39,10 @@ use sp_runtime::traits::TryConvert;
 /// Messages relaying params.
 #[derive(StructOpt)]
 pub struct RelayMessagesParams {
-	/// Hex-encoded lane id that should be served by the relay.
+	/// Hex-encoded lane ids that should be served by the relay. If multiple lanes are served,
+	/// lanes with larger queued delivery fees get their messages delivered first.
 	#[structopt(long)]
-	lane: HexLaneId,
+	lane: Vec<HexLaneId>,
 	#[structopt(flatten)]
 	source: SourceConnectionParams,
 	#[structopt(flatten)]
@@ -117,31 +119,76 @@ where
 		let target_client = data.target.into_client::<Self::Target>().await?;
 		let target_sign = data.target_sign.to_keypair::<Self::Target>()?;
 		let target_transactions_mortality = data.target_sign.transactions_mortality()?;
-		let lane_id = HexLaneId::try_convert(data.lane).map_err(|invalid_lane_id| {
-			anyhow::format_err!("Invalid laneId: {:?}!", invalid_lane_id)
-		})?;
+		let lanes = data
+			.lane
+			.into_iter()
+			.map(HexLaneId::try_convert)
+			.collect::<Result<Vec<_>, HexLaneId>>()
+			.map_err(|invalid_lane_id| {
+				anyhow::format_err!("Invalid laneId: {:?}!", invalid_lane_id)
+			})?;
+		if lanes.is_empty() {
+			return Err(anyhow::format_err!("At least one lane must be specified!"))
+		}
 
 		Self::start_relay_guards(&target_client, target_client.can_start_version_guard()).await?;
 
-		crate::messages::run::<Self::MessagesLane, _, _>(MessagesRelayParams {
-			source_client,
-			source_transaction_params: TransactionParams {
-				signer: source_sign,
-				mortality: source_transactions_mortality,
-			},
-			target_client,
-			target_transaction_params: TransactionParams {
-				signer: target_sign,
-				mortality: target_transactions_mortality,
-			},
-			source_to_target_headers_relay: None,
-			target_to_source_headers_relay: None,
-			lane_id,
-			limits: Self::maybe_messages_limits(),
-			metrics_params: data.prometheus_params.into_metrics_params()?,
-		})
-		.await
-		.map_err(|e| anyhow::format_err!("{}", e))
+		let source_transaction_params =
+			TransactionParams { signer: source_sign, mortality: source_transactions_mortality };
+		let target_transaction_params =
+			TransactionParams { signer: target_sign, mortality: target_transactions_mortality };
+		let metrics_params = data.prometheus_params.into_metrics_params()?;
+
+		// a single lane is served exactly as before
+		let is_multi_lane = lanes.len() > 1;
+		let delivery_scheduler = if is_multi_lane {
+			Some(
+				DeliveryScheduler::new(
+					target_client.clone(),
+					target_transaction_params.clone(),
+					Self::Target::AVERAGE_BLOCK_INTERVAL / 4,
+					&metrics_params,
+				)
+				.map_err(|e| anyhow::format_err!("{}", e))?,
+			)
+		} else {
+			None
+		};
+
+		let mut message_relays = Vec::with_capacity(lanes.len());
+		for (lane_index, lane_id) in lanes.into_iter().enumerate() {
+			let message_relay =
+				crate::messages::run::<Self::MessagesLane, _, _>(MessagesRelayParams {
+					source_client: source_client.clone(),
+					source_transaction_params: source_transaction_params.clone(),
+					target_client: target_client.clone(),
+					target_transaction_params: target_transaction_params.clone(),
+					source_to_target_headers_relay: None,
+					target_to_source_headers_relay: None,
+					lane_id,
+					delivery_scheduler: delivery_scheduler.clone(),
+					limits: Self::maybe_messages_limits(),
+					// all lanes are registering their metrics in the same registry, which is
+					// exposed by the first lane
+					metrics_params: if lane_index == 0 {
+						metrics_params.clone()
+					} else {
+						metrics_params.clone().disable()
+					},
+				})
+				.map_err(|e| anyhow::format_err!("{}", e))
+				.boxed();
+			message_relays.push(message_relay);
+		}
+
+		if is_multi_lane {
+			relay_utils::relay_metrics(metrics_params)
+				.expose()
+				.await
+				.map_err(|e| anyhow::format_err!("{}", e))?;
+		}
+
+		futures::future::select_all(message_relays).await.0
 	}
 
 	/// Relay a consequitive range of messages.
@@ -0,0 +1,356 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Scheduling of messages delivery transactions of multiple lanes.
+//!
+//! When the relay serves multiple lanes of the same bridge, every lane loop prepares its own
+//! `receive_messages_proof` transaction. The [`DeliveryScheduler`] may be shared by lane loops to
+//! submit those transactions in the order of lane priorities: deliveries, prepared within a short
+//! window, are submitted starting from the lane with the largest queued message fees.
+//!
+//! The reward for a message is configured by the source chain runtime (the `DeliveryReward` of
+//! `pallet_bridge_relayers::DeliveryConfirmationPaymentsAdapter`) and is split between delivery
+//! and confirmation relayers, using the lane share from
+//! `pallet_bridge_relayers::DeliveryRewardShares`. So queued message fees of the lane are the
+//! number of its queued messages, multiplied by the share of delivery relayers.
+//!
+//! Every transaction delivers messages of a single lane, because the bridge relayers transaction
+//! extension only refunds, boosts priority and rejects obsolete transactions, which deliver
+//! messages of a single lane. Deliveries that have become obsolete while waiting for their turn
+//! (e.g. because some other relayer has delivered the same messages) are dropped.
+
+use crate::{
+	messages::{target::read_latest_received_nonce, SubstrateMessageLane},
+	TransactionParams,
+};
+
+use bp_messages::MessageNonce;
+use futures::{
+	channel::{mpsc, oneshot},
+	StreamExt,
+};
+use messages_relay::Labeled;
+use relay_substrate_client::{
+	AccountIdOf, AccountKeyPairOf, CallOf, Chain, Client, Error as SubstrateError, TransactionEra,
+	TransactionTracker, UnsignedTransaction,
+};
+use relay_utils::metrics::{
+	metric_name, register, CounterVec, GaugeVec, Metric, MetricsParams, Opts, PrometheusError,
+	Registry, F64, U64,
+};
+use sp_core::Pair;
+use sp_runtime::{PerThing, Perbill};
+use std::{cmp::Reverse, ops::RangeInclusive, time::Duration};
+
+/// Returns fees of messages, queued at the source lane.
+///
+/// The fees are measured in billionths of the reward for a single message, configured at the
+/// source chain.
+pub fn queued_fees(queued_messages: MessageNonce, delivery_reward_share: Perbill) -> u128 {
+	u128::from(queued_messages).saturating_mul(delivery_reward_share.deconstruct().into())
+}
+
+/// Messages delivery of a single lane that is waiting to be submitted.
+struct PendingDelivery<LaneId, Payload> {
+	/// Lane of delivered messages.
+	lane_id: LaneId,
+	/// Nonces of delivered messages.
+	nonces: RangeInclusive<MessageNonce>,
+	/// Fees of messages that are queued at the source lane, including delivered ones.
+	queued_fees: u128,
+	/// Delivery call and the channel to send its transaction tracker over.
+	payload: Payload,
+}
+
+/// Pending delivery of the given pipeline.
+type PendingDeliveryOf<P, TargetClnt> = PendingDelivery<
+	<P as SubstrateMessageLane>::LaneId,
+	(
+		CallOf<<P as SubstrateMessageLane>::TargetChain>,
+		oneshot::Sender<
+			Result<
+				TransactionTracker<<P as SubstrateMessageLane>::TargetChain, TargetClnt>,
+				SubstrateError,
+			>,
+		>,
+	),
+>;
+
+/// Schedules messages delivery transactions of multiple lanes.
+///
+/// Cloned schedulers share the same queue.
+pub struct DeliveryScheduler<P: SubstrateMessageLane, TargetClnt> {
+	sender: mpsc::UnboundedSender<PendingDeliveryOf<P, TargetClnt>>,
+}
+
+impl<P: SubstrateMessageLane, TargetClnt> Clone for DeliveryScheduler<P, TargetClnt> {
+	fn clone(&self) -> Self {
+		Self { sender: self.sender.clone() }
+	}
+}
+
+impl<P: SubstrateMessageLane, TargetClnt: Client<P::TargetChain>> DeliveryScheduler<P, TargetClnt> {
+	/// Start the scheduler.
+	///
+	/// The scheduler waits for `schedule_window` after the first delivery is submitted, so that
+	/// other lanes could compete for the first transaction. Scheduler metrics are registered in
+	/// the `metrics_params` registry.
+	pub fn new(
+		target_client: TargetClnt,
+		transaction_params: TransactionParams<AccountKeyPairOf<P::TargetChain>>,
+		schedule_window: Duration,
+		metrics_params: &MetricsParams,
+	) -> Result<Self, PrometheusError>
+	where
+		AccountIdOf<P::TargetChain>: From<<AccountKeyPairOf<P::TargetChain> as Pair>::Public>,
+	{
+		let metrics = DeliverySchedulerMetrics::new(Some(&format!(
+			"{}_to_{}_DeliveryScheduler",
+			P::SourceChain::NAME,
+			P::TargetChain::NAME,
+		)))?;
+		metrics.register(&metrics_params.registry)?;
+
+		let (sender, receiver) = mpsc::unbounded();
+		async_std::task::spawn(run_scheduler::<P, _>(
+			target_client,
+			transaction_params,
+			schedule_window,
+			metrics,
+			receiver,
+		));
+		Ok(Self { sender })
+	}
+
+	/// Submit messages delivery call to be scheduled.
+	pub async fn submit(
+		&self,
+		lane_id: P::LaneId,
+		nonces: RangeInclusive<MessageNonce>,
+		queued_fees: u128,
+		call: CallOf<P::TargetChain>,
+	) -> Result<TransactionTracker<P::TargetChain, TargetClnt>, SubstrateError> {
+		let (tx_tracker_sender, tx_tracker_receiver) = oneshot::channel();
+		self.sender
+			.unbounded_send(PendingDelivery {
+				lane_id,
+				nonces,
+				queued_fees,
+				payload: (call, tx_tracker_sender),
+			})
+			.map_err(|_| scheduler_stopped_error::<P>())?;
+		tx_tracker_receiver.await.map_err(|_| scheduler_stopped_error::<P>())?
+	}
+}
+
+/// Error that is returned when the scheduler has unexpectedly stopped.
+fn scheduler_stopped_error<P: SubstrateMessageLane>() -> SubstrateError {
+	SubstrateError::Custom(format!(
+		"{} -> {} messages delivery scheduler has stopped",
+		P::SourceChain::NAME,
+		P::TargetChain::NAME,
+	))
+}
+
+/// Scheduler background loop.
+async fn run_scheduler<P: SubstrateMessageLane, TargetClnt: Client<P::TargetChain>>(
+	target_client: TargetClnt,
+	transaction_params: TransactionParams<AccountKeyPairOf<P::TargetChain>>,
+	schedule_window: Duration,
+	metrics: DeliverySchedulerMetrics,
+	mut receiver: mpsc::UnboundedReceiver<PendingDeliveryOf<P, TargetClnt>>,
+) where
+	AccountIdOf<P::TargetChain>: From<<AccountKeyPairOf<P::TargetChain> as Pair>::Public>,
+{
+	let mut pending = Vec::new();
+	loop {
+		// wait for the first delivery, then give other lanes some time to join the queue
+		match receiver.next().await {
+			Some(delivery) => pending.push(delivery),
+			None => return,
+		}
+		async_std::task::sleep(schedule_window).await;
+		while let Ok(Some(delivery)) = receiver.try_next() {
+			pending.push(delivery);
+		}
+
+		prioritize(&mut pending);
+		for delivery in pending.drain(..) {
+			let (call, tx_tracker_sender) = delivery.payload;
+			let lane_label = delivery.lane_id.label();
+			metrics.update_queued_fees(&lane_label, delivery.queued_fees);
+
+			let result =
+				match is_obsolete::<P>(&target_client, delivery.lane_id, &delivery.nonces).await {
+					Ok(false) => {
+						log::debug!(
+							target: "bridge",
+							"Submitting {} -> {} messages {:?} delivery transaction for lane {:?}",
+							P::SourceChain::NAME,
+							P::TargetChain::NAME,
+							delivery.nonces,
+							delivery.lane_id,
+						);
+
+						let mortality = transaction_params.mortality;
+						target_client
+							.submit_and_watch_signed_extrinsic(
+								&transaction_params.signer,
+								move |best_block_id, transaction_nonce| {
+									Ok(UnsignedTransaction::new(call.into(), transaction_nonce)
+										.era(TransactionEra::new(best_block_id, mortality)))
+								},
+							)
+							.await
+					},
+					Ok(true) => {
+						log::debug!(
+							target: "bridge",
+							"Dropping obsolete {} -> {} messages {:?} delivery transaction for lane {:?}",
+							P::SourceChain::NAME,
+							P::TargetChain::NAME,
+							delivery.nonces,
+							delivery.lane_id,
+						);
+
+						metrics.note_obsolete_delivery(&lane_label);
+						Err(SubstrateError::Custom(format!(
+							"Messages {:?} of lane {:?} are already delivered to {}",
+							delivery.nonces,
+							delivery.lane_id,
+							P::TargetChain::NAME,
+						)))
+					},
+					Err(e) => Err(e),
+				};
+			let _ = tx_tracker_sender.send(result);
+		}
+	}
+}
+
+/// Returns true if some of `nonces` are already delivered to the target chain.
+///
+/// The bridge runtime rejects such transactions.
+async fn is_obsolete<P: SubstrateMessageLane>(
+	target_client: &impl Client<P::TargetChain>,
+	lane_id: P::LaneId,
+	nonces: &RangeInclusive<MessageNonce>,
+) -> Result<bool, SubstrateError> {
+	let best_target_header_hash = target_client.best_header_hash().await?;
+	let latest_received_nonce =
+		read_latest_received_nonce::<P>(target_client, lane_id, best_target_header_hash).await?;
+	Ok(latest_received_nonce >= *nonces.start())
+}
+
+/// Order deliveries by their priority.
+///
+/// Deliveries of lanes with larger queued fees go first. The sort is stable, so deliveries with
+/// the same fees keep the submission order.
+fn prioritize<LaneId, Payload>(pending: &mut [PendingDelivery<LaneId, Payload>]) {
+	pending.sort_by_key(|delivery| Reverse(delivery.queued_fees));
+}
+
+/// Delivery scheduler metrics.
+///
+/// Cloning only clones references.
+#[derive(Clone)]
+struct DeliverySchedulerMetrics {
+	/// Queued message fees of lanes, in rewards for delivering a single message.
+	queued_fees: GaugeVec<F64>,
+	/// Number of dropped obsolete delivery transactions of lanes.
+	obsolete_deliveries: CounterVec<U64>,
+}
+
+impl DeliverySchedulerMetrics {
+	/// Create delivery scheduler metrics.
+	fn new(prefix: Option<&str>) -> Result<Self, PrometheusError> {
+		Ok(DeliverySchedulerMetrics {
+			queued_fees: GaugeVec::new(
+				Opts::new(
+					metric_name(prefix, "queued_fees"),
+					"Fees of messages queued at the source lane, in rewards for delivering a single message",
+				),
+				&["lane"],
+			)?,
+			obsolete_deliveries: CounterVec::new(
+				Opts::new(
+					metric_name(prefix, "obsolete_deliveries"),
+					"Number of dropped obsolete messages delivery transactions",
+				),
+				&["lane"],
+			)?,
+		})
+	}
+
+	/// Update queued message fees of the lane.
+	fn update_queued_fees(&self, lane: &str, queued_fees: u128) {
+		self.queued_fees
+			.with_label_values(&[lane])
+			.set(queued_fees as f64 / Perbill::ACCURACY as f64);
+	}
+
+	/// Note that the obsolete delivery transaction of the lane has been dropped.
+	fn note_obsolete_delivery(&self, lane: &str) {
+		self.obsolete_deliveries.with_label_values(&[lane]).inc();
+	}
+}
+
+impl Metric for DeliverySchedulerMetrics {
+	fn register(&self, registry: &Registry) -> Result<(), PrometheusError> {
+		register(self.queued_fees.clone(), registry)?;
+		register(self.obsolete_deliveries.clone(), registry)?;
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn delivery(lane_id: u32, queued_fees: u128) -> PendingDelivery<u32, ()> {
+		PendingDelivery { lane_id, nonces: 1..=1, queued_fees, payload: () }
+	}
+
+	fn lanes(deliveries: &[PendingDelivery<u32, ()>]) -> Vec<u32> {
+		deliveries.iter().map(|delivery| delivery.lane_id).collect()
+	}
+
+	#[test]
+	fn queued_fees_depend_on_delivery_reward_share() {
+		assert_eq!(queued_fees(10, Perbill::one()), 10 * Perbill::ACCURACY as u128);
+		assert_eq!(queued_fees(10, Perbill::from_percent(50)), 5 * Perbill::ACCURACY as u128);
+		// lanes without delivery reward are never preferred
+		assert_eq!(queued_fees(10, Perbill::zero()), 0);
+		// a few messages with the full reward are worth more than many messages with a small share
+		assert!(queued_fees(3, Perbill::one()) > queued_fees(20, Perbill::from_percent(10)));
+	}
+
+	#[test]
+	fn prioritize_prefers_lanes_with_larger_queued_fees() {
+		let mut pending = vec![delivery(1, 5), delivery(2, 50), delivery(3, 5), delivery(4, 20)];
+		prioritize(&mut pending);
+		assert_eq!(lanes(&pending), vec![2, 4, 1, 3]);
+	}
+
+	#[test]
+	fn delivery_scheduler_metrics_are_valid() {
+		let metrics =
+			DeliverySchedulerMetrics::new(Some("Source_to_Target_DeliveryScheduler")).unwrap();
+		metrics.register(&Registry::new()).unwrap();
+		metrics.update_queued_fees("lane", queued_fees(3, Perbill::from_percent(50)));
+		assert_eq!(metrics.queued_fees.with_label_values(&["lane"]).get(), 1.5);
+	}
+}
@@ -18,6 +18,7 @@
 
 use crate::{
 	messages::{
+		delivery_scheduler::DeliveryScheduler,
 		source::{SubstrateMessagesProof, SubstrateMessagesSource},
 		target::{SubstrateMessagesDeliveryProof, SubstrateMessagesTarget},
 	},
@@ -47,6 +48,7 @@ use sp_core::Pair;
 use sp_runtime::traits::Zero;
 use std::{fmt::Debug, marker::PhantomData, ops::RangeInclusive};
 
+pub mod delivery_scheduler;
 pub mod metrics;
 pub mod source;
 pub mod target;
@@ -122,6 +124,8 @@ pub struct MessagesRelayParams<P: SubstrateMessageLane, SourceClnt, TargetClnt>
 		Option<Arc<dyn OnDemandRelay<P::TargetChain, P::SourceChain>>>,
 	/// Identifier of lane that needs to be served.
 	pub lane_id: P::LaneId,
+	/// Optional scheduler of messages delivery transactions, shared with other lanes.
+	pub delivery_scheduler: Option<DeliveryScheduler<P, TargetClnt>>,
 	/// Messages relay limits. If not provided, the relay tries to determine it automatically,
 	/// using `TransactionPayment` pallet runtime API.
 	pub limits: Option<MessagesRelayLimits>,
@@ -286,7 +290,8 @@ where
 			relayer_id_at_source,
 			Some(params.target_transaction_params),
 			params.source_to_target_headers_relay,
-		),
+		)
+		.with_delivery_scheduler(params.delivery_scheduler),
 		{
 			GlobalMetrics::new()?.register_and_spawn(&params.metrics_params.registry)?;
 			params.metrics_params
@@ -37,7 +37,8 @@ use bp_messages::{
 	ChainWithMessages as _, InboundMessageDetails, MessageNonce, MessagePayload,
 	MessagesOperatingMode, OutboundMessageDetails,
 };
-use bp_runtime::{BasicOperatingMode, HeaderIdProvider, RangeInclusiveExt};
+use bp_relayers::DeliveryRewardSharesKeyProvider;
+use bp_runtime::{BasicOperatingMode, HeaderIdProvider, RangeInclusiveExt, StorageMapKeyProvider};
 use codec::{Decode, Encode};
 use frame_support::weights::Weight;
 use messages_relay::{
@@ -55,6 +56,7 @@ use relay_substrate_client::{
 };
 use relay_utils::relay_loop::Client as RelayClient;
 use sp_core::Pair;
+use sp_runtime::Perbill;
 use std::ops::RangeInclusive;
 
 /// Intermediate message proof returned by the source Substrate node. Includes everything
@@ -75,6 +77,53 @@ struct LegacyOutboundLaneData {
 	latest_generated_nonce: MessageNonce,
 }
 
+/// Read outbound lane state from the on-chain storage at given block.
+async fn read_outbound_lane_data<P: SubstrateMessageLane>(
+	source_client: &impl Client<P::SourceChain>,
+	lane_id: P::LaneId,
+	id: SourceHeaderIdOf<MessageLaneAdapter<P>>,
+) -> Result<Option<LegacyOutboundLaneData>, SubstrateError> {
+	source_client
+		.storage_value(
+			id.hash(),
+			outbound_lane_data_key(P::TargetChain::WITH_CHAIN_MESSAGES_PALLET_NAME, &lane_id),
+		)
+		.await
+}
+
+/// Read nonce of the latest message, generated at the source lane at given block.
+pub(crate) async fn read_latest_generated_nonce<P: SubstrateMessageLane>(
+	source_client: &impl Client<P::SourceChain>,
+	lane_id: P::LaneId,
+	id: SourceHeaderIdOf<MessageLaneAdapter<P>>,
+) -> Result<MessageNonce, SubstrateError> {
+	// lane data missing from the storage is fine until first message is sent
+	Ok(read_outbound_lane_data::<P>(source_client, lane_id, id)
+		.await?
+		.map(|data| data.latest_generated_nonce)
+		.unwrap_or(0))
+}
+
+/// Read share of the delivery reward, paid to relayers that are delivering messages over the lane.
+pub(crate) async fn read_delivery_reward_share<P: SubstrateMessageLane>(
+	source_client: &impl Client<P::SourceChain>,
+	lane_id: P::LaneId,
+	id: SourceHeaderIdOf<MessageLaneAdapter<P>>,
+) -> Result<Perbill, SubstrateError> {
+	// without relayers pallet or a lane entry, the whole reward is paid to delivery relayers
+	let relayers_pallet_name = match P::TargetChain::WITH_CHAIN_RELAYERS_PALLET_NAME {
+		Some(relayers_pallet_name) => relayers_pallet_name,
+		None => return Ok(Perbill::one()),
+	};
+	Ok(source_client
+		.storage_value(
+			id.hash(),
+			DeliveryRewardSharesKeyProvider::<P::LaneId>::final_key(relayers_pallet_name, &lane_id),
+		)
+		.await?
+		.unwrap_or_else(Perbill::one))
+}
+
 /// Substrate client as Substrate messages source.
 pub struct SubstrateMessagesSource<P: SubstrateMessageLane, SourceClnt, TargetClnt> {
 	source_client: SourceClnt,
@@ -111,15 +160,7 @@ impl<P: SubstrateMessageLane, SourceClnt: Client<P::SourceChain>, TargetClnt>
 		&self,
 		id: SourceHeaderIdOf<MessageLaneAdapter<P>>,
 	) -> Result<Option<LegacyOutboundLaneData>, SubstrateError> {
-		self.source_client
-			.storage_value(
-				id.hash(),
-				outbound_lane_data_key(
-					P::TargetChain::WITH_CHAIN_MESSAGES_PALLET_NAME,
-					&self.lane_id,
-				),
-			)
-			.await
+		read_outbound_lane_data::<P>(&self.source_client, self.lane_id, id).await
 	}
 
 	/// Ensure that the messages pallet at source chain is active.
@@ -20,9 +20,10 @@
 
 use crate::{
 	messages::{
+		delivery_scheduler::{queued_fees, DeliveryScheduler},
 		source::{
 			ensure_messages_pallet_active, read_client_state_from_both_chains,
-			SubstrateMessagesProof,
+			read_delivery_reward_share, read_latest_generated_nonce, SubstrateMessagesProof,
 		},
 		BatchProofTransaction, MessageLaneAdapter, ReceiveMessagesProofCallBuilder,
 		SubstrateMessageLane,
@@ -95,6 +96,33 @@ impl<RelayerId> From<LegacyInboundLaneData<RelayerId>> for UnrewardedRelayersSta
 	}
 }
 
+/// Read inbound lane state from the on-chain storage at given block.
+async fn read_inbound_lane_data<P: SubstrateMessageLane>(
+	target_client: &impl Client<P::TargetChain>,
+	lane_id: P::LaneId,
+	at: HashOf<P::TargetChain>,
+) -> Result<Option<LegacyInboundLaneData<AccountIdOf<P::SourceChain>>>, SubstrateError> {
+	target_client
+		.storage_value(
+			at,
+			inbound_lane_data_key(P::SourceChain::WITH_CHAIN_MESSAGES_PALLET_NAME, &lane_id),
+		)
+		.await
+}
+
+/// Read nonce of the latest message, received by the target lane at given block.
+pub(crate) async fn read_latest_received_nonce<P: SubstrateMessageLane>(
+	target_client: &impl Client<P::TargetChain>,
+	lane_id: P::LaneId,
+	at: HashOf<P::TargetChain>,
+) -> Result<MessageNonce, SubstrateError> {
+	// lane data missing from the storage is fine until first message is received
+	Ok(read_inbound_lane_data::<P>(target_client, lane_id, at)
+		.await?
+		.map(|data| data.last_delivered_nonce())
+		.unwrap_or(0))
+}
+
 /// Substrate client as Substrate messages target.
 pub struct SubstrateMessagesTarget<P: SubstrateMessageLane, SourceClnt, TargetClnt> {
 	target_client: TargetClnt,
@@ -103,6 +131,7 @@ pub struct SubstrateMessagesTarget<P: SubstrateMessageLane, SourceClnt, TargetCl
 	relayer_id_at_source: AccountIdOf<P::SourceChain>,
 	transaction_params: Option<TransactionParams<AccountKeyPairOf<P::TargetChain>>>,
 	source_to_target_headers_relay: Option<Arc<dyn OnDemandRelay<P::SourceChain, P::TargetChain>>>,
+	delivery_scheduler: Option<DeliveryScheduler<P, TargetClnt>>,
 }
 
 impl<P, SourceClnt, TargetClnt> SubstrateMessagesTarget<P, SourceClnt, TargetClnt>
@@ -128,23 +157,25 @@ where
 			relayer_id_at_source,
 			transaction_params,
 			source_to_target_headers_relay,
+			delivery_scheduler: None,
 		}
 	}
 
+	/// Deliver messages using given scheduler, shared with other lanes.
+	pub fn with_delivery_scheduler(
+		mut self,
+		delivery_scheduler: Option<DeliveryScheduler<P, TargetClnt>>,
+	) -> Self {
+		self.delivery_scheduler = delivery_scheduler;
+		self
+	}
+
 	/// Read inbound lane state from the on-chain storage at given block.
 	async fn inbound_lane_data(
 		&self,
 		id: TargetHeaderIdOf<MessageLaneAdapter<P>>,
 	) -> Result<Option<LegacyInboundLaneData<AccountIdOf<P::SourceChain>>>, SubstrateError> {
-		self.target_client
-			.storage_value(
-				id.hash(),
-				inbound_lane_data_key(
-					P::SourceChain::WITH_CHAIN_MESSAGES_PALLET_NAME,
-					&self.lane_id,
-				),
-			)
-			.await
+		read_inbound_lane_data::<P>(&self.target_client, self.lane_id, id.hash()).await
 	}
 
 	/// Ensure that the messages pallet at target chain is active.
@@ -165,6 +196,7 @@ impl<P: SubstrateMessageLane, SourceClnt: Clone, TargetClnt: Clone> Clone
 			relayer_id_at_source: self.relayer_id_at_source.clone(),
 			transaction_params: self.transaction_params.clone(),
 			source_to_target_headers_relay: self.source_to_target_headers_relay.clone(),
+			delivery_scheduler: self.delivery_scheduler.clone(),
 		}
 	}
 }
@@ -291,7 +323,7 @@ where
 	async fn submit_messages_proof(
 		&self,
 		maybe_batch_tx: Option<Self::BatchTransaction>,
-		_generated_at_header: SourceHeaderIdOf<MessageLaneAdapter<P>>,
+		generated_at_header: SourceHeaderIdOf<MessageLaneAdapter<P>>,
 		nonces: RangeInclusive<MessageNonce>,
 		proof: <MessageLaneAdapter<P> as MessageLane>::MessagesProof,
 	) -> Result<NoncesSubmitArtifacts<Self::TransactionTracker>, SubstrateError> {
@@ -301,9 +333,36 @@ where
 			proof,
 			maybe_batch_tx.is_none(),
 		);
-		let final_call = match maybe_batch_tx {
-			Some(batch_tx) => batch_tx.append_call_and_build(messages_proof_call),
-			None => messages_proof_call,
+		let final_call = match (maybe_batch_tx, self.delivery_scheduler.as_ref()) {
+			(Some(batch_tx), _) => batch_tx.append_call_and_build(messages_proof_call),
+			// transactions that also prove source headers are submitted right away, because
+			// deliveries of all lanes are waiting for those headers
+			(None, Some(delivery_scheduler)) => {
+				let latest_generated_nonce = read_latest_generated_nonce::<P>(
+					&self.source_client,
+					self.lane_id,
+					generated_at_header,
+				)
+				.await?;
+				let delivery_reward_share = read_delivery_reward_share::<P>(
+					&self.source_client,
+					self.lane_id,
+					generated_at_header,
+				)
+				.await?;
+				let queued_messages =
+					latest_generated_nonce.saturating_sub(*nonces.start()).saturating_add(1);
+				let tx_tracker = delivery_scheduler
+					.submit(
+						self.lane_id,
+						nonces.clone(),
+						queued_fees(queued_messages, delivery_reward_share),
+						messages_proof_call,
+					)
+					.await?;
+				return Ok(NoncesSubmitArtifacts { nonces, tx_tracker })
+			},
+			(None, None) => messages_proof_call,
 		};
 
 		let transaction_params = self.transaction_params.clone().map(Ok).unwrap_or_else(|| {
@@ -0,0 +1,83 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: 2023 Snowfork <hello@snowfork.com>
+//! Sync committees with BLS keys controlled by the harness.
+use crate::{SC_BITS_SIZE, SC_SIZE};
+use milagro_bls::{PublicKey as BlsPublicKey, SecretKey, Signature as BlsSignature};
+use snowbridge_beacon_primitives::{PublicKey, Signature, SyncAggregate, SyncCommittee};
+use sp_core::H256;
+
+/// A sync committee whose secret keys are known.
+///
+/// The secret key of every member is a small scalar derived from the chain seed, the sync
+/// committee period and the position of the member. This is insecure, but allows signing with
+/// the aggregate secret key of the participants, which is much faster than aggregating 512
+/// signatures and yields the same aggregate signature.
+#[derive(Clone, Debug)]
+pub struct Committee {
+	first_secret: u128,
+	committee: SyncCommittee<SC_SIZE>,
+	root: H256,
+}
+
+impl Committee {
+	/// Generates the sync committee of `period` for the chain with the given `seed`.
+	pub fn generate(seed: u32, period: u64) -> Self {
+		let first_secret = 1 + ((seed as u128) << 64) + (period as u128) * SC_SIZE as u128;
+
+		let mut committee = SyncCommittee::<SC_SIZE>::default();
+		for (index, pubkey) in committee.pubkeys.iter_mut().enumerate() {
+			*pubkey = public_key(first_secret + index as u128);
+		}
+		let aggregate_secret = (0..SC_SIZE as u128).map(|index| first_secret + index).sum();
+		committee.aggregate_pubkey = public_key(aggregate_secret);
+
+		let root = committee.hash_tree_root().expect("committee is well-formed; qed");
+		Self { first_secret, committee, root }
+	}
+
+	/// The sync committee, as it is stored in the beacon state.
+	pub fn sync_committee(&self) -> &SyncCommittee<SC_SIZE> {
+		&self.committee
+	}
+
+	/// The hash tree root of the sync committee.
+	pub fn root(&self) -> H256 {
+		self.root
+	}
+
+	/// Signs `signing_root` by the members of the committee for which `participates` returns
+	/// true.
+	pub fn sign(
+		&self,
+		signing_root: H256,
+		participates: impl Fn(usize) -> bool,
+	) -> SyncAggregate<SC_SIZE, SC_BITS_SIZE> {
+		let mut sync_committee_bits = [0u8; SC_BITS_SIZE];
+		let mut aggregate_secret = 0u128;
+		for index in (0..SC_SIZE).filter(|index| participates(*index)) {
+			sync_committee_bits[index / 8] |= 1 << (index % 8);
+			aggregate_secret += self.first_secret + index as u128;
+		}
+
+		// an empty aggregate can't be signed, so give it a signature no one can verify
+		let sync_committee_signature = if aggregate_secret == 0 {
+			Signature::default()
+		} else {
+			let signature =
+				BlsSignature::new(signing_root.as_bytes(), &secret_key(aggregate_secret));
+			Signature(signature.as_bytes())
+		};
+
+		SyncAggregate { sync_committee_bits, sync_committee_signature }
+	}
+}
+
+fn secret_key(scalar: u128) -> SecretKey {
+	let mut bytes = [0u8; 32];
+	bytes[16..].copy_from_slice(&scalar.to_be_bytes());
+	SecretKey::from_bytes(&bytes).expect("scalar is less than the curve order; qed")
+}
+
+fn public_key(scalar: u128) -> PublicKey {
+	PublicKey(BlsPublicKey::from_secret_key(&secret_key(scalar)).as_bytes())
+}
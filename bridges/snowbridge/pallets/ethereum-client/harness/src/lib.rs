@@ -0,0 +1,598 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: 2023 Snowfork <hello@snowfork.com>
+//! Ethereum Beacon Client Test Harness
+//!
+//! Simulates a beacon chain without a beacon node, so that the Ethereum beacon client and its
+//! users can be tested against arbitrary valid chains instead of static fixtures.
+//!
+//! The [`BeaconChain`] produces blocks at the requested slots (the slots in between are missed),
+//! rotates its sync committees every sync committee period and finalizes blocks two epochs
+//! behind the head. The BLS keys of all sync committees are controlled by the harness, so it can
+//! generate signed light client updates for any attested block, with any participation.
+//!
+//! Only the parts of the beacon state and block body which are proven to the light client are
+//! simulated, the remaining fields are left empty.
+mod committee;
+mod merkle;
+mod receipt;
+
+pub use committee::Committee;
+pub use merkle::MerkleTree;
+pub use receipt::ReceiptsTrie;
+
+use merkle::{hash_pair, uint64_leaf};
+use snowbridge_beacon_primitives::{
+	types::deneb, AncestryProof, BeaconHeader, ExecutionProof, ForkData, ForkVersion, ForkVersions,
+	NextSyncCommitteeUpdate, SigningData, VersionedExecutionPayloadHeader,
+};
+use snowbridge_core::inbound::{Log, Proof};
+use sp_core::{H256, U256};
+use std::collections::BTreeMap;
+
+pub const SC_SIZE: usize = 512;
+pub const SC_BITS_SIZE: usize = 64;
+pub const SLOTS_PER_EPOCH: u64 = 32;
+pub const EPOCHS_PER_SYNC_COMMITTEE_PERIOD: u64 = 256;
+pub const SLOTS_PER_HISTORICAL_ROOT: usize = 8192;
+
+pub type CheckpointUpdate = snowbridge_beacon_primitives::CheckpointUpdate<SC_SIZE>;
+pub type Update = snowbridge_beacon_primitives::Update<SC_SIZE, SC_BITS_SIZE>;
+pub type SyncAggregate = snowbridge_beacon_primitives::SyncAggregate<SC_SIZE, SC_BITS_SIZE>;
+
+/// DomainType('0x07000000')
+const DOMAIN_SYNC_COMMITTEE: [u8; 4] = [7, 0, 0, 0];
+
+/// Leaves of the `BeaconState` container (28 fields, padded to 32).
+const STATE_WIDTH: usize = 32;
+const STATE_GENESIS_VALIDATORS_ROOT: usize = 1;
+const STATE_SLOT: usize = 2;
+const STATE_BLOCK_ROOTS: usize = 5;
+const STATE_FINALIZED_CHECKPOINT: usize = 20;
+const STATE_CURRENT_SYNC_COMMITTEE: usize = 22;
+const STATE_NEXT_SYNC_COMMITTEE: usize = 23;
+const STATE_LATEST_EXECUTION_PAYLOAD_HEADER: usize = 24;
+
+/// Leaves of the `BeaconBlockBody` container (12 fields, padded to 16).
+const BODY_WIDTH: usize = 16;
+const BODY_GRAFFITI: usize = 2;
+const BODY_EXECUTION_PAYLOAD: usize = 9;
+
+/// Compute the sync committee period in which a slot is contained.
+pub fn compute_period(slot: u64) -> u64 {
+	slot / SLOTS_PER_EPOCH / EPOCHS_PER_SYNC_COMMITTEE_PERIOD
+}
+
+/// Compute the epoch in which a slot is contained.
+pub fn compute_epoch(slot: u64) -> u64 {
+	slot / SLOTS_PER_EPOCH
+}
+
+/// Parameters of a simulated beacon chain.
+#[derive(Clone, Debug)]
+pub struct ChainConfig {
+	/// Seed of the BLS keys of the sync committees.
+	pub seed: u32,
+	/// Hash tree root of the genesis validators, which identifies the chain.
+	pub genesis_validators_root: H256,
+	/// Fork versions used for the signing domain, which must match the light client config.
+	pub fork_versions: ForkVersions,
+}
+
+/// A block of the simulated chain.
+#[derive(Clone, Debug)]
+pub struct Block {
+	/// The beacon block header.
+	pub header: BeaconHeader,
+	/// The hash tree root of `header`.
+	pub root: H256,
+	/// Leaves of the post-state of the block.
+	pub state: MerkleTree,
+	/// Leaves of the block body.
+	pub body: MerkleTree,
+	/// The execution payload header of the block.
+	pub execution_header: deneb::ExecutionPayloadHeader,
+	/// The receipts trie of the execution payload.
+	pub receipts: ReceiptsTrie,
+	/// Slot of the block finalized in the post-state of the block.
+	pub finalized_slot: u64,
+}
+
+impl Block {
+	/// The `block_roots` root in the post-state of the block.
+	pub fn block_roots_root(&self) -> H256 {
+		self.state.leaf(STATE_BLOCK_ROOTS)
+	}
+}
+
+/// A simulated beacon chain.
+///
+/// Cloning the chain and producing different blocks on the clones (see [`BeaconChain::fork`])
+/// simulates competing forks.
+#[derive(Clone, Debug)]
+pub struct BeaconChain {
+	config: ChainConfig,
+	/// Identifier of the fork, mixed into the produced blocks so that forks diverge.
+	fork_id: u64,
+	committees: BTreeMap<u64, Committee>,
+	blocks: BTreeMap<u64, Block>,
+	/// The `block_roots` of the state at the head slot.
+	block_roots: MerkleTree,
+}
+
+impl BeaconChain {
+	/// Starts a chain with a genesis block at slot 0.
+	pub fn new(config: ChainConfig) -> Self {
+		let mut chain = Self {
+			config,
+			fork_id: 0,
+			committees: BTreeMap::new(),
+			blocks: BTreeMap::new(),
+			block_roots: MerkleTree::new(&[H256::zero(); SLOTS_PER_HISTORICAL_ROOT]),
+		};
+		chain.produce_block(0);
+		chain
+	}
+
+	/// Returns a copy of the chain which produces blocks that differ from the blocks of this
+	/// chain. Blocks produced before the fork are shared.
+	pub fn fork(&self, fork_id: u64) -> Self {
+		assert_ne!(fork_id, self.fork_id, "a fork must have a different id");
+		Self { fork_id, ..self.clone() }
+	}
+
+	/// The parameters of the chain.
+	pub fn config(&self) -> &ChainConfig {
+		&self.config
+	}
+
+	/// The latest block of the chain.
+	pub fn head(&self) -> &Block {
+		self.blocks.values().next_back().expect("chain starts with genesis block; qed")
+	}
+
+	/// The block at `slot`, if the slot was not missed.
+	pub fn block(&self, slot: u64) -> Option<&Block> {
+		self.blocks.get(&slot)
+	}
+
+	/// The latest block at or before `slot`.
+	pub fn block_at_or_before(&self, slot: u64) -> &Block {
+		self.blocks
+			.range(..=slot)
+			.next_back()
+			.expect("chain starts with genesis block; qed")
+			.1
+	}
+
+	/// The first block after `slot`, if any.
+	pub fn block_after(&self, slot: u64) -> Option<&Block> {
+		self.blocks.range(slot + 1..).next().map(|(_, block)| block)
+	}
+
+	/// The sync committee of `period`, which must not be after the period following the head.
+	pub fn committee(&self, period: u64) -> &Committee {
+		self.committees.get(&period).expect("committees are generated with blocks; qed")
+	}
+
+	/// Produces a block at `slot` without logs. The slots since the previous block are missed.
+	pub fn produce_block(&mut self, slot: u64) -> &Block {
+		self.produce_block_with_logs(slot, &[])
+	}
+
+	/// Produces blocks at all `slots`, which must be increasing.
+	pub fn produce_blocks(&mut self, slots: impl IntoIterator<Item = u64>) -> &Block {
+		for slot in slots {
+			self.produce_block(slot);
+		}
+		self.head()
+	}
+
+	/// Produces blocks at all slots up to `slot`, including it.
+	pub fn produce_blocks_until(&mut self, slot: u64) -> &Block {
+		let next = self.head().header.slot + 1;
+		self.produce_blocks(next..=slot)
+	}
+
+	/// Produces a block at `slot`, whose execution payload emits `logs` in a single receipt.
+	/// The slots since the previous block are missed.
+	pub fn produce_block_with_logs(&mut self, slot: u64, logs: &[Log]) -> &Block {
+		let parent = self.blocks.values().next_back().cloned();
+		if let Some(parent) = &parent {
+			assert!(slot > parent.header.slot, "blocks must be produced at increasing slots");
+
+			// process the slots since the parent block, recording the root of the latest block
+			let first_slot =
+				parent.header.slot.max(slot.saturating_sub(SLOTS_PER_HISTORICAL_ROOT as u64));
+			for processed in first_slot..slot {
+				self.block_roots
+					.set_leaf(processed as usize % SLOTS_PER_HISTORICAL_ROOT, parent.root);
+			}
+		}
+
+		let period = compute_period(slot);
+		for period in period..=period + 1 {
+			self.committees
+				.entry(period)
+				.or_insert_with(|| Committee::generate(self.config.seed, period));
+		}
+
+		// execution payload
+		let receipts = ReceiptsTrie::new(logs);
+		let execution_header = deneb::ExecutionPayloadHeader {
+			parent_hash: parent
+				.as_ref()
+				.map(|parent| parent.execution_header.block_hash)
+				.unwrap_or_default(),
+			receipts_root: receipts.root(),
+			logs_bloom: vec![0; 256],
+			block_number: parent
+				.as_ref()
+				.map(|parent| parent.execution_header.block_number + 1)
+				.unwrap_or_default(),
+			gas_limit: 30_000_000,
+			timestamp: slot * 12,
+			base_fee_per_gas: U256::from(1_000_000_000u64),
+			block_hash: hash_pair(uint64_leaf(slot), uint64_leaf(self.fork_id)),
+			..Default::default()
+		};
+		let execution_header_root =
+			VersionedExecutionPayloadHeader::Deneb(execution_header.clone())
+				.hash_tree_root()
+				.expect("execution header is well-formed; qed");
+
+		// block body
+		let mut body_leaves = [H256::zero(); BODY_WIDTH];
+		body_leaves[BODY_GRAFFITI] = uint64_leaf(self.fork_id);
+		body_leaves[BODY_EXECUTION_PAYLOAD] = execution_header_root;
+		let body = MerkleTree::new(&body_leaves);
+
+		// post-state: blocks of the epoch two epochs behind are finalized
+		let finalized = match parent {
+			Some(_) => {
+				let finalized_epoch = compute_epoch(slot).saturating_sub(2);
+				let finalized = self.block_at_or_before(finalized_epoch * SLOTS_PER_EPOCH);
+				(finalized.header.slot, finalized.root)
+			},
+			None => (0, H256::zero()),
+		};
+		let mut state_leaves = [H256::zero(); STATE_WIDTH];
+		state_leaves[STATE_GENESIS_VALIDATORS_ROOT] = self.config.genesis_validators_root;
+		state_leaves[STATE_SLOT] = uint64_leaf(slot);
+		state_leaves[STATE_BLOCK_ROOTS] = self.block_roots.root();
+		state_leaves[STATE_FINALIZED_CHECKPOINT] =
+			hash_pair(uint64_leaf(compute_epoch(finalized.0)), finalized.1);
+		state_leaves[STATE_CURRENT_SYNC_COMMITTEE] = self.committee(period).root();
+		state_leaves[STATE_NEXT_SYNC_COMMITTEE] = self.committee(period + 1).root();
+		state_leaves[STATE_LATEST_EXECUTION_PAYLOAD_HEADER] = execution_header_root;
+		let state = MerkleTree::new(&state_leaves);
+
+		let header = BeaconHeader {
+			slot,
+			proposer_index: slot % 1024,
+			parent_root: parent.as_ref().map(|parent| parent.root).unwrap_or_default(),
+			state_root: state.root(),
+			body_root: body.root(),
+		};
+		let root = header.hash_tree_root().expect("header is well-formed; qed");
+
+		self.blocks.insert(
+			slot,
+			Block {
+				header,
+				root,
+				state,
+				body,
+				execution_header,
+				receipts,
+				finalized_slot: finalized.0,
+			},
+		);
+		self.head()
+	}
+
+	/// The checkpoint to bootstrap the light client from the block at `slot`.
+	pub fn checkpoint(&self, slot: u64) -> CheckpointUpdate {
+		let block = self.block(slot).expect("checkpoint block must exist");
+		CheckpointUpdate {
+			header: block.header,
+			current_sync_committee: self.committee(compute_period(slot)).sync_committee().clone(),
+			current_sync_committee_branch: block.state.branch(STATE_CURRENT_SYNC_COMMITTEE),
+			validators_root: self.config.genesis_validators_root,
+			block_roots_root: block.block_roots_root(),
+			block_roots_branch: block.state.branch(STATE_BLOCK_ROOTS),
+		}
+	}
+
+	/// The light client update attesting the block at `attested_slot`, and the block finalized
+	/// in its state. The update is signed in the slot of the next block (or the next slot if the
+	/// head is attested) by the first `participants` members of the sync committee.
+	pub fn update(
+		&self,
+		attested_slot: u64,
+		participants: usize,
+		with_next_sync_committee: bool,
+	) -> Update {
+		let signature_slot = self
+			.block_after(attested_slot)
+			.map_or(attested_slot + 1, |block| block.header.slot);
+		self.update_signed_at(
+			attested_slot,
+			signature_slot,
+			|index| index < participants,
+			with_next_sync_committee,
+		)
+	}
+
+	/// The light client update attesting the block at `attested_slot`, signed at
+	/// `signature_slot` by the members of the sync committee for which `participates` returns
+	/// true.
+	pub fn update_signed_at(
+		&self,
+		attested_slot: u64,
+		signature_slot: u64,
+		participates: impl Fn(usize) -> bool,
+		with_next_sync_committee: bool,
+	) -> Update {
+		let attested = self.block(attested_slot).expect("attested block must exist");
+		let finalized = self.block(attested.finalized_slot).expect("finalized block exists; qed");
+
+		// the finalized root is the right child of the finalized checkpoint
+		let mut finality_branch = vec![uint64_leaf(compute_epoch(finalized.header.slot))];
+		finality_branch.extend(attested.state.branch(STATE_FINALIZED_CHECKPOINT));
+
+		let next_sync_committee_update =
+			with_next_sync_committee.then(|| NextSyncCommitteeUpdate {
+				next_sync_committee: self
+					.committee(compute_period(attested_slot) + 1)
+					.sync_committee()
+					.clone(),
+				next_sync_committee_branch: attested.state.branch(STATE_NEXT_SYNC_COMMITTEE),
+			});
+
+		Update {
+			attested_header: attested.header,
+			sync_aggregate: self.sign(&attested.header, signature_slot, participates),
+			signature_slot,
+			next_sync_committee_update,
+			finalized_header: finalized.header,
+			finality_branch,
+			block_roots_root: finalized.block_roots_root(),
+			block_roots_branch: finalized.state.branch(STATE_BLOCK_ROOTS),
+		}
+	}
+
+	/// Signs `header` at `signature_slot` by the members of the sync committee of the signature
+	/// period for which `participates` returns true.
+	pub fn sign(
+		&self,
+		header: &BeaconHeader,
+		signature_slot: u64,
+		participates: impl Fn(usize) -> bool,
+	) -> SyncAggregate {
+		let committee = self.committee(compute_period(signature_slot));
+		committee.sign(self.signing_root(header, signature_slot), participates)
+	}
+
+	/// The root signed by the sync committee for `header` at `signature_slot`.
+	pub fn signing_root(&self, header: &BeaconHeader, signature_slot: u64) -> H256 {
+		let fork_version =
+			select_fork_version(&self.config.fork_versions, compute_epoch(signature_slot));
+		let fork_data_root = ForkData {
+			current_version: fork_version,
+			genesis_validators_root: self.config.genesis_validators_root.into(),
+		}
+		.hash_tree_root()
+		.expect("fork data is well-formed; qed");
+
+		let mut domain = [0u8; 32];
+		domain[0..4].copy_from_slice(&DOMAIN_SYNC_COMMITTEE);
+		domain[4..32].copy_from_slice(&fork_data_root[..28]);
+
+		SigningData {
+			object_root: header.hash_tree_root().expect("header is well-formed; qed"),
+			domain: domain.into(),
+		}
+		.hash_tree_root()
+		.expect("signing data is well-formed; qed")
+	}
+
+	/// The proof of the execution payload of the block at `slot`.
+	///
+	/// If `finalized_slot` is the slot of the block itself, the block must be imported into the
+	/// light client as a finalized header. Otherwise the proof contains an ancestry proof against
+	/// the block at `finalized_slot`, which must be imported.
+	pub fn execution_proof(&self, slot: u64, finalized_slot: u64) -> ExecutionProof {
+		let block = self.block(slot).expect("proven block must exist");
+		let ancestry_proof = (slot != finalized_slot).then(|| {
+			let finalized = self.block(finalized_slot).expect("finalized block must exist");
+			assert!(slot < finalized_slot, "only ancestors of finalized blocks can be proven");
+			assert!(
+				finalized_slot - slot <= SLOTS_PER_HISTORICAL_ROOT as u64,
+				"block is too old to be proven against the finalized block"
+			);
+
+			let block_roots = MerkleTree::new(&self.block_roots_at(finalized_slot));
+			assert_eq!(block_roots.root(), finalized.block_roots_root());
+			AncestryProof {
+				header_branch: block_roots.branch(slot as usize % SLOTS_PER_HISTORICAL_ROOT),
+				finalized_block_root: finalized.root,
+			}
+		});
+
+		ExecutionProof {
+			header: block.header,
+			ancestry_proof,
+			execution_header: VersionedExecutionPayloadHeader::Deneb(
+				block.execution_header.clone(),
+			),
+			execution_branch: block.body.branch(BODY_EXECUTION_PAYLOAD),
+		}
+	}
+
+	/// The proof of the logs emitted in the block at `slot`, for the inbound queue. See
+	/// [`Self::execution_proof`] for `finalized_slot`.
+	pub fn message_proof(&self, slot: u64, finalized_slot: u64) -> Proof {
+		let block = self.block(slot).expect("proven block must exist");
+		Proof {
+			receipt_proof: (Vec::new(), block.receipts.proof()),
+			execution_proof: self.execution_proof(slot, finalized_slot),
+		}
+	}
+
+	/// The `block_roots` vector in the post-state of the block at `slot`.
+	fn block_roots_at(&self, slot: u64) -> Vec<H256> {
+		let mut block_roots = vec![H256::zero(); SLOTS_PER_HISTORICAL_ROOT];
+		let first_slot = slot.saturating_sub(SLOTS_PER_HISTORICAL_ROOT as u64);
+		for processed in first_slot..slot {
+			block_roots[processed as usize % SLOTS_PER_HISTORICAL_ROOT] =
+				self.block_at_or_before(processed).root;
+		}
+		block_roots
+	}
+}
+
+/// Returns the fork version active at `epoch`.
+pub fn select_fork_version(fork_versions: &ForkVersions, epoch: u64) -> ForkVersion {
+	[&fork_versions.deneb, &fork_versions.capella, &fork_versions.bellatrix, &fork_versions.altair]
+		.into_iter()
+		.find(|fork| epoch >= fork.epoch)
+		.unwrap_or(&fork_versions.genesis)
+		.version
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use snowbridge_beacon_primitives::{
+		fast_aggregate_verify, prepare_g1_pubkeys, verify_merkle_branch, BlsError, Fork,
+	};
+
+	fn chain() -> BeaconChain {
+		let fork = |version: u8| Fork { version: [version, 0, 0, 1], epoch: 0 };
+		BeaconChain::new(ChainConfig {
+			seed: 1,
+			genesis_validators_root: H256::repeat_byte(1),
+			fork_versions: ForkVersions {
+				genesis: fork(0),
+				altair: fork(1),
+				bellatrix: fork(2),
+				capella: fork(3),
+				deneb: fork(4),
+			},
+		})
+	}
+
+	#[test]
+	fn blocks_two_epochs_behind_are_finalized() {
+		let mut chain = chain();
+		chain.produce_blocks_until(100);
+		// the slots until the sixth epoch are missed
+		chain.produce_block(200);
+
+		assert_eq!(chain.block(100).unwrap().finalized_slot, 32);
+		assert_eq!(chain.head().finalized_slot, 100);
+		assert_eq!(chain.head().header.parent_root, chain.block(100).unwrap().root);
+	}
+
+	#[test]
+	fn forks_diverge_after_fork_point() {
+		let mut chain = chain();
+		chain.produce_blocks_until(10);
+		let mut fork = chain.fork(1);
+		chain.produce_block(11);
+		fork.produce_block(11);
+
+		assert_eq!(chain.block(10).unwrap().root, fork.block(10).unwrap().root);
+		assert_ne!(chain.head().root, fork.head().root);
+		assert_ne!(
+			chain.head().execution_header.block_hash,
+			fork.head().execution_header.block_hash
+		);
+	}
+
+	#[test]
+	fn proofs_are_verified() {
+		let mut chain = chain();
+		chain.produce_blocks_until(100);
+		chain.produce_block(200);
+
+		let checkpoint = chain.checkpoint(200);
+		assert!(verify_merkle_branch(
+			chain.committee(0).root(),
+			&checkpoint.current_sync_committee_branch,
+			STATE_CURRENT_SYNC_COMMITTEE,
+			5,
+			checkpoint.header.state_root
+		));
+		assert!(verify_merkle_branch(
+			checkpoint.block_roots_root,
+			&checkpoint.block_roots_branch,
+			STATE_BLOCK_ROOTS,
+			5,
+			checkpoint.header.state_root
+		));
+
+		let update = chain.update(200, 512, true);
+		assert_eq!(update.finalized_header.slot, 100);
+		assert!(verify_merkle_branch(
+			update.finalized_header.hash_tree_root().unwrap(),
+			&update.finality_branch,
+			STATE_FINALIZED_CHECKPOINT * 2 + 1,
+			6,
+			update.attested_header.state_root
+		));
+		let next_sync_committee_update = update.next_sync_committee_update.unwrap();
+		assert!(verify_merkle_branch(
+			chain.committee(1).root(),
+			&next_sync_committee_update.next_sync_committee_branch,
+			STATE_NEXT_SYNC_COMMITTEE,
+			5,
+			update.attested_header.state_root
+		));
+
+		let execution_proof = chain.execution_proof(50, 200);
+		assert!(verify_merkle_branch(
+			execution_proof.execution_header.hash_tree_root().unwrap(),
+			&execution_proof.execution_branch,
+			BODY_EXECUTION_PAYLOAD,
+			4,
+			execution_proof.header.body_root
+		));
+		let ancestry_proof = execution_proof.ancestry_proof.unwrap();
+		assert_eq!(ancestry_proof.finalized_block_root, chain.head().root);
+		assert!(verify_merkle_branch(
+			chain.block(50).unwrap().root,
+			&ancestry_proof.header_branch,
+			50,
+			13,
+			chain.head().block_roots_root()
+		));
+	}
+
+	#[test]
+	fn committees_rotate_and_sign() {
+		let mut chain = chain();
+		let period_start = SLOTS_PER_EPOCH * EPOCHS_PER_SYNC_COMMITTEE_PERIOD;
+		chain.produce_block(period_start);
+		assert_ne!(chain.committee(0).root(), chain.committee(1).root());
+
+		let header = chain.head().header;
+		let signing_root = chain.signing_root(&header, period_start + 1);
+		// the members at even positions participate
+		let aggregate = chain.sign(&header, period_start + 1, |index| index % 2 == 0);
+
+		let verify = |committee: &Committee| {
+			let sync_committee = committee.sync_committee();
+			let absent: Vec<_> =
+				sync_committee.pubkeys.iter().skip(1).step_by(2).cloned().collect();
+			fast_aggregate_verify(
+				&prepare_g1_pubkeys(&[sync_committee.aggregate_pubkey]).unwrap()[0],
+				&prepare_g1_pubkeys(&absent).unwrap(),
+				signing_root,
+				&aggregate.sync_committee_signature,
+			)
+		};
+		assert_eq!(verify(chain.committee(1)), Ok(()));
+		assert_eq!(verify(chain.committee(0)), Err(BlsError::SignatureVerificationFailed));
+	}
+}
@@ -0,0 +1,103 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: 2023 Snowfork <hello@snowfork.com>
+//! Binary SSZ merkle trees with proofs for their leaves.
+use sp_core::H256;
+use sp_io::hashing::sha2_256;
+
+/// Hashes two sibling nodes into their parent node.
+pub fn hash_pair(left: H256, right: H256) -> H256 {
+	let mut data = [0u8; 64];
+	data[0..32].copy_from_slice(left.as_bytes());
+	data[32..64].copy_from_slice(right.as_bytes());
+	sha2_256(&data).into()
+}
+
+/// The SSZ leaf of an `uint64` value.
+pub fn uint64_leaf(value: u64) -> H256 {
+	let mut leaf = [0u8; 32];
+	leaf[0..8].copy_from_slice(&value.to_le_bytes());
+	leaf.into()
+}
+
+/// A complete binary merkle tree, stored by generalized index: the root is at index 1 and the
+/// children of node `i` are at `2 * i` and `2 * i + 1`.
+#[derive(Clone, Debug)]
+pub struct MerkleTree {
+	nodes: Vec<H256>,
+}
+
+impl MerkleTree {
+	/// Builds a tree over `leaves`, whose number must be a power of two.
+	pub fn new(leaves: &[H256]) -> Self {
+		let width = leaves.len();
+		assert!(width.is_power_of_two(), "number of leaves must be a power of two");
+
+		let mut nodes = vec![H256::zero(); 2 * width];
+		nodes[width..].copy_from_slice(leaves);
+		for index in (1..width).rev() {
+			nodes[index] = hash_pair(nodes[2 * index], nodes[2 * index + 1]);
+		}
+		Self { nodes }
+	}
+
+	/// Number of leaves of the tree.
+	pub fn width(&self) -> usize {
+		self.nodes.len() / 2
+	}
+
+	/// The root of the tree.
+	pub fn root(&self) -> H256 {
+		self.nodes[1]
+	}
+
+	/// The leaf at `index`.
+	pub fn leaf(&self, index: usize) -> H256 {
+		self.nodes[self.width() + index]
+	}
+
+	/// Replaces the leaf at `index`, updating the nodes on its path to the root.
+	pub fn set_leaf(&mut self, index: usize, leaf: H256) {
+		let mut node = self.width() + index;
+		self.nodes[node] = leaf;
+		while node > 1 {
+			node /= 2;
+			self.nodes[node] = hash_pair(self.nodes[2 * node], self.nodes[2 * node + 1]);
+		}
+	}
+
+	/// The branch proving the leaf at `index`, ordered from the leaf to the root as expected by
+	/// `verify_merkle_branch`.
+	pub fn branch(&self, index: usize) -> Vec<H256> {
+		let mut node = self.width() + index;
+		let mut branch = Vec::new();
+		while node > 1 {
+			branch.push(self.nodes[node ^ 1]);
+			node /= 2;
+		}
+		branch
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use snowbridge_beacon_primitives::verify_merkle_branch;
+
+	#[test]
+	fn branches_are_verified() {
+		let leaves: Vec<H256> = (0..16u64).map(uint64_leaf).collect();
+		let mut tree = MerkleTree::new(&leaves);
+		tree.set_leaf(9, H256::repeat_byte(9));
+
+		for index in 0..16 {
+			assert!(verify_merkle_branch(
+				tree.leaf(index),
+				&tree.branch(index),
+				index,
+				4,
+				tree.root()
+			));
+		}
+		assert_eq!(tree.root(), MerkleTree::new(&tree.nodes[16..]).root());
+	}
+}
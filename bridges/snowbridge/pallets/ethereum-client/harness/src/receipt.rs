@@ -0,0 +1,81 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: 2023 Snowfork <hello@snowfork.com>
+//! Receipts tries of execution blocks.
+use rlp::RlpStream;
+use snowbridge_core::inbound::Log;
+use sp_core::H256;
+use sp_io::hashing::keccak_256;
+
+/// Size of the logs bloom of a receipt.
+const BLOOM_SIZE: usize = 256;
+
+/// The receipts trie of an execution block with a single successful receipt, which contains all
+/// the logs emitted in the block.
+///
+/// The trie consists of a single leaf node, so the proof of the receipt is the leaf itself.
+#[derive(Clone, Debug)]
+pub struct ReceiptsTrie {
+	leaf: Vec<u8>,
+}
+
+impl ReceiptsTrie {
+	/// Builds the receipts trie of a block which emitted `logs`.
+	pub fn new(logs: &[Log]) -> Self {
+		let mut receipt = RlpStream::new_list(4);
+		// successful status
+		receipt.append(&vec![1u8]);
+		receipt.append(&(21_000u64 * logs.len() as u64));
+		receipt.append(&vec![0u8; BLOOM_SIZE]);
+		receipt.begin_list(logs.len());
+		for log in logs {
+			receipt.begin_list(3);
+			receipt.append(&log.address.as_bytes().to_vec());
+			receipt.begin_list(log.topics.len());
+			for topic in &log.topics {
+				receipt.append(&topic.as_bytes().to_vec());
+			}
+			receipt.append(&log.data);
+		}
+
+		// the key of the first receipt is `rlp(0)`, hex-prefix encoded as an even leaf path
+		let key = vec![0x20, rlp::encode(&0u64)[0]];
+		let mut leaf = RlpStream::new_list(2);
+		leaf.append(&key);
+		leaf.append(&receipt.out().to_vec());
+
+		Self { leaf: leaf.out().to_vec() }
+	}
+
+	/// The receipts root of the block.
+	pub fn root(&self) -> H256 {
+		keccak_256(&self.leaf).into()
+	}
+
+	/// The nodes proving the receipt of the block.
+	pub fn proof(&self) -> Vec<Vec<u8>> {
+		vec![self.leaf.clone()]
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use snowbridge_beacon_primitives::verify_receipt_proof;
+	use sp_core::H160;
+
+	#[test]
+	fn receipt_proof_is_verified() {
+		let log = Log {
+			address: H160::repeat_byte(1),
+			topics: vec![H256::repeat_byte(2), H256::repeat_byte(3)],
+			data: vec![4; 100],
+		};
+		let trie = ReceiptsTrie::new(&[log.clone()]);
+
+		let receipt = verify_receipt_proof(trie.root(), &trie.proof()).unwrap().unwrap();
+		assert_eq!(receipt.logs.len(), 1);
+		assert_eq!(receipt.logs[0].address, log.address);
+		assert_eq!(receipt.logs[0].topics, log.topics);
+		assert_eq!(receipt.logs[0].data, log.data);
+	}
+}
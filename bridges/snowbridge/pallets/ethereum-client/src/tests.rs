@@ -9,17 +9,19 @@ use crate::{
 		load_finalized_header_update_fixture, load_next_finalized_header_update_fixture,
 		load_next_sync_committee_update_fixture, load_sync_committee_update_fixture,
 	},
-	sync_committee_sum, verify_merkle_branch, BeaconHeader, CompactBeaconState, Error,
-	FinalizedBeaconState, LatestFinalizedBlockRoot, LatestSyncCommitteeUpdatePeriod,
-	NextSyncCommittee, SyncCommitteePrepared,
+	sync_committee_sum, verify_merkle_branch, BeaconHeader, CompactBeaconState,
+	CurrentSyncCommittee, Error, FinalizedBeaconState, LatestFinalizedBlockRoot,
+	LatestSyncCommitteeUpdatePeriod, NextSyncCommittee, SyncCommitteePrepared,
 };
 use frame_support::{assert_err, assert_noop, assert_ok, pallet_prelude::Pays};
 use hex_literal::hex;
 use snowbridge_beacon_primitives::{
-	types::deneb, Fork, ForkVersions, NextSyncCommitteeUpdate, VersionedExecutionPayloadHeader,
+	types::deneb, BlsError, Fork, ForkVersions, NextSyncCommitteeUpdate,
+	VersionedExecutionPayloadHeader,
 };
-use snowbridge_core::inbound::{VerificationError, Verifier};
-use sp_core::H256;
+use snowbridge_core::inbound::{Log, VerificationError, Verifier};
+use snowbridge_pallet_ethereum_client_harness::{BeaconChain, ChainConfig};
+use sp_core::{H160, H256};
 use sp_runtime::DispatchError;
 
 /// Arbitrary hash used for tests and invalid hashes.
@@ -970,3 +972,154 @@ fn verify_execution_proof_not_finalized() {
 		);
 	});
 }
+
+/* HARNESS */
+
+fn harness_chain(fork_versions: ForkVersions) -> BeaconChain {
+	BeaconChain::new(ChainConfig {
+		seed: 1,
+		genesis_validators_root: TEST_HASH.into(),
+		fork_versions,
+	})
+}
+
+fn harness_log() -> Log {
+	Log {
+		address: H160::repeat_byte(1),
+		topics: vec![H256::repeat_byte(2), H256::repeat_byte(3)],
+		data: vec![4; 64],
+	}
+}
+
+#[test]
+fn harness_signing_root_matches_light_client() {
+	let chain = harness_chain(ChainForkVersions::get());
+	let header = chain.head().header;
+	let validators_root = chain.config().genesis_validators_root;
+
+	new_tester().execute_with(|| {
+		assert_eq!(
+			EthereumBeaconClient::signing_root(&header, validators_root, 1).unwrap(),
+			chain.signing_root(&header, 1)
+		);
+	});
+}
+
+#[test]
+fn harness_submit_updates_across_sync_committee_period_boundary() {
+	let period_start = (SLOTS_PER_EPOCH * EPOCHS_PER_SYNC_COMMITTEE_PERIOD) as u64;
+	let mut chain = harness_chain(ChainForkVersions::get());
+	chain.produce_blocks_until(160);
+	// the slots until the last epochs of the period are missed
+	chain.produce_blocks(period_start - 192..period_start + 128);
+
+	let checkpoint = Box::new(chain.checkpoint(64));
+	let sync_committee_update = Box::new(chain.update(160, 512, true));
+	// attested in the last slot of the period, signed by the sync committee of the next period
+	let finalized_header_update = Box::new(chain.update(period_start - 1, 400, false));
+	assert_eq!(finalized_header_update.signature_slot, period_start);
+	let next_period_update = Box::new(chain.update(period_start + 100, 512, false));
+	let next_sync_committee_update = Box::new(chain.update(period_start + 100, 512, true));
+	assert_eq!(compute_period(next_sync_committee_update.finalized_header.slot), 1);
+
+	new_tester().execute_with(|| {
+		assert_ok!(EthereumBeaconClient::process_checkpoint_update(&checkpoint));
+		assert_ok!(EthereumBeaconClient::submit(RuntimeOrigin::signed(1), sync_committee_update));
+		assert!(<NextSyncCommittee<Test>>::exists());
+
+		assert_ok!(EthereumBeaconClient::submit(
+			RuntimeOrigin::signed(1),
+			finalized_header_update.clone()
+		));
+		assert_eq!(
+			<LatestFinalizedBlockRoot<Test>>::get(),
+			finalized_header_update.finalized_header.hash_tree_root().unwrap()
+		);
+
+		assert_err!(
+			EthereumBeaconClient::submit(RuntimeOrigin::signed(1), next_period_update),
+			Error::<Test>::SyncCommitteeUpdateRequired
+		);
+		assert_ok!(EthereumBeaconClient::submit(
+			RuntimeOrigin::signed(1),
+			next_sync_committee_update.clone()
+		));
+		assert_eq!(<CurrentSyncCommittee<Test>>::get().root, chain.committee(1).root());
+		assert_eq!(<NextSyncCommittee<Test>>::get().root, chain.committee(2).root());
+		assert_eq!(
+			<LatestFinalizedBlockRoot<Test>>::get(),
+			next_sync_committee_update.finalized_header.hash_tree_root().unwrap()
+		);
+	});
+}
+
+#[test]
+fn harness_submit_update_without_supermajority() {
+	let mut chain = harness_chain(ChainForkVersions::get());
+	chain.produce_blocks_until(160);
+
+	let checkpoint = Box::new(chain.checkpoint(64));
+	let update = Box::new(chain.update(160, 341, true));
+	let supermajority_update = Box::new(chain.update(160, 342, true));
+
+	new_tester().execute_with(|| {
+		assert_ok!(EthereumBeaconClient::process_checkpoint_update(&checkpoint));
+		assert_err!(
+			EthereumBeaconClient::submit(RuntimeOrigin::signed(1), update),
+			Error::<Test>::SyncCommitteeParticipantsNotSupermajority
+		);
+		assert_ok!(EthereumBeaconClient::submit(RuntimeOrigin::signed(1), supermajority_update));
+	});
+}
+
+#[test]
+fn harness_submit_update_signed_with_other_fork_version() {
+	// the chain forks to deneb later than configured in the light client
+	let mut fork_versions = ChainForkVersions::get();
+	fork_versions.deneb.epoch = 10;
+	let mut chain = harness_chain(fork_versions);
+	chain.produce_blocks_until(400);
+
+	let checkpoint = Box::new(chain.checkpoint(64));
+	let capella_update = Box::new(chain.update(160, 512, true));
+	let deneb_update = Box::new(chain.update(380, 512, true));
+
+	new_tester().execute_with(|| {
+		assert_ok!(EthereumBeaconClient::process_checkpoint_update(&checkpoint));
+		assert_err!(
+			EthereumBeaconClient::submit(RuntimeOrigin::signed(1), capella_update),
+			Error::<Test>::BLSVerificationFailed(BlsError::SignatureVerificationFailed)
+		);
+		assert_ok!(EthereumBeaconClient::submit(RuntimeOrigin::signed(1), deneb_update));
+	});
+}
+
+#[test]
+fn harness_verify_message() {
+	let mut chain = harness_chain(ChainForkVersions::get());
+	chain.produce_blocks_until(99);
+	let mut fork = chain.fork(1);
+	chain.produce_block_with_logs(100, &[harness_log()]);
+	fork.produce_block_with_logs(100, &[harness_log()]);
+	// the slots until the finalized block are missed
+	chain.produce_block_with_logs(150, &[harness_log()]);
+
+	let checkpoint = Box::new(chain.checkpoint(150));
+	let ancestor_proof = chain.message_proof(100, 150);
+	let finalized_proof = chain.message_proof(150, 150);
+	let mut fork_proof = fork.message_proof(100, 100);
+	fork_proof.execution_proof.ancestry_proof =
+		ancestor_proof.execution_proof.ancestry_proof.clone();
+
+	new_tester().execute_with(|| {
+		assert_ok!(EthereumBeaconClient::process_checkpoint_update(&checkpoint));
+		assert_ok!(EthereumBeaconClient::verify(&harness_log(), &ancestor_proof));
+		assert_ok!(EthereumBeaconClient::verify(&harness_log(), &finalized_proof));
+		assert_err!(
+			EthereumBeaconClient::verify(&harness_log(), &fork_proof),
+			VerificationError::InvalidExecutionProof(
+				Error::<Test>::InvalidAncestryMerkleProof.into()
+			)
+		);
+	});
+}
@@ -95,11 +95,19 @@ impl snowbridge_pallet_ethereum_client::Config for Test {
 	type WeightInfo = ();
 }
 
+parameter_types! {
+	/// Whether messages are verified by the Ethereum beacon client instead of being accepted.
+	pub storage VerifyWithBeaconClient: bool = false;
+}
+
 // Mock verifier
 pub struct MockVerifier;
 
 impl Verifier for MockVerifier {
-	fn verify(_: &Log, _: &Proof) -> Result<(), VerificationError> {
+	fn verify(log: &Log, proof: &Proof) -> Result<(), VerificationError> {
+		if VerifyWithBeaconClient::get() {
+			return EthereumBeaconClient::verify(log, proof)
+		}
 		Ok(())
 	}
 }
@@ -4,7 +4,12 @@ use super::*;
 
 use frame_support::{assert_noop, assert_ok};
 use hex_literal::hex;
-use snowbridge_core::{inbound::Proof, ChannelId};
+use snowbridge_core::{
+	inbound::{Proof, VerificationError},
+	ChannelId,
+};
+use snowbridge_pallet_ethereum_client_harness::{BeaconChain, ChainConfig};
+use sp_core::H256;
 use sp_keyring::AccountKeyring as Keyring;
 use sp_runtime::DispatchError;
 use sp_std::convert::From;
@@ -243,3 +248,39 @@ fn test_submit_no_funds_to_reward_relayers_and_ed_preserved() {
 		assert_eq!(amount, ExistentialDeposit::get());
 	});
 }
+
+#[test]
+fn test_submit_with_beacon_chain_proof() {
+	let mut chain = BeaconChain::new(ChainConfig {
+		seed: 1,
+		genesis_validators_root: H256::repeat_byte(1),
+		fork_versions: ChainForkVersions::get(),
+	});
+	chain.produce_blocks_until(99);
+	chain.produce_block_with_logs(100, &[mock_event_log()]);
+	chain.produce_block_with_logs(101, &[mock_event_log_invalid_channel()]);
+	// the slots until the finalized block are missed
+	chain.produce_block(150);
+
+	let checkpoint = Box::new(chain.checkpoint(150));
+	let message = Message { event_log: mock_event_log(), proof: chain.message_proof(100, 150) };
+	// the log was not emitted in the proven block
+	let unproven_message =
+		Message { event_log: mock_event_log(), proof: chain.message_proof(101, 150) };
+
+	new_tester().execute_with(|| {
+		VerifyWithBeaconClient::set(&true);
+		let origin = RuntimeOrigin::signed(Keyring::Bob.into());
+		assert_ok!(EthereumBeaconClient::force_checkpoint(RuntimeOrigin::root(), checkpoint));
+
+		assert_noop!(
+			InboundQueue::submit(origin.clone(), unproven_message),
+			Error::<Test>::Verification(VerificationError::LogNotFound)
+		);
+
+		assert_ok!(InboundQueue::submit(origin, message));
+		let channel_id: ChannelId =
+			hex!("c173fac324158e77fb5840738a1a541f633cbec8884c6a601c567d2b376a0539").into();
+		assert_eq!(Nonce::<Test>::get(channel_id), 1);
+	});
+}
@@ -26,6 +26,7 @@ use std::{
 };
 
 use codec::Encode;
+use cumulus_client_pov_recovery::{RecoveryConfig, RecoveryStrategy};
 use sc_chain_spec::ChainSpec;
 use sc_cli::RpcEndpoint;
 use sc_client_api::HeaderBackend;
@@ -37,6 +38,10 @@ use sp_core::hexdisplay::HexDisplay;
 use sp_runtime::traits::{Block as BlockT, Zero};
 use url::Url;
 
+mod solo_to_para;
+
+pub use solo_to_para::ExportSoloToParaCommand;
+
 /// The `purge-chain` command used to remove the whole chain: the parachain and the relay chain.
 #[derive(Debug, clap::Parser)]
 #[group(skip)]
@@ -307,6 +312,39 @@ pub struct RunCmd {
 	/// Will use the specified relay chain chainspec.
 	#[arg(long, conflicts_with_all = ["relay_chain_rpc_urls", "collator"])]
 	pub relay_chain_light_client: bool,
+
+	/// Parameters of the PoV recovery.
+	#[command(flatten)]
+	pub pov_recovery_params: PoVRecoveryParams,
+}
+
+/// Parameters of the PoV recovery.
+#[derive(Debug, Clone, clap::Args)]
+pub struct PoVRecoveryParams {
+	/// The strategy used to recover the PoV of a parachain block from the relay chain.
+	#[arg(long, value_enum, default_value_t = RecoveryStrategy::Chunks)]
+	pub pov_recovery_strategy: RecoveryStrategy,
+
+	/// Maximum number of PoV recoveries that are in flight at the same time.
+	#[arg(long, default_value_t = 16)]
+	pub pov_recovery_max_parallel: usize,
+
+	/// Maximum number of blocks waiting to be recovered.
+	///
+	/// When the queue is full, blocks that are not building on the best chain are dropped first.
+	#[arg(long, default_value_t = 1024)]
+	pub pov_recovery_max_queued: usize,
+}
+
+impl PoVRecoveryParams {
+	/// Returns the [`RecoveryConfig`] described by these parameters.
+	pub fn recovery_config(&self) -> RecoveryConfig {
+		RecoveryConfig {
+			strategy: self.pov_recovery_strategy,
+			max_parallel_recoveries: self.pov_recovery_max_parallel,
+			max_queued_recoveries: self.pov_recovery_max_queued,
+		}
+	}
 }
 
 impl RunCmd {
@@ -329,7 +367,10 @@ impl RunCmd {
 				_ => RelayChainMode::Embedded,
 			};
 
-		CollatorOptions { relay_chain_mode }
+		CollatorOptions {
+			relay_chain_mode,
+			pov_recovery_config: self.pov_recovery_params.recovery_config(),
+		}
 	}
 }
 
@@ -349,6 +390,8 @@ pub enum RelayChainMode {
 pub struct CollatorOptions {
 	/// How this collator retrieves relay chain information
 	pub relay_chain_mode: RelayChainMode,
+	/// Configuration of the PoV recovery
+	pub pov_recovery_config: RecoveryConfig,
 }
 
 /// A non-redundant version of the `RunCmd` that sets the `validator` field when the
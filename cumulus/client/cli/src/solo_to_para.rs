@@ -0,0 +1,282 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Cumulus.
+
+// Cumulus is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Cumulus is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Cumulus.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Command for exporting the data needed to continue a solochain as a parachain.
+
+use std::{fmt::Debug, fs, path::PathBuf, str::FromStr, sync::Arc};
+
+use codec::Encode;
+use cumulus_primitives_core::CollectCollationInfo;
+use sc_cli::BlockNumberOrHash;
+use sc_client_api::{Backend, HeaderBackend, StorageProvider};
+use sc_executor::{RuntimeVersion, WasmExecutor};
+use sc_executor_common::runtime_blob::RuntimeBlob;
+use sp_api::RuntimeApiInfo;
+use sp_core::{
+	hexdisplay::HexDisplay,
+	storage::StorageKey,
+	traits::{CallContext, ReadRuntimeVersionExt, RuntimeCode, WrappedRuntimeCode},
+};
+use sp_externalities::Extensions;
+use sp_runtime::traits::{Block as BlockT, Header as HeaderT, NumberFor};
+use sp_state_machine::{OverlayedChanges, StateMachine};
+
+/// Name of the file the head data is written to.
+const HEAD_DATA_FILE: &str = "head-data";
+/// Name of the file the validation code is written to.
+const VALIDATION_CODE_FILE: &str = "validation-code";
+
+/// SCALE encoded `UpgradeCheckSelect::PreAndPost` argument of
+/// `TryRuntime_on_runtime_upgrade`.
+const PRE_AND_POST_UPGRADE_CHECKS: &[u8] = &[2];
+
+/// Host functions available to the target runtime when checking the runtime upgrade.
+type HostFunctions = (
+	sp_io::SubstrateHostFunctions,
+	cumulus_primitives_proof_size_hostfunction::storage_proof_size::HostFunctions,
+);
+
+/// Command for exporting the head data and validation code needed to register a solochain as a
+/// parachain.
+///
+/// The head data is the header of the exported block, which becomes the parent of the first
+/// parachain block. Before anything is written, the command checks that the target runtime fits
+/// into the limits of the relay chain, exposes the `CollectCollationInfo` API of a parachain
+/// runtime and is a valid upgrade of the runtime of the exported block. With `--check-upgrade`,
+/// the runtime upgrade of the target runtime is also executed on top of that state, including the
+/// `pre_upgrade` and `post_upgrade` checks of its migrations. The runtime migrations that need to
+/// be part of the target runtime are provided by `cumulus_pallet_solo_to_para::migrations`.
+///
+/// The command does not build a parachain block or execute `validate_block`, so it can not prove
+/// that the relay chain will accept the blocks of the target runtime. This still needs to be
+/// tested, e.g. on a test network, before the handover.
+///
+/// This command needs to be run by the node of the solochain, which has to add it as a
+/// subcommand.
+#[derive(Debug, clap::Parser)]
+pub struct ExportSoloToParaCommand {
+	/// Directory to write the `head-data` and `validation-code` files to.
+	#[arg()]
+	pub output: PathBuf,
+
+	/// Block hash or number to export the head data of. Defaults to the finalized block.
+	#[arg(long, value_name = "HASH or NUMBER")]
+	pub at: Option<BlockNumberOrHash>,
+
+	/// Path to the wasm blob of the parachain runtime that continues the chain.
+	///
+	/// Defaults to the runtime of the exported block, which then needs to be a parachain runtime
+	/// already.
+	#[arg(long)]
+	pub target_runtime: Option<PathBuf>,
+
+	/// The maximum size of the head data accepted by the relay chain.
+	#[arg(long, default_value_t = 20 * 1024)]
+	pub max_head_data_size: u32,
+
+	/// The maximum size of the validation code accepted by the relay chain.
+	#[arg(long, default_value_t = 3 * 1024 * 1024)]
+	pub max_code_size: u32,
+
+	/// Execute the runtime upgrade of the target runtime on the state of the exported block.
+	///
+	/// Runs the `pre_upgrade` and `post_upgrade` checks of all migrations, e.g. the
+	/// `EnsureParachainReady` migration. Requires the target runtime to be built with the
+	/// `try-runtime` feature.
+	#[arg(long)]
+	pub check_upgrade: bool,
+
+	/// Write output in binary. Default is to write in hex.
+	#[arg(short, long)]
+	pub raw: bool,
+
+	#[allow(missing_docs)]
+	#[command(flatten)]
+	pub shared_params: sc_cli::SharedParams,
+
+	#[allow(missing_docs)]
+	#[command(flatten)]
+	pub pruning_params: sc_cli::PruningParams,
+
+	#[allow(missing_docs)]
+	#[command(flatten)]
+	pub database_params: sc_cli::DatabaseParams,
+}
+
+impl ExportSoloToParaCommand {
+	/// Run the export-solo-to-para command
+	pub fn run<B, BA, C>(&self, client: Arc<C>, backend: Arc<BA>) -> sc_cli::Result<()>
+	where
+		B: BlockT,
+		BA: Backend<B>,
+		C: HeaderBackend<B> + StorageProvider<B, BA> + 'static,
+		<B::Hash as FromStr>::Err: Debug,
+		NumberFor<B>: FromStr,
+		<NumberFor<B> as FromStr>::Err: Debug,
+	{
+		let hash = match &self.at {
+			Some(at) => client.expect_block_hash_from_id(&at.parse::<B>()?)?,
+			None => client.info().finalized_hash,
+		};
+		let header = client.expect_header(hash)?;
+		let head_data = header.encode();
+
+		let current_code = client
+			.storage(hash, &StorageKey(sp_core::storage::well_known_keys::CODE.to_vec()))?
+			.ok_or_else(|| sc_cli::Error::Input("No runtime code found in state".into()))?
+			.0;
+		let target_code = match &self.target_runtime {
+			Some(path) => fs::read(path)?,
+			None => current_code.clone(),
+		};
+
+		if head_data.len() > self.max_head_data_size as usize {
+			return Err(sc_cli::Error::Input(format!(
+				"Head data is too large: {} > {} bytes",
+				head_data.len(),
+				self.max_head_data_size,
+			)))
+		}
+		if target_code.len() > self.max_code_size as usize {
+			return Err(sc_cli::Error::Input(format!(
+				"Validation code is too large: {} > {} bytes",
+				target_code.len(),
+				self.max_code_size,
+			)))
+		}
+
+		let target_version = runtime_version(&target_code)?
+			.ok_or_else(|| sc_cli::Error::Input("Target runtime has no embedded version".into()))?;
+		if !target_version.has_api_with(&<dyn CollectCollationInfo<B>>::ID, |_| true) {
+			return Err(sc_cli::Error::Input(
+				"Target runtime is not a parachain runtime: `CollectCollationInfo` API is missing"
+					.into(),
+			))
+		}
+		if let Some(current_version) = runtime_version(&current_code)? {
+			check_version_succession(&current_version, &target_version)?;
+		}
+		if self.check_upgrade {
+			check_runtime_upgrade::<B, BA>(&*backend, hash, &target_code)?;
+		}
+
+		fs::create_dir_all(&self.output)?;
+		fs::write(self.output.join(HEAD_DATA_FILE), self.encode_output(head_data))?;
+		fs::write(self.output.join(VALIDATION_CODE_FILE), self.encode_output(target_code))?;
+
+		println!(
+			"Exported head data of block #{} ({hash:?}) and validation code of runtime {} v{} to {}",
+			header.number(),
+			target_version.spec_name,
+			target_version.spec_version,
+			self.output.display(),
+		);
+
+		Ok(())
+	}
+
+	fn encode_output(&self, data: Vec<u8>) -> Vec<u8> {
+		if self.raw {
+			data
+		} else {
+			format!("0x{:?}", HexDisplay::from(&data)).into_bytes()
+		}
+	}
+}
+
+/// Read the version embedded into the given runtime `code`.
+fn runtime_version(code: &[u8]) -> sc_cli::Result<Option<RuntimeVersion>> {
+	let blob = RuntimeBlob::uncompress_if_needed(code)
+		.map_err(|e| sc_cli::Error::Input(format!("Invalid runtime code: {e}")))?;
+	sc_executor::read_embedded_version(&blob)
+		.map_err(|e| sc_cli::Error::Input(format!("Invalid runtime version: {e}")))
+}
+
+/// Execute the runtime upgrade of the runtime `code` on top of the state of block `hash`.
+///
+/// The state changes are discarded, only the outcome of the migrations and their checks matters.
+fn check_runtime_upgrade<B, BA>(backend: &BA, hash: B::Hash, code: &[u8]) -> sc_cli::Result<()>
+where
+	B: BlockT,
+	BA: Backend<B>,
+{
+	let state = backend.state_at(hash)?;
+	let executor = WasmExecutor::<HostFunctions>::builder().build();
+
+	let code_fetcher = WrappedRuntimeCode(code.into());
+	let runtime_code = RuntimeCode {
+		code_fetcher: &code_fetcher,
+		heap_pages: None,
+		hash: sp_core::blake2_256(code).to_vec(),
+	};
+	let mut extensions = Extensions::default();
+	extensions.register(ReadRuntimeVersionExt::new(executor.clone()));
+
+	StateMachine::new(
+		&state,
+		&mut OverlayedChanges::default(),
+		&executor,
+		"TryRuntime_on_runtime_upgrade",
+		PRE_AND_POST_UPGRADE_CHECKS,
+		&mut extensions,
+		&runtime_code,
+		CallContext::Offchain,
+	)
+	.execute()
+	.map_err(|e| {
+		sc_cli::Error::Input(format!(
+			"Runtime upgrade of the target runtime failed on the state of block {hash:?}: {e}. \
+			 Note that the target runtime needs to be built with the `try-runtime` feature."
+		))
+	})?;
+
+	Ok(())
+}
+
+/// Check that the `target` runtime can be applied on top of the `current` runtime.
+fn check_version_succession(
+	current: &RuntimeVersion,
+	target: &RuntimeVersion,
+) -> sc_cli::Result<()> {
+	if current.spec_name != target.spec_name {
+		return Err(sc_cli::Error::Input(format!(
+			"Target runtime is for a different chain: `{}` != `{}`",
+			target.spec_name, current.spec_name,
+		)))
+	}
+	if target.spec_version < current.spec_version {
+		return Err(sc_cli::Error::Input(format!(
+			"Target runtime downgrades the spec version: {} < {}",
+			target.spec_version, current.spec_version,
+		)))
+	}
+
+	Ok(())
+}
+
+impl sc_cli::CliConfiguration for ExportSoloToParaCommand {
+	fn shared_params(&self) -> &sc_cli::SharedParams {
+		&self.shared_params
+	}
+
+	fn pruning_params(&self) -> Option<&sc_cli::PruningParams> {
+		Some(&self.pruning_params)
+	}
+
+	fn database_params(&self) -> Option<&sc_cli::DatabaseParams> {
+		Some(&self.database_params)
+	}
+}
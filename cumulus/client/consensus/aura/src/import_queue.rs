@@ -94,6 +94,7 @@ where
 		check_for_equivocation: sc_consensus_aura::CheckForEquivocation::No,
 		telemetry,
 		compatibility_mode: CompatibilityMode::None,
+		verification_threads: None,
 	})
 }
 
@@ -23,7 +23,7 @@ use futures::{channel::oneshot, stream::FuturesUnordered, Future, FutureExt, Str
 
 use std::{pin::Pin, sync::Arc};
 
-use crate::RecoveryHandle;
+use crate::{metrics::Metrics, RecoveryHandle, RecoveryStrategy};
 
 /// The active candidate recovery.
 ///
@@ -33,11 +33,23 @@ pub(crate) struct ActiveCandidateRecovery<Block: BlockT> {
 	recoveries:
 		FuturesUnordered<Pin<Box<dyn Future<Output = (Block::Hash, Option<Arc<PoV>>)> + Send>>>,
 	recovery_handle: Box<dyn RecoveryHandle>,
+	/// Which hints to pass to the availability recovery subsystem.
+	strategy: RecoveryStrategy,
+	metrics: Metrics,
 }
 
 impl<Block: BlockT> ActiveCandidateRecovery<Block> {
-	pub fn new(recovery_handle: Box<dyn RecoveryHandle>) -> Self {
-		Self { recoveries: Default::default(), recovery_handle }
+	pub fn new(
+		recovery_handle: Box<dyn RecoveryHandle>,
+		strategy: RecoveryStrategy,
+		metrics: Metrics,
+	) -> Self {
+		Self { recoveries: Default::default(), recovery_handle, strategy, metrics }
+	}
+
+	/// Returns the number of recoveries that are currently in flight.
+	pub fn num_active(&self) -> usize {
+		self.recoveries.len()
 	}
 
 	/// Recover the given `candidate`.
@@ -48,22 +60,31 @@ impl<Block: BlockT> ActiveCandidateRecovery<Block> {
 	) {
 		let (tx, rx) = oneshot::channel();
 
+		let backing_group =
+			if self.strategy.prefers_backers() { candidate.backing_group } else { None };
+		let core_index =
+			if self.strategy.prefers_systematic_chunks() { candidate.core_index } else { None };
+
 		self.recovery_handle
 			.send_recovery_msg(
 				AvailabilityRecoveryMessage::RecoverAvailableData(
 					candidate.receipt.clone(),
 					candidate.session_index,
-					None,
-					None,
+					backing_group,
+					core_index,
 					tx,
 				),
 				"ActiveCandidateRecovery",
 			)
 			.await;
 
+		let timer = self.metrics.time_recovery();
 		self.recoveries.push(
 			async move {
-				match rx.await {
+				let res = rx.await;
+				drop(timer);
+
+				match res {
 					Ok(Ok(res)) => (block_hash, Some(res.pov)),
 					Ok(Err(error)) => {
 						tracing::debug!(
@@ -45,6 +45,16 @@
 //!
 //! If we need to recover multiple PoV blocks (which should hopefully not happen in real life), we
 //! make sure that the blocks are imported in the correct order.
+//!
+//! The behaviour of the recovery can be tuned through [`RecoveryConfig`]:
+//!
+//! - The [`RecoveryStrategy`] decides which hints are passed to the availability recovery
+//!   subsystem, e.g. to fetch the full PoV from the backing group first or to prefer systematic
+//!   chunks.
+//! - The recovery queue is bounded. Blocks that build on top of the current best block are
+//!   recovered before any other block, e.g. candidates on forks. When the queue is full, the lowest
+//!   priority entries are evicted first.
+//! - The number of recoveries that are in flight at the same time is bounded as well.
 
 use sc_client_api::{BlockBackend, BlockchainEvents, UsageProvider};
 use sc_consensus::import_queue::{ImportQueueService, IncomingBlock};
@@ -58,19 +68,20 @@ use polkadot_overseer::Handle as OverseerHandle;
 use polkadot_primitives::{
 	vstaging::{
 		CandidateReceiptV2 as CandidateReceipt,
-		CommittedCandidateReceiptV2 as CommittedCandidateReceipt,
+		CommittedCandidateReceiptV2 as CommittedCandidateReceipt, CoreState,
 	},
-	Id as ParaId, SessionIndex,
+	CandidateHash, CoreIndex, GroupIndex, Id as ParaId, SessionIndex,
 };
 
 use cumulus_primitives_core::ParachainBlockData;
-use cumulus_relay_chain_interface::{RelayChainInterface, RelayChainResult};
+use cumulus_relay_chain_interface::{PHash, RelayChainInterface, RelayChainResult};
 
 use codec::Decode;
 use futures::{
 	channel::mpsc::Receiver, select, stream::FuturesUnordered, Future, FutureExt, Stream, StreamExt,
 };
 use futures_timer::Delay;
+use prometheus_endpoint::Registry;
 use rand::{distributions::Uniform, prelude::Distribution, thread_rng};
 
 use std::{
@@ -86,6 +97,9 @@ mod tests;
 mod active_candidate_recovery;
 use active_candidate_recovery::ActiveCandidateRecovery;
 
+mod metrics;
+use metrics::Metrics;
+
 const LOG_TARGET: &str = "cumulus-pov-recovery";
 
 /// Test-friendly wrapper trait for the overseer handle.
@@ -147,60 +161,237 @@ impl RecoveryDelayRange {
 	}
 }
 
+/// The hints passed to the availability recovery subsystem when recovering a PoV.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+#[value(rename_all = "kebab-case")]
+pub enum RecoveryStrategy {
+	/// Recover the PoV from regular chunks, leaving all decisions to the availability recovery
+	/// subsystem.
+	#[default]
+	Chunks,
+	/// Try to fetch the full PoV from the validators that backed the candidate first.
+	PreferBackers,
+	/// Prefer recovering the PoV from systematic chunks, which avoids the decoding step.
+	SystematicChunks,
+	/// Try the backing group first and fall back to systematic chunks.
+	BackersThenSystematicChunks,
+}
+
+impl RecoveryStrategy {
+	/// Returns `true` if the backing group of a candidate should be passed to the recovery.
+	fn prefers_backers(&self) -> bool {
+		matches!(self, Self::PreferBackers | Self::BackersThenSystematicChunks)
+	}
+
+	/// Returns `true` if the core index of a candidate should be passed to the recovery.
+	fn prefers_systematic_chunks(&self) -> bool {
+		matches!(self, Self::SystematicChunks | Self::BackersThenSystematicChunks)
+	}
+
+	/// Returns `true` if the strategy requires to know the occupied core of a candidate.
+	fn requires_core_info(&self) -> bool {
+		self.prefers_backers() || self.prefers_systematic_chunks()
+	}
+}
+
+/// Configuration of the PoV recovery.
+#[derive(Clone, Copy, Debug)]
+pub struct RecoveryConfig {
+	/// The strategy used to recover a single PoV.
+	pub strategy: RecoveryStrategy,
+	/// Maximum number of recoveries that are in flight at the same time.
+	///
+	/// Further recoveries wait until one of the active recoveries has finished.
+	pub max_parallel_recoveries: usize,
+	/// Maximum number of blocks waiting in the recovery queue.
+	///
+	/// When the queue is full, blocks that are not building on the best chain are evicted first.
+	pub max_queued_recoveries: usize,
+}
+
+impl Default for RecoveryConfig {
+	fn default() -> Self {
+		Self {
+			strategy: RecoveryStrategy::default(),
+			max_parallel_recoveries: 16,
+			max_queued_recoveries: 1024,
+		}
+	}
+}
+
+/// The relay chain core that is occupied by a pending candidate.
+#[derive(Clone, Copy, Debug)]
+struct OccupiedCoreInfo {
+	core_index: CoreIndex,
+	backing_group: GroupIndex,
+}
+
 /// Represents an outstanding block candidate.
 struct Candidate<Block: BlockT> {
 	receipt: CandidateReceipt,
 	session_index: SessionIndex,
 	block_number: NumberFor<Block>,
 	parent_hash: Block::Hash,
+	/// The backing group of the candidate, if known.
+	backing_group: Option<GroupIndex>,
+	/// The core the candidate is occupying, if known.
+	core_index: Option<CoreIndex>,
 	// Lazy recovery has been submitted.
 	// Should be true iff a block is either queued to be recovered or
 	// recovery is currently in progress.
 	waiting_recovery: bool,
 }
 
+/// Priority of a block in the [`RecoveryQueue`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum RecoveryPriority {
+	/// The block is building on top of the best block.
+	BestChain,
+	/// Any other block, e.g. a block on a fork.
+	Other,
+}
+
 /// Queue that is used to decide when to start PoV-recovery operations.
 struct RecoveryQueue<Block: BlockT> {
 	recovery_delay_range: RecoveryDelayRange,
-	// Queue that keeps the hashes of blocks to be recovered.
-	recovery_queue: VecDeque<Block::Hash>,
+	// Maximum number of hashes in all queues combined.
+	max_len: usize,
+	// Queue that keeps the hashes of best chain blocks to be recovered.
+	best_chain_queue: VecDeque<Block::Hash>,
+	// Queue that keeps the hashes of all other blocks to be recovered.
+	other_queue: VecDeque<Block::Hash>,
+	// Hashes of best chain blocks whose recovery was signaled, but could not be started yet.
+	deferred_best_chain_queue: VecDeque<Block::Hash>,
+	// Hashes of all other blocks whose recovery was signaled, but could not be started yet.
+	deferred_other_queue: VecDeque<Block::Hash>,
 	// Futures that resolve when a new recovery should be started.
+	//
+	// There is one for every hash in `best_chain_queue` and `other_queue`.
 	signaling_queue: FuturesUnordered<Pin<Box<dyn Future<Output = ()> + Send>>>,
 }
 
 impl<Block: BlockT> RecoveryQueue<Block> {
-	pub fn new(recovery_delay_range: RecoveryDelayRange) -> Self {
+	pub fn new(recovery_delay_range: RecoveryDelayRange, max_len: usize) -> Self {
 		Self {
 			recovery_delay_range,
-			recovery_queue: Default::default(),
+			max_len: max_len.max(1),
+			best_chain_queue: Default::default(),
+			other_queue: Default::default(),
+			deferred_best_chain_queue: Default::default(),
+			deferred_other_queue: Default::default(),
 			signaling_queue: Default::default(),
 		}
 	}
 
-	/// Add hash of a block that should go to the end of the recovery queue.
-	/// A new recovery will be signaled after `delay` has passed.
-	pub fn push_recovery(&mut self, hash: Block::Hash) {
+	/// Returns the number of blocks waiting in the queue.
+	pub fn len(&self) -> usize {
+		self.best_chain_queue.len() +
+			self.other_queue.len() +
+			self.deferred_best_chain_queue.len() +
+			self.deferred_other_queue.len()
+	}
+
+	/// Add hash of a block that should go to the end of the recovery queue with the given
+	/// `priority`.
+	///
+	/// A new recovery will be signaled after `delay` has passed. If the queue is full, the newest
+	/// entry with the lowest priority is evicted and its hash is returned. This can also be the
+	/// given `hash` itself.
+	pub fn push_recovery(
+		&mut self,
+		hash: Block::Hash,
+		priority: RecoveryPriority,
+	) -> Option<Block::Hash> {
+		let mut evicted = None;
+
+		if self.len() >= self.max_len {
+			// Blocks still waiting for their recovery slot are evicted before deferred ones.
+			let evicted_entry = match priority {
+				_ if !self.other_queue.is_empty() =>
+					self.other_queue.pop_back().map(|h| (h, false)),
+				RecoveryPriority::Other => return Some(hash),
+				RecoveryPriority::BestChain => self
+					.deferred_other_queue
+					.pop_back()
+					.map(|h| (h, true))
+					.or_else(|| self.best_chain_queue.pop_back().map(|h| (h, false)))
+					.or_else(|| self.deferred_best_chain_queue.pop_back().map(|h| (h, true))),
+			};
+
+			tracing::debug!(
+				target: LOG_TARGET,
+				block_hash = ?hash,
+				evicted = ?evicted_entry,
+				"Recovery queue is full, evicting block",
+			);
+
+			match evicted_entry {
+				// The evicted block still waits for its recovery slot, which is taken over by
+				// `hash`.
+				Some((evicted, false)) => {
+					self.queue_mut(priority).push_back(hash);
+					return Some(evicted)
+				},
+				// The recovery slot of a deferred block was already signaled and is released with
+				// it, `hash` waits for a new one.
+				Some((deferred, true)) => evicted = Some(deferred),
+				None => (),
+			}
+		}
+
 		let delay = self.recovery_delay_range.duration();
 		tracing::debug!(
 			target: LOG_TARGET,
 			block_hash = ?hash,
+			?priority,
 			"Adding block to queue and adding new recovery slot in {:?} sec",
 			delay.as_secs(),
 		);
-		self.recovery_queue.push_back(hash);
+		self.queue_mut(priority).push_back(hash);
 		self.signaling_queue.push(
 			async move {
 				Delay::new(delay).await;
 			}
 			.boxed(),
 		);
+
+		evicted
+	}
+
+	fn queue_mut(&mut self, priority: RecoveryPriority) -> &mut VecDeque<Block::Hash> {
+		match priority {
+			RecoveryPriority::BestChain => &mut self.best_chain_queue,
+			RecoveryPriority::Other => &mut self.other_queue,
+		}
+	}
+
+	/// Put back the hash of a block whose recovery was signaled, but could not be started yet.
+	///
+	/// The block keeps its recovery slot, so it can be taken out again with [`Self::pop_ready`]
+	/// without waiting for a new delay.
+	pub fn defer_recovery(&mut self, hash: Block::Hash, priority: RecoveryPriority) {
+		match priority {
+			RecoveryPriority::BestChain => self.deferred_best_chain_queue.push_back(hash),
+			RecoveryPriority::Other => self.deferred_other_queue.push_back(hash),
+		}
+	}
+
+	/// Get the next hash for block recovery whose recovery slot was already signaled.
+	pub fn pop_ready(&mut self) -> Option<Block::Hash> {
+		self.deferred_best_chain_queue
+			.pop_front()
+			.or_else(|| self.deferred_other_queue.pop_front())
 	}
 
 	/// Get the next hash for block recovery.
+	///
+	/// Blocks building on the best chain are returned before any other block.
 	pub async fn next_recovery(&mut self) -> Block::Hash {
 		loop {
 			if self.signaling_queue.next().await.is_some() {
-				if let Some(hash) = self.recovery_queue.pop_front() {
+				if let Some(hash) =
+					self.best_chain_queue.pop_front().or_else(|| self.other_queue.pop_front())
+				{
 					return hash
 				} else {
 					tracing::error!(
@@ -225,6 +416,10 @@ pub struct PoVRecovery<Block: BlockT, PC, RC> {
 	/// available anymore in this map, it means that it was already imported.
 	candidate_recovery_queue: RecoveryQueue<Block>,
 	active_candidate_recovery: ActiveCandidateRecovery<Block>,
+	/// Maximum number of recoveries that are in flight at the same time.
+	max_parallel_recoveries: usize,
+	/// The strategy used to recover a single PoV.
+	strategy: RecoveryStrategy,
 	/// Blocks that wait that the parent is imported.
 	///
 	/// Uses parent -> blocks mapping.
@@ -238,6 +433,7 @@ pub struct PoVRecovery<Block: BlockT, PC, RC> {
 	/// Blocks that we are retrying currently
 	candidates_in_retry: HashSet<Block::Hash>,
 	parachain_sync_service: Arc<dyn SyncOracle + Sync + Send>,
+	metrics: Metrics,
 }
 
 impl<Block: BlockT, PC, RCInterface> PoVRecovery<Block, PC, RCInterface>
@@ -249,17 +445,41 @@ where
 	pub fn new(
 		recovery_handle: Box<dyn RecoveryHandle>,
 		recovery_delay_range: RecoveryDelayRange,
+		recovery_config: RecoveryConfig,
 		parachain_client: Arc<PC>,
 		parachain_import_queue: Box<dyn ImportQueueService<Block>>,
 		relay_chain_interface: RCInterface,
 		para_id: ParaId,
 		recovery_chan_rx: Receiver<RecoveryRequest<Block>>,
 		parachain_sync_service: Arc<dyn SyncOracle + Sync + Send>,
+		prometheus_registry: Option<&Registry>,
 	) -> Self {
+		let metrics = prometheus_registry
+			.map(|registry| {
+				Metrics::register(registry).unwrap_or_else(|error| {
+					tracing::warn!(
+						target: LOG_TARGET,
+						%error,
+						"Unable to register PoV recovery metrics, continuing without metrics.",
+					);
+					Metrics::default()
+				})
+			})
+			.unwrap_or_default();
+
 		Self {
 			candidates: HashMap::new(),
-			candidate_recovery_queue: RecoveryQueue::new(recovery_delay_range),
-			active_candidate_recovery: ActiveCandidateRecovery::new(recovery_handle),
+			candidate_recovery_queue: RecoveryQueue::new(
+				recovery_delay_range,
+				recovery_config.max_queued_recoveries,
+			),
+			active_candidate_recovery: ActiveCandidateRecovery::new(
+				recovery_handle,
+				recovery_config.strategy,
+				metrics.clone(),
+			),
+			max_parallel_recoveries: recovery_config.max_parallel_recoveries.max(1),
+			strategy: recovery_config.strategy,
 			waiting_for_parent: HashMap::new(),
 			parachain_client,
 			parachain_import_queue,
@@ -268,6 +488,7 @@ where
 			candidates_in_retry: HashSet::new(),
 			recovery_chan_rx,
 			parachain_sync_service,
+			metrics,
 		}
 	}
 
@@ -276,6 +497,7 @@ where
 		&mut self,
 		receipt: CommittedCandidateReceipt,
 		session_index: SessionIndex,
+		core_info: Option<OccupiedCoreInfo>,
 	) {
 		let header = match Block::Header::decode(&mut &receipt.commitments.head_data.0[..]) {
 			Ok(header) => header,
@@ -307,6 +529,8 @@ where
 				receipt: receipt.to_plain(),
 				session_index,
 				parent_hash: *header.parent_hash(),
+				backing_group: core_info.map(|info| info.backing_group),
+				core_index: core_info.map(|info| info.core_index),
 				waiting_recovery: false,
 			},
 		);
@@ -329,15 +553,69 @@ where
 		self.candidates.retain(|_, pc| pc.block_number > block_number);
 	}
 
+	/// Returns the priority of the candidate with `hash` in the recovery queue.
+	///
+	/// A candidate is building on the best chain if the best block is one of its ancestors.
+	fn recovery_priority(&self, mut hash: Block::Hash) -> RecoveryPriority {
+		let best_hash = self.parachain_client.usage_info().chain.best_hash;
+
+		while let Some(candidate) = self.candidates.get(&hash) {
+			if candidate.parent_hash == best_hash {
+				return RecoveryPriority::BestChain
+			}
+			hash = candidate.parent_hash;
+		}
+
+		RecoveryPriority::Other
+	}
+
+	/// Add the block with `hash` to the recovery queue.
+	fn queue_recovery(&mut self, hash: Block::Hash) {
+		let priority = self.recovery_priority(hash);
+
+		if let Some(evicted) = self.candidate_recovery_queue.push_recovery(hash, priority) {
+			self.candidates_in_retry.remove(&evicted);
+			self.reset_candidate(evicted);
+			self.metrics.on_recovery_finished("evicted");
+		}
+		self.metrics.set_queued_recoveries(self.candidate_recovery_queue.len());
+	}
+
 	/// Recover the candidate for the given `block_hash`.
+	///
+	/// If `max_parallel_recoveries` are already in flight, the block is put back into the recovery
+	/// queue and its recovery is started once one of the active recoveries has finished.
 	async fn recover_candidate(&mut self, block_hash: Block::Hash) {
 		match self.candidates.get(&block_hash) {
 			Some(candidate) if candidate.waiting_recovery => {
-				tracing::debug!(target: LOG_TARGET, ?block_hash, "Issuing recovery request");
-				self.active_candidate_recovery.recover_candidate(block_hash, candidate).await;
+				if self.active_candidate_recovery.num_active() >= self.max_parallel_recoveries {
+					tracing::debug!(
+						target: LOG_TARGET,
+						?block_hash,
+						"Too many recoveries in flight, deferring recovery request",
+					);
+					let priority = self.recovery_priority(block_hash);
+					self.candidate_recovery_queue.defer_recovery(block_hash, priority);
+				} else {
+					tracing::debug!(target: LOG_TARGET, ?block_hash, "Issuing recovery request");
+					self.active_candidate_recovery.recover_candidate(block_hash, candidate).await;
+					self.metrics.set_active_recoveries(self.active_candidate_recovery.num_active());
+				}
 			},
 			_ => (),
 		}
+
+		self.metrics.set_queued_recoveries(self.candidate_recovery_queue.len());
+	}
+
+	/// Start deferred recoveries until `max_parallel_recoveries` are in flight again.
+	async fn recover_deferred_candidates(&mut self) {
+		while self.active_candidate_recovery.num_active() < self.max_parallel_recoveries {
+			match self.candidate_recovery_queue.pop_ready() {
+				Some(block_hash) => self.recover_candidate(block_hash).await,
+				None => break,
+			}
+		}
 	}
 
 	/// Clear `waiting_for_parent` and `waiting_recovery` for the candidate with `hash`.
@@ -355,6 +633,8 @@ where
 
 	/// Handle a recovered candidate.
 	async fn handle_candidate_recovered(&mut self, block_hash: Block::Hash, pov: Option<&PoV>) {
+		self.metrics.set_active_recoveries(self.active_candidate_recovery.num_active());
+
 		let pov = match pov {
 			Some(pov) => {
 				self.candidates_in_retry.remove(&block_hash);
@@ -363,7 +643,8 @@ where
 			None =>
 				if self.candidates_in_retry.insert(block_hash) {
 					tracing::debug!(target: LOG_TARGET, ?block_hash, "Recovery failed, retrying.");
-					self.candidate_recovery_queue.push_recovery(block_hash);
+					self.metrics.on_recovery_finished("retry");
+					self.queue_recovery(block_hash);
 					return
 				} else {
 					tracing::warn!(
@@ -371,6 +652,7 @@ where
 						?block_hash,
 						"Unable to recover block after retry.",
 					);
+					self.metrics.on_recovery_finished("failure");
 					self.candidates_in_retry.remove(&block_hash);
 					self.reset_candidate(block_hash);
 					return
@@ -382,6 +664,7 @@ where
 				Ok(r) => r,
 				Err(error) => {
 					tracing::debug!(target: LOG_TARGET, ?error, "Failed to decompress PoV");
+					self.metrics.on_recovery_finished("invalid");
 
 					self.reset_candidate(block_hash);
 					return
@@ -396,12 +679,15 @@ where
 					?error,
 					"Failed to decode parachain block data from recovered PoV",
 				);
+				self.metrics.on_recovery_finished("invalid");
 
 				self.reset_candidate(block_hash);
 				return
 			},
 		};
 
+		self.metrics.on_recovery_finished("success");
+
 		let block = block_data.into_block();
 
 		let parent = *block.header().parent_hash();
@@ -537,7 +823,7 @@ where
 		}
 
 		for hash in to_recover.into_iter().rev() {
-			self.candidate_recovery_queue.push_recovery(hash);
+			self.queue_recovery(hash);
 		}
 	}
 
@@ -549,6 +835,7 @@ where
 			self.relay_chain_interface.clone(),
 			self.para_id,
 			self.parachain_sync_service.clone(),
+			self.strategy.requires_core_info(),
 		)
 		.await
 		{
@@ -563,9 +850,10 @@ where
 		loop {
 			select! {
 				next_pending_candidates = pending_candidates.next() => {
-					if let Some((candidates, session_index)) = next_pending_candidates {
+					if let Some((candidates, session_index, occupied_cores)) = next_pending_candidates {
 						for candidate in candidates {
-							self.handle_pending_candidate(candidate, session_index);
+							let core_info = occupied_cores.get(&candidate.hash()).copied();
+							self.handle_pending_candidate(candidate, session_index, core_info);
 						}
 					} else {
 						tracing::debug!(target: LOG_TARGET, "Pending candidates stream ended");
@@ -614,6 +902,7 @@ where
 					self.active_candidate_recovery.wait_for_recovery().fuse() =>
 				{
 					self.handle_candidate_recovered(block_hash, pov.as_deref()).await;
+					self.recover_deferred_candidates().await;
 				},
 			}
 		}
@@ -621,11 +910,23 @@ where
 }
 
 /// Returns a stream over pending candidates for the parachain corresponding to `para_id`.
+///
+/// If `fetch_occupied_cores` is `true`, the cores occupied by the pending candidates are fetched as
+/// well.
 async fn pending_candidates(
 	relay_chain_client: impl RelayChainInterface + Clone,
 	para_id: ParaId,
 	sync_service: Arc<dyn SyncOracle + Sync + Send>,
-) -> RelayChainResult<impl Stream<Item = (Vec<CommittedCandidateReceipt>, SessionIndex)>> {
+	fetch_occupied_cores: bool,
+) -> RelayChainResult<
+	impl Stream<
+		Item = (
+			Vec<CommittedCandidateReceipt>,
+			SessionIndex,
+			HashMap<CandidateHash, OccupiedCoreInfo>,
+		),
+	>,
+> {
 	let import_notification_stream = relay_chain_client.import_notification_stream().await?;
 
 	let filtered_stream = import_notification_stream.filter_map(move |n| {
@@ -699,8 +1000,16 @@ async fn pending_candidates(
 					)
 				});
 
+			let occupied_cores = if fetch_occupied_cores {
+				occupied_cores(&client_for_closure, hash, para_id).await
+			} else {
+				HashMap::new()
+			};
+
 			if let Ok(candidates) = pending_availability_result {
-				session_index_result.map(|session_index| (candidates, session_index)).ok()
+				session_index_result
+					.map(|session_index| (candidates, session_index, occupied_cores))
+					.ok()
 			} else {
 				None
 			}
@@ -708,3 +1017,41 @@ async fn pending_candidates(
 	});
 	Ok(filtered_stream)
 }
+
+/// Returns the cores occupied by candidates of `para_id` at the relay chain block `hash`.
+///
+/// Errors are logged and result in an empty map, as the recovery still works without knowing the
+/// occupied cores.
+async fn occupied_cores(
+	relay_chain_client: &impl RelayChainInterface,
+	hash: PHash,
+	para_id: ParaId,
+) -> HashMap<CandidateHash, OccupiedCoreInfo> {
+	let cores = match relay_chain_client.availability_cores(hash).await {
+		Ok(cores) => cores,
+		Err(e) => {
+			tracing::debug!(
+				target: LOG_TARGET,
+				error = ?e,
+				relay_hash = ?hash,
+				"Failed to fetch availability cores.",
+			);
+			return HashMap::new()
+		},
+	};
+
+	cores
+		.into_iter()
+		.enumerate()
+		.filter_map(|(index, core)| match core {
+			CoreState::Occupied(occupied) if occupied.para_id() == para_id => Some((
+				occupied.candidate_hash,
+				OccupiedCoreInfo {
+					core_index: CoreIndex(index as u32),
+					backing_group: occupied.group_responsible,
+				},
+			)),
+			_ => None,
+		})
+		.collect()
+}
@@ -0,0 +1,115 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Cumulus.
+
+// Cumulus is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Cumulus is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Cumulus.  If not, see <http://www.gnu.org/licenses/>.
+
+use prometheus_endpoint::{
+	exponential_buckets, prometheus::HistogramTimer, register, CounterVec, Gauge, Histogram,
+	HistogramOpts, Opts, PrometheusError, Registry, U64,
+};
+
+/// PoV recovery metrics.
+#[derive(Clone, Default)]
+pub(crate) struct Metrics(Option<MetricsInner>);
+
+#[derive(Clone)]
+struct MetricsInner {
+	/// Time between issuing an availability recovery request and receiving its result.
+	recovery_time: Histogram,
+
+	/// Finished recoveries, split by outcome:
+	/// - `success` ... the PoV was recovered and decoded.
+	/// - `retry` ... the recovery failed and was re-queued.
+	/// - `failure` ... the recovery failed and was given up.
+	/// - `invalid` ... the PoV was recovered, but could not be decompressed or decoded.
+	/// - `evicted` ... the recovery was dropped from the full queue before being started.
+	recoveries_finished: CounterVec<U64>,
+
+	/// Number of blocks waiting in the recovery queue.
+	queued_recoveries: Gauge<U64>,
+
+	/// Number of availability recoveries currently in flight.
+	active_recoveries: Gauge<U64>,
+}
+
+impl Metrics {
+	/// Register the metrics in the given `registry`.
+	pub fn register(registry: &Registry) -> Result<Self, PrometheusError> {
+		Ok(Self(Some(MetricsInner {
+			recovery_time: register(
+				Histogram::with_opts(
+					HistogramOpts::new(
+						"cumulus_pov_recovery_time",
+						"Time spent recovering a PoV from the relay chain availability layer",
+					)
+					.buckets(
+						exponential_buckets(0.1, 2.0, 12)
+							.expect("function parameters are constant and always valid; qed"),
+					),
+				)?,
+				registry,
+			)?,
+			recoveries_finished: register(
+				CounterVec::new(
+					Opts::new(
+						"cumulus_pov_recoveries_finished",
+						"Number of finished PoV recoveries, split by outcome",
+					),
+					&["outcome"],
+				)?,
+				registry,
+			)?,
+			queued_recoveries: register(
+				Gauge::new(
+					"cumulus_pov_recovery_queue_size",
+					"Number of blocks waiting in the PoV recovery queue",
+				)?,
+				registry,
+			)?,
+			active_recoveries: register(
+				Gauge::new(
+					"cumulus_pov_recovery_active",
+					"Number of PoV recoveries currently in flight",
+				)?,
+				registry,
+			)?,
+		})))
+	}
+
+	/// Start a timer measuring the duration of a single recovery.
+	pub fn time_recovery(&self) -> Option<HistogramTimer> {
+		self.0.as_ref().map(|metrics| metrics.recovery_time.start_timer())
+	}
+
+	/// Note that a recovery finished with the given `outcome`.
+	pub fn on_recovery_finished(&self, outcome: &str) {
+		if let Some(metrics) = &self.0 {
+			metrics.recoveries_finished.with_label_values(&[outcome]).inc();
+		}
+	}
+
+	/// Set the number of blocks waiting in the recovery queue.
+	pub fn set_queued_recoveries(&self, queued: usize) {
+		if let Some(metrics) = &self.0 {
+			metrics.queued_recoveries.set(queued as u64);
+		}
+	}
+
+	/// Set the number of recoveries currently in flight.
+	pub fn set_active_recoveries(&self, active: usize) {
+		if let Some(metrics) = &self.0 {
+			metrics.active_recoveries.set(active as u64);
+		}
+	}
+}
@@ -18,7 +18,8 @@ use super::*;
 use assert_matches::assert_matches;
 use codec::{Decode, Encode};
 use cumulus_primitives_core::relay_chain::{
-	vstaging::CoreState, BlockId, CandidateCommitments, CandidateDescriptor, CoreIndex,
+	vstaging::{CoreState, OccupiedCore},
+	BlockId, CandidateCommitments, CandidateDescriptor, CoreIndex,
 };
 use cumulus_relay_chain_interface::{
 	InboundDownwardMessage, InboundHrmpMessage, OccupiedCoreAssumption, PHash, PHeader,
@@ -280,6 +281,7 @@ struct RelaychainInner {
 	runtime_version: u32,
 	import_notifications: Vec<PHeader>,
 	candidates_pending_availability: HashMap<PHash, Vec<CommittedCandidateReceipt>>,
+	availability_cores: Vec<CoreState<PHash, NumberFor<Block>>>,
 }
 
 #[derive(Clone)]
@@ -297,6 +299,7 @@ impl Relaychain {
 			inner: Arc::new(Mutex::new(RelaychainInner {
 				import_notifications,
 				candidates_pending_availability,
+				availability_cores: Vec::new(),
 				// The version that introduced candidates_pending_availability
 				runtime_version:
 					RuntimeApiRequest::CANDIDATES_PENDING_AVAILABILITY_RUNTIME_REQUIREMENT,
@@ -307,6 +310,10 @@ impl Relaychain {
 	fn set_runtime_version(&self, version: u32) {
 		self.inner.lock().expect("Poisoned lock").runtime_version = version;
 	}
+
+	fn set_availability_cores(&self, cores: Vec<CoreState<PHash, NumberFor<Block>>>) {
+		self.inner.lock().expect("Poisoned lock").availability_cores = cores;
+	}
 }
 
 #[async_trait::async_trait]
@@ -485,7 +492,7 @@ impl RelayChainInterface for Relaychain {
 		&self,
 		_: PHash,
 	) -> RelayChainResult<Vec<CoreState<PHash, NumberFor<Block>>>> {
-		unimplemented!("Not needed for test");
+		Ok(self.inner.lock().expect("Poisoned lock").availability_cores.clone())
 	}
 
 	async fn claim_queue(
@@ -605,12 +612,14 @@ async fn pending_candidate_height_lower_than_latest_finalized() {
 		let pov_recovery = PoVRecovery::<Block, _, _>::new(
 			Box::new(recovery_subsystem_tx),
 			recovery_delay_range,
+			RecoveryConfig::default(),
 			Arc::new(parachain_client),
 			Box::new(parachain_import_queue),
 			relay_chain_client,
 			ParaId::new(1000),
 			explicit_recovery_chan_rx,
 			Arc::new(DummySyncOracle::default()),
+			None,
 		);
 
 		task::spawn(pov_recovery.run());
@@ -663,12 +672,14 @@ async fn single_pending_candidate_recovery_success(#[case] runtime_version: u32)
 	let pov_recovery = PoVRecovery::<Block, _, _>::new(
 		Box::new(recovery_subsystem_tx),
 		recovery_delay_range,
+		RecoveryConfig::default(),
 		Arc::new(parachain_client),
 		Box::new(parachain_import_queue),
 		relay_chain_client,
 		ParaId::new(1000),
 		explicit_recovery_chan_rx,
 		Arc::new(DummySyncOracle::default()),
+		None,
 	);
 
 	task::spawn(pov_recovery.run());
@@ -746,12 +757,14 @@ async fn single_pending_candidate_recovery_retry_succeeds() {
 	let pov_recovery = PoVRecovery::<Block, _, _>::new(
 		Box::new(recovery_subsystem_tx),
 		recovery_delay_range,
+		RecoveryConfig::default(),
 		Arc::new(parachain_client),
 		Box::new(parachain_import_queue),
 		relay_chain_client,
 		ParaId::new(1000),
 		explicit_recovery_chan_rx,
 		Arc::new(DummySyncOracle::default()),
+		None,
 	);
 
 	task::spawn(pov_recovery.run());
@@ -849,12 +862,14 @@ async fn single_pending_candidate_recovery_retry_fails() {
 	let pov_recovery = PoVRecovery::<Block, _, _>::new(
 		Box::new(recovery_subsystem_tx),
 		recovery_delay_range,
+		RecoveryConfig::default(),
 		Arc::new(parachain_client),
 		Box::new(parachain_import_queue),
 		relay_chain_client,
 		ParaId::new(1000),
 		explicit_recovery_chan_rx,
 		Arc::new(DummySyncOracle::default()),
+		None,
 	);
 
 	task::spawn(pov_recovery.run());
@@ -935,12 +950,14 @@ async fn single_pending_candidate_recovery_irrecoverable_error() {
 	let pov_recovery = PoVRecovery::<Block, _, _>::new(
 		Box::new(recovery_subsystem_tx),
 		recovery_delay_range,
+		RecoveryConfig::default(),
 		Arc::new(parachain_client),
 		Box::new(parachain_import_queue),
 		relay_chain_client,
 		ParaId::new(1000),
 		explicit_recovery_chan_rx,
 		Arc::new(DummySyncOracle::default()),
+		None,
 	);
 
 	task::spawn(pov_recovery.run());
@@ -1007,12 +1024,14 @@ async fn pending_candidates_recovery_skipped_while_syncing() {
 	let pov_recovery = PoVRecovery::<Block, _, _>::new(
 		Box::new(recovery_subsystem_tx),
 		recovery_delay_range,
+		RecoveryConfig::default(),
 		Arc::new(parachain_client),
 		Box::new(parachain_import_queue),
 		relay_chain_client,
 		ParaId::new(1000),
 		explicit_recovery_chan_rx,
 		Arc::new(DummySyncOracle::new(true)),
+		None,
 	);
 
 	task::spawn(pov_recovery.run());
@@ -1056,12 +1075,14 @@ async fn candidate_is_imported_while_awaiting_recovery() {
 	let pov_recovery = PoVRecovery::<Block, _, _>::new(
 		Box::new(recovery_subsystem_tx),
 		recovery_delay_range,
+		RecoveryConfig::default(),
 		Arc::new(parachain_client),
 		Box::new(parachain_import_queue),
 		relay_chain_client,
 		ParaId::new(1000),
 		explicit_recovery_chan_rx,
 		Arc::new(DummySyncOracle::default()),
+		None,
 	);
 
 	task::spawn(pov_recovery.run());
@@ -1157,12 +1178,14 @@ async fn candidate_is_finalized_while_awaiting_recovery() {
 	let pov_recovery = PoVRecovery::<Block, _, _>::new(
 		Box::new(recovery_subsystem_tx),
 		recovery_delay_range,
+		RecoveryConfig::default(),
 		Arc::new(parachain_client),
 		Box::new(parachain_import_queue),
 		relay_chain_client,
 		ParaId::new(1000),
 		explicit_recovery_chan_rx,
 		Arc::new(DummySyncOracle::default()),
+		None,
 	);
 
 	task::spawn(pov_recovery.run());
@@ -1259,12 +1282,14 @@ async fn chained_recovery_success() {
 	let pov_recovery = PoVRecovery::<Block, _, _>::new(
 		Box::new(recovery_subsystem_tx),
 		recovery_delay_range,
+		RecoveryConfig::default(),
 		Arc::new(parachain_client),
 		Box::new(parachain_import_queue),
 		relay_chain_client,
 		ParaId::new(1000),
 		explicit_recovery_chan_rx,
 		Arc::new(DummySyncOracle::default()),
+		None,
 	);
 
 	task::spawn(pov_recovery.run());
@@ -1366,12 +1391,14 @@ async fn chained_recovery_child_succeeds_before_parent() {
 	let pov_recovery = PoVRecovery::<Block, _, _>::new(
 		Box::new(recovery_subsystem_tx),
 		recovery_delay_range,
+		RecoveryConfig::default(),
 		Arc::new(parachain_client),
 		Box::new(parachain_import_queue),
 		relay_chain_client,
 		ParaId::new(1000),
 		explicit_recovery_chan_rx,
 		Arc::new(DummySyncOracle::default()),
+		None,
 	);
 
 	task::spawn(pov_recovery.run());
@@ -1428,3 +1455,275 @@ async fn chained_recovery_child_succeeds_before_parent() {
 	// No more import requests received
 	assert_matches!(import_requests_rx.next().timeout(Duration::from_millis(100)).await, None);
 }
+
+fn occupied_core(
+	candidate: &CommittedCandidateReceipt,
+	group_responsible: GroupIndex,
+) -> CoreState<PHash, NumberFor<Block>> {
+	CoreState::Occupied(OccupiedCore {
+		next_up_on_available: None,
+		occupied_since: 1,
+		time_out_at: 10,
+		next_up_on_time_out: None,
+		availability: Default::default(),
+		group_responsible,
+		candidate_hash: candidate.hash(),
+		candidate_descriptor: candidate.descriptor.clone(),
+	})
+}
+
+#[rstest]
+#[case(RecoveryStrategy::Chunks, None, None)]
+#[case(RecoveryStrategy::PreferBackers, Some(GroupIndex(3)), None)]
+#[case(RecoveryStrategy::SystematicChunks, None, Some(CoreIndex(1)))]
+#[case(RecoveryStrategy::BackersThenSystematicChunks, Some(GroupIndex(3)), Some(CoreIndex(1)))]
+#[tokio::test]
+async fn recovery_strategy_hints(
+	#[case] strategy: RecoveryStrategy,
+	#[case] expected_backing_group: Option<GroupIndex>,
+	#[case] expected_core_index: Option<CoreIndex>,
+) {
+	sp_tracing::init_for_tests();
+
+	let (recovery_subsystem_tx, mut recovery_subsystem_rx) =
+		AvailabilityRecoverySubsystemHandle::new();
+	let recovery_delay_range =
+		RecoveryDelayRange { min: Duration::from_millis(0), max: Duration::from_millis(0) };
+	let (_explicit_recovery_chan_tx, explicit_recovery_chan_rx) = mpsc::channel(10);
+	let candidates = make_candidate_chain(1..2);
+	let candidate_hash = candidates[0].hash();
+
+	let relay_chain_client = Relaychain::new(vec![(
+		PHeader {
+			parent_hash: PHash::from_low_u64_be(0),
+			number: 1,
+			state_root: PHash::random(),
+			extrinsics_root: PHash::random(),
+			digest: Default::default(),
+		},
+		candidates.clone(),
+	)]);
+	// The candidate occupies the second core.
+	relay_chain_client.set_availability_cores(vec![
+		CoreState::Free,
+		occupied_core(&candidates[0], GroupIndex(3)),
+	]);
+
+	let mut known_blocks = HashMap::new();
+	known_blocks.insert(GENESIS_HASH, BlockStatus::InChainWithState);
+	let (parachain_client, _import_notifications_tx, _finality_notifications_tx) =
+		ParachainClient::new(vec![dummy_usage_info(0)], Arc::new(Mutex::new(known_blocks)));
+	let (parachain_import_queue, _import_requests_rx) = ParachainImportQueue::new();
+
+	let pov_recovery = PoVRecovery::<Block, _, _>::new(
+		Box::new(recovery_subsystem_tx),
+		recovery_delay_range,
+		RecoveryConfig { strategy, ..Default::default() },
+		Arc::new(parachain_client),
+		Box::new(parachain_import_queue),
+		relay_chain_client,
+		ParaId::new(1000),
+		explicit_recovery_chan_rx,
+		Arc::new(DummySyncOracle::default()),
+		None,
+	);
+
+	task::spawn(pov_recovery.run());
+
+	assert_matches!(
+		recovery_subsystem_rx.next().await,
+		Some(AvailabilityRecoveryMessage::RecoverAvailableData(
+			receipt,
+			session_index,
+			backing_group,
+			core_index,
+			_response_tx
+		)) => {
+			assert_eq!(receipt.hash(), candidate_hash);
+			assert_eq!(session_index, TEST_SESSION_INDEX);
+			assert_eq!(backing_group, expected_backing_group);
+			assert_eq!(core_index, expected_core_index);
+		}
+	);
+}
+
+#[tokio::test]
+async fn parallel_recoveries_are_bounded() {
+	sp_tracing::init_for_tests();
+
+	let (recovery_subsystem_tx, mut recovery_subsystem_rx) =
+		AvailabilityRecoverySubsystemHandle::new();
+	let recovery_delay_range =
+		RecoveryDelayRange { min: Duration::from_millis(0), max: Duration::from_millis(0) };
+	let (_explicit_recovery_chan_tx, explicit_recovery_chan_rx) = mpsc::channel(10);
+	let candidates = make_candidate_chain(1..3);
+	let headers = candidates
+		.iter()
+		.map(|candidate| Header::decode(&mut &candidate.commitments.head_data.0[..]).unwrap())
+		.collect::<Vec<_>>();
+	let candidate_hashes = candidates.iter().map(|candidate| candidate.hash()).collect::<Vec<_>>();
+
+	let relay_chain_client = Relaychain::new(vec![(
+		PHeader {
+			parent_hash: PHash::from_low_u64_be(0),
+			number: 1,
+			state_root: PHash::random(),
+			extrinsics_root: PHash::random(),
+			digest: Default::default(),
+		},
+		// 2 pending candidates
+		candidates,
+	)]);
+	let mut known_blocks = HashMap::new();
+	known_blocks.insert(GENESIS_HASH, BlockStatus::InChainWithState);
+	let (parachain_client, _import_notifications_tx, _finality_notifications_tx) =
+		ParachainClient::new(vec![dummy_usage_info(0)], Arc::new(Mutex::new(known_blocks)));
+	let (parachain_import_queue, _import_requests_rx) = ParachainImportQueue::new();
+
+	let pov_recovery = PoVRecovery::<Block, _, _>::new(
+		Box::new(recovery_subsystem_tx),
+		recovery_delay_range,
+		RecoveryConfig { max_parallel_recoveries: 1, ..Default::default() },
+		Arc::new(parachain_client),
+		Box::new(parachain_import_queue),
+		relay_chain_client,
+		ParaId::new(1000),
+		explicit_recovery_chan_rx,
+		Arc::new(DummySyncOracle::default()),
+		None,
+	);
+
+	task::spawn(pov_recovery.run());
+
+	let response_tx = assert_matches!(
+		recovery_subsystem_rx.next().await,
+		Some(AvailabilityRecoveryMessage::RecoverAvailableData(
+			receipt,
+			_,
+			None,
+			None,
+			response_tx
+		)) => {
+			assert_eq!(receipt.hash(), candidate_hashes[0]);
+			response_tx
+		}
+	);
+
+	// The second recovery is only started once the first one has finished.
+	assert_matches!(recovery_subsystem_rx.next().timeout(Duration::from_millis(100)).await, None);
+
+	response_tx
+		.send(Ok(AvailableData {
+			pov: Arc::new(PoV {
+				block_data: ParachainBlockData::<Block>::new(
+					headers[0].clone(),
+					vec![],
+					CompactProof { encoded_nodes: vec![] },
+				)
+				.encode()
+				.into(),
+			}),
+			validation_data: dummy_pvd(),
+		}))
+		.unwrap();
+
+	assert_matches!(
+		recovery_subsystem_rx.next().await,
+		Some(AvailabilityRecoveryMessage::RecoverAvailableData(receipt, _, None, None, _)) => {
+			assert_eq!(receipt.hash(), candidate_hashes[1]);
+		}
+	);
+}
+
+#[tokio::test]
+async fn recovery_queue_prefers_best_chain_and_evicts_other_blocks() {
+	let recovery_delay_range =
+		RecoveryDelayRange { min: Duration::from_millis(0), max: Duration::from_millis(0) };
+	let mut queue = RecoveryQueue::<Block>::new(recovery_delay_range, 2);
+
+	let fork_block = PHash::repeat_byte(1);
+	let best_block = PHash::repeat_byte(2);
+	let next_best_block = PHash::repeat_byte(3);
+	let another_fork_block = PHash::repeat_byte(4);
+
+	assert_eq!(queue.push_recovery(fork_block, RecoveryPriority::Other), None);
+	assert_eq!(queue.push_recovery(best_block, RecoveryPriority::BestChain), None);
+
+	// The queue is full, the fork block makes room for the best chain block.
+	assert_eq!(queue.push_recovery(next_best_block, RecoveryPriority::BestChain), Some(fork_block));
+	// Blocks with a lower priority are rejected while the queue is full.
+	assert_eq!(
+		queue.push_recovery(another_fork_block, RecoveryPriority::Other),
+		Some(another_fork_block)
+	);
+	assert_eq!(queue.len(), 2);
+
+	assert_eq!(queue.next_recovery().await, best_block);
+	assert_eq!(queue.next_recovery().await, next_best_block);
+	assert_eq!(queue.len(), 0);
+}
+
+#[tokio::test]
+async fn deferred_recoveries_stay_bounded_and_prioritized() {
+	let recovery_delay_range =
+		RecoveryDelayRange { min: Duration::from_millis(0), max: Duration::from_millis(0) };
+	let mut queue = RecoveryQueue::<Block>::new(recovery_delay_range, 2);
+
+	let fork_block = PHash::repeat_byte(1);
+	let best_block = PHash::repeat_byte(2);
+	let another_fork_block = PHash::repeat_byte(3);
+
+	assert_eq!(queue.push_recovery(fork_block, RecoveryPriority::Other), None);
+	assert_eq!(queue.push_recovery(best_block, RecoveryPriority::BestChain), None);
+
+	// Both recoveries are signaled, but could not be started yet.
+	let first = queue.next_recovery().await;
+	let second = queue.next_recovery().await;
+	assert_eq!((first, second), (best_block, fork_block));
+	queue.defer_recovery(second, RecoveryPriority::Other);
+	queue.defer_recovery(first, RecoveryPriority::BestChain);
+	assert_eq!(queue.len(), 2);
+
+	// Deferred blocks still count against the bound of the queue.
+	assert_eq!(
+		queue.push_recovery(another_fork_block, RecoveryPriority::Other),
+		Some(another_fork_block)
+	);
+
+	// All deferred recoveries can be started at once, best chain blocks first.
+	assert_eq!(queue.pop_ready(), Some(best_block));
+	assert_eq!(queue.pop_ready(), Some(fork_block));
+	assert_eq!(queue.pop_ready(), None);
+	assert_eq!(queue.len(), 0);
+}
+
+#[tokio::test]
+async fn evicting_deferred_recoveries_releases_their_slots() {
+	let recovery_delay_range =
+		RecoveryDelayRange { min: Duration::from_millis(0), max: Duration::from_millis(0) };
+	let mut queue = RecoveryQueue::<Block>::new(recovery_delay_range, 2);
+
+	let first_block = PHash::repeat_byte(1);
+	let second_block = PHash::repeat_byte(2);
+	let third_block = PHash::repeat_byte(3);
+	let fourth_block = PHash::repeat_byte(4);
+
+	assert_eq!(queue.push_recovery(first_block, RecoveryPriority::BestChain), None);
+	assert_eq!(queue.push_recovery(second_block, RecoveryPriority::BestChain), None);
+
+	// Both recoveries are signaled, but could not be started yet.
+	assert_eq!(queue.next_recovery().await, first_block);
+	assert_eq!(queue.next_recovery().await, second_block);
+	queue.defer_recovery(first_block, RecoveryPriority::BestChain);
+	queue.defer_recovery(second_block, RecoveryPriority::BestChain);
+
+	// Pushing past the bound evicts deferred blocks, the new blocks wait for their own slot.
+	assert_eq!(queue.push_recovery(third_block, RecoveryPriority::BestChain), Some(second_block));
+	assert_eq!(queue.push_recovery(fourth_block, RecoveryPriority::BestChain), Some(first_block));
+	assert_eq!(queue.len(), 2);
+	assert_eq!(queue.pop_ready(), None);
+
+	assert_eq!(queue.next_recovery().await, third_block);
+	assert_eq!(queue.next_recovery().await, fourth_block);
+	assert_eq!(queue.len(), 0);
+}
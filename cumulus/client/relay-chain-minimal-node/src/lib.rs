@@ -17,7 +17,9 @@
 use collator_overseer::NewMinimalNode;
 
 use cumulus_relay_chain_interface::{RelayChainError, RelayChainInterface, RelayChainResult};
-use cumulus_relay_chain_rpc_interface::{RelayChainRpcClient, RelayChainRpcInterface, Url};
+use cumulus_relay_chain_rpc_interface::{
+	RelayChainDataCache, RelayChainRpcClient, RelayChainRpcInterface, Url,
+};
 use network::build_collator_network;
 use polkadot_network_bridge::{peer_sets_info, IsAuthority};
 use polkadot_node_network_protocol::{
@@ -51,6 +53,9 @@ pub use blockchain_rpc_client::BlockChainRpcClient;
 
 const LOG_TARGET: &str = "minimal-relaychain-node";
 
+/// Name of the file in the relay chain data directory that holds the relay chain data cache.
+const RELAY_CHAIN_DATA_CACHE_FILE: &str = "relay-chain-data-cache";
+
 fn build_authority_discovery_service<Block: BlockT>(
 	task_manager: &TaskManager,
 	client: Arc<BlockChainRpcClient>,
@@ -96,6 +101,13 @@ async fn build_interface(
 	client: RelayChainRpcClient,
 ) -> RelayChainResult<(Arc<(dyn RelayChainInterface + 'static)>, Option<CollatorPair>)> {
 	let collator_pair = CollatorPair::generate().0;
+
+	// Reuse the relay chain data fetched before a restart, to become productive faster.
+	let cache =
+		RelayChainDataCache::load(polkadot_config.data_path.join(RELAY_CHAIN_DATA_CACHE_FILE));
+	let (client, cache_worker) = client.with_cache(cache).await?;
+	task_manager.spawn_handle().spawn("relay-chain-data-cache", None, cache_worker);
+
 	let blockchain_rpc_client = Arc::new(BlockChainRpcClient::new(client.clone()));
 	let collator_node = match polkadot_config.network.network_backend {
 		sc_network::config::NetworkBackendType::Libp2p =>
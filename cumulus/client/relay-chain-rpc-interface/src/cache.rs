@@ -0,0 +1,499 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Cumulus.
+
+// Cumulus is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Cumulus is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Cumulus.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Persistent cache for relay chain data that does not change once it is known.
+//!
+//! Headers and authority discovery keys are looked up by block hash and can therefore always be
+//! served from the cache. Block numbers are only resolved to hashes for finalized blocks and
+//! session info is only cached for sessions that started on the finalized chain. On startup, the
+//! cache is checked against the genesis and finalized head of the relay chain before it is used.
+
+use codec::{Decode, Encode};
+use cumulus_primitives_core::relay_chain::{
+	BlockNumber, Hash as RelayHash, Header as RelayHeader, SessionIndex, SessionInfo,
+};
+use futures::StreamExt;
+use schnellru::{ByLength, LruMap};
+use sp_authority_discovery::AuthorityId;
+use sp_runtime::traits::Header as HeaderT;
+use std::{
+	collections::BTreeMap,
+	path::{Path, PathBuf},
+	sync::{Arc, Mutex},
+};
+
+use crate::RelayChainRpcClient;
+
+const LOG_TARGET: &str = "relay-chain-data-cache";
+
+/// Version of the on-disk format. Files with a different version are discarded.
+const CACHE_VERSION: u32 = 1;
+/// Maximum number of cached headers.
+const MAX_HEADERS: u32 = 1024;
+/// Maximum number of finalized block numbers that can be resolved from the cache.
+const MAX_FINALIZED: usize = 1024;
+/// Maximum number of cached session infos.
+const MAX_SESSIONS: usize = 6;
+/// Maximum number of blocks for which authority discovery keys are cached.
+const MAX_AUTHORITY_SETS: u32 = 16;
+/// The cache is written to disk after this many finality notifications.
+const PERSIST_INTERVAL: u32 = 16;
+
+/// The on-disk representation of the cache.
+#[derive(Encode, Decode, Default)]
+struct PersistedCache {
+	version: u32,
+	genesis_hash: Option<RelayHash>,
+	finalized_session: Option<SessionIndex>,
+	headers: Vec<RelayHeader>,
+	finalized: Vec<(BlockNumber, RelayHash)>,
+	session_info: Vec<(SessionIndex, SessionInfo)>,
+	authorities: Vec<(RelayHash, Vec<AuthorityId>)>,
+}
+
+struct CacheInner {
+	genesis_hash: Option<RelayHash>,
+	/// The index of the session following the last finalized block we know of.
+	finalized_session: Option<SessionIndex>,
+	headers: LruMap<RelayHash, RelayHeader>,
+	finalized: BTreeMap<BlockNumber, RelayHash>,
+	session_info: BTreeMap<SessionIndex, SessionInfo>,
+	authorities: LruMap<RelayHash, Vec<AuthorityId>>,
+	notifications_since_persist: u32,
+}
+
+impl Default for CacheInner {
+	fn default() -> Self {
+		Self {
+			genesis_hash: None,
+			finalized_session: None,
+			headers: LruMap::new(ByLength::new(MAX_HEADERS)),
+			finalized: BTreeMap::new(),
+			session_info: BTreeMap::new(),
+			authorities: LruMap::new(ByLength::new(MAX_AUTHORITY_SETS)),
+			notifications_since_persist: 0,
+		}
+	}
+}
+
+impl CacheInner {
+	fn from_persisted(persisted: PersistedCache) -> Self {
+		let mut inner = Self {
+			genesis_hash: persisted.genesis_hash,
+			finalized_session: persisted.finalized_session,
+			..Default::default()
+		};
+		// The persisted entries are ordered from the most to the least recently used ones.
+		for header in persisted.headers.into_iter().rev() {
+			inner.headers.insert(header.hash(), header);
+		}
+		for (hash, authorities) in persisted.authorities.into_iter().rev() {
+			inner.authorities.insert(hash, authorities);
+		}
+		inner.finalized = persisted.finalized.into_iter().collect();
+		inner.session_info = persisted.session_info.into_iter().collect();
+		inner
+	}
+
+	fn to_persisted(&self) -> PersistedCache {
+		PersistedCache {
+			version: CACHE_VERSION,
+			genesis_hash: self.genesis_hash,
+			finalized_session: self.finalized_session,
+			headers: self.headers.iter().map(|(_, header)| header.clone()).collect(),
+			finalized: self.finalized.iter().map(|(number, hash)| (*number, *hash)).collect(),
+			session_info: self
+				.session_info
+				.iter()
+				.map(|(index, info)| (*index, info.clone()))
+				.collect(),
+			authorities: self
+				.authorities
+				.iter()
+				.map(|(hash, authorities)| (*hash, authorities.clone()))
+				.collect(),
+		}
+	}
+
+	fn note_finalized(&mut self, header: &RelayHeader) {
+		let mut number = header.number;
+		let mut hash = header.hash();
+		// Finality may skip blocks, so we fill the gaps with the ancestors we know of.
+		while self.finalized.insert(number, hash).is_none() {
+			let Some(header) = self.headers.peek(&hash) else { break };
+			if number == 0 {
+				break
+			}
+			hash = header.parent_hash;
+			number -= 1;
+		}
+
+		while self.finalized.len() > MAX_FINALIZED {
+			self.finalized.pop_first();
+		}
+	}
+}
+
+/// Cache for relay chain data that is reused across restarts.
+pub struct RelayChainDataCache {
+	path: Option<PathBuf>,
+	inner: Mutex<CacheInner>,
+}
+
+impl RelayChainDataCache {
+	/// Load the cache from the file at `path`.
+	///
+	/// Starts with an empty cache if the file does not exist or could not be decoded. The cache
+	/// is written back to the same file.
+	pub fn load(path: PathBuf) -> Self {
+		let inner = match std::fs::read(&path) {
+			Ok(data) => match PersistedCache::decode(&mut &data[..]) {
+				Ok(persisted) if persisted.version == CACHE_VERSION =>
+					CacheInner::from_persisted(persisted),
+				Ok(persisted) => {
+					tracing::info!(
+						target: LOG_TARGET,
+						version = persisted.version,
+						"Discarding relay chain data cache with unknown version."
+					);
+					CacheInner::default()
+				},
+				Err(error) => {
+					tracing::warn!(
+						target: LOG_TARGET,
+						?error,
+						path = %path.display(),
+						"Discarding undecodable relay chain data cache."
+					);
+					CacheInner::default()
+				},
+			},
+			Err(error) if error.kind() == std::io::ErrorKind::NotFound => CacheInner::default(),
+			Err(error) => {
+				tracing::warn!(
+					target: LOG_TARGET,
+					?error,
+					path = %path.display(),
+					"Unable to read relay chain data cache."
+				);
+				CacheInner::default()
+			},
+		};
+
+		Self { path: Some(path), inner: Mutex::new(inner) }
+	}
+
+	/// Create a cache that is not persisted.
+	pub fn in_memory() -> Self {
+		Self { path: None, inner: Mutex::new(CacheInner::default()) }
+	}
+
+	/// Write the cache to disk.
+	pub fn persist(&self) {
+		let Some(path) = &self.path else { return };
+		let encoded = self.inner.lock().expect("Cache lock is poisoned").to_persisted().encode();
+		if let Err(error) = write_atomically(path, &encoded) {
+			tracing::warn!(
+				target: LOG_TARGET,
+				?error,
+				path = %path.display(),
+				"Unable to persist relay chain data cache."
+			);
+		}
+	}
+
+	/// Check the cache against the relay chain and drop everything that can not be trusted.
+	///
+	/// - `genesis_hash`: The genesis hash of the relay chain. If it differs, the cache belongs to
+	///   another chain and is cleared.
+	/// - `canonical_hash`: The canonical hash of the relay chain at the number of the highest
+	///   cached finalized block, or `None` if the relay chain did not finalize that block yet.
+	/// - `finalized_session`: The session following the current finalized relay chain head.
+	pub fn verify(
+		&self,
+		genesis_hash: RelayHash,
+		canonical_hash: Option<RelayHash>,
+		finalized_session: SessionIndex,
+	) {
+		let mut inner = self.inner.lock().expect("Cache lock is poisoned");
+
+		if inner.genesis_hash.map_or(false, |hash| hash != genesis_hash) {
+			tracing::info!(
+				target: LOG_TARGET,
+				"Relay chain data cache belongs to a different chain, clearing it."
+			);
+			*inner = CacheInner::default();
+		}
+		inner.genesis_hash = Some(genesis_hash);
+
+		let highest_finalized = inner.finalized.last_key_value().map(|(_, hash)| *hash);
+		if highest_finalized.is_some() && highest_finalized != canonical_hash {
+			tracing::info!(
+				target: LOG_TARGET,
+				"Cached finalized blocks are not canonical, dropping them."
+			);
+			inner.finalized.clear();
+		}
+
+		inner.session_info.retain(|index, _| *index < finalized_session);
+		inner.finalized_session = Some(finalized_session);
+	}
+
+	/// The highest finalized block number we know the hash of.
+	pub fn highest_finalized_number(&self) -> Option<BlockNumber> {
+		let inner = self.inner.lock().expect("Cache lock is poisoned");
+		inner.finalized.last_key_value().map(|(number, _)| *number)
+	}
+
+	/// Get the header with the given `hash`.
+	pub fn header(&self, hash: &RelayHash) -> Option<RelayHeader> {
+		self.inner.lock().expect("Cache lock is poisoned").headers.get(hash).cloned()
+	}
+
+	/// Insert a header.
+	pub fn insert_header(&self, header: RelayHeader) {
+		self.inner
+			.lock()
+			.expect("Cache lock is poisoned")
+			.headers
+			.insert(header.hash(), header);
+	}
+
+	/// Get the hash of the finalized block with the given `number`.
+	pub fn finalized_hash(&self, number: BlockNumber) -> Option<RelayHash> {
+		let inner = self.inner.lock().expect("Cache lock is poisoned");
+		if number == 0 {
+			return inner.genesis_hash
+		}
+		inner.finalized.get(&number).copied()
+	}
+
+	/// Get the session info of the session with the given `index`.
+	pub fn session_info(&self, index: SessionIndex) -> Option<SessionInfo> {
+		self.inner
+			.lock()
+			.expect("Cache lock is poisoned")
+			.session_info
+			.get(&index)
+			.cloned()
+	}
+
+	/// Insert the session info of the session with the given `index`.
+	///
+	/// Only sessions that started on the finalized chain are cached.
+	pub fn insert_session_info(&self, index: SessionIndex, info: SessionInfo) {
+		let mut inner = self.inner.lock().expect("Cache lock is poisoned");
+		if inner.finalized_session.map_or(true, |finalized| index >= finalized) {
+			return
+		}
+
+		inner.session_info.insert(index, info);
+		while inner.session_info.len() > MAX_SESSIONS {
+			inner.session_info.pop_first();
+		}
+	}
+
+	/// Get the authority discovery keys at the block with the given `hash`.
+	pub fn authorities(&self, hash: &RelayHash) -> Option<Vec<AuthorityId>> {
+		self.inner
+			.lock()
+			.expect("Cache lock is poisoned")
+			.authorities
+			.get(hash)
+			.cloned()
+	}
+
+	/// Insert the authority discovery keys at the block with the given `hash`.
+	pub fn insert_authorities(&self, hash: RelayHash, authorities: Vec<AuthorityId>) {
+		self.inner
+			.lock()
+			.expect("Cache lock is poisoned")
+			.authorities
+			.insert(hash, authorities);
+	}
+
+	/// Note a new finalized relay chain block.
+	///
+	/// Returns `true` if the cache should be persisted.
+	pub fn note_finalized(&self, header: RelayHeader, session: Option<SessionIndex>) -> bool {
+		let mut inner = self.inner.lock().expect("Cache lock is poisoned");
+		inner.headers.insert(header.hash(), header.clone());
+		inner.note_finalized(&header);
+		if let Some(session) = session {
+			inner.finalized_session = Some(session);
+		}
+
+		inner.notifications_since_persist += 1;
+		if inner.notifications_since_persist >= PERSIST_INTERVAL {
+			inner.notifications_since_persist = 0;
+			true
+		} else {
+			false
+		}
+	}
+}
+
+/// Write `data` to a temporary file and move it to `path` afterwards, so that a crash does not
+/// leave a partially written cache behind.
+fn write_atomically(path: &Path, data: &[u8]) -> std::io::Result<()> {
+	if let Some(parent) = path.parent() {
+		std::fs::create_dir_all(parent)?;
+	}
+	let tmp_path = path.with_extension("tmp");
+	std::fs::write(&tmp_path, data)?;
+	std::fs::rename(tmp_path, path)
+}
+
+/// Keep the cache up to date with the finalized relay chain and persist it regularly.
+pub(crate) async fn run_cache_worker(client: RelayChainRpcClient, cache: Arc<RelayChainDataCache>) {
+	let mut finalized_heads = match client.get_finalized_heads_stream() {
+		Ok(stream) => stream,
+		Err(error) => {
+			tracing::error!(
+				target: LOG_TARGET,
+				?error,
+				"Unable to subscribe to finalized relay chain heads, cache will not be updated."
+			);
+			return
+		},
+	};
+
+	while let Some(header) = finalized_heads.next().await {
+		let session = client
+			.parachain_host_session_index_for_child(header.hash())
+			.await
+			.map_err(|error| {
+				tracing::debug!(
+					target: LOG_TARGET,
+					?error,
+					"Unable to fetch session index of finalized relay chain block."
+				)
+			})
+			.ok();
+
+		if cache.note_finalized(header, session) {
+			cache.persist();
+		}
+	}
+
+	cache.persist();
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_runtime::generic::Digest;
+
+	fn header(number: BlockNumber, parent_hash: RelayHash) -> RelayHeader {
+		RelayHeader {
+			parent_hash,
+			number,
+			state_root: Default::default(),
+			extrinsics_root: Default::default(),
+			digest: Digest::default(),
+		}
+	}
+
+	fn chain(len: BlockNumber) -> Vec<RelayHeader> {
+		let mut headers = vec![header(0, Default::default())];
+		for number in 1..len {
+			let parent_hash = headers.last().unwrap().hash();
+			headers.push(header(number, parent_hash));
+		}
+		headers
+	}
+
+	#[test]
+	fn finalized_gaps_are_filled_from_known_ancestors() {
+		let cache = RelayChainDataCache::in_memory();
+		let headers = chain(5);
+		headers.iter().take(4).for_each(|header| cache.insert_header(header.clone()));
+
+		cache.note_finalized(headers[4].clone(), None);
+
+		for header in &headers {
+			assert_eq!(cache.finalized_hash(header.number), Some(header.hash()));
+		}
+	}
+
+	#[test]
+	fn cache_survives_restart() {
+		let dir = tempfile::tempdir().unwrap();
+		let path = dir.path().join("cache");
+		let headers = chain(3);
+		let genesis_hash = headers[0].hash();
+
+		let cache = RelayChainDataCache::load(path.clone());
+		cache.verify(genesis_hash, None, 0);
+		headers.iter().for_each(|header| cache.insert_header(header.clone()));
+		cache.note_finalized(headers[2].clone(), Some(2));
+		cache.insert_session_info(1, dummy_session_info());
+		cache.insert_authorities(headers[2].hash(), Vec::new());
+		cache.persist();
+
+		let cache = RelayChainDataCache::load(path);
+		cache.verify(genesis_hash, Some(headers[2].hash()), 2);
+		assert_eq!(cache.finalized_hash(0), Some(genesis_hash));
+		assert_eq!(cache.highest_finalized_number(), Some(2));
+		assert_eq!(cache.header(&headers[1].hash()), Some(headers[1].clone()));
+		assert_eq!(cache.finalized_hash(1), Some(headers[1].hash()));
+		assert_eq!(cache.session_info(1), Some(dummy_session_info()));
+		assert_eq!(cache.authorities(&headers[2].hash()), Some(Vec::new()));
+	}
+
+	#[test]
+	fn verify_drops_untrusted_data() {
+		let headers = chain(3);
+		let genesis_hash = headers[0].hash();
+		let cache = RelayChainDataCache::in_memory();
+		cache.verify(genesis_hash, None, 2);
+		headers.iter().for_each(|header| cache.insert_header(header.clone()));
+		cache.note_finalized(headers[2].clone(), None);
+		cache.insert_session_info(1, dummy_session_info());
+
+		// Sessions that did not start on the finalized chain are not cached.
+		cache.insert_session_info(2, dummy_session_info());
+		assert_eq!(cache.session_info(2), None);
+
+		// The cached finalized blocks are not canonical.
+		cache.verify(genesis_hash, Some(Default::default()), 1);
+		assert_eq!(cache.finalized_hash(2), None);
+		assert_eq!(cache.session_info(1), None);
+		assert_eq!(cache.header(&headers[2].hash()), Some(headers[2].clone()));
+
+		// The cache belongs to a different chain.
+		cache.verify(Default::default(), None, 1);
+		assert_eq!(cache.header(&headers[2].hash()), None);
+	}
+
+	fn dummy_session_info() -> SessionInfo {
+		SessionInfo {
+			active_validator_indices: Vec::new(),
+			random_seed: [0u8; 32],
+			dispute_period: 6,
+			validators: Default::default(),
+			discovery_keys: Vec::new(),
+			assignment_keys: Vec::new(),
+			validator_groups: Default::default(),
+			n_cores: 0,
+			zeroth_delay_tranche_width: 0,
+			relay_vrf_modulo_samples: 0,
+			n_delay_tranches: 0,
+			no_show_slots: 0,
+			needed_approvals: 0,
+		}
+	}
+}
@@ -39,12 +39,14 @@ use std::{collections::btree_map::BTreeMap, pin::Pin};
 use cumulus_primitives_core::relay_chain::BlockId;
 pub use url::Url;
 
+mod cache;
 mod light_client_worker;
 mod metrics;
 mod reconnecting_ws_client;
 mod rpc_client;
 mod tokio_platform;
 
+pub use cache::RelayChainDataCache;
 pub use rpc_client::{
 	create_client_and_start_light_client_worker, create_client_and_start_worker,
 	RelayChainRpcClient,
@@ -25,7 +25,11 @@ use jsonrpsee::{
 use prometheus::Registry;
 use serde::{de::DeserializeOwned, Serialize};
 use serde_json::Value as JsonValue;
-use std::collections::{btree_map::BTreeMap, VecDeque};
+use std::{
+	collections::{btree_map::BTreeMap, VecDeque},
+	future::Future,
+	sync::Arc,
+};
 use tokio::sync::mpsc::Sender as TokioSender;
 
 use codec::{Decode, Encode};
@@ -57,6 +61,7 @@ use sp_storage::StorageKey;
 use sp_version::RuntimeVersion;
 
 use crate::{
+	cache::{run_cache_worker, RelayChainDataCache},
 	light_client_worker::{build_smoldot_client, LightClientRpcWorker},
 	metrics::RelaychainRpcMetrics,
 	reconnecting_ws_client::ReconnectingWebsocketWorker,
@@ -136,6 +141,8 @@ pub struct RelayChainRpcClient {
 	/// Sender to send messages to the worker.
 	worker_channel: TokioSender<RpcDispatcherMessage>,
 	metrics: Option<RelaychainRpcMetrics>,
+	/// Cache for relay chain data that does not change once it is known.
+	cache: Option<Arc<RelayChainDataCache>>,
 }
 
 impl RelayChainRpcClient {
@@ -153,9 +160,47 @@ impl RelayChainRpcClient {
 				.and_then(|inner| RelaychainRpcMetrics::register(inner).map_err(|err| {
 					tracing::warn!(target: LOG_TARGET, error = %err, "Unable to instantiate the RPC client metrics, continuing w/o metrics setup.");
 				}).ok()),
+			cache: None,
 		}
 	}
 
+	/// Serve headers, finalized block hashes, session info and authority discovery keys from
+	/// `cache` where possible.
+	///
+	/// The cache is checked against the genesis and the finalized head of the relay chain first.
+	/// Returns the client together with a future that keeps the cache up to date and persists it.
+	/// The future needs to be spawned.
+	pub async fn with_cache(
+		mut self,
+		cache: RelayChainDataCache,
+	) -> RelayChainResult<(Self, impl Future<Output = ()> + Send + 'static)> {
+		let genesis_hash = self.chain_get_block_hash(Some(0)).await?.ok_or_else(|| {
+			RelayChainError::GenericError("Unable to fetch relay chain genesis hash.".to_string())
+		})?;
+		let finalized_hash = self.chain_get_finalized_head().await?;
+		let finalized_number = self
+			.chain_get_header(Some(finalized_hash))
+			.await?
+			.ok_or_else(|| {
+				RelayChainError::GenericError(
+					"Unable to fetch relay chain finalized header.".to_string(),
+				)
+			})?
+			.number;
+		let canonical_hash = match cache.highest_finalized_number() {
+			Some(number) if number <= finalized_number =>
+				self.chain_get_block_hash(Some(number)).await?,
+			_ => None,
+		};
+		let finalized_session = self.parachain_host_session_index_for_child(finalized_hash).await?;
+		cache.verify(genesis_hash, canonical_hash, finalized_session);
+
+		let cache = Arc::new(cache);
+		self.cache = Some(cache.clone());
+		let worker = run_cache_worker(self.clone(), cache);
+		Ok((self, worker))
+	}
+
 	/// Same as `call_remote_runtime_function` but work on encoded data
 	pub async fn call_remote_runtime_function_encoded(
 		&self,
@@ -384,6 +429,15 @@ impl RelayChainRpcClient {
 		&self,
 		block_number: Option<BlockNumber>,
 	) -> Result<Option<RelayHash>, RelayChainError> {
+		if let Some(hash) = self
+			.cache
+			.as_ref()
+			.zip(block_number)
+			.and_then(|(cache, number)| cache.finalized_hash(number))
+		{
+			return Ok(Some(hash))
+		}
+
 		let params = rpc_params![block_number];
 		self.request("chain_getBlockHash", params).await
 	}
@@ -495,8 +549,17 @@ impl RelayChainRpcClient {
 		&self,
 		at: RelayHash,
 	) -> Result<Vec<sp_authority_discovery::AuthorityId>, RelayChainError> {
-		self.call_remote_runtime_function("AuthorityDiscoveryApi_authorities", at, None::<()>)
-			.await
+		if let Some(authorities) = self.cache.as_ref().and_then(|cache| cache.authorities(&at)) {
+			return Ok(authorities)
+		}
+
+		let authorities: Vec<sp_authority_discovery::AuthorityId> = self
+			.call_remote_runtime_function("AuthorityDiscoveryApi_authorities", at, None::<()>)
+			.await?;
+		if let Some(cache) = &self.cache {
+			cache.insert_authorities(at, authorities.clone());
+		}
+		Ok(authorities)
 	}
 
 	/// Fetch the validation code used by a para, making the given `OccupiedCoreAssumption`.
@@ -539,8 +602,17 @@ impl RelayChainRpcClient {
 		at: RelayHash,
 		index: SessionIndex,
 	) -> Result<Option<SessionInfo>, RelayChainError> {
-		self.call_remote_runtime_function("ParachainHost_session_info", at, Some(index))
-			.await
+		if let Some(info) = self.cache.as_ref().and_then(|cache| cache.session_info(index)) {
+			return Ok(Some(info))
+		}
+
+		let info: Option<SessionInfo> = self
+			.call_remote_runtime_function("ParachainHost_session_info", at, Some(index))
+			.await?;
+		if let Some((cache, info)) = self.cache.as_ref().zip(info.as_ref()) {
+			cache.insert_session_info(index, info.clone());
+		}
+		Ok(info)
 	}
 
 	/// Get the executor parameters for the given session, if stored
@@ -562,8 +634,18 @@ impl RelayChainRpcClient {
 		&self,
 		hash: Option<RelayHash>,
 	) -> Result<Option<RelayHeader>, RelayChainError> {
+		if let Some(header) =
+			self.cache.as_ref().zip(hash).and_then(|(cache, hash)| cache.header(&hash))
+		{
+			return Ok(Some(header))
+		}
+
 		let params = rpc_params![hash];
-		self.request("chain_getHeader", params).await
+		let header: Option<RelayHeader> = self.request("chain_getHeader", params).await?;
+		if let Some((cache, header)) = self.cache.as_ref().zip(header.as_ref()) {
+			cache.insert_header(header.clone());
+		}
+		Ok(header)
 	}
 
 	/// Get the receipt of a candidate pending availability. This returns `Some` for any paras
@@ -21,7 +21,9 @@
 use cumulus_client_cli::CollatorOptions;
 use cumulus_client_consensus_common::ParachainConsensus;
 use cumulus_client_network::{AssumeSybilResistance, RequireSecondedInBlockAnnounce};
-use cumulus_client_pov_recovery::{PoVRecovery, RecoveryDelayRange, RecoveryHandle};
+use cumulus_client_pov_recovery::{
+	PoVRecovery, RecoveryConfig, RecoveryDelayRange, RecoveryHandle,
+};
 use cumulus_primitives_core::{CollectCollationInfo, ParaId};
 use cumulus_relay_chain_inprocess_interface::build_inprocess_relay_chain;
 use cumulus_relay_chain_interface::{RelayChainInterface, RelayChainResult};
@@ -30,8 +32,10 @@ use cumulus_relay_chain_minimal_node::{
 };
 use futures::{channel::mpsc, StreamExt};
 use polkadot_primitives::{CollatorPair, OccupiedCoreAssumption};
+use prometheus_endpoint::Registry;
 use sc_client_api::{
-	Backend as BackendT, BlockBackend, BlockchainEvents, Finalizer, ProofProvider, UsageProvider,
+	AuxStore, Backend as BackendT, BlockBackend, BlockchainEvents, Finalizer, ProofProvider,
+	UsageProvider,
 };
 use sc_consensus::{
 	import_queue::{ImportQueue, ImportQueueService},
@@ -106,6 +110,10 @@ pub struct StartRelayChainTasksParams<'a, Block: BlockT, Client, RCInterface> {
 	pub relay_chain_slot_duration: Duration,
 	pub recovery_handle: Box<dyn RecoveryHandle>,
 	pub sync_service: Arc<SyncingService<Block>>,
+	/// Configuration of the PoV recovery, e.g. the recovery strategy.
+	pub pov_recovery_config: RecoveryConfig,
+	/// Registry used to expose the PoV recovery metrics.
+	pub prometheus_registry: Option<&'a Registry>,
 }
 
 /// Parameters given to [`start_full_node`].
@@ -177,6 +185,8 @@ where
 		relay_chain_slot_duration,
 		recovery_handle,
 		sync_service,
+		pov_recovery_config: Default::default(),
+		prometheus_registry: None,
 	})?;
 
 	#[allow(deprecated)]
@@ -214,6 +224,8 @@ pub fn start_relay_chain_tasks<Block, Client, Backend, RCInterface>(
 		relay_chain_slot_duration,
 		recovery_handle,
 		sync_service,
+		pov_recovery_config,
+		prometheus_registry,
 	}: StartRelayChainTasksParams<Block, Client, RCInterface>,
 ) -> sc_service::error::Result<()>
 where
@@ -270,12 +282,14 @@ where
 	let pov_recovery = PoVRecovery::new(
 		recovery_handle,
 		da_recovery_profile,
+		pov_recovery_config,
 		client.clone(),
 		import_queue,
 		relay_chain_interface.clone(),
 		para_id,
 		recovery_chan_rx,
 		sync_service,
+		prometheus_registry,
 	);
 
 	task_manager
@@ -327,6 +341,8 @@ where
 		recovery_handle,
 		sync_service,
 		da_recovery_profile: DARecoveryProfile::FullNode,
+		pov_recovery_config: Default::default(),
+		prometheus_registry: None,
 	})
 }
 
@@ -454,6 +470,7 @@ where
 		+ HeaderMetadata<Block, Error = sp_blockchain::Error>
 		+ BlockIdTo<Block, Error = sp_blockchain::Error>
 		+ ProofProvider<Block>
+		+ AuxStore
 		+ 'static,
 	Client::Api: CollectCollationInfo<Block>
 		+ sp_transaction_pool::runtime_api::TaggedTransactionQueue<Block>,
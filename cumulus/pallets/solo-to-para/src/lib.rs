@@ -18,6 +18,13 @@
 
 extern crate alloc;
 
+pub mod migrations;
+
+#[cfg(test)]
+mod mock;
+#[cfg(test)]
+mod tests;
+
 use alloc::vec::Vec;
 use cumulus_pallet_parachain_system as parachain_system;
 use frame_support::pallet_prelude::*;
@@ -0,0 +1,276 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Cumulus.
+
+// Cumulus is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Cumulus is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Cumulus.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Runtime migrations that turn the state of a solochain into the state of a parachain.
+//!
+//! They are meant to be executed by the first runtime of the chain that runs as a parachain,
+//! usually in this order:
+//!
+//! ```ignore
+//! parameter_types! {
+//! 	pub const GrandpaPalletName: &'static str = "Grandpa";
+//! 	pub const BabePalletName: &'static str = "Babe";
+//! }
+//!
+//! pub type Migrations = (
+//! 	// Remove the state of the solochain consensus.
+//! 	cumulus_pallet_solo_to_para::migrations::RemoveSoloConsensus<
+//! 		GrandpaPalletName,
+//! 		BabePalletName,
+//! 		RocksDbWeight,
+//! 	>,
+//! 	// Translate the session keys of the validators to the parachain session keys.
+//! 	cumulus_pallet_solo_to_para::migrations::MigrateSessionKeys<Runtime, SessionKeysUpgrade>,
+//! 	// Let the current validators author blocks with Aura.
+//! 	cumulus_pallet_solo_to_para::migrations::InitializeAuraAuthorities<
+//! 		Runtime,
+//! 		cumulus_pallet_solo_to_para::migrations::SessionAuraKeys<Runtime>,
+//! 	>,
+//! 	// Check that nothing was forgotten.
+//! 	cumulus_pallet_solo_to_para::migrations::EnsureParachainReady<
+//! 		Runtime,
+//! 		GrandpaPalletName,
+//! 		BabePalletName,
+//! 	>,
+//! );
+//! ```
+//!
+//! The head data and validation code for registering the chain on the relay chain can be
+//! exported with the `ExportSoloToParaCommand` of `cumulus-client-cli`.
+
+use alloc::vec::Vec;
+use core::{cell::Cell, marker::PhantomData};
+use frame_support::{
+	migrations::RemovePallet, pallet_prelude::*, storage::unhashed, traits::OnRuntimeUpgrade,
+};
+use sp_runtime::{
+	traits::{Member, OpaqueKeys},
+	RuntimeAppPublic,
+};
+
+#[cfg(feature = "try-runtime")]
+use sp_runtime::TryRuntimeError;
+
+const LOG_TARGET: &str = "runtime::solo-to-para";
+
+/// The well-known key under which GRANDPA stored its authorities before they were moved into the
+/// pallet storage.
+const LEGACY_GRANDPA_AUTHORITIES_KEY: &[u8] = b":grandpa_authorities";
+
+/// Removes the legacy GRANDPA authorities stored under a well-known key.
+pub struct RemoveLegacyGrandpaAuthorities<DbWeight>(PhantomData<DbWeight>);
+
+impl<DbWeight: Get<RuntimeDbWeight>> OnRuntimeUpgrade for RemoveLegacyGrandpaAuthorities<DbWeight> {
+	fn on_runtime_upgrade() -> Weight {
+		unhashed::kill(LEGACY_GRANDPA_AUTHORITIES_KEY);
+		DbWeight::get().writes(1)
+	}
+}
+
+/// Removes the storage of the GRANDPA and BABE pallets, which are not used by parachains.
+///
+/// The pallets must also be removed from `construct_runtime`.
+pub type RemoveSoloConsensus<GrandpaPalletName, BabePalletName, DbWeight> = (
+	RemovePallet<GrandpaPalletName, DbWeight>,
+	RemovePallet<BabePalletName, DbWeight>,
+	RemoveLegacyGrandpaAuthorities<DbWeight>,
+);
+
+/// Describes how the session keys of the solochain are translated to the session keys of the
+/// parachain.
+pub trait SessionKeysUpgrade<T: pallet_session::Config> {
+	/// The session keys of the solochain.
+	type Old: OpaqueKeys + Member + Decode;
+
+	/// Translate the `old` session keys of `validator`.
+	///
+	/// The Aura key is usually derived from the old BABE key, since both are sr25519 keys.
+	fn upgrade(validator: T::ValidatorId, old: Self::Old) -> T::Keys;
+}
+
+/// Translates the session keys of all validators using `Upgrade`.
+pub struct MigrateSessionKeys<T, Upgrade>(PhantomData<(T, Upgrade)>);
+
+impl<T, Upgrade> OnRuntimeUpgrade for MigrateSessionKeys<T, Upgrade>
+where
+	T: pallet_session::Config,
+	Upgrade: SessionKeysUpgrade<T>,
+{
+	fn on_runtime_upgrade() -> Weight {
+		// `upgrade_keys` translates every entry of `NextKeys` and `QueuedKeys`, so the entries of
+		// `NextKeys` are counted by the translations minus the length of `QueuedKeys`.
+		let queued = pallet_session::QueuedKeys::<T>::decode_len().unwrap_or(0) as u64;
+		let translated = Cell::new(0u64);
+		pallet_session::Pallet::<T>::upgrade_keys::<Upgrade::Old, _>(|validator, old| {
+			translated.set(translated.get().saturating_add(1));
+			Upgrade::upgrade(validator, old)
+		});
+
+		let keys = translated.get().saturating_sub(queued);
+		let key_types =
+			(<Upgrade::Old as OpaqueKeys>::key_ids().len() + T::Keys::key_ids().len()) as u64;
+		// Every entry of `NextKeys` is read and written, the key owners of the old keys are
+		// cleared and those of the new keys are set. `QueuedKeys` is translated as a whole after
+		// reading its length.
+		T::DbWeight::get().reads_writes(
+			keys.saturating_add(2),
+			keys.saturating_mul(key_types.saturating_add(1)).saturating_add(1),
+		)
+	}
+
+	#[cfg(feature = "try-runtime")]
+	fn post_upgrade(_state: Vec<u8>) -> Result<(), TryRuntimeError> {
+		for validator in pallet_session::Validators::<T>::get() {
+			ensure!(
+				pallet_session::NextKeys::<T>::contains_key(&validator),
+				"Session keys of a validator could not be translated"
+			);
+		}
+		Ok(())
+	}
+}
+
+/// The Aura keys of the current session validators.
+pub struct SessionAuraKeys<T>(PhantomData<T>);
+
+impl<T> Get<Vec<T::AuthorityId>> for SessionAuraKeys<T>
+where
+	T: pallet_session::Config + pallet_aura::Config,
+{
+	fn get() -> Vec<T::AuthorityId> {
+		pallet_session::Validators::<T>::get()
+			.into_iter()
+			.filter_map(|validator| pallet_session::NextKeys::<T>::get(validator))
+			.filter_map(|keys| keys.get::<T::AuthorityId>(<T::AuthorityId as RuntimeAppPublic>::ID))
+			.collect()
+	}
+}
+
+/// Initializes the Aura authorities with `Authorities`, unless they are set already.
+///
+/// Solochains usually do not include `pallet-aura`, so its genesis configuration was never
+/// applied.
+///
+/// The weight assumes that `Authorities` reads at most one storage item per authority plus one,
+/// like [`SessionAuraKeys`] does.
+pub struct InitializeAuraAuthorities<T, Authorities>(PhantomData<(T, Authorities)>);
+
+impl<T, Authorities> OnRuntimeUpgrade for InitializeAuraAuthorities<T, Authorities>
+where
+	T: pallet_aura::Config,
+	Authorities: Get<Vec<T::AuthorityId>>,
+{
+	fn on_runtime_upgrade() -> Weight {
+		if pallet_aura::Pallet::<T>::authorities_len() > 0 {
+			log::info!(target: LOG_TARGET, "Aura authorities are set already, skipping.");
+			return T::DbWeight::get().reads(1)
+		}
+
+		let authorities = Authorities::get();
+		// Reading the length of the authorities and the `Authorities` source.
+		let reads = (authorities.len() as u64).saturating_add(2);
+		if authorities.is_empty() {
+			log::error!(target: LOG_TARGET, "No Aura authorities found; the chain will stall.");
+		} else if authorities.len() > T::MaxAuthorities::get() as usize {
+			log::error!(
+				target: LOG_TARGET,
+				"Too many Aura authorities: {} > {}; not initializing them.",
+				authorities.len(),
+				T::MaxAuthorities::get(),
+			);
+		} else {
+			pallet_aura::Pallet::<T>::initialize_authorities(&authorities);
+			return T::DbWeight::get().reads_writes(reads.saturating_add(1), 1)
+		}
+
+		T::DbWeight::get().reads(reads)
+	}
+
+	#[cfg(feature = "try-runtime")]
+	fn post_upgrade(_state: Vec<u8>) -> Result<(), TryRuntimeError> {
+		ensure!(pallet_aura::Pallet::<T>::authorities_len() > 0, "Aura authorities are empty");
+		Ok(())
+	}
+}
+
+/// The reasons why the state is not ready to be continued as a parachain.
+#[derive(Clone, Copy, Eq, PartialEq, RuntimeDebug)]
+pub enum NotReady {
+	/// The storage of the GRANDPA pallet still exists.
+	GrandpaStatePresent,
+	/// The storage of the BABE pallet still exists.
+	BabeStatePresent,
+	/// There are no Aura authorities, so no collator can author blocks.
+	NoAuraAuthorities,
+	/// The runtime code is missing.
+	NoRuntimeCode,
+}
+
+/// Checks that the state of the chain can be continued as a parachain.
+///
+/// This is a no-op migration whose `post_upgrade` runs [`ensure_parachain_ready`] with
+/// `try-runtime`. It should be executed after all other migrations.
+pub struct EnsureParachainReady<T, GrandpaPalletName, BabePalletName>(
+	PhantomData<(T, GrandpaPalletName, BabePalletName)>,
+);
+
+impl<T, GrandpaPalletName, BabePalletName> OnRuntimeUpgrade
+	for EnsureParachainReady<T, GrandpaPalletName, BabePalletName>
+where
+	T: pallet_aura::Config,
+	GrandpaPalletName: Get<&'static str>,
+	BabePalletName: Get<&'static str>,
+{
+	fn on_runtime_upgrade() -> Weight {
+		Weight::zero()
+	}
+
+	#[cfg(feature = "try-runtime")]
+	fn post_upgrade(_state: Vec<u8>) -> Result<(), TryRuntimeError> {
+		ensure_parachain_ready::<T, GrandpaPalletName, BabePalletName>().map_err(|reason| {
+			log::error!(target: LOG_TARGET, "Chain is not ready to run as a parachain: {reason:?}");
+			"Chain is not ready to run as a parachain".into()
+		})
+	}
+}
+
+/// Check that the state of the chain can be continued as a parachain.
+pub fn ensure_parachain_ready<T, GrandpaPalletName, BabePalletName>() -> Result<(), NotReady>
+where
+	T: pallet_aura::Config,
+	GrandpaPalletName: Get<&'static str>,
+	BabePalletName: Get<&'static str>,
+{
+	let pallet_state_present =
+		|name: &str| unhashed::contains_prefixed_key(&sp_io::hashing::twox_128(name.as_bytes()));
+
+	if pallet_state_present(GrandpaPalletName::get()) ||
+		unhashed::exists(LEGACY_GRANDPA_AUTHORITIES_KEY)
+	{
+		return Err(NotReady::GrandpaStatePresent)
+	}
+	if pallet_state_present(BabePalletName::get()) {
+		return Err(NotReady::BabeStatePresent)
+	}
+	if pallet_aura::Pallet::<T>::authorities_len() == 0 {
+		return Err(NotReady::NoAuraAuthorities)
+	}
+	if !unhashed::exists(sp_core::storage::well_known_keys::CODE) {
+		return Err(NotReady::NoRuntimeCode)
+	}
+
+	Ok(())
+}
@@ -0,0 +1,151 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Cumulus.
+
+// Cumulus is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Cumulus is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Cumulus.  If not, see <http://www.gnu.org/licenses/>.
+
+use crate as solo_to_para;
+use cumulus_pallet_parachain_system::AnyRelayNumber;
+use frame_support::{
+	derive_impl, parameter_types,
+	traits::{ConstBool, ConstU32, ConstU64},
+	weights::RuntimeDbWeight,
+};
+use sp_consensus_aura::sr25519::AuthorityId as AuraId;
+use sp_runtime::{
+	impl_opaque_keys, testing::UintAuthorityId, traits::OpaqueKeys, BuildStorage, RuntimeAppPublic,
+};
+
+type Block = frame_system::mocking::MockBlock<Test>;
+
+frame_support::construct_runtime!(
+	pub enum Test
+	{
+		System: frame_system,
+		ParachainSystem: cumulus_pallet_parachain_system,
+		Timestamp: pallet_timestamp,
+		Aura: pallet_aura,
+		Session: pallet_session,
+		Sudo: pallet_sudo,
+		SoloToPara: solo_to_para,
+	}
+);
+
+parameter_types! {
+	pub const TestDbWeight: RuntimeDbWeight = RuntimeDbWeight { read: 10, write: 100 };
+	pub const GrandpaPalletName: &'static str = "Grandpa";
+	pub const BabePalletName: &'static str = "Babe";
+}
+
+#[derive_impl(frame_system::config_preludes::TestDefaultConfig)]
+impl frame_system::Config for Test {
+	type Block = Block;
+	type DbWeight = TestDbWeight;
+}
+
+impl cumulus_pallet_parachain_system::Config for Test {
+	type WeightInfo = ();
+	type RuntimeEvent = RuntimeEvent;
+	type OnSystemEvent = SoloToPara;
+	type SelfParaId = ();
+	type OutboundXcmpMessageSource = ();
+	// Ignore all DMP messages by enqueueing them into `()`:
+	type DmpQueue = frame_support::traits::EnqueueWithOrigin<(), sp_core::ConstU8<0>>;
+	type ReservedDmpWeight = ();
+	type XcmpMessageHandler = ();
+	type ReservedXcmpWeight = ();
+	type CheckAssociatedRelayNumber = AnyRelayNumber;
+	type ConsensusHook = cumulus_pallet_parachain_system::consensus_hook::ExpectParentIncluded;
+	type SelectCore = cumulus_pallet_parachain_system::DefaultCoreSelector<Test>;
+}
+
+impl pallet_timestamp::Config for Test {
+	type Moment = u64;
+	type OnTimestampSet = Aura;
+	type MinimumPeriod = ConstU64<1>;
+	type WeightInfo = ();
+}
+
+impl pallet_aura::Config for Test {
+	type AuthorityId = AuraId;
+	type MaxAuthorities = ConstU32<4>;
+	type DisabledValidators = ();
+	type AllowMultipleBlocksPerSlot = ConstBool<false>;
+	type SlotDuration = pallet_aura::MinimumPeriodTimesTwo<Self>;
+}
+
+impl_opaque_keys! {
+	/// The session keys of the parachain.
+	pub struct MockSessionKeys {
+		pub aura: AuraId,
+	}
+}
+
+impl_opaque_keys! {
+	/// The session keys of the solochain.
+	pub struct OldSessionKeys {
+		pub babe: UintAuthorityId,
+	}
+}
+
+/// The Aura key that the solochain key `id` is translated to.
+pub fn aura_id(id: u64) -> AuraId {
+	sp_core::sr25519::Public::from_raw([id as u8; 32]).into()
+}
+
+pub struct UpgradeToAura;
+impl solo_to_para::migrations::SessionKeysUpgrade<Test> for UpgradeToAura {
+	type Old = OldSessionKeys;
+
+	fn upgrade(_validator: u64, old: OldSessionKeys) -> MockSessionKeys {
+		MockSessionKeys { aura: aura_id(old.babe.0) }
+	}
+}
+
+pub struct TestSessionHandler;
+impl pallet_session::SessionHandler<u64> for TestSessionHandler {
+	const KEY_TYPE_IDS: &'static [sp_runtime::KeyTypeId] = &[AuraId::ID];
+	fn on_genesis_session<Ks: OpaqueKeys>(_: &[(u64, Ks)]) {}
+	fn on_new_session<Ks: OpaqueKeys>(_: bool, _: &[(u64, Ks)], _: &[(u64, Ks)]) {}
+	fn on_before_session_ending() {}
+	fn on_disabled(_: u32) {}
+}
+
+impl pallet_session::Config for Test {
+	type RuntimeEvent = RuntimeEvent;
+	type ValidatorId = <Self as frame_system::Config>::AccountId;
+	type ValidatorIdOf = sp_runtime::traits::ConvertInto;
+	type ShouldEndSession = pallet_session::PeriodicSessions<ConstU64<10>, ConstU64<0>>;
+	type NextSessionRotation = pallet_session::PeriodicSessions<ConstU64<10>, ConstU64<0>>;
+	type SessionManager = ();
+	type SessionHandler = TestSessionHandler;
+	type Keys = MockSessionKeys;
+	type WeightInfo = ();
+}
+
+impl pallet_sudo::Config for Test {
+	type RuntimeEvent = RuntimeEvent;
+	type RuntimeCall = RuntimeCall;
+	type WeightInfo = ();
+}
+
+impl solo_to_para::Config for Test {
+	type RuntimeEvent = RuntimeEvent;
+}
+
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	let mut ext: sp_io::TestExternalities =
+		frame_system::GenesisConfig::<Test>::default().build_storage().unwrap().into();
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
@@ -0,0 +1,203 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Cumulus.
+
+// Cumulus is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Cumulus is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Cumulus.  If not, see <http://www.gnu.org/licenses/>.
+
+use super::{migrations::*, *};
+use crate::mock::*;
+use cumulus_pallet_parachain_system::{
+	CustomValidationHeadData, HostConfiguration, OnSystemEvent, PendingValidationCode,
+	ValidationData,
+};
+use frame_support::{
+	assert_noop, assert_ok,
+	storage::unhashed,
+	traits::{Get, OnRuntimeUpgrade},
+};
+use polkadot_primitives::{AbridgedHostConfiguration, AsyncBackingParams};
+use sp_runtime::{testing::UintAuthorityId, traits::BadOrigin, RuntimeAppPublic};
+
+type RemoveConsensus = RemoveSoloConsensus<GrandpaPalletName, BabePalletName, TestDbWeight>;
+type EnsureReady = EnsureParachainReady<Test, GrandpaPalletName, BabePalletName>;
+
+/// Store the `old` session keys of the solochain for `validators`.
+fn set_solo_session_keys(validators: &[u64]) {
+	pallet_session::Validators::<Test>::put(validators.to_vec());
+	for validator in validators {
+		unhashed::put(
+			&pallet_session::NextKeys::<Test>::hashed_key_for(validator),
+			&OldSessionKeys { babe: UintAuthorityId(*validator) },
+		);
+	}
+	unhashed::put(
+		&pallet_session::QueuedKeys::<Test>::hashed_key(),
+		&validators
+			.iter()
+			.map(|validator| (*validator, OldSessionKeys { babe: UintAuthorityId(*validator) }))
+			.collect::<Vec<_>>(),
+	);
+}
+
+fn set_validation_data() {
+	ValidationData::<Test>::put(PersistedValidationData::default());
+	HostConfiguration::<Test>::put(AbridgedHostConfiguration {
+		max_code_size: 1024,
+		max_head_data_size: 1024,
+		max_upward_queue_count: 8,
+		max_upward_queue_size: 1024,
+		max_upward_message_size: 256,
+		max_upward_message_num_per_candidate: 5,
+		hrmp_max_message_num_per_candidate: 5,
+		validation_upgrade_cooldown: 6,
+		validation_upgrade_delay: 6,
+		async_backing_params: AsyncBackingParams {
+			allowed_ancestry_len: 0,
+			max_candidate_depth: 0,
+		},
+	});
+}
+
+#[test]
+fn schedule_migration_applies_head_data_with_the_code() {
+	new_test_ext().execute_with(|| {
+		let code = vec![1, 2, 3];
+		let head_data = vec![4, 5, 6];
+		set_validation_data();
+
+		assert_noop!(
+			SoloToPara::schedule_migration(
+				RuntimeOrigin::signed(1),
+				code.clone(),
+				head_data.clone()
+			),
+			BadOrigin
+		);
+		assert_ok!(SoloToPara::schedule_migration(
+			RuntimeOrigin::root(),
+			code.clone(),
+			head_data.clone()
+		));
+		assert_eq!(PendingValidationCode::<Test>::get(), code);
+		assert_eq!(PendingCustomValidationHeadData::<Test>::get(), Some(head_data.clone()));
+		assert_eq!(CustomValidationHeadData::<Test>::get(), None);
+		System::assert_last_event(Event::CustomValidationHeadDataStored.into());
+
+		// The head data is only applied together with the code:
+		<SoloToPara as OnSystemEvent>::on_validation_code_applied();
+		assert_eq!(CustomValidationHeadData::<Test>::get(), Some(head_data));
+		assert_eq!(PendingCustomValidationHeadData::<Test>::get(), None);
+		System::assert_last_event(Event::CustomValidationHeadDataApplied.into());
+	});
+}
+
+#[test]
+fn migrate_session_keys_translates_keys_of_all_validators() {
+	new_test_ext().execute_with(|| {
+		set_solo_session_keys(&[1, 2, 3]);
+
+		let weight = MigrateSessionKeys::<Test, UpgradeToAura>::on_runtime_upgrade();
+
+		for validator in 1..=3 {
+			assert_eq!(
+				pallet_session::NextKeys::<Test>::get(validator),
+				Some(MockSessionKeys { aura: aura_id(validator) })
+			);
+			assert_eq!(
+				Session::key_owner(AuraId::ID, aura_id(validator).as_ref()),
+				Some(validator)
+			);
+		}
+		assert_eq!(
+			pallet_session::QueuedKeys::<Test>::get(),
+			(1..=3)
+				.map(|validator| (validator, MockSessionKeys { aura: aura_id(validator) }))
+				.collect::<Vec<_>>()
+		);
+		// Three validators with one old and one new key each.
+		assert_eq!(weight, TestDbWeight::get().reads_writes(3 + 2, 3 * (2 + 1) + 1));
+	});
+}
+
+#[test]
+fn initialize_aura_authorities_uses_session_keys() {
+	new_test_ext().execute_with(|| {
+		set_solo_session_keys(&[1, 2, 3]);
+		MigrateSessionKeys::<Test, UpgradeToAura>::on_runtime_upgrade();
+
+		let weight = InitializeAuraAuthorities::<Test, SessionAuraKeys<Test>>::on_runtime_upgrade();
+		assert_eq!(
+			pallet_aura::Authorities::<Test>::get(),
+			vec![aura_id(1), aura_id(2), aura_id(3)]
+		);
+		assert_eq!(weight, TestDbWeight::get().reads_writes(3 + 3, 1));
+
+		// Authorities that are set already are kept.
+		pallet_session::Validators::<Test>::put(vec![1]);
+		let weight = InitializeAuraAuthorities::<Test, SessionAuraKeys<Test>>::on_runtime_upgrade();
+		assert_eq!(
+			pallet_aura::Authorities::<Test>::get(),
+			vec![aura_id(1), aura_id(2), aura_id(3)]
+		);
+		assert_eq!(weight, TestDbWeight::get().reads(1));
+	});
+}
+
+#[test]
+fn initialize_aura_authorities_rejects_too_many_authorities() {
+	new_test_ext().execute_with(|| {
+		set_solo_session_keys(&[1, 2, 3, 4, 5]);
+		MigrateSessionKeys::<Test, UpgradeToAura>::on_runtime_upgrade();
+
+		let weight = InitializeAuraAuthorities::<Test, SessionAuraKeys<Test>>::on_runtime_upgrade();
+		assert!(pallet_aura::Authorities::<Test>::get().is_empty());
+		assert_eq!(weight, TestDbWeight::get().reads(5 + 2));
+	});
+}
+
+#[test]
+fn ensure_parachain_ready_detects_leftovers_of_the_solochain() {
+	new_test_ext().execute_with(|| {
+		let pallet_key = |name: &str, item: &str| {
+			[sp_io::hashing::twox_128(name.as_bytes()), sp_io::hashing::twox_128(item.as_bytes())]
+				.concat()
+		};
+		unhashed::put(&pallet_key("Grandpa", "State"), &1u32);
+		unhashed::put(&pallet_key("Babe", "EpochIndex"), &1u64);
+		unhashed::put(b":grandpa_authorities", &1u8);
+		set_solo_session_keys(&[1, 2, 3]);
+
+		assert_eq!(
+			ensure_parachain_ready::<Test, GrandpaPalletName, BabePalletName>(),
+			Err(NotReady::GrandpaStatePresent)
+		);
+
+		RemoveConsensus::on_runtime_upgrade();
+		assert!(!unhashed::exists(b":grandpa_authorities"));
+		assert_eq!(
+			ensure_parachain_ready::<Test, GrandpaPalletName, BabePalletName>(),
+			Err(NotReady::NoAuraAuthorities)
+		);
+
+		MigrateSessionKeys::<Test, UpgradeToAura>::on_runtime_upgrade();
+		InitializeAuraAuthorities::<Test, SessionAuraKeys<Test>>::on_runtime_upgrade();
+		assert_eq!(
+			ensure_parachain_ready::<Test, GrandpaPalletName, BabePalletName>(),
+			Err(NotReady::NoRuntimeCode)
+		);
+
+		unhashed::put_raw(sp_core::storage::well_known_keys::CODE, &[1, 2, 3]);
+		assert_eq!(ensure_parachain_ready::<Test, GrandpaPalletName, BabePalletName>(), Ok(()));
+		assert_eq!(EnsureReady::on_runtime_upgrade(), Weight::zero());
+	});
+}
@@ -40,6 +40,16 @@ mod benchmarks {
 		Pallet::<T>::update_resume_threshold(RawOrigin::Root, 1);
 	}
 
+	#[benchmark]
+	fn update_outbound_scheduling() {
+		let new = OutboundScheduling::FairShare { max_bytes_per_block: 1024 };
+
+		#[extrinsic_call]
+		_(RawOrigin::Root, new);
+
+		assert_eq!(OutboundSchedulingConfig::<T>::get(), new);
+	}
+
 	#[benchmark]
 	fn enqueue_xcmp_message() {
 		assert!(QueueConfig::<T>::get().drop_threshold * MaxXcmpMessageLenOf::<T>::get() > 1000);
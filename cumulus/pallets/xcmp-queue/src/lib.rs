@@ -32,10 +32,18 @@
 //! It is defined in the channel configuration.
 //! - `THRESHOLD_FACTOR` just declares which percentage of the max size is the actual threshold.
 //! If it's 2, then the threshold is half of the max size, if it's 4, it's a quarter, and so on.
+//!
+//! While the [`OutboundScheduling::FairShare`] scheduler is active, the bandwidth used by every
+//! outbound channel is accounted in [`OutboundBandwidth`]. It is used to give the channels that
+//! sent the least data recently precedence when the outbound pages compete for the limits of the
+//! block. The
+//! queue sizes, suspension state and bandwidth of the channels are exposed through the
+//! [`runtime_api::XcmpQueueApi`] runtime API.
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
 pub mod migration;
+pub mod runtime_api;
 
 #[cfg(test)]
 mod mock;
@@ -71,7 +79,9 @@ use polkadot_runtime_common::xcm_sender::PriceForMessageDelivery;
 use polkadot_runtime_parachains::FeeTracker;
 use scale_info::TypeInfo;
 use sp_core::MAX_POSSIBLE_ALLOCATION;
-use sp_runtime::{FixedU128, RuntimeDebug, Saturating, WeakBoundedVec};
+use sp_runtime::{
+	traits::SaturatedConversion, FixedU128, RuntimeDebug, Saturating, WeakBoundedVec,
+};
 use xcm::{latest::prelude::*, VersionedLocation, VersionedXcm, WrapVersion, MAX_XCM_DECODE_DEPTH};
 use xcm_builder::InspectMessageQueues;
 use xcm_executor::traits::ConvertOrigin;
@@ -254,6 +264,23 @@ pub mod pallet {
 				data.validate::<T>()
 			})
 		}
+
+		/// Overwrites the strategy used to schedule outbound pages among the channels.
+		///
+		/// - `origin`: Must pass `Root`.
+		/// - `new`: Desired value for `OutboundSchedulingConfig`
+		#[pallet::call_index(6)]
+		#[pallet::weight((T::WeightInfo::update_outbound_scheduling(), DispatchClass::Operational,))]
+		pub fn update_outbound_scheduling(
+			origin: OriginFor<T>,
+			new: OutboundScheduling,
+		) -> DispatchResult {
+			ensure_root(origin)?;
+
+			new.validate::<T>()?;
+			OutboundSchedulingConfig::<T>::put(new);
+			Ok(())
+		}
 	}
 
 	#[pallet::hooks]
@@ -349,6 +376,18 @@ pub mod pallet {
 	#[pallet::storage]
 	pub(super) type QueueConfig<T: Config> = StorageValue<_, QueueConfigData, ValueQuery>;
 
+	/// The strategy used to schedule outbound pages among the channels.
+	#[pallet::storage]
+	pub(super) type OutboundSchedulingConfig<T: Config> =
+		StorageValue<_, OutboundScheduling, ValueQuery>;
+
+	/// The bandwidth used by each outbound XCMP channel.
+	///
+	/// Only accounted while [`OutboundScheduling::FairShare`] is active.
+	#[pallet::storage]
+	pub(super) type OutboundBandwidth<T: Config> =
+		StorageMap<_, Twox64Concat, ParaId, ChannelBandwidth, ValueQuery>;
+
 	/// Whether or not the XCMP queue is suspended from executing incoming XCMs or not.
 	#[pallet::storage]
 	pub(super) type QueueSuspended<T: Config> = StorageValue<_, bool, ValueQuery>;
@@ -450,6 +489,66 @@ impl QueueConfigData {
 	}
 }
 
+/// How the outbound pages are scheduled among the channels that compete for the block's HRMP
+/// limits.
+#[derive(
+	Copy, Clone, Default, Eq, PartialEq, Encode, Decode, RuntimeDebug, TypeInfo, MaxEncodedLen,
+)]
+pub enum OutboundScheduling {
+	/// Channels are served one page each, in an order that rotates every block.
+	#[default]
+	RoundRobin,
+	/// Channels that sent the least data recently are served first.
+	///
+	/// At most `max_bytes_per_block` bytes of message pages are sent per block, but at least one
+	/// page to guarantee progress. Signals are exempt from the limit.
+	FairShare { max_bytes_per_block: u32 },
+}
+
+impl OutboundScheduling {
+	/// Validate all assumptions about `Self`.
+	///
+	/// Should be called prior to accepting this as new config.
+	pub fn validate<T: crate::Config>(&self) -> sp_runtime::DispatchResult {
+		match self {
+			Self::FairShare { max_bytes_per_block: 0 } => Err(Error::<T>::BadQueueConfig.into()),
+			_ => Ok(()),
+		}
+	}
+}
+
+/// The bandwidth used by an outbound XCMP channel.
+#[derive(
+	Copy, Clone, Default, Eq, PartialEq, Encode, Decode, RuntimeDebug, TypeInfo, MaxEncodedLen,
+)]
+pub struct ChannelBandwidth {
+	/// Total number of bytes sent over the channel, including signals.
+	pub total_bytes: u64,
+	/// Total number of pages sent over the channel, including signals.
+	pub total_pages: u64,
+	/// Number of bytes sent recently. This halves with every block that passes.
+	pub recent_bytes: u32,
+	/// The block number at which `recent_bytes` was last updated.
+	pub last_updated: u32,
+}
+
+impl ChannelBandwidth {
+	/// The number of bytes sent recently, as seen at block `now`.
+	pub fn recent_bytes_at(&self, now: u32) -> u32 {
+		self.recent_bytes
+			.checked_shr(now.saturating_sub(self.last_updated))
+			.unwrap_or(0)
+	}
+
+	/// Account a page of `len` bytes being sent at block `now`.
+	fn note_sent(&mut self, now: u32, len: usize) {
+		self.total_bytes.saturating_accrue(len as u64);
+		self.total_pages.saturating_inc();
+		self.recent_bytes = self.recent_bytes_at(now).saturating_add(len.saturated_into());
+		self.last_updated = now;
+	}
+}
+
 #[derive(PartialEq, Eq, Copy, Clone, Encode, Decode, TypeInfo)]
 pub enum ChannelSignal {
 	Suspend,
@@ -691,6 +790,47 @@ impl<T: Config> Pallet<T> {
 			.max(<T as crate::Config>::WeightInfo::on_idle_large_msg())
 	}
 
+	/// The current block number, as used for the bandwidth accounting.
+	fn bandwidth_now() -> u32 {
+		frame_system::Pallet::<T>::block_number().saturated_into()
+	}
+
+	/// Details of all outbound channels that have messages or signals queued or are suspended.
+	pub fn outbound_channels() -> Vec<runtime_api::OutboundChannelInfo> {
+		let now = Self::bandwidth_now();
+		<OutboundXcmpStatus<T>>::get()
+			.into_iter()
+			.map(|c| {
+				let queued_bytes = (c.first_index..c.last_index)
+					.map(|index| {
+						<OutboundXcmpMessages<T>>::decode_len(c.recipient, index).unwrap_or(0)
+					})
+					.sum::<usize>();
+				let bandwidth = <OutboundBandwidth<T>>::get(c.recipient);
+				runtime_api::OutboundChannelInfo {
+					recipient: c.recipient,
+					suspended: c.state == OutboundState::Suspended,
+					signals_queued: c.signals_exist,
+					queued_pages: c.last_index.saturating_sub(c.first_index) as u32,
+					queued_bytes: queued_bytes.saturated_into(),
+					recent_bytes: bandwidth.recent_bytes_at(now),
+					total_bytes: bandwidth.total_bytes,
+				}
+			})
+			.collect()
+	}
+
+	/// Details of all inbound channels that are suspended.
+	pub fn suspended_inbound_channels() -> Vec<runtime_api::InboundChannelInfo> {
+		<InboundXcmpSuspended<T>>::get()
+			.into_iter()
+			.map(|sender| runtime_api::InboundChannelInfo {
+				sender,
+				queued_pages: T::XcmpQueue::footprint(sender).ready_pages,
+			})
+			.collect()
+	}
+
 	#[cfg(feature = "bridging")]
 	fn is_inbound_channel_suspended(sender: ParaId) -> bool {
 		<InboundXcmpSuspended<T>>::get().iter().any(|c| c == &sender)
@@ -829,7 +969,24 @@ impl<T: Config> XcmpMessageSource for Pallet<T> {
 		let max_message_count = statuses.len().min(maximum_channels);
 		let mut result = Vec::with_capacity(max_message_count);
 
-		for status in statuses.iter_mut() {
+		let now = Self::bandwidth_now();
+		let scheduling = <OutboundSchedulingConfig<T>>::get();
+		// The channels are visited in this order. With fair-share scheduling, the channels that
+		// sent the least data recently go first; the sort is stable, so ties keep the rotation.
+		let mut order: Vec<usize> = (0..statuses.len()).collect();
+		let mut remaining_budget = match scheduling {
+			OutboundScheduling::RoundRobin => None,
+			OutboundScheduling::FairShare { max_bytes_per_block } => {
+				order.sort_by_cached_key(|&index| {
+					<OutboundBandwidth<T>>::get(statuses[index].recipient).recent_bytes_at(now)
+				});
+				Some(max_bytes_per_block as usize)
+			},
+		};
+		let mut sent_message_pages = false;
+
+		for index in order {
+			let status = &mut statuses[index];
 			let OutboundChannelDetails {
 				recipient: para_id,
 				state: outbound_state,
@@ -848,6 +1005,7 @@ impl<T: Config> XcmpMessageSource for Pallet<T> {
 					if signals_exist {
 						<SignalMessages<T>>::remove(para_id);
 					}
+					<OutboundBandwidth<T>>::remove(para_id);
 					*status = OutboundChannelDetails::new(para_id);
 					continue
 				},
@@ -879,9 +1037,16 @@ impl<T: Config> XcmpMessageSource for Pallet<T> {
 				continue
 			} else if last_index > first_index {
 				let page = <OutboundXcmpMessages<T>>::get(para_id, first_index);
-				if page.len() < max_size_now {
+				// The first message page of the block is always sent to guarantee progress.
+				let within_budget = !sent_message_pages ||
+					remaining_budget.map_or(true, |budget| page.len() <= budget);
+				if page.len() < max_size_now && within_budget {
 					<OutboundXcmpMessages<T>>::remove(para_id, first_index);
 					first_index += 1;
+					sent_message_pages = true;
+					if let Some(budget) = remaining_budget.as_mut() {
+						budget.saturating_reduce(page.len());
+					}
 					page
 				} else {
 					continue
@@ -900,6 +1065,13 @@ impl<T: Config> XcmpMessageSource for Pallet<T> {
 				//   since it's so unlikely then for now we just drop it.
 				defensive!("WARNING: oversize message in queue - dropping");
 			} else {
+				// The bandwidth is only needed by the fair-share scheduler, don't pay for it
+				// otherwise.
+				if remaining_budget.is_some() {
+					<OutboundBandwidth<T>>::mutate(para_id, |bandwidth| {
+						bandwidth.note_sent(now, page.len())
+					});
+				}
 				result.push((para_id, page.into_inner()));
 			}
 
@@ -944,13 +1116,20 @@ impl<T: Config> XcmpMessageSource for Pallet<T> {
 			x.state == OutboundState::Suspended || x.signals_exist || x.first_index < x.last_index
 		});
 
-		// old_status_len must be >= status.len() since we never add anything to status.
-		let pruned = old_statuses_len - statuses.len();
-		// removing an item from status implies a message being sent, so the result messages must
-		// be no less than the pruned channels.
-		let _ = statuses.try_rotate_left(result.len().saturating_sub(pruned)).defensive_proof(
-			"Could not store HRMP channels config. Some HRMP channels may be broken.",
-		);
+		if remaining_budget.is_some() {
+			// With fair-share scheduling the served channels are not necessarily the first ones,
+			// so move exactly the served channels to the end. The sort is stable, which keeps the
+			// rotation among the channels that were not served.
+			statuses.sort_by_key(|x| result.binary_search_by_key(&x.recipient, |m| m.0).is_ok());
+		} else {
+			// old_status_len must be >= status.len() since we never add anything to status.
+			let pruned = old_statuses_len - statuses.len();
+			// removing an item from status implies a message being sent, so the result messages
+			// must be no less than the pruned channels.
+			let _ = statuses.try_rotate_left(result.len().saturating_sub(pruned)).defensive_proof(
+				"Could not store HRMP channels config. Some HRMP channels may be broken.",
+			);
+		}
 
 		<OutboundXcmpStatus<T>>::put(statuses);
 
@@ -223,11 +223,17 @@ pub fn new_test_ext() -> sp_io::TestExternalities {
 
 /// A para that we have an HRMP channel with.
 pub const HRMP_PARA_ID: u32 = 7777;
+/// Another para that we have an HRMP channel with.
+pub const OTHER_HRMP_PARA_ID: u32 = 7778;
+
+fn is_mocked_channel(id: ParaId) -> bool {
+	id == HRMP_PARA_ID.into() || id == OTHER_HRMP_PARA_ID.into()
+}
 
 pub struct MockedChannelInfo;
 impl GetChannelInfo for MockedChannelInfo {
 	fn get_channel_status(id: ParaId) -> ChannelStatus {
-		if id == HRMP_PARA_ID.into() {
+		if is_mocked_channel(id) {
 			return ChannelStatus::Ready(usize::MAX, usize::MAX)
 		}
 
@@ -235,7 +241,7 @@ impl GetChannelInfo for MockedChannelInfo {
 	}
 
 	fn get_channel_info(id: ParaId) -> Option<ChannelInfo> {
-		if id == HRMP_PARA_ID.into() {
+		if is_mocked_channel(id) {
 			return Some(ChannelInfo {
 				max_capacity: u32::MAX,
 				max_total_size: u32::MAX,
@@ -0,0 +1,63 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Runtime API definition for the XCMP queue pallet.
+
+use alloc::vec::Vec;
+use codec::{Decode, Encode};
+use cumulus_primitives_core::ParaId;
+use scale_info::TypeInfo;
+use sp_runtime::RuntimeDebug;
+
+/// State of an outbound XCMP channel.
+#[derive(Clone, Eq, PartialEq, Encode, Decode, RuntimeDebug, TypeInfo)]
+pub struct OutboundChannelInfo {
+	/// The `ParaId` of the parachain that this channel is connected with.
+	pub recipient: ParaId,
+	/// Whether the recipient told us to suspend sending.
+	pub suspended: bool,
+	/// Whether a signal is waiting to be sent.
+	pub signals_queued: bool,
+	/// Number of message pages waiting to be sent.
+	pub queued_pages: u32,
+	/// Total size of the message pages waiting to be sent, in bytes.
+	pub queued_bytes: u32,
+	/// Number of bytes sent recently, as used by the fair-share scheduler.
+	///
+	/// The bandwidth is only accounted while the fair-share scheduler is active.
+	pub recent_bytes: u32,
+	/// Total number of bytes sent over the channel while the fair-share scheduler was active.
+	pub total_bytes: u64,
+}
+
+/// State of a suspended inbound XCMP channel.
+#[derive(Clone, Eq, PartialEq, Encode, Decode, RuntimeDebug, TypeInfo)]
+pub struct InboundChannelInfo {
+	/// The `ParaId` of the parachain that sends over this channel.
+	pub sender: ParaId,
+	/// Number of pages waiting to be processed.
+	pub queued_pages: u32,
+}
+
+sp_api::decl_runtime_apis! {
+	/// API to query the state of the XCMP channels.
+	pub trait XcmpQueueApi {
+		/// All outbound channels that have messages or signals queued or are suspended.
+		fn outbound_channels() -> Vec<OutboundChannelInfo>;
+
+		/// All inbound channels that are suspended.
+		fn suspended_inbound_channels() -> Vec<InboundChannelInfo>;
+	}
+}
@@ -14,7 +14,7 @@
 // limitations under the License.
 
 use super::{
-	mock::{mk_page, versioned_xcm, EnqueuedMessages, HRMP_PARA_ID},
+	mock::{mk_page, versioned_xcm, EnqueuedMessages, HRMP_PARA_ID, OTHER_HRMP_PARA_ID},
 	*,
 };
 use XcmpMessageFormat::*;
@@ -329,6 +329,277 @@ fn update_resume_threshold_works() {
 	});
 }
 
+#[test]
+fn update_outbound_scheduling_works() {
+	new_test_ext().execute_with(|| {
+		let fair_share = OutboundScheduling::FairShare { max_bytes_per_block: 1024 };
+
+		assert_eq!(<OutboundSchedulingConfig<Test>>::get(), OutboundScheduling::RoundRobin);
+		assert_noop!(
+			XcmpQueue::update_outbound_scheduling(Origin::signed(2), fair_share),
+			BadOrigin
+		);
+		assert_noop!(
+			XcmpQueue::update_outbound_scheduling(
+				Origin::root(),
+				OutboundScheduling::FairShare { max_bytes_per_block: 0 }
+			),
+			Error::<Test>::BadQueueConfig
+		);
+		assert_ok!(XcmpQueue::update_outbound_scheduling(Origin::root(), fair_share));
+
+		assert_eq!(<OutboundSchedulingConfig<Test>>::get(), fair_share);
+	});
+}
+
+#[test]
+fn outbound_bandwidth_is_accounted_and_decays() {
+	let para = ParaId::from(HRMP_PARA_ID);
+	let dest = (Parent, Parachain(HRMP_PARA_ID));
+
+	new_test_ext().execute_with(|| {
+		frame_system::Pallet::<Test>::set_block_number(1);
+
+		// Round-robin scheduling does not need the bandwidth and does not account it:
+		assert_ok!(send_xcm::<XcmpQueue>(dest.into(), Xcm(vec![ClearOrigin; 8])));
+		assert_eq!(XcmpQueue::take_outbound_messages(usize::MAX).len(), 1);
+		assert!(!<OutboundBandwidth<Test>>::contains_key(para));
+
+		assert_ok!(XcmpQueue::update_outbound_scheduling(
+			Origin::root(),
+			OutboundScheduling::FairShare { max_bytes_per_block: u32::MAX }
+		));
+		assert_ok!(send_xcm::<XcmpQueue>(dest.into(), Xcm(vec![ClearOrigin; 8])));
+		assert_ok!(send_xcm::<XcmpQueue>(dest.into(), Xcm(vec![ClearOrigin; 8])));
+
+		let taken = XcmpQueue::take_outbound_messages(usize::MAX);
+		assert_eq!(taken.len(), 1);
+		let sent = taken[0].1.len() as u32;
+
+		let bandwidth = <OutboundBandwidth<Test>>::get(para);
+		assert_eq!(
+			bandwidth,
+			ChannelBandwidth {
+				total_bytes: sent as u64,
+				total_pages: 1,
+				recent_bytes: sent,
+				last_updated: 1
+			}
+		);
+		assert_eq!(bandwidth.recent_bytes_at(2), sent / 2);
+		assert_eq!(bandwidth.recent_bytes_at(100), 0);
+
+		// Sending again later adds to the decayed value:
+		frame_system::Pallet::<Test>::set_block_number(2);
+		assert_ok!(send_xcm::<XcmpQueue>(dest.into(), Xcm(vec![ClearOrigin; 8])));
+		assert_eq!(XcmpQueue::take_outbound_messages(usize::MAX).len(), 1);
+
+		let bandwidth = <OutboundBandwidth<Test>>::get(para);
+		assert_eq!(bandwidth.total_pages, 2);
+		assert_eq!(bandwidth.recent_bytes, sent / 2 + (bandwidth.total_bytes as u32 - sent));
+		assert_eq!(bandwidth.last_updated, 2);
+	});
+}
+
+#[test]
+fn fair_share_scheduling_prefers_least_used_channels() {
+	let para = ParaId::from(HRMP_PARA_ID);
+	let other = ParaId::from(OTHER_HRMP_PARA_ID);
+
+	new_test_ext().execute_with(|| {
+		frame_system::Pallet::<Test>::set_block_number(1);
+		for dest in [para, other] {
+			assert_ok!(send_xcm::<XcmpQueue>(
+				(Parent, Parachain(dest.into())).into(),
+				Xcm(vec![ClearOrigin])
+			));
+		}
+		<OutboundBandwidth<Test>>::insert(
+			para,
+			ChannelBandwidth { recent_bytes: 1024, last_updated: 1, ..Default::default() },
+		);
+
+		// Round-robin serves the channels in the order in which they became active:
+		hypothetically!({
+			let taken = XcmpQueue::take_outbound_messages(1);
+			assert_eq!(taken.len(), 1);
+			assert_eq!(taken[0].0, para);
+		});
+
+		// Fair-share serves the channel that sent less data recently first:
+		assert_ok!(XcmpQueue::update_outbound_scheduling(
+			Origin::root(),
+			OutboundScheduling::FairShare { max_bytes_per_block: u32::MAX }
+		));
+		let taken = XcmpQueue::take_outbound_messages(1);
+		assert_eq!(taken.len(), 1);
+		assert_eq!(taken[0].0, other);
+
+		let taken = XcmpQueue::take_outbound_messages(1);
+		assert_eq!(taken.len(), 1);
+		assert_eq!(taken[0].0, para);
+	});
+}
+
+#[test]
+fn fair_share_scheduling_rotates_only_served_channels() {
+	let para = ParaId::from(HRMP_PARA_ID);
+	let other = ParaId::from(OTHER_HRMP_PARA_ID);
+
+	new_test_ext().execute_with(|| {
+		frame_system::Pallet::<Test>::set_block_number(1);
+		assert_ok!(XcmpQueue::update_outbound_scheduling(
+			Origin::root(),
+			OutboundScheduling::FairShare { max_bytes_per_block: u32::MAX }
+		));
+		for dest in [para, other] {
+			for index in 0..2 {
+				<OutboundXcmpMessages<Test>>::insert(
+					dest,
+					index,
+					WeakBoundedVec::force_from(vec![0u8; 4], None),
+				);
+			}
+		}
+		let details = |recipient| OutboundChannelDetails {
+			recipient,
+			state: OutboundState::Ok,
+			signals_exist: false,
+			first_index: 0,
+			last_index: 2,
+		};
+		<OutboundXcmpStatus<Test>>::put(BoundedVec::truncate_from(vec![
+			details(para),
+			details(other),
+		]));
+		<OutboundBandwidth<Test>>::insert(
+			para,
+			ChannelBandwidth { recent_bytes: 1024, last_updated: 1, ..Default::default() },
+		);
+
+		let taken = XcmpQueue::take_outbound_messages(1);
+		assert_eq!(taken.len(), 1);
+		assert_eq!(taken[0].0, other);
+
+		// Only the served channel moves to the end of the rotation:
+		let recipients =
+			<OutboundXcmpStatus<Test>>::get().iter().map(|c| c.recipient).collect::<Vec<_>>();
+		assert_eq!(recipients, vec![para, other]);
+	});
+}
+
+#[test]
+fn fair_share_scheduling_respects_byte_budget() {
+	let para = ParaId::from(HRMP_PARA_ID);
+	let other = ParaId::from(OTHER_HRMP_PARA_ID);
+
+	new_test_ext().execute_with(|| {
+		frame_system::Pallet::<Test>::set_block_number(1);
+		assert_ok!(XcmpQueue::update_outbound_scheduling(
+			Origin::root(),
+			OutboundScheduling::FairShare { max_bytes_per_block: 1 }
+		));
+		for dest in [para, other] {
+			assert_ok!(send_xcm::<XcmpQueue>(
+				(Parent, Parachain(dest.into())).into(),
+				Xcm(vec![ClearOrigin])
+			));
+		}
+
+		// The first page is always sent, even though it exceeds the budget:
+		let taken = XcmpQueue::take_outbound_messages(usize::MAX);
+		assert_eq!(taken.len(), 1);
+		assert_eq!(taken[0].0, para);
+
+		let taken = XcmpQueue::take_outbound_messages(usize::MAX);
+		assert_eq!(taken.len(), 1);
+		assert_eq!(taken[0].0, other);
+
+		assert!(XcmpQueue::take_outbound_messages(usize::MAX).is_empty());
+	});
+}
+
+#[test]
+fn fair_share_scheduling_does_not_limit_signals() {
+	let para = ParaId::from(HRMP_PARA_ID);
+	let other = ParaId::from(OTHER_HRMP_PARA_ID);
+
+	new_test_ext().execute_with(|| {
+		assert_ok!(XcmpQueue::update_outbound_scheduling(
+			Origin::root(),
+			OutboundScheduling::FairShare { max_bytes_per_block: 1 }
+		));
+		assert_ok!(send_xcm::<XcmpQueue>(
+			(Parent, Parachain(HRMP_PARA_ID)).into(),
+			Xcm(vec![ClearOrigin])
+		));
+		assert_ok!(XcmpQueue::send_signal(other, ChannelSignal::Suspend));
+
+		let taken = XcmpQueue::take_outbound_messages(usize::MAX);
+		assert_eq!(
+			taken,
+			vec![
+				(
+					para,
+					(
+						XcmpMessageFormat::ConcatenatedVersionedXcm,
+						VersionedXcm::from(Xcm::<()>(vec![ClearOrigin]))
+					)
+						.encode()
+				),
+				(other, (XcmpMessageFormat::Signals, ChannelSignal::Suspend).encode()),
+			]
+		);
+	});
+}
+
+#[test]
+fn channel_info_runtime_api_works() {
+	let para = ParaId::from(HRMP_PARA_ID);
+	let other = ParaId::from(OTHER_HRMP_PARA_ID);
+
+	new_test_ext().execute_with(|| {
+		assert!(XcmpQueue::outbound_channels().is_empty());
+		assert!(XcmpQueue::suspended_inbound_channels().is_empty());
+
+		assert_ok!(send_xcm::<XcmpQueue>(
+			(Parent, Parachain(HRMP_PARA_ID)).into(),
+			Xcm(vec![ClearOrigin])
+		));
+		XcmpQueue::suspend_channel(other);
+		assert_ok!(<InboundXcmpSuspended<Test>>::try_mutate(|s| s.try_insert(para).map(|_| ())));
+
+		let queued_bytes = OutboundXcmpMessages::<Test>::get(para, 0).len() as u32;
+		assert_eq!(
+			XcmpQueue::outbound_channels(),
+			vec![
+				runtime_api::OutboundChannelInfo {
+					recipient: para,
+					suspended: false,
+					signals_queued: false,
+					queued_pages: 1,
+					queued_bytes,
+					recent_bytes: 0,
+					total_bytes: 0,
+				},
+				runtime_api::OutboundChannelInfo {
+					recipient: other,
+					suspended: true,
+					signals_queued: false,
+					queued_pages: 0,
+					queued_bytes: 0,
+					recent_bytes: 0,
+					total_bytes: 0,
+				},
+			]
+		);
+		assert_eq!(
+			XcmpQueue::suspended_inbound_channels(),
+			vec![runtime_api::InboundChannelInfo { sender: para, queued_pages: 0 }]
+		);
+	});
+}
+
 /// Validates [`validate`] for required Some(destination) and Some(message)
 struct OkFixedXcmHashWithAssertingRequiredInputsSender;
 impl OkFixedXcmHashWithAssertingRequiredInputsSender {
@@ -47,6 +47,7 @@ use core::marker::PhantomData;
 /// Weight functions needed for `cumulus_pallet_xcmp_queue`.
 pub trait WeightInfo {
 	fn set_config_with_u32() -> Weight;
+	fn update_outbound_scheduling() -> Weight;
 	fn enqueue_xcmp_message() -> Weight;
 	fn suspend_channel() -> Weight;
 	fn resume_channel() -> Weight;
@@ -69,6 +70,15 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
 			.saturating_add(T::DbWeight::get().reads(1_u64))
 			.saturating_add(T::DbWeight::get().writes(1_u64))
 	}
+	/// Storage: `XcmpQueue::OutboundSchedulingConfig` (r:0 w:1)
+	/// Proof: `XcmpQueue::OutboundSchedulingConfig` (`max_values`: Some(1), `max_size`: Some(5), added: 500, mode: `MaxEncodedLen`)
+	fn update_outbound_scheduling() -> Weight {
+		// Proof Size summary in bytes:
+		//  Measured:  `0`
+		//  Estimated: `0`
+		Weight::from_parts(6_000_000, 0)
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
 	/// Storage: `XcmpQueue::QueueConfig` (r:1 w:0)
 	/// Proof: `XcmpQueue::QueueConfig` (`max_values`: Some(1), `max_size`: None, mode: `Measured`)
 	/// Storage: `MessageQueue::BookStateFor` (r:1 w:1)
@@ -166,6 +176,15 @@ impl WeightInfo for () {
 			.saturating_add(RocksDbWeight::get().reads(1_u64))
 			.saturating_add(RocksDbWeight::get().writes(1_u64))
 	}
+	/// Storage: `XcmpQueue::OutboundSchedulingConfig` (r:0 w:1)
+	/// Proof: `XcmpQueue::OutboundSchedulingConfig` (`max_values`: Some(1), `max_size`: Some(5), added: 500, mode: `MaxEncodedLen`)
+	fn update_outbound_scheduling() -> Weight {
+		// Proof Size summary in bytes:
+		//  Measured:  `0`
+		//  Estimated: `0`
+		Weight::from_parts(6_000_000, 0)
+			.saturating_add(RocksDbWeight::get().writes(1_u64))
+	}
 	/// Storage: `XcmpQueue::QueueConfig` (r:1 w:0)
 	/// Proof: `XcmpQueue::QueueConfig` (`max_values`: Some(1), `max_size`: None, mode: `Measured`)
 	/// Storage: `MessageQueue::BookStateFor` (r:1 w:1)
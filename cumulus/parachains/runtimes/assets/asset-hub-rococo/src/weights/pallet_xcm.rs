@@ -395,4 +395,17 @@ impl<T: frame_system::Config> pallet_xcm::WeightInfo for WeightInfo<T> {
 			.saturating_add(T::DbWeight::get().reads(1))
 			.saturating_add(T::DbWeight::get().writes(1))
 	}
+	/// The range of component `n` is `[1, 64]`.
+	fn force_xcm_versions(n: u32, ) -> Weight {
+		// Scaled from `force_xcm_version` until the benchmark is run for this runtime.
+		<Self as pallet_xcm::WeightInfo>::force_xcm_version().saturating_mul(n.into())
+	}
+	/// The range of component `n` is `[1, 64]`.
+	fn force_version_requery(n: u32, ) -> Weight {
+		// Scaled from `force_unsubscribe_version_notify` and `force_subscribe_version_notify`
+		// until the benchmark is run for this runtime.
+		<Self as pallet_xcm::WeightInfo>::force_unsubscribe_version_notify()
+			.saturating_add(<Self as pallet_xcm::WeightInfo>::force_subscribe_version_notify())
+			.saturating_mul(n.into())
+	}
 }
@@ -104,7 +104,7 @@ use xcm::latest::prelude::{
 };
 
 use xcm_runtime_apis::{
-	dry_run::{CallDryRunEffects, Error as XcmDryRunApiError, XcmDryRunEffects},
+	dry_run::{CallDryRunEffects, Error as XcmDryRunApiError, TracedEffects, XcmDryRunEffects},
 	fees::Error as XcmPaymentApiError,
 };
 
@@ -1574,6 +1574,7 @@ impl_runtime_apis! {
 		}
 	}
 
+	#[api_version(2)]
 	impl xcm_runtime_apis::dry_run::DryRunApi<Block, RuntimeCall, RuntimeEvent, OriginCaller> for Runtime {
 		fn dry_run_call(origin: OriginCaller, call: RuntimeCall) -> Result<CallDryRunEffects<RuntimeEvent>, XcmDryRunApiError> {
 			PolkadotXcm::dry_run_call::<Runtime, xcm_config::XcmRouter, OriginCaller, RuntimeCall>(origin, call)
@@ -1582,6 +1583,14 @@ impl_runtime_apis! {
 		fn dry_run_xcm(origin_location: VersionedLocation, xcm: VersionedXcm<RuntimeCall>) -> Result<XcmDryRunEffects<RuntimeEvent>, XcmDryRunApiError> {
 			PolkadotXcm::dry_run_xcm::<Runtime, xcm_config::XcmRouter, RuntimeCall, xcm_config::XcmConfig>(origin_location, xcm)
 		}
+
+		fn dry_run_call_with_trace(origin: OriginCaller, call: RuntimeCall) -> Result<TracedEffects<CallDryRunEffects<RuntimeEvent>>, XcmDryRunApiError> {
+			PolkadotXcm::dry_run_call_with_trace::<Runtime, xcm_config::XcmRouter, OriginCaller, RuntimeCall>(origin, call)
+		}
+
+		fn dry_run_xcm_with_trace(origin_location: VersionedLocation, xcm: VersionedXcm<RuntimeCall>) -> Result<TracedEffects<XcmDryRunEffects<RuntimeEvent>>, XcmDryRunApiError> {
+			PolkadotXcm::dry_run_xcm_with_trace::<Runtime, xcm_config::XcmRouter, RuntimeCall, xcm_config::XcmConfig>(origin_location, xcm)
+		}
 	}
 
 	impl xcm_runtime_apis::conversions::LocationToAccountApi<Block, AccountId> for Runtime {
@@ -1665,6 +1674,16 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl cumulus_pallet_xcmp_queue::runtime_api::XcmpQueueApi<Block> for Runtime {
+		fn outbound_channels() -> Vec<cumulus_pallet_xcmp_queue::runtime_api::OutboundChannelInfo> {
+			XcmpQueue::outbound_channels()
+		}
+
+		fn suspended_inbound_channels() -> Vec<cumulus_pallet_xcmp_queue::runtime_api::InboundChannelInfo> {
+			XcmpQueue::suspended_inbound_channels()
+		}
+	}
+
 	impl cumulus_primitives_core::GetCoreSelectorApi<Block> for Runtime {
 		fn core_selector() -> (CoreSelector, ClaimQueueOffset) {
 			ParachainSystem::core_selector()
@@ -2071,6 +2090,18 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl xcm_runtime_apis::versions::XcmVersionsApi<Block> for Runtime {
+		fn known_destinations() -> Vec<xcm_runtime_apis::versions::DestinationVersion> {
+			PolkadotXcm::known_destinations()
+		}
+		fn destination_version(location: VersionedLocation) -> Result<xcm_runtime_apis::versions::DestinationVersion, xcm_runtime_apis::versions::Error> {
+			PolkadotXcm::destination_version(location)
+		}
+		fn safe_xcm_version() -> Option<xcm::Version> {
+			PolkadotXcm::safe_xcm_version()
+		}
+	}
+
 	impl pallet_revive::ReviveApi<Block, AccountId, Balance, Nonce, BlockNumber, EventRecord> for Runtime
 	{
 		fn balance(address: H160) -> U256 {
@@ -23,7 +23,10 @@
 
 use super::{weights, AccountId, Balance, Balances, BlockNumber, Runtime, RuntimeEvent};
 use bp_parachains::SingleParaStoredHeaderDataBuilder;
-use frame_support::{parameter_types, traits::ConstU32};
+use frame_support::{
+	parameter_types,
+	traits::{ConstBool, ConstU32},
+};
 
 parameter_types! {
 	pub const RelayChainHeadersToKeep: u32 = 1024;
@@ -35,6 +38,9 @@ parameter_types! {
 	pub storage RequiredStakeForStakeAndSlash: Balance = 1_000_000;
 	pub const RelayerStakeLease: u32 = 8;
 	pub const RelayerStakeReserveId: [u8; 8] = *b"brdgrlrs";
+	pub const MaxRelayerInvalidSubmissions: u32 = 8;
+	pub const RelayerPenaltyDuration: BlockNumber = 300;
+	pub const MaxRelayerDeliveredMessagesRecords: u32 = 64;
 
 	pub storage DeliveryRewardInBalance: u64 = 1_000_000;
 }
@@ -47,6 +53,8 @@ impl pallet_bridge_grandpa::Config<BridgeGrandpaWestendInstance> for Runtime {
 	type MaxFreeHeadersPerBlock = ConstU32<4>;
 	type FreeHeadersInterval = ConstU32<5>;
 	type HeadersToKeep = RelayChainHeadersToKeep;
+	type ArchivePrunedHeaders = ConstBool<true>;
+	type AuthoritySetChangesToKeep = ConstU32<256>;
 	type WeightInfo = weights::pallet_bridge_grandpa::WeightInfo<Runtime>;
 }
 
@@ -83,6 +91,9 @@ impl pallet_bridge_relayers::Config<RelayersForLegacyLaneIdsMessagesInstance> fo
 	>;
 	type WeightInfo = weights::pallet_bridge_relayers::WeightInfo<Runtime>;
 	type LaneId = bp_messages::LegacyLaneId;
+	type MaxInvalidSubmissions = MaxRelayerInvalidSubmissions;
+	type PenaltyDuration = RelayerPenaltyDuration;
+	type MaxDeliveredMessagesRecords = MaxRelayerDeliveredMessagesRecords;
 }
 
 /// Allows collect and claim rewards for relayers
@@ -105,6 +116,9 @@ impl pallet_bridge_relayers::Config<RelayersForPermissionlessLanesInstance> for
 	>;
 	type WeightInfo = weights::pallet_bridge_relayers::WeightInfo<Runtime>;
 	type LaneId = bp_messages::HashedLaneId;
+	type MaxInvalidSubmissions = MaxRelayerInvalidSubmissions;
+	type PenaltyDuration = RelayerPenaltyDuration;
+	type MaxDeliveredMessagesRecords = MaxRelayerDeliveredMessagesRecords;
 }
 
 /// Add GRANDPA bridge pallet to track Rococo Bulletin chain.
@@ -115,6 +129,8 @@ impl pallet_bridge_grandpa::Config<BridgeGrandpaRococoBulletinInstance> for Runt
 	type MaxFreeHeadersPerBlock = ConstU32<4>;
 	type FreeHeadersInterval = ConstU32<5>;
 	type HeadersToKeep = RelayChainHeadersToKeep;
+	type ArchivePrunedHeaders = ConstBool<false>;
+	type AuthoritySetChangesToKeep = ConstU32<0>;
 	// Technically this is incorrect - we have two pallet instances and ideally we shall
 	// benchmark every instance separately. But the benchmarking engine has a flaw - it
 	// messes with components. E.g. in Kusama maximal validators count is 1024 and in
@@ -900,12 +900,23 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl cumulus_pallet_xcmp_queue::runtime_api::XcmpQueueApi<Block> for Runtime {
+		fn outbound_channels() -> Vec<cumulus_pallet_xcmp_queue::runtime_api::OutboundChannelInfo> {
+			XcmpQueue::outbound_channels()
+		}
+
+		fn suspended_inbound_channels() -> Vec<cumulus_pallet_xcmp_queue::runtime_api::InboundChannelInfo> {
+			XcmpQueue::suspended_inbound_channels()
+		}
+	}
+
 	impl cumulus_primitives_core::GetCoreSelectorApi<Block> for Runtime {
 		fn core_selector() -> (CoreSelector, ClaimQueueOffset) {
 			ParachainSystem::core_selector()
 		}
 	}
 
+	#[api_version(2)]
 	impl bp_westend::WestendFinalityApi<Block> for Runtime {
 		fn best_finalized() -> Option<HeaderId<bp_westend::Hash, bp_westend::BlockNumber>> {
 			BridgeWestendGrandpa::best_finalized()
@@ -919,6 +930,12 @@ impl_runtime_apis! {
 		) -> Vec<bp_header_chain::StoredHeaderGrandpaInfo<bp_westend::Header>> {
 			BridgeWestendGrandpa::synced_headers_grandpa_info()
 		}
+		fn grandpa_authority_set_changes(
+			from_set_id: u64,
+			limit: u32,
+		) -> Vec<bp_header_chain::StoredHeaderGrandpaInfo<bp_westend::Header>> {
+			BridgeWestendGrandpa::authority_set_changes(from_set_id, limit)
+		}
 	}
 
 	impl bp_bridge_hub_westend::BridgeHubWestendFinalityApi<Block> for Runtime {
@@ -960,6 +977,7 @@ impl_runtime_apis! {
 		}
 	}
 
+	#[api_version(2)]
 	impl bp_polkadot_bulletin::PolkadotBulletinFinalityApi<Block> for Runtime {
 		fn best_finalized() -> Option<bp_runtime::HeaderId<bp_polkadot_bulletin::Hash, bp_polkadot_bulletin::BlockNumber>> {
 			BridgePolkadotBulletinGrandpa::best_finalized()
@@ -975,6 +993,13 @@ impl_runtime_apis! {
 		) -> Vec<bp_header_chain::StoredHeaderGrandpaInfo<bp_polkadot_bulletin::Header>> {
 			BridgePolkadotBulletinGrandpa::synced_headers_grandpa_info()
 		}
+
+		fn grandpa_authority_set_changes(
+			from_set_id: u64,
+			limit: u32,
+		) -> Vec<bp_header_chain::StoredHeaderGrandpaInfo<bp_polkadot_bulletin::Header>> {
+			BridgePolkadotBulletinGrandpa::authority_set_changes(from_set_id, limit)
+		}
 	}
 
 	impl bp_polkadot_bulletin::FromPolkadotBulletinInboundLaneApi<Block> for Runtime {
@@ -29,6 +29,9 @@ parameter_types! {
 	pub storage RequiredStakeForStakeAndSlash: Balance = 1_000_000;
 	pub const RelayerStakeLease: u32 = 8;
 	pub const RelayerStakeReserveId: [u8; 8] = *b"brdgrlrs";
+	pub const MaxRelayerInvalidSubmissions: u32 = 8;
+	pub const RelayerPenaltyDuration: BlockNumber = 300;
+	pub const MaxRelayerDeliveredMessagesRecords: u32 = 64;
 
 	pub storage DeliveryRewardInBalance: u64 = 1_000_000;
 }
@@ -53,4 +56,7 @@ impl pallet_bridge_relayers::Config<RelayersForLegacyLaneIdsMessagesInstance> fo
 	>;
 	type WeightInfo = weights::pallet_bridge_relayers::WeightInfo<Runtime>;
 	type LaneId = LegacyLaneId;
+	type MaxInvalidSubmissions = MaxRelayerInvalidSubmissions;
+	type PenaltyDuration = RelayerPenaltyDuration;
+	type MaxDeliveredMessagesRecords = MaxRelayerDeliveredMessagesRecords;
 }
@@ -33,7 +33,7 @@ use pallet_xcm_bridge_hub::XcmAsPlainPayload;
 
 use frame_support::{
 	parameter_types,
-	traits::{ConstU32, PalletInfoAccess},
+	traits::{ConstBool, ConstU32, PalletInfoAccess},
 };
 use frame_system::{EnsureNever, EnsureRoot};
 use pallet_bridge_messages::LaneIdOf;
@@ -114,6 +114,8 @@ impl pallet_bridge_grandpa::Config<BridgeGrandpaRococoInstance> for Runtime {
 	type MaxFreeHeadersPerBlock = ConstU32<4>;
 	type FreeHeadersInterval = ConstU32<5>;
 	type HeadersToKeep = RelayChainHeadersToKeep;
+	type ArchivePrunedHeaders = ConstBool<true>;
+	type AuthoritySetChangesToKeep = ConstU32<256>;
 	type WeightInfo = weights::pallet_bridge_grandpa::WeightInfo<Runtime>;
 }
 
@@ -833,12 +833,23 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl cumulus_pallet_xcmp_queue::runtime_api::XcmpQueueApi<Block> for Runtime {
+		fn outbound_channels() -> Vec<cumulus_pallet_xcmp_queue::runtime_api::OutboundChannelInfo> {
+			XcmpQueue::outbound_channels()
+		}
+
+		fn suspended_inbound_channels() -> Vec<cumulus_pallet_xcmp_queue::runtime_api::InboundChannelInfo> {
+			XcmpQueue::suspended_inbound_channels()
+		}
+	}
+
 	impl cumulus_primitives_core::GetCoreSelectorApi<Block> for Runtime {
 		fn core_selector() -> (CoreSelector, ClaimQueueOffset) {
 			ParachainSystem::core_selector()
 		}
 	}
 
+	#[api_version(2)]
 	impl bp_rococo::RococoFinalityApi<Block> for Runtime {
 		fn best_finalized() -> Option<HeaderId<bp_rococo::Hash, bp_rococo::BlockNumber>> {
 			BridgeRococoGrandpa::best_finalized()
@@ -852,6 +863,12 @@ impl_runtime_apis! {
 		) -> Vec<bp_header_chain::StoredHeaderGrandpaInfo<bp_rococo::Header>> {
 			BridgeRococoGrandpa::synced_headers_grandpa_info()
 		}
+		fn grandpa_authority_set_changes(
+			from_set_id: u64,
+			limit: u32,
+		) -> Vec<bp_header_chain::StoredHeaderGrandpaInfo<bp_rococo::Header>> {
+			BridgeRococoGrandpa::authority_set_changes(from_set_id, limit)
+		}
 	}
 
 	impl bp_bridge_hub_rococo::BridgeHubRococoFinalityApi<Block> for Runtime {
@@ -1017,6 +1017,16 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl cumulus_pallet_xcmp_queue::runtime_api::XcmpQueueApi<Block> for Runtime {
+		fn outbound_channels() -> Vec<cumulus_pallet_xcmp_queue::runtime_api::OutboundChannelInfo> {
+			XcmpQueue::outbound_channels()
+		}
+
+		fn suspended_inbound_channels() -> Vec<cumulus_pallet_xcmp_queue::runtime_api::InboundChannelInfo> {
+			XcmpQueue::suspended_inbound_channels()
+		}
+	}
+
 	impl cumulus_primitives_core::GetCoreSelectorApi<Block> for Runtime {
 		fn core_selector() -> (CoreSelector, ClaimQueueOffset) {
 			ParachainSystem::core_selector()
@@ -324,6 +324,8 @@ pub(crate) trait NodeSpec: BaseNodeSpec {
 					relay_chain_slot_duration,
 					recovery_handle: Box::new(overseer_handle.clone()),
 					sync_service,
+					pov_recovery_config: collator_options.pov_recovery_config,
+					prometheus_registry: prometheus_registry.as_ref(),
 				})?;
 
 				if validator {
@@ -0,0 +1,182 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Storage weight reclaim for pallet hooks.
+
+use crate::{get_proof_size, StorageWeightReclaimer, LOG_TARGET};
+use core::marker::PhantomData;
+use cumulus_primitives_core::Weight;
+use frame_support::{
+	traits::{
+		BeforeAllRuntimeMigrations, OffchainWorker, OnFinalize, OnIdle, OnInitialize, OnPoll,
+		OnRuntimeUpgrade,
+	},
+	weights::WeightMeter,
+};
+
+#[cfg(feature = "try-runtime")]
+use alloc::vec::Vec;
+#[cfg(feature = "try-runtime")]
+use frame_support::traits::{
+	TryDecodeEntireStorage, TryDecodeEntireStorageError, TryState, TryStateSelect,
+};
+#[cfg(feature = "try-runtime")]
+use sp_runtime::TryRuntimeError;
+
+/// Wrapper around the hooks of all pallets that reclaims the proof size weight of the
+/// `on_initialize`, `on_idle` and `on_poll` hooks.
+///
+/// The weight returned by the wrapped hooks is corrected to the proof size that was actually
+/// recorded by the node while executing them. All other hooks are forwarded unchanged.
+///
+/// Use it in place of `AllPalletsWithSystem` when declaring the `Executive`:
+///
+/// ```ignore
+/// pub type Executive = frame_executive::Executive<
+/// 	Runtime,
+/// 	Block,
+/// 	frame_system::ChainContext<Runtime>,
+/// 	Runtime,
+/// 	cumulus_primitives_storage_weight_reclaim::StorageWeightReclaimHooks<AllPalletsWithSystem>,
+/// >;
+/// ```
+pub struct StorageWeightReclaimHooks<Hooks>(PhantomData<Hooks>);
+
+impl<Hooks> StorageWeightReclaimHooks<Hooks> {
+	/// Execute `hook` and correct the proof size of the weight it returns to the proof size
+	/// recorded on the node side.
+	fn reclaim(hook_name: &str, hook: impl FnOnce() -> Weight) -> Weight {
+		let Some(pre_hook_proof_size) = get_proof_size() else { return hook() };
+
+		let weight = hook();
+
+		let Some(post_hook_proof_size) = get_proof_size() else {
+			log::debug!(
+				target: LOG_TARGET,
+				"Proof recording enabled before `{hook_name}`, now disabled. This should not happen."
+			);
+			return weight
+		};
+
+		let benchmarked = weight.proof_size();
+		let consumed = post_hook_proof_size.saturating_sub(pre_hook_proof_size);
+		if consumed > benchmarked {
+			log::error!(
+				target: LOG_TARGET,
+				"Benchmarked storage weight smaller than consumed storage weight. hook: {hook_name} benchmarked: {benchmarked} consumed: {consumed}"
+			);
+		} else {
+			log::trace!(
+				target: LOG_TARGET,
+				"Reclaiming storage weight. hook: {hook_name} benchmarked: {benchmarked} consumed: {consumed}"
+			);
+		}
+
+		weight.set_proof_size(consumed)
+	}
+}
+
+impl<BlockNumber, Hooks> OnInitialize<BlockNumber> for StorageWeightReclaimHooks<Hooks>
+where
+	Hooks: OnInitialize<BlockNumber>,
+{
+	fn on_initialize(n: BlockNumber) -> Weight {
+		Self::reclaim("on_initialize", || Hooks::on_initialize(n))
+	}
+}
+
+impl<BlockNumber, Hooks> OnIdle<BlockNumber> for StorageWeightReclaimHooks<Hooks>
+where
+	Hooks: OnIdle<BlockNumber>,
+{
+	fn on_idle(n: BlockNumber, remaining_weight: Weight) -> Weight {
+		Self::reclaim("on_idle", || Hooks::on_idle(n, remaining_weight))
+	}
+}
+
+impl<BlockNumber, Hooks> OnPoll<BlockNumber> for StorageWeightReclaimHooks<Hooks>
+where
+	Hooks: OnPoll<BlockNumber>,
+{
+	fn on_poll(n: BlockNumber, weight: &mut WeightMeter) {
+		let mut reclaimer = StorageWeightReclaimer::new(weight);
+		Hooks::on_poll(n, weight);
+		reclaimer.reclaim_with_meter(weight);
+	}
+}
+
+impl<BlockNumber, Hooks> OnFinalize<BlockNumber> for StorageWeightReclaimHooks<Hooks>
+where
+	Hooks: OnFinalize<BlockNumber>,
+{
+	fn on_finalize(n: BlockNumber) {
+		Hooks::on_finalize(n)
+	}
+}
+
+impl<BlockNumber, Hooks> OffchainWorker<BlockNumber> for StorageWeightReclaimHooks<Hooks>
+where
+	Hooks: OffchainWorker<BlockNumber>,
+{
+	fn offchain_worker(n: BlockNumber) {
+		Hooks::offchain_worker(n)
+	}
+}
+
+impl<Hooks: BeforeAllRuntimeMigrations> BeforeAllRuntimeMigrations
+	for StorageWeightReclaimHooks<Hooks>
+{
+	fn before_all_runtime_migrations() -> Weight {
+		Hooks::before_all_runtime_migrations()
+	}
+}
+
+impl<Hooks: OnRuntimeUpgrade> OnRuntimeUpgrade for StorageWeightReclaimHooks<Hooks> {
+	fn on_runtime_upgrade() -> Weight {
+		Hooks::on_runtime_upgrade()
+	}
+
+	#[cfg(feature = "try-runtime")]
+	fn try_on_runtime_upgrade(checks: bool) -> Result<Weight, TryRuntimeError> {
+		Hooks::try_on_runtime_upgrade(checks)
+	}
+
+	#[cfg(feature = "try-runtime")]
+	fn pre_upgrade() -> Result<Vec<u8>, TryRuntimeError> {
+		Hooks::pre_upgrade()
+	}
+
+	#[cfg(feature = "try-runtime")]
+	fn post_upgrade(state: Vec<u8>) -> Result<(), TryRuntimeError> {
+		Hooks::post_upgrade(state)
+	}
+}
+
+#[cfg(feature = "try-runtime")]
+impl<BlockNumber, Hooks> TryState<BlockNumber> for StorageWeightReclaimHooks<Hooks>
+where
+	Hooks: TryState<BlockNumber>,
+{
+	fn try_state(n: BlockNumber, targets: TryStateSelect) -> Result<(), TryRuntimeError> {
+		Hooks::try_state(n, targets)
+	}
+}
+
+#[cfg(feature = "try-runtime")]
+impl<Hooks: TryDecodeEntireStorage> TryDecodeEntireStorage for StorageWeightReclaimHooks<Hooks> {
+	fn try_decode_entire_state() -> Result<usize, Vec<TryDecodeEntireStorageError>> {
+		Hooks::try_decode_entire_state()
+	}
+}
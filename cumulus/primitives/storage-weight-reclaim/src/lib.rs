@@ -14,9 +14,15 @@
 // limitations under the License.
 
 //! Mechanism to reclaim PoV proof size weight after an extrinsic has been applied.
+//!
+//! The [`StorageWeightReclaim`] transaction extension reclaims the proof size weight of signed and
+//! general extrinsics as well as of inherents. [`StorageWeightReclaimHooks`] does the same for the
+//! `on_initialize`, `on_idle` and `on_poll` hooks of all pallets.
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
+extern crate alloc;
+
 use codec::{Decode, Encode};
 use core::marker::PhantomData;
 use cumulus_primitives_core::Weight;
@@ -39,8 +45,56 @@ use sp_runtime::{
 #[cfg(test)]
 mod tests;
 
+mod hooks;
+pub use hooks::StorageWeightReclaimHooks;
+
 const LOG_TARGET: &'static str = "runtime::storage_reclaim";
 
+/// Proof size recorded before dispatching a bare extrinsic.
+///
+/// Bare extrinsics, e.g. inherents, can not pass any state from
+/// [`TransactionExtension::bare_validate_and_prepare`] to
+/// [`TransactionExtension::bare_post_dispatch`], so the proof size is kept in memory in between.
+/// Keeping it out of the state ensures that it is neither part of the storage proof nor of the
+/// storage root.
+mod bare_proof_size {
+	use core::cell::Cell;
+
+	#[cfg(feature = "std")]
+	std::thread_local! {
+		static PROOF_SIZE: Cell<Option<u64>> = const { Cell::new(None) };
+	}
+
+	#[cfg(feature = "std")]
+	pub(crate) fn set(proof_size: Option<u64>) {
+		PROOF_SIZE.with(|p| p.set(proof_size))
+	}
+
+	#[cfg(feature = "std")]
+	pub(crate) fn take() -> Option<u64> {
+		PROOF_SIZE.with(|p| p.take())
+	}
+
+	#[cfg(not(feature = "std"))]
+	struct ProofSize(Cell<Option<u64>>);
+	// NOTE: Safe only in wasm (guarded above) because there's only one thread.
+	#[cfg(not(feature = "std"))]
+	unsafe impl Sync for ProofSize {}
+
+	#[cfg(not(feature = "std"))]
+	static PROOF_SIZE: ProofSize = ProofSize(Cell::new(None));
+
+	#[cfg(not(feature = "std"))]
+	pub(crate) fn set(proof_size: Option<u64>) {
+		PROOF_SIZE.0.set(proof_size)
+	}
+
+	#[cfg(not(feature = "std"))]
+	pub(crate) fn take() -> Option<u64> {
+		PROOF_SIZE.0.take()
+	}
+}
+
 /// `StorageWeightReclaimer` is a mechanism for manually reclaiming storage weight.
 ///
 /// It internally keeps track of the proof size and storage weight at initialization time. At
@@ -123,43 +177,23 @@ impl<T: Config + Send + Sync> core::fmt::Debug for StorageWeightReclaim<T> {
 	}
 }
 
-impl<T: Config + Send + Sync> TransactionExtension<T::RuntimeCall> for StorageWeightReclaim<T>
+impl<T: Config + Send + Sync> StorageWeightReclaim<T>
 where
 	T::RuntimeCall: Dispatchable<Info = DispatchInfo, PostInfo = PostDispatchInfo>,
 {
-	const IDENTIFIER: &'static str = "StorageWeightReclaim";
-	type Implicit = ();
-	type Val = ();
-	type Pre = Option<u64>;
-
-	fn prepare(
-		self,
-		_val: Self::Val,
-		_origin: &T::RuntimeOrigin,
-		_call: &T::RuntimeCall,
-		_info: &DispatchInfoOf<T::RuntimeCall>,
-		_len: usize,
-	) -> Result<Self::Pre, TransactionValidityError> {
-		Ok(get_proof_size())
-	}
-
-	fn post_dispatch_details(
-		pre: Self::Pre,
+	/// Correct the proof size of [`frame_system::BlockWeight`] after an extrinsic has been
+	/// dispatched, given the proof size reported by the node before the dispatch.
+	fn do_post_dispatch(
+		pre_dispatch_proof_size: u64,
 		info: &DispatchInfoOf<T::RuntimeCall>,
 		post_info: &PostDispatchInfoOf<T::RuntimeCall>,
-		_len: usize,
-		_result: &DispatchResult,
-	) -> Result<Weight, TransactionValidityError> {
-		let Some(pre_dispatch_proof_size) = pre else {
-			return Ok(Weight::zero());
-		};
-
+	) {
 		let Some(post_dispatch_proof_size) = get_proof_size() else {
 			log::debug!(
 				target: LOG_TARGET,
 				"Proof recording enabled during pre-dispatch, now disabled. This should not happen."
 			);
-			return Ok(Weight::zero())
+			return
 		};
 		// Unspent weight according to the `actual_weight` from `PostDispatchInfo`
 		// This unspent weight will be refunded by the `CheckWeight` extension, so we need to
@@ -205,8 +239,62 @@ where
 				current.accrue(Weight::from_parts(0, missing_from_node), info.class);
 			}
 		});
+	}
+}
+
+impl<T: Config + Send + Sync> TransactionExtension<T::RuntimeCall> for StorageWeightReclaim<T>
+where
+	T::RuntimeCall: Dispatchable<Info = DispatchInfo, PostInfo = PostDispatchInfo>,
+{
+	const IDENTIFIER: &'static str = "StorageWeightReclaim";
+	type Implicit = ();
+	type Val = ();
+	type Pre = Option<u64>;
+
+	fn prepare(
+		self,
+		_val: Self::Val,
+		_origin: &T::RuntimeOrigin,
+		_call: &T::RuntimeCall,
+		_info: &DispatchInfoOf<T::RuntimeCall>,
+		_len: usize,
+	) -> Result<Self::Pre, TransactionValidityError> {
+		Ok(get_proof_size())
+	}
+
+	fn post_dispatch_details(
+		pre: Self::Pre,
+		info: &DispatchInfoOf<T::RuntimeCall>,
+		post_info: &PostDispatchInfoOf<T::RuntimeCall>,
+		_len: usize,
+		_result: &DispatchResult,
+	) -> Result<Weight, TransactionValidityError> {
+		if let Some(pre_dispatch_proof_size) = pre {
+			Self::do_post_dispatch(pre_dispatch_proof_size, info, post_info);
+		}
 		Ok(Weight::zero())
 	}
 
+	fn bare_validate_and_prepare(
+		_call: &T::RuntimeCall,
+		_info: &DispatchInfoOf<T::RuntimeCall>,
+		_len: usize,
+	) -> Result<(), TransactionValidityError> {
+		bare_proof_size::set(get_proof_size());
+		Ok(())
+	}
+
+	fn bare_post_dispatch(
+		info: &DispatchInfoOf<T::RuntimeCall>,
+		post_info: &mut PostDispatchInfoOf<T::RuntimeCall>,
+		_len: usize,
+		_result: &DispatchResult,
+	) -> Result<(), TransactionValidityError> {
+		if let Some(pre_dispatch_proof_size) = bare_proof_size::take() {
+			Self::do_post_dispatch(pre_dispatch_proof_size, info, post_info);
+		}
+		Ok(())
+	}
+
 	impl_tx_ext_default!(T::RuntimeCall; weight validate);
 }
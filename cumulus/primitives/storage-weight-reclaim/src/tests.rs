@@ -18,6 +18,7 @@ use core::marker::PhantomData;
 use frame_support::{
 	assert_ok,
 	dispatch::{DispatchClass, PerDispatchClass},
+	traits::{OnIdle, OnInitialize, OnPoll},
 	weights::{Weight, WeightMeter},
 };
 use frame_system::{BlockWeight, CheckWeight};
@@ -704,3 +705,133 @@ fn test_reclaim_helper_works_with_meter() {
 		reclaim_with_weight_meter();
 	});
 }
+
+#[test]
+fn bare_extrinsic_refund() {
+	// The real cost will be 100 bytes of storage size
+	let mut test_ext = setup_test_externalities(&[0, 100]);
+
+	test_ext.execute_with(|| {
+		set_current_storage_weight(1000);
+
+		// Benchmarked storage weight: 500
+		let info = DispatchInfo {
+			call_weight: Weight::from_parts(0, 500),
+			class: DispatchClass::Mandatory,
+			..Default::default()
+		};
+		let mut post_info = PostDispatchInfo::default();
+
+		// Should add 500 + 150 (len) to weight.
+		assert_ok!(CheckWeight::<Test>::bare_validate_and_prepare(CALL, &info, LEN));
+		assert_ok!(StorageWeightReclaim::<Test>::bare_validate_and_prepare(CALL, &info, LEN));
+
+		assert_ok!(CheckWeight::<Test>::bare_post_dispatch(&info, &mut post_info, LEN, &Ok(())));
+		// We expect a refund of 400
+		assert_ok!(StorageWeightReclaim::<Test>::bare_post_dispatch(
+			&info,
+			&mut post_info,
+			LEN,
+			&Ok(()),
+		));
+
+		assert_eq!(get_storage_weight().total().proof_size(), 1250);
+		// The stashed proof size is removed again.
+		assert_eq!(bare_proof_size::take(), None);
+	})
+}
+
+#[test]
+fn bare_extrinsic_does_nothing_without_extension() {
+	let mut test_ext = new_test_ext();
+
+	test_ext.execute_with(|| {
+		set_current_storage_weight(1000);
+
+		let info = DispatchInfo {
+			call_weight: Weight::from_parts(0, 500),
+			class: DispatchClass::Mandatory,
+			..Default::default()
+		};
+		let mut post_info = PostDispatchInfo::default();
+
+		assert_ok!(CheckWeight::<Test>::bare_validate_and_prepare(CALL, &info, LEN));
+		assert_ok!(StorageWeightReclaim::<Test>::bare_validate_and_prepare(CALL, &info, LEN));
+		assert_eq!(bare_proof_size::take(), None);
+
+		assert_ok!(CheckWeight::<Test>::bare_post_dispatch(&info, &mut post_info, LEN, &Ok(())));
+		assert_ok!(StorageWeightReclaim::<Test>::bare_post_dispatch(
+			&info,
+			&mut post_info,
+			LEN,
+			&Ok(()),
+		));
+
+		assert_eq!(get_storage_weight().total().proof_size(), 1650);
+	})
+}
+
+struct TestHooks;
+
+impl OnInitialize<u32> for TestHooks {
+	fn on_initialize(_: u32) -> Weight {
+		Weight::from_parts(10, 500)
+	}
+}
+
+impl OnIdle<u32> for TestHooks {
+	fn on_idle(_: u32, _: Weight) -> Weight {
+		Weight::from_parts(10, 50)
+	}
+}
+
+impl OnPoll<u32> for TestHooks {
+	fn on_poll(_: u32, weight: &mut WeightMeter) {
+		weight.consume(Weight::from_parts(10, 500));
+	}
+}
+
+#[test]
+fn hooks_reclaim_proof_size() {
+	// The hook consumes 100 bytes of storage size.
+	let mut test_ext = setup_test_externalities(&[1000, 1100]);
+	test_ext.execute_with(|| {
+		// Benchmarked storage weight is 500.
+		assert_eq!(
+			StorageWeightReclaimHooks::<TestHooks>::on_initialize(1),
+			Weight::from_parts(10, 100)
+		);
+	});
+
+	// The hook consumes more than the benchmarked 50 bytes of storage size.
+	let mut test_ext = setup_test_externalities(&[1000, 1100]);
+	test_ext.execute_with(|| {
+		assert_eq!(
+			StorageWeightReclaimHooks::<TestHooks>::on_idle(1, Weight::MAX),
+			Weight::from_parts(10, 100)
+		);
+	});
+
+	let mut test_ext = setup_test_externalities(&[1000, 1100]);
+	test_ext.execute_with(|| {
+		let mut meter = WeightMeter::with_limit(Weight::from_parts(100, 1000));
+		StorageWeightReclaimHooks::<TestHooks>::on_poll(1, &mut meter);
+		assert_eq!(meter.consumed(), Weight::from_parts(10, 100));
+	});
+}
+
+#[test]
+fn hooks_do_nothing_without_extension() {
+	let mut test_ext = new_test_ext();
+
+	test_ext.execute_with(|| {
+		assert_eq!(
+			StorageWeightReclaimHooks::<TestHooks>::on_initialize(1),
+			Weight::from_parts(10, 500)
+		);
+		assert_eq!(
+			StorageWeightReclaimHooks::<TestHooks>::on_idle(1, Weight::MAX),
+			Weight::from_parts(10, 50)
+		);
+	});
+}
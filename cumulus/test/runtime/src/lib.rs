@@ -391,7 +391,7 @@ pub type Executive = frame_executive::Executive<
 	Block,
 	frame_system::ChainContext<Runtime>,
 	Runtime,
-	AllPalletsWithSystem,
+	cumulus_primitives_storage_weight_reclaim::StorageWeightReclaimHooks<AllPalletsWithSystem>,
 	TestOnRuntimeUpgrade,
 >;
 /// The payload being signed in transactions.
@@ -440,6 +440,8 @@ where
 		relay_chain_slot_duration,
 		recovery_handle,
 		sync_service: sync_service.clone(),
+		pov_recovery_config: collator_options.pov_recovery_config,
+		prometheus_registry: prometheus_registry.as_ref(),
 	})?;
 
 	if let Some(collator_key) = collator_key {
@@ -743,7 +745,10 @@ impl TestNodeBuilder {
 			false,
 		);
 
-		let collator_options = CollatorOptions { relay_chain_mode: self.relay_chain_mode };
+		let collator_options = CollatorOptions {
+			relay_chain_mode: self.relay_chain_mode,
+			pov_recovery_config: Default::default(),
+		};
 
 		relay_chain_config.network.node_name =
 			format!("{} (relay chain)", relay_chain_config.network.node_name);
@@ -855,6 +860,7 @@ pub fn node_config(
 		keystore: KeystoreConfig::InMemory,
 		database: DatabaseSource::RocksDb { path: root.join("db"), cache_size: 128 },
 		trie_cache_maximum_size: Some(64 * 1024 * 1024),
+		import_verification_threads: None,
 		state_pruning: Some(PruningMode::ArchiveAll),
 		blocks_pruning: BlocksPruning::KeepAll,
 		chain_spec: spec,
@@ -883,7 +889,11 @@ pub fn node_config(
 		},
 		prometheus_config: None,
 		telemetry_endpoints: None,
-		offchain_worker: OffchainWorkerConfig { enabled: true, indexing_enabled: false },
+		offchain_worker: OffchainWorkerConfig {
+			enabled: true,
+			indexing_enabled: false,
+			http_policy: Default::default(),
+		},
 		force_authoring: false,
 		disable_grandpa: false,
 		dev_key_seed: Some(key_seed),
@@ -28,6 +28,7 @@ use polkadot_service::{
 #[cfg(feature = "pyroscope")]
 use pyroscope_pprofrs::{pprof_backend, PprofConfig};
 use sc_cli::SubstrateCli;
+use sc_storage_monitor::{DegradationAction, DegradationHandlers};
 use sp_core::crypto::Ss58AddressFormatRegistry;
 use sp_keyring::Sr25519Keyring;
 
@@ -214,7 +215,8 @@ where
 			.flatten();
 
 		let database_source = config.database.clone();
-		let task_manager = polkadot_service::build_full(
+		let prometheus_registry = config.prometheus_registry().cloned();
+		let full = polkadot_service::build_full(
 			config,
 			polkadot_service::NewFullParams {
 				is_parachain_node: polkadot_service::IsParachainNode::No,
@@ -236,18 +238,35 @@ where
 				prepare_workers_soft_max_num: cli.run.prepare_workers_soft_max_num,
 				enable_approval_voting_parallel: cli.run.enable_approval_voting_parallel,
 			},
-		)
-		.map(|full| full.task_manager)?;
+		)?;
 
 		if let Some(path) = database_source.path() {
+			let degraded_pruning = cli.storage_monitor.degraded_pruning;
+			let backend = full.backend.clone();
+			let client = full.client.clone();
+			let rpc_handlers = full.rpc_handlers.clone();
+			let degradation_handlers = DegradationHandlers::new()
+				.with(DegradationAction::AggressivePruning, move |degraded| {
+					backend.set_pruning_override(degraded.then_some(degraded_pruning))
+				})
+				.with(DegradationAction::DisableOffchainIndexing, move |degraded| {
+					client.set_offchain_indexing_suspended(degraded)
+				})
+				.with(DegradationAction::DisableRpcCaches, move |degraded| {
+					rpc_handlers.set_chain_head_suspended(degraded)
+				});
+
 			sc_storage_monitor::StorageMonitorService::try_spawn(
 				cli.storage_monitor,
 				path.to_path_buf(),
-				&task_manager.spawn_essential_handle(),
+				&full.task_manager.spawn_essential_handle(),
+				prometheus_registry.as_ref(),
+				full.telemetry.clone(),
+				degradation_handlers,
 			)?;
 		}
 
-		Ok(task_manager)
+		Ok(full.task_manager)
 	})
 }
 
@@ -83,7 +83,7 @@ use sc_service::KeystoreContainer;
 use sc_service::{RpcHandlers, SpawnTaskHandle};
 use sc_telemetry::TelemetryWorker;
 #[cfg(feature = "full-node")]
-use sc_telemetry::{Telemetry, TelemetryWorkerHandle};
+use sc_telemetry::{Telemetry, TelemetryHandle, TelemetryWorkerHandle};
 
 pub use chain_spec::{GenericChainSpec, RococoChainSpec, WestendChainSpec};
 use frame_benchmarking_cli::SUBSTRATE_REFERENCE_HARDWARE;
@@ -541,6 +541,7 @@ where
 			registry: config.prometheus_registry(),
 			telemetry: telemetry.as_ref().map(|x| x.handle()),
 			offchain_tx_pool_factory: OffchainTransactionPoolFactory::new(transaction_pool.clone()),
+			verification_threads: config.import_verification_threads,
 		})?;
 
 	let justification_stream = grandpa_link.justification_stream();
@@ -646,6 +647,7 @@ pub struct NewFull {
 	pub sync_service: Arc<sc_network_sync::SyncingService<Block>>,
 	pub rpc_handlers: RpcHandlers,
 	pub backend: Arc<FullBackend>,
+	pub telemetry: Option<TelemetryHandle>,
 }
 
 /// Is this node running as in-process node for a parachain node?
@@ -1033,6 +1035,7 @@ pub fn new_full<
 				network_provider: Arc::new(network.clone()),
 				is_validator: role.is_authority(),
 				enable_http_requests: false,
+				http_policy: config.offchain_worker.http_policy.clone(),
 				custom_extensions: move |_| vec![],
 			})?
 			.run(client.clone(), task_manager.spawn_handle())
@@ -1391,6 +1394,7 @@ pub fn new_full<
 		sync_service,
 		rpc_handlers,
 		backend,
+		telemetry: telemetry.as_ref().map(|x| x.handle()),
 	})
 }
 
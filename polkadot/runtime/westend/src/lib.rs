@@ -115,7 +115,7 @@ use xcm::{
 use xcm_builder::PayOverXcm;
 
 use xcm_runtime_apis::{
-	dry_run::{CallDryRunEffects, Error as XcmDryRunApiError, XcmDryRunEffects},
+	dry_run::{CallDryRunEffects, Error as XcmDryRunApiError, TracedEffects, XcmDryRunEffects},
 	fees::Error as XcmPaymentApiError,
 };
 
@@ -2467,6 +2467,7 @@ sp_api::impl_runtime_apis! {
 		}
 	}
 
+	#[api_version(2)]
 	impl xcm_runtime_apis::dry_run::DryRunApi<Block, RuntimeCall, RuntimeEvent, OriginCaller> for Runtime {
 		fn dry_run_call(origin: OriginCaller, call: RuntimeCall) -> Result<CallDryRunEffects<RuntimeEvent>, XcmDryRunApiError> {
 			XcmPallet::dry_run_call::<Runtime, xcm_config::XcmRouter, OriginCaller, RuntimeCall>(origin, call)
@@ -2475,6 +2476,14 @@ sp_api::impl_runtime_apis! {
 		fn dry_run_xcm(origin_location: VersionedLocation, xcm: VersionedXcm<RuntimeCall>) -> Result<XcmDryRunEffects<RuntimeEvent>, XcmDryRunApiError> {
 			XcmPallet::dry_run_xcm::<Runtime, xcm_config::XcmRouter, RuntimeCall, xcm_config::XcmConfig>(origin_location, xcm)
 		}
+
+		fn dry_run_call_with_trace(origin: OriginCaller, call: RuntimeCall) -> Result<TracedEffects<CallDryRunEffects<RuntimeEvent>>, XcmDryRunApiError> {
+			XcmPallet::dry_run_call_with_trace::<Runtime, xcm_config::XcmRouter, OriginCaller, RuntimeCall>(origin, call)
+		}
+
+		fn dry_run_xcm_with_trace(origin_location: VersionedLocation, xcm: VersionedXcm<RuntimeCall>) -> Result<TracedEffects<XcmDryRunEffects<RuntimeEvent>>, XcmDryRunApiError> {
+			XcmPallet::dry_run_xcm_with_trace::<Runtime, xcm_config::XcmRouter, RuntimeCall, xcm_config::XcmConfig>(origin_location, xcm)
+		}
 	}
 
 	impl xcm_runtime_apis::conversions::LocationToAccountApi<Block, AccountId> for Runtime {
@@ -2842,4 +2851,16 @@ sp_api::impl_runtime_apis! {
 			XcmPallet::is_trusted_teleporter(asset, location)
 		}
 	}
+
+	impl xcm_runtime_apis::versions::XcmVersionsApi<Block> for Runtime {
+		fn known_destinations() -> Vec<xcm_runtime_apis::versions::DestinationVersion> {
+			XcmPallet::known_destinations()
+		}
+		fn destination_version(location: VersionedLocation) -> Result<xcm_runtime_apis::versions::DestinationVersion, xcm_runtime_apis::versions::Error> {
+			XcmPallet::destination_version(location)
+		}
+		fn safe_xcm_version() -> Option<xcm::Version> {
+			XcmPallet::safe_xcm_version()
+		}
+	}
 }
@@ -0,0 +1,117 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! The pallet benchmarks.
+
+use super::{Pallet as RateLimiter, *};
+use frame_benchmarking::v2::*;
+use frame_support::traits::EnsureOrigin;
+use xcm::latest::prelude::*;
+
+fn assert_last_event<T: Config>(generic_event: <T as Config>::RuntimeEvent) {
+	frame_system::Pallet::<T>::assert_last_event(generic_event.into());
+}
+
+fn rate_limit<T: Config>() -> RateLimitOf<T> {
+	RateLimit {
+		period: 10u32.into(),
+		max_messages: 10,
+		max_weight: Weight::from_parts(1_000, 1_000),
+	}
+}
+
+#[benchmarks]
+mod benchmarks {
+	use super::*;
+
+	#[benchmark]
+	fn set_rate_limit() -> Result<(), BenchmarkError> {
+		let origin =
+			T::AdminOrigin::try_successful_origin().map_err(|_| BenchmarkError::Weightless)?;
+		let location = VersionedLocation::from(Location::new(1, [Parachain(1000)]));
+		let limit = Some(rate_limit::<T>());
+
+		#[extrinsic_call]
+		_(origin as T::RuntimeOrigin, Box::new(location.clone()), limit);
+
+		assert_eq!(RateLimits::<T>::get(&location), limit);
+		assert_last_event::<T>(Event::RateLimitSet { location, limit }.into());
+		Ok(())
+	}
+
+	#[benchmark]
+	fn set_default_rate_limit() -> Result<(), BenchmarkError> {
+		let origin =
+			T::AdminOrigin::try_successful_origin().map_err(|_| BenchmarkError::Weightless)?;
+		let limit = Some(rate_limit::<T>());
+
+		#[extrinsic_call]
+		_(origin as T::RuntimeOrigin, limit);
+
+		assert_eq!(DefaultRateLimit::<T>::get(), limit);
+		assert_last_event::<T>(Event::DefaultRateLimitSet { limit }.into());
+		Ok(())
+	}
+
+	#[benchmark]
+	fn remove_expired_usage() {
+		let location = VersionedLocation::from(Location::new(1, [Parachain(1000)]));
+		let usage = Usage { period_end: Zero::zero(), messages: 1, weight: Weight::zero() };
+		Usages::<T>::insert(&location, usage);
+		let mut meter = WeightMeter::with_limit(
+			T::WeightInfo::remove_expired_usage()
+				.saturating_add(T::DbWeight::get().reads_writes(1, 1)),
+		);
+
+		#[block]
+		{
+			RateLimiter::<T>::remove_expired_usages(1u32.into(), &mut meter);
+		}
+
+		assert!(Usages::<T>::get(&location).is_none());
+	}
+
+	#[benchmark]
+	fn charge_budget() -> Result<(), BenchmarkError> {
+		// The origin has no rate limit of its own and already used some of its budget.
+		let origin = Location::new(1, [Parachain(1000)]);
+		let location = VersionedLocation::from(origin.clone());
+		DefaultRateLimit::<T>::put(rate_limit::<T>());
+		let now = frame_system::Pallet::<T>::block_number();
+		let period_end = now.saturating_add(10u32.into());
+		Usages::<T>::insert(
+			&location,
+			Usage { period_end, messages: 1, weight: Weight::from_parts(1, 1) },
+		);
+
+		#[block]
+		{
+			assert!(<RateLimiter<T> as xcm_executor::traits::RateLimit>::can_execute(&origin));
+			<RateLimiter<T> as xcm_executor::traits::RateLimit>::note_executed(
+				&origin,
+				Weight::from_parts(1, 1),
+			);
+		}
+
+		assert_eq!(
+			Usages::<T>::get(&location),
+			Some(Usage { period_end, messages: 2, weight: Weight::from_parts(2, 2) })
+		);
+		Ok(())
+	}
+
+	impl_benchmark_test_suite!(RateLimiter, crate::mock::new_test_ext(), crate::mock::Test);
+}
@@ -0,0 +1,301 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! # XCM Rate Limiter Pallet
+//!
+//! Budgets of origin locations for executing XCMs, so that a single origin, e.g. a sibling chain,
+//! cannot monopolise the XCM execution weight of the chain. The pallet implements
+//! [`xcm_executor::traits::RateLimit`] to be used with `xcm_builder::RateLimitedMessageProcessor`.
+//!
+//! A [`RateLimit`] allows an origin to execute up to `max_messages` XCMs with a total weight of up
+//! to `max_weight` within each `period` of blocks. It is set by [`Config::AdminOrigin`] for a
+//! single origin, or by default for all origins without their own rate limit. Origins without any
+//! rate limit are not limited.
+//!
+//! Every executed XCM is charged with the weight it actually used. Once the budget of an origin is
+//! exhausted, its queued XCMs yield, so that they remain queued until the next period of the origin
+//! starts. An XCM is executed as long as any budget is left, so the last XCM of a period may exceed
+//! `max_weight`. This also means that an XCM heavier than `max_weight` does not block the queue of
+//! its origin forever.
+//!
+//! Rate limits and usages are keyed by the origin location in the latest XCM version, so
+//! [`migration::MigrateToLatestXcmVersion`] needs to be applied whenever that version changes.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+pub mod migration;
+#[cfg(test)]
+mod mock;
+#[cfg(test)]
+mod tests;
+pub mod weights;
+
+pub use pallet::*;
+pub use weights::WeightInfo;
+
+use alloc::{boxed::Box, vec::Vec};
+use codec::{Decode, Encode, MaxEncodedLen};
+use frame_support::{
+	traits::Get,
+	weights::{Weight, WeightMeter},
+};
+use frame_system::pallet_prelude::BlockNumberFor;
+use scale_info::TypeInfo;
+use sp_runtime::{
+	traits::{Saturating, Zero},
+	RuntimeDebug,
+};
+use xcm::{latest::Location, VersionedLocation};
+
+/// Budget of an origin for executing XCMs.
+#[derive(Clone, Copy, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub struct RateLimit<BlockNumber> {
+	/// Number of blocks after which the budget is replenished.
+	pub period: BlockNumber,
+	/// Maximum number of XCMs executed within a period.
+	pub max_messages: u32,
+	/// Maximum total weight of the XCMs executed within a period.
+	pub max_weight: Weight,
+}
+
+/// Budget used by an origin in its current period.
+#[derive(Clone, Copy, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub struct Usage<BlockNumber> {
+	/// Block at which the period ends.
+	pub period_end: BlockNumber,
+	/// Number of XCMs executed within the period.
+	pub messages: u32,
+	/// Total weight of the XCMs executed within the period.
+	pub weight: Weight,
+}
+
+/// The [`RateLimit`] of runtime `T`.
+pub type RateLimitOf<T> = RateLimit<BlockNumberFor<T>>;
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::*;
+	use frame_support::pallet_prelude::*;
+	use frame_system::pallet_prelude::*;
+
+	/// The in-code storage version.
+	const STORAGE_VERSION: StorageVersion = StorageVersion::new(0);
+
+	#[pallet::pallet]
+	#[pallet::storage_version(STORAGE_VERSION)]
+	pub struct Pallet<T>(_);
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		/// The overarching event type.
+		type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
+
+		/// Origin allowed to set rate limits.
+		type AdminOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+
+		/// Weight information for extrinsics in this pallet.
+		type WeightInfo: WeightInfo;
+	}
+
+	/// Rate limits of origins.
+	#[pallet::storage]
+	pub type RateLimits<T: Config> =
+		StorageMap<_, Blake2_128Concat, VersionedLocation, RateLimitOf<T>, OptionQuery>;
+
+	/// Rate limit of origins without their own entry in [`RateLimits`].
+	#[pallet::storage]
+	pub type DefaultRateLimit<T: Config> = StorageValue<_, RateLimitOf<T>, OptionQuery>;
+
+	/// Budget used by origins in their current period.
+	///
+	/// Entries of ended periods are removed when the chain is idle.
+	#[pallet::storage]
+	pub type Usages<T: Config> =
+		StorageMap<_, Blake2_128Concat, VersionedLocation, Usage<BlockNumberFor<T>>, OptionQuery>;
+
+	/// The last entry of [`Usages`] checked for an ended period, if the check did not reach the end
+	/// of the map yet.
+	#[pallet::storage]
+	pub type ExpiryCursor<T: Config> = StorageValue<_, VersionedLocation, OptionQuery>;
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// The rate limit of `location` was set, or removed if `limit` is `None`.
+		RateLimitSet { location: VersionedLocation, limit: Option<RateLimitOf<T>> },
+		/// The default rate limit was set, or removed if `limit` is `None`.
+		DefaultRateLimitSet { limit: Option<RateLimitOf<T>> },
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// The location could not be converted to the latest XCM version.
+		BadLocation,
+		/// The period of the rate limit is zero.
+		ZeroPeriod,
+		/// A component of the maximum weight of the rate limit is zero.
+		ZeroWeight,
+	}
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn on_idle(now: BlockNumberFor<T>, limit: Weight) -> Weight {
+			let mut meter = WeightMeter::with_limit(limit);
+			Self::remove_expired_usages(now, &mut meter);
+			meter.consumed()
+		}
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Set the rate limit of `location`, or remove it if `limit` is `None`.
+		///
+		/// Without its own rate limit, `location` is limited by the default rate limit. The budget
+		/// used by `location` in its current period is reset.
+		#[pallet::call_index(0)]
+		#[pallet::weight(T::WeightInfo::set_rate_limit())]
+		pub fn set_rate_limit(
+			origin: OriginFor<T>,
+			location: Box<VersionedLocation>,
+			limit: Option<RateLimitOf<T>>,
+		) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+			let location: Location =
+				(*location).try_into().map_err(|()| Error::<T>::BadLocation)?;
+			Self::ensure_valid(&limit)?;
+
+			let location = VersionedLocation::from(location);
+			RateLimits::<T>::set(&location, limit);
+			Usages::<T>::remove(&location);
+			Self::deposit_event(Event::RateLimitSet { location, limit });
+			Ok(())
+		}
+
+		/// Set the rate limit of all origins without their own rate limit, or remove it if `limit`
+		/// is `None`.
+		#[pallet::call_index(1)]
+		#[pallet::weight(T::WeightInfo::set_default_rate_limit())]
+		pub fn set_default_rate_limit(
+			origin: OriginFor<T>,
+			limit: Option<RateLimitOf<T>>,
+		) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+			Self::ensure_valid(&limit)?;
+
+			DefaultRateLimit::<T>::set(limit);
+			Self::deposit_event(Event::DefaultRateLimitSet { limit });
+			Ok(())
+		}
+	}
+}
+
+impl<T: Config> Pallet<T> {
+	/// The rate limit of `location`, if it is limited.
+	pub fn rate_limit(location: &VersionedLocation) -> Option<RateLimitOf<T>> {
+		RateLimits::<T>::get(location).or_else(DefaultRateLimit::<T>::get)
+	}
+
+	fn ensure_valid(limit: &Option<RateLimitOf<T>>) -> Result<(), Error<T>> {
+		match limit {
+			Some(limit) if limit.period.is_zero() => Err(Error::<T>::ZeroPeriod),
+			// The budget is exhausted once any component of the weight is used up, so a zero
+			// component would block the origin after its first XCM of every period.
+			Some(limit) if limit.max_weight.any_eq(Weight::zero()) => Err(Error::<T>::ZeroWeight),
+			_ => Ok(()),
+		}
+	}
+
+	/// Remove the usages of ended periods, as far as `meter` allows.
+	///
+	/// Continues after the last usage checked by the previous call, and starts from the beginning
+	/// again once all usages were checked.
+	pub(crate) fn remove_expired_usages(now: BlockNumberFor<T>, meter: &mut WeightMeter) {
+		if meter.try_consume(T::DbWeight::get().reads_writes(1, 1)).is_err() {
+			return
+		}
+		let cursor = ExpiryCursor::<T>::get();
+		let mut usages = match &cursor {
+			Some(location) => Usages::<T>::iter_from(Usages::<T>::hashed_key_for(location)),
+			None => Usages::<T>::iter(),
+		};
+
+		let weight = T::WeightInfo::remove_expired_usage();
+		let mut last = cursor;
+		let mut expired = Vec::new();
+		loop {
+			if meter.try_consume(weight).is_err() {
+				break
+			}
+			let Some((location, usage)) = usages.next() else {
+				last = None;
+				break
+			};
+			if usage.period_end <= now {
+				expired.push(location.clone());
+			}
+			last = Some(location);
+		}
+		ExpiryCursor::<T>::set(last);
+		for location in expired {
+			Usages::<T>::remove(location);
+		}
+	}
+
+	/// The usage of `location` in its current period at block `now`, if any.
+	fn current_usage(
+		location: &VersionedLocation,
+		now: BlockNumberFor<T>,
+	) -> Option<Usage<BlockNumberFor<T>>> {
+		Usages::<T>::get(location).filter(|usage| now < usage.period_end)
+	}
+}
+
+impl<T: Config> xcm_executor::traits::RateLimit for Pallet<T> {
+	fn can_execute(origin: &Location) -> bool {
+		let location = VersionedLocation::from(origin.clone());
+		let Some(limit) = Self::rate_limit(&location) else { return true };
+
+		match Self::current_usage(&location, frame_system::Pallet::<T>::block_number()) {
+			Some(usage) =>
+				usage.messages < limit.max_messages && usage.weight.all_lt(limit.max_weight),
+			None => limit.max_messages > 0,
+		}
+	}
+
+	fn weight() -> Weight {
+		T::WeightInfo::charge_budget()
+	}
+
+	fn note_executed(origin: &Location, weight: Weight) {
+		let location = VersionedLocation::from(origin.clone());
+		let Some(limit) = Self::rate_limit(&location) else { return };
+
+		let now = frame_system::Pallet::<T>::block_number();
+		let usage = match Self::current_usage(&location, now) {
+			Some(usage) => Usage {
+				messages: usage.messages.saturating_add(1),
+				weight: usage.weight.saturating_add(weight),
+				..usage
+			},
+			None => Usage { period_end: now.saturating_add(limit.period), messages: 1, weight },
+		};
+		Usages::<T>::insert(&location, usage);
+	}
+}
@@ -0,0 +1,89 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Migrations of the XCM rate limiter pallet.
+
+use crate::{Config, ExpiryCursor, RateLimits, Usages};
+use alloc::vec::Vec;
+use codec::FullCodec;
+use core::marker::PhantomData;
+use frame_support::{
+	storage::{IterableStorageMap, StorageMap},
+	traits::{Get, OnRuntimeUpgrade},
+	weights::Weight,
+};
+use sp_runtime::traits::Saturating;
+use xcm::{
+	latest::{Location, VERSION as XCM_VERSION},
+	IdentifyVersion, VersionedLocation,
+};
+
+/// Migrates the keys of [`RateLimits`] and [`Usages`] to the latest XCM version.
+///
+/// Both maps are keyed by the origin location in the latest XCM version, so entries stored in an
+/// older version no longer match their origin. This migration needs to be part of the runtime
+/// upgrade that changes the latest XCM version. Entries that can not be converted are removed.
+pub struct MigrateToLatestXcmVersion<T>(PhantomData<T>);
+
+impl<T: Config> OnRuntimeUpgrade for MigrateToLatestXcmVersion<T> {
+	fn on_runtime_upgrade() -> Weight {
+		let mut weight = Weight::zero();
+		weight.saturating_accrue(migrate_keys::<T, RateLimits<T>, _>());
+		weight.saturating_accrue(migrate_keys::<T, Usages<T>, _>());
+
+		// The cursor may point to a key that was just migrated.
+		ExpiryCursor::<T>::kill();
+		weight.saturating_add(T::DbWeight::get().writes(1))
+	}
+
+	#[cfg(feature = "try-runtime")]
+	fn post_upgrade(_state: Vec<u8>) -> Result<(), sp_runtime::TryRuntimeError> {
+		frame_support::ensure!(
+			RateLimits::<T>::iter_keys()
+				.chain(Usages::<T>::iter_keys())
+				.all(|location| location.identify_version() == XCM_VERSION),
+			"Not all locations were migrated to the latest XCM version"
+		);
+		Ok(())
+	}
+}
+
+/// Move every entry of `Map` that is not keyed by a location in the latest XCM version to the key
+/// in the latest version.
+fn migrate_keys<T, Map, Value>() -> Weight
+where
+	T: Config,
+	Map: IterableStorageMap<VersionedLocation, Value> + StorageMap<VersionedLocation, Value>,
+	Value: FullCodec,
+{
+	let mut entries = 0u64;
+	let outdated = Map::iter()
+		.inspect(|_| entries.saturating_inc())
+		.filter(|(location, _)| location.identify_version() != XCM_VERSION)
+		.collect::<Vec<_>>();
+	let mut weight = T::DbWeight::get().reads(entries);
+
+	for (location, value) in outdated {
+		Map::remove(&location);
+		weight.saturating_accrue(T::DbWeight::get().writes(1));
+		if let Ok(latest) = Location::try_from(location) {
+			Map::insert(VersionedLocation::from(latest), value);
+			weight.saturating_accrue(T::DbWeight::get().writes(1));
+		}
+	}
+
+	weight
+}
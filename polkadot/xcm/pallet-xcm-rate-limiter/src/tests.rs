@@ -0,0 +1,373 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Tests for the XCM rate limiter pallet.
+
+use crate::{mock::*, *};
+use codec::Encode;
+use frame_support::{
+	assert_noop, assert_ok,
+	traits::{EnqueueMessage, Hooks, ProcessMessage, ServiceQueues},
+	BoundedSlice,
+};
+use sp_runtime::DispatchError;
+use xcm::{latest::prelude::*, VersionedXcm};
+use xcm_executor::traits::RateLimit as _;
+
+fn sibling() -> Location {
+	Location::new(1, [Parachain(1000)])
+}
+
+fn limit(period: u64, max_messages: u32, max_weight: u64) -> RateLimitOf<Test> {
+	RateLimit { period, max_messages, max_weight: Weight::from_parts(max_weight, max_weight) }
+}
+
+fn weight(weight: u64) -> Weight {
+	Weight::from_parts(weight, weight)
+}
+
+/// Execute an XCM of `used` weight from `origin`, if its budget allows it.
+fn execute(origin: &Location, used: u64) -> bool {
+	if !RateLimiter::can_execute(origin) {
+		return false
+	}
+	RateLimiter::note_executed(origin, weight(used));
+	true
+}
+
+#[test]
+fn only_admin_can_set_rate_limits() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			RateLimiter::set_rate_limit(
+				RuntimeOrigin::signed(1),
+				Box::new(sibling().into()),
+				Some(limit(1, 1, 1))
+			),
+			DispatchError::BadOrigin
+		);
+		assert_noop!(
+			RateLimiter::set_default_rate_limit(RuntimeOrigin::signed(1), Some(limit(1, 1, 1))),
+			DispatchError::BadOrigin
+		);
+	});
+}
+
+#[test]
+fn rate_limits_with_zero_period_are_rejected() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			RateLimiter::set_rate_limit(
+				RuntimeOrigin::root(),
+				Box::new(sibling().into()),
+				Some(limit(0, 1, 1))
+			),
+			Error::<Test>::ZeroPeriod
+		);
+		assert_noop!(
+			RateLimiter::set_default_rate_limit(RuntimeOrigin::root(), Some(limit(0, 1, 1))),
+			Error::<Test>::ZeroPeriod
+		);
+	});
+}
+
+#[test]
+fn rate_limits_with_zero_weight_components_are_rejected() {
+	new_test_ext().execute_with(|| {
+		for max_weight in [Weight::zero(), Weight::from_parts(1, 0), Weight::from_parts(0, 1)] {
+			let limit = RateLimit { period: 1, max_messages: 1, max_weight };
+			assert_noop!(
+				RateLimiter::set_rate_limit(
+					RuntimeOrigin::root(),
+					Box::new(sibling().into()),
+					Some(limit)
+				),
+				Error::<Test>::ZeroWeight
+			);
+			assert_noop!(
+				RateLimiter::set_default_rate_limit(RuntimeOrigin::root(), Some(limit)),
+				Error::<Test>::ZeroWeight
+			);
+		}
+	});
+}
+
+#[test]
+fn unlimited_origins_are_not_limited() {
+	new_test_ext().execute_with(|| {
+		for _ in 0..100 {
+			assert!(execute(&sibling(), 1_000));
+		}
+		assert_eq!(Usages::<Test>::iter().count(), 0);
+	});
+}
+
+#[test]
+fn origins_without_messages_are_blocked() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(RateLimiter::set_rate_limit(
+			RuntimeOrigin::root(),
+			Box::new(sibling().into()),
+			Some(limit(1, 0, 1_000))
+		));
+		assert!(!execute(&sibling(), 10));
+		assert!(execute(&Location::parent(), 10));
+	});
+}
+
+#[test]
+fn message_count_is_limited_per_period() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(RateLimiter::set_rate_limit(
+			RuntimeOrigin::root(),
+			Box::new(sibling().into()),
+			Some(limit(2, 2, 1_000))
+		));
+		System::assert_last_event(
+			Event::RateLimitSet { location: sibling().into(), limit: Some(limit(2, 2, 1_000)) }
+				.into(),
+		);
+
+		assert!(execute(&sibling(), 10));
+		assert!(execute(&sibling(), 10));
+		assert!(!execute(&sibling(), 10));
+		// Other origins are not affected.
+		assert!(execute(&Location::parent(), 10));
+
+		// The budget is not replenished before the period ends.
+		System::set_block_number(2);
+		assert!(!execute(&sibling(), 10));
+
+		System::set_block_number(3);
+		assert!(execute(&sibling(), 10));
+		assert_eq!(
+			Usages::<Test>::get(VersionedLocation::from(sibling())),
+			Some(Usage { period_end: 5, messages: 1, weight: weight(10) })
+		);
+	});
+}
+
+#[test]
+fn weight_is_limited_per_period() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(RateLimiter::set_rate_limit(
+			RuntimeOrigin::root(),
+			Box::new(sibling().into()),
+			Some(limit(1, 10, 100))
+		));
+
+		assert!(execute(&sibling(), 60));
+		// The last message of a period may exceed the budget.
+		assert!(execute(&sibling(), 60));
+		assert!(!execute(&sibling(), 1));
+		assert_eq!(
+			Usages::<Test>::get(VersionedLocation::from(sibling())),
+			Some(Usage { period_end: 2, messages: 2, weight: weight(120) })
+		);
+
+		// A message heavier than the budget does not block the origin forever.
+		System::set_block_number(2);
+		assert!(execute(&sibling(), 150));
+		assert!(!execute(&sibling(), 1));
+	});
+}
+
+#[test]
+fn default_rate_limit_applies_to_origins_without_their_own() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(RateLimiter::set_default_rate_limit(
+			RuntimeOrigin::root(),
+			Some(limit(1, 1, 100))
+		));
+		System::assert_last_event(
+			Event::DefaultRateLimitSet { limit: Some(limit(1, 1, 100)) }.into(),
+		);
+		assert_ok!(RateLimiter::set_rate_limit(
+			RuntimeOrigin::root(),
+			Box::new(sibling().into()),
+			Some(limit(1, 3, 100))
+		));
+
+		assert!(execute(&Location::parent(), 10));
+		assert!(!execute(&Location::parent(), 10));
+		for _ in 0..3 {
+			assert!(execute(&sibling(), 10));
+		}
+		assert!(!execute(&sibling(), 10));
+
+		// Removing the rate limit of an origin falls back to the default and resets its budget.
+		assert_ok!(RateLimiter::set_rate_limit(
+			RuntimeOrigin::root(),
+			Box::new(sibling().into()),
+			None
+		));
+		assert!(execute(&sibling(), 10));
+		assert!(!execute(&sibling(), 10));
+	});
+}
+
+#[test]
+fn expired_usages_are_removed_when_idle() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(RateLimiter::set_default_rate_limit(
+			RuntimeOrigin::root(),
+			Some(limit(2, 1, 100))
+		));
+		assert!(execute(&sibling(), 10));
+		System::set_block_number(2);
+		assert!(execute(&Location::parent(), 10));
+
+		// Nothing is removed without enough weight.
+		RateLimiter::on_idle(3, Weight::zero());
+		assert_eq!(Usages::<Test>::iter().count(), 2);
+
+		RateLimiter::on_idle(3, Weight::MAX);
+		assert_eq!(Usages::<Test>::get(VersionedLocation::from(sibling())), None);
+		assert!(Usages::<Test>::get(VersionedLocation::from(Location::parent())).is_some());
+		assert_eq!(ExpiryCursor::<Test>::get(), None);
+
+		RateLimiter::on_idle(4, Weight::MAX);
+		assert_eq!(Usages::<Test>::iter().count(), 0);
+	});
+}
+
+#[test]
+fn removing_expired_usages_resumes_after_the_last_checked_usage() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(RateLimiter::set_default_rate_limit(
+			RuntimeOrigin::root(),
+			Some(limit(1, 1, 100))
+		));
+		for para in 1..=3 {
+			assert!(execute(&Location::new(1, [Parachain(para)]), 10));
+		}
+		let locations = Usages::<Test>::iter_keys().collect::<Vec<_>>();
+		let one_usage = <() as WeightInfo>::remove_expired_usage();
+
+		// Nothing expired yet, but the first usage was checked.
+		RateLimiter::on_idle(1, one_usage);
+		assert_eq!(Usages::<Test>::iter().count(), 3);
+		assert_eq!(ExpiryCursor::<Test>::get(), Some(locations[0].clone()));
+
+		// The next call continues with the second usage, although the first one expired too.
+		RateLimiter::on_idle(2, one_usage);
+		assert!(Usages::<Test>::contains_key(&locations[0]));
+		assert!(!Usages::<Test>::contains_key(&locations[1]));
+		assert!(Usages::<Test>::contains_key(&locations[2]));
+
+		// Once the end is reached, it starts from the beginning again.
+		RateLimiter::on_idle(2, one_usage.saturating_mul(2));
+		assert_eq!(ExpiryCursor::<Test>::get(), None);
+		assert_eq!(Usages::<Test>::iter_keys().collect::<Vec<_>>(), vec![locations[0].clone()]);
+		RateLimiter::on_idle(2, one_usage);
+		assert_eq!(Usages::<Test>::iter().count(), 0);
+	});
+}
+
+/// Enqueue an XCM of a single `ClearOrigin` from the child chain `para`.
+fn enqueue(para: u32) {
+	let message = VersionedXcm::<RuntimeCall>::from(Xcm::<RuntimeCall>(vec![ClearOrigin])).encode();
+	MessageQueue::enqueue_message(BoundedSlice::defensive_truncate_from(&message), Parachain(para));
+}
+
+fn queued(para: u32) -> u64 {
+	pallet_message_queue::BookStateFor::<Test>::get(Parachain(para)).message_count
+}
+
+#[test]
+fn queued_messages_of_origins_over_budget_stay_queued() {
+	new_test_ext().execute_with(|| {
+		let child = Location::new(0, [Parachain(1000)]);
+		assert_ok!(RateLimiter::set_rate_limit(
+			RuntimeOrigin::root(),
+			Box::new(child.clone().into()),
+			Some(limit(2, 2, 1_000))
+		));
+		for _ in 0..3 {
+			enqueue(1000);
+		}
+		enqueue(2000);
+
+		MessageQueue::service_queues(Weight::MAX);
+		// Two messages were executed and charged with the weight they used, the third yielded.
+		assert_eq!(queued(1000), 1);
+		assert_eq!(queued(2000), 0);
+		assert_eq!(
+			Usages::<Test>::get(VersionedLocation::from(child.clone())),
+			Some(Usage { period_end: 3, messages: 2, weight: UnitWeightCost::get() * 2 })
+		);
+		assert!(!System::events().iter().any(|record| matches!(
+			record.event,
+			RuntimeEvent::MessageQueue(pallet_message_queue::Event::ProcessingFailed { .. })
+		)));
+
+		// The message is not dropped, but executed once the budget is replenished.
+		System::set_block_number(2);
+		MessageQueue::service_queues(Weight::MAX);
+		assert_eq!(queued(1000), 1);
+		System::set_block_number(3);
+		MessageQueue::service_queues(Weight::MAX);
+		assert_eq!(queued(1000), 0);
+		assert_eq!(
+			Usages::<Test>::get(VersionedLocation::from(child)),
+			Some(Usage { period_end: 5, messages: 1, weight: UnitWeightCost::get() })
+		);
+	});
+}
+
+#[test]
+fn checking_the_budget_is_charged_to_the_meter() {
+	new_test_ext().execute_with(|| {
+		let message = VersionedXcm::<RuntimeCall>::from(Xcm::<RuntimeCall>(vec![ClearOrigin]));
+		let mut meter = WeightMeter::new();
+		assert_ok!(MessageProcessor::process_message(
+			&message.encode(),
+			Parachain(1000),
+			&mut meter,
+			&mut [0; 32]
+		));
+		assert_eq!(
+			meter.consumed(),
+			UnitWeightCost::get().saturating_add(<() as WeightInfo>::charge_budget())
+		);
+	});
+}
+
+#[test]
+fn migration_moves_entries_to_the_latest_xcm_version() {
+	use frame_support::traits::OnRuntimeUpgrade;
+	use xcm::v4;
+
+	new_test_ext().execute_with(|| {
+		let old = VersionedLocation::V4(v4::Location::new(1, [v4::Junction::Parachain(1000)]));
+		let latest = VersionedLocation::from(sibling());
+		let usage = Usage { period_end: 2, messages: 1, weight: weight(10) };
+		RateLimits::<Test>::insert(&old, limit(1, 1, 100));
+		Usages::<Test>::insert(&old, usage);
+		ExpiryCursor::<Test>::put(&old);
+
+		migration::MigrateToLatestXcmVersion::<Test>::on_runtime_upgrade();
+
+		assert_eq!(
+			RateLimits::<Test>::iter().collect::<Vec<_>>(),
+			vec![(latest.clone(), limit(1, 1, 100))]
+		);
+		assert_eq!(Usages::<Test>::iter().collect::<Vec<_>>(), vec![(latest, usage)]);
+		assert_eq!(ExpiryCursor::<Test>::get(), None);
+		// The migrated entries apply to their origin again.
+		assert!(!RateLimiter::can_execute(&sibling()));
+	});
+}
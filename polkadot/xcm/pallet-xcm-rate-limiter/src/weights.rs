@@ -0,0 +1,47 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! The pallet weight info trait and its unit implementation.
+
+use frame_support::weights::{constants::RocksDbWeight, Weight};
+
+/// Weights information needed for the pallet.
+pub trait WeightInfo {
+	/// Returns the weight of the `set_rate_limit` extrinsic.
+	fn set_rate_limit() -> Weight;
+	/// Returns the weight of the `set_default_rate_limit` extrinsic.
+	fn set_default_rate_limit() -> Weight;
+	/// Returns the weight of checking and removing a single expired usage in `on_idle`.
+	fn remove_expired_usage() -> Weight;
+	/// Returns the weight of checking the budget of an origin and charging one XCM to it.
+	fn charge_budget() -> Weight;
+}
+
+/// Unit implementation of the [`WeightInfo`], accounting only for storage accesses.
+impl WeightInfo for () {
+	fn set_rate_limit() -> Weight {
+		RocksDbWeight::get().writes(2)
+	}
+	fn set_default_rate_limit() -> Weight {
+		RocksDbWeight::get().writes(1)
+	}
+	fn remove_expired_usage() -> Weight {
+		RocksDbWeight::get().reads_writes(1, 1)
+	}
+	fn charge_budget() -> Weight {
+		RocksDbWeight::get().reads_writes(3, 1)
+	}
+}
@@ -271,6 +271,26 @@ benchmarks! {
 
 	force_suspension {}: _(RawOrigin::Root, true)
 
+	force_xcm_versions {
+		let n in 1 .. MAX_VERSION_BATCH;
+		let versions = (0..n)
+			.map(|i| (Box::new(VersionedLocation::from(Location::new(1, [Parachain(i)]))), XCM_VERSION))
+			.collect::<Vec<_>>();
+		let versions = BoundedVec::try_from(versions).map_err(|_| BenchmarkError::Weightless)?;
+	}: _(RawOrigin::Root, versions)
+
+	force_version_requery {
+		let n in 1 .. MAX_VERSION_BATCH;
+		let loc = T::reachable_dest().ok_or(
+			BenchmarkError::Override(BenchmarkResult::from_weight(Weight::MAX)),
+		)?;
+		let _ = crate::Pallet::<T>::request_version_notify(loc.clone());
+		let locations = (0..n)
+			.map(|_| Box::new(VersionedLocation::from(loc.clone())))
+			.collect::<Vec<_>>();
+		let locations = BoundedVec::try_from(locations).map_err(|_| BenchmarkError::Weightless)?;
+	}: _(RawOrigin::Root, locations)
+
 	migrate_supported_version {
 		let old_version = XCM_VERSION - 1;
 		let loc = VersionedLocation::from(Location::from(Parent));
@@ -29,7 +29,7 @@ pub mod migration;
 
 extern crate alloc;
 
-use alloc::{boxed::Box, vec, vec::Vec};
+use alloc::{boxed::Box, collections::btree_map::BTreeMap, vec, vec::Vec};
 use codec::{Decode, Encode, EncodeLike, MaxEncodedLen};
 use core::{marker::PhantomData, result::Result};
 use frame_support::{
@@ -68,9 +68,12 @@ use xcm_executor::{
 	AssetsInHolding,
 };
 use xcm_runtime_apis::{
-	dry_run::{CallDryRunEffects, Error as XcmDryRunApiError, XcmDryRunEffects},
+	dry_run::{CallDryRunEffects, Error as XcmDryRunApiError, TracedEffects, XcmDryRunEffects},
 	fees::Error as XcmPaymentApiError,
 	trusted_query::Error as TrustedQueryApiError,
+	versions::{
+		DestinationVersion, Error as XcmVersionsApiError, VersionNotifier, VersionNotifyTarget,
+	},
 };
 
 #[cfg(any(feature = "try-runtime", test))]
@@ -98,6 +101,8 @@ pub trait WeightInfo {
 	fn new_query() -> Weight;
 	fn take_response() -> Weight;
 	fn claim_assets() -> Weight;
+	fn force_xcm_versions(n: u32) -> Weight;
+	fn force_version_requery(n: u32) -> Weight;
 }
 
 /// fallback implementation
@@ -182,6 +187,14 @@ impl WeightInfo for TestWeightInfo {
 	fn claim_assets() -> Weight {
 		Weight::from_parts(100_000_000, 0)
 	}
+
+	fn force_xcm_versions(n: u32) -> Weight {
+		Weight::from_parts(100_000_000, 0).saturating_mul(n.into())
+	}
+
+	fn force_version_requery(n: u32) -> Weight {
+		Weight::from_parts(100_000_000, 0).saturating_mul(n.into())
+	}
 }
 
 #[frame_support::pallet]
@@ -505,6 +518,14 @@ pub mod pallet {
 		AssetsClaimed { hash: H256, origin: Location, assets: VersionedAssets },
 		/// A XCM version migration finished.
 		VersionMigrationFinished { version: XcmVersion },
+		/// A message could not be sent to `destination` since it cannot be expressed in the
+		/// `version` of XCM that `destination` supports, or since that version is unknown and no
+		/// safe version is set if `version` is `None`.
+		///
+		/// The event is discarded if the failed send reverts the enclosing transaction.
+		VersionConversionFailed { destination: Location, version: Option<XcmVersion> },
+		/// Version notifications could not be requested again from a location.
+		VersionRequeryFailed { location: Location, error: XcmError },
 	}
 
 	#[pallet::origin]
@@ -1429,9 +1450,72 @@ pub mod pallet {
 				weight_limit,
 			)
 		}
+
+		/// Extoll that particular destinations can be communicated with through particular
+		/// versions of XCM.
+		///
+		/// - `origin`: Must be an origin specified by AdminOrigin.
+		/// - `versions`: The destinations that are being described, each with the latest version of
+		///   XCM that it supports.
+		#[pallet::call_index(14)]
+		#[pallet::weight(T::WeightInfo::force_xcm_versions(versions.len() as u32))]
+		pub fn force_xcm_versions(
+			origin: OriginFor<T>,
+			versions: BoundedVec<(Box<VersionedLocation>, XcmVersion), ConstU32<MAX_VERSION_BATCH>>,
+		) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+			let versions = versions
+				.into_iter()
+				.map(|(location, version)| {
+					let location: Location =
+						(*location).try_into().map_err(|()| Error::<T>::BadLocation)?;
+					Ok((location, version))
+				})
+				.collect::<Result<Vec<_>, Error<T>>>()?;
+			for (location, version) in versions {
+				SupportedVersion::<T>::insert(
+					XCM_VERSION,
+					LatestVersionedLocation(&location),
+					version,
+				);
+				Self::deposit_event(Event::SupportedVersionChanged { location, version });
+			}
+			Ok(())
+		}
+
+		/// Ask locations to notify us regarding their XCM version again, e.g. because they are
+		/// stuck on an outdated version.
+		///
+		/// Any existing subscriptions to the locations are dropped first. Locations which cannot
+		/// be subscribed to are reported with `VersionRequeryFailed` and do not affect the others.
+		///
+		/// - `origin`: Must be an origin specified by AdminOrigin.
+		/// - `locations`: The locations to which we should subscribe for XCM version notifications.
+		#[pallet::call_index(15)]
+		#[pallet::weight(T::WeightInfo::force_version_requery(locations.len() as u32))]
+		pub fn force_version_requery(
+			origin: OriginFor<T>,
+			locations: BoundedVec<Box<VersionedLocation>, ConstU32<MAX_VERSION_BATCH>>,
+		) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+			let locations = locations
+				.into_iter()
+				.map(|location| (*location).try_into().map_err(|()| Error::<T>::BadLocation))
+				.collect::<Result<Vec<Location>, Error<T>>>()?;
+			for location in locations {
+				if let Err(error) = Self::requery_version(location.clone()) {
+					Self::deposit_event(Event::VersionRequeryFailed { location, error });
+				}
+			}
+			Ok(())
+		}
 	}
 }
 
+/// The maximum number of locations in a single call of `force_xcm_versions` or
+/// `force_version_requery`.
+const MAX_VERSION_BATCH: u32 = 64;
+
 /// The maximum number of distinct assets allowed to be transferred in a single helper extrinsic.
 const MAX_ASSETS_FOR_TRANSFER: usize = 2;
 
@@ -1805,6 +1889,7 @@ impl<T: Config> Pallet<T> {
 			let (ticket, price) = validate_send::<T::XcmRouter>(dest.clone(), remote_xcm.clone())
 				.map_err(|error| {
 					tracing::error!(target: "xcm::pallet_xcm::execute_xcm_transfer", ?error, ?dest, ?remote_xcm, "XCM validate_send failed with error");
+					Self::note_send_failure(&dest, &remote_xcm);
 					Error::<T>::from(error)
 				})?;
 			if origin != Here.into_location() {
@@ -2342,7 +2427,7 @@ impl<T: Config> Pallet<T> {
 				let response = Response::Version(xcm_version);
 				let message =
 					Xcm(vec![QueryResponse { query_id, response, max_weight, querier: None }]);
-				let event = match send_xcm::<T::XcmRouter>(new_key.clone(), message) {
+				let event = match Self::route_xcm(new_key.clone(), message) {
 					Ok((message_id, cost)) => {
 						let value = (query_id, max_weight, xcm_version);
 						VersionNotifyTargets::<T>::insert(XCM_VERSION, key, value);
@@ -2399,7 +2484,7 @@ impl<T: Config> Pallet<T> {
 							max_weight,
 							querier: None,
 						}]);
-						let event = match send_xcm::<T::XcmRouter>(new_key.clone(), message) {
+						let event = match Self::route_xcm(new_key.clone(), message) {
 							Ok((message_id, cost)) => {
 								VersionNotifyTargets::<T>::insert(
 									XCM_VERSION,
@@ -2444,7 +2529,7 @@ impl<T: Config> Pallet<T> {
 		});
 		// TODO #3735: Correct weight.
 		let instruction = SubscribeVersion { query_id, max_response_weight: Weight::zero() };
-		let (message_id, cost) = send_xcm::<T::XcmRouter>(dest.clone(), Xcm(vec![instruction]))?;
+		let (message_id, cost) = Self::route_xcm(dest.clone(), Xcm(vec![instruction]))?;
 		Self::deposit_event(Event::VersionNotifyRequested { destination: dest, cost, message_id });
 		VersionNotifiers::<T>::insert(XCM_VERSION, &versioned_dest, query_id);
 		let query_status =
@@ -2459,8 +2544,7 @@ impl<T: Config> Pallet<T> {
 		let versioned_dest = LatestVersionedLocation(&dest);
 		let query_id = VersionNotifiers::<T>::take(XCM_VERSION, versioned_dest)
 			.ok_or(XcmError::InvalidLocation)?;
-		let (message_id, cost) =
-			send_xcm::<T::XcmRouter>(dest.clone(), Xcm(vec![UnsubscribeVersion]))?;
+		let (message_id, cost) = Self::route_xcm(dest.clone(), Xcm(vec![UnsubscribeVersion]))?;
 		Self::deposit_event(Event::VersionNotifyUnrequested {
 			destination: dest,
 			cost,
@@ -2470,6 +2554,31 @@ impl<T: Config> Pallet<T> {
 		Ok(())
 	}
 
+	/// Request that `dest` informs us of its version again, dropping any existing subscription.
+	///
+	/// Unlike with [`Self::unrequest_version_notify`], the subscription is dropped locally even if
+	/// the unsubscription cannot be sent.
+	pub fn requery_version(dest: impl Into<Location>) -> XcmResult {
+		let dest = dest.into();
+		if let Some(query_id) =
+			VersionNotifiers::<T>::take(XCM_VERSION, LatestVersionedLocation(&dest))
+		{
+			Queries::<T>::remove(query_id);
+			match Self::route_xcm(dest.clone(), Xcm(vec![UnsubscribeVersion])) {
+				Ok((message_id, cost)) => Self::deposit_event(Event::VersionNotifyUnrequested {
+					destination: dest.clone(),
+					cost,
+					message_id,
+				}),
+				Err(e) => tracing::debug!(
+					target: "xcm::pallet_xcm::requery_version",
+					?dest, ?e, "Failed to send unsubscription",
+				),
+			}
+		}
+		Self::request_version_notify(dest)
+	}
+
 	/// Relay an XCM `message` from a given `interior` location in this context to a given `dest`
 	/// location. The `fee_payer` is charged for the delivery unless `None` in which case fees
 	/// are not charged (and instead borne by the chain).
@@ -2487,7 +2596,11 @@ impl<T: Config> Pallet<T> {
 			message.0.insert(0, DescendOrigin(interior.clone()));
 		}
 		tracing::debug!(target: "xcm::send_xcm", "{:?}, {:?}", dest.clone(), message.clone());
-		let (ticket, price) = validate_send::<T::XcmRouter>(dest, message)?;
+		let (ticket, price) = validate_send::<T::XcmRouter>(dest.clone(), message.clone())
+			.map_err(|error| {
+				Self::note_send_failure(&dest, &message);
+				error
+			})?;
 		if !is_waived {
 			Self::charge_fees(local_origin, price).map_err(|e| {
 				tracing::error!(
@@ -2501,6 +2614,35 @@ impl<T: Config> Pallet<T> {
 		T::XcmRouter::deliver(ticket)
 	}
 
+	/// Send `message` to `dest` with the `XcmRouter`, noting whether a failure is due to the XCM
+	/// version of `dest`.
+	fn route_xcm(dest: Location, message: Xcm<()>) -> Result<(XcmHash, Assets), SendError> {
+		send_xcm::<T::XcmRouter>(dest.clone(), message.clone()).map_err(|error| {
+			Self::note_send_failure(&dest, &message);
+			error
+		})
+	}
+
+	/// Deposit [`Event::VersionConversionFailed`] if `message` cannot be wrapped in the XCM
+	/// version of `dest`, after sending it to `dest` failed.
+	///
+	/// This is not done by [`WrapVersion::wrap_version`] itself, since messages are also wrapped
+	/// without being sent, e.g. to query delivery fees.
+	fn note_send_failure(dest: &Location, message: &Xcm<()>) {
+		let version = Self::get_version_for(dest)
+			.or_else(SafeXcmVersion::<T>::get)
+			.map(|v| v.min(XCM_VERSION));
+		let wrappable = version.map_or(false, |version| {
+			VersionedXcm::from(message.clone()).into_version(version).is_ok()
+		});
+		if !wrappable {
+			Self::deposit_event(Event::VersionConversionFailed {
+				destination: dest.clone(),
+				version,
+			});
+		}
+	}
+
 	pub fn check_account() -> T::AccountId {
 		const ID: PalletId = PalletId(*b"py/xcmch");
 		AccountIdConversion::<T::AccountId>::into_account_truncating(&ID)
@@ -2587,6 +2729,52 @@ impl<T: Config> Pallet<T> {
 		Ok(XcmDryRunEffects { forwarded_xcms, emitted_events: events, execution_result: result })
 	}
 
+	/// Dry-runs `call` with the given `origin` like [`Self::dry_run_call`], recording a trace of
+	/// all XCM instructions executed by it.
+	pub fn dry_run_call_with_trace<Runtime, Router, OriginCaller, RuntimeCall>(
+		origin: OriginCaller,
+		call: RuntimeCall,
+	) -> Result<
+		TracedEffects<CallDryRunEffects<<Runtime as frame_system::Config>::RuntimeEvent>>,
+		XcmDryRunApiError,
+	>
+	where
+		Runtime: crate::Config,
+		Router: InspectMessageQueues,
+		RuntimeCall: Dispatchable<PostInfo = PostDispatchInfo>,
+		<RuntimeCall as Dispatchable>::RuntimeOrigin: From<OriginCaller>,
+	{
+		let (effects, trace) = xcm_executor::trace::with_trace(|| {
+			Self::dry_run_call::<Runtime, Router, OriginCaller, RuntimeCall>(origin, call)
+		});
+		Ok(TracedEffects { effects: effects?, trace })
+	}
+
+	/// Dry-runs `xcm` with the given `origin_location` like [`Self::dry_run_xcm`], recording a
+	/// trace of all executed instructions.
+	pub fn dry_run_xcm_with_trace<
+		Runtime,
+		Router,
+		RuntimeCall: Decode + GetDispatchInfo,
+		XcmConfig,
+	>(
+		origin_location: VersionedLocation,
+		xcm: VersionedXcm<RuntimeCall>,
+	) -> Result<
+		TracedEffects<XcmDryRunEffects<<Runtime as frame_system::Config>::RuntimeEvent>>,
+		XcmDryRunApiError,
+	>
+	where
+		Runtime: frame_system::Config,
+		Router: InspectMessageQueues,
+		XcmConfig: xcm_executor::Config<RuntimeCall = RuntimeCall>,
+	{
+		let (effects, trace) = xcm_executor::trace::with_trace(|| {
+			Self::dry_run_xcm::<Runtime, Router, RuntimeCall, XcmConfig>(origin_location, xcm)
+		});
+		Ok(TracedEffects { effects: effects?, trace })
+	}
+
 	/// Given a list of asset ids, returns the correct API response for
 	/// `XcmPaymentApi::query_acceptable_payment_assets`.
 	///
@@ -2615,6 +2803,136 @@ impl<T: Config> Pallet<T> {
 		})
 	}
 
+	/// Returns all locations which have a known XCM version, a version subscription in either
+	/// direction or which are queued for version discovery.
+	///
+	/// Meant to be used in the `xcm_runtime_apis::versions::XcmVersionsApi` runtime API.
+	pub fn known_destinations() -> Vec<DestinationVersion> {
+		fn destination(
+			destinations: &mut BTreeMap<VersionedLocation, DestinationVersion>,
+			key_version: XcmVersion,
+			location: VersionedLocation,
+		) -> &mut DestinationVersion {
+			let location = Location::try_from(location.clone())
+				.map(VersionedLocation::from)
+				.unwrap_or(location);
+			let destination =
+				destinations.entry(location.clone()).or_insert_with(|| DestinationVersion {
+					location,
+					supported_version: None,
+					notifier: None,
+					notify_target: None,
+					discovery_attempts: None,
+					pending_migration: false,
+				});
+			destination.pending_migration |= key_version != XCM_VERSION;
+			destination
+		}
+
+		let mut destinations = BTreeMap::new();
+		for (key_version, location, version) in SupportedVersion::<T>::iter() {
+			let destination = destination(&mut destinations, key_version, location);
+			if key_version == XCM_VERSION || destination.supported_version.is_none() {
+				destination.supported_version = Some(version);
+			}
+		}
+		for (key_version, location, query_id) in VersionNotifiers::<T>::iter() {
+			let is_active = matches!(
+				Queries::<T>::get(query_id),
+				Some(QueryStatus::VersionNotifier { is_active: true, .. })
+			);
+			destination(&mut destinations, key_version, location).notifier =
+				Some(VersionNotifier { query_id, is_active });
+		}
+		let advertised_version = T::AdvertisedXcmVersion::get();
+		for (key_version, location, (query_id, max_weight, notified_version)) in
+			VersionNotifyTargets::<T>::iter()
+		{
+			destination(&mut destinations, key_version, location).notify_target =
+				Some(VersionNotifyTarget {
+					query_id,
+					max_weight,
+					notified_version,
+					is_pending: notified_version != advertised_version,
+				});
+		}
+		for (location, attempts) in VersionDiscoveryQueue::<T>::get() {
+			destination(&mut destinations, XCM_VERSION, location).discovery_attempts =
+				Some(attempts);
+		}
+		destinations.into_values().collect()
+	}
+
+	/// Returns what is known about the XCM version of `location`.
+	///
+	/// Meant to be used in the `xcm_runtime_apis::versions::XcmVersionsApi` runtime API.
+	pub fn destination_version(
+		location: VersionedLocation,
+	) -> Result<DestinationVersion, XcmVersionsApiError> {
+		let location: Location = location.try_into().map_err(|e| {
+			tracing::debug!(
+				target: "xcm::pallet_xcm::destination_version",
+				"Location version conversion failed with error: {:?}",
+				e,
+			);
+			XcmVersionsApiError::VersionedLocationConversionFailed
+		})?;
+		let location = VersionedLocation::from(location);
+		let mut destination = DestinationVersion {
+			location: location.clone(),
+			supported_version: None,
+			notifier: None,
+			notify_target: None,
+			discovery_attempts: None,
+			pending_migration: false,
+		};
+
+		// Entries are keyed by the location in the XCM version they were stored with, newer
+		// entries take precedence over the ones still to be migrated.
+		let advertised_version = T::AdvertisedXcmVersion::get();
+		for key_version in (0..=XCM_VERSION).rev() {
+			let Ok(key) = location.clone().into_version(key_version) else { continue };
+
+			if let Some(version) = SupportedVersion::<T>::get(key_version, &key) {
+				destination.supported_version.get_or_insert(version);
+				destination.pending_migration |= key_version != XCM_VERSION;
+			}
+			if let Some(query_id) = VersionNotifiers::<T>::get(key_version, &key) {
+				let is_active = matches!(
+					Queries::<T>::get(query_id),
+					Some(QueryStatus::VersionNotifier { is_active: true, .. })
+				);
+				destination.notifier.get_or_insert(VersionNotifier { query_id, is_active });
+				destination.pending_migration |= key_version != XCM_VERSION;
+			}
+			if let Some((query_id, max_weight, notified_version)) =
+				VersionNotifyTargets::<T>::get(key_version, &key)
+			{
+				destination.notify_target.get_or_insert(VersionNotifyTarget {
+					query_id,
+					max_weight,
+					notified_version,
+					is_pending: notified_version != advertised_version,
+				});
+				destination.pending_migration |= key_version != XCM_VERSION;
+			}
+		}
+		destination.discovery_attempts = VersionDiscoveryQueue::<T>::get()
+			.into_iter()
+			.find(|(queued, _)| {
+				Location::try_from(queued.clone()).ok().map(VersionedLocation::from).as_ref() ==
+					Some(&location)
+			})
+			.map(|(_, attempts)| attempts);
+
+		Ok(destination)
+	}
+
+	/// Returns the XCM version used for destinations whose version is unknown, if any.
+	pub fn safe_xcm_version() -> Option<XcmVersion> {
+		SafeXcmVersion::<T>::get()
+	}
+
 	/// Given an Asset and a Location, returns if the provided location is a trusted reserve for the
 	/// given asset.
 	pub fn is_trusted_reserve(
@@ -3082,19 +3400,25 @@ impl<T: Config> WrapVersion for Pallet<T> {
 		dest: &Location,
 		xcm: impl Into<VersionedXcm<RuntimeCall>>,
 	) -> Result<VersionedXcm<RuntimeCall>, ()> {
-		Self::get_version_for(dest)
+		let version = Self::get_version_for(dest)
 			.or_else(|| {
 				Self::note_unknown_version(dest);
 				SafeXcmVersion::<T>::get()
 			})
-			.ok_or_else(|| {
-				tracing::trace!(
-					target: "xcm::pallet_xcm::wrap_version",
-					?dest, "Could not determine a version to wrap XCM for destination",
-				);
-				()
-			})
-			.and_then(|v| xcm.into().into_version(v.min(XCM_VERSION)))
+			.map(|v| v.min(XCM_VERSION));
+		let Some(version) = version else {
+			tracing::trace!(
+				target: "xcm::pallet_xcm::wrap_version",
+				?dest, "Could not determine a version to wrap XCM for destination",
+			);
+			return Err(())
+		};
+		xcm.into().into_version(version).map_err(|()| {
+			tracing::debug!(
+				target: "xcm::pallet_xcm::wrap_version",
+				?dest, ?version, "Could not convert XCM to the version of destination",
+			);
+		})
 	}
 }
 
@@ -3126,7 +3450,7 @@ impl<T: Config> VersionChangeNotifier for Pallet<T> {
 		let xcm_version = T::AdvertisedXcmVersion::get();
 		let response = Response::Version(xcm_version);
 		let instruction = QueryResponse { query_id, response, max_weight, querier: None };
-		let (message_id, cost) = send_xcm::<T::XcmRouter>(dest.clone(), Xcm(vec![instruction]))?;
+		let (message_id, cost) = Self::route_xcm(dest.clone(), Xcm(vec![instruction]))?;
 		Self::deposit_event(Event::<T>::VersionNotifyStarted {
 			destination: dest.clone(),
 			cost,
@@ -41,6 +41,7 @@ use xcm_executor::{
 	traits::{Properties, QueryHandler, QueryResponseStatus, ShouldExecute},
 	XcmExecutor,
 };
+use xcm_runtime_apis::versions::{DestinationVersion, VersionNotifier, VersionNotifyTarget};
 
 const ALICE: AccountId = AccountId::new([0u8; 32]);
 const BOB: AccountId = AccountId::new([1u8; 32]);
@@ -1183,6 +1184,210 @@ fn get_and_wrap_version_works() {
 	})
 }
 
+#[test]
+fn send_failure_emits_version_conversion_failed_event() {
+	new_test_ext_with_balances_and_xcm_version(vec![], None).execute_with(|| {
+		let remote: Location = Parachain(1000).into();
+		let xcm = Xcm::<()>::default();
+		let version_conversion_failed = |version| {
+			RuntimeEvent::XcmPallet(crate::Event::VersionConversionFailed {
+				destination: remote.clone(),
+				version,
+			})
+		};
+
+		// wrapping alone does not emit the event, e.g. when only querying delivery fees
+		assert_eq!(XcmPallet::wrap_version(&remote, xcm.clone()), Err(()));
+		assert!(!System::events().iter().any(|r| r.event == version_conversion_failed(None)));
+
+		// the version of `remote` is unknown and there is no safe version
+		set_send_xcm_artificial_failure(true);
+		assert!(XcmPallet::send_xcm(Here, remote.clone(), xcm.clone()).is_err());
+		assert_eq!(last_event(), version_conversion_failed(None));
+
+		// `XCM_VERSION` cannot be wrapped to the `1`
+		assert_ok!(XcmPallet::force_xcm_version(
+			RuntimeOrigin::root(),
+			Box::new(remote.clone()),
+			1
+		));
+		assert!(XcmPallet::send_xcm(Here, remote.clone(), xcm).is_err());
+		assert_eq!(last_event(), version_conversion_failed(Some(1)));
+		set_send_xcm_artificial_failure(false);
+	})
+}
+
+#[test]
+fn force_xcm_versions_works() {
+	new_test_ext_with_balances(vec![]).execute_with(|| {
+		let remote_a: Location = Parachain(1000).into();
+		let remote_b: Location = Parachain(1001).into();
+		let versions: Vec<(Box<VersionedLocation>, u32)> = vec![
+			(Box::new(remote_a.clone().into()), 3),
+			(Box::new(remote_b.clone().into()), XCM_VERSION),
+		];
+		assert_noop!(
+			XcmPallet::force_xcm_versions(
+				RuntimeOrigin::signed(ALICE),
+				versions.clone().try_into().unwrap()
+			),
+			sp_runtime::DispatchError::BadOrigin
+		);
+		assert_ok!(XcmPallet::force_xcm_versions(
+			RuntimeOrigin::root(),
+			versions.try_into().unwrap()
+		));
+
+		assert_eq!(XcmPallet::get_version_for(&remote_a), Some(3));
+		assert_eq!(XcmPallet::get_version_for(&remote_b), Some(XCM_VERSION));
+		assert_eq!(
+			last_events(2),
+			vec![
+				RuntimeEvent::XcmPallet(crate::Event::SupportedVersionChanged {
+					location: remote_a,
+					version: 3,
+				}),
+				RuntimeEvent::XcmPallet(crate::Event::SupportedVersionChanged {
+					location: remote_b,
+					version: XCM_VERSION,
+				}),
+			]
+		);
+	})
+}
+
+#[test]
+fn force_version_requery_works() {
+	new_test_ext_with_balances(vec![]).execute_with(|| {
+		let remote: Location = Parachain(1000).into();
+		let unreachable = Location::new(
+			0,
+			[
+				Parachain(1001),
+				GeneralIndex(1),
+				GeneralIndex(2),
+				GeneralIndex(3),
+				GeneralIndex(4),
+				GeneralIndex(5),
+				GeneralIndex(6),
+				GeneralIndex(7),
+			],
+		);
+		assert_ok!(XcmPallet::force_subscribe_version_notify(
+			RuntimeOrigin::root(),
+			Box::new(remote.clone().into()),
+		));
+		take_sent_xcm();
+
+		let locations: Vec<Box<VersionedLocation>> =
+			vec![Box::new(remote.clone().into()), Box::new(unreachable.clone().into())];
+		assert_ok!(XcmPallet::force_version_requery(
+			RuntimeOrigin::root(),
+			locations.try_into().unwrap()
+		));
+
+		// the old subscription is dropped and a new one is requested
+		assert_eq!(
+			take_sent_xcm(),
+			vec![
+				(remote.clone(), Xcm(vec![UnsubscribeVersion])),
+				(
+					remote.clone(),
+					Xcm(vec![SubscribeVersion {
+						query_id: 1,
+						max_response_weight: Weight::zero()
+					}]),
+				),
+			]
+		);
+		assert_eq!(
+			Queries::<Test>::iter().collect::<Vec<_>>(),
+			vec![(
+				1,
+				QueryStatus::VersionNotifier { origin: remote.clone().into(), is_active: false }
+			)]
+		);
+		assert_eq!(
+			VersionNotifiers::<Test>::iter().collect::<Vec<_>>(),
+			vec![(XCM_VERSION, remote.into(), 1)]
+		);
+		assert_eq!(
+			last_event(),
+			RuntimeEvent::XcmPallet(crate::Event::VersionRequeryFailed {
+				location: unreachable,
+				error: XcmError::Transport("Destination location full"),
+			})
+		);
+	})
+}
+
+#[test]
+fn known_destinations_works() {
+	new_test_ext_with_balances_and_xcm_version(vec![], None).execute_with(|| {
+		AdvertisedXcmVersion::set(4);
+		let unknown = |location: &Location| DestinationVersion {
+			location: location.clone().into(),
+			supported_version: None,
+			notifier: None,
+			notify_target: None,
+			discovery_attempts: None,
+			pending_migration: false,
+		};
+
+		let remote_a: Location = Parachain(1000).into();
+		assert_ok!(XcmPallet::force_xcm_version(
+			RuntimeOrigin::root(),
+			Box::new(remote_a.clone()),
+			XCM_VERSION
+		));
+		let remote_b: Location = Parachain(1001).into();
+		assert_ok!(XcmPallet::force_subscribe_version_notify(
+			RuntimeOrigin::root(),
+			Box::new(remote_b.clone().into()),
+		));
+		let remote_c: Location = Parachain(1002).into();
+		VersionNotifyTargets::<Test>::insert(
+			XCM_VERSION,
+			LatestVersionedLocation(&remote_c),
+			(70, Weight::zero(), 3),
+		);
+		// An entry from a previous runtime with v3 XCM.
+		let remote_d: Location = Parachain(1003).into();
+		let v3_location = VersionedLocation::V3(xcm::v3::Junction::Parachain(1003).into());
+		SupportedVersion::<Test>::insert(xcm::v3::VERSION, v3_location, xcm::v3::VERSION);
+		let remote_e: Location = Parachain(1004).into();
+		assert_eq!(XcmPallet::wrap_version(&remote_e, Xcm::<()>::default()), Err(()));
+
+		let expected = vec![
+			DestinationVersion { supported_version: Some(XCM_VERSION), ..unknown(&remote_a) },
+			DestinationVersion {
+				notifier: Some(VersionNotifier { query_id: 0, is_active: false }),
+				..unknown(&remote_b)
+			},
+			DestinationVersion {
+				notify_target: Some(VersionNotifyTarget {
+					query_id: 70,
+					max_weight: Weight::zero(),
+					notified_version: 3,
+					is_pending: true,
+				}),
+				..unknown(&remote_c)
+			},
+			DestinationVersion {
+				supported_version: Some(xcm::v3::VERSION),
+				pending_migration: true,
+				..unknown(&remote_d)
+			},
+			DestinationVersion { discovery_attempts: Some(1), ..unknown(&remote_e) },
+		];
+		assert_eq!(XcmPallet::known_destinations(), expected);
+
+		assert_eq!(XcmPallet::destination_version(remote_b.into()), Ok(expected[1].clone()));
+		let remote_f: Location = Parachain(1005).into();
+		assert_eq!(XcmPallet::destination_version(remote_f.clone().into()), Ok(unknown(&remote_f)));
+	})
+}
+
 #[test]
 fn multistage_migration_works() {
 	new_test_ext_with_balances(vec![]).execute_with(|| {
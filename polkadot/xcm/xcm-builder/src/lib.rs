@@ -122,7 +122,7 @@ mod pay;
 pub use pay::{FixedLocation, LocatableAssetId, PayAccountId32OnChainOverXcm, PayOverXcm};
 
 mod process_xcm_message;
-pub use process_xcm_message::ProcessXcmMessage;
+pub use process_xcm_message::{ProcessXcmMessage, RateLimitedMessageProcessor};
 
 mod routing;
 pub use routing::{
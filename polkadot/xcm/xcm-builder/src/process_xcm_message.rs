@@ -25,6 +25,7 @@ use frame_support::{
 use scale_info::TypeInfo;
 use sp_weights::{Weight, WeightMeter};
 use xcm::prelude::*;
+use xcm_executor::traits::RateLimit;
 
 const LOG_TARGET: &str = "xcm::process-message";
 
@@ -118,6 +119,63 @@ impl<
 	}
 }
 
+/// A message processor that limits the rate at which origins can have their messages processed
+/// by `Processor`, usually a [`ProcessXcmMessage`].
+///
+/// The messages of origins without budget left yield, so that they remain queued until the budget
+/// of their origin is replenished. Every other message is charged to the budget of its origin with
+/// the weight it used, so a message may exceed the budget that was left for it. The weight of
+/// checking and charging the budget is consumed from the meter before the message is processed.
+///
+/// Rate limiting is done here rather than in a barrier: a barrier can only reject a message, which
+/// makes the message queue drop it, while yielding keeps it queued for a later period.
+pub struct RateLimitedMessageProcessor<Processor, RateLimiter>(
+	PhantomData<(Processor, RateLimiter)>,
+);
+impl<Processor, RateLimiter> ProcessMessage for RateLimitedMessageProcessor<Processor, RateLimiter>
+where
+	Processor: ProcessMessage,
+	Processor::Origin: Into<Location>,
+	RateLimiter: RateLimit,
+{
+	type Origin = Processor::Origin;
+
+	fn process_message(
+		message: &[u8],
+		origin: Self::Origin,
+		meter: &mut WeightMeter,
+		id: &mut XcmHash,
+	) -> Result<bool, ProcessMessageError> {
+		let rate_limit_weight = RateLimiter::weight();
+		if meter.try_consume(rate_limit_weight).is_err() {
+			log::trace!(
+				target: LOG_TARGET,
+				"Not enough weight left to check the budget of the origin, required: {rate_limit_weight:?}",
+			);
+
+			return Err(ProcessMessageError::Overweight(rate_limit_weight))
+		}
+
+		let location = origin.clone().into();
+		if !RateLimiter::can_execute(&location) {
+			log::trace!(
+				target: LOG_TARGET,
+				"Origin {location:?} exceeded its budget, yielding.",
+			);
+
+			return Err(ProcessMessageError::Yield)
+		}
+
+		let consumed = meter.consumed();
+		let result = Processor::process_message(message, origin, meter, id);
+		// Messages which were not processed yet are not charged.
+		if !matches!(result, Err(ProcessMessageError::Overweight(_) | ProcessMessageError::Yield)) {
+			RateLimiter::note_executed(&location, meter.consumed().saturating_sub(consumed));
+		}
+		result
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -222,6 +280,65 @@ mod tests {
 		}
 	}
 
+	#[test]
+	fn rate_limited_messages_yield_and_are_charged_with_used_weight() {
+		use core::cell::{Cell, RefCell};
+
+		thread_local! {
+			static LIMITED: Cell<bool> = Cell::new(false);
+			static CHARGED: RefCell<Vec<(Location, Weight)>> = RefCell::new(vec![]);
+		}
+
+		const RATE_LIMIT_WEIGHT: Weight = Weight::from_parts(10, 0);
+
+		struct TestRateLimiter;
+		impl RateLimit for TestRateLimiter {
+			fn can_execute(_: &Location) -> bool {
+				!LIMITED.get()
+			}
+
+			fn note_executed(origin: &Location, weight: Weight) {
+				CHARGED.with(|charged| charged.borrow_mut().push((origin.clone(), weight)));
+			}
+
+			fn weight() -> Weight {
+				RATE_LIMIT_WEIGHT
+			}
+		}
+
+		type Limited = RateLimitedMessageProcessor<Processor, TestRateLimiter>;
+		let msg = v5_xcm(true).encode();
+
+		// Processed messages are charged with the weight they used, the meter is also charged
+		// with the weight of the rate limiter.
+		let meter = &mut WeightMeter::new();
+		assert_ok!(Limited::process_message(&msg, ORIGIN, meter, &mut [0; 32]));
+		assert_eq!(CHARGED.take(), vec![(ORIGIN.into(), meter.consumed() - RATE_LIMIT_WEIGHT)]);
+
+		// Messages are not processed without weight left for the rate limiter.
+		let meter = &mut WeightMeter::with_limit(Weight::zero());
+		assert_err!(
+			Limited::process_message(&msg, ORIGIN, meter, &mut [0; 32]),
+			Overweight(RATE_LIMIT_WEIGHT)
+		);
+		assert!(CHARGED.take().is_empty());
+
+		// Messages which do not fit into the remaining weight are not charged.
+		let meter = &mut WeightMeter::with_limit(RATE_LIMIT_WEIGHT);
+		assert_err!(
+			Limited::process_message(&msg, ORIGIN, meter, &mut [0; 32]),
+			Overweight(1000.into())
+		);
+		assert!(CHARGED.take().is_empty());
+
+		// Origins without budget left yield without processing the message.
+		LIMITED.set(true);
+		let meter = &mut WeightMeter::new();
+		assert_err!(Limited::process_message(&msg, ORIGIN, meter, &mut [0; 32]), Yield);
+		assert_eq!(meter.consumed(), RATE_LIMIT_WEIGHT);
+		assert!(CHARGED.take().is_empty());
+	}
+
 	fn v3_xcm(success: bool) -> VersionedXcm<RuntimeCall> {
 		let instr = if success {
 			v3::Instruction::<RuntimeCall>::ClearOrigin
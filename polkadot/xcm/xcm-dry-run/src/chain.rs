@@ -0,0 +1,230 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! [`DryRunChain`] implementation on top of the runtime APIs of a chain.
+
+use crate::{DryRunChain, DryRunEffects, Error, HopOutcome};
+use codec::{Decode, Encode};
+use sc_client_api::{CallExecutor, ExecutorProvider};
+use serde::{de::DeserializeOwned, Serialize};
+use sp_core::traits::CallContext;
+use sp_runtime::traits::Block as BlockT;
+use std::{marker::PhantomData, sync::Arc};
+use substrate_rpc_client::{ChainApi, StateApi, WsClient};
+use xcm::{
+	latest::{InteriorLocation, Weight},
+	VersionedAssetId, VersionedAssets, VersionedLocation, VersionedXcm,
+};
+use xcm_runtime_apis::{
+	dry_run::{CallDryRunEffects, Error as DryRunApiError, XcmDryRunEffects},
+	fees::Error as PaymentApiError,
+};
+
+const DRY_RUN_CALL: &str = "DryRunApi_dry_run_call";
+const DRY_RUN_XCM: &str = "DryRunApi_dry_run_xcm";
+const QUERY_XCM_WEIGHT: &str = "XcmPaymentApi_query_xcm_weight";
+const QUERY_WEIGHT_TO_ASSET_FEE: &str = "XcmPaymentApi_query_weight_to_asset_fee";
+const QUERY_DELIVERY_FEES: &str = "XcmPaymentApi_query_delivery_fees";
+
+/// Calls runtime API functions of a chain at a fixed block.
+#[async_trait::async_trait]
+pub trait RuntimeCaller: Send + Sync {
+	/// Call the runtime API function `method` with the SCALE encoded arguments `data`.
+	async fn call(&self, method: &str, data: Vec<u8>) -> Result<Vec<u8>, Error>;
+}
+
+/// Calls the runtime of a local client.
+pub struct LocalCaller<Block: BlockT, Client> {
+	client: Arc<Client>,
+	at: Block::Hash,
+}
+
+impl<Block: BlockT, Client> LocalCaller<Block, Client> {
+	/// Create a new instance calling the runtime at block `at`.
+	pub fn new(client: Arc<Client>, at: Block::Hash) -> Self {
+		Self { client, at }
+	}
+}
+
+#[async_trait::async_trait]
+impl<Block, Client> RuntimeCaller for LocalCaller<Block, Client>
+where
+	Block: BlockT,
+	Client: ExecutorProvider<Block> + Send + Sync,
+{
+	async fn call(&self, method: &str, data: Vec<u8>) -> Result<Vec<u8>, Error> {
+		self.client
+			.executor()
+			.call(self.at, method, &data, CallContext::Offchain)
+			.map_err(|e| Error::RuntimeCall(e.to_string()))
+	}
+}
+
+/// Calls the runtime of a remote node through `state_call`.
+///
+/// All calls are made at the same block, so that every hop of a dry-run sees the same state of
+/// the chain.
+pub struct RemoteCaller<Hash> {
+	client: Arc<WsClient>,
+	at: Hash,
+}
+
+impl<Hash> RemoteCaller<Hash>
+where
+	Hash: Serialize + DeserializeOwned + Send + Sync + 'static,
+{
+	/// Create a new instance calling the runtime at block `at`.
+	pub fn new(client: Arc<WsClient>, at: Hash) -> Self {
+		Self { client, at }
+	}
+
+	/// Connect to the node at `uri` and create a new instance calling the runtime at block `at`.
+	///
+	/// If `at` is `None`, the finalized block of the node at the time of connecting is used.
+	pub async fn connect(uri: impl AsRef<str>, at: Option<Hash>) -> Result<Self, Error> {
+		let client = substrate_rpc_client::ws_client(uri).await.map_err(Error::RuntimeCall)?;
+		let at = match at {
+			Some(at) => at,
+			None => ChainApi::<(), Hash, (), ()>::finalized_head(&client)
+				.await
+				.map_err(|e| Error::RuntimeCall(e.to_string()))?,
+		};
+		Ok(Self::new(Arc::new(client), at))
+	}
+
+	/// The block the runtime is called at.
+	pub fn at(&self) -> &Hash {
+		&self.at
+	}
+}
+
+#[async_trait::async_trait]
+impl<Hash> RuntimeCaller for RemoteCaller<Hash>
+where
+	Hash: Serialize + DeserializeOwned + Clone + Send + Sync + 'static,
+{
+	async fn call(&self, method: &str, data: Vec<u8>) -> Result<Vec<u8>, Error> {
+		StateApi::<Hash>::call(&*self.client, method.into(), data.into(), Some(self.at.clone()))
+			.await
+			.map(|result| result.0)
+			.map_err(|e| Error::RuntimeCall(e.to_string()))
+	}
+}
+
+/// A chain accessed through its `DryRunApi` and `XcmPaymentApi` runtime APIs.
+///
+/// `Event` is the `RuntimeEvent` type of the chain, which is needed to decode the results of the
+/// `DryRunApi`.
+pub struct RuntimeApiChain<Event> {
+	universal_location: InteriorLocation,
+	caller: Box<dyn RuntimeCaller>,
+	_marker: PhantomData<fn() -> Event>,
+}
+
+impl<Event: Decode> RuntimeApiChain<Event> {
+	/// Create a new instance for the chain at `universal_location`, calling its runtime with
+	/// `caller`.
+	pub fn new(universal_location: InteriorLocation, caller: impl RuntimeCaller + 'static) -> Self {
+		Self { universal_location, caller: Box::new(caller), _marker: PhantomData }
+	}
+
+	async fn call<R: Decode>(&self, method: &str, data: Vec<u8>) -> Result<R, Error> {
+		let result = self.caller.call(method, data).await?;
+		R::decode(&mut &result[..]).map_err(|_| Error::Decode(method.into()))
+	}
+}
+
+#[async_trait::async_trait]
+impl<Event> DryRunChain for RuntimeApiChain<Event>
+where
+	Event: Encode + Decode + Send + 'static,
+{
+	fn universal_location(&self) -> InteriorLocation {
+		self.universal_location.clone()
+	}
+
+	async fn dry_run_call(&self, origin: &[u8], call: &[u8]) -> Result<DryRunEffects, Error> {
+		let effects = self
+			.call::<Result<CallDryRunEffects<Event>, DryRunApiError>>(
+				DRY_RUN_CALL,
+				[origin, call].concat(),
+			)
+			.await?
+			.map_err(Error::DryRunApi)?;
+
+		Ok(DryRunEffects {
+			outcome: HopOutcome::Call(effects.execution_result),
+			events: effects.emitted_events.iter().map(Encode::encode).collect(),
+			local_xcm: effects.local_xcm,
+			forwarded_xcms: effects.forwarded_xcms,
+		})
+	}
+
+	async fn dry_run_xcm(
+		&self,
+		origin: VersionedLocation,
+		xcm: VersionedXcm<()>,
+	) -> Result<DryRunEffects, Error> {
+		// `VersionedXcm<()>` is encoded the same as the `VersionedXcm<RuntimeCall>` of the runtime,
+		// calls are kept double encoded.
+		let effects = self
+			.call::<Result<XcmDryRunEffects<Event>, DryRunApiError>>(
+				DRY_RUN_XCM,
+				(origin, xcm).encode(),
+			)
+			.await?
+			.map_err(Error::DryRunApi)?;
+
+		Ok(DryRunEffects {
+			outcome: HopOutcome::Xcm(effects.execution_result),
+			events: effects.emitted_events.iter().map(Encode::encode).collect(),
+			local_xcm: None,
+			forwarded_xcms: effects.forwarded_xcms,
+		})
+	}
+
+	async fn query_xcm_weight(&self, xcm: VersionedXcm<()>) -> Result<Weight, Error> {
+		self.call::<Result<Weight, PaymentApiError>>(QUERY_XCM_WEIGHT, xcm.encode())
+			.await?
+			.map_err(Error::PaymentApi)
+	}
+
+	async fn query_weight_to_asset_fee(
+		&self,
+		weight: Weight,
+		asset: VersionedAssetId,
+	) -> Result<u128, Error> {
+		self.call::<Result<u128, PaymentApiError>>(
+			QUERY_WEIGHT_TO_ASSET_FEE,
+			(weight, asset).encode(),
+		)
+		.await?
+		.map_err(Error::PaymentApi)
+	}
+
+	async fn query_delivery_fees(
+		&self,
+		destination: VersionedLocation,
+		xcm: VersionedXcm<()>,
+	) -> Result<VersionedAssets, Error> {
+		self.call::<Result<VersionedAssets, PaymentApiError>>(
+			QUERY_DELIVERY_FEES,
+			(destination, xcm).encode(),
+		)
+		.await?
+		.map_err(Error::PaymentApi)
+	}
+}
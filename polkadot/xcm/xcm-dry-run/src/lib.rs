@@ -0,0 +1,700 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Multi-hop dry-run of XCM programs across chains.
+//!
+//! The [`DryRunApi`](xcm_runtime_apis::dry_run::DryRunApi) of a runtime only simulates the
+//! execution on that one chain and returns the messages that would be forwarded to other chains.
+//! [`MultiHopDryRun`] takes those messages and feeds them into the dry-run API of their
+//! destinations, recursively, until no more messages are sent or the destination is not known.
+//! The result is a [`DryRunTrace`] with the outcome, events and fees of every hop.
+//!
+//! Chains are accessed through the [`DryRunChain`] trait. [`RuntimeApiChain`] implements it on top
+//! of a local client or a remote node, in both cases against the state of a fixed block. Every hop
+//! is executed on top of that state, so changes of an earlier hop on the same chain are not
+//! visible to later hops.
+//!
+//! The [`rpc`] module exposes the multi-hop dry-run as RPC methods of a node. The node needs to
+//! configure the other chains, e.g. with a [`RemoteCaller`] for every chain.
+
+use codec::{Decode, Encode};
+use frame_support::dispatch::DispatchResultWithPostInfo;
+use std::{collections::VecDeque, sync::Arc};
+use xcm::{latest::prelude::*, VersionedAssetId, VersionedAssets, VersionedLocation, VersionedXcm};
+
+mod chain;
+pub mod rpc;
+
+pub use chain::{LocalCaller, RemoteCaller, RuntimeApiChain, RuntimeCaller};
+
+const LOG_TARGET: &str = "xcm::dry-run";
+
+/// The default maximum number of hops that are dry-run.
+pub const DEFAULT_MAX_HOPS: u32 = 16;
+
+/// Errors of the multi-hop dry-run.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, thiserror::Error)]
+pub enum Error {
+	/// Calling the runtime failed.
+	#[error("Runtime call failed: {0}")]
+	RuntimeCall(String),
+	/// The result of a runtime call could not be decoded.
+	#[error("Failed to decode the result of `{0}`")]
+	Decode(String),
+	/// The `DryRunApi` returned an error.
+	#[error("Dry-run API error: {0:?}")]
+	DryRunApi(xcm_runtime_apis::dry_run::Error),
+	/// The `XcmPaymentApi` returned an error.
+	#[error("Payment API error: {0:?}")]
+	PaymentApi(xcm_runtime_apis::fees::Error),
+	/// A location or message could not be converted to the latest XCM version.
+	#[error("Unsupported XCM version")]
+	UnsupportedVersion,
+	/// The destination of a message does not resolve to a chain.
+	#[error("Invalid destination {0:?}")]
+	InvalidDestination(Location),
+	/// No chain is configured at the given universal location.
+	#[error("No chain known at {0:?}")]
+	UnknownChain(InteriorLocation),
+	/// The maximum number of hops was reached before the message was dry-run.
+	#[error("Maximum number of hops reached")]
+	TooManyHops,
+}
+
+/// The outcome of dry-running a hop.
+#[derive(Debug, Clone, PartialEq, Encode, Decode)]
+pub enum HopOutcome {
+	/// The result of dispatching a call.
+	Call(DispatchResultWithPostInfo),
+	/// The outcome of executing an XCM program.
+	Xcm(Outcome),
+}
+
+impl HopOutcome {
+	/// Whether the call or program was executed successfully.
+	pub fn is_success(&self) -> bool {
+		match self {
+			Self::Call(result) => result.is_ok(),
+			Self::Xcm(outcome) => matches!(outcome, Outcome::Complete { .. }),
+		}
+	}
+}
+
+/// Effects of dry-running a call or XCM program on a single chain.
+#[derive(Debug, Clone, PartialEq, Encode, Decode)]
+pub struct DryRunEffects {
+	/// The outcome of the execution.
+	pub outcome: HopOutcome,
+	/// The SCALE encoded events emitted by the execution.
+	pub events: Vec<Vec<u8>>,
+	/// The local XCM that was attempted to be executed by a call, if any.
+	pub local_xcm: Option<VersionedXcm<()>>,
+	/// The messages queued for sending, with their destination relative to the chain.
+	pub forwarded_xcms: Vec<(VersionedLocation, Vec<VersionedXcm<()>>)>,
+}
+
+/// A chain that the multi-hop dry-run can be executed on.
+///
+/// The methods correspond to the `DryRunApi` and `XcmPaymentApi` runtime APIs.
+#[async_trait::async_trait]
+pub trait DryRunChain: Send + Sync {
+	/// The universal location of the chain.
+	fn universal_location(&self) -> InteriorLocation;
+
+	/// Dry-run the SCALE encoded `call` dispatched by the SCALE encoded `origin`.
+	///
+	/// The encoding of both depends on the runtime of the chain, they are passed to it as is.
+	async fn dry_run_call(&self, origin: &[u8], call: &[u8]) -> Result<DryRunEffects, Error>;
+
+	/// Dry-run the XCM program `xcm` sent by `origin`.
+	async fn dry_run_xcm(
+		&self,
+		origin: VersionedLocation,
+		xcm: VersionedXcm<()>,
+	) -> Result<DryRunEffects, Error>;
+
+	/// Returns the weight needed to execute `xcm`.
+	async fn query_xcm_weight(&self, xcm: VersionedXcm<()>) -> Result<Weight, Error>;
+
+	/// Converts `weight` into a fee in `asset`.
+	async fn query_weight_to_asset_fee(
+		&self,
+		weight: Weight,
+		asset: VersionedAssetId,
+	) -> Result<u128, Error>;
+
+	/// Returns the fees for delivering `xcm` to `destination`.
+	async fn query_delivery_fees(
+		&self,
+		destination: VersionedLocation,
+		xcm: VersionedXcm<()>,
+	) -> Result<VersionedAssets, Error>;
+}
+
+/// A message sent by a hop.
+#[derive(Debug, Clone, PartialEq, Encode, Decode)]
+pub struct ForwardedXcm {
+	/// The destination of the message, relative to the sending chain.
+	pub destination: VersionedLocation,
+	/// The message.
+	pub xcm: VersionedXcm<()>,
+	/// The delivery fees charged by the sending chain, if they could be determined.
+	pub delivery_fees: Option<VersionedAssets>,
+	/// The index of the hop that dry-ran the message on the destination.
+	pub hop: Option<u32>,
+}
+
+/// The effects of a single hop.
+#[derive(Debug, Clone, PartialEq, Encode, Decode)]
+pub struct HopEffects {
+	/// The outcome of the execution.
+	pub outcome: HopOutcome,
+	/// The SCALE encoded events emitted by the execution.
+	pub events: Vec<Vec<u8>>,
+	/// The weight needed to execute the received message.
+	///
+	/// `None` for the hop that dispatched the initial call.
+	pub execution_weight: Option<Weight>,
+	/// The fee for the execution weight in the asset the message pays fees with.
+	///
+	/// Only known if the message pays for its execution with `BuyExecution` or `PayFees`.
+	pub execution_fee: Option<(VersionedAssetId, u128)>,
+	/// The messages sent by the hop.
+	pub forwarded_xcms: Vec<ForwardedXcm>,
+}
+
+/// A single hop of the multi-hop dry-run.
+#[derive(Debug, Clone, PartialEq, Encode, Decode)]
+pub struct Hop {
+	/// The index of the hop that sent the message executed by this hop.
+	///
+	/// `None` for the first hop.
+	pub parent: Option<u32>,
+	/// The universal location of the chain.
+	pub chain: InteriorLocation,
+	/// The origin of the executed message, as seen by the chain.
+	///
+	/// `None` if the hop dispatched a call.
+	pub origin: Option<VersionedLocation>,
+	/// The executed message, or the local XCM of a dispatched call.
+	pub xcm: Option<VersionedXcm<()>>,
+	/// The effects of the hop, or why it could not be dry-run.
+	pub result: Result<HopEffects, Error>,
+}
+
+/// The combined trace of a multi-hop dry-run.
+///
+/// Hops are ordered breadth-first, the first hop is the one on the origin chain.
+#[derive(Debug, Clone, PartialEq, Encode, Decode)]
+pub struct DryRunTrace {
+	/// All hops that were dry-run.
+	pub hops: Vec<Hop>,
+}
+
+impl DryRunTrace {
+	/// Whether all hops executed successfully.
+	pub fn is_success(&self) -> bool {
+		self.hops
+			.iter()
+			.all(|hop| hop.result.as_ref().map_or(false, |effects| effects.outcome.is_success()))
+	}
+
+	/// The first hop that failed, if any.
+	pub fn first_failure(&self) -> Option<&Hop> {
+		self.hops
+			.iter()
+			.find(|hop| hop.result.as_ref().map_or(true, |effects| !effects.outcome.is_success()))
+	}
+}
+
+/// A message waiting to be dry-run on its destination.
+struct PendingXcm {
+	parent: u32,
+	forwarded_index: usize,
+	sender: InteriorLocation,
+	destination: VersionedLocation,
+	xcm: VersionedXcm<()>,
+}
+
+/// Dry-runs calls and XCM programs across a set of chains.
+pub struct MultiHopDryRun {
+	chains: Vec<Arc<dyn DryRunChain>>,
+	max_hops: u32,
+}
+
+impl Default for MultiHopDryRun {
+	fn default() -> Self {
+		Self { chains: Vec::new(), max_hops: DEFAULT_MAX_HOPS }
+	}
+}
+
+impl MultiHopDryRun {
+	/// Create a new instance without any chains.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Add a chain that messages can be dry-run on.
+	pub fn with_chain(mut self, chain: Arc<dyn DryRunChain>) -> Self {
+		self.chains.push(chain);
+		self
+	}
+
+	/// Set the maximum number of hops that are dry-run.
+	pub fn with_max_hops(mut self, max_hops: u32) -> Self {
+		self.max_hops = max_hops;
+		self
+	}
+
+	fn chain(&self, location: &InteriorLocation) -> Option<&Arc<dyn DryRunChain>> {
+		self.chains.iter().find(|chain| chain.universal_location() == *location)
+	}
+
+	/// Dry-run the SCALE encoded `call` dispatched by `origin` on the chain at `chain` and all
+	/// messages it sends.
+	pub async fn dry_run_call(
+		&self,
+		chain: &InteriorLocation,
+		origin: &[u8],
+		call: &[u8],
+	) -> DryRunTrace {
+		let result = match self.chain(chain) {
+			Some(dry_run_chain) => dry_run_chain.dry_run_call(origin, call).await,
+			None => Err(Error::UnknownChain(chain.clone())),
+		};
+		let xcm = result.as_ref().ok().and_then(|effects| effects.local_xcm.clone());
+
+		self.follow(chain.clone(), None, xcm, result).await
+	}
+
+	/// Dry-run `xcm` sent by `origin` on the chain at `chain` and all messages it sends.
+	///
+	/// `origin` is relative to the chain the message is executed on.
+	pub async fn dry_run_xcm(
+		&self,
+		chain: &InteriorLocation,
+		origin: VersionedLocation,
+		xcm: VersionedXcm<()>,
+	) -> DryRunTrace {
+		let result = match self.chain(chain) {
+			Some(dry_run_chain) => dry_run_chain.dry_run_xcm(origin.clone(), xcm.clone()).await,
+			None => Err(Error::UnknownChain(chain.clone())),
+		};
+
+		self.follow(chain.clone(), Some(origin), Some(xcm), result).await
+	}
+
+	/// Record the first hop and dry-run the forwarded messages breadth-first.
+	async fn follow(
+		&self,
+		chain: InteriorLocation,
+		origin: Option<VersionedLocation>,
+		xcm: Option<VersionedXcm<()>>,
+		result: Result<DryRunEffects, Error>,
+	) -> DryRunTrace {
+		let mut trace = DryRunTrace { hops: Vec::new() };
+		let mut pending = VecDeque::new();
+
+		self.record_hop(&mut trace, &mut pending, None, chain, origin, xcm, result)
+			.await;
+
+		while let Some(next) = pending.pop_front() {
+			let PendingXcm { parent, forwarded_index, sender, destination, xcm } = next;
+			let index = trace.hops.len() as u32;
+			if let Ok(effects) = &mut trace.hops[parent as usize].result {
+				effects.forwarded_xcms[forwarded_index].hop = Some(index);
+			}
+
+			let destination = match resolve_destination(&sender, destination) {
+				Ok(destination) => destination,
+				Err(error) => {
+					trace.hops.push(Hop {
+						parent: Some(parent),
+						chain: Here,
+						origin: None,
+						xcm: Some(xcm),
+						result: Err(error),
+					});
+					continue
+				},
+			};
+			let origin = VersionedLocation::from(relative_location(&destination, &sender));
+
+			if index >= self.max_hops {
+				trace.hops.push(Hop {
+					parent: Some(parent),
+					chain: destination,
+					origin: Some(origin),
+					xcm: Some(xcm),
+					result: Err(Error::TooManyHops),
+				});
+				continue
+			}
+
+			let Some(dry_run_chain) = self.chain(&destination) else {
+				trace.hops.push(Hop {
+					parent: Some(parent),
+					chain: destination.clone(),
+					origin: Some(origin),
+					xcm: Some(xcm),
+					result: Err(Error::UnknownChain(destination)),
+				});
+				continue
+			};
+
+			let result = dry_run_chain.dry_run_xcm(origin.clone(), xcm.clone()).await;
+			self.record_hop(
+				&mut trace,
+				&mut pending,
+				Some(parent),
+				destination,
+				Some(origin),
+				Some(xcm),
+				result,
+			)
+			.await;
+		}
+
+		trace
+	}
+
+	/// Complete the effects of a hop with its fees, append it to `trace` and queue the messages
+	/// it sends.
+	#[allow(clippy::too_many_arguments)]
+	async fn record_hop(
+		&self,
+		trace: &mut DryRunTrace,
+		pending: &mut VecDeque<PendingXcm>,
+		parent: Option<u32>,
+		chain: InteriorLocation,
+		origin: Option<VersionedLocation>,
+		xcm: Option<VersionedXcm<()>>,
+		result: Result<DryRunEffects, Error>,
+	) {
+		let index = trace.hops.len() as u32;
+		let dry_run_chain = self.chain(&chain);
+
+		let result = match (result, dry_run_chain) {
+			(Ok(effects), Some(dry_run_chain)) => {
+				// Only received messages pay for their execution, calls pay transaction fees.
+				let received_xcm = origin.as_ref().and(xcm.as_ref());
+				let execution_weight = match received_xcm {
+					Some(xcm) => dry_run_chain.query_xcm_weight(xcm.clone()).await.ok(),
+					None => None,
+				};
+				let execution_fee = match (execution_weight, received_xcm) {
+					(Some(weight), Some(xcm)) =>
+						execution_fee(dry_run_chain.as_ref(), weight, xcm).await,
+					_ => None,
+				};
+
+				let mut forwarded_xcms = Vec::new();
+				for (destination, messages) in effects.forwarded_xcms {
+					for message in messages {
+						let delivery_fees = dry_run_chain
+							.query_delivery_fees(destination.clone(), message.clone())
+							.await
+							.inspect_err(|error| {
+								log::debug!(
+									target: LOG_TARGET,
+									"Failed to query delivery fees to {destination:?}: {error}",
+								)
+							})
+							.ok();
+
+						pending.push_back(PendingXcm {
+							parent: index,
+							forwarded_index: forwarded_xcms.len(),
+							sender: chain.clone(),
+							destination: destination.clone(),
+							xcm: message.clone(),
+						});
+						forwarded_xcms.push(ForwardedXcm {
+							destination: destination.clone(),
+							xcm: message,
+							delivery_fees,
+							hop: None,
+						});
+					}
+				}
+
+				Ok(HopEffects {
+					outcome: effects.outcome,
+					events: effects.events,
+					execution_weight,
+					execution_fee,
+					forwarded_xcms,
+				})
+			},
+			(Ok(_), None) => Err(Error::UnknownChain(chain.clone())),
+			(Err(error), _) => Err(error),
+		};
+
+		trace.hops.push(Hop { parent, chain, origin, xcm, result });
+	}
+}
+
+/// Determine the execution fee of `xcm` from the asset it pays fees with.
+async fn execution_fee(
+	chain: &dyn DryRunChain,
+	weight: Weight,
+	xcm: &VersionedXcm<()>,
+) -> Option<(VersionedAssetId, u128)> {
+	let xcm = Xcm::<()>::try_from(xcm.clone()).ok()?;
+	let asset_id = xcm.0.iter().find_map(|instruction| match instruction {
+		BuyExecution { fees, .. } => Some(fees.id.clone()),
+		PayFees { asset } => Some(asset.id.clone()),
+		_ => None,
+	})?;
+	let asset_id = VersionedAssetId::from(asset_id);
+
+	match chain.query_weight_to_asset_fee(weight, asset_id.clone()).await {
+		Ok(fee) => Some((asset_id, fee)),
+		Err(error) => {
+			log::debug!(target: LOG_TARGET, "Failed to query execution fee in {asset_id:?}: {error}");
+			None
+		},
+	}
+}
+
+/// Resolve `destination`, relative to `sender`, to a universal location.
+fn resolve_destination(
+	sender: &InteriorLocation,
+	destination: VersionedLocation,
+) -> Result<InteriorLocation, Error> {
+	let destination = Location::try_from(destination).map_err(|()| Error::UnsupportedVersion)?;
+	let absolute = Location::new(0, sender.clone())
+		.appended_with(destination.clone())
+		.map_err(|_| Error::InvalidDestination(destination.clone()))?;
+
+	if absolute.parent_count() > 0 {
+		return Err(Error::InvalidDestination(destination))
+	}
+
+	Ok(absolute.interior().clone())
+}
+
+/// The location of `to` as seen from `from`, both being universal locations.
+pub fn relative_location(from: &InteriorLocation, to: &InteriorLocation) -> Location {
+	let common = from.iter().zip(to.iter()).take_while(|(a, b)| a == b).count();
+
+	let mut interior = Here;
+	for junction in to.iter().skip(common) {
+		interior
+			.push(junction.clone())
+			.expect("suffix of a valid interior location is a valid interior location; qed");
+	}
+
+	Location::new((from.len() - common) as u8, interior)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use frame_support::dispatch::PostDispatchInfo;
+
+	/// A chain that answers every message by sending `next` to `destination`.
+	struct MockChain {
+		location: InteriorLocation,
+		forward: Option<(Location, Xcm<()>)>,
+		fails: bool,
+	}
+
+	impl MockChain {
+		fn new(location: impl Into<InteriorLocation>) -> Self {
+			Self { location: location.into(), forward: None, fails: false }
+		}
+
+		fn forwarding(mut self, destination: Location, xcm: Xcm<()>) -> Self {
+			self.forward = Some((destination, xcm));
+			self
+		}
+
+		fn failing(mut self) -> Self {
+			self.fails = true;
+			self
+		}
+
+		fn effects(&self, outcome: HopOutcome) -> DryRunEffects {
+			DryRunEffects {
+				outcome,
+				events: vec![self.location.encode()],
+				local_xcm: None,
+				forwarded_xcms: self
+					.forward
+					.iter()
+					.map(|(destination, xcm)| {
+						(destination.clone().into(), vec![VersionedXcm::from(xcm.clone())])
+					})
+					.collect(),
+			}
+		}
+	}
+
+	#[async_trait::async_trait]
+	impl DryRunChain for MockChain {
+		fn universal_location(&self) -> InteriorLocation {
+			self.location.clone()
+		}
+
+		async fn dry_run_call(&self, _: &[u8], _: &[u8]) -> Result<DryRunEffects, Error> {
+			Ok(self.effects(HopOutcome::Call(Ok(PostDispatchInfo::default()))))
+		}
+
+		async fn dry_run_xcm(
+			&self,
+			_: VersionedLocation,
+			_: VersionedXcm<()>,
+		) -> Result<DryRunEffects, Error> {
+			let outcome = if self.fails {
+				Outcome::Incomplete { used: Weight::zero(), error: XcmError::Barrier }
+			} else {
+				Outcome::Complete { used: Weight::from_parts(1, 1) }
+			};
+			Ok(self.effects(HopOutcome::Xcm(outcome)))
+		}
+
+		async fn query_xcm_weight(&self, _: VersionedXcm<()>) -> Result<Weight, Error> {
+			Ok(Weight::from_parts(1_000, 10))
+		}
+
+		async fn query_weight_to_asset_fee(
+			&self,
+			weight: Weight,
+			_: VersionedAssetId,
+		) -> Result<u128, Error> {
+			Ok(weight.ref_time() as u128)
+		}
+
+		async fn query_delivery_fees(
+			&self,
+			_: VersionedLocation,
+			_: VersionedXcm<()>,
+		) -> Result<VersionedAssets, Error> {
+			Ok(VersionedAssets::from(Assets::from((Parent, 42u128))))
+		}
+	}
+
+	fn para(id: u32) -> InteriorLocation {
+		[GlobalConsensus(Westend), Parachain(id)].into()
+	}
+
+	fn reserve_transfer() -> Xcm<()> {
+		Xcm(vec![
+			ReserveAssetDeposited((Parent, 100u128).into()),
+			BuyExecution { fees: (Parent, 100u128).into(), weight_limit: Unlimited },
+		])
+	}
+
+	#[test]
+	fn relative_location_works() {
+		let relay: InteriorLocation = [GlobalConsensus(Westend)].into();
+		assert_eq!(
+			relative_location(&para(2000), &para(1000)),
+			Location::new(1, [Parachain(1000)])
+		);
+		assert_eq!(relative_location(&para(2000), &relay), Location::parent());
+		assert_eq!(relative_location(&relay, &para(1000)), Location::new(0, [Parachain(1000)]));
+		assert_eq!(relative_location(&para(1000), &para(1000)), Location::here());
+		assert_eq!(
+			relative_location(&para(1000), &[GlobalConsensus(Rococo), Parachain(1000)].into()),
+			Location::new(2, [GlobalConsensus(Rococo), Parachain(1000)]),
+		);
+	}
+
+	#[tokio::test]
+	async fn follows_messages_across_chains() {
+		let dry_run = MultiHopDryRun::new()
+			.with_chain(Arc::new(
+				MockChain::new(para(2000))
+					.forwarding(Location::new(1, [Parachain(1000)]), reserve_transfer()),
+			))
+			.with_chain(Arc::new(
+				MockChain::new(para(1000))
+					.forwarding(Location::new(1, [Parachain(2001)]), reserve_transfer()),
+			))
+			.with_chain(Arc::new(MockChain::new(para(2001))));
+
+		let trace = dry_run.dry_run_call(&para(2000), &[], &[]).await;
+
+		assert!(trace.is_success());
+		assert_eq!(
+			trace.hops.iter().map(|hop| (hop.parent, hop.chain.clone())).collect::<Vec<_>>(),
+			vec![(None, para(2000)), (Some(0), para(1000)), (Some(1), para(2001))],
+		);
+		assert_eq!(trace.hops[1].origin, Some(Location::new(1, [Parachain(2000)]).into()));
+		assert_eq!(trace.hops[2].origin, Some(Location::new(1, [Parachain(1000)]).into()));
+
+		let first = trace.hops[0].result.as_ref().unwrap();
+		assert_eq!(first.execution_weight, None);
+		assert_eq!(first.execution_fee, None);
+		assert_eq!(first.forwarded_xcms[0].hop, Some(1));
+		assert_eq!(
+			first.forwarded_xcms[0].delivery_fees,
+			Some(Assets::from((Parent, 42u128)).into())
+		);
+
+		let second = trace.hops[1].result.as_ref().unwrap();
+		assert_eq!(second.events, vec![para(1000).encode()]);
+		assert_eq!(second.execution_weight, Some(Weight::from_parts(1_000, 10)));
+		assert_eq!(second.execution_fee, Some((AssetId(Location::parent()).into(), 1_000)));
+		assert_eq!(second.forwarded_xcms[0].hop, Some(2));
+	}
+
+	#[tokio::test]
+	async fn reports_failures_and_unknown_chains() {
+		let dry_run = MultiHopDryRun::new()
+			.with_chain(Arc::new(
+				MockChain::new(para(2000))
+					.forwarding(Location::new(1, [Parachain(1000)]), reserve_transfer()),
+			))
+			.with_chain(Arc::new(
+				MockChain::new(para(1000))
+					.forwarding(Location::new(1, [Parachain(2001)]), reserve_transfer())
+					.failing(),
+			));
+
+		let trace = dry_run
+			.dry_run_xcm(&para(2000), Location::parent().into(), reserve_transfer().into())
+			.await;
+
+		assert!(!trace.is_success());
+		assert_eq!(trace.first_failure().map(|hop| hop.chain.clone()), Some(para(1000)));
+		assert_eq!(trace.hops.len(), 3);
+		assert_eq!(trace.hops[2].result, Err(Error::UnknownChain(para(2001))));
+	}
+
+	#[tokio::test]
+	async fn stops_after_max_hops() {
+		// Two chains that keep sending messages to each other.
+		let dry_run = MultiHopDryRun::new()
+			.with_chain(Arc::new(
+				MockChain::new(para(2000))
+					.forwarding(Location::new(1, [Parachain(1000)]), reserve_transfer()),
+			))
+			.with_chain(Arc::new(
+				MockChain::new(para(1000))
+					.forwarding(Location::new(1, [Parachain(2000)]), reserve_transfer()),
+			))
+			.with_max_hops(4);
+
+		let trace = dry_run.dry_run_call(&para(2000), &[], &[]).await;
+
+		assert_eq!(trace.hops.len(), 5);
+		assert_eq!(trace.hops[4].result, Err(Error::TooManyHops));
+		assert_eq!(trace.first_failure().map(|hop| hop.parent), Some(Some(3)));
+	}
+}
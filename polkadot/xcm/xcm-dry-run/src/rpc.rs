@@ -0,0 +1,179 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! RPC interface for the multi-hop dry-run.
+
+use crate::{DryRunChain, LocalCaller, MultiHopDryRun, RuntimeApiChain, DEFAULT_MAX_HOPS};
+use codec::{Decode, Encode};
+use jsonrpsee::{
+	core::{async_trait, RpcResult},
+	proc_macros::rpc,
+	types::error::ErrorObject,
+	Extensions,
+};
+use sc_client_api::{ExecutorProvider, HeaderBackend};
+use sc_rpc_api::check_if_safe;
+use sp_core::Bytes;
+use sp_runtime::traits::Block as BlockT;
+use std::{marker::PhantomData, sync::Arc};
+use xcm::{latest::InteriorLocation, VersionedLocation, VersionedXcm};
+
+/// Multi-hop dry-run RPC methods.
+///
+/// Both methods return the SCALE encoded [`DryRunTrace`](crate::DryRunTrace). They are unsafe,
+/// since a single request can execute up to `max_hops` runtime calls, some of them on remote
+/// nodes.
+#[rpc(client, server)]
+pub trait XcmDryRunApi<BlockHash> {
+	/// Dry-run the SCALE encoded `call` dispatched by the SCALE encoded `origin` on this chain, and
+	/// all messages it sends.
+	#[method(name = "xcm_dryRunCall", with_extensions)]
+	async fn dry_run_call(
+		&self,
+		origin: Bytes,
+		call: Bytes,
+		at: Option<BlockHash>,
+	) -> RpcResult<Bytes>;
+
+	/// Dry-run the SCALE encoded `VersionedXcm` sent by the SCALE encoded `VersionedLocation`
+	/// `origin` on this chain, and all messages it sends.
+	#[method(name = "xcm_dryRunXcm", with_extensions)]
+	async fn dry_run_xcm(
+		&self,
+		origin: Bytes,
+		xcm: Bytes,
+		at: Option<BlockHash>,
+	) -> RpcResult<Bytes>;
+}
+
+/// Error type of this RPC api.
+pub enum Error {
+	/// The origin or message was not decodable.
+	DecodeError,
+}
+
+impl From<Error> for i32 {
+	fn from(e: Error) -> i32 {
+		match e {
+			Error::DecodeError => 1,
+		}
+	}
+}
+
+/// Implements the [`XcmDryRunApiServer`] RPC trait.
+///
+/// Messages sent by this chain are dry-run on the configured other chains, usually
+/// [`RuntimeApiChain`]s using a [`RemoteCaller`](crate::RemoteCaller).
+///
+/// Every request is dry-run on the best block of this chain, or the requested one. The other
+/// chains are called at the block they were configured with.
+pub struct XcmDryRun<Block, Client, Event> {
+	client: Arc<Client>,
+	universal_location: InteriorLocation,
+	chains: Vec<Arc<dyn DryRunChain>>,
+	max_hops: u32,
+	_marker: PhantomData<fn() -> (Block, Event)>,
+}
+
+impl<Block, Client, Event> XcmDryRun<Block, Client, Event>
+where
+	Block: BlockT,
+	Client: HeaderBackend<Block> + ExecutorProvider<Block> + Send + Sync + 'static,
+	Event: Encode + Decode + Send + 'static,
+{
+	/// Create a new instance for this chain at `universal_location`.
+	pub fn new(client: Arc<Client>, universal_location: InteriorLocation) -> Self {
+		Self {
+			client,
+			universal_location,
+			chains: Vec::new(),
+			max_hops: DEFAULT_MAX_HOPS,
+			_marker: PhantomData,
+		}
+	}
+
+	/// Add another chain that messages can be dry-run on.
+	pub fn with_chain(mut self, chain: Arc<dyn DryRunChain>) -> Self {
+		self.chains.push(chain);
+		self
+	}
+
+	/// Set the maximum number of hops that are dry-run.
+	pub fn with_max_hops(mut self, max_hops: u32) -> Self {
+		self.max_hops = max_hops;
+		self
+	}
+
+	fn multi_hop(&self, at: Option<Block::Hash>) -> MultiHopDryRun {
+		let at = at.unwrap_or_else(|| self.client.info().best_hash);
+		let local = RuntimeApiChain::<Event>::new(
+			self.universal_location.clone(),
+			LocalCaller::<Block, _>::new(self.client.clone(), at),
+		);
+
+		self.chains.iter().cloned().fold(
+			MultiHopDryRun::new().with_max_hops(self.max_hops).with_chain(Arc::new(local)),
+			MultiHopDryRun::with_chain,
+		)
+	}
+}
+
+fn decode<T: Decode>(encoded: &Bytes, what: &'static str) -> RpcResult<T> {
+	T::decode(&mut &encoded[..]).map_err(|e| {
+		ErrorObject::owned(
+			Error::DecodeError.into(),
+			format!("Unable to decode {what}."),
+			Some(format!("{:?}", e)),
+		)
+	})
+}
+
+#[async_trait]
+impl<Block, Client, Event> XcmDryRunApiServer<Block::Hash> for XcmDryRun<Block, Client, Event>
+where
+	Block: BlockT,
+	Client: HeaderBackend<Block> + ExecutorProvider<Block> + Send + Sync + 'static,
+	Event: Encode + Decode + Send + 'static,
+{
+	async fn dry_run_call(
+		&self,
+		ext: &Extensions,
+		origin: Bytes,
+		call: Bytes,
+		at: Option<Block::Hash>,
+	) -> RpcResult<Bytes> {
+		check_if_safe(ext)?;
+
+		let trace = self.multi_hop(at).dry_run_call(&self.universal_location, &origin, &call).await;
+		Ok(trace.encode().into())
+	}
+
+	async fn dry_run_xcm(
+		&self,
+		ext: &Extensions,
+		origin: Bytes,
+		xcm: Bytes,
+		at: Option<Block::Hash>,
+	) -> RpcResult<Bytes> {
+		check_if_safe(ext)?;
+
+		let origin: VersionedLocation = decode(&origin, "origin")?;
+		let xcm: VersionedXcm<()> = decode(&xcm, "message")?;
+
+		let trace = self.multi_hop(at).dry_run_xcm(&self.universal_location, origin, xcm).await;
+		Ok(trace.encode().into())
+	}
+}
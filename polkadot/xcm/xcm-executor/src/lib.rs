@@ -42,6 +42,9 @@ use traits::{
 
 pub use traits::RecordXcm;
 
+pub mod trace;
+use trace::TraceEntry;
+
 mod assets;
 pub use assets::AssetsInHolding;
 mod config;
@@ -64,6 +67,10 @@ const RECURSION_LIMIT: u8 = 10;
 
 environmental::environmental!(recursion_count: u8);
 
+/// The asset transactor of `Config`, recording its calls while tracing.
+type AssetTransactor<Config> =
+	trace::TracedAssetTransactor<<Config as config::Config>::AssetTransactor>;
+
 /// The XCM executor.
 pub struct XcmExecutor<Config: config::Config> {
 	holding: AssetsInHolding,
@@ -290,7 +297,7 @@ impl<Config: config::Config> ExecuteXcm<Config::RuntimeCall> for XcmExecutor<Con
 		let origin = origin.into();
 		if !Config::FeeManager::is_waived(Some(&origin), FeeReason::ChargeFees) {
 			for asset in fees.inner() {
-				Config::AssetTransactor::withdraw_asset(&asset, &origin, None)?;
+				AssetTransactor::<Config>::withdraw_asset(&asset, &origin, None)?;
 			}
 			Config::FeeManager::handle_fee(fees.into(), None, FeeReason::ChargeFees);
 		}
@@ -538,7 +545,7 @@ impl<Config: config::Config> XcmExecutor<Config> {
 		// We withdraw or take from holding the asset the user wants to use for fee payment.
 		let withdrawn_fee_asset: AssetsInHolding = if self.fees_mode.jit_withdraw {
 			let origin = self.origin_ref().ok_or(XcmError::BadOrigin)?;
-			Config::AssetTransactor::withdraw_asset(
+			AssetTransactor::<Config>::withdraw_asset(
 				&asset_to_pay_for_fees,
 				origin,
 				Some(&self.context),
@@ -730,10 +737,10 @@ impl<Config: config::Config> XcmExecutor<Config> {
 			// this to be in error, there would need to be an accounting violation
 			// by ourselves, so it's unlikely, but we don't want to allow that kind
 			// of bug to leak into a trusted chain.
-			Config::AssetTransactor::can_check_out(dest, &asset, context)?;
+			AssetTransactor::<Config>::can_check_out(dest, &asset, context)?;
 		}
 		for asset in assets.assets_iter() {
-			Config::AssetTransactor::check_out(dest, &asset, context);
+			AssetTransactor::<Config>::check_out(dest, &asset, context);
 		}
 		// Note that we pass `None` as `maybe_failed_bin` and drop any assets which
 		// cannot be reanchored, because we have already checked all assets out.
@@ -777,6 +784,40 @@ impl<Config: config::Config> XcmExecutor<Config> {
 		self.post_process(xcm_weight)
 	}
 
+	/// Start the trace entry of the instruction at `index`, if tracing is enabled.
+	fn trace_instruction_start(
+		&self,
+		index: usize,
+		instr: &Instruction<Config::RuntimeCall>,
+	) -> Option<usize> {
+		trace::start(|| TraceEntry {
+			depth: recursion_count::with(|count| count.saturating_sub(1) as u32).unwrap_or(0),
+			index: index as u32,
+			instruction: instr.clone().into(),
+			origin_before: self.context.origin.clone(),
+			origin_after: None,
+			holding_before: self.holding.clone().into(),
+			holding_after: Assets::new(),
+			fees_before: self.fees.clone().into(),
+			fees_after: Assets::new(),
+			weight: Config::Weigher::instr_weight(&mut instr.clone()).unwrap_or_default(),
+			asset_transactor_calls: Vec::new(),
+			result: Ok(()),
+		})
+	}
+
+	/// Complete the trace `entry` of an instruction that finished with `result`.
+	fn trace_instruction_finish(&self, entry: usize, surplus_before: Weight, result: &XcmResult) {
+		let surplus = self.total_surplus.saturating_sub(surplus_before);
+		trace::finish(entry, |entry| {
+			entry.origin_after = self.context.origin.clone();
+			entry.holding_after = self.holding.clone().into();
+			entry.fees_after = self.fees.clone().into();
+			entry.weight = entry.weight.saturating_sub(surplus);
+			entry.result = *result;
+		});
+	}
+
 	fn process(&mut self, xcm: Xcm<Config::RuntimeCall>) -> Result<(), ExecutorError> {
 		tracing::trace!(
 			target: "xcm::process",
@@ -789,6 +830,8 @@ impl<Config: config::Config> XcmExecutor<Config> {
 		for (i, mut instr) in xcm.0.into_iter().enumerate() {
 			match &mut result {
 				r @ Ok(()) => {
+					let trace_entry = self.trace_instruction_start(i, &instr);
+					let surplus_before = self.total_surplus;
 					// Initialize the recursion count only the first time we hit this code in our
 					// potential recursive execution.
 					let inst_res = recursion_count::using_once(&mut 1, || {
@@ -812,6 +855,9 @@ impl<Config: config::Config> XcmExecutor<Config> {
 
 						self.process_instruction(instr)
 					});
+					if let Some(entry) = trace_entry {
+						self.trace_instruction_finish(entry, surplus_before, &inst_res);
+					}
 					if let Err(e) = inst_res {
 						tracing::trace!(target: "xcm::execute", "!!! ERROR: {:?}", e);
 						*r = Err(ExecutorError {
@@ -848,7 +894,7 @@ impl<Config: config::Config> XcmExecutor<Config> {
 				Config::TransactionalProcessor::process(|| {
 					// Take `assets` from the origin account (on-chain)...
 					for asset in assets.inner() {
-						Config::AssetTransactor::withdraw_asset(
+						AssetTransactor::<Config>::withdraw_asset(
 							asset,
 							origin,
 							Some(&self.context),
@@ -881,7 +927,7 @@ impl<Config: config::Config> XcmExecutor<Config> {
 					// Take `assets` from the origin account (on-chain) and place into dest account.
 					let origin = self.origin_ref().ok_or(XcmError::BadOrigin)?;
 					for asset in assets.inner() {
-						Config::AssetTransactor::transfer_asset(
+						AssetTransactor::<Config>::transfer_asset(
 							&asset,
 							origin,
 							&beneficiary,
@@ -896,7 +942,7 @@ impl<Config: config::Config> XcmExecutor<Config> {
 					let origin = self.origin_ref().ok_or(XcmError::BadOrigin)?;
 					// Take `assets` from the origin account (on-chain) and place into dest account.
 					for asset in assets.inner() {
-						Config::AssetTransactor::transfer_asset(
+						AssetTransactor::<Config>::transfer_asset(
 							asset,
 							origin,
 							&dest,
@@ -929,8 +975,8 @@ impl<Config: config::Config> XcmExecutor<Config> {
 						// be in error, there would need to be an accounting violation by one of the
 						// trusted chains, so it's unlikely, but we don't want to punish a possibly
 						// innocent chain/user).
-						Config::AssetTransactor::can_check_in(origin, asset, &self.context)?;
-						Config::AssetTransactor::check_in(origin, asset, &self.context);
+						AssetTransactor::<Config>::can_check_in(origin, asset, &self.context)?;
+						AssetTransactor::<Config>::check_in(origin, asset, &self.context);
 					}
 					Ok(())
 				})
@@ -1691,7 +1737,8 @@ impl<Config: config::Config> XcmExecutor<Config> {
 
 		let mut deposit_result = Ok(());
 		for asset in to_deposit.assets_iter() {
-			deposit_result = Config::AssetTransactor::deposit_asset(&asset, &beneficiary, context);
+			deposit_result =
+				AssetTransactor::<Config>::deposit_asset(&asset, &beneficiary, context);
 			// if deposit failed for asset, mark it for retry after depositing the others.
 			if deposit_result.is_err() {
 				failed_deposits.push(asset);
@@ -1709,7 +1756,7 @@ impl<Config: config::Config> XcmExecutor<Config> {
 
 		// retry previously failed deposits, this time short-circuiting on any error.
 		for asset in failed_deposits {
-			Config::AssetTransactor::deposit_asset(&asset, &beneficiary, context)?;
+			AssetTransactor::<Config>::deposit_asset(&asset, &beneficiary, context)?;
 		}
 		Ok(())
 	}
@@ -0,0 +1,199 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Structured execution trace of XCM programs.
+//!
+//! Tracing is enabled for everything executed within [`with_trace`], including programs executed
+//! by nested executors. It is meant for dry-running and tests, since it clones the registers of
+//! the executor before and after every instruction.
+
+use crate::{traits::TransactAsset, AssetsInHolding};
+use alloc::vec::Vec;
+use codec::{Decode, Encode};
+use core::marker::PhantomData;
+use scale_info::TypeInfo;
+use sp_weights::Weight;
+use xcm::latest::prelude::*;
+
+/// A call into the asset transactor which moved assets.
+#[derive(Clone, Eq, PartialEq, Debug, Encode, Decode, TypeInfo)]
+pub enum AssetTransactorCall {
+	/// `what` was deposited into `who`.
+	Deposit { what: Asset, who: Location },
+	/// `what` was withdrawn from `who`.
+	Withdraw { what: Asset, who: Location },
+	/// `asset` was transferred from `from` to `to`.
+	Transfer { asset: Asset, from: Location, to: Location },
+	/// `what` was checked in from `origin`.
+	CheckIn { origin: Location, what: Asset },
+	/// `what` was checked out to `dest`.
+	CheckOut { dest: Location, what: Asset },
+}
+
+/// The record of executing a single instruction.
+#[derive(Clone, Eq, PartialEq, Debug, Encode, Decode, TypeInfo)]
+pub struct TraceEntry {
+	/// The nesting depth of the executed program, `0` for programs that are not executed by
+	/// another instruction.
+	pub depth: u32,
+	/// The index of the instruction in its program.
+	pub index: u32,
+	/// The executed instruction.
+	pub instruction: Instruction<()>,
+	/// The origin register before executing the instruction.
+	pub origin_before: Option<Location>,
+	/// The origin register after executing the instruction.
+	pub origin_after: Option<Location>,
+	/// The holding register before executing the instruction.
+	pub holding_before: Assets,
+	/// The holding register after executing the instruction.
+	pub holding_after: Assets,
+	/// The fees register before executing the instruction.
+	pub fees_before: Assets,
+	/// The fees register after executing the instruction.
+	pub fees_after: Assets,
+	/// The weight consumed by the instruction, which is its estimated weight minus the surplus
+	/// it reported.
+	pub weight: Weight,
+	/// The calls into the asset transactor made by the instruction, with their results.
+	pub asset_transactor_calls: Vec<(AssetTransactorCall, XcmResult)>,
+	/// The result of the instruction.
+	pub result: XcmResult,
+}
+
+#[derive(Default)]
+struct Tracer {
+	entries: Vec<TraceEntry>,
+	/// Indices of the entries of the instructions that are currently executing.
+	executing: Vec<usize>,
+}
+
+environmental::environmental!(tracer: Tracer);
+
+/// Execute `f` with tracing enabled and return its result together with the trace of all
+/// instructions it executed, in the order they started executing.
+pub fn with_trace<R>(f: impl FnOnce() -> R) -> (R, Vec<TraceEntry>) {
+	let mut trace = Tracer::default();
+	let result = tracer::using(&mut trace, f);
+	(result, trace.entries)
+}
+
+/// Whether tracing is enabled.
+pub fn is_tracing() -> bool {
+	tracer::with(|_| ()).is_some()
+}
+
+/// Record the start of executing an instruction, if tracing is enabled.
+///
+/// Returns the index of the entry, which needs to be passed to [`finish`].
+pub(crate) fn start(entry: impl FnOnce() -> TraceEntry) -> Option<usize> {
+	tracer::with(|trace| {
+		let index = trace.entries.len();
+		trace.entries.push(entry());
+		trace.executing.push(index);
+		index
+	})
+}
+
+/// Record the end of executing the instruction of entry `index`.
+pub(crate) fn finish(index: usize, complete: impl FnOnce(&mut TraceEntry)) {
+	tracer::with(|trace| {
+		trace.executing.retain(|executing| *executing != index);
+		if let Some(entry) = trace.entries.get_mut(index) {
+			complete(entry);
+		}
+	});
+}
+
+fn record(call: impl FnOnce() -> AssetTransactorCall, result: XcmResult) {
+	tracer::with(|trace| {
+		if let Some(&index) = trace.executing.last() {
+			trace.entries[index].asset_transactor_calls.push((call(), result));
+		}
+	});
+}
+
+/// Records the calls into the asset transactor `T` which move assets.
+pub(crate) struct TracedAssetTransactor<T>(PhantomData<T>);
+
+impl<T: TransactAsset> TransactAsset for TracedAssetTransactor<T> {
+	fn can_check_in(origin: &Location, what: &Asset, context: &XcmContext) -> XcmResult {
+		T::can_check_in(origin, what, context)
+	}
+
+	fn check_in(origin: &Location, what: &Asset, context: &XcmContext) {
+		T::check_in(origin, what, context);
+		record(
+			|| AssetTransactorCall::CheckIn { origin: origin.clone(), what: what.clone() },
+			Ok(()),
+		);
+	}
+
+	fn can_check_out(dest: &Location, what: &Asset, context: &XcmContext) -> XcmResult {
+		T::can_check_out(dest, what, context)
+	}
+
+	fn check_out(dest: &Location, what: &Asset, context: &XcmContext) {
+		T::check_out(dest, what, context);
+		record(|| AssetTransactorCall::CheckOut { dest: dest.clone(), what: what.clone() }, Ok(()));
+	}
+
+	fn deposit_asset(what: &Asset, who: &Location, context: Option<&XcmContext>) -> XcmResult {
+		let result = T::deposit_asset(what, who, context);
+		record(|| AssetTransactorCall::Deposit { what: what.clone(), who: who.clone() }, result);
+		result
+	}
+
+	fn withdraw_asset(
+		what: &Asset,
+		who: &Location,
+		maybe_context: Option<&XcmContext>,
+	) -> Result<AssetsInHolding, XcmError> {
+		let result = T::withdraw_asset(what, who, maybe_context);
+		record(
+			|| AssetTransactorCall::Withdraw { what: what.clone(), who: who.clone() },
+			result.as_ref().map(|_| ()).map_err(|e| *e),
+		);
+		result
+	}
+
+	fn internal_transfer_asset(
+		asset: &Asset,
+		from: &Location,
+		to: &Location,
+		context: &XcmContext,
+	) -> Result<AssetsInHolding, XcmError> {
+		T::internal_transfer_asset(asset, from, to, context)
+	}
+
+	fn transfer_asset(
+		asset: &Asset,
+		from: &Location,
+		to: &Location,
+		context: &XcmContext,
+	) -> Result<AssetsInHolding, XcmError> {
+		let result = T::transfer_asset(asset, from, to, context);
+		record(
+			|| AssetTransactorCall::Transfer {
+				asset: asset.clone(),
+				from: from.clone(),
+				to: to.clone(),
+			},
+			result.as_ref().map(|_| ()).map_err(|e| *e),
+		);
+		result
+	}
+}
@@ -127,3 +127,31 @@ impl CheckSuspension for Tuple {
 		false
 	}
 }
+
+/// Trait to limit the rate at which origins can execute XCMs.
+///
+/// Implemented by a budget per origin which is replenished over time, see
+/// `xcm_builder::RateLimitedMessageProcessor` for enforcing it when processing queued messages.
+pub trait RateLimit {
+	/// Whether `origin` has budget left to execute another XCM.
+	fn can_execute(origin: &Location) -> bool;
+
+	/// Charge the budget of `origin` for one XCM which used `weight`.
+	fn note_executed(origin: &Location, weight: Weight);
+
+	/// The weight of checking the budget of an origin with [`Self::can_execute`] and charging it
+	/// with [`Self::note_executed`].
+	fn weight() -> Weight;
+}
+
+impl RateLimit for () {
+	fn can_execute(_: &Location) -> bool {
+		true
+	}
+
+	fn weight() -> Weight {
+		Weight::zero()
+	}
+
+	fn note_executed(_: &Location, _: Weight) {}
+}
@@ -22,6 +22,7 @@ use alloc::vec::Vec;
 use codec::{Decode, Encode};
 use frame_support::pallet_prelude::{DispatchResultWithPostInfo, TypeInfo};
 use xcm::prelude::*;
+use xcm_executor::trace::TraceEntry;
 
 /// Effects of dry-running an extrinsic.
 #[derive(Encode, Decode, Debug, TypeInfo)]
@@ -47,6 +48,15 @@ pub struct XcmDryRunEffects<Event> {
 	pub forwarded_xcms: Vec<(VersionedLocation, Vec<VersionedXcm<()>>)>,
 }
 
+/// Effects of a dry-run, together with the trace of all executed XCM instructions.
+#[derive(Encode, Decode, Debug, TypeInfo)]
+pub struct TracedEffects<Effects> {
+	/// The effects of the dry-run.
+	pub effects: Effects,
+	/// The executed instructions, in the order they started executing.
+	pub trace: Vec<TraceEntry>,
+}
+
 sp_api::decl_runtime_apis! {
 	/// API for dry-running extrinsics and XCM programs to get the programs that need to be passed to the fees API.
 	///
@@ -68,6 +78,14 @@ sp_api::decl_runtime_apis! {
 
 		/// Dry run XCM program
 		fn dry_run_xcm(origin_location: VersionedLocation, xcm: VersionedXcm<Call>) -> Result<XcmDryRunEffects<Event>, Error>;
+
+		/// Dry run call, recording a trace of all XCM instructions it executes.
+		#[api_version(2)]
+		fn dry_run_call_with_trace(origin: OriginCaller, call: Call) -> Result<TracedEffects<CallDryRunEffects<Event>>, Error>;
+
+		/// Dry run XCM program, recording a trace of all instructions it executes.
+		#[api_version(2)]
+		fn dry_run_xcm_with_trace(origin_location: VersionedLocation, xcm: VersionedXcm<Call>) -> Result<TracedEffects<XcmDryRunEffects<Event>>, Error>;
 	}
 }
 
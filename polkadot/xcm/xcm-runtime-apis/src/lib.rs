@@ -34,3 +34,7 @@ pub mod fees;
 // Exposes runtime API for querying whether a Location is trusted as a reserve or teleporter for a
 // given Asset.
 pub mod trusted_query;
+
+/// XCM version negotiation API.
+/// Lists the locations known to the runtime with their negotiated XCM versions.
+pub mod versions;
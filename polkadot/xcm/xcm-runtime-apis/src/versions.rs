@@ -0,0 +1,89 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Runtime API definition for inspecting the XCM version negotiation with other locations.
+
+use alloc::vec::Vec;
+use codec::{Decode, Encode};
+use frame_support::pallet_prelude::TypeInfo;
+use sp_weights::Weight;
+use xcm::{latest::QueryId, Version, VersionedLocation};
+
+/// Our subscription to the XCM version of a location.
+#[derive(Copy, Clone, Encode, Decode, Eq, PartialEq, Debug, TypeInfo)]
+pub struct VersionNotifier {
+	/// The id of the query the location responds to.
+	pub query_id: QueryId,
+	/// Whether the location responded to the subscription. Until it does, the subscription is
+	/// pending.
+	pub is_active: bool,
+}
+
+/// The subscription of a location to our XCM version.
+#[derive(Copy, Clone, Encode, Decode, Eq, PartialEq, Debug, TypeInfo)]
+pub struct VersionNotifyTarget {
+	/// The id of the query the location expects responses to.
+	pub query_id: QueryId,
+	/// The maximum weight of the responses.
+	pub max_weight: Weight,
+	/// The XCM version last notified to the location.
+	pub notified_version: Version,
+	/// Whether our XCM version changed since the location was last notified, so that a
+	/// notification is pending.
+	pub is_pending: bool,
+}
+
+/// What is known about the XCM version of a location.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, Debug, TypeInfo)]
+pub struct DestinationVersion {
+	/// The location.
+	pub location: VersionedLocation,
+	/// The latest XCM version the location is known to support, if any.
+	pub supported_version: Option<Version>,
+	/// Our subscription to the XCM version of the location, if any.
+	pub notifier: Option<VersionNotifier>,
+	/// The subscription of the location to our XCM version, if any.
+	pub notify_target: Option<VersionNotifyTarget>,
+	/// The number of messages sent to the location while its version was unknown, if it is
+	/// queued for version discovery.
+	pub discovery_attempts: Option<u32>,
+	/// Whether some of the above is stored for an older XCM version and is yet to be migrated.
+	pub pending_migration: bool,
+}
+
+sp_api::decl_runtime_apis! {
+	/// API for inspecting the XCM version negotiation with other locations.
+	pub trait XcmVersionsApi {
+		/// Returns all locations which have a known XCM version, a version subscription in either
+		/// direction or which are queued for version discovery.
+		fn known_destinations() -> Vec<DestinationVersion>;
+
+		/// Returns what is known about the XCM version of a location.
+		///
+		/// # Arguments
+		/// * `location`: `VersionedLocation`.
+		fn destination_version(location: VersionedLocation) -> Result<DestinationVersion, Error>;
+
+		/// Returns the XCM version used for locations whose version is unknown, if any.
+		fn safe_xcm_version() -> Option<Version>;
+	}
+}
+
+#[derive(Copy, Clone, Encode, Decode, Eq, PartialEq, Debug, TypeInfo)]
+pub enum Error {
+	/// Converting a versioned Location structure from one version to another failed.
+	VersionedLocationConversionFailed,
+}
@@ -20,13 +20,14 @@ use frame_support::sp_runtime::testing::H256;
 use frame_system::RawOrigin;
 use sp_api::ProvideRuntimeApi;
 use xcm::prelude::*;
+use xcm_executor::trace::AssetTransactorCall;
 use xcm_runtime_apis::{dry_run::DryRunApi, fees::XcmPaymentApi};
 
 mod mock;
 use mock::{
 	fake_message_hash, new_test_ext_with_balances, new_test_ext_with_balances_and_assets,
-	DeliveryFees, ExistentialDeposit, HereLocation, OriginCaller, RuntimeCall, RuntimeEvent,
-	TestClient,
+	BaseXcmWeight, DeliveryFees, ExistentialDeposit, HereLocation, OriginCaller, RuntimeCall,
+	RuntimeEvent, TestClient,
 };
 
 // Scenario: User `1` in the local chain (id 2000) wants to transfer assets to account `[0u8; 32]`
@@ -354,6 +355,77 @@ fn dry_run_xcm() {
 	});
 }
 
+#[test]
+fn dry_run_xcm_with_trace() {
+	sp_tracing::init_for_tests();
+	let who = 1; // AccountId = u64.
+	let origin = Location::new(0, [AccountIndex64 { index: who, network: None }]);
+	let beneficiary = Location::new(0, [AccountIndex64 { index: 2, network: None }]);
+	let amount = 100u128;
+	let program = |amount: u128| {
+		Xcm::<RuntimeCall>::builder_unsafe()
+			.withdraw_asset((Here, amount))
+			.clear_origin()
+			.buy_execution((Here, amount), Unlimited)
+			.deposit_asset(AllCounted(1), beneficiary.clone())
+			.build()
+	};
+	let client = TestClient;
+	let runtime_api = client.runtime_api();
+	let balances = vec![(who, amount + ExistentialDeposit::get())];
+	new_test_ext_with_balances(balances).execute_with(|| {
+		let traced = runtime_api
+			.dry_run_xcm_with_trace(
+				H256::zero(),
+				origin.clone().into(),
+				VersionedXcm::from(program(amount)),
+			)
+			.unwrap()
+			.unwrap();
+		assert!(matches!(traced.effects.execution_result, Outcome::Complete { .. }));
+
+		let trace = traced.trace;
+		assert_eq!(trace.iter().map(|entry| entry.index).collect::<Vec<_>>(), vec![0, 1, 2, 3]);
+		assert!(trace.iter().all(|entry| entry.depth == 0 && entry.result.is_ok()));
+		assert!(trace.iter().all(|entry| entry.weight == BaseXcmWeight::get()));
+		// `WithdrawAsset` moves the assets of the origin into holding.
+		assert_eq!(trace[0].holding_before, Assets::new());
+		assert_eq!(trace[0].holding_after, (Here, amount).into());
+		assert_eq!(
+			trace[0].asset_transactor_calls,
+			vec![(
+				AssetTransactorCall::Withdraw { what: (Here, amount).into(), who: origin.clone() },
+				Ok(())
+			)]
+		);
+		// `ClearOrigin` clears the origin.
+		assert_eq!(trace[1].origin_before, Some(origin.clone()));
+		assert_eq!(trace[1].origin_after, None);
+		// `DepositAsset` deposits what is left in holding to the beneficiary.
+		assert_eq!(trace[3].holding_before, trace[2].holding_after);
+		assert_eq!(trace[3].holding_after, Assets::new());
+		assert!(matches!(
+			&trace[3].asset_transactor_calls[..],
+			[(AssetTransactorCall::Deposit { who, .. }, Ok(()))] if *who == beneficiary
+		));
+
+		// Withdrawing more than the origin owns fails on the first instruction.
+		let traced = runtime_api
+			.dry_run_xcm_with_trace(
+				H256::zero(),
+				origin.clone().into(),
+				VersionedXcm::from(program(amount * 2)),
+			)
+			.unwrap()
+			.unwrap();
+		assert!(matches!(traced.effects.execution_result, Outcome::Incomplete { .. }));
+		assert_eq!(traced.trace.len(), 1);
+		assert!(traced.trace[0].result.is_err());
+		assert_eq!(traced.trace[0].holding_after, Assets::new());
+		assert!(traced.trace[0].asset_transactor_calls[0].1.is_err());
+	});
+}
+
 #[test]
 fn calling_payment_api_with_a_lower_version_works() {
 	let transfer_amount = 100u128;
@@ -46,7 +46,9 @@ use xcm_executor::{
 
 use xcm_runtime_apis::{
 	conversions::{Error as LocationToAccountApiError, LocationToAccountApi},
-	dry_run::{CallDryRunEffects, DryRunApi, Error as XcmDryRunApiError, XcmDryRunEffects},
+	dry_run::{
+		CallDryRunEffects, DryRunApi, Error as XcmDryRunApiError, TracedEffects, XcmDryRunEffects,
+	},
 	fees::{Error as XcmPaymentApiError, XcmPaymentApi},
 	trusted_query::{Error as TrustedQueryApiError, TrustedQueryApi},
 };
@@ -482,63 +484,85 @@ sp_api::mock_impl_runtime_apis! {
 
 	impl DryRunApi<Block, RuntimeCall, RuntimeEvent, OriginCaller> for RuntimeApi {
 		fn dry_run_call(origin: OriginCaller, call: RuntimeCall) -> Result<CallDryRunEffects<RuntimeEvent>, XcmDryRunApiError> {
-			use xcm_executor::RecordXcm;
-			pallet_xcm::Pallet::<TestRuntime>::set_record_xcm(true);
-			let result = call.dispatch(origin.into());
-			pallet_xcm::Pallet::<TestRuntime>::set_record_xcm(false);
-			let local_xcm = pallet_xcm::Pallet::<TestRuntime>::recorded_xcm();
-			let forwarded_xcms = sent_xcm()
-							   .into_iter()
-							   .map(|(location, message)| (
-									   VersionedLocation::from(location),
-									   vec![VersionedXcm::from(message)],
-							   )).collect();
-			let events: Vec<RuntimeEvent> = System::read_events_no_consensus().map(|record| record.event.clone()).collect();
-			Ok(CallDryRunEffects {
-				local_xcm: local_xcm.map(VersionedXcm::<()>::from),
-				forwarded_xcms,
-				emitted_events: events,
-				execution_result: result,
-			})
+			dry_run_call(origin, call)
 		}
 
 		fn dry_run_xcm(origin_location: VersionedLocation, xcm: VersionedXcm<RuntimeCall>) -> Result<XcmDryRunEffects<RuntimeEvent>, XcmDryRunApiError> {
-			let origin_location: Location = origin_location.try_into().map_err(|error| {
-				log::error!(
-					target: "xcm::DryRunApi::dry_run_xcm",
-					"Location version conversion failed with error: {:?}",
-					error,
-				);
-				XcmDryRunApiError::VersionedConversionFailed
-			})?;
-			let xcm: Xcm<RuntimeCall> = xcm.try_into().map_err(|error| {
-				log::error!(
-					target: "xcm::DryRunApi::dry_run_xcm",
-					"Xcm version conversion failed with error {:?}",
-					error,
-				);
-				XcmDryRunApiError::VersionedConversionFailed
-			})?;
-			let mut hash = fake_message_hash(&xcm);
-			let result = XcmExecutor::<XcmConfig>::prepare_and_execute(
-				origin_location,
-				xcm,
-				&mut hash,
-				Weight::MAX, // Max limit available for execution.
-				Weight::zero(),
-			);
-			let forwarded_xcms = sent_xcm()
-				.into_iter()
-				.map(|(location, message)| (
-					VersionedLocation::from(location),
-					vec![VersionedXcm::from(message)],
-				)).collect();
-			let events: Vec<RuntimeEvent> = System::events().iter().map(|record| record.event.clone()).collect();
-			Ok(XcmDryRunEffects {
-				forwarded_xcms,
-				emitted_events: events,
-				execution_result: result,
-			})
+			dry_run_xcm(origin_location, xcm)
+		}
+
+		fn dry_run_call_with_trace(origin: OriginCaller, call: RuntimeCall) -> Result<TracedEffects<CallDryRunEffects<RuntimeEvent>>, XcmDryRunApiError> {
+			let (effects, trace) = xcm_executor::trace::with_trace(|| dry_run_call(origin, call));
+			Ok(TracedEffects { effects: effects?, trace })
+		}
+
+		fn dry_run_xcm_with_trace(origin_location: VersionedLocation, xcm: VersionedXcm<RuntimeCall>) -> Result<TracedEffects<XcmDryRunEffects<RuntimeEvent>>, XcmDryRunApiError> {
+			let (effects, trace) = xcm_executor::trace::with_trace(|| dry_run_xcm(origin_location, xcm));
+			Ok(TracedEffects { effects: effects?, trace })
 		}
 	}
 }
+
+pub(crate) fn dry_run_call(
+	origin: OriginCaller,
+	call: RuntimeCall,
+) -> Result<CallDryRunEffects<RuntimeEvent>, XcmDryRunApiError> {
+	use xcm_executor::RecordXcm;
+	pallet_xcm::Pallet::<TestRuntime>::set_record_xcm(true);
+	let result = call.dispatch(origin.into());
+	pallet_xcm::Pallet::<TestRuntime>::set_record_xcm(false);
+	let local_xcm = pallet_xcm::Pallet::<TestRuntime>::recorded_xcm();
+	let forwarded_xcms = sent_xcm()
+		.into_iter()
+		.map(|(location, message)| {
+			(VersionedLocation::from(location), vec![VersionedXcm::from(message)])
+		})
+		.collect();
+	let events: Vec<RuntimeEvent> =
+		System::read_events_no_consensus().map(|record| record.event.clone()).collect();
+	Ok(CallDryRunEffects {
+		local_xcm: local_xcm.map(VersionedXcm::<()>::from),
+		forwarded_xcms,
+		emitted_events: events,
+		execution_result: result,
+	})
+}
+
+pub(crate) fn dry_run_xcm(
+	origin_location: VersionedLocation,
+	xcm: VersionedXcm<RuntimeCall>,
+) -> Result<XcmDryRunEffects<RuntimeEvent>, XcmDryRunApiError> {
+	let origin_location: Location = origin_location.try_into().map_err(|error| {
+		log::error!(
+			target: "xcm::DryRunApi::dry_run_xcm",
+			"Location version conversion failed with error: {:?}",
+			error,
+		);
+		XcmDryRunApiError::VersionedConversionFailed
+	})?;
+	let xcm: Xcm<RuntimeCall> = xcm.try_into().map_err(|error| {
+		log::error!(
+			target: "xcm::DryRunApi::dry_run_xcm",
+			"Xcm version conversion failed with error {:?}",
+			error,
+		);
+		XcmDryRunApiError::VersionedConversionFailed
+	})?;
+	let mut hash = fake_message_hash(&xcm);
+	let result = XcmExecutor::<XcmConfig>::prepare_and_execute(
+		origin_location,
+		xcm,
+		&mut hash,
+		Weight::MAX, // Max limit available for execution.
+		Weight::zero(),
+	);
+	let forwarded_xcms = sent_xcm()
+		.into_iter()
+		.map(|(location, message)| {
+			(VersionedLocation::from(location), vec![VersionedXcm::from(message)])
+		})
+		.collect();
+	let events: Vec<RuntimeEvent> =
+		System::events().iter().map(|record| record.event.clone()).collect();
+	Ok(XcmDryRunEffects { forwarded_xcms, emitted_events: events, execution_result: result })
+}
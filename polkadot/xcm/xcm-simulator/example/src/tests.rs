@@ -19,7 +19,7 @@ use crate::*;
 use codec::Encode;
 use frame_support::{assert_ok, weights::Weight};
 use xcm::latest::QueryResponseInfo;
-use xcm_simulator::{mock_message_queue::ReceivedDmp, TestExt};
+use xcm_simulator::{mock_message_queue::ReceivedDmp, AssetTransactorCall, TestExt};
 
 // Helper function for forming buy execution message
 fn buy_execution<C>(fees: impl Into<Asset>) -> Instruction<C> {
@@ -453,6 +453,47 @@ fn withdraw_and_deposit() {
 	});
 }
 
+/// A parachain transfers funds on the relay chain to another parachain account, with tracing
+/// enabled for all chains.
+///
+/// Asserts that the trace records the execution on the relay chain.
+#[test]
+fn withdraw_and_deposit_with_trace() {
+	MockNet::reset();
+
+	let send_amount = 10;
+
+	let ((), trace) = xcm_simulator::with_trace(|| {
+		ParaA::execute_with(|| {
+			let message = Xcm(vec![
+				WithdrawAsset((Here, send_amount).into()),
+				buy_execution((Here, send_amount)),
+				DepositAsset { assets: AllCounted(1).into(), beneficiary: Parachain(2).into() },
+			]);
+			assert_ok!(ParachainPalletXcm::send_xcm(Here, Parent, message.clone()));
+		})
+	});
+
+	assert_eq!(trace.len(), 3);
+	assert!(trace.iter().all(|entry| entry.depth == 0 && entry.result.is_ok()));
+	assert!(trace.iter().all(|entry| entry.origin_after == Some(Parachain(1).into())));
+	assert_eq!(
+		trace[0].asset_transactor_calls,
+		vec![(
+			AssetTransactorCall::Withdraw {
+				what: (Here, send_amount).into(),
+				who: Parachain(1).into()
+			},
+			Ok(())
+		)]
+	);
+	assert!(matches!(
+		&trace[2].asset_transactor_calls[..],
+		[(AssetTransactorCall::Deposit { who, .. }, Ok(()))] if *who == Parachain(2).into()
+	));
+	assert_eq!(trace[2].holding_after, Assets::new());
+}
+
 /// Scenario:
 /// A parachain wants to be notified that a transfer worked correctly.
 /// It sends a `QueryHolding` after the deposit to get notified on success.
@@ -14,10 +14,12 @@
 // You should have received a copy of the GNU General Public License
 // along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
 
+mod invariants;
 mod parachain;
 mod relay_chain;
 
 use codec::DecodeLimit;
+use invariants::{Ledger, Sender};
 use polkadot_core_primitives::AccountId;
 use polkadot_parachain_primitives::primitives::Id as ParaId;
 use sp_runtime::{traits::AccountIdConversion, BuildStorage};
@@ -25,8 +27,8 @@ use xcm_simulator::{decl_test_network, decl_test_parachain, decl_test_relay_chai
 
 #[cfg(feature = "try-runtime")]
 use frame_support::traits::{TryState, TryStateSelect::All};
-use frame_support::{assert_ok, traits::IntegrityTest};
-use xcm::{latest::prelude::*, MAX_XCM_DECODE_DEPTH};
+use frame_support::{assert_ok, traits::IntegrityTest, weights::Weight};
+use xcm::{latest::prelude::*, VersionedXcm, MAX_XCM_DECODE_DEPTH};
 
 use arbitrary::{Arbitrary, Error, Unstructured};
 
@@ -88,6 +90,8 @@ struct XcmMessage {
 	source: u32,
 	// Destination chain
 	destination: u32,
+	// Whether the message is executed by an account on the source chain instead of being sent
+	execute: bool,
 	// XCM message
 	message: Xcm<()>,
 }
@@ -96,11 +100,12 @@ impl<'a> Arbitrary<'a> for XcmMessage {
 	fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self, Error> {
 		let source: u32 = u.arbitrary()?;
 		let destination: u32 = u.arbitrary()?;
+		let execute: bool = u.arbitrary()?;
 		let mut encoded_message: &[u8] = u.arbitrary()?;
 		if let Ok(message) =
 			DecodeLimit::decode_with_depth_limit(MAX_XCM_DECODE_DEPTH, &mut encoded_message)
 		{
-			return Ok(XcmMessage { source, destination, message });
+			return Ok(XcmMessage { source, destination, execute, message });
 		}
 		Err(Error::IncorrectFormat)
 	}
@@ -147,6 +152,9 @@ pub fn relay_ext() -> sp_io::TestExternalities {
 	ext
 }
 
+// The account executing messages on the source chain.
+pub const ALICE: [u8; 32] = [0; 32];
+
 pub type RelayChainPalletXcm = pallet_xcm::Pallet<relay_chain::Runtime>;
 pub type ParachainPalletXcm = pallet_xcm::Pallet<parachain::Runtime>;
 
@@ -165,8 +173,94 @@ fn recursively_matches_blocklisted_messages(message: &Instruction<()>) -> bool {
 	}
 }
 
+// Execute the message as Alice on the source chain.
+fn execute_message(xcm_message: XcmMessage) {
+	#[cfg(not(fuzzing))]
+	{
+		let source_str = match xcm_message.source % 4 {
+			0 => "Relay Chain".to_string(),
+			n => format!("Parachain {n}"),
+		};
+		println!("  executor:    Alice on {}", source_str);
+		println!("  message:     {:?}", xcm_message.message);
+	}
+	if xcm_message.source % 4 == 0 {
+		Relay::execute_with(|| {
+			let _ = RelayChainPalletXcm::execute(
+				relay_chain::RuntimeOrigin::signed(ALICE.into()),
+				Box::new(VersionedXcm::from(xcm_message.message.into())),
+				Weight::MAX,
+			);
+		})
+	} else {
+		// We get the source's execution method
+		let execute_with = match xcm_message.source % 4 {
+			1 => ParaA::execute_with,
+			2 => ParaB::execute_with,
+			_ => ParaC::execute_with,
+		};
+		execute_with(|| {
+			let _ = ParachainPalletXcm::execute(
+				parachain::RuntimeOrigin::signed(ALICE.into()),
+				Box::new(VersionedXcm::from(xcm_message.message.into())),
+				Weight::MAX,
+			);
+		});
+	}
+}
+
+// Send the message from the source chain as the chain itself.
+//
+// Returns the source chain.
+fn send_message(xcm_message: XcmMessage) -> Sender {
+	if xcm_message.source % 4 == 0 {
+		// We get the destination for the message
+		let parachain_id = (xcm_message.destination % 3) + 1;
+		let destination: Location = Parachain(parachain_id).into();
+		#[cfg(not(fuzzing))]
+		{
+			println!("  source:      Relay Chain");
+			println!("  destination: Parachain {parachain_id}");
+			println!("  message:     {:?}", xcm_message.message);
+		}
+		Relay::execute_with(|| {
+			assert_ok!(RelayChainPalletXcm::send_xcm(Here, destination, xcm_message.message));
+		});
+		Sender::Relay
+	} else {
+		// We get the source's execution method
+		let execute_with = match xcm_message.source % 4 {
+			1 => ParaA::execute_with,
+			2 => ParaB::execute_with,
+			_ => ParaC::execute_with,
+		};
+		// We get the destination for the message
+		let destination: Location = match xcm_message.destination % 4 {
+			n @ 1..=3 => (Parent, Parachain(n)).into(),
+			_ => Parent.into(),
+		};
+		#[cfg(not(fuzzing))]
+		{
+			let destination_str = match xcm_message.destination % 4 {
+				n @ 1..=3 => format!("Parachain {n}"),
+				_ => "Relay Chain".to_string(),
+			};
+			println!("  source:      Parachain {}", xcm_message.source % 4);
+			println!("  destination: {}", destination_str);
+			println!("  message:     {:?}", xcm_message.message);
+		}
+		// We execute the message with the appropriate source and destination
+		execute_with(|| {
+			assert_ok!(ParachainPalletXcm::send_xcm(Here, destination, xcm_message.message));
+		});
+		Sender::Para(xcm_message.source % 4)
+	}
+}
+
 fn run_input(xcm_messages: [XcmMessage; 5]) {
 	MockNet::reset();
+	// We track the assets of the network to check that they are conserved
+	let mut ledger = Ledger::new();
 
 	#[cfg(not(fuzzing))]
 	println!();
@@ -177,46 +271,12 @@ fn run_input(xcm_messages: [XcmMessage; 5]) {
 			continue;
 		}
 
-		if xcm_message.source % 4 == 0 {
-			// We get the destination for the message
-			let parachain_id = (xcm_message.destination % 3) + 1;
-			let destination: Location = Parachain(parachain_id).into();
-			#[cfg(not(fuzzing))]
-			{
-				println!("  source:      Relay Chain");
-				println!("  destination: Parachain {parachain_id}");
-				println!("  message:     {:?}", xcm_message.message);
-			}
-			Relay::execute_with(|| {
-				assert_ok!(RelayChainPalletXcm::send_xcm(Here, destination, xcm_message.message));
-			})
+		let sender = if xcm_message.execute {
+			execute_message(xcm_message);
+			None
 		} else {
-			// We get the source's execution method
-			let execute_with = match xcm_message.source % 4 {
-				1 => ParaA::execute_with,
-				2 => ParaB::execute_with,
-				_ => ParaC::execute_with,
-			};
-			// We get the destination for the message
-			let destination: Location = match xcm_message.destination % 4 {
-				n @ 1..=3 => (Parent, Parachain(n)).into(),
-				_ => Parent.into(),
-			};
-			#[cfg(not(fuzzing))]
-			{
-				let destination_str = match xcm_message.destination % 4 {
-					n @ 1..=3 => format!("Parachain {n}"),
-					_ => "Relay Chain".to_string(),
-				};
-				println!("  source:      Parachain {}", xcm_message.source % 4);
-				println!("  destination: {}", destination_str);
-				println!("  message:     {:?}", xcm_message.message);
-			}
-			// We execute the message with the appropriate source and destination
-			execute_with(|| {
-				assert_ok!(ParachainPalletXcm::send_xcm(Here, destination, xcm_message.message));
-			});
-		}
+			Some(send_message(xcm_message))
+		};
 		#[cfg(not(fuzzing))]
 		println!();
 		// We run integrity tests and try_runtime invariants
@@ -234,6 +294,8 @@ fn run_input(xcm_messages: [XcmMessage; 5]) {
 			relay_chain::AllPalletsWithSystem::try_state(Default::default(), All).unwrap();
 			relay_chain::AllPalletsWithSystem::integrity_test();
 		});
+		// We check that no assets were created on any chain
+		ledger.check(sender);
 	}
 }
 
@@ -0,0 +1,322 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Asset conservation invariants checked after each step of the fuzzer.
+//!
+//! The relay chain token is the only fungible asset of the network. It is native to the relay
+//! chain, and the parachains hold derivatives of it which are backed by their sovereign accounts
+//! on the relay chain. Value can be destroyed, e.g. by fees or rejected teleports, but it must
+//! never be created.
+
+use crate::{para_account_id, parachain, relay_chain, ParaA, ParaB, ParaC, Relay};
+use codec::Encode;
+use core::marker::PhantomData;
+use frame_support::{
+	storage::{unhashed, with_transaction, TransactionOutcome},
+	traits::{fungible::Inspect, Get},
+};
+use sp_core::H256;
+use sp_runtime::DispatchError;
+use std::collections::BTreeMap;
+use xcm::latest::{prelude::*, Weight};
+use xcm_executor::{traits::TransactAsset, AssetsInHolding};
+use xcm_simulator::{TestExt, XcmExecutor};
+
+/// The fungible amount of `asset`.
+fn amount(asset: &Asset) -> u128 {
+	match asset.fun {
+		Fungible(amount) => amount,
+		NonFungible(_) => 0,
+	}
+}
+
+/// The fungible amount of `id` in `assets`.
+fn amount_of(assets: &Assets, id: &AssetId) -> u128 {
+	assets.inner().iter().filter(|asset| asset.id == *id).map(amount).sum()
+}
+
+/// The assets trapped on runtime `T` which were not claimed yet, with the origins that can claim
+/// them.
+fn trapped_assets<T>() -> Vec<(Location, Assets)>
+where
+	T: pallet_xcm::Config,
+	<T as frame_system::Config>::RuntimeEvent: TryInto<pallet_xcm::Event<T>>,
+{
+	let mut traps = BTreeMap::<H256, (Location, VersionedAssets, usize)>::new();
+	let events = frame_system::Pallet::<T>::events()
+		.into_iter()
+		.filter_map(|record| record.event.try_into().ok());
+	for event in events {
+		match event {
+			pallet_xcm::Event::AssetsTrapped { hash, origin, assets } =>
+				traps.entry(hash).or_insert((origin, assets, 0)).2 += 1,
+			pallet_xcm::Event::AssetsClaimed { hash, .. } =>
+				if let Some((_, _, count)) = traps.get_mut(&hash) {
+					*count = count.saturating_sub(1);
+				},
+			_ => {},
+		}
+	}
+
+	traps
+		.into_values()
+		.flat_map(|(origin, assets, count)| {
+			let assets =
+				Assets::try_from(assets).expect("assets are trapped at the latest version");
+			core::iter::repeat((origin, assets)).take(count)
+		})
+		.collect()
+}
+
+/// Ensure that all `trapped` assets can be claimed by their origin with `ClaimAsset`.
+///
+/// The claims are rolled back.
+fn ensure_claimable<Config: xcm_executor::Config>(trapped: &[(Location, Assets)]) {
+	for (origin, assets) in trapped {
+		let outcome = with_transaction(|| {
+			let claim = Xcm::<Config::RuntimeCall>(vec![ClaimAsset {
+				assets: assets.clone(),
+				ticket: Here.into(),
+			}]);
+			let mut hash = claim.using_encoded(sp_io::hashing::blake2_256);
+			let outcome = XcmExecutor::<Config>::prepare_and_execute(
+				origin.clone(),
+				claim,
+				&mut hash,
+				Weight::MAX,
+				Weight::zero(),
+			);
+			TransactionOutcome::Rollback(Ok::<_, DispatchError>(outcome))
+		})
+		.expect("the claim is infallible");
+		assert!(
+			matches!(outcome, Outcome::Complete { .. }),
+			"assets {assets:?} trapped for {origin:?} are not claimable: {outcome:?}",
+		);
+	}
+}
+
+/// The storage key of the teleports recorded by [`RecordTeleports`] on a chain.
+const TELEPORTS_KEY: &[u8] = b":xcm_fuzzer:teleports";
+
+/// The amounts checked out and in on a chain, by the teleported asset as seen from the relay
+/// chain.
+type Teleports = BTreeMap<Location, (u128, u128)>;
+
+/// Records the teleports of the asset transactor `T` of the chain at `ChainLocation`, which is
+/// the location of the chain as seen from the relay chain.
+///
+/// The teleports are recorded in the storage of the chain, so they are rolled back together with
+/// the instruction that made them.
+pub(crate) struct RecordTeleports<T, ChainLocation>(PhantomData<(T, ChainLocation)>);
+
+impl<T, ChainLocation: Get<Location>> RecordTeleports<T, ChainLocation> {
+	fn record(what: &Asset, update: impl FnOnce(&mut (u128, u128), u128)) {
+		let id = ChainLocation::get()
+			.appended_with(what.id.0.clone())
+			.expect("the simulated chains are at most one level below the relay chain");
+		let mut teleports = unhashed::get::<Teleports>(TELEPORTS_KEY).unwrap_or_default();
+		update(teleports.entry(id).or_default(), amount(what));
+		unhashed::put(TELEPORTS_KEY, &teleports);
+	}
+}
+
+impl<T: TransactAsset, ChainLocation: Get<Location>> TransactAsset
+	for RecordTeleports<T, ChainLocation>
+{
+	fn can_check_in(origin: &Location, what: &Asset, context: &XcmContext) -> XcmResult {
+		T::can_check_in(origin, what, context)
+	}
+
+	fn check_in(origin: &Location, what: &Asset, context: &XcmContext) {
+		T::check_in(origin, what, context);
+		Self::record(what, |(_, checked_in), amount| *checked_in += amount);
+	}
+
+	fn can_check_out(dest: &Location, what: &Asset, context: &XcmContext) -> XcmResult {
+		T::can_check_out(dest, what, context)
+	}
+
+	fn check_out(dest: &Location, what: &Asset, context: &XcmContext) {
+		T::check_out(dest, what, context);
+		Self::record(what, |(checked_out, _), amount| *checked_out += amount);
+	}
+
+	fn deposit_asset(what: &Asset, who: &Location, context: Option<&XcmContext>) -> XcmResult {
+		T::deposit_asset(what, who, context)
+	}
+
+	fn withdraw_asset(
+		what: &Asset,
+		who: &Location,
+		maybe_context: Option<&XcmContext>,
+	) -> Result<AssetsInHolding, XcmError> {
+		T::withdraw_asset(what, who, maybe_context)
+	}
+
+	fn internal_transfer_asset(
+		asset: &Asset,
+		from: &Location,
+		to: &Location,
+		context: &XcmContext,
+	) -> Result<AssetsInHolding, XcmError> {
+		T::internal_transfer_asset(asset, from, to, context)
+	}
+
+	fn transfer_asset(
+		asset: &Asset,
+		from: &Location,
+		to: &Location,
+		context: &XcmContext,
+	) -> Result<AssetsInHolding, XcmError> {
+		T::transfer_asset(asset, from, to, context)
+	}
+}
+
+/// Take the teleports recorded on the current chain.
+fn take_teleports() -> Teleports {
+	unhashed::take(TELEPORTS_KEY).unwrap_or_default()
+}
+
+/// Ensure that no teleported asset was checked in without being checked out.
+///
+/// Teleports are only recorded within a single step of the fuzzer, so any check in of an asset
+/// must be matched by a check out of the same asset in the same step.
+fn ensure_teleports_burnt() {
+	let mut teleports = Relay::execute_with(take_teleports);
+	for execute_with in [ParaA::execute_with, ParaB::execute_with, ParaC::execute_with] {
+		for (id, (checked_out, checked_in)) in execute_with(take_teleports) {
+			let total = teleports.entry(id).or_default();
+			total.0 += checked_out;
+			total.1 += checked_in;
+		}
+	}
+	for (id, (checked_out, checked_in)) in teleports {
+		assert!(
+			checked_in <= checked_out,
+			"teleported {checked_in} of {id:?} into chains but only {checked_out} out of chains",
+		);
+	}
+}
+
+/// The value of the relay chain token held on each chain of the network.
+#[derive(Clone, Debug)]
+struct Snapshot {
+	/// Total issuance on the relay chain, including trapped assets.
+	relay: u128,
+	/// Total issuance of the derivatives on each parachain, including trapped assets.
+	paras: [u128; 3],
+	/// Balances of the sovereign accounts of the parachains on the relay chain.
+	sovereigns: [u128; 3],
+}
+
+impl Snapshot {
+	/// Take a snapshot of the network, ensuring that all trapped assets are claimable.
+	fn take() -> Self {
+		let (relay, sovereigns) = Relay::execute_with(|| {
+			let trapped = trapped_assets::<relay_chain::Runtime>();
+			ensure_claimable::<relay_chain::XcmConfig>(&trapped);
+			let trapped: u128 =
+				trapped.iter().map(|(_, assets)| amount_of(assets, &AssetId(Here.into()))).sum();
+			(
+				relay_chain::Balances::total_issuance() + trapped,
+				[1, 2, 3].map(|id| relay_chain::Balances::total_balance(&para_account_id(id))),
+			)
+		});
+		let paras =
+			[ParaA::execute_with, ParaB::execute_with, ParaC::execute_with].map(|execute_with| {
+				execute_with(|| {
+					let trapped = trapped_assets::<parachain::Runtime>();
+					ensure_claimable::<parachain::XcmConfig>(&trapped);
+					let trapped: u128 = trapped
+						.iter()
+						.map(|(_, assets)| amount_of(assets, &AssetId(Parent.into())))
+						.sum();
+					parachain::Balances::total_issuance() + trapped
+				})
+			});
+
+		Self { relay, paras, sovereigns }
+	}
+}
+
+/// The chain which sent a message as itself in a step of the fuzzer.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum Sender {
+	/// The relay chain.
+	Relay,
+	/// The parachain with the given id.
+	Para(u32),
+}
+
+/// Tracks the value of the network across the steps of a fuzzer run.
+pub(crate) struct Ledger {
+	initial: Snapshot,
+	/// The snapshot taken after the previous step.
+	last: Snapshot,
+	/// By how much the derivatives minted on each parachain may exceed the backing added to its
+	/// sovereign account.
+	///
+	/// Parachains are trusted to spend from their own sovereign account, so this is raised by what
+	/// a parachain spent from it in a step in which it sent a message itself. The relay chain is
+	/// the reserve of the derivatives, so it is raised by whatever the relay chain itself reports
+	/// as deposited.
+	allowances: [i128; 3],
+}
+
+impl Ledger {
+	/// Start tracking the current state of the network.
+	pub(crate) fn new() -> Self {
+		let initial = Snapshot::take();
+		Self { last: initial.clone(), initial, allowances: [0; 3] }
+	}
+
+	/// Check the invariants after a step of the fuzzer.
+	///
+	/// `sender` is the chain which sent a message as itself in this step, if any.
+	pub(crate) fn check(&mut self, sender: Option<Sender>) {
+		ensure_teleports_burnt();
+
+		let snapshot = Snapshot::take();
+		assert!(
+			snapshot.relay <= self.initial.relay,
+			"relay chain token was minted: {snapshot:?}, initially {:?}",
+			self.initial,
+		);
+		for (index, allowance) in self.allowances.iter_mut().enumerate() {
+			let minted = snapshot.paras[index] as i128 - self.initial.paras[index] as i128;
+			let backed =
+				snapshot.sovereigns[index] as i128 - self.initial.sovereigns[index] as i128;
+			let para_id = index as u32 + 1;
+			match sender {
+				Some(Sender::Relay) => *allowance = (*allowance).max(minted - backed),
+				Some(Sender::Para(id)) if id == para_id => {
+					let spent =
+						self.last.sovereigns[index].saturating_sub(snapshot.sovereigns[index]);
+					*allowance += spent as i128;
+				},
+				_ => {},
+			}
+			assert!(
+				minted - backed <= *allowance,
+				"derivatives on parachain {para_id} were minted without backing: {snapshot:?}, \
+				initially {:?}",
+				self.initial,
+			);
+		}
+		self.last = snapshot;
+	}
+}
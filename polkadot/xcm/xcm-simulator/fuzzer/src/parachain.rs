@@ -44,6 +44,8 @@ use xcm_builder::{
 };
 use xcm_executor::{Config, XcmExecutor};
 
+use crate::invariants::RecordTeleports;
+
 pub type TxExtension = (frame_system::CheckNonZeroSender<Runtime>,);
 
 pub type BlockNumber = u64;
@@ -89,6 +91,7 @@ parameter_types! {
 	pub const KsmLocation: Location = Location::parent();
 	pub const RelayNetwork: NetworkId = NetworkId::Kusama;
 	pub UniversalLocation: InteriorLocation = [GlobalConsensus(RelayNetwork::get()), Parachain(MsgQueue::parachain_id().into())].into();
+	pub ChainLocation: Location = Parachain(MsgQueue::parachain_id().into()).into();
 }
 
 pub type LocationToAccountId = (
@@ -120,7 +123,7 @@ pub struct XcmConfig;
 impl Config for XcmConfig {
 	type RuntimeCall = RuntimeCall;
 	type XcmSender = XcmRouter;
-	type AssetTransactor = LocalAssetTransactor;
+	type AssetTransactor = RecordTeleports<LocalAssetTransactor, ChainLocation>;
 	type OriginConverter = XcmOriginToCallOrigin;
 	type IsReserve = NativeAsset;
 	type IsTeleporter = ();
@@ -129,10 +132,10 @@ impl Config for XcmConfig {
 	type Weigher = FixedWeightBounds<UnitWeightCost, RuntimeCall, MaxInstructions>;
 	type Trader = FixedRateOfFungible<KsmPerSecondPerByte, ()>;
 	type ResponseHandler = ();
-	type AssetTrap = ();
+	type AssetTrap = PolkadotXcm;
 	type AssetLocker = ();
 	type AssetExchanger = ();
-	type AssetClaims = ();
+	type AssetClaims = PolkadotXcm;
 	type SubscriptionService = ();
 	type PalletInstancesInfo = ();
 	type FeeManager = ();
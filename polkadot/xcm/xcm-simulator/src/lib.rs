@@ -44,7 +44,10 @@ pub use polkadot_runtime_parachains::{
 };
 pub use xcm::{latest::prelude::*, VersionedXcm};
 pub use xcm_builder::ProcessXcmMessage;
-pub use xcm_executor::XcmExecutor;
+pub use xcm_executor::{
+	trace::{with_trace, AssetTransactorCall, TraceEntry},
+	XcmExecutor,
+};
 
 pub trait TestExt {
 	/// Initialize the test environment.
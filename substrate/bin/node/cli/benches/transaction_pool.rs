@@ -63,6 +63,7 @@ fn new_node(tokio_handle: Handle) -> node_cli::service::NewFullBase {
 		keystore: KeystoreConfig::InMemory,
 		database: DatabaseSource::RocksDb { path: root.join("db"), cache_size: 128 },
 		trie_cache_maximum_size: Some(64 * 1024 * 1024),
+		import_verification_threads: None,
 		state_pruning: Some(PruningMode::ArchiveAll),
 		blocks_pruning: BlocksPruning::KeepAll,
 		chain_spec: spec,
@@ -85,7 +86,11 @@ fn new_node(tokio_handle: Handle) -> node_cli::service::NewFullBase {
 		},
 		prometheus_config: None,
 		telemetry_endpoints: None,
-		offchain_worker: OffchainWorkerConfig { enabled: true, indexing_enabled: false },
+		offchain_worker: OffchainWorkerConfig {
+			enabled: true,
+			indexing_enabled: false,
+			http_policy: Default::default(),
+		},
 		force_authoring: false,
 		disable_grandpa: false,
 		dev_key_seed: Some(Sr25519Keyring::Alice.to_seed()),
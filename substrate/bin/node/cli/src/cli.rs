@@ -86,18 +86,28 @@ pub enum Subcommand {
 	/// Export blocks.
 	ExportBlocks(sc_cli::ExportBlocksCmd),
 
-	/// Export the state of a given block into a chain spec.
+	/// Export the state of a given block into a chain spec or a state snapshot.
 	ExportState(sc_cli::ExportStateCmd),
 
 	/// Import blocks.
 	ImportBlocks(sc_cli::ImportBlocksCmd),
 
+	/// Import a state snapshot produced by `export-state --snapshot`.
+	ImportState(sc_cli::ImportStateCmd),
+
 	/// Remove the whole chain.
 	PurgeChain(sc_cli::PurgeChainCmd),
 
 	/// Revert the chain to a previous state.
 	Revert(sc_cli::RevertCmd),
 
+	/// Restore the database from the snapshot.
+	Restore(sc_cli::RestoreCmd),
+
+	/// Database maintenance utilities.
+	#[command(subcommand)]
+	Db(sc_cli::DbSubcommand),
+
 	/// Db meta columns information.
 	ChainInfo(sc_cli::ChainInfoCmd),
 }
@@ -195,8 +195,22 @@ pub fn run() -> Result<()> {
 		Some(Subcommand::ExportState(cmd)) => {
 			let runner = cli.create_runner(cmd)?;
 			runner.async_run(|config| {
-				let PartialComponents { client, task_manager, .. } = new_partial(&config, None)?;
-				Ok((cmd.run(client, config.chain_spec), task_manager))
+				let PartialComponents {
+					client,
+					backend,
+					task_manager,
+					other: (_, (_, grandpa_link, ..), ..),
+					..
+				} = new_partial(&config, None)?;
+				let warp_sync = Arc::new(sc_consensus_grandpa::warp_proof::NetworkProvider::new(
+					backend,
+					grandpa_link.shared_authority_set().clone(),
+					Vec::default(),
+				));
+				Ok((
+					cmd.run_with_warp_sync_provider(client, config.chain_spec, Some(warp_sync)),
+					task_manager,
+				))
 			})
 		},
 		Some(Subcommand::ImportBlocks(cmd)) => {
@@ -207,6 +221,25 @@ pub fn run() -> Result<()> {
 				Ok((cmd.run(client, import_queue), task_manager))
 			})
 		},
+		Some(Subcommand::ImportState(cmd)) => {
+			let runner = cli.create_runner(cmd)?;
+			runner.async_run(|config| {
+				let PartialComponents {
+					client,
+					backend,
+					task_manager,
+					import_queue,
+					other: (_, (_, grandpa_link, ..), ..),
+					..
+				} = new_partial(&config, None)?;
+				let warp_sync = Arc::new(sc_consensus_grandpa::warp_proof::NetworkProvider::new(
+					backend,
+					grandpa_link.shared_authority_set().clone(),
+					Vec::default(),
+				));
+				Ok((cmd.run(client, import_queue, Some(warp_sync)), task_manager))
+			})
+		},
 		Some(Subcommand::PurgeChain(cmd)) => {
 			let runner = cli.create_runner(cmd)?;
 			runner.sync_run(|config| cmd.run(config.database))
@@ -224,6 +257,20 @@ pub fn run() -> Result<()> {
 				Ok((cmd.run(client, backend, Some(aux_revert)), task_manager))
 			})
 		},
+		Some(Subcommand::Restore(cmd)) => {
+			let runner = cli.create_runner(cmd)?;
+			runner.sync_run(|config| cmd.run::<Block>(&config))
+		},
+		Some(Subcommand::Db(sc_cli::DbSubcommand::Convert(cmd))) => {
+			let runner = cli.create_runner(cmd)?;
+			runner.sync_run(|config| {
+				cmd.run::<Block>(&config, |finalized| {
+					let mut keys = sc_consensus_babe::aux_schema::aux_keys(finalized);
+					keys.extend(sc_consensus_grandpa::aux_keys());
+					keys
+				})
+			})
+		},
 		Some(Subcommand::ChainInfo(cmd)) => {
 			let runner = cli.create_runner(cmd)?;
 			runner.sync_run(|config| cmd.run::<Block>(&config))
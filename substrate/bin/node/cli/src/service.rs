@@ -40,7 +40,8 @@ use sc_network::{
 use sc_network_sync::{strategy::warp::WarpSyncConfig, SyncingService};
 use sc_service::{config::Configuration, error::Error as ServiceError, RpcHandlers, TaskManager};
 use sc_statement_store::Store as StatementStore;
-use sc_telemetry::{Telemetry, TelemetryWorker};
+use sc_storage_monitor::{DegradationAction, DegradationHandlers};
+use sc_telemetry::{Telemetry, TelemetryHandle, TelemetryWorker};
 use sc_transaction_pool::TransactionPoolHandle;
 use sc_transaction_pool_api::OffchainTransactionPoolFactory;
 use sp_api::ProvideRuntimeApi;
@@ -284,6 +285,7 @@ pub fn new_partial(
 			registry: config.prometheus_registry(),
 			telemetry: telemetry.as_ref().map(|x| x.handle()),
 			offchain_tx_pool_factory: OffchainTransactionPoolFactory::new(transaction_pool.clone()),
+			verification_threads: config.import_verification_threads,
 		})?;
 
 	let import_setup = (block_import, grandpa_link, babe_link, beefy_voter_links);
@@ -384,6 +386,8 @@ pub struct NewFullBase {
 	pub task_manager: TaskManager,
 	/// The client instance of the node.
 	pub client: Arc<FullClient>,
+	/// The database backend of the node.
+	pub backend: Arc<FullBackend>,
 	/// The networking service of the node.
 	pub network: Arc<dyn NetworkService>,
 	/// The syncing service of the node.
@@ -392,6 +396,8 @@ pub struct NewFullBase {
 	pub transaction_pool: Arc<TransactionPoolHandle<Block, FullClient>>,
 	/// The rpc handlers of the node.
 	pub rpc_handlers: RpcHandlers,
+	/// The telemetry handle of the node, if telemetry is enabled.
+	pub telemetry: Option<TelemetryHandle>,
 }
 
 /// Creates a full service from the configuration.
@@ -417,6 +423,7 @@ pub fn new_full_base<N: NetworkBackend<Block, <Block as BlockT>::Hash>>(
 	let enable_grandpa = !config.disable_grandpa;
 	let prometheus_registry = config.prometheus_registry().cloned();
 	let enable_offchain_worker = config.offchain_worker.enabled;
+	let offchain_http_policy = config.offchain_worker.http_policy.clone();
 
 	let hwbench = (!disable_hardware_benchmarks)
 		.then(|| {
@@ -790,6 +797,7 @@ pub fn new_full_base<N: NetworkBackend<Block, <Block as BlockT>::Hash>>(
 				network_provider: Arc::new(network.clone()),
 				is_validator: role.is_authority(),
 				enable_http_requests: true,
+				http_policy: offchain_http_policy,
 				custom_extensions: move |_| {
 					vec![Box::new(statement_store.clone().as_statement_store_ext()) as Box<_>]
 				},
@@ -804,10 +812,12 @@ pub fn new_full_base<N: NetworkBackend<Block, <Block as BlockT>::Hash>>(
 	Ok(NewFullBase {
 		task_manager,
 		client,
+		backend,
 		network,
 		sync: sync_service,
 		transaction_pool,
 		rpc_handlers,
+		telemetry: telemetry.as_ref().map(|x| x.handle()),
 	})
 }
 
@@ -815,40 +825,53 @@ pub fn new_full_base<N: NetworkBackend<Block, <Block as BlockT>::Hash>>(
 pub fn new_full(config: Configuration, cli: Cli) -> Result<TaskManager, ServiceError> {
 	let mixnet_config = cli.mixnet_params.config(config.role.is_authority());
 	let database_path = config.database.path().map(Path::to_path_buf);
+	let prometheus_registry = config.prometheus_registry().cloned();
 
-	let task_manager = match config.network.network_backend {
-		sc_network::config::NetworkBackendType::Libp2p => {
-			let task_manager = new_full_base::<sc_network::NetworkWorker<_, _>>(
+	let full = match config.network.network_backend {
+		sc_network::config::NetworkBackendType::Libp2p =>
+			new_full_base::<sc_network::NetworkWorker<_, _>>(
 				config,
 				mixnet_config,
 				cli.no_hardware_benchmarks,
 				|_, _| (),
-			)
-			.map(|NewFullBase { task_manager, .. }| task_manager)?;
-			task_manager
-		},
-		sc_network::config::NetworkBackendType::Litep2p => {
-			let task_manager = new_full_base::<sc_network::Litep2pNetworkBackend>(
+			)?,
+		sc_network::config::NetworkBackendType::Litep2p =>
+			new_full_base::<sc_network::Litep2pNetworkBackend>(
 				config,
 				mixnet_config,
 				cli.no_hardware_benchmarks,
 				|_, _| (),
-			)
-			.map(|NewFullBase { task_manager, .. }| task_manager)?;
-			task_manager
-		},
+			)?,
 	};
 
 	if let Some(database_path) = database_path {
+		let degraded_pruning = cli.storage_monitor.degraded_pruning;
+		let backend = full.backend.clone();
+		let client = full.client.clone();
+		let rpc_handlers = full.rpc_handlers.clone();
+		let degradation_handlers = DegradationHandlers::new()
+			.with(DegradationAction::AggressivePruning, move |degraded| {
+				backend.set_pruning_override(degraded.then_some(degraded_pruning))
+			})
+			.with(DegradationAction::DisableOffchainIndexing, move |degraded| {
+				client.set_offchain_indexing_suspended(degraded)
+			})
+			.with(DegradationAction::DisableRpcCaches, move |degraded| {
+				rpc_handlers.set_chain_head_suspended(degraded)
+			});
+
 		sc_storage_monitor::StorageMonitorService::try_spawn(
 			cli.storage_monitor,
 			database_path,
-			&task_manager.spawn_essential_handle(),
+			&full.task_manager.spawn_essential_handle(),
+			prometheus_registry.as_ref(),
+			full.telemetry.clone(),
+			degradation_handlers,
 		)
 		.map_err(|e| ServiceError::Application(e.into()))?;
 	}
 
-	Ok(task_manager)
+	Ok(full.task_manager)
 }
 
 #[cfg(test)]
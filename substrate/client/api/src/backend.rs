@@ -18,7 +18,7 @@
 
 //! Substrate Client data backend
 
-use std::collections::HashSet;
+use std::{collections::HashSet, path::Path};
 
 use parking_lot::RwLock;
 
@@ -122,6 +122,20 @@ where
 	)
 }
 
+/// Blocks which state is kept by the backend.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StateRetention {
+	/// The state of all finalized blocks is kept.
+	All,
+	/// The state of finalized blocks is pruned.
+	Pruned {
+		/// The state of the last `keep_last` finalized blocks is kept.
+		keep_last: u32,
+		/// The state of every `keep_every`th finalized block is kept, if set.
+		keep_every: Option<u32>,
+	},
+}
+
 /// State of a new block.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum NewBlockState {
@@ -587,6 +601,11 @@ pub trait Backend<Block: BlockT>: AuxStore + Send + Sync {
 		self.state_at(hash).is_ok()
 	}
 
+	/// Returns blocks which state is kept by the backend.
+	fn state_retention(&self) -> StateRetention {
+		StateRetention::All
+	}
+
 	/// Returns state backend with post-state of given block.
 	fn state_at(&self, hash: Block::Hash) -> sp_blockchain::Result<Self::State>;
 
@@ -635,6 +654,15 @@ pub trait Backend<Block: BlockT>: AuxStore + Send + Sync {
 
 	/// Tells whether the backend requires full-sync mode.
 	fn requires_full_sync(&self) -> bool;
+
+	/// Write a consistent copy of the backend database to the new database at `path`, while
+	/// the node keeps running. Returns the number and hash of the best finalized block in the
+	/// copy.
+	///
+	/// Returns an error if the backend doesn't support snapshots.
+	fn snapshot(&self, _path: &Path) -> sp_blockchain::Result<(NumberFor<Block>, Block::Hash)> {
+		Err(sp_blockchain::Error::Backend("Snapshots are not supported by the backend".into()))
+	}
 }
 
 /// Mark for all Backend implementations, that are making use of state data, stored locally.
@@ -0,0 +1,249 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{error, CliConfiguration, DatabaseParams, PruningParams, SharedParams};
+#[cfg(feature = "rocksdb")]
+use log::info;
+use sc_service::DatabaseSource;
+use sp_runtime::traits::Block as BlockT;
+use std::path::{Path, PathBuf};
+
+/// The `db convert` command used to convert the database between RocksDB and ParityDB.
+///
+/// ParityDB only keeps hashes of the keys in most of its columns, so the conversion into RocksDB
+/// only keeps the finalized chain and the state of the finalized block, like a warp sync does. The
+/// node must be stopped while the database is converted.
+#[derive(Debug, Clone, clap::Parser)]
+pub struct DbConvertCmd {
+	/// Path of the converted database.
+	///
+	/// Defaults to the ParityDB path of the chain, which requires `--database auto`. Required to
+	/// convert the ParityDB database.
+	#[arg(long, value_name = "PATH")]
+	pub target: Option<PathBuf>,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub pruning_params: PruningParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub database_params: DatabaseParams,
+}
+
+impl DbConvertCmd {
+	/// Run the convert command.
+	///
+	/// `aux_keys` returns the keys of the auxiliary data, e.g. of the consensus engines, that
+	/// should be kept when the ParityDB database is converted up to the given finalized block.
+	pub fn run<B>(
+		&self,
+		config: &sc_service::Configuration,
+		aux_keys: impl FnOnce(&B::Hash) -> Vec<Vec<u8>>,
+	) -> error::Result<()>
+	where
+		B: BlockT,
+	{
+		match &config.database {
+			DatabaseSource::Auto { rocksdb_path, paritydb_path, cache_size } => self
+				.convert_to_paritydb::<B>(
+					config,
+					rocksdb_path,
+					*cache_size,
+					self.target.as_ref().unwrap_or(paritydb_path),
+				),
+			#[cfg(feature = "rocksdb")]
+			DatabaseSource::RocksDb { path, cache_size } => match &self.target {
+				Some(target) => self.convert_to_paritydb::<B>(config, path, *cache_size, target),
+				None => Err(error::Error::Input(
+					"`--target` is required to convert the RocksDB database".into(),
+				)),
+			},
+			DatabaseSource::ParityDb { path } => match &self.target {
+				Some(target) => self.convert_to_rocksdb::<B>(config, path, target, aux_keys),
+				None => Err(error::Error::Input(
+					"`--target` is required to convert the ParityDB database".into(),
+				)),
+			},
+			DatabaseSource::Custom { .. } =>
+				Err(error::Error::Input("Custom database can't be converted".into())),
+		}
+	}
+
+	#[cfg(feature = "rocksdb")]
+	fn convert_to_paritydb<B: BlockT>(
+		&self,
+		config: &sc_service::Configuration,
+		source_path: &Path,
+		cache_size: usize,
+		target_path: &Path,
+	) -> error::Result<()> {
+		use sc_client_api::{backend::Backend as BackendT, blockchain::HeaderBackend};
+
+		let source = DatabaseSource::RocksDb { path: source_path.into(), cache_size };
+		let source_info = sc_service::new_db_backend::<B>(db_settings(config, source))?
+			.blockchain()
+			.info();
+		info!(
+			"Converting database {:?} into {:?}, finalized block #{} ({}), best block #{} ({})",
+			source_path,
+			target_path,
+			source_info.finalized_number,
+			source_info.finalized_hash,
+			source_info.best_number,
+			source_info.best_hash,
+		);
+		sc_client_db::convert_rocksdb_to_paritydb::<B>(source_path, target_path, log_progress)?;
+
+		let target = DatabaseSource::ParityDb { path: target_path.into() };
+		let info =
+			verify::<B>(config, target, (source_info.best_hash, source_info.finalized_hash))?;
+		println!(
+			"Database converted to {:?}, finalized block #{} ({}), best block #{} ({}). \
+			Remove the RocksDB database or start the node with `--database paritydb` to use it.",
+			target_path,
+			info.finalized_number,
+			info.finalized_hash,
+			info.best_number,
+			info.best_hash,
+		);
+		Ok(())
+	}
+
+	#[cfg(feature = "rocksdb")]
+	fn convert_to_rocksdb<B: BlockT>(
+		&self,
+		config: &sc_service::Configuration,
+		source_path: &Path,
+		target_path: &Path,
+		aux_keys: impl FnOnce(&B::Hash) -> Vec<Vec<u8>>,
+	) -> error::Result<()> {
+		use sc_client_api::{backend::Backend as BackendT, blockchain::HeaderBackend};
+
+		let source = DatabaseSource::ParityDb { path: source_path.into() };
+		let source_info = sc_service::new_db_backend::<B>(db_settings(config, source))?
+			.blockchain()
+			.info();
+		info!(
+			"Converting database {:?} into {:?}, finalized block #{} ({}). \
+			Blocks above the finalized one are dropped.",
+			source_path, target_path, source_info.finalized_number, source_info.finalized_hash,
+		);
+		sc_client_db::convert_paritydb_to_rocksdb::<B>(
+			source_path,
+			target_path,
+			aux_keys,
+			log_progress,
+		)?;
+
+		let cache_size = self.database_params.database_cache_size().unwrap_or(1024);
+		let target = DatabaseSource::RocksDb { path: target_path.into(), cache_size };
+		let info =
+			verify::<B>(config, target, (source_info.finalized_hash, source_info.finalized_hash))?;
+		println!(
+			"Database converted to {:?}, finalized block #{} ({}). \
+			Remove the ParityDB database or start the node with `--database rocksdb` to use it.",
+			target_path, info.finalized_number, info.finalized_hash,
+		);
+		Ok(())
+	}
+
+	#[cfg(not(feature = "rocksdb"))]
+	fn convert_to_paritydb<B: BlockT>(
+		&self,
+		_config: &sc_service::Configuration,
+		_source_path: &Path,
+		_cache_size: usize,
+		_target_path: &Path,
+	) -> error::Result<()> {
+		Err(error::Error::Input("`rocksdb` feature is not enabled".into()))
+	}
+
+	#[cfg(not(feature = "rocksdb"))]
+	fn convert_to_rocksdb<B: BlockT>(
+		&self,
+		_config: &sc_service::Configuration,
+		_source_path: &Path,
+		_target_path: &Path,
+		_aux_keys: impl FnOnce(&B::Hash) -> Vec<Vec<u8>>,
+	) -> error::Result<()> {
+		Err(error::Error::Input("`rocksdb` feature is not enabled".into()))
+	}
+}
+
+#[cfg(feature = "rocksdb")]
+fn db_settings(
+	config: &sc_service::Configuration,
+	source: DatabaseSource,
+) -> sc_client_db::DatabaseSettings {
+	sc_client_db::DatabaseSettings {
+		trie_cache_maximum_size: config.trie_cache_maximum_size,
+		state_pruning: config.state_pruning.clone(),
+		source,
+		blocks_pruning: config.blocks_pruning,
+	}
+}
+
+#[cfg(feature = "rocksdb")]
+fn log_progress(progress: sc_client_db::ConvertProgress) {
+	info!("Converting column {}/{}: {} keys", progress.column + 1, progress.columns, progress.keys)
+}
+
+/// Open the converted database and make sure that it has the `expected` best and finalized blocks
+/// and that the state roots of both blocks match their headers.
+#[cfg(feature = "rocksdb")]
+fn verify<B: BlockT>(
+	config: &sc_service::Configuration,
+	target: DatabaseSource,
+	expected: (B::Hash, B::Hash),
+) -> error::Result<sp_blockchain::Info<B>> {
+	use sc_client_api::{backend::Backend as BackendT, blockchain::HeaderBackend};
+
+	let backend = sc_service::new_db_backend::<B>(db_settings(config, target))?;
+	let info = backend.blockchain().info();
+	if (info.best_hash, info.finalized_hash) != expected {
+		return Err(sp_blockchain::Error::Backend(format!(
+			"Converted database has best block {} and finalized block {}, expected {} and {}",
+			info.best_hash, info.finalized_hash, expected.0, expected.1
+		))
+		.into())
+	}
+	for hash in [info.finalized_hash, info.best_hash] {
+		let keys = sc_client_db::verify_state_root(&backend, hash)?;
+		info!("Verified state root of block {}: {} keys", hash, keys);
+	}
+	Ok(info)
+}
+
+impl CliConfiguration for DbConvertCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn pruning_params(&self) -> Option<&PruningParams> {
+		Some(&self.pruning_params)
+	}
+
+	fn database_params(&self) -> Option<&DatabaseParams> {
+		Some(&self.database_params)
+	}
+}
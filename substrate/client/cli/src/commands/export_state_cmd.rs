@@ -23,18 +23,27 @@ use crate::{
 };
 use clap::Parser;
 use log::info;
-use sc_client_api::{HeaderBackend, StorageProvider, UsageProvider};
+use sc_client_api::{BlockBackend, HeaderBackend, StorageProvider, UsageProvider};
+use sc_network_sync::strategy::warp::WarpSyncProvider;
 use sp_runtime::traits::{Block as BlockT, Header as HeaderT};
-use std::{fmt::Debug, io::Write, str::FromStr, sync::Arc};
+use std::{fmt::Debug, fs, io::Write, path::PathBuf, str::FromStr, sync::Arc};
 
 /// The `export-state` command used to export the state of a given block into
-/// a chain spec.
+/// a chain spec or a state snapshot.
 #[derive(Debug, Clone, Parser)]
 pub struct ExportStateCmd {
 	/// Block hash or number.
 	#[arg(value_name = "HASH or NUMBER")]
 	pub input: Option<BlockNumberOrHash>,
 
+	/// Write a state snapshot of a finalized block to the given file instead of a chain spec.
+	///
+	/// The snapshot can be loaded into a new node with `import-state`. If the node supports warp
+	/// sync, the snapshot includes the warp sync proof and is taken at the last block with a
+	/// finality proof. Otherwise the last finalized block is used if no block is given.
+	#[arg(long, value_name = "PATH")]
+	pub snapshot: Option<PathBuf>,
+
 	#[allow(missing_docs)]
 	#[clap(flatten)]
 	pub shared_params: SharedParams,
@@ -51,19 +60,50 @@ pub struct ExportStateCmd {
 impl ExportStateCmd {
 	/// Run the `export-state` command
 	pub async fn run<B, BA, C>(
+		&self,
+		client: Arc<C>,
+		input_spec: Box<dyn sc_service::ChainSpec>,
+	) -> error::Result<()>
+	where
+		B: BlockT,
+		C: UsageProvider<B> + StorageProvider<B, BA> + HeaderBackend<B> + BlockBackend<B>,
+		BA: sc_client_api::backend::Backend<B>,
+		<B::Hash as FromStr>::Err: Debug,
+		<<B::Header as HeaderT>::Number as FromStr>::Err: Debug,
+	{
+		self.run_with_warp_sync_provider(client, input_spec, None).await
+	}
+
+	/// Run the `export-state` command, state snapshots include the warp sync proof generated by
+	/// `warp_sync_provider`.
+	pub async fn run_with_warp_sync_provider<B, BA, C>(
 		&self,
 		client: Arc<C>,
 		mut input_spec: Box<dyn sc_service::ChainSpec>,
+		warp_sync_provider: Option<Arc<dyn WarpSyncProvider<B>>>,
 	) -> error::Result<()>
 	where
 		B: BlockT,
-		C: UsageProvider<B> + StorageProvider<B, BA> + HeaderBackend<B>,
+		C: UsageProvider<B> + StorageProvider<B, BA> + HeaderBackend<B> + BlockBackend<B>,
 		BA: sc_client_api::backend::Backend<B>,
 		<B::Hash as FromStr>::Err: Debug,
 		<<B::Header as HeaderT>::Number as FromStr>::Err: Debug,
 	{
-		info!("Exporting raw state...");
 		let block_id = self.input.as_ref().map(|b| b.parse()).transpose()?;
+		if let Some(path) = &self.snapshot {
+			info!("Exporting state snapshot...");
+			let hash = block_id.map(|id| client.expect_block_hash_from_id(&id)).transpose()?;
+			let file = std::io::BufWriter::new(fs::File::create(path)?);
+			sc_service::chain_ops::export_state_snapshot(
+				client,
+				hash,
+				warp_sync_provider.as_deref(),
+				file,
+			)?;
+			return Ok(())
+		}
+
+		info!("Exporting raw state...");
 		let hash = match block_id {
 			Some(id) => client.expect_block_hash_from_id(&id)?,
 			None => client.usage_info().chain.best_hash,
@@ -0,0 +1,90 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+use crate::{
+	error,
+	params::{ImportParams, SharedParams},
+	CliConfiguration,
+};
+use clap::Parser;
+use sc_client_api::HeaderBackend;
+use sc_network_sync::strategy::warp::WarpSyncProvider;
+use sc_service::chain_ops::import_state_snapshot;
+use sp_runtime::traits::Block as BlockT;
+use std::{
+	fmt::Debug,
+	fs,
+	io::{self, BufReader, Read},
+	path::PathBuf,
+	sync::Arc,
+};
+
+/// The `import-state` command used to import a state snapshot.
+///
+/// The snapshot is produced by `export-state --snapshot` and is imported as if it was
+/// downloaded by warp sync, its warp sync proof is verified if the node supports warp sync. The
+/// node continues syncing from the snapshot block afterwards.
+#[derive(Debug, Parser)]
+pub struct ImportStateCmd {
+	/// Input file or stdin if unspecified.
+	#[arg()]
+	pub input: Option<PathBuf>,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub import_params: ImportParams,
+}
+
+impl ImportStateCmd {
+	/// Run the import-state command
+	///
+	/// The finality of the snapshot block is verified with `warp_sync_provider`, if it's given.
+	pub async fn run<B, C, IQ>(
+		&self,
+		client: Arc<C>,
+		import_queue: IQ,
+		warp_sync_provider: Option<Arc<dyn WarpSyncProvider<B>>>,
+	) -> error::Result<()>
+	where
+		C: HeaderBackend<B> + Send + Sync + 'static,
+		B: BlockT,
+		IQ: sc_service::ImportQueue<B> + 'static,
+	{
+		let file: Box<dyn Read + Send> = match &self.input {
+			Some(filename) => Box::new(BufReader::new(fs::File::open(filename)?)),
+			None => Box::new(io::stdin()),
+		};
+
+		import_state_snapshot(client, import_queue, warp_sync_provider, file)
+			.await
+			.map_err(Into::into)
+	}
+}
+
+impl CliConfiguration for ImportStateCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn import_params(&self) -> Option<&ImportParams> {
+		Some(&self.import_params)
+	}
+}
@@ -21,16 +21,20 @@
 mod build_spec_cmd;
 mod chain_info_cmd;
 mod check_block_cmd;
+mod db;
+mod db_convert_cmd;
 mod export_blocks_cmd;
 mod export_state_cmd;
 mod generate;
 mod generate_node_key;
 mod import_blocks_cmd;
+mod import_state_cmd;
 mod insert_key;
 mod inspect_key;
 mod inspect_node_key;
 mod key;
 mod purge_chain_cmd;
+mod restore_cmd;
 mod revert_cmd;
 mod run_cmd;
 mod sign;
@@ -41,9 +45,11 @@ mod verify;
 
 pub use self::{
 	build_spec_cmd::BuildSpecCmd, chain_info_cmd::ChainInfoCmd, check_block_cmd::CheckBlockCmd,
-	export_blocks_cmd::ExportBlocksCmd, export_state_cmd::ExportStateCmd, generate::GenerateCmd,
+	db::DbSubcommand, db_convert_cmd::DbConvertCmd, export_blocks_cmd::ExportBlocksCmd,
+	export_state_cmd::ExportStateCmd, generate::GenerateCmd,
 	generate_node_key::GenerateKeyCmdCommon, import_blocks_cmd::ImportBlocksCmd,
-	insert_key::InsertKeyCmd, inspect_key::InspectKeyCmd, inspect_node_key::InspectNodeKeyCmd,
-	key::KeySubcommand, purge_chain_cmd::PurgeChainCmd, revert_cmd::RevertCmd, run_cmd::RunCmd,
-	sign::SignCmd, vanity::VanityCmd, verify::VerifyCmd,
+	import_state_cmd::ImportStateCmd, insert_key::InsertKeyCmd, inspect_key::InspectKeyCmd,
+	inspect_node_key::InspectNodeKeyCmd, key::KeySubcommand, purge_chain_cmd::PurgeChainCmd,
+	restore_cmd::RestoreCmd, revert_cmd::RevertCmd, run_cmd::RunCmd, sign::SignCmd,
+	vanity::VanityCmd, verify::VerifyCmd,
 };
@@ -0,0 +1,174 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{error, CliConfiguration, DatabaseParams, PruningParams, SharedParams};
+use sc_client_api::{backend::Backend as BackendT, blockchain::HeaderBackend};
+use sc_service::DatabaseSource;
+use sp_runtime::traits::Block as BlockT;
+use std::{
+	fmt::Debug,
+	fs, io,
+	path::{Path, PathBuf},
+};
+
+/// The `restore` command used to restore the database from the snapshot.
+///
+/// Snapshots are written by the unsafe `db_snapshot` RPC while the node is running. The node
+/// must be stopped while the snapshot is restored. Only RocksDB databases are supported,
+/// ParityDB databases are not.
+#[derive(Debug, Clone, clap::Parser)]
+pub struct RestoreCmd {
+	/// Path to the snapshot directory.
+	#[arg(value_name = "SNAPSHOT")]
+	pub snapshot: PathBuf,
+
+	/// Replace the existing database, if there's any.
+	#[arg(long)]
+	pub force: bool,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub pruning_params: PruningParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub database_params: DatabaseParams,
+}
+
+impl RestoreCmd {
+	/// Run the restore command
+	pub fn run<B>(&self, config: &sc_service::Configuration) -> error::Result<()>
+	where
+		B: BlockT,
+	{
+		let (db_path, cache_size) = match &config.database {
+			#[cfg(feature = "rocksdb")]
+			DatabaseSource::RocksDb { path, cache_size } => (path, *cache_size),
+			#[cfg(feature = "rocksdb")]
+			DatabaseSource::Auto { rocksdb_path, cache_size, .. } => (rocksdb_path, *cache_size),
+			_ => return Err(error::Error::Input(
+				"Snapshots may only be restored to the RocksDB database, ParityDB is not supported"
+					.into(),
+			)),
+		};
+
+		if !self.snapshot.join("db_version").exists() {
+			return Err(error::Error::Input(format!(
+				"{:?} is not a database snapshot",
+				self.snapshot
+			)))
+		}
+		if db_path.exists() && !self.force {
+			return Err(error::Error::Input(format!(
+				"Database already exists at {:?}, use `--force` to replace it",
+				db_path
+			)))
+		}
+
+		// the snapshot is copied next to the database and only replaces the database after it
+		// has been opened, so the database is kept if the snapshot can't be restored
+		let restore_path = sibling_path(db_path, "restore");
+		if restore_path.exists() {
+			fs::remove_dir_all(&restore_path)?;
+		}
+		copy_dir(&self.snapshot, &restore_path)?;
+
+		let info = {
+			let db_config = sc_client_db::DatabaseSettings {
+				trie_cache_maximum_size: config.trie_cache_maximum_size,
+				state_pruning: config.state_pruning.clone(),
+				source: restored_source(restore_path.clone(), cache_size),
+				blocks_pruning: config.blocks_pruning,
+			};
+			let backend = sc_service::new_db_backend::<B>(db_config).inspect_err(|_| {
+				let _ = fs::remove_dir_all(&restore_path);
+			})?;
+			backend.blockchain().info()
+		};
+
+		if db_path.exists() {
+			let replaced_path = sibling_path(db_path, "replaced");
+			if replaced_path.exists() {
+				fs::remove_dir_all(&replaced_path)?;
+			}
+			fs::rename(db_path, &replaced_path)?;
+			fs::rename(&restore_path, db_path)?;
+			fs::remove_dir_all(&replaced_path)?;
+		} else {
+			fs::rename(&restore_path, db_path)?;
+		}
+
+		println!(
+			"Database restored to {:?}, finalized block #{} ({}), best block #{} ({})",
+			db_path, info.finalized_number, info.finalized_hash, info.best_number, info.best_hash,
+		);
+		Ok(())
+	}
+}
+
+/// Returns the source of the restored RocksDB database.
+#[cfg(feature = "rocksdb")]
+fn restored_source(path: PathBuf, cache_size: usize) -> DatabaseSource {
+	DatabaseSource::RocksDb { path, cache_size }
+}
+
+#[cfg(not(feature = "rocksdb"))]
+fn restored_source(_path: PathBuf, _cache_size: usize) -> DatabaseSource {
+	unreachable!("RocksDB databases are rejected before they are restored; qed")
+}
+
+/// Returns the path next to `path`, with the `suffix` appended to the file name.
+fn sibling_path(path: &Path, suffix: &str) -> PathBuf {
+	let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+	file_name.push(".");
+	file_name.push(suffix);
+	path.with_file_name(file_name)
+}
+
+/// Recursively copies the directory content.
+fn copy_dir(from: &Path, to: &Path) -> io::Result<()> {
+	fs::create_dir_all(to)?;
+	for entry in fs::read_dir(from)? {
+		let entry = entry?;
+		let target = to.join(entry.file_name());
+		if entry.file_type()?.is_dir() {
+			copy_dir(&entry.path(), &target)?;
+		} else {
+			fs::copy(entry.path(), target)?;
+		}
+	}
+	Ok(())
+}
+
+impl CliConfiguration for RestoreCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn pruning_params(&self) -> Option<&PruningParams> {
+		Some(&self.pruning_params)
+	}
+
+	fn database_params(&self) -> Option<&DatabaseParams> {
+		Some(&self.database_params)
+	}
+}
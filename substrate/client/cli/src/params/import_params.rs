@@ -24,7 +24,7 @@ use crate::{
 	params::{DatabaseParams, PruningParams},
 };
 use clap::Args;
-use std::path::PathBuf;
+use std::{num::NonZeroUsize, path::PathBuf};
 
 /// Parameters for block import.
 #[derive(Debug, Clone, Args)]
@@ -80,6 +80,13 @@ pub struct ImportParams {
 	/// Providing `0` will disable the cache.
 	#[arg(long, value_name = "Bytes", default_value_t = 1024 * 1024 * 1024)]
 	pub trie_cache_size: usize,
+
+	/// Prepare the verification of upcoming blocks on this many threads while importing.
+	///
+	/// Mainly speeds up syncing long ranges of blocks, by checking their seals in parallel.
+	/// Blocks are verified sequentially if not given.
+	#[arg(long, value_name = "COUNT")]
+	pub import_verification_threads: Option<NonZeroUsize>,
 }
 
 impl ImportParams {
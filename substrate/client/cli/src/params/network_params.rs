@@ -279,6 +279,7 @@ impl NetworkParams {
 			ipfs_server: self.ipfs_server,
 			sync_mode: self.sync.into(),
 			network_backend: self.network_backend.into(),
+			bandwidth_limits: Default::default(),
 		}
 	}
 }
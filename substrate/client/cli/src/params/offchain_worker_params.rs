@@ -25,7 +25,8 @@
 
 use clap::{ArgAction, Args};
 use sc_network::config::Role;
-use sc_service::config::OffchainWorkerConfig;
+use sc_service::config::{OffchainHttpPolicy, OffchainWorkerConfig};
+use std::{num::NonZeroU32, path::PathBuf};
 
 use crate::{error, OffchainWorkerEnabled};
 
@@ -47,6 +48,28 @@ pub struct OffchainWorkerParams {
 	/// Allows the runtime to write directly to offchain workers DB during block import.
 	#[arg(long = "enable-offchain-indexing", value_name = "ENABLE_OFFCHAIN_INDEXING", default_value_t = false, action = ArgAction::Set)]
 	pub indexing_enabled: bool,
+
+	/// Host offchain workers are allowed to send HTTP requests to.
+	///
+	/// Can be passed multiple times. `*.example.com` allows all subdomains of `example.com`. If
+	/// not passed, all hosts are allowed.
+	#[arg(long = "offchain-http-allow-host", value_name = "HOST")]
+	pub http_allowed_hosts: Vec<String>,
+
+	/// Maximum number of HTTP requests offchain workers may start per minute.
+	#[arg(long = "offchain-http-max-requests-per-minute", value_name = "COUNT")]
+	pub http_max_requests_per_minute: Option<NonZeroU32>,
+
+	/// Maximum size in bytes of the HTTP response bodies offchain workers may read.
+	#[arg(long = "offchain-http-max-response-size", value_name = "BYTES")]
+	pub http_max_response_size: Option<u64>,
+
+	/// Load the offchain worker HTTP policy from a JSON file.
+	///
+	/// The file may contain the `allowedHosts`, `maxRequestsPerMinute` and `maxResponseSize`
+	/// fields. Values passed through the other `--offchain-http-*` options take precedence.
+	#[arg(long = "offchain-http-policy", value_name = "PATH")]
+	pub http_policy_file: Option<PathBuf>,
 }
 
 impl OffchainWorkerParams {
@@ -60,6 +83,26 @@ impl OffchainWorkerParams {
 		};
 
 		let indexing_enabled = self.indexing_enabled;
-		Ok(OffchainWorkerConfig { enabled, indexing_enabled })
+
+		let mut http_policy = match &self.http_policy_file {
+			Some(path) => OffchainHttpPolicy::from_file(path).map_err(|e| {
+				error::Error::Input(format!(
+					"Failed to load offchain HTTP policy from {}: {e}",
+					path.display()
+				))
+			})?,
+			None => Default::default(),
+		};
+		if !self.http_allowed_hosts.is_empty() {
+			http_policy.allowed_hosts = Some(self.http_allowed_hosts.clone());
+		}
+		if self.http_max_requests_per_minute.is_some() {
+			http_policy.max_requests_per_minute = self.http_max_requests_per_minute;
+		}
+		if self.http_max_response_size.is_some() {
+			http_policy.max_response_size = self.http_max_response_size;
+		}
+
+		Ok(OffchainWorkerConfig { enabled, indexing_enabled, http_policy })
 	}
 }
@@ -41,6 +41,10 @@ pub struct PruningParams {
 	///
 	/// - NUMBER: Keep the data of the last NUMBER of finalized blocks.
 	///
+	/// - sparse:EVERY:NUMBER: Keep the data of every EVERYth finalized block and of the last
+	///   NUMBER of finalized blocks. Only supported by ParityDb. EVERY can't be changed between
+	///   subsequent runs.
+	///
 	/// [default: 256]
 	#[arg(alias = "pruning", long, value_name = "PRUNING_MODE")]
 	pub state_pruning: Option<DatabasePruningMode>,
@@ -74,6 +78,11 @@ impl PruningParams {
 
 	/// Get the block pruning value from the parameters
 	pub fn blocks_pruning(&self) -> error::Result<BlocksPruning> {
+		if let DatabasePruningMode::Sparse { .. } = self.blocks_pruning {
+			return Err(error::Error::Input(
+				"Sparse pruning mode is only supported by `--state-pruning`".into(),
+			))
+		}
 		Ok(self.blocks_pruning.into())
 	}
 }
@@ -91,6 +100,14 @@ pub enum DatabasePruningMode {
 	ArchiveCanonical,
 	/// Keep the data of the last number of finalized blocks.
 	Custom(u32),
+	/// Keep the data of every `keep_every`th finalized block and of the last `keep_last`
+	/// finalized blocks.
+	Sparse {
+		/// Keep the data of every `keep_every`th finalized block.
+		keep_every: u32,
+		/// Keep the data of the last `keep_last` finalized blocks.
+		keep_last: u32,
+	},
 }
 
 impl std::str::FromStr for DatabasePruningMode {
@@ -100,6 +117,18 @@ impl std::str::FromStr for DatabasePruningMode {
 		match input {
 			"archive" => Ok(Self::Archive),
 			"archive-canonical" => Ok(Self::ArchiveCanonical),
+			sparse if sparse.starts_with("sparse:") => {
+				let invalid =
+					|| "Invalid sparse pruning mode specified, expected `sparse:EVERY:NUMBER`";
+				let (keep_every, keep_last) =
+					sparse["sparse:".len()..].split_once(':').ok_or_else(invalid)?;
+				let keep_every: u32 = keep_every.parse().map_err(|_| invalid())?;
+				let keep_last = keep_last.parse().map_err(|_| invalid())?;
+				if keep_every == 0 {
+					return Err(invalid().into())
+				}
+				Ok(Self::Sparse { keep_every, keep_last })
+			},
 			bc => bc
 				.parse()
 				.map_err(|_| "Invalid pruning mode specified".to_string())
@@ -114,6 +143,8 @@ impl Into<PruningMode> for DatabasePruningMode {
 			DatabasePruningMode::Archive => PruningMode::ArchiveAll,
 			DatabasePruningMode::ArchiveCanonical => PruningMode::ArchiveCanonical,
 			DatabasePruningMode::Custom(n) => PruningMode::blocks_pruning(n),
+			DatabasePruningMode::Sparse { keep_every, keep_last } =>
+				PruningMode::sparse_archive(keep_every, keep_last),
 		}
 	}
 }
@@ -123,7 +154,8 @@ impl Into<BlocksPruning> for DatabasePruningMode {
 		match self {
 			DatabasePruningMode::Archive => BlocksPruning::KeepAll,
 			DatabasePruningMode::ArchiveCanonical => BlocksPruning::KeepFinalized,
-			DatabasePruningMode::Custom(n) => BlocksPruning::Some(n),
+			DatabasePruningMode::Custom(n) | DatabasePruningMode::Sparse { keep_last: n, .. } =>
+				BlocksPruning::Some(n),
 		}
 	}
 }
@@ -161,5 +193,18 @@ mod tests {
 
 		assert!(matches!(dbg!(pruning.state_pruning), Some(DatabasePruningMode::ArchiveCanonical)));
 		assert!(matches!(pruning.blocks_pruning, DatabasePruningMode::ArchiveCanonical));
+
+		let Cli { pruning } = Cli::parse_from(["", "--state-pruning=sparse:1000:256"]);
+
+		assert!(matches!(
+			pruning.state_pruning,
+			Some(DatabasePruningMode::Sparse { keep_every: 1000, keep_last: 256 })
+		));
+		assert!(Cli::try_parse_from(["", "--state-pruning=sparse:0:256"]).is_err());
+		assert!(Cli::try_parse_from(["", "--state-pruning=sparse:1000"]).is_err());
+		assert!(Cli::parse_from(["", "--blocks-pruning=sparse:1000:256"])
+			.pruning
+			.blocks_pruning()
+			.is_err());
 	}
 }
@@ -44,12 +44,26 @@ use sp_runtime::{
 	traits::{Block as BlockT, Header, NumberFor},
 	DigestItem,
 };
-use std::{fmt::Debug, marker::PhantomData, sync::Arc};
+use std::{fmt::Debug, marker::PhantomData, num::NonZeroUsize, sync::Arc};
+
+/// Header whose slot and seal were checked by [`AuraVerifier`] ahead of its import.
+pub(crate) struct AuraAheadIntermediate<H, A> {
+	/// The authorities the seal was checked against.
+	pub(crate) authorities: Vec<A>,
+	pub(crate) pre_header: H,
+	pub(crate) slot: Slot,
+	pub(crate) seal: DigestItem,
+}
+
+/// Intermediate key for headers checked ahead of import.
+pub(crate) static AHEAD_INTERMEDIATE_KEY: &[u8] = b"aura_ahead";
 
 /// check a header has been signed by the right key. If the slot is too far in the future, an error
 /// will be returned. If it's successful, returns the pre-header and the digest item
 /// containing the seal.
 ///
+/// The slot and seal are not checked again if given as `checked_ahead`.
+///
 /// This digest item will always return `Some` when used with `as_aura_seal`.
 fn check_header<C, B: BlockT, P: Pair>(
 	client: &C,
@@ -58,14 +72,20 @@ fn check_header<C, B: BlockT, P: Pair>(
 	hash: B::Hash,
 	authorities: &[AuthorityId<P>],
 	check_for_equivocation: CheckForEquivocation,
+	checked_ahead: Option<(B::Header, Slot, DigestItem)>,
 ) -> Result<CheckedHeader<B::Header, (Slot, DigestItem)>, Error<B>>
 where
 	P::Public: Codec,
 	P::Signature: Codec,
 	C: sc_client_api::backend::AuxStore,
 {
-	let check_result =
-		crate::standalone::check_header_slot_and_seal::<B, P>(slot_now, header, authorities);
+	let check_result = match checked_ahead {
+		// The current slot only moves forward, so a header that was not too far in the future
+		// then is not now.
+		Some(checked) => Ok(checked),
+		None =>
+			crate::standalone::check_header_slot_and_seal::<B, P>(slot_now, header, authorities),
+	};
 
 	match check_result {
 		Ok((header, slot, seal)) => {
@@ -165,9 +185,9 @@ where
 #[async_trait::async_trait]
 impl<B: BlockT, C, P, CIDP> Verifier<B> for AuraVerifier<C, P, CIDP, NumberFor<B>>
 where
-	C: ProvideRuntimeApi<B> + Send + Sync + sc_client_api::backend::AuxStore,
+	C: ProvideRuntimeApi<B> + HeaderBackend<B> + Send + Sync + sc_client_api::backend::AuxStore,
 	C::Api: BlockBuilderApi<B> + AuraApi<B, AuthorityId<P>> + ApiExt<B>,
-	P: Pair,
+	P: Pair + 'static,
 	P::Public: Codec + Debug,
 	P::Signature: Codec,
 	CIDP: CreateInherentDataProviders<B, ()> + Send + Sync,
@@ -177,6 +197,13 @@ where
 		&self,
 		mut block: BlockImportParams<B>,
 	) -> Result<BlockImportParams<B>, String> {
+		// The header checked ahead, only trusted below if checked against the right authorities.
+		let checked_ahead = block
+			.remove_intermediate::<AuraAheadIntermediate<B::Header, AuthorityId<P>>>(
+				AHEAD_INTERMEDIATE_KEY,
+			)
+			.ok();
+
 		// Skip checks that include execution, if being told so or when importing only state.
 		//
 		// This is done for example when gap syncing and it is expected that the block after the gap
@@ -198,6 +225,9 @@ where
 			&self.compatibility_mode,
 		)
 		.map_err(|e| format!("Could not fetch authorities at {:?}: {}", parent_hash, e))?;
+		let checked_ahead = checked_ahead
+			.filter(|ahead| ahead.authorities == authorities)
+			.map(|ahead| (ahead.pre_header, ahead.slot, ahead.seal));
 
 		let create_inherent_data_providers = self
 			.create_inherent_data_providers
@@ -222,6 +252,7 @@ where
 			hash,
 			&authorities[..],
 			self.check_for_equivocation,
+			checked_ahead,
 		)
 		.map_err(|e| e.to_string())?;
 		match checked_header {
@@ -285,6 +316,52 @@ where
 			},
 		}
 	}
+
+	async fn verify_ahead(&self, mut block: BlockImportParams<B>) -> BlockImportParams<B> {
+		if block.with_state() || block.state_action.skip_execution_checks() {
+			return block
+		}
+
+		// The parent is usually not imported yet, the block is then expected to have the same
+		// authorities as a child of the best block. `verify` checks that guess once the parent is
+		// imported.
+		let parent_hash = match self.client.number(*block.header.parent_hash()) {
+			Ok(Some(_)) => *block.header.parent_hash(),
+			_ => self.client.info().best_hash,
+		};
+
+		let authorities = match authorities(
+			self.client.as_ref(),
+			parent_hash,
+			*block.header.number(),
+			&self.compatibility_mode,
+		) {
+			Ok(authorities) => authorities,
+			Err(_) => return block,
+		};
+
+		let slot_now = match self
+			.create_inherent_data_providers
+			.create_inherent_data_providers(parent_hash, ())
+			.await
+		{
+			Ok(create_inherent_data_providers) => create_inherent_data_providers.slot(),
+			Err(_) => return block,
+		};
+
+		if let Ok((pre_header, slot, seal)) = crate::standalone::check_header_slot_and_seal::<B, P>(
+			slot_now + 1,
+			block.header.clone(),
+			&authorities,
+		) {
+			block.insert_intermediate(
+				AHEAD_INTERMEDIATE_KEY,
+				AuraAheadIntermediate { authorities, pre_header, slot, seal },
+			);
+		}
+
+		block
+	}
 }
 
 /// Should we check for equivocation of a block author?
@@ -333,6 +410,10 @@ pub struct ImportQueueParams<'a, Block: BlockT, I, C, S, CIDP> {
 	///
 	/// If in doubt, use `Default::default()`.
 	pub compatibility_mode: CompatibilityMode<NumberFor<Block>>,
+	/// Number of threads checking seals ahead of their import.
+	///
+	/// If `None` is given seals are checked sequentially, see [`BasicQueue::new_pipelined`].
+	pub verification_threads: Option<NonZeroUsize>,
 }
 
 /// Start an import queue for the Aura consensus algorithm.
@@ -347,6 +428,7 @@ pub fn import_queue<P, Block, I, C, S, CIDP>(
 		check_for_equivocation,
 		telemetry,
 		compatibility_mode,
+		verification_threads,
 	}: ImportQueueParams<Block, I, C, S, CIDP>,
 ) -> Result<DefaultImportQueue<Block>, sp_consensus::Error>
 where
@@ -376,7 +458,23 @@ where
 		compatibility_mode,
 	});
 
-	Ok(BasicQueue::new(verifier, Box::new(block_import), justification_import, spawner, registry))
+	Ok(match verification_threads {
+		Some(verification_threads) => BasicQueue::new_pipelined(
+			verifier,
+			Box::new(block_import),
+			justification_import,
+			spawner,
+			registry,
+			verification_threads,
+		),
+		None => BasicQueue::new(
+			verifier,
+			Box::new(block_import),
+			justification_import,
+			spawner,
+			registry,
+		),
+	})
 }
 
 /// Parameters of [`build_verifier`].
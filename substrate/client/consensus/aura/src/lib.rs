@@ -550,19 +550,19 @@ mod tests {
 	use parking_lot::Mutex;
 	use sc_block_builder::BlockBuilderBuilder;
 	use sc_client_api::BlockchainEvents;
-	use sc_consensus::BoxJustificationImport;
+	use sc_consensus::{BoxJustificationImport, Verifier};
 	use sc_consensus_slots::{BackoffAuthoringOnFinalizedHeadLagging, SimpleSlotWorker};
 	use sc_keystore::LocalKeystore;
 	use sc_network_test::{Block as TestBlock, *};
 	use sp_application_crypto::{key_types::AURA, AppCrypto};
 	use sp_consensus::{DisableProofRecording, NoNetwork as DummyOracle, Proposal};
-	use sp_consensus_aura::sr25519::AuthorityPair;
+	use sp_consensus_aura::sr25519::{AuthorityPair, AuthoritySignature};
 	use sp_inherents::InherentData;
 	use sp_keyring::sr25519::Keyring;
 	use sp_keystore::Keystore;
 	use sp_runtime::{
 		traits::{Block as BlockT, Header as _},
-		Digest,
+		Digest, DigestItem,
 	};
 	use sp_timestamp::Timestamp;
 	use std::{
@@ -880,4 +880,75 @@ mod tests {
 		// The returned block should be imported and we should be able to get its header by now.
 		assert!(client.header(res.block.hash()).unwrap().is_some());
 	}
+
+	#[tokio::test]
+	async fn headers_checked_ahead_are_only_reused_for_the_same_authorities() {
+		let net = AuraTestNet::new(1);
+
+		let peer = &net.peers()[0];
+		let client = peer.client().as_client();
+		let verifier = net.make_verifier(peer.client().clone(), &());
+
+		let genesis_hash = client.chain_info().genesis_hash;
+		let authorities: Vec<AuthorityId<AuthorityPair>> =
+			crate::authorities(&*client, genesis_hash, 1, &CompatibilityMode::None).unwrap();
+
+		// build a block sealed by the expected slot author.
+		let slot = Slot::from(999);
+		let author = standalone::slot_author::<AuthorityPair>(slot, &authorities).unwrap();
+		let keyring = Keyring::iter()
+			.find(|k| AuthorityId::<AuthorityPair>::from(k.public()) == *author)
+			.unwrap();
+
+		let mut header = BlockBuilderBuilder::new(&*client)
+			.on_parent_block(genesis_hash)
+			.with_parent_block_number(0)
+			.with_inherent_digests(Digest {
+				logs: vec![standalone::pre_digest::<AuthorityPair>(slot)],
+			})
+			.build()
+			.unwrap()
+			.build()
+			.unwrap()
+			.block
+			.header;
+		let signature = AuthoritySignature::from(keyring.pair().sign(header.hash().as_ref()));
+		let seal = <DigestItem as CompatibleDigestItem<AuthoritySignature>>::aura_seal(signature);
+		header.digest_mut().push(seal.clone());
+
+		let verified = verifier
+			.verify(BlockImportParams::new(BlockOrigin::NetworkInitialSync, header.clone()))
+			.await
+			.unwrap();
+
+		// the header checked ahead is reused for the same authorities.
+		let block = verifier
+			.verify_ahead(BlockImportParams::new(BlockOrigin::NetworkInitialSync, header.clone()))
+			.await;
+		assert!(block
+			.get_intermediate::<import_queue::AuraAheadIntermediate<Header, AuthorityId<AuthorityPair>>>(
+				import_queue::AHEAD_INTERMEDIATE_KEY,
+			)
+			.is_ok());
+
+		let verified_ahead = verifier.verify(block).await.unwrap();
+		assert_eq!(verified_ahead.header, verified.header);
+		assert_eq!(verified_ahead.post_digests, vec![seal.clone()]);
+
+		// the header is checked again if it was checked ahead against other authorities.
+		let mut block = BlockImportParams::new(BlockOrigin::NetworkInitialSync, header.clone());
+		block.insert_intermediate(
+			import_queue::AHEAD_INTERMEDIATE_KEY,
+			import_queue::AuraAheadIntermediate {
+				authorities: authorities[1..].to_vec(),
+				pre_header: client.expect_header(genesis_hash).unwrap(),
+				slot,
+				seal: seal.clone(),
+			},
+		);
+
+		let verified_again = verifier.verify(block).await.unwrap();
+		assert_eq!(verified_again.header, verified.header);
+		assert_eq!(verified_again.post_digests, vec![seal]);
+	}
 }
@@ -246,6 +246,7 @@ mod tests {
 			offchain_tx_pool_factory: OffchainTransactionPoolFactory::new(
 				RejectAllTxPool::default(),
 			),
+			verification_threads: None,
 		})
 		.unwrap();
 
@@ -40,6 +40,16 @@ pub fn block_weight_key<H: Encode>(block_hash: H) -> Vec<u8> {
 	(b"block_weight", block_hash).encode()
 }
 
+/// The aux storage keys that should be kept when the chain is copied up to the given finalized
+/// block, e.g. when the database is converted.
+pub fn aux_keys<H: Encode>(finalized_hash: H) -> Vec<Vec<u8>> {
+	vec![
+		BABE_EPOCH_CHANGES_VERSION.to_vec(),
+		BABE_EPOCH_CHANGES_KEY.to_vec(),
+		block_weight_key(finalized_hash),
+	]
+}
+
 fn load_decode<B, T>(backend: &B, key: &[u8]) -> ClientResult<Option<T>>
 where
 	B: AuxStore,
@@ -69,6 +69,7 @@
 use std::{
 	collections::HashSet,
 	future::Future,
+	num::NonZeroUsize,
 	ops::{Deref, DerefMut},
 	pin::Pin,
 	sync::Arc,
@@ -375,6 +376,17 @@ pub struct BabeIntermediate<B: BlockT> {
 /// Intermediate key for Babe engine.
 pub static INTERMEDIATE_KEY: &[u8] = b"babe1";
 
+/// Header checked by [`BabeVerifier`] ahead of its import.
+struct BabeAheadIntermediate<B: BlockT> {
+	/// The epoch the header was checked against.
+	epoch: Epoch,
+	pre_header: B::Header,
+	verified_info: verification::VerifiedHeaderInfo,
+}
+
+/// Intermediate key for headers checked ahead of import.
+static AHEAD_INTERMEDIATE_KEY: &[u8] = b"babe_ahead";
+
 /// Read configuration from the runtime state at current best block.
 pub fn configuration<B: BlockT, C>(client: &C) -> ClientResult<BabeConfiguration>
 where
@@ -1140,6 +1152,11 @@ where
 			block.body,
 		);
 
+		// The header checked ahead, only trusted below if checked against the right epoch.
+		let checked_ahead = block
+			.remove_intermediate::<BabeAheadIntermediate<Block>>(AHEAD_INTERMEDIATE_KEY)
+			.ok();
+
 		let hash = block.header.hash();
 		let parent_hash = *block.header.parent_hash();
 
@@ -1194,16 +1211,26 @@ where
 				.viable_epoch(&epoch_descriptor, |slot| Epoch::genesis(&self.config, slot))
 				.ok_or(Error::<Block>::FetchEpoch(parent_hash))?;
 
-			// We add one to the current slot to allow for some small drift.
-			// FIXME #1019 in the future, alter this queue to allow deferring of headers
-			let v_params = verification::VerificationParams {
-				header: block.header.clone(),
-				pre_digest: Some(pre_digest),
-				slot_now: slot_now + 1,
-				epoch: viable_epoch.as_ref(),
+			let check_header = match checked_ahead {
+				// The current slot only moves forward, so a header that was not too far in the
+				// future then is not now.
+				Some(ahead) if ahead.epoch == *viable_epoch.as_ref() =>
+					CheckedHeader::Checked(ahead.pre_header, ahead.verified_info),
+				_ => {
+					// We add one to the current slot to allow for some small drift.
+					// FIXME #1019 in the future, alter this queue to allow deferring of headers
+					let v_params = verification::VerificationParams {
+						header: block.header.clone(),
+						pre_digest: Some(pre_digest),
+						slot_now: slot_now + 1,
+						epoch: viable_epoch.as_ref(),
+					};
+
+					verification::check_header::<Block>(v_params)?
+				},
 			};
 
-			(verification::check_header::<Block>(v_params)?, epoch_descriptor)
+			(check_header, epoch_descriptor)
 		};
 
 		match check_header {
@@ -1288,6 +1315,81 @@ where
 			},
 		}
 	}
+
+	async fn verify_ahead(&self, mut block: BlockImportParams<Block>) -> BlockImportParams<Block> {
+		let info = self.client.info();
+		let number = *block.header.number();
+
+		if info.block_gap.map_or(false, |gap| gap.start <= number && number <= gap.end) ||
+			block.with_state()
+		{
+			return block
+		}
+
+		let pre_digest = match find_pre_digest::<Block>(&block.header) {
+			Ok(pre_digest) => pre_digest,
+			Err(_) => return block,
+		};
+
+		// The parent is usually not imported yet, the block is then expected to be in the same
+		// epoch as a child of the best block. `verify` checks that guess once the parent is
+		// imported.
+		let parent_hash = *block.header.parent_hash();
+		let (parent_hash, parent_number) = match self.client.header_metadata(parent_hash) {
+			Ok(parent_header_metadata) => (parent_hash, parent_header_metadata.number),
+			Err(_) => (info.best_hash, info.best_number),
+		};
+
+		let slot_now = match self
+			.create_inherent_data_providers
+			.create_inherent_data_providers(parent_hash, ())
+			.await
+		{
+			Ok(create_inherent_data_providers) => create_inherent_data_providers.slot(),
+			Err(_) => return block,
+		};
+
+		let epoch = {
+			let epoch_changes = self.epoch_changes.shared_data();
+			let epoch = epoch_changes
+				.epoch_descriptor_for_child_of(
+					descendent_query(&*self.client),
+					&parent_hash,
+					parent_number,
+					pre_digest.slot(),
+				)
+				.ok()
+				.flatten()
+				.and_then(|epoch_descriptor| {
+					epoch_changes
+						.viable_epoch(&epoch_descriptor, |slot| Epoch::genesis(&self.config, slot))
+						.map(|viable_epoch| viable_epoch.as_ref().clone())
+				});
+
+			match epoch {
+				Some(epoch) => epoch,
+				None => return block,
+			}
+		};
+
+		let v_params = verification::VerificationParams {
+			header: block.header.clone(),
+			pre_digest: Some(pre_digest),
+			slot_now: slot_now + 1,
+			epoch: &epoch,
+		};
+
+		if let Ok(CheckedHeader::Checked(pre_header, verified_info)) =
+			verification::check_header::<Block>(v_params)
+		{
+			block.insert_intermediate(
+				AHEAD_INTERMEDIATE_KEY,
+				BabeAheadIntermediate::<Block> { epoch, pre_header, verified_info },
+			);
+		}
+
+		block
+	}
 }
 
 /// A block-import handler for BABE.
@@ -1796,6 +1898,10 @@ pub struct ImportQueueParams<'a, Block: BlockT, BI, Client, CIDP, SelectChain, S
 	///
 	/// Will be used when sending equivocation reports.
 	pub offchain_tx_pool_factory: OffchainTransactionPoolFactory<Block>,
+	/// Number of threads checking headers ahead of their import.
+	///
+	/// If `None` is given headers are checked sequentially, see [`BasicQueue::new_pipelined`].
+	pub verification_threads: Option<NonZeroUsize>,
 }
 
 /// Start an import queue for the BABE consensus algorithm.
@@ -1819,6 +1925,7 @@ pub fn import_queue<Block: BlockT, Client, SelectChain, BI, CIDP, Spawn>(
 		registry,
 		telemetry,
 		offchain_tx_pool_factory,
+		verification_threads,
 	}: ImportQueueParams<'_, Block, BI, Client, CIDP, SelectChain, Spawn>,
 ) -> ClientResult<(DefaultImportQueue<Block>, BabeWorkerHandle<Block>)>
 where
@@ -1855,10 +1962,25 @@ where
 
 	spawner.spawn_essential("babe-worker", Some("babe"), answer_requests.boxed());
 
-	Ok((
-		BasicQueue::new(verifier, Box::new(block_import), justification_import, spawner, registry),
-		BabeWorkerHandle(worker_tx),
-	))
+	let import_queue = match verification_threads {
+		Some(verification_threads) => BasicQueue::new_pipelined(
+			verifier,
+			Box::new(block_import),
+			justification_import,
+			spawner,
+			registry,
+			verification_threads,
+		),
+		None => BasicQueue::new(
+			verifier,
+			Box::new(block_import),
+			justification_import,
+			spawner,
+			registry,
+		),
+	};
+
+	Ok((import_queue, BabeWorkerHandle(worker_tx)))
 }
 
 /// Reverts protocol aux data to at most the last finalized block.
@@ -1355,3 +1355,86 @@ async fn allows_skipping_epochs_on_some_forks() {
 
 	assert_eq!(epoch_data, epoch3);
 }
+
+#[tokio::test]
+async fn headers_checked_ahead_are_only_reused_for_the_same_epoch() {
+	let net = BabeTestNet::new(1);
+
+	let peer = &net.peers()[0];
+	let data = peer.data.as_ref().expect("babe link set up during initialization");
+	let client = peer.client().as_client();
+	let verifier = net.make_verifier(peer.client().clone(), &peer.data).inner;
+
+	let mut proposer_factory = DummyFactory {
+		client: client.clone(),
+		epoch_changes: data.link.epoch_changes.clone(),
+		mutator: Arc::new(|_, _| ()),
+	};
+
+	let genesis_header = client.header(client.chain_info().genesis_hash).unwrap().unwrap();
+
+	// build a block sealed by the expected secondary slot author.
+	let slot = Slot::from(999);
+	let epoch = Epoch::genesis(&data.link.config, slot);
+	let author =
+		authorship::secondary_slot_author(slot, &epoch.authorities, epoch.randomness).unwrap();
+	let authority_index = epoch.authorities.iter().position(|(id, _)| id == author).unwrap();
+	let keyring = Sr25519Keyring::iter()
+		.find(|k| AuthorityId::from(k.public()) == *author)
+		.unwrap();
+
+	let pre_digest = Digest {
+		logs: vec![Item::babe_pre_digest(PreDigest::SecondaryPlain(SecondaryPlainPreDigest {
+			authority_index: authority_index as u32,
+			slot,
+		}))],
+	};
+
+	let mut proposer = proposer_factory.init(&genesis_header).await.unwrap();
+	let mut header = proposer.propose_with(pre_digest).await.unwrap().block.header;
+	let signature = AuthoritySignature::from(keyring.pair().sign(header.hash().as_ref()));
+	let seal = Item::babe_seal(signature);
+	header.digest_mut().push(seal.clone());
+
+	let verified = verifier
+		.verify(BlockImportParams::new(BlockOrigin::NetworkInitialSync, header.clone()))
+		.await
+		.unwrap();
+
+	// the header checked ahead is reused against the same epoch.
+	let block = verifier
+		.verify_ahead(BlockImportParams::new(BlockOrigin::NetworkInitialSync, header.clone()))
+		.await;
+	assert!(block
+		.get_intermediate::<BabeAheadIntermediate<TestBlock>>(AHEAD_INTERMEDIATE_KEY)
+		.is_ok());
+
+	let verified_ahead = verifier.verify(block).await.unwrap();
+	assert_eq!(verified_ahead.header, verified.header);
+	assert_eq!(verified_ahead.post_digests, vec![seal.clone()]);
+	assert!(verified_ahead
+		.get_intermediate::<BabeAheadIntermediate<TestBlock>>(AHEAD_INTERMEDIATE_KEY)
+		.is_err());
+
+	// the header is checked again if it was checked ahead against another epoch.
+	let mut other_epoch = epoch.clone();
+	other_epoch.randomness = [0xff; 32];
+
+	let mut block = BlockImportParams::new(BlockOrigin::NetworkInitialSync, header.clone());
+	block.insert_intermediate(
+		AHEAD_INTERMEDIATE_KEY,
+		BabeAheadIntermediate::<TestBlock> {
+			epoch: other_epoch,
+			pre_header: genesis_header,
+			verified_info: verification::VerifiedHeaderInfo {
+				pre_digest: header.digest().logs()[0].clone(),
+				seal: seal.clone(),
+				author: author.clone(),
+			},
+		},
+	);
+
+	let verified_again = verifier.verify(block).await.unwrap();
+	assert_eq!(verified_again.header, verified.header);
+	assert_eq!(verified_again.post_digests, vec![seal]);
+}
@@ -0,0 +1,242 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Throughput of the import queue when syncing a long range of blocks into a test client, with
+//! and without preparing the verification of blocks ahead of their import.
+
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput};
+use futures::{executor::block_on, future::poll_fn, task::Poll};
+use sc_block_builder::BlockBuilderBuilder;
+use sc_consensus::{
+	BasicQueue, BlockImport, BlockImportError, BlockImportParams, BlockImportStatus,
+	ForkChoiceStrategy, ImportQueue, IncomingBlock, Link, Verifier,
+};
+use sp_consensus::BlockOrigin;
+use sp_core::{sr25519, testing::TaskExecutor, Pair};
+use sp_runtime::{
+	traits::{Block as BlockT, Header as _},
+	ConsensusEngineId, DigestItem,
+};
+use std::{
+	num::NonZeroUsize,
+	sync::{
+		atomic::{AtomicUsize, Ordering},
+		Arc,
+	},
+};
+use substrate_test_runtime_client::{
+	prelude::*,
+	runtime::{Block, BlockNumber, Hash, Transfer},
+};
+
+/// Number of blocks imported per iteration.
+const BLOCKS: BlockNumber = 128;
+/// Number of transfers in every block.
+const TRANSFERS: u64 = 16;
+/// Engine sealing the blocks.
+const ENGINE_ID: ConsensusEngineId = *b"BNCH";
+/// Intermediate carrying the pre-hash whose seal was checked ahead of import.
+const CHECKED_SEAL_KEY: &[u8] = b"checked_seal";
+
+/// Verifier of the seal added by [`seal`], checking it ahead of import.
+struct SealVerifier;
+
+impl SealVerifier {
+	/// Strip the seal of the block and check it, returning the seal and the pre-hash.
+	fn check_seal(block: &mut BlockImportParams<Block>) -> Result<(DigestItem, Hash), String> {
+		let seal = block.header.digest_mut().pop().ok_or("Missing seal")?;
+		let signature = seal.seal_try_to::<sr25519::Signature>(&ENGINE_ID).ok_or("Invalid seal")?;
+		let pre_hash = block.header.hash();
+
+		if !sr25519::Pair::verify(&signature, pre_hash, &Sr25519Keyring::Alice.public()) {
+			return Err("Bad signature".into())
+		}
+
+		Ok((seal, pre_hash))
+	}
+}
+
+#[async_trait::async_trait]
+impl Verifier<Block> for SealVerifier {
+	async fn verify(
+		&self,
+		mut block: BlockImportParams<Block>,
+	) -> Result<BlockImportParams<Block>, String> {
+		let seal = match block.remove_intermediate::<Hash>(CHECKED_SEAL_KEY) {
+			// The seal was already checked, as long as the header is the same.
+			Ok(pre_hash) => {
+				let seal = block.header.digest_mut().pop().ok_or("Missing seal")?;
+				if block.header.hash() != pre_hash {
+					return Err("Seal checked for another header".into())
+				}
+				seal
+			},
+			Err(_) => Self::check_seal(&mut block)?.0,
+		};
+
+		block.post_digests.push(seal);
+		block.fork_choice = Some(ForkChoiceStrategy::LongestChain);
+		Ok(block)
+	}
+
+	async fn verify_ahead(&self, mut block: BlockImportParams<Block>) -> BlockImportParams<Block> {
+		// The seal is put back, `verify` strips it again.
+		if let Ok((seal, pre_hash)) = Self::check_seal(&mut block) {
+			block.header.digest_mut().push(seal);
+			block.insert_intermediate(CHECKED_SEAL_KEY, pre_hash);
+		}
+
+		block
+	}
+}
+
+/// Seal the given header, returning the seal.
+fn seal(header: &<Block as BlockT>::Header) -> DigestItem {
+	let signature = Sr25519Keyring::Alice.pair().sign(header.hash().as_ref());
+	DigestItem::Seal(ENGINE_ID, signature.0.to_vec())
+}
+
+/// Build a chain of sealed blocks full of transfers.
+fn chain() -> Vec<IncomingBlock<Block>> {
+	let client = Arc::new(substrate_test_runtime_client::new());
+	let mut nonce = 0;
+
+	(0..BLOCKS)
+		.map(|_| {
+			let info = client.chain_info();
+			let mut builder = BlockBuilderBuilder::new(&*client)
+				.on_parent_block(info.best_hash)
+				.with_parent_block_number(info.best_number)
+				.build()
+				.expect("best block is in the chain");
+			for _ in 0..TRANSFERS {
+				builder
+					.push_transfer(Transfer {
+						from: Sr25519Keyring::Alice.into(),
+						to: Sr25519Keyring::Bob.into(),
+						amount: 1,
+						nonce,
+					})
+					.expect("Alice can pay for the transfer");
+				nonce += 1;
+			}
+			let (header, body) = builder.build().expect("block builds").block.deconstruct();
+
+			let seal = seal(&header);
+			let mut import = BlockImportParams::new(BlockOrigin::Own, header.clone());
+			import.body = Some(body.clone());
+			import.post_digests.push(seal.clone());
+			import.fork_choice = Some(ForkChoiceStrategy::LongestChain);
+			block_on(client.import_block(import)).expect("block imports");
+
+			let mut header = header;
+			header.digest_mut().push(seal);
+			IncomingBlock {
+				hash: header.hash(),
+				header: Some(header),
+				body: Some(body),
+				indexed_body: None,
+				justifications: None,
+				origin: None,
+				allow_missing_state: false,
+				import_existing: false,
+				state: None,
+				skip_execution: false,
+			}
+		})
+		.collect()
+}
+
+#[derive(Default)]
+struct CountingLink {
+	processed: AtomicUsize,
+}
+
+impl Link<Block> for CountingLink {
+	fn blocks_processed(
+		&self,
+		_imported: usize,
+		count: usize,
+		results: Vec<(Result<BlockImportStatus<BlockNumber>, BlockImportError>, Hash)>,
+	) {
+		assert!(results.iter().all(|(r, _)| r.is_ok()), "blocks import");
+		self.processed.fetch_add(count, Ordering::Relaxed);
+	}
+}
+
+fn import_chain(mut queue: BasicQueue<Block>, blocks: Vec<IncomingBlock<Block>>) {
+	let link = CountingLink::default();
+	let target = blocks.len();
+	queue.service_ref().import_blocks(BlockOrigin::NetworkInitialSync, blocks);
+
+	block_on(poll_fn(|cx| {
+		queue.poll_actions(cx, &link);
+
+		if link.processed.load(Ordering::Relaxed) >= target {
+			Poll::Ready(())
+		} else {
+			Poll::Pending
+		}
+	}));
+}
+
+fn import_queue_throughput(c: &mut Criterion) {
+	let spawner = TaskExecutor::new();
+	let blocks = chain();
+	let mut group = c.benchmark_group("import_queue");
+	group.throughput(Throughput::Elements(BLOCKS as u64));
+	group.sample_size(10);
+
+	let mut run = |id: BenchmarkId, verification_threads: Option<NonZeroUsize>| {
+		group.bench_function(id, |b| {
+			b.iter_batched(
+				|| Arc::new(substrate_test_runtime_client::new()),
+				|client| {
+					let queue = match verification_threads {
+						None =>
+							BasicQueue::new(SealVerifier, Box::new(client), None, &spawner, None),
+						Some(threads) => BasicQueue::new_pipelined(
+							SealVerifier,
+							Box::new(client),
+							None,
+							&spawner,
+							None,
+							threads,
+						),
+					};
+					import_chain(queue, blocks.clone())
+				},
+				BatchSize::PerIteration,
+			)
+		});
+	};
+
+	run(BenchmarkId::new("sequential", 1), None);
+
+	for threads in [1, 2, 4] {
+		run(
+			BenchmarkId::new("pipelined", threads),
+			Some(NonZeroUsize::new(threads).expect("thread count is not zero")),
+		);
+	}
+
+	group.finish();
+}
+
+criterion_group!(benches, import_queue_throughput);
+criterion_main!(benches);
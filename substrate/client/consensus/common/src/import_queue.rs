@@ -101,6 +101,20 @@ pub trait Verifier<B: BlockT>: Send + Sync {
 	/// Verify the given block data and return the `BlockImportParams` to
 	/// continue the block import process.
 	async fn verify(&self, block: BlockImportParams<B>) -> Result<BlockImportParams<B>, String>;
+
+	/// Prepare the verification of the given block before its parent is imported.
+	///
+	/// A pipelined [`BasicQueue`] calls this for upcoming blocks on dedicated threads, while the
+	/// blocks before them are imported. The work done here, e.g. checking the seal against the
+	/// authorities expected for the block, can be passed to [`Self::verify`] through the
+	/// intermediates of the block. As the expectations may turn out to be wrong once the parent is
+	/// imported, [`Self::verify`] is still called for every block and must only reuse that work
+	/// after checking it was done against the right data.
+	///
+	/// By default, nothing is done ahead of [`Self::verify`].
+	async fn verify_ahead(&self, block: BlockImportParams<B>) -> BlockImportParams<B> {
+		block
+	}
 }
 
 /// Blocks import queue API.
@@ -290,16 +304,38 @@ pub(crate) struct SingleBlockImportParameters<Block: BlockT> {
 	verification_time: Duration,
 }
 
-/// Single block import function with metering.
-pub(crate) async fn verify_single_block_metered<B: BlockT, V: Verifier<B>>(
-	import_handle: &impl BlockImport<B, Error = ConsensusError>,
+/// A block that passed the sanity checks and is ready to be verified.
+pub(crate) struct PreparedBlock<Block: BlockT> {
+	check_params: BlockCheckParams<Block>,
+	import_block: BlockImportParams<Block>,
+	hash: Block::Hash,
+	block_origin: Option<RuntimeOrigin>,
+}
+
+impl<Block: BlockT> PreparedBlock<Block> {
+	/// Parameters to pass to [`BlockImport::check_block`] for this block.
+	pub(crate) fn check_params(&self) -> &BlockCheckParams<Block> {
+		&self.check_params
+	}
+
+	/// The peer this block was received from.
+	pub(crate) fn block_origin(&self) -> Option<RuntimeOrigin> {
+		self.block_origin
+	}
+
+	/// Run [`Verifier::verify_ahead`] on this block.
+	pub(crate) async fn verify_ahead<V: Verifier<Block>>(mut self, verifier: &V) -> Self {
+		self.import_block = verifier.verify_ahead(self.import_block).await;
+		self
+	}
+}
+
+/// Build the parameters required for checking and verifying the given block.
+pub(crate) fn prepare_single_block<B: BlockT>(
 	block_origin: BlockOrigin,
 	block: IncomingBlock<B>,
-	verifier: &V,
-	metrics: Option<&Metrics>,
-) -> Result<SingleBlockVerificationOutcome<B>, BlockImportError> {
+) -> Result<PreparedBlock<B>, BlockImportError> {
 	let peer = block.origin;
-	let justifications = block.justifications;
 
 	let Some(header) = block.header else {
 		if let Some(ref peer) = peer {
@@ -312,38 +348,19 @@ pub(crate) async fn verify_single_block_metered<B: BlockT, V: Verifier<B>>(
 
 	trace!(target: LOG_TARGET, "Header {} has {:?} logs", block.hash, header.digest().logs().len());
 
-	let number = *header.number();
 	let hash = block.hash;
-	let parent_hash = *header.parent_hash();
-
-	match import_handler::<B>(
-		number,
+	let check_params = BlockCheckParams {
 		hash,
-		parent_hash,
-		peer,
-		import_handle
-			.check_block(BlockCheckParams {
-				hash,
-				number,
-				parent_hash,
-				allow_missing_state: block.allow_missing_state,
-				import_existing: block.import_existing,
-				allow_missing_parent: block.state.is_some(),
-			})
-			.await,
-	)? {
-		BlockImportStatus::ImportedUnknown { .. } => (),
-		r => {
-			// Any other successful result means that the block is already imported.
-			return Ok(SingleBlockVerificationOutcome::Imported(r))
-		},
-	}
-
-	let started = Instant::now();
+		number: *header.number(),
+		parent_hash: *header.parent_hash(),
+		allow_missing_state: block.allow_missing_state,
+		import_existing: block.import_existing,
+		allow_missing_parent: block.state.is_some(),
+	};
 
 	let mut import_block = BlockImportParams::new(block_origin, header);
 	import_block.body = block.body;
-	import_block.justifications = justifications;
+	import_block.justifications = block.justifications;
 	import_block.post_hash = Some(hash);
 	import_block.import_existing = block.import_existing;
 	import_block.indexed_body = block.indexed_body;
@@ -357,6 +374,43 @@ pub(crate) async fn verify_single_block_metered<B: BlockT, V: Verifier<B>>(
 		import_block.state_action = StateAction::ExecuteIfPossible;
 	}
 
+	Ok(PreparedBlock { check_params, import_block, hash, block_origin: peer })
+}
+
+/// Check whether the block still needs to be verified and imported.
+///
+/// Returns `None` if it does, or the import status of the already imported block otherwise.
+pub(crate) async fn check_single_block<B: BlockT>(
+	import_handle: &impl BlockImport<B, Error = ConsensusError>,
+	check_params: BlockCheckParams<B>,
+	block_origin: Option<RuntimeOrigin>,
+) -> Result<Option<BlockImportStatus<NumberFor<B>>>, BlockImportError> {
+	let BlockCheckParams { hash, number, parent_hash, .. } = check_params;
+
+	match import_handler::<B>(
+		number,
+		hash,
+		parent_hash,
+		block_origin,
+		import_handle.check_block(check_params).await,
+	)? {
+		BlockImportStatus::ImportedUnknown { .. } => Ok(None),
+		// Any other successful result means that the block is already imported.
+		r => Ok(Some(r)),
+	}
+}
+
+/// Run the verifier on a prepared block with metering.
+pub(crate) async fn verify_prepared_block_metered<B: BlockT, V: Verifier<B>>(
+	block: PreparedBlock<B>,
+	verifier: &V,
+	metrics: Option<&Metrics>,
+) -> Result<SingleBlockImportParameters<B>, BlockImportError> {
+	let PreparedBlock { check_params, import_block, hash, block_origin: peer } = block;
+	let number = check_params.number;
+
+	let started = Instant::now();
+
 	let import_block = verifier.verify(import_block).await.map_err(|msg| {
 		if let Some(ref peer) = peer {
 			trace!(
@@ -381,12 +435,29 @@ pub(crate) async fn verify_single_block_metered<B: BlockT, V: Verifier<B>>(
 		metrics.report_verification(true, verification_time);
 	}
 
-	Ok(SingleBlockVerificationOutcome::Verified(SingleBlockImportParameters {
-		import_block,
-		hash,
-		block_origin: peer,
-		verification_time,
-	}))
+	Ok(SingleBlockImportParameters { import_block, hash, block_origin: peer, verification_time })
+}
+
+/// Single block import function with metering.
+pub(crate) async fn verify_single_block_metered<B: BlockT, V: Verifier<B>>(
+	import_handle: &impl BlockImport<B, Error = ConsensusError>,
+	block_origin: BlockOrigin,
+	block: IncomingBlock<B>,
+	verifier: &V,
+	metrics: Option<&Metrics>,
+) -> Result<SingleBlockVerificationOutcome<B>, BlockImportError> {
+	let block = prepare_single_block(block_origin, block)?;
+
+	if let Some(status) =
+		check_single_block(import_handle, block.check_params().clone(), block.block_origin())
+			.await?
+	{
+		return Ok(SingleBlockVerificationOutcome::Imported(status))
+	}
+
+	verify_prepared_block_metered(block, verifier, metrics)
+		.await
+		.map(SingleBlockVerificationOutcome::Verified)
 }
 
 pub(crate) async fn import_single_block_metered<Block: BlockT>(
@@ -16,31 +16,43 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 use futures::{
+	executor::ThreadPool,
+	future::RemoteHandle,
 	prelude::*,
-	task::{Context, Poll},
+	task::{Context, Poll, SpawnExt},
 };
 use log::{debug, trace};
 use prometheus_endpoint::Registry;
 use sc_utils::mpsc::{tracing_unbounded, TracingUnboundedReceiver, TracingUnboundedSender};
-use sp_consensus::BlockOrigin;
+use sp_consensus::{error::Error as ConsensusError, BlockOrigin};
 use sp_runtime::{
 	traits::{Block as BlockT, Header as HeaderT, NumberFor},
 	Justification, Justifications,
 };
-use std::pin::Pin;
+use std::{collections::VecDeque, num::NonZeroUsize, pin::Pin, sync::Arc};
 
 use crate::{
+	block_import::BlockCheckParams,
 	import_queue::{
 		buffered_link::{self, BufferedLinkReceiver, BufferedLinkSender},
-		import_single_block_metered, verify_single_block_metered, BlockImportError,
+		check_single_block, import_single_block_metered, prepare_single_block,
+		verify_prepared_block_metered, verify_single_block_metered, BlockImportError,
 		BlockImportStatus, BoxBlockImport, BoxJustificationImport, ImportQueue, ImportQueueService,
-		IncomingBlock, Link, RuntimeOrigin, SingleBlockVerificationOutcome, Verifier, LOG_TARGET,
+		IncomingBlock, Link, PreparedBlock, RuntimeOrigin, SingleBlockVerificationOutcome,
+		Verifier, LOG_TARGET,
 	},
 	metrics::Metrics,
 };
 
+/// Maximum number of blocks prepared for verification ahead of the block currently being imported.
+const MAX_BLOCKS_VERIFIED_AHEAD: usize = 64;
+
 /// Interface to a basic block import queue that is importing blocks sequentially in a separate
 /// task, with plugable verification.
+///
+/// When created with [`BasicQueue::new_pipelined`], blocks are still verified and imported
+/// sequentially, but the verification of upcoming blocks is
+/// [prepared](Verifier::verify_ahead) on a dedicated thread pool while the current one is imported.
 pub struct BasicQueue<B: BlockT> {
 	/// Handle for sending justification and block import messages to the background task.
 	handle: BasicQueueHandle<B>,
@@ -67,6 +79,64 @@ impl<B: BlockT> BasicQueue<B> {
 		spawner: &impl sp_core::traits::SpawnEssentialNamed,
 		prometheus_registry: Option<&Registry>,
 	) -> Self
+	where
+		V: Verifier<B> + 'static,
+	{
+		Self::with_pipeline(
+			verifier,
+			block_import,
+			justification_import,
+			spawner,
+			prometheus_registry,
+			None,
+		)
+	}
+
+	/// Instantiate a new basic queue that prepares the verification of blocks ahead of their
+	/// import.
+	///
+	/// [`Verifier::verify_ahead`] is called for up to 64 blocks following the one currently being
+	/// imported, in parallel on `verification_threads` threads. This is mainly useful when syncing
+	/// long ranges of historical blocks, with a verifier doing its expensive checks ahead.
+	pub fn new_pipelined<V>(
+		verifier: V,
+		block_import: BoxBlockImport<B>,
+		justification_import: Option<BoxJustificationImport<B>>,
+		spawner: &impl sp_core::traits::SpawnEssentialNamed,
+		prometheus_registry: Option<&Registry>,
+		verification_threads: NonZeroUsize,
+	) -> Self
+	where
+		V: Verifier<B> + 'static,
+	{
+		let pipeline = VerificationPipeline::new(verification_threads)
+			.map_err(|err| {
+				log::warn!(
+					target: LOG_TARGET,
+					"Failed to start block verification threads, verifying sequentially: {}",
+					err,
+				);
+			})
+			.ok();
+
+		Self::with_pipeline(
+			verifier,
+			block_import,
+			justification_import,
+			spawner,
+			prometheus_registry,
+			pipeline,
+		)
+	}
+
+	fn with_pipeline<V>(
+		verifier: V,
+		block_import: BoxBlockImport<B>,
+		justification_import: Option<BoxJustificationImport<B>>,
+		spawner: &impl sp_core::traits::SpawnEssentialNamed,
+		prometheus_registry: Option<&Registry>,
+		pipeline: Option<VerificationPipeline>,
+	) -> Self
 	where
 		V: Verifier<B> + 'static,
 	{
@@ -86,6 +156,7 @@ impl<B: BlockT> BasicQueue<B> {
 			block_import,
 			justification_import,
 			metrics,
+			pipeline,
 		);
 
 		spawner.spawn_essential_blocking(
@@ -220,13 +291,16 @@ mod worker_messages {
 /// to give other futures the possibility to be run.
 ///
 /// Returns when `block_import` ended.
-async fn block_import_process<B: BlockT>(
+async fn block_import_process<B: BlockT, V: Verifier<B> + 'static>(
 	mut block_import: BoxBlockImport<B>,
-	verifier: impl Verifier<B>,
+	verifier: V,
 	result_sender: BufferedLinkSender<B>,
 	mut block_import_receiver: TracingUnboundedReceiver<worker_messages::ImportBlocks<B>>,
 	metrics: Option<Metrics>,
+	pipeline: Option<VerificationPipeline>,
 ) {
+	let verifier = Arc::new(verifier);
+
 	loop {
 		let worker_messages::ImportBlocks(origin, blocks) = match block_import_receiver.next().await
 		{
@@ -240,8 +314,15 @@ async fn block_import_process<B: BlockT>(
 			},
 		};
 
-		let res =
-			import_many_blocks(&mut block_import, origin, blocks, &verifier, metrics.clone()).await;
+		let res = import_many_blocks(
+			&mut block_import,
+			origin,
+			blocks,
+			&verifier,
+			pipeline.as_ref(),
+			metrics.clone(),
+		)
+		.await;
 
 		result_sender.blocks_processed(res.imported, res.block_count, res.results);
 	}
@@ -260,6 +341,7 @@ impl<B: BlockT> BlockImportWorker<B> {
 		block_import: BoxBlockImport<B>,
 		justification_import: Option<BoxJustificationImport<B>>,
 		metrics: Option<Metrics>,
+		pipeline: Option<VerificationPipeline>,
 	) -> (
 		impl Future<Output = ()> + Send,
 		TracingUnboundedSender<worker_messages::ImportJustification<B>>,
@@ -292,6 +374,7 @@ impl<B: BlockT> BlockImportWorker<B> {
 				worker.result_sender.clone(),
 				block_import_receiver,
 				worker.metrics.clone(),
+				pipeline,
 			);
 			futures::pin_mut!(block_import_process);
 
@@ -379,15 +462,87 @@ struct ImportManyBlocksResult<B: BlockT> {
 	results: Vec<(Result<BlockImportStatus<NumberFor<B>>, BlockImportError>, B::Hash)>,
 }
 
+/// Threads preparing the verification of blocks ahead of their import.
+struct VerificationPipeline {
+	pool: ThreadPool,
+}
+
+impl VerificationPipeline {
+	fn new(threads: NonZeroUsize) -> std::io::Result<Self> {
+		let pool = ThreadPool::builder()
+			.pool_size(threads.get())
+			.name_prefix("block-verifier-")
+			.create()?;
+
+		Ok(Self { pool })
+	}
+
+	/// Start preparing the verification of the given block in the background.
+	fn verify_ahead<B: BlockT, V: Verifier<B> + 'static>(
+		&self,
+		block_origin: BlockOrigin,
+		block: IncomingBlock<B>,
+		verifier: &Arc<V>,
+	) -> Result<AheadVerification<B>, BlockImportError> {
+		let block = prepare_single_block(block_origin, block)?;
+		let check_params = block.check_params().clone();
+		let block_origin = block.block_origin();
+
+		let verifier = verifier.clone();
+		let prepared = self
+			.pool
+			.spawn_with_handle(async move { block.verify_ahead(&*verifier).await })
+			.map_err(|e| BlockImportError::Other(ConsensusError::Other(Box::new(e))))?;
+
+		Ok(AheadVerification { check_params, block_origin, prepared })
+	}
+}
+
+/// A block whose verification is being prepared ahead of its import.
+///
+/// Dropping it cancels the preparation.
+struct AheadVerification<B: BlockT> {
+	check_params: BlockCheckParams<B>,
+	block_origin: Option<RuntimeOrigin>,
+	prepared: RemoteHandle<PreparedBlock<B>>,
+}
+
+impl<B: BlockT> AheadVerification<B> {
+	/// Verify and import the block once its parent is imported.
+	///
+	/// The block is checked once its turn comes, as its parent was not yet imported when the
+	/// preparation started.
+	async fn import<V: Verifier<B>>(
+		self,
+		import_handle: &mut BoxBlockImport<B>,
+		verifier: &V,
+		metrics: Option<&Metrics>,
+	) -> Result<BlockImportStatus<NumberFor<B>>, BlockImportError> {
+		if let Some(import_status) =
+			check_single_block(&*import_handle, self.check_params, self.block_origin).await?
+		{
+			return Ok(import_status)
+		}
+
+		let import_parameters =
+			verify_prepared_block_metered(self.prepared.await, verifier, metrics).await?;
+		import_single_block_metered(import_handle, import_parameters, metrics).await
+	}
+}
+
 /// Import several blocks at once, returning import result for each block.
 ///
+/// With a `pipeline`, the verification of the blocks following the one being imported is prepared
+/// in parallel.
+///
 /// This will yield after each imported block once, to ensure that other futures can
 /// be called as well.
-async fn import_many_blocks<B: BlockT, V: Verifier<B>>(
+async fn import_many_blocks<B: BlockT, V: Verifier<B> + 'static>(
 	import_handle: &mut BoxBlockImport<B>,
 	blocks_origin: BlockOrigin,
 	blocks: Vec<IncomingBlock<B>>,
-	verifier: &V,
+	verifier: &Arc<V>,
+	pipeline: Option<&VerificationPipeline>,
 	metrics: Option<Metrics>,
 ) -> ImportManyBlocksResult<B> {
 	let count = blocks.len();
@@ -407,40 +562,69 @@ async fn import_many_blocks<B: BlockT, V: Verifier<B>>(
 	let mut results = vec![];
 	let mut has_error = false;
 	let mut blocks = blocks.into_iter();
+	let mut verifying_ahead = VecDeque::new();
 
 	// Blocks in the response/drain should be in ascending order.
 	loop {
-		// Is there any block left to import?
-		let block = match blocks.next() {
-			Some(b) => b,
-			None => {
+		// Keep the pipeline filled, unless the remaining blocks are going to be cancelled anyway.
+		if let Some(pipeline) = pipeline.filter(|_| !has_error) {
+			while verifying_ahead.len() < MAX_BLOCKS_VERIFIED_AHEAD {
+				let Some(block) = blocks.next() else { break };
+
+				let block_number = block.header.as_ref().map(|h| *h.number());
+				let block_hash = block.hash;
+				let verification = pipeline.verify_ahead(blocks_origin, block, verifier);
+				verifying_ahead.push_back((block_number, block_hash, verification));
+			}
+		}
+
+		let (block_number, block_hash, import_result) =
+			if let Some((block_number, block_hash, verification)) = verifying_ahead.pop_front() {
+				let import_result = if has_error {
+					Err(BlockImportError::Cancelled)
+				} else {
+					match verification {
+						Ok(verification) =>
+							verification.import(import_handle, &**verifier, metrics.as_ref()).await,
+						Err(e) => Err(e),
+					}
+				};
+
+				(block_number, block_hash, import_result)
+			} else if let Some(block) = blocks.next() {
+				let block_number = block.header.as_ref().map(|h| *h.number());
+				let block_hash = block.hash;
+				let import_result = if has_error {
+					Err(BlockImportError::Cancelled)
+				} else {
+					let verification_fut = verify_single_block_metered(
+						import_handle,
+						blocks_origin,
+						block,
+						&**verifier,
+						metrics.as_ref(),
+					);
+					match verification_fut.await {
+						Ok(SingleBlockVerificationOutcome::Imported(import_status)) =>
+							Ok(import_status),
+						Ok(SingleBlockVerificationOutcome::Verified(import_parameters)) => {
+							// The actual import.
+							import_single_block_metered(
+								import_handle,
+								import_parameters,
+								metrics.as_ref(),
+							)
+							.await
+						},
+						Err(e) => Err(e),
+					}
+				};
+
+				(block_number, block_hash, import_result)
+			} else {
 				// No block left to import, success!
 				return ImportManyBlocksResult { block_count: count, imported, results }
-			},
-		};
-
-		let block_number = block.header.as_ref().map(|h| *h.number());
-		let block_hash = block.hash;
-		let import_result = if has_error {
-			Err(BlockImportError::Cancelled)
-		} else {
-			let verification_fut = verify_single_block_metered(
-				import_handle,
-				blocks_origin,
-				block,
-				verifier,
-				metrics.as_ref(),
-			);
-			match verification_fut.await {
-				Ok(SingleBlockVerificationOutcome::Imported(import_status)) => Ok(import_status),
-				Ok(SingleBlockVerificationOutcome::Verified(import_parameters)) => {
-					// The actual import.
-					import_single_block_metered(import_handle, import_parameters, metrics.as_ref())
-						.await
-				},
-				Err(e) => Err(e),
-			}
-		};
+			};
 
 		if let Some(metrics) = metrics.as_ref() {
 			metrics.report_import::<B>(&import_result);
@@ -590,7 +774,7 @@ mod tests {
 		let (result_sender, mut result_port) = buffered_link::buffered_link(100_000);
 
 		let (worker, finality_sender, block_import_sender) =
-			BlockImportWorker::new(result_sender, (), Box::new(()), Some(Box::new(())), None);
+			BlockImportWorker::new(result_sender, (), Box::new(()), Some(Box::new(())), None, None);
 		futures::pin_mut!(worker);
 
 		let import_block = |n| {
@@ -682,4 +866,185 @@ mod tests {
 			]
 		);
 	}
+
+	const AHEAD_KEY: &[u8] = b"ahead";
+
+	/// Verifier preparing the verification of blocks ahead of their import, failing the given
+	/// block.
+	struct AheadVerifier {
+		fail_at: Option<BlockNumber>,
+		imported: Arc<Mutex<Vec<(Hash, BlockNumber)>>>,
+	}
+
+	#[async_trait::async_trait]
+	impl Verifier<Block> for AheadVerifier {
+		async fn verify(
+			&self,
+			mut block: BlockImportParams<Block>,
+		) -> Result<BlockImportParams<Block>, String> {
+			let number =
+				block.remove_intermediate::<BlockNumber>(AHEAD_KEY).map_err(|e| e.to_string())?;
+			assert_eq!(number, block.header.number);
+
+			// Unlike the preparation, the verification only runs once the parent is imported.
+			let imported = self.imported.lock();
+			assert!(
+				number == 1 || imported.iter().any(|(hash, _)| *hash == block.header.parent_hash)
+			);
+
+			if Some(number) == self.fail_at {
+				return Err("invalid seal".into())
+			}
+
+			Ok(block)
+		}
+
+		async fn verify_ahead(
+			&self,
+			mut block: BlockImportParams<Block>,
+		) -> BlockImportParams<Block> {
+			block.insert_intermediate(AHEAD_KEY, block.header.number);
+			block
+		}
+	}
+
+	/// Block import that only accepts blocks whose parent was imported before.
+	#[derive(Clone, Default)]
+	struct ChainImport {
+		imported: Arc<Mutex<Vec<(Hash, BlockNumber)>>>,
+	}
+
+	#[async_trait::async_trait]
+	impl BlockImport<Block> for ChainImport {
+		type Error = sp_consensus::Error;
+
+		async fn check_block(
+			&self,
+			block: BlockCheckParams<Block>,
+		) -> Result<ImportResult, Self::Error> {
+			let imported = self.imported.lock();
+			if imported.iter().any(|(hash, _)| *hash == block.hash) {
+				Ok(ImportResult::AlreadyInChain)
+			} else if block.number == 1 ||
+				imported.iter().any(|(hash, _)| *hash == block.parent_hash)
+			{
+				Ok(ImportResult::imported(false))
+			} else {
+				Ok(ImportResult::UnknownParent)
+			}
+		}
+
+		async fn import_block(
+			&self,
+			block: BlockImportParams<Block>,
+		) -> Result<ImportResult, Self::Error> {
+			assert!(block.intermediates.is_empty());
+			self.imported.lock().push((block.header.hash(), block.header.number));
+			Ok(ImportResult::imported(true))
+		}
+	}
+
+	fn chain(len: BlockNumber) -> Vec<IncomingBlock<Block>> {
+		let mut parent_hash = Hash::random();
+
+		(1..=len)
+			.map(|number| {
+				let header = Header {
+					parent_hash,
+					number,
+					extrinsics_root: Hash::random(),
+					state_root: Default::default(),
+					digest: Default::default(),
+				};
+				parent_hash = header.hash();
+
+				IncomingBlock {
+					hash: header.hash(),
+					header: Some(header),
+					body: None,
+					indexed_body: None,
+					justifications: None,
+					origin: None,
+					allow_missing_state: false,
+					import_existing: false,
+					state: None,
+					skip_execution: false,
+				}
+			})
+			.collect()
+	}
+
+	fn pipeline() -> VerificationPipeline {
+		VerificationPipeline::new(NonZeroUsize::new(4).unwrap()).unwrap()
+	}
+
+	#[test]
+	fn pipelined_import_imports_blocks_in_order() {
+		let block_import = ChainImport::default();
+		let blocks = chain(MAX_BLOCKS_VERIFIED_AHEAD as BlockNumber * 2);
+		let count = blocks.len();
+
+		let res = block_on(import_many_blocks(
+			&mut (Box::new(block_import.clone()) as BoxBlockImport<Block>),
+			BlockOrigin::NetworkInitialSync,
+			blocks.clone(),
+			&Arc::new(AheadVerifier { fail_at: None, imported: block_import.imported.clone() }),
+			Some(&pipeline()),
+			None,
+		));
+
+		assert_eq!(res.imported, count);
+		assert_eq!(res.block_count, count);
+		assert!(res.results.iter().all(|(r, _)| r.is_ok()));
+		assert_eq!(
+			*block_import.imported.lock(),
+			blocks
+				.iter()
+				.map(|b| (b.hash, *b.header.as_ref().unwrap().number()))
+				.collect::<Vec<_>>(),
+		);
+
+		// Importing the same blocks again only finds them in the chain.
+		let res = block_on(import_many_blocks(
+			&mut (Box::new(block_import.clone()) as BoxBlockImport<Block>),
+			BlockOrigin::NetworkInitialSync,
+			blocks,
+			&Arc::new(AheadVerifier { fail_at: None, imported: block_import.imported.clone() }),
+			Some(&pipeline()),
+			None,
+		));
+
+		assert_eq!(res.imported, count);
+		assert!(res
+			.results
+			.iter()
+			.all(|(r, _)| matches!(r, Ok(BlockImportStatus::ImportedKnown(_, _)))));
+		assert_eq!(block_import.imported.lock().len(), count);
+	}
+
+	#[test]
+	fn pipelined_import_cancels_blocks_after_failed_verification() {
+		let block_import = ChainImport::default();
+
+		let res = block_on(import_many_blocks(
+			&mut (Box::new(block_import.clone()) as BoxBlockImport<Block>),
+			BlockOrigin::NetworkInitialSync,
+			chain(10),
+			&Arc::new(AheadVerifier { fail_at: Some(4), imported: block_import.imported.clone() }),
+			Some(&pipeline()),
+			None,
+		));
+
+		assert_eq!(res.imported, 3);
+		assert_eq!(res.block_count, 10);
+		assert!(res.results[..3].iter().all(|(r, _)| r.is_ok()));
+		assert!(matches!(res.results[3].0, Err(BlockImportError::VerificationFailed(None, _))));
+		assert!(res.results[4..]
+			.iter()
+			.all(|(r, _)| matches!(r, Err(BlockImportError::Cancelled))));
+		assert_eq!(
+			block_import.imported.lock().iter().map(|(_, n)| *n).collect::<Vec<_>>(),
+			vec![1, 2, 3],
+		);
+	}
 }
@@ -460,6 +460,17 @@ where
 	write_aux(&[(BEST_JUSTIFICATION, &encoded_justification[..])])
 }
 
+/// The aux storage keys that should be kept when the chain is copied up to the finalized block,
+/// e.g. when the database is converted.
+pub fn aux_keys() -> Vec<Vec<u8>> {
+	vec![
+		VERSION_KEY.to_vec(),
+		SET_STATE_KEY.to_vec(),
+		AUTHORITY_SET_KEY.to_vec(),
+		BEST_JUSTIFICATION.to_vec(),
+	]
+}
+
 /// Fetch the justification for the latest block finalized by GRANDPA, if any.
 pub fn best_justification<B, Block>(
 	backend: &B,
@@ -127,7 +127,7 @@ mod voting_rule;
 pub mod warp_proof;
 
 pub use authorities::{AuthoritySet, AuthoritySetChanges, SharedAuthoritySet};
-pub use aux_schema::best_justification;
+pub use aux_schema::{aux_keys, best_justification};
 pub use communication::grandpa_protocol_name::standard_name as protocol_standard_name;
 pub use finality_grandpa::voter::report;
 pub use finality_proof::{FinalityProof, FinalityProofError, FinalityProofProvider};
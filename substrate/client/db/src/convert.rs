@@ -0,0 +1,479 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Conversion between the RocksDB and the ParityDB databases.
+//!
+//! ParityDB only keeps hashes of the keys in most of its columns, so its keys can't be enumerated.
+//! The conversion into RocksDB follows the canonical chain up to the finalized block instead and
+//! only copies the state of the finalized block, like a warp sync does.
+
+use std::{collections::HashMap, fs, io, path::Path, sync::Arc};
+
+use codec::Decode;
+use hash_db::{Hasher, Prefix};
+use kvdb::KeyValueDB;
+use parking_lot::Mutex;
+use sc_client_api::{backend::Backend as _, leaves::LeafSet};
+use sc_state_db::{PruningMode, StateDb};
+use sp_blockchain::HeaderBackend as _;
+use sp_core::storage::{well_known_keys::DEFAULT_CHILD_STORAGE_KEY_PREFIX, ChildInfo};
+use sp_database::{Database, Transaction};
+use sp_runtime::traits::{Block as BlockT, HashingFor, Header as HeaderT, UniqueSaturatedInto};
+use sp_state_machine::{Backend as StateBackend, DBValue, IterArgs};
+use sp_trie::{prefixed_key, LayoutV0, LayoutV1, TrieConfiguration};
+use trie_db::{trie_visit, TrieHash, TrieRoot};
+
+use crate::{
+	apply_state_commit, children, columns,
+	upgrade::UpgradeError,
+	utils::{self, meta_keys, DatabaseType, OpenDbError, NUM_COLUMNS},
+	Backend, DatabaseSource, DbExtrinsic, DbHash, DbStateBuilder, StateMetaDb, DB_HASH_LEN,
+};
+
+/// Number of changes written to the target database at once.
+const CONVERT_BATCH_SIZE: usize = 16 * 1024;
+
+/// Progress of the database conversion.
+#[derive(Debug, Clone, Copy)]
+pub struct ConvertProgress {
+	/// The column being converted.
+	pub column: u32,
+	/// Total number of columns.
+	pub columns: u32,
+	/// Number of keys of the column converted so far.
+	pub keys: u64,
+}
+
+// wrapper that reads state-db meta from the source database
+struct SourceMetaDb<'a>(&'a kvdb_rocksdb::Database);
+
+impl sc_state_db::MetaDb for SourceMetaDb<'_> {
+	type Error = io::Error;
+
+	fn get_meta(&self, key: &[u8]) -> Result<Option<Vec<u8>>, Self::Error> {
+		self.0.get(columns::STATE_META, key)
+	}
+}
+
+/// Converts the RocksDB database at `source` into a new ParityDB database at `target`.
+///
+/// The source database is upgraded to the current version first. The prefix is stripped from the
+/// keys of state nodes and every stored node adds a reference, the reference counters of the
+/// transaction column are carried over and the state-db journals are converted with
+/// [`sc_state_db::convert_journals_to_ref_counted`]. The node must be stopped while the database
+/// is converted.
+pub fn convert_rocksdb_to_paritydb<Block: BlockT>(
+	source: &Path,
+	target: &Path,
+	mut progress: impl FnMut(ConvertProgress),
+) -> sp_blockchain::Result<()> {
+	ensure_empty(target)?;
+	match crate::upgrade::upgrade_db::<Block>(source, DatabaseType::Full) {
+		Ok(()) => (),
+		Err(UpgradeError::MissingDatabaseVersionFile) =>
+			return Err(OpenDbError::DoesNotExist.into()),
+		Err(err) => return Err(sp_blockchain::Error::Backend(err.to_string())),
+	}
+	let mut db_config = kvdb_rocksdb::DatabaseConfig::with_columns(NUM_COLUMNS);
+	db_config.create_if_missing = false;
+	let source = kvdb_rocksdb::Database::open(&db_config, source).map_err(OpenDbError::from)?;
+	let target: Arc<dyn Database<DbHash>> =
+		crate::parity_db::open(target, DatabaseType::Full, true, false)
+			.map_err(OpenDbError::from)?;
+
+	let map_e = |e: io::Error| sp_blockchain::Error::Backend(e.to_string());
+	let mut journals: HashMap<_, _> =
+		sc_state_db::convert_journals_to_ref_counted::<Block::Hash, Vec<u8>, _>(
+			&SourceMetaDb(&source),
+			|mut key| key.split_off(key.len() - DB_HASH_LEN),
+		)
+		.map_err(sp_blockchain::Error::from_state_db)?
+		.into_iter()
+		.collect();
+	for column in 0..NUM_COLUMNS {
+		let mut keys = 0u64;
+		let mut tx = Transaction::new();
+		for entry in source.iter(column) {
+			let (key, value) = entry.map_err(map_e)?;
+			match column {
+				columns::STATE => {
+					// nodes are stored once per prefixed key, every one of them is a reference
+					tx.set_from_vec(column, &key[key.len() - DB_HASH_LEN..], value);
+				},
+				columns::STATE_META => {
+					let value = journals.remove(&key[..]).unwrap_or(value);
+					tx.set_from_vec(column, &key, value);
+				},
+				columns::TRANSACTION => {
+					// skip the reference counters, see `sp_database::kvdb`
+					if key.len() != DB_HASH_LEN {
+						continue
+					}
+					let mut counter_key = key.to_vec();
+					counter_key.push(0);
+					let references = match source.get(column, &counter_key).map_err(map_e)? {
+						Some(counter) =>
+							u32::from_le_bytes(counter.as_slice().try_into().map_err(|_| {
+								sp_blockchain::Error::Backend(format!(
+									"Unexpected counter len {}",
+									counter.len()
+								))
+							})?),
+						None => 1,
+					};
+					let hash = DbHash::from_slice(&key);
+					for _ in 0..references {
+						tx.store(column, hash, value.clone());
+					}
+				},
+				_ => tx.set_from_vec(column, &key, value),
+			}
+			keys += 1;
+			if tx.0.len() >= CONVERT_BATCH_SIZE {
+				target.commit(std::mem::take(&mut tx))?;
+				progress(ConvertProgress { column, columns: NUM_COLUMNS, keys });
+			}
+		}
+
+		target.commit(tx)?;
+		progress(ConvertProgress { column, columns: NUM_COLUMNS, keys });
+	}
+	Ok(())
+}
+
+/// Converts the ParityDB database at `source` into a new RocksDB database at `target`.
+///
+/// Headers, bodies, justifications and indexed transactions of the canonical chain up to the
+/// finalized block are copied together with the state of the finalized block, which becomes the
+/// best block. The blocks that are not finalized are dropped and synced again, so only databases
+/// with the constrained state pruning are supported. The aux data is copied for the keys returned
+/// by `aux_keys` for the finalized block, the offchain storage is not copied. The node must be
+/// stopped while the database is converted.
+pub fn convert_paritydb_to_rocksdb<Block: BlockT>(
+	source: &Path,
+	target: &Path,
+	aux_keys: impl FnOnce(&Block::Hash) -> Vec<Vec<u8>>,
+	mut progress: impl FnMut(ConvertProgress),
+) -> sp_blockchain::Result<()> {
+	ensure_empty(target)?;
+
+	let source = utils::open_database::<Block>(
+		&DatabaseSource::ParityDb { path: source.into() },
+		DatabaseType::Full,
+		false,
+	)?;
+	let meta = utils::read_meta::<Block>(&*source, columns::HEADER)?;
+	let (hash, number) = (meta.finalized_hash, meta.finalized_number);
+	if meta.finalized_state != Some((hash, number)) {
+		return Err(sp_blockchain::Error::Backend(format!(
+			"State of the finalized block {} is missing",
+			hash
+		)))
+	}
+	let mode =
+		StateDb::<Block::Hash, Vec<u8>, _>::open(StateMetaDb(source.clone()), None, false, false)
+			.map_err(sp_blockchain::Error::from_state_db)?
+			.1
+			.pruning_mode();
+	if !matches!(mode, PruningMode::Constrained(_)) {
+		return Err(sp_blockchain::Error::Backend(format!(
+			"Only the state of the finalized block is converted, {:?} state pruning is not supported",
+			mode
+		)))
+	}
+
+	let mut db_config = kvdb_rocksdb::DatabaseConfig::with_columns(NUM_COLUMNS);
+	db_config.create_if_missing = true;
+	let db = kvdb_rocksdb::Database::open(&db_config, target).map_err(OpenDbError::from)?;
+	crate::upgrade::update_version(target).map_err(OpenDbError::from)?;
+	let target: Arc<dyn Database<DbHash>> = sp_database::as_database(db);
+	utils::check_database_type(&*target, DatabaseType::Full)?;
+
+	// follow the canonical chain, blocks of the block gap are missing
+	let number_u64: u64 = number.unique_saturated_into();
+	let mut finalized = None;
+	let mut blocks = 0u64;
+	let mut tx = Transaction::new();
+	for block in 0..=number_u64 {
+		let lookup_key = match source.get(columns::KEY_LOOKUP, &utils::number_index_key(block)?) {
+			Some(lookup_key) => lookup_key,
+			None => continue,
+		};
+		let encoded = match source.get(columns::HEADER, &lookup_key) {
+			Some(encoded) => encoded,
+			None => continue,
+		};
+		let header = Block::Header::decode(&mut &encoded[..]).map_err(|e| {
+			sp_blockchain::Error::Backend(format!("Error decoding header of #{}: {}", block, e))
+		})?;
+		let block_hash = header.hash();
+		utils::insert_number_to_key_mapping(&mut tx, columns::KEY_LOOKUP, block, block_hash)?;
+		utils::insert_hash_to_key_mapping(&mut tx, columns::KEY_LOOKUP, block, block_hash)?;
+		tx.set_from_vec(columns::HEADER, &lookup_key, encoded);
+		for column in [columns::BODY, columns::JUSTIFICATIONS] {
+			if let Some(value) = source.get(column, &lookup_key) {
+				tx.set_from_vec(column, &lookup_key, value);
+			}
+		}
+		if let Some(index) = source.get(columns::BODY_INDEX, &lookup_key) {
+			let extrinsics = Vec::<DbExtrinsic<Block>>::decode(&mut &index[..]).map_err(|e| {
+				sp_blockchain::Error::Backend(format!("Error decoding body list: {}", e))
+			})?;
+			// every indexed extrinsic holds a reference to its transaction
+			for extrinsic in extrinsics {
+				if let DbExtrinsic::Indexed { hash: transaction, .. } = extrinsic {
+					let value = source.get(columns::TRANSACTION, transaction.as_ref()).ok_or_else(
+						|| {
+							sp_blockchain::Error::Backend(format!(
+								"Indexed transaction {} is missing",
+								transaction
+							))
+						},
+					)?;
+					tx.store(columns::TRANSACTION, transaction, value);
+				}
+			}
+			tx.set_from_vec(columns::BODY_INDEX, &lookup_key, index);
+		}
+		if block > 0 {
+			children::write_children(
+				&mut tx,
+				columns::META,
+				meta_keys::CHILDREN_PREFIX,
+				*header.parent_hash(),
+				vec![block_hash],
+			);
+		}
+		if block_hash == hash {
+			finalized = Some(header);
+		}
+
+		blocks += 1;
+		if tx.0.len() >= CONVERT_BATCH_SIZE {
+			target.commit(std::mem::take(&mut tx))?;
+			progress(ConvertProgress {
+				column: columns::HEADER,
+				columns: NUM_COLUMNS,
+				keys: blocks,
+			});
+		}
+	}
+	let finalized = finalized.ok_or_else(|| {
+		sp_blockchain::Error::Backend(format!("Finalized block {} is not canonical", hash))
+	})?;
+
+	let lookup_key = utils::number_and_hash_to_lookup_key(number, hash)?;
+	for key in [
+		&meta_keys::BEST_BLOCK[..],
+		&meta_keys::FINALIZED_BLOCK[..],
+		&meta_keys::FINALIZED_STATE[..],
+	] {
+		tx.set(columns::META, key, &lookup_key);
+	}
+	for key in
+		[&meta_keys::GENESIS_HASH[..], &meta_keys::BLOCK_GAP[..], &meta_keys::BLOCK_GAP_VERSION[..]]
+	{
+		if let Some(value) = source.get(columns::META, key) {
+			tx.set_from_vec(columns::META, key, value);
+		}
+	}
+	let mut leaves = LeafSet::new();
+	leaves.import(hash, number, *finalized.parent_hash());
+	leaves.prepare_transaction(&mut tx, columns::META, meta_keys::LEAF_PREFIX);
+	for key in aux_keys(&hash) {
+		if let Some(value) = source.get(columns::AUX, &key) {
+			tx.set_from_vec(columns::AUX, &key, value);
+		}
+	}
+	target.commit(std::mem::take(&mut tx))?;
+	progress(ConvertProgress { column: columns::HEADER, columns: NUM_COLUMNS, keys: blocks });
+
+	// reading the whole state of the finalized block copies all of its nodes
+	let storage = Arc::new(StateCopyDb {
+		source: source.clone(),
+		target: target.clone(),
+		pending: Default::default(),
+	});
+	let state =
+		DbStateBuilder::<HashingFor<Block>>::new(storage.clone(), *finalized.state_root()).build();
+	let map_e = |e: String| {
+		sp_blockchain::Error::Backend(format!("State of block {} is incomplete: {}", hash, e))
+	};
+	let mut keys = 0u64;
+	let mut child_tries = Vec::new();
+	for pair in state.pairs(Default::default()).map_err(map_e)? {
+		let (key, _) = pair.map_err(map_e)?;
+		if let Some(storage_key) = key.strip_prefix(DEFAULT_CHILD_STORAGE_KEY_PREFIX) {
+			child_tries.push(ChildInfo::new_default(storage_key));
+		}
+		keys += 1;
+		if keys % CONVERT_BATCH_SIZE as u64 == 0 {
+			progress(ConvertProgress { column: columns::STATE, columns: NUM_COLUMNS, keys });
+		}
+	}
+	for child_info in child_tries {
+		let args = IterArgs { child_info: Some(child_info), ..Default::default() };
+		for pair in state.pairs(args).map_err(map_e)? {
+			pair.map_err(map_e)?;
+			keys += 1;
+			if keys % CONVERT_BATCH_SIZE as u64 == 0 {
+				progress(ConvertProgress { column: columns::STATE, columns: NUM_COLUMNS, keys });
+			}
+		}
+	}
+	let mut tx = std::mem::take(&mut *storage.pending.lock());
+
+	// the state-db starts at the finalized block and counts the references in memory
+	let map_e = sp_blockchain::Error::from_state_db;
+	let (commit, state_db) = StateDb::<Block::Hash, Vec<u8>, _>::open(
+		StateMetaDb(target.clone()),
+		Some(mode),
+		true,
+		true,
+	)
+	.map_err(map_e)?;
+	apply_state_commit(&mut tx, commit);
+	let commit = state_db
+		.insert_block(&hash, number_u64, finalized.parent_hash(), Default::default())
+		.map_err(map_e)?;
+	apply_state_commit(&mut tx, commit);
+	apply_state_commit(&mut tx, state_db.canonicalize_block(&hash).map_err(map_e)?);
+	target.commit(tx)?;
+	progress(ConvertProgress { column: columns::STATE, columns: NUM_COLUMNS, keys });
+	Ok(())
+}
+
+/// Verifies that the state of the block is complete, by computing the trie root from its
+/// key-value pairs and comparing it with the state root of the header. Child tries are compared
+/// with their roots, stored in the main trie. Returns the number of keys.
+///
+/// The roots are computed with both state versions, so the state that is partially migrated to
+/// the new state version can't be verified.
+pub fn verify_state_root<Block: BlockT>(
+	backend: &Backend<Block>,
+	hash: Block::Hash,
+) -> sp_blockchain::Result<u64> {
+	let header = backend.blockchain().expect_header(hash)?;
+	let state = backend.state_at(hash)?;
+	let state = &state;
+	let pairs = |child_info: Option<ChildInfo>| {
+		move || {
+			state
+				.pairs(IterArgs { child_info: child_info.clone(), ..Default::default() })
+				.map(|pairs| pairs.map(|pair| pair.map_err(|e| e.to_string())))
+				.map_err(|e| e.to_string())
+		}
+	};
+	let map_e = |e: String| {
+		sp_blockchain::Error::Backend(format!("State of block {} is incomplete: {}", hash, e))
+	};
+	let mismatch = |trie: &str| {
+		sp_blockchain::Error::Backend(format!(
+			"Computed root of the {} of block {} doesn't match the stored one",
+			trie, hash
+		))
+	};
+
+	let mut keys =
+		verify_trie_root::<HashingFor<Block>, _>(header.state_root().as_ref(), pairs(None))
+			.map_err(map_e)?
+			.ok_or_else(|| mismatch("state"))?;
+	let args = IterArgs { prefix: Some(DEFAULT_CHILD_STORAGE_KEY_PREFIX), ..Default::default() };
+	let child_roots = state
+		.pairs(args)
+		.and_then(|pairs| pairs.collect::<Result<Vec<_>, _>>())
+		.map_err(|e| map_e(e.to_string()))?;
+	for (key, root) in child_roots {
+		let child_info = ChildInfo::new_default(&key[DEFAULT_CHILD_STORAGE_KEY_PREFIX.len()..]);
+		keys += verify_trie_root::<HashingFor<Block>, _>(&root, pairs(Some(child_info)))
+			.map_err(map_e)?
+			.ok_or_else(|| mismatch("child trie"))?;
+	}
+	Ok(keys)
+}
+
+/// Computes the root of the trie from its sorted key-value pairs, first with the current and then
+/// with the previous state version. Returns the number of keys if one of the roots is `expected`.
+fn verify_trie_root<H: Hasher, I>(
+	expected: &[u8],
+	pairs: impl Fn() -> Result<I, String>,
+) -> Result<Option<u64>, String>
+where
+	I: Iterator<Item = Result<(Vec<u8>, Vec<u8>), String>>,
+{
+	let (root, keys) = trie_root::<LayoutV1<H>>(pairs()?)?;
+	if root.as_ref() == expected {
+		return Ok(Some(keys))
+	}
+	let (root, keys) = trie_root::<LayoutV0<H>>(pairs()?)?;
+	Ok((root.as_ref() == expected).then_some(keys))
+}
+
+// computes the root without keeping the pairs in memory, returns it with the number of keys
+fn trie_root<L: TrieConfiguration>(
+	pairs: impl Iterator<Item = Result<(Vec<u8>, Vec<u8>), String>>,
+) -> Result<(TrieHash<L>, u64), String> {
+	let mut error = None;
+	let mut keys = 0u64;
+	let mut root = TrieRoot::<L>::default();
+	trie_visit::<L, _, _, _, _>(
+		pairs
+			.map_while(|pair| pair.map_err(|e| error = Some(e)).ok())
+			.inspect(|_| keys += 1),
+		&mut root,
+	);
+	match error {
+		Some(e) => Err(e),
+		None => Ok((root.root.unwrap_or_default(), keys)),
+	}
+}
+
+// state storage, that copies the nodes read from the ParityDB database into the RocksDB database
+// under the prefixed keys
+struct StateCopyDb {
+	source: Arc<dyn Database<DbHash>>,
+	target: Arc<dyn Database<DbHash>>,
+	pending: Mutex<Transaction<DbHash>>,
+}
+
+impl<H: Hasher> sp_state_machine::Storage<H> for StateCopyDb {
+	fn get(&self, key: &H::Out, prefix: Prefix) -> Result<Option<DBValue>, String> {
+		let value = self.source.get(columns::STATE, key.as_ref());
+		if let Some(ref value) = value {
+			let mut pending = self.pending.lock();
+			pending.set(columns::STATE, &prefixed_key::<H>(key, prefix), value);
+			if pending.0.len() >= CONVERT_BATCH_SIZE {
+				self.target
+					.commit(std::mem::take(&mut *pending))
+					.map_err(|e| format!("Database backend error: {e}"))?;
+			}
+		}
+		Ok(value)
+	}
+}
+
+// fails if the target directory exists and is not empty
+fn ensure_empty(target: &Path) -> sp_blockchain::Result<()> {
+	if target.exists() && fs::read_dir(target).map_err(OpenDbError::from)?.next().is_some() {
+		return Err(sp_blockchain::Error::Backend(format!(
+			"Target directory {:?} is not empty",
+			target
+		)))
+	}
+	Ok(())
+}
@@ -33,6 +33,8 @@ pub mod offchain;
 pub mod bench;
 
 mod children;
+#[cfg(any(feature = "rocksdb", test))]
+mod convert;
 mod parity_db;
 mod pinned_blocks_cache;
 mod record_stats_state;
@@ -42,7 +44,7 @@ mod upgrade;
 mod utils;
 
 use linked_hash_map::LinkedHashMap;
-use log::{debug, trace, warn};
+use log::{debug, info, trace, warn};
 use parking_lot::{Mutex, RwLock};
 use std::{
 	collections::{HashMap, HashSet},
@@ -99,6 +101,10 @@ pub use sc_state_db::PruningMode;
 pub use sp_database::Database;
 
 pub use bench::BenchmarkingState;
+#[cfg(any(feature = "rocksdb", test))]
+pub use convert::{
+	convert_paritydb_to_rocksdb, convert_rocksdb_to_paritydb, verify_state_root, ConvertProgress,
+};
 
 const CACHE_HEADERS: usize = 8;
 
@@ -1099,6 +1105,18 @@ impl<T: Clone> FrozenForDuration<T> {
 	}
 }
 
+/// Maximum number of blocks pruned per finalized block while catching up with a smaller blocks
+/// pruning window.
+const MAX_BLOCKS_PRUNED_PER_FINALIZATION: u32 = 64;
+
+/// Blocks pruning window set by [`Backend::set_pruning_override`].
+struct BlocksPruningOverride<N> {
+	/// Number of finalized blocks to keep.
+	keep: u32,
+	/// Next block to prune, if any was pruned since the override was set.
+	next: Option<N>,
+}
+
 /// Disk backend.
 ///
 /// Disk backend keeps data in a key-value store. In archive mode, trie nodes are kept from all
@@ -1111,6 +1129,7 @@ pub struct Backend<Block: BlockT> {
 	import_lock: Arc<RwLock<()>>,
 	is_archive: bool,
 	blocks_pruning: BlocksPruning,
+	blocks_pruning_override: Mutex<Option<BlocksPruningOverride<NumberFor<Block>>>>,
 	io_stats: FrozenForDuration<(kvdb::IoStats, StateUsageInfo)>,
 	state_usage: Arc<StateUsageStats>,
 	genesis_state: RwLock<Option<Arc<DbGenesisStorage<Block>>>>,
@@ -1234,6 +1253,7 @@ impl<Block: BlockT> Backend<Block> {
 			io_stats: FrozenForDuration::new(std::time::Duration::from_secs(1)),
 			state_usage: Arc::new(StateUsageStats::new()),
 			blocks_pruning: config.blocks_pruning,
+			blocks_pruning_override: Mutex::new(None),
 			genesis_state: RwLock::new(None),
 			shared_trie_cache: config.trie_cache_maximum_size.map(|maximum_size| {
 				SharedTrieCache::new(sp_trie::cache::CacheSize::new(maximum_size))
@@ -1885,6 +1905,19 @@ impl<Block: BlockT> Backend<Block> {
 		Ok(())
 	}
 
+	/// Temporarily shrink the state and blocks pruning windows to `max_blocks` blocks, or restore
+	/// the configured windows if `None` is given.
+	///
+	/// Only pruning modes with a window are affected, archive nodes keep everything. The override
+	/// isn't persisted, and blocks falling out of the smaller windows are pruned progressively as
+	/// new blocks are finalized. At least one block is always kept.
+	pub fn set_pruning_override(&self, max_blocks: Option<u32>) {
+		let max_blocks = max_blocks.map(|max_blocks| max_blocks.max(1));
+		self.storage.state_db.set_max_blocks_override(max_blocks);
+		*self.blocks_pruning_override.lock() =
+			max_blocks.map(|keep| BlocksPruningOverride { keep, next: None });
+	}
+
 	fn prune_blocks(
 		&self,
 		transaction: &mut Transaction<DbHash>,
@@ -1894,28 +1927,67 @@ impl<Block: BlockT> Backend<Block> {
 		if let BlocksPruning::Some(blocks_pruning) = self.blocks_pruning {
 			// Always keep the last finalized block
 			let keep = std::cmp::max(blocks_pruning, 1);
-			if finalized_number >= keep.into() {
-				let number = finalized_number.saturating_sub(keep.into());
-
-				// Before we prune a block, check if it is pinned
-				if let Some(hash) = self.blockchain.hash(number)? {
-					self.blockchain.insert_persisted_body_if_pinned(hash)?;
 
-					// If the block was finalized in this transaction, it will not be in the db
-					// yet.
-					if let Some(justification) = current_transaction_justifications.remove(&hash) {
-						self.blockchain.insert_justifications_if_pinned(hash, justification);
-					} else {
-						self.blockchain.insert_persisted_justifications_if_pinned(hash)?;
+			let mut blocks_pruning_override = self.blocks_pruning_override.lock();
+			match blocks_pruning_override.as_mut() {
+				// Catch up with the smaller window, a bounded number of blocks at a time.
+				Some(BlocksPruningOverride { keep: override_keep, next })
+					if *override_keep < keep =>
+				{
+					if finalized_number < (*override_keep).into() {
+						return Ok(())
 					}
-				};
-
-				self.prune_block(transaction, BlockId::<Block>::number(number))?;
+					let last = finalized_number.saturating_sub((*override_keep).into());
+					let mut number =
+						next.unwrap_or_else(|| finalized_number.saturating_sub(keep.into()));
+					let mut pruned = 0;
+					while number <= last && pruned < MAX_BLOCKS_PRUNED_PER_FINALIZATION {
+						self.prune_finalized_block(
+							transaction,
+							number,
+							current_transaction_justifications,
+						)?;
+						number = number.saturating_add(One::one());
+						pruned += 1;
+					}
+					*next = Some(number);
+				},
+				_ =>
+					if finalized_number >= keep.into() {
+						let number = finalized_number.saturating_sub(keep.into());
+						self.prune_finalized_block(
+							transaction,
+							number,
+							current_transaction_justifications,
+						)?;
+					},
 			}
 		}
 		Ok(())
 	}
 
+	fn prune_finalized_block(
+		&self,
+		transaction: &mut Transaction<DbHash>,
+		number: NumberFor<Block>,
+		current_transaction_justifications: &mut HashMap<Block::Hash, Justification>,
+	) -> ClientResult<()> {
+		// Before we prune a block, check if it is pinned
+		if let Some(hash) = self.blockchain.hash(number)? {
+			self.blockchain.insert_persisted_body_if_pinned(hash)?;
+
+			// If the block was finalized in this transaction, it will not be in the db
+			// yet.
+			if let Some(justification) = current_transaction_justifications.remove(&hash) {
+				self.blockchain.insert_justifications_if_pinned(hash, justification);
+			} else {
+				self.blockchain.insert_persisted_justifications_if_pinned(hash)?;
+			}
+		};
+
+		self.prune_block(transaction, BlockId::<Block>::number(number))
+	}
+
 	fn prune_displaced_branches(
 		&self,
 		transaction: &mut Transaction<DbHash>,
@@ -2500,6 +2572,14 @@ impl<Block: BlockT> sc_client_api::backend::Backend<Block> for Backend<Block> {
 							.build();
 					let state = RefTrackingState::new(db_state, self.storage.clone(), Some(hash));
 					Ok(RecordStatsState::new(state, Some(hash), self.state_usage.clone()))
+				} else if let PruningMode::ArchiveSparse { keep_every, max_blocks } =
+					self.storage.state_db.pruning_mode()
+				{
+					Err(sp_blockchain::Error::UnknownBlock(format!(
+						"State already discarded for {hash:?} (#{}): only the state of every \
+						{keep_every}th block and of the last {max_blocks} finalized blocks is kept",
+						hdr.number,
+					)))
 				} else {
 					Err(sp_blockchain::Error::UnknownBlock(format!(
 						"State already discarded for {hash:?}",
@@ -2510,6 +2590,20 @@ impl<Block: BlockT> sc_client_api::backend::Backend<Block> for Backend<Block> {
 		}
 	}
 
+	fn state_retention(&self) -> sc_client_api::backend::StateRetention {
+		use sc_client_api::backend::StateRetention;
+
+		match self.storage.state_db.pruning_mode() {
+			PruningMode::ArchiveAll | PruningMode::ArchiveCanonical => StateRetention::All,
+			PruningMode::Constrained(constraints) => StateRetention::Pruned {
+				keep_last: constraints.max_blocks.unwrap_or(0),
+				keep_every: None,
+			},
+			PruningMode::ArchiveSparse { keep_every, max_blocks } =>
+				StateRetention::Pruned { keep_last: max_blocks, keep_every: Some(keep_every) },
+		}
+	}
+
 	fn have_state_at(&self, hash: Block::Hash, number: NumberFor<Block>) -> bool {
 		if self.is_archive {
 			match self.blockchain.header_metadata(hash) {
@@ -2544,10 +2638,27 @@ impl<Block: BlockT> sc_client_api::backend::Backend<Block> for Backend<Block> {
 		&self.import_lock
 	}
 
+	/// Only RocksDB databases support snapshots. The snapshot is written to the new RocksDB
+	/// database at `path`, which must be empty or missing. ParityDB databases are not supported.
+	///
+	/// Commits are only blocked while the snapshot iterators are created, so block import keeps
+	/// going while the database is copied.
+	fn snapshot(&self, path: &Path) -> ClientResult<(NumberFor<Block>, Block::Hash)> {
+		self.storage.db.snapshot(path)?;
+		let meta = utils::read_snapshot_meta::<Block>(path)?;
+		info!(
+			"💾 Database snapshot at finalized block #{} ({}) written to {:?}",
+			meta.finalized_number, meta.finalized_hash, path,
+		);
+		Ok((meta.finalized_number, meta.finalized_hash))
+	}
+
 	fn requires_full_sync(&self) -> bool {
 		matches!(
 			self.storage.state_db.pruning_mode(),
-			PruningMode::ArchiveAll | PruningMode::ArchiveCanonical
+			PruningMode::ArchiveAll |
+				PruningMode::ArchiveCanonical |
+				PruningMode::ArchiveSparse { .. }
 		)
 	}
 
@@ -3762,6 +3873,53 @@ pub(crate) mod tests {
 		}
 	}
 
+	#[test]
+	fn pruning_override_shrinks_blocks_pruning_window() {
+		let backend = Backend::<Block>::new_test_with_tx_storage(BlocksPruning::Some(4), 0);
+		let mut blocks = Vec::new();
+		let mut prev_hash = Default::default();
+		for i in 0..8 {
+			let hash = insert_block(
+				&backend,
+				i,
+				prev_hash,
+				None,
+				Default::default(),
+				vec![UncheckedXt::new_transaction(i.into(), ())],
+				None,
+			)
+			.unwrap();
+			blocks.push(hash);
+			prev_hash = hash;
+		}
+
+		let finalize = |range: std::ops::Range<usize>| {
+			let mut op = backend.begin_operation().unwrap();
+			backend.begin_state_operation(&mut op, blocks[range.end - 1]).unwrap();
+			for i in range {
+				op.mark_finalized(blocks[i], None).unwrap();
+			}
+			backend.commit_operation(op).unwrap();
+		};
+
+		finalize(1..5);
+		assert_eq!(None, backend.blockchain().body(blocks[0]).unwrap());
+		assert!(backend.blockchain().body(blocks[1]).unwrap().is_some());
+
+		backend.set_pruning_override(Some(1));
+		finalize(5..6);
+		for i in 0..5 {
+			assert_eq!(None, backend.blockchain().body(blocks[i]).unwrap());
+		}
+		assert!(backend.blockchain().body(blocks[5]).unwrap().is_some());
+
+		// the configured window applies again once the override is lifted
+		backend.set_pruning_override(None);
+		finalize(6..8);
+		assert!(backend.blockchain().body(blocks[5]).unwrap().is_some());
+		assert!(backend.blockchain().body(blocks[7]).unwrap().is_some());
+	}
+
 	#[test]
 	fn prune_blocks_on_finalize_with_fork() {
 		sp_tracing::try_init_simple();
@@ -4883,4 +5041,208 @@ pub(crate) mod tests {
 		backend.unpin_block(fork_hash_3);
 		assert!(bc.body(fork_hash_3).unwrap().is_none());
 	}
+
+	#[cfg(feature = "rocksdb")]
+	#[test]
+	fn snapshot_is_written_and_restored() {
+		let rocksdb_settings = |path: &Path| DatabaseSettings {
+			trie_cache_maximum_size: Some(16 * 1024 * 1024),
+			state_pruning: Some(PruningMode::ArchiveAll),
+			source: DatabaseSource::RocksDb { path: path.into(), cache_size: 16 },
+			blocks_pruning: BlocksPruning::KeepFinalized,
+		};
+		let base_path = tempfile::TempDir::new().unwrap();
+		let db_path = base_path.path().join("db");
+		let snapshot_path = base_path.path().join("snapshot");
+
+		let backend = Backend::<Block>::new(rocksdb_settings(&db_path), 0).unwrap();
+		let mut blocks = Vec::new();
+		let mut prev_hash = Default::default();
+		for i in 0..5 {
+			let hash = insert_header(&backend, i, prev_hash, None, Default::default());
+			blocks.push(hash);
+			prev_hash = hash;
+		}
+		backend.finalize_block(blocks[3], None).unwrap();
+
+		// snapshot is taken while the backend is alive
+		assert_eq!(backend.snapshot(&snapshot_path).unwrap(), (3, blocks[3]));
+		// snapshot directory must be empty
+		assert!(backend.snapshot(&snapshot_path).is_err());
+		// new blocks do not affect the snapshot
+		insert_header(&backend, 5, prev_hash, None, Default::default());
+
+		let restored = Backend::<Block>::new(rocksdb_settings(&snapshot_path), 0).unwrap();
+		let info = restored.blockchain().info();
+		assert_eq!(info.finalized_hash, blocks[3]);
+		assert_eq!(info.best_hash, blocks[4]);
+		assert!(restored.have_state_at(blocks[3], 3));
+		assert!(restored.have_state_at(blocks[4], 4));
+
+		// neither ParityDB nor in-memory databases support snapshots
+		let backend = Backend::<Block>::new(
+			DatabaseSettings {
+				source: DatabaseSource::ParityDb { path: base_path.path().join("paritydb") },
+				..rocksdb_settings(&db_path)
+			},
+			0,
+		)
+		.unwrap();
+		assert!(backend.snapshot(&base_path.path().join("paritydb-snapshot")).is_err());
+		let backend = Backend::<Block>::new_test(2, 0);
+		assert!(backend.snapshot(&base_path.path().join("memory")).is_err());
+	}
+
+	#[cfg(feature = "rocksdb")]
+	fn convert_settings(source: DatabaseSource) -> DatabaseSettings {
+		DatabaseSettings {
+			trie_cache_maximum_size: Some(16 * 1024 * 1024),
+			state_pruning: Some(PruningMode::blocks_pruning(2)),
+			source,
+			blocks_pruning: BlocksPruning::KeepFinalized,
+		}
+	}
+
+	#[cfg(feature = "rocksdb")]
+	fn commit_convert_block(backend: &Backend<Block>, number: u64, parent_hash: H256) -> H256 {
+		let mut op = backend.begin_operation().unwrap();
+		backend.begin_state_operation(&mut op, parent_hash).unwrap();
+		let mut header = Header {
+			number,
+			parent_hash,
+			state_root: Default::default(),
+			digest: Default::default(),
+			extrinsics_root: Default::default(),
+		};
+		// the value of the first key changes back and forth, so its nodes are re-inserted
+		let storage = vec![
+			(vec![0], Some(vec![(number % 2) as u8; 40])),
+			(vec![number as u8 + 1], Some(vec![1; 40])),
+		];
+		let (root, overlay) = op.old_state.storage_root(
+			storage.iter().map(|(k, v)| (k.as_slice(), v.as_deref())),
+			StateVersion::V1,
+		);
+		op.update_db_storage(overlay).unwrap();
+		header.state_root = root.into();
+		op.update_storage(storage, Vec::new()).unwrap();
+		op.set_block_data(header.clone(), Some(vec![]), None, None, NewBlockState::Best)
+			.unwrap();
+		backend.commit_operation(op).unwrap();
+		header.hash()
+	}
+
+	#[cfg(feature = "rocksdb")]
+	#[test]
+	fn rocksdb_is_converted_to_paritydb() {
+		let base_path = tempfile::TempDir::new().unwrap();
+		let rocksdb_path = base_path.path().join("rocksdb");
+		let paritydb_path = base_path.path().join("paritydb");
+
+		let mut blocks = Vec::new();
+		{
+			let source = DatabaseSource::RocksDb { path: rocksdb_path.clone(), cache_size: 16 };
+			let backend = Backend::<Block>::new(convert_settings(source), 0).unwrap();
+			let mut parent_hash = Default::default();
+			for number in 0..5 {
+				parent_hash = commit_convert_block(&backend, number, parent_hash);
+				blocks.push(parent_hash);
+			}
+			backend.finalize_block(blocks[3], None).unwrap();
+		}
+
+		let mut converted_columns = Vec::new();
+		convert_rocksdb_to_paritydb::<Block>(&rocksdb_path, &paritydb_path, |progress| {
+			converted_columns.push(progress.column)
+		})
+		.unwrap();
+		assert_eq!(converted_columns, (0..utils::NUM_COLUMNS).collect::<Vec<_>>());
+		// the target directory must be empty
+		assert!(
+			convert_rocksdb_to_paritydb::<Block>(&rocksdb_path, &paritydb_path, |_| ()).is_err()
+		);
+
+		let backend = Backend::<Block>::new(
+			convert_settings(DatabaseSource::ParityDb { path: paritydb_path }),
+			0,
+		)
+		.unwrap();
+		let info = backend.blockchain().info();
+		assert_eq!(info.finalized_hash, blocks[3]);
+		assert_eq!(info.best_hash, blocks[4]);
+		for number in 2..5 {
+			let state = backend.state_at(blocks[number]).unwrap();
+			assert_eq!(state.storage(&[0]).unwrap(), Some(vec![(number % 2) as u8; 40]));
+			assert_eq!(state.storage(&[number as u8 + 1]).unwrap(), Some(vec![1; 40]));
+		}
+		assert_eq!(verify_state_root(&backend, blocks[4]).unwrap(), 6);
+
+		// pruning the converted journals keeps the re-inserted nodes
+		let mut parent_hash = blocks[4];
+		for number in 5..8 {
+			parent_hash = commit_convert_block(&backend, number, parent_hash);
+			backend.finalize_block(parent_hash, None).unwrap();
+		}
+		let state = backend.state_at(parent_hash).unwrap();
+		for key in 0..9 {
+			assert!(state.storage(&[key]).unwrap().is_some());
+		}
+	}
+	#[cfg(feature = "rocksdb")]
+	#[test]
+	fn paritydb_is_converted_to_rocksdb() {
+		let base_path = tempfile::TempDir::new().unwrap();
+		let paritydb_path = base_path.path().join("paritydb");
+		let rocksdb_path = base_path.path().join("rocksdb");
+
+		let mut blocks = Vec::new();
+		{
+			let source = DatabaseSource::ParityDb { path: paritydb_path.clone() };
+			let backend = Backend::<Block>::new(convert_settings(source), 0).unwrap();
+			let mut parent_hash = Default::default();
+			for number in 0..5 {
+				parent_hash = commit_convert_block(&backend, number, parent_hash);
+				blocks.push(parent_hash);
+			}
+			backend.finalize_block(blocks[3], None).unwrap();
+			backend
+				.insert_aux(&[(&b"aux"[..], &b"kept"[..]), (&b"other"[..], &b"dropped"[..])], &[])
+				.unwrap();
+		}
+
+		convert_paritydb_to_rocksdb::<Block>(
+			&paritydb_path,
+			&rocksdb_path,
+			|_| vec![b"aux".to_vec()],
+			|_| (),
+		)
+		.unwrap();
+		// the target directory must be empty
+		assert!(convert_paritydb_to_rocksdb::<Block>(
+			&paritydb_path,
+			&rocksdb_path,
+			|_| Vec::new(),
+			|_| ()
+		)
+		.is_err());
+
+		let source = DatabaseSource::RocksDb { path: rocksdb_path, cache_size: 16 };
+		let backend = Backend::<Block>::new(convert_settings(source), 0).unwrap();
+		let info = backend.blockchain().info();
+		// only the finalized chain is converted
+		assert_eq!(info.finalized_hash, blocks[3]);
+		assert_eq!(info.best_hash, blocks[3]);
+		assert!(backend.blockchain().header(blocks[4]).unwrap().is_none());
+		assert_eq!(backend.get_aux(b"aux").unwrap(), Some(b"kept".to_vec()));
+		assert_eq!(backend.get_aux(b"other").unwrap(), None);
+		assert_eq!(verify_state_root(&backend, blocks[3]).unwrap(), 5);
+		assert!(backend.state_at(blocks[2]).is_err());
+
+		let mut parent_hash = blocks[3];
+		for number in 4..7 {
+			parent_hash = commit_convert_block(&backend, number, parent_hash);
+			backend.finalize_block(parent_hash, None).unwrap();
+		}
+		assert_eq!(verify_state_root(&backend, parent_hash).unwrap(), 8);
+	}
 }
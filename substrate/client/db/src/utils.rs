@@ -349,7 +349,28 @@ fn open_kvdb_rocksdb<Block: BlockT>(
 	let db = kvdb_rocksdb::Database::open(&db_config, path)?;
 	// write database version only after the database is successfully opened
 	crate::upgrade::update_version(path)?;
-	Ok(sp_database::as_database(db))
+	Ok(sp_database::as_database_with_snapshots(
+		db,
+		NUM_COLUMNS,
+		Box::new(open_kvdb_rocksdb_snapshot),
+	))
+}
+
+/// Opens the empty RocksDB database at given path, where the snapshot is written to.
+#[cfg(any(feature = "rocksdb", test))]
+fn open_kvdb_rocksdb_snapshot(path: &Path) -> io::Result<kvdb_rocksdb::Database> {
+	if path.exists() && fs::read_dir(path)?.next().is_some() {
+		return Err(io::Error::new(
+			io::ErrorKind::AlreadyExists,
+			format!("Snapshot directory {:?} is not empty", path),
+		))
+	}
+
+	let mut db_config = kvdb_rocksdb::DatabaseConfig::with_columns(NUM_COLUMNS);
+	db_config.create_if_missing = true;
+	let db = kvdb_rocksdb::Database::open(&db_config, path)?;
+	crate::upgrade::update_version(path)?;
+	Ok(db)
 }
 
 #[cfg(not(any(feature = "rocksdb", test)))]
@@ -475,6 +496,25 @@ pub fn read_header<Block: BlockT>(
 }
 
 /// Read meta from the database.
+/// Read database metadata from the RocksDB database snapshot at `path`.
+#[cfg(feature = "rocksdb")]
+pub fn read_snapshot_meta<Block: BlockT>(
+	path: &Path,
+) -> Result<Meta<<<Block as BlockT>::Header as HeaderT>::Number, Block::Hash>, sp_blockchain::Error>
+{
+	let source = DatabaseSource::RocksDb { path: path.into(), cache_size: 16 };
+	let db = open_database_at::<Block>(&source, DatabaseType::Full, false)?;
+	read_meta::<Block>(&*db, crate::columns::HEADER)
+}
+
+#[cfg(not(feature = "rocksdb"))]
+pub fn read_snapshot_meta<Block: BlockT>(
+	_path: &Path,
+) -> Result<Meta<<<Block as BlockT>::Header as HeaderT>::Number, Block::Hash>, sp_blockchain::Error>
+{
+	Err(OpenDbError::NotEnabled("with-kvdb-rocksdb").into())
+}
+
 pub fn read_meta<Block>(
 	db: &dyn Database<DbHash>,
 	col_header: u32,
@@ -0,0 +1,376 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+//! [`BanList`] keeps the peers and address ranges banned by the node operator.
+//!
+//! Unlike reputation-based bans in [`crate::peer_store`], these bans never expire. The list is
+//! written to [`BAN_LIST_FILE`] in the network configuration directory on every change, so that
+//! it survives restarts.
+//!
+//! With the `libp2p` backend, [`BanList`] is also a [`NetworkBehaviour`] refusing the connections
+//! from and to the banned address ranges.
+
+use libp2p::{
+	core::Endpoint,
+	swarm::{
+		dummy, ConnectionDenied, ConnectionId, FromSwarm, NetworkBehaviour, PollParameters,
+		THandler, THandlerInEvent, THandlerOutEvent, ToSwarm,
+	},
+};
+use parking_lot::Mutex;
+use sc_network_common::types::ReputationChange;
+use sc_network_types::{
+	multiaddr::{Multiaddr, Protocol},
+	PeerId,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+	collections::BTreeMap,
+	fs, io,
+	net::IpAddr,
+	path::{Path, PathBuf},
+	str::FromStr,
+	sync::Arc,
+	task::{Context, Poll},
+};
+
+pub use ip_network::IpNetwork;
+
+/// Log target for this file.
+const LOG_TARGET: &str = "sub-libp2p::ban-list";
+
+/// Name of the file the ban list is persisted to.
+pub const BAN_LIST_FILE: &str = "banned_peers.json";
+
+/// Reputation change reported for peers connecting from a banned address range.
+pub const BANNED_ADDRESS_RANGE: ReputationChange =
+	ReputationChange::new_fatal("Connected from a banned address range");
+
+/// Error refusing the connection from or to a banned address range.
+#[derive(Debug, thiserror::Error)]
+#[error("Address {0} belongs to a banned range")]
+pub struct BannedAddress(pub Multiaddr);
+
+/// Peers and address ranges banned by the node operator.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BannedEntries {
+	/// Banned peers with the reason given for the ban.
+	pub peers: BTreeMap<PeerId, String>,
+	/// Banned IP address ranges.
+	pub address_ranges: Vec<IpNetwork>,
+}
+
+/// On-disk representation of [`BannedEntries`].
+#[derive(Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BanListFile {
+	peers: BTreeMap<String, String>,
+	address_ranges: Vec<String>,
+}
+
+/// Shared, persisted list of banned peers and address ranges.
+#[derive(Debug, Clone, Default)]
+pub struct BanList {
+	entries: Arc<Mutex<BannedEntries>>,
+	path: Option<PathBuf>,
+}
+
+impl BanList {
+	/// Load the ban list stored in the network configuration directory `net_config_path`.
+	///
+	/// Without a configuration directory the list is kept in memory only.
+	pub fn load(net_config_path: Option<&Path>) -> io::Result<Self> {
+		let Some(path) = net_config_path.map(|path| path.join(BAN_LIST_FILE)) else {
+			return Ok(Self::default())
+		};
+
+		let mut entries = BannedEntries::default();
+		if path.exists() {
+			let file: BanListFile = serde_json::from_slice(&fs::read(&path)?)
+				.map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+			let invalid = |e: String| io::Error::new(io::ErrorKind::InvalidData, e);
+
+			for (peer_id, reason) in file.peers {
+				let peer_id = PeerId::from_str(&peer_id)
+					.map_err(|e| invalid(format!("Invalid banned peer {peer_id}: {e}")))?;
+				entries.peers.insert(peer_id, reason);
+			}
+			for range in file.address_ranges {
+				let range = IpNetwork::from_str(&range)
+					.map_err(|e| invalid(format!("Invalid banned address range {range}: {e}")))?;
+				entries.address_ranges.push(range);
+			}
+
+			log::debug!(
+				target: LOG_TARGET,
+				"Loaded {} banned peers and {} banned address ranges from {}",
+				entries.peers.len(),
+				entries.address_ranges.len(),
+				path.display(),
+			);
+		}
+
+		Ok(Self { entries: Arc::new(Mutex::new(entries)), path: Some(path) })
+	}
+
+	/// Get the banned peers and address ranges.
+	pub fn entries(&self) -> BannedEntries {
+		self.entries.lock().clone()
+	}
+
+	/// Ban `peer_id`. Returns `false` if the peer was already banned.
+	pub fn ban_peer(&self, peer_id: PeerId, reason: String) -> bool {
+		self.update(|entries| entries.peers.insert(peer_id, reason).is_none())
+	}
+
+	/// Lift the ban of `peer_id`. Returns `false` if the peer was not banned.
+	pub fn unban_peer(&self, peer_id: &PeerId) -> bool {
+		self.update(|entries| entries.peers.remove(peer_id).is_some())
+	}
+
+	/// Ban all addresses in `range`. Returns `false` if the range was already banned.
+	pub fn ban_address_range(&self, range: IpNetwork) -> bool {
+		self.update(|entries| {
+			if entries.address_ranges.contains(&range) {
+				return false
+			}
+			entries.address_ranges.push(range);
+			true
+		})
+	}
+
+	/// Lift the ban of `range`. Returns `false` if the range was not banned.
+	pub fn unban_address_range(&self, range: &IpNetwork) -> bool {
+		self.update(|entries| {
+			let len = entries.address_ranges.len();
+			entries.address_ranges.retain(|banned| banned != range);
+			entries.address_ranges.len() != len
+		})
+	}
+
+	/// Check whether the IP address of `address` belongs to a banned range.
+	pub fn is_address_banned(&self, address: &Multiaddr) -> bool {
+		let Some(ip) = address.iter().find_map(|protocol| match protocol {
+			Protocol::Ip4(ip) => Some(IpAddr::V4(ip)),
+			Protocol::Ip6(ip) => Some(IpAddr::V6(ip)),
+			_ => None,
+		}) else {
+			return false
+		};
+
+		self.entries.lock().address_ranges.iter().any(|range| match (range, ip) {
+			(IpNetwork::V4(range), IpAddr::V4(ip)) => range.contains(ip),
+			(IpNetwork::V6(range), IpAddr::V6(ip)) => range.contains(ip),
+			_ => false,
+		})
+	}
+
+	/// Apply `f` to the entries and persist them if it reports a change.
+	fn update(&self, f: impl FnOnce(&mut BannedEntries) -> bool) -> bool {
+		let mut entries = self.entries.lock();
+		if !f(&mut entries) {
+			return false
+		}
+
+		if let Err(e) = self.persist(&entries) {
+			log::error!(target: LOG_TARGET, "Failed to persist the ban list: {e}");
+		}
+		true
+	}
+
+	fn persist(&self, entries: &BannedEntries) -> io::Result<()> {
+		let Some(path) = &self.path else { return Ok(()) };
+
+		let file = BanListFile {
+			peers: entries
+				.peers
+				.iter()
+				.map(|(peer_id, reason)| (peer_id.to_base58(), reason.clone()))
+				.collect(),
+			address_ranges: entries.address_ranges.iter().map(ToString::to_string).collect(),
+		};
+		let encoded = serde_json::to_vec_pretty(&file)
+			.map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+
+		// Write to a temporary file first to not lose the list on a crash mid-write.
+		let tmp_path = path.with_extension("json.tmp");
+		fs::write(&tmp_path, encoded)?;
+		fs::rename(tmp_path, path)
+	}
+}
+
+impl BanList {
+	fn deny_banned(&self, address: &libp2p::Multiaddr) -> Result<(), ConnectionDenied> {
+		let address = address.clone().into();
+		if self.is_address_banned(&address) {
+			return Err(ConnectionDenied::new(BannedAddress(address)))
+		}
+		Ok(())
+	}
+}
+
+impl NetworkBehaviour for BanList {
+	type ConnectionHandler = dummy::ConnectionHandler;
+	type ToSwarm = void::Void;
+
+	fn handle_pending_inbound_connection(
+		&mut self,
+		_connection_id: ConnectionId,
+		_local_addr: &libp2p::Multiaddr,
+		remote_addr: &libp2p::Multiaddr,
+	) -> Result<(), ConnectionDenied> {
+		self.deny_banned(remote_addr)
+	}
+
+	fn handle_pending_outbound_connection(
+		&mut self,
+		_connection_id: ConnectionId,
+		_maybe_peer: Option<libp2p::PeerId>,
+		addresses: &[libp2p::Multiaddr],
+		_effective_role: Endpoint,
+	) -> Result<Vec<libp2p::Multiaddr>, ConnectionDenied> {
+		// `addresses` are given explicitly to the dial, the addresses of the known peers are
+		// filtered by the discovery
+		addresses.iter().try_for_each(|address| self.deny_banned(address))?;
+		Ok(Vec::new())
+	}
+
+	fn handle_established_inbound_connection(
+		&mut self,
+		_connection_id: ConnectionId,
+		_peer: libp2p::PeerId,
+		_local_addr: &libp2p::Multiaddr,
+		remote_addr: &libp2p::Multiaddr,
+	) -> Result<THandler<Self>, ConnectionDenied> {
+		self.deny_banned(remote_addr)?;
+		Ok(dummy::ConnectionHandler)
+	}
+
+	fn handle_established_outbound_connection(
+		&mut self,
+		_connection_id: ConnectionId,
+		_peer: libp2p::PeerId,
+		addr: &libp2p::Multiaddr,
+		_role_override: Endpoint,
+	) -> Result<THandler<Self>, ConnectionDenied> {
+		self.deny_banned(addr)?;
+		Ok(dummy::ConnectionHandler)
+	}
+
+	fn on_swarm_event(&mut self, _event: FromSwarm<Self::ConnectionHandler>) {}
+
+	fn on_connection_handler_event(
+		&mut self,
+		_peer_id: libp2p::PeerId,
+		_connection_id: ConnectionId,
+		event: THandlerOutEvent<Self>,
+	) {
+		void::unreachable(event)
+	}
+
+	fn poll(
+		&mut self,
+		_cx: &mut Context,
+		_params: &mut impl PollParameters,
+	) -> Poll<ToSwarm<Self::ToSwarm, THandlerInEvent<Self>>> {
+		Poll::Pending
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn ban_list_is_persisted() {
+		let dir = tempfile::tempdir().unwrap();
+		let peer_id = PeerId::random();
+		let range = IpNetwork::from_str("10.0.0.0/8").unwrap();
+
+		let ban_list = BanList::load(Some(dir.path())).unwrap();
+		assert!(ban_list.ban_peer(peer_id, "spam".into()));
+		assert!(!ban_list.ban_peer(peer_id, "spam".into()));
+		assert!(ban_list.ban_address_range(range));
+		assert!(!ban_list.ban_address_range(range));
+
+		let entries = ban_list.entries();
+		let ban_list = BanList::load(Some(dir.path())).unwrap();
+		assert_eq!(ban_list.entries(), entries);
+		assert_eq!(entries.peers.get(&peer_id).map(String::as_str), Some("spam"));
+
+		assert!(ban_list.unban_peer(&peer_id));
+		assert!(!ban_list.unban_peer(&peer_id));
+		assert!(ban_list.unban_address_range(&range));
+		assert_eq!(BanList::load(Some(dir.path())).unwrap().entries(), BannedEntries::default());
+	}
+
+	#[test]
+	fn addresses_in_banned_ranges_are_detected() {
+		let ban_list = BanList::load(None).unwrap();
+		ban_list.ban_address_range(IpNetwork::from_str("192.168.1.0/24").unwrap());
+		ban_list.ban_address_range(IpNetwork::from_str("fd00::/8").unwrap());
+
+		let banned = |address: &str| ban_list.is_address_banned(&address.parse().unwrap());
+		assert!(banned("/ip4/192.168.1.20/tcp/30333"));
+		assert!(!banned("/ip4/192.168.2.20/tcp/30333"));
+		assert!(banned("/ip6/fd00::1/tcp/30333"));
+		assert!(!banned("/ip6/fe80::1/tcp/30333"));
+		assert!(!banned("/dns/example.com/tcp/30333"));
+	}
+
+	#[test]
+	fn connections_with_banned_ranges_are_refused() {
+		let mut ban_list = BanList::load(None).unwrap();
+		ban_list.ban_address_range(IpNetwork::from_str("192.168.1.0/24").unwrap());
+
+		let local: libp2p::Multiaddr = "/ip4/127.0.0.1/tcp/30333".parse().unwrap();
+		let banned: libp2p::Multiaddr = "/ip4/192.168.1.20/tcp/30333".parse().unwrap();
+		let allowed: libp2p::Multiaddr = "/ip4/192.168.2.20/tcp/30333".parse().unwrap();
+		let connection_id = ConnectionId::new_unchecked(0);
+
+		assert!(ban_list
+			.handle_pending_inbound_connection(connection_id, &local, &banned)
+			.is_err());
+		assert!(ban_list
+			.handle_pending_inbound_connection(connection_id, &local, &allowed)
+			.is_ok());
+		assert!(ban_list
+			.handle_pending_outbound_connection(
+				connection_id,
+				None,
+				&[allowed.clone(), banned.clone()],
+				Endpoint::Dialer
+			)
+			.is_err());
+		assert!(ban_list
+			.handle_established_outbound_connection(
+				connection_id,
+				libp2p::PeerId::random(),
+				&banned,
+				Endpoint::Dialer
+			)
+			.is_err());
+		assert!(ban_list
+			.handle_established_outbound_connection(
+				connection_id,
+				libp2p::PeerId::random(),
+				&allowed,
+				Endpoint::Dialer
+			)
+			.is_ok());
+	}
+}
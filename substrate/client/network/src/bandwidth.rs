@@ -0,0 +1,627 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Upload and download rate limits of the node and of individual protocols.
+//!
+//! Limits are enforced above the transport, which makes them work the same way for both network
+//! backends: outbound notifications and responses wait for the upload budget before being handed
+//! to the transport. Outbound requests are sent right away, since the caller can't be made to wait,
+//! but they are charged to the upload budget, so the uploads following them wait longer.
+//!
+//! The download limits don't limit the rate at which the transport reads from the network: a
+//! response is only held back after it has been downloaded, until the download budget allows it.
+//! They limit the rate at which responses to our requests are processed, which slows down the
+//! follow-up requests of the caller and with them the data the remotes send to the node.
+//!
+//! Received requests and notifications are not subject to the download budget. By the time they
+//! are delivered their bytes have already been downloaded, so holding them back wouldn't reduce
+//! the download rate, only delay the work done for the remote, and serving requests is already
+//! limited by the upload budget of the responses.
+//!
+//! The node-wide [`BandwidthLimiter`] is created by the network backend from
+//! [`BandwidthLimits`] and handed out to protocols as [`ProtocolBandwidthLimiter`]s, which draw
+//! from both the budget of the protocol and the budget of the node. The node-wide limits are split
+//! between notifications and request/response protocols, see [`NOTIFICATIONS_SHARE_PERCENT`], so
+//! that serving sync requests can't starve consensus gossip.
+
+use crate::{
+	request_responses::{IncomingRequest, OutgoingResponse, RequestFailure},
+	types::ProtocolName,
+};
+
+use futures::{
+	channel::oneshot,
+	future::BoxFuture,
+	stream::{FuturesUnordered, StreamExt},
+	FutureExt,
+};
+use futures_timer::Delay;
+use parking_lot::Mutex;
+use sc_utils::mpsc::{tracing_unbounded, TracingUnboundedReceiver, TracingUnboundedSender};
+
+use std::{
+	collections::HashMap,
+	fmt,
+	num::NonZeroU32,
+	sync::{Arc, OnceLock},
+	time::{Duration, Instant},
+};
+
+/// Log target for this file.
+const LOG_TARGET: &str = "sub-libp2p::bandwidth";
+
+/// Share of the node-wide limits reserved for notification protocols, in percent.
+///
+/// Request/response protocols draw from the rest, so they never borrow from the budget of
+/// notifications, even when the latter is idle.
+pub const NOTIFICATIONS_SHARE_PERCENT: u32 = 25;
+
+/// Kind of the traffic of a protocol, drawing from its own share of the node-wide limits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TrafficKind {
+	/// Notification protocols, e.g. GRANDPA or transactions.
+	Notifications,
+
+	/// Request/response protocols, e.g. block or state requests.
+	RequestResponse,
+}
+
+/// Bandwidth limits of the node.
+#[derive(Debug, Clone, Default)]
+pub struct BandwidthLimits {
+	/// Maximum upload rate of the node in bytes per second. `None` means unlimited.
+	///
+	/// [`NOTIFICATIONS_SHARE_PERCENT`] of the rate is reserved for notification protocols.
+	pub upload: Option<NonZeroU32>,
+
+	/// Maximum rate in bytes per second at which the node processes responses to its requests.
+	/// `None` means unlimited.
+	///
+	/// This is not a limit on the rate the transport reads from the network. Responses are held
+	/// back after they have been downloaded, which only reduces the download rate by delaying the
+	/// follow-up requests. See the [module documentation](self).
+	///
+	/// [`NOTIFICATIONS_SHARE_PERCENT`] of the rate is reserved for notification protocols.
+	pub download: Option<NonZeroU32>,
+
+	/// Budgets of individual protocols, enforced on top of the node-wide limits.
+	///
+	/// Budgets are matched against protocol names by suffix so that they don't depend on the
+	/// genesis hash the names are prefixed with. If several budgets match a protocol, the first
+	/// one is used.
+	pub protocols: Vec<ProtocolBandwidthLimits>,
+}
+
+impl BandwidthLimits {
+	/// Returns `true` if no limit is configured.
+	pub fn is_unlimited(&self) -> bool {
+		self.upload.is_none() &&
+			self.download.is_none() &&
+			self.protocols
+				.iter()
+				.all(|limits| limits.upload.is_none() && limits.download.is_none())
+	}
+}
+
+/// Bandwidth budget of the protocols whose name ends with `protocol_suffix`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProtocolBandwidthLimits {
+	/// Suffix of the protocol names, e.g. `/sync/2` for block requests or `/grandpa/1`.
+	pub protocol_suffix: String,
+
+	/// Maximum upload rate of the protocols in bytes per second. `None` means unlimited.
+	pub upload: Option<NonZeroU32>,
+
+	/// Maximum rate in bytes per second at which responses received over the protocols are
+	/// processed. `None` means unlimited.
+	///
+	/// Like [`BandwidthLimits::download`], this doesn't limit the rate the transport reads from
+	/// the network.
+	pub download: Option<NonZeroU32>,
+}
+
+impl ProtocolBandwidthLimits {
+	/// Returns `true` if the budget applies to `protocol`.
+	fn matches(&self, protocol: &ProtocolName) -> bool {
+		protocol.ends_with(self.protocol_suffix.as_str())
+	}
+}
+
+/// Token bucket allowing bursts of up to one second worth of traffic.
+///
+/// Consumers may take more than the available tokens, in which case the bucket goes into debt and
+/// the consumer is told how long to wait for the debt to be repaid.
+#[derive(Debug)]
+struct TokenBucket {
+	/// Refill rate in bytes per second, also the capacity of the bucket.
+	rate: f64,
+
+	/// Available tokens and the time they were last refilled.
+	state: Mutex<(f64, Instant)>,
+}
+
+impl TokenBucket {
+	/// Create new full [`TokenBucket`].
+	fn new(rate: NonZeroU32) -> Self {
+		let rate = rate.get() as f64;
+		Self { rate, state: Mutex::new((rate, Instant::now())) }
+	}
+
+	/// Refill `state` with the tokens accrued since the last refill.
+	fn refill(&self, state: &mut (f64, Instant)) {
+		let now = Instant::now();
+		state.0 = (state.0 + now.duration_since(state.1).as_secs_f64() * self.rate).min(self.rate);
+		state.1 = now;
+	}
+
+	/// Take `bytes` tokens and return how long the consumer must wait before using them.
+	fn reserve(&self, bytes: usize) -> Duration {
+		let mut state = self.state.lock();
+		self.refill(&mut state);
+		state.0 -= bytes as f64;
+
+		if state.0 >= 0.0 {
+			Duration::ZERO
+		} else {
+			Duration::from_secs_f64(-state.0 / self.rate)
+		}
+	}
+
+	/// Check whether `bytes` tokens can be taken right away.
+	///
+	/// Amounts larger than the capacity of the bucket are allowed once the bucket is full.
+	fn has_capacity(&self, bytes: usize) -> bool {
+		let mut state = self.state.lock();
+		self.refill(&mut state);
+		state.0 >= (bytes as f64).min(self.rate)
+	}
+}
+
+/// Token buckets a protocol draws from.
+#[derive(Debug, Clone, Default)]
+struct Budgets {
+	upload: Vec<Arc<TokenBucket>>,
+	download: Vec<Arc<TokenBucket>>,
+}
+
+impl Budgets {
+	/// Create the buckets of the given rates.
+	fn new(upload: Option<NonZeroU32>, download: Option<NonZeroU32>) -> Self {
+		Self {
+			upload: upload.map(|rate| Arc::new(TokenBucket::new(rate))).into_iter().collect(),
+			download: download.map(|rate| Arc::new(TokenBucket::new(rate))).into_iter().collect(),
+		}
+	}
+}
+
+/// Split the node-wide `rate` into the rates of notifications and request/response protocols.
+fn split_rate(rate: Option<NonZeroU32>) -> (Option<NonZeroU32>, Option<NonZeroU32>) {
+	let Some(rate) = rate else { return (None, None) };
+	let notifications = (rate.get() as u64 * NOTIFICATIONS_SHARE_PERCENT as u64 / 100) as u32;
+	let notifications = NonZeroU32::new(notifications).unwrap_or(NonZeroU32::MIN);
+	let requests =
+		NonZeroU32::new(rate.get().saturating_sub(notifications.get())).unwrap_or(NonZeroU32::MIN);
+
+	(Some(notifications), Some(requests))
+}
+
+/// Wait until `bytes` may pass all of `buckets`.
+async fn acquire(buckets: &[Arc<TokenBucket>], bytes: usize) {
+	let delay = buckets.iter().map(|bucket| bucket.reserve(bytes)).max().unwrap_or_default();
+
+	if !delay.is_zero() {
+		Delay::new(delay).await;
+	}
+}
+
+/// Bandwidth limiter of a single protocol.
+///
+/// The limiter may be created before the network is started, in which case it doesn't limit
+/// anything until the network backend installs the budgets of the protocol.
+#[derive(Debug, Clone, Default)]
+pub struct ProtocolBandwidthLimiter {
+	budgets: Arc<OnceLock<Budgets>>,
+}
+
+impl ProtocolBandwidthLimiter {
+	/// Install the budgets of notification `protocol` from the node-wide `limiter`.
+	///
+	/// Has no effect if the budgets have already been installed.
+	pub(crate) fn install(&self, limiter: &BandwidthLimiter, protocol: &ProtocolName) {
+		let _ = self.budgets.set(limiter.budgets(protocol, TrafficKind::Notifications));
+	}
+
+	/// Returns `true` if the protocol has any limit.
+	pub fn is_limited(&self) -> bool {
+		self.budgets
+			.get()
+			.is_some_and(|budgets| !budgets.upload.is_empty() || !budgets.download.is_empty())
+	}
+
+	/// Returns `true` if the protocol has an upload limit.
+	fn is_upload_limited(&self) -> bool {
+		self.budgets.get().is_some_and(|budgets| !budgets.upload.is_empty())
+	}
+
+	/// Wait until `bytes` may be sent.
+	pub async fn upload(&self, bytes: usize) {
+		if let Some(budgets) = self.budgets.get() {
+			acquire(&budgets.upload, bytes).await;
+		}
+	}
+
+	/// Take `bytes` from the upload budget if they can be sent right away.
+	///
+	/// Returns `false`, without taking anything, if the budget is exhausted.
+	pub fn try_upload(&self, bytes: usize) -> bool {
+		let Some(budgets) = self.budgets.get() else { return true };
+
+		if !budgets.upload.iter().all(|bucket| bucket.has_capacity(bytes)) {
+			return false
+		}
+		budgets.upload.iter().for_each(|bucket| {
+			bucket.reserve(bytes);
+		});
+
+		true
+	}
+
+	/// Take `bytes` that are sent right away from the upload budget.
+	///
+	/// If the budget is exhausted it goes into debt, which the following uploads wait for.
+	pub fn charge_upload(&self, bytes: usize) {
+		if let Some(budgets) = self.budgets.get() {
+			budgets.upload.iter().for_each(|bucket| {
+				bucket.reserve(bytes);
+			});
+		}
+	}
+
+	/// Wait until `bytes` that were received may be processed.
+	pub async fn download(&self, bytes: usize) {
+		if let Some(budgets) = self.budgets.get() {
+			acquire(&budgets.download, bytes).await;
+		}
+	}
+}
+
+/// Node-wide bandwidth limiter.
+#[derive(Clone)]
+pub struct BandwidthLimiter {
+	/// Configured limits.
+	limits: Arc<BandwidthLimits>,
+
+	/// Share of the node-wide token buckets reserved for notification protocols.
+	notifications: Budgets,
+
+	/// Share of the node-wide token buckets used by request/response protocols.
+	requests: Budgets,
+
+	/// Token buckets of the budgets in [`BandwidthLimits::protocols`], in the same order.
+	protocols: Arc<Vec<Budgets>>,
+
+	/// Cached budgets of every protocol the limiter has been asked about.
+	cache: Arc<Mutex<HashMap<(ProtocolName, TrafficKind), Budgets>>>,
+
+	/// TX channel for throttled transfers, driven by the future returned from
+	/// [`BandwidthLimiter::new`].
+	tasks: TracingUnboundedSender<BoxFuture<'static, ()>>,
+}
+
+impl fmt::Debug for BandwidthLimiter {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("BandwidthLimiter").field("limits", &self.limits).finish()
+	}
+}
+
+impl BandwidthLimiter {
+	/// Create new [`BandwidthLimiter`].
+	///
+	/// Returns the limiter and a future that must be polled by the network backend for throttled
+	/// transfers to make progress.
+	pub fn new(limits: BandwidthLimits) -> (Self, BoxFuture<'static, ()>) {
+		let (notifications_upload, requests_upload) = split_rate(limits.upload);
+		let (notifications_download, requests_download) = split_rate(limits.download);
+		let notifications = Budgets::new(notifications_upload, notifications_download);
+		let requests = Budgets::new(requests_upload, requests_download);
+		let protocols = limits
+			.protocols
+			.iter()
+			.map(|limits| Budgets::new(limits.upload, limits.download))
+			.collect();
+		let (tasks, rx) = tracing_unbounded("mpsc_bandwidth_limiter", 10_000);
+
+		if !limits.is_unlimited() {
+			log::info!(
+				target: LOG_TARGET,
+				"Limiting bandwidth: upload {:?}, download {:?} bytes/s, {} protocol budgets",
+				limits.upload,
+				limits.download,
+				limits.protocols.len(),
+			);
+		}
+
+		(
+			Self {
+				limits: Arc::new(limits),
+				notifications,
+				requests,
+				protocols: Arc::new(protocols),
+				cache: Default::default(),
+				tasks,
+			},
+			Self::run(rx).boxed(),
+		)
+	}
+
+	/// Drive throttled transfers until all limiters have been dropped.
+	async fn run(mut rx: TracingUnboundedReceiver<BoxFuture<'static, ()>>) {
+		let mut pending = FuturesUnordered::new();
+
+		loop {
+			futures::select! {
+				task = rx.next() => match task {
+					Some(task) => pending.push(task),
+					None => break,
+				},
+				_ = pending.select_next_some() => {},
+			}
+		}
+
+		while pending.next().await.is_some() {}
+	}
+
+	/// Get the token buckets `protocol` draws from.
+	fn budgets(&self, protocol: &ProtocolName, kind: TrafficKind) -> Budgets {
+		self.cache
+			.lock()
+			.entry((protocol.clone(), kind))
+			.or_insert_with(|| {
+				let mut budgets = self
+					.limits
+					.protocols
+					.iter()
+					.position(|limits| limits.matches(protocol))
+					.map(|index| self.protocols[index].clone())
+					.unwrap_or_default();
+
+				let node = match kind {
+					TrafficKind::Notifications => &self.notifications,
+					TrafficKind::RequestResponse => &self.requests,
+				};
+				budgets.upload.extend(node.upload.iter().cloned());
+				budgets.download.extend(node.download.iter().cloned());
+				budgets
+			})
+			.clone()
+	}
+
+	/// Get the bandwidth limiter of `protocol`.
+	pub fn protocol(&self, protocol: &ProtocolName, kind: TrafficKind) -> ProtocolBandwidthLimiter {
+		let limiter = ProtocolBandwidthLimiter::default();
+		let _ = limiter.budgets.set(self.budgets(protocol, kind));
+		limiter
+	}
+
+	/// Throttle the responses sent back to the requests received over `protocol`.
+	///
+	/// The requests themselves are delivered as soon as they are received, see the module
+	/// documentation.
+	///
+	/// Returns the queue the network backend must deliver inbound requests to in place of
+	/// `inbound_queue`.
+	pub(crate) fn throttle_inbound_requests(
+		&self,
+		protocol: &ProtocolName,
+		inbound_queue: Option<async_channel::Sender<IncomingRequest>>,
+	) -> Option<async_channel::Sender<IncomingRequest>> {
+		let limiter = self.protocol(protocol, TrafficKind::RequestResponse);
+		let inbound_queue = inbound_queue?;
+
+		if !limiter.is_upload_limited() {
+			return Some(inbound_queue)
+		}
+
+		let (tx, rx) = match inbound_queue.capacity() {
+			Some(capacity) => async_channel::bounded(capacity),
+			None => async_channel::unbounded(),
+		};
+		let tasks = self.tasks.clone();
+
+		let _ = self.tasks.unbounded_send(
+			async move {
+				while let Ok(request) = rx.recv().await {
+					let (response_tx, response_rx) = oneshot::channel();
+					let pending_response = request.pending_response;
+					let request = IncomingRequest { pending_response: response_tx, ..request };
+
+					if inbound_queue.send(request).await.is_err() {
+						break
+					}
+
+					let limiter = limiter.clone();
+					let _ = tasks.unbounded_send(
+						async move {
+							let Ok(response) = response_rx.await else { return };
+
+							if let Ok(payload) = &response.result {
+								limiter.upload(payload.len()).await;
+							}
+							let _ = pending_response.send(response);
+						}
+						.boxed(),
+					);
+				}
+			}
+			.boxed(),
+		);
+
+		Some(tx)
+	}
+
+	/// Throttle an outbound request of `request_len` bytes sent over `protocol` and its response.
+	///
+	/// Returns the sender the network backend must deliver the response to in place of `tx`.
+	pub(crate) fn throttle_outbound_request(
+		&self,
+		protocol: &ProtocolName,
+		request_len: usize,
+		tx: oneshot::Sender<Result<(Vec<u8>, ProtocolName), RequestFailure>>,
+	) -> oneshot::Sender<Result<(Vec<u8>, ProtocolName), RequestFailure>> {
+		let limiter = self.protocol(protocol, TrafficKind::RequestResponse);
+
+		if !limiter.is_limited() {
+			return tx
+		}
+
+		// the caller can't be made to wait, so the request is sent right away and the uploads
+		// following it wait for the budget instead
+		limiter.charge_upload(request_len);
+
+		let (response_tx, response_rx) = oneshot::channel();
+		let _ = self.tasks.unbounded_send(
+			async move {
+				let Ok(response) = response_rx.await else { return };
+
+				if let Ok((payload, _)) = &response {
+					limiter.download(payload.len()).await;
+				}
+				let _ = tx.send(response);
+			}
+			.boxed(),
+		);
+
+		response_tx
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn rate(bytes_per_second: u32) -> Option<NonZeroU32> {
+		NonZeroU32::new(bytes_per_second)
+	}
+
+	#[test]
+	fn budgets_are_matched_by_protocol_suffix() {
+		let (limiter, _) = BandwidthLimiter::new(BandwidthLimits {
+			upload: rate(1000),
+			download: None,
+			protocols: vec![ProtocolBandwidthLimits {
+				protocol_suffix: "/sync/2".into(),
+				upload: rate(100),
+				download: None,
+			}],
+		});
+
+		let sync =
+			limiter.budgets(&ProtocolName::from("/abcd/sync/2"), TrafficKind::RequestResponse);
+		assert_eq!((sync.upload.len(), sync.download.len()), (2, 0));
+
+		let state =
+			limiter.budgets(&ProtocolName::from("/abcd/state/2"), TrafficKind::RequestResponse);
+		assert_eq!((state.upload.len(), state.download.len()), (1, 0));
+
+		// the node-wide bucket is shared between protocols of the same kind
+		assert!(Arc::ptr_eq(&sync.upload[1], &state.upload[0]));
+	}
+
+	#[test]
+	fn unlimited_protocol_is_not_limited() {
+		let (limiter, _) = BandwidthLimiter::new(BandwidthLimits::default());
+		let protocol = limiter
+			.protocol(&ProtocolName::from("/abcd/transactions/1"), TrafficKind::Notifications);
+
+		assert!(!protocol.is_limited());
+		assert!(protocol.try_upload(usize::MAX));
+		assert!(!ProtocolBandwidthLimiter::default().is_limited());
+	}
+
+	#[test]
+	fn exhausted_budget_rejects_uploads() {
+		let (limiter, _) =
+			BandwidthLimiter::new(BandwidthLimits { upload: rate(4000), ..Default::default() });
+		let protocol = limiter
+			.protocol(&ProtocolName::from("/abcd/transactions/1"), TrafficKind::Notifications);
+
+		assert!(protocol.try_upload(600));
+		assert!(!protocol.try_upload(600));
+		assert!(protocol.try_upload(300));
+	}
+
+	#[test]
+	fn outbound_requests_are_charged_to_exhausted_budget() {
+		let (limiter, _) =
+			BandwidthLimiter::new(BandwidthLimits { upload: rate(4000), ..Default::default() });
+		let protocol = ProtocolName::from("/abcd/sync/2");
+		let sync = limiter.protocol(&protocol, TrafficKind::RequestResponse);
+
+		assert!(sync.try_upload(3000));
+		let (tx, _rx) = oneshot::channel();
+		let _ = limiter.throttle_outbound_request(&protocol, 1500, tx);
+
+		// the request put the budget into debt, which the next response waits for
+		let requests = limiter.budgets(&protocol, TrafficKind::RequestResponse);
+		assert!(requests.upload[0].reserve(0) > Duration::from_millis(400));
+	}
+
+	#[test]
+	fn requests_never_borrow_from_notifications() {
+		let (limiter, _) = BandwidthLimiter::new(BandwidthLimits {
+			upload: rate(4000),
+			download: rate(4000),
+			..Default::default()
+		});
+		let sync =
+			limiter.protocol(&ProtocolName::from("/abcd/sync/2"), TrafficKind::RequestResponse);
+		let grandpa =
+			limiter.protocol(&ProtocolName::from("/abcd/grandpa/1"), TrafficKind::Notifications);
+
+		// responses exhaust their share and go into debt
+		assert!(sync.try_upload(3000));
+		assert!(!sync.try_upload(1));
+		let requests =
+			limiter.budgets(&ProtocolName::from("/abcd/sync/2"), TrafficKind::RequestResponse);
+		assert!(requests.download[0].reserve(10_000) > Duration::from_secs(2));
+
+		// while the reserved share of notifications stays untouched
+		assert!(grandpa.try_upload(1000));
+		assert!(!grandpa.try_upload(1));
+		let notifications =
+			limiter.budgets(&ProtocolName::from("/abcd/grandpa/1"), TrafficKind::Notifications);
+		assert_eq!(notifications.download[0].reserve(1000), Duration::ZERO);
+	}
+
+	#[test]
+	fn node_wide_rate_is_split_between_kinds() {
+		assert_eq!(split_rate(rate(4000)), (rate(1000), rate(3000)));
+		assert_eq!(split_rate(rate(1)), (rate(1), rate(1)));
+		assert_eq!(split_rate(None), (None, None));
+	}
+
+	#[test]
+	fn token_bucket_goes_into_debt() {
+		let bucket = TokenBucket::new(NonZeroU32::new(1000).unwrap());
+
+		assert_eq!(bucket.reserve(1000), Duration::ZERO);
+		let delay = bucket.reserve(500);
+		assert!(delay > Duration::from_millis(400) && delay <= Duration::from_millis(500));
+		assert!(!bucket.has_capacity(1));
+	}
+}
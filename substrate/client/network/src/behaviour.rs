@@ -17,6 +17,7 @@
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
 use crate::{
+	ban_list::BanList,
 	discovery::{DiscoveryBehaviour, DiscoveryConfig, DiscoveryOut},
 	event::DhtEvent,
 	peer_info,
@@ -56,6 +57,8 @@ pub use crate::request_responses::{InboundFailure, OutboundFailure, ResponseFail
 pub struct Behaviour<B: BlockT> {
 	/// Connection limits.
 	connection_limits: libp2p::connection_limits::Behaviour,
+	/// Refuses the connections from and to the banned address ranges.
+	ban_list: BanList,
 	/// All the substrate-specific protocols.
 	substrate: Protocol<B>,
 	/// Periodically pings and identifies the nodes we are connected to, and store information in a
@@ -184,6 +187,7 @@ impl<B: BlockT> Behaviour<B> {
 		peer_store_handle: Arc<dyn PeerStoreProvider>,
 		external_addresses: Arc<Mutex<HashSet<Multiaddr>>>,
 		connection_limits: ConnectionLimits,
+		ban_list: BanList,
 	) -> Result<Self, request_responses::RegisterError> {
 		Ok(Self {
 			substrate,
@@ -198,6 +202,7 @@ impl<B: BlockT> Behaviour<B> {
 				peer_store_handle,
 			)?,
 			connection_limits: libp2p::connection_limits::Behaviour::new(connection_limits),
+			ban_list,
 		})
 	}
 
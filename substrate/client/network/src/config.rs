@@ -22,6 +22,7 @@
 //! See the documentation of [`Params`].
 
 pub use crate::{
+	bandwidth::{BandwidthLimits, ProtocolBandwidthLimits},
 	discovery::DEFAULT_KADEMLIA_REPLICATION_FACTOR,
 	peer_store::PeerStoreProvider,
 	protocol::{notification_service, NotificationsSink, ProtocolHandlePair},
@@ -673,6 +674,9 @@ pub struct NetworkConfiguration {
 
 	/// Networking backend used for P2P communication.
 	pub network_backend: NetworkBackendType,
+
+	/// Upload and download rate limits of the node and of individual protocols.
+	pub bandwidth_limits: BandwidthLimits,
 }
 
 impl NetworkConfiguration {
@@ -706,6 +710,7 @@ impl NetworkConfiguration {
 			yamux_window_size: None,
 			ipfs_server: false,
 			network_backend: NetworkBackendType::Libp2p,
+			bandwidth_limits: BandwidthLimits::default(),
 		}
 	}
 
@@ -46,7 +46,7 @@
 //! active mechanism that asks nodes for the addresses they are listening on. Whenever we learn
 //! of a node's address, you must call `add_self_reported_address`.
 
-use crate::{config::ProtocolId, utils::LruHashSet};
+use crate::{ban_list::BanList, config::ProtocolId, utils::LruHashSet};
 
 use array_bytes::bytes2hex;
 use futures::prelude::*;
@@ -113,6 +113,7 @@ pub struct DiscoveryConfig {
 	kademlia_protocol: Option<StreamProtocol>,
 	kademlia_legacy_protocol: Option<StreamProtocol>,
 	kademlia_replication_factor: NonZeroUsize,
+	ban_list: BanList,
 }
 
 impl DiscoveryConfig {
@@ -131,6 +132,7 @@ impl DiscoveryConfig {
 			kademlia_legacy_protocol: None,
 			kademlia_replication_factor: NonZeroUsize::new(DEFAULT_KADEMLIA_REPLICATION_FACTOR)
 				.expect("value is a constant; constant is non-zero; qed."),
+			ban_list: BanList::default(),
 		}
 	}
 
@@ -202,6 +204,12 @@ impl DiscoveryConfig {
 		self
 	}
 
+	/// Sets the banned address ranges that are never dialed.
+	pub fn with_ban_list(&mut self, ban_list: BanList) -> &mut Self {
+		self.ban_list = ban_list;
+		self
+	}
+
 	/// Create a `DiscoveryBehaviour` from this config.
 	pub fn finish(self) -> DiscoveryBehaviour {
 		let Self {
@@ -216,6 +224,7 @@ impl DiscoveryConfig {
 			kademlia_protocol,
 			kademlia_legacy_protocol,
 			kademlia_replication_factor,
+			ban_list,
 		} = self;
 
 		let kademlia = if let Some(ref kademlia_protocol) = kademlia_protocol {
@@ -285,6 +294,7 @@ impl DiscoveryConfig {
 			),
 			records_to_publish: Default::default(),
 			kademlia_protocol,
+			ban_list,
 		}
 	}
 }
@@ -333,6 +343,8 @@ pub struct DiscoveryBehaviour {
 	/// Remove when all nodes are upgraded to genesis hash and fork ID-based Kademlia:
 	/// <https://github.com/paritytech/polkadot-sdk/issues/504>.
 	kademlia_protocol: Option<StreamProtocol>,
+	/// Address ranges banned by the node operator, never dialed.
+	ban_list: BanList,
 }
 
 impl DiscoveryBehaviour {
@@ -687,7 +699,10 @@ impl NetworkBehaviour for DiscoveryBehaviour {
 
 		trace!(target: "sub-libp2p", "Addresses of {:?}: {:?}", peer_id, list);
 
-		Ok(list.into_iter().collect())
+		Ok(list
+			.into_iter()
+			.filter(|address| !self.ban_list.is_address_banned(&address.clone().into()))
+			.collect())
 	}
 
 	fn on_swarm_event(&mut self, event: FromSwarm<Self::ConnectionHandler>) {
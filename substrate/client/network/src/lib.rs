@@ -250,6 +250,8 @@ mod protocol;
 #[cfg(test)]
 mod mock;
 
+pub mod ban_list;
+pub mod bandwidth;
 pub mod config;
 pub mod discovery;
 pub mod error;
@@ -280,7 +282,7 @@ pub use service::{
 	metrics::NotificationMetrics,
 	signature::Signature,
 	traits::{
-		KademliaKey, MessageSink, NetworkBackend, NetworkBlock, NetworkDHTProvider,
+		KademliaKey, MessageSink, NetworkBackend, NetworkBanList, NetworkBlock, NetworkDHTProvider,
 		NetworkEventStream, NetworkPeers, NetworkRequest, NetworkSigner, NetworkStateInfo,
 		NetworkStatus, NetworkStatusProvider, NetworkSyncForkRequest, NotificationConfig,
 		NotificationSender as NotificationSenderT, NotificationSenderError,
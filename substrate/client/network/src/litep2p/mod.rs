@@ -19,6 +19,8 @@
 //! `NetworkBackend` implementation for `litep2p`.
 
 use crate::{
+	ban_list::{BanList, BANNED_ADDRESS_RANGE},
+	bandwidth::BandwidthLimiter,
 	config::{
 		FullNetworkConfiguration, IncomingRequest, NodeKeyConfig, NotificationHandshake, Params,
 		SetConfig, TransportConfig,
@@ -175,6 +177,9 @@ pub struct Litep2pNetworkBackend {
 	/// Peerstore.
 	peerstore_handle: Arc<dyn PeerStoreProvider>,
 
+	/// Peers and address ranges banned by the node operator.
+	ban_list: BanList,
+
 	/// Block announce protocol name.
 	block_announce_protocol: ProtocolName,
 
@@ -228,7 +233,8 @@ impl Litep2pNetworkBackend {
 					return Some(peer)
 				}
 
-				if self.litep2p.add_known_address(peer.into(), addresses.clone().into_iter()) == 0 {
+				let addresses = self.unbanned_addresses(addresses);
+				if self.litep2p.add_known_address(peer.into(), addresses.into_iter()) == 0 {
 					log::warn!(
 						target: LOG_TARGET,
 						"couldn't add any addresses for {peer:?} and it won't be added as reserved peer",
@@ -241,6 +247,27 @@ impl Litep2pNetworkBackend {
 			})
 			.collect()
 	}
+
+	/// Drop the addresses from the banned address ranges, so they are never dialed.
+	fn unbanned_addresses(&self, addresses: Vec<Multiaddr>) -> Vec<Multiaddr> {
+		addresses
+			.into_iter()
+			.filter(|address| !self.ban_list.is_address_banned(&address.clone().into()))
+			.collect()
+	}
+
+	/// Report the connected peers using an address from a banned range.
+	fn disconnect_banned_addresses(&self) {
+		for (peer, context) in &self.peers {
+			if context
+				.endpoints
+				.values()
+				.any(|endpoint| self.ban_list.is_address_banned(&endpoint.address().clone().into()))
+			{
+				self.peerstore_handle.report_peer((*peer).into(), BANNED_ADDRESS_RANGE);
+			}
+		}
+	}
 }
 
 impl Litep2pNetworkBackend {
@@ -423,6 +450,8 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkBackend<B, H> for Litep2pNetworkBac
 		if let Some(path) = &params.network_config.network_config.net_config_path {
 			fs::create_dir_all(path)?;
 		}
+		let ban_list =
+			BanList::load(params.network_config.network_config.net_config_path.as_deref())?;
 
 		log::info!(target: LOG_TARGET, "Local node identity is: {local_peer_id}");
 		log::info!(target: LOG_TARGET, "Running litep2p network backend");
@@ -434,6 +463,9 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkBackend<B, H> for Litep2pNetworkBac
 			Self::configure_transport(&params.network_config).with_keypair(keypair.clone());
 		let known_addresses = params.network_config.known_addresses();
 		let peer_store_handle = params.network_config.peer_store_handle();
+		for (peer_id, reason) in ban_list.entries().peers {
+			peer_store_handle.ban_peer(peer_id, reason);
+		}
 		let executor = Arc::new(Litep2pExecutor { executor: params.executor });
 
 		let FullNetworkConfiguration {
@@ -443,12 +475,17 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkBackend<B, H> for Litep2pNetworkBac
 			..
 		} = params.network_config;
 
+		let (bandwidth_limiter, bandwidth_limiter_task) =
+			BandwidthLimiter::new(network_config.bandwidth_limits.clone());
+		executor.run(bandwidth_limiter_task);
+
 		// initialize notification protocols
 		//
 		// pass the protocol configuration to `Litep2pConfigBuilder` and save the TX channel
 		// to the protocol's `Peerset` together with the protocol name to allow other subsystems
 		// of Polkadot SDK to control connectivity of the notification protocol
 		let block_announce_protocol = params.block_announce_config.protocol_name().clone();
+		params.block_announce_config.set_bandwidth_limiter(&bandwidth_limiter);
 		let mut notif_protocols = HashMap::from_iter([(
 			params.block_announce_config.protocol_name().clone(),
 			params.block_announce_config.handle,
@@ -459,6 +496,7 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkBackend<B, H> for Litep2pNetworkBac
 			.into_iter()
 			.fold(config_builder, |config_builder, mut config| {
 				config.config.set_handshake(Roles::from(&params.role).encode());
+				config.set_bandwidth_limiter(&bandwidth_limiter);
 				notif_protocols.insert(config.protocol_name, config.handle);
 
 				config_builder.with_notification_protocol(config.config)
@@ -502,7 +540,8 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkBackend<B, H> for Litep2pNetworkBac
 					config.protocol_name.clone(),
 					handle,
 					Arc::clone(&peer_store_handle),
-					config.inbound_queue,
+					bandwidth_limiter
+						.throttle_inbound_requests(&config.protocol_name, config.inbound_queue),
 					request_response_receivers
 						.remove(&config.protocol_name)
 						.expect("receiver exists as it was just added and there are no duplicate protocols; qed"),
@@ -529,6 +568,10 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkBackend<B, H> for Litep2pNetworkBac
 					Some(Protocol::P2p(_)) => address,
 					_ => return acc,
 				};
+				if ban_list.is_address_banned(&address) {
+					log::debug!(target: LOG_TARGET, "ignoring banned address {address} of {peer:?}");
+					return acc
+				}
 
 				acc.entry(peer.into()).or_default().push(address.into());
 				peer_store_handle.add_known_peer(peer);
@@ -592,6 +635,8 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkBackend<B, H> for Litep2pNetworkBac
 			keypair.clone(),
 			cmd_tx,
 			Arc::clone(&peer_store_handle),
+			ban_list.clone(),
+			bandwidth_limiter,
 			notif_protocols.clone(),
 			block_announce_protocol.clone(),
 			request_response_senders,
@@ -618,6 +663,7 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkBackend<B, H> for Litep2pNetworkBac
 			pending_put_values: HashMap::new(),
 			pending_get_values: HashMap::new(),
 			peerstore_handle: peer_store_handle,
+			ban_list,
 			block_announce_protocol,
 			event_streams: out_events::OutChannels::new(None)?,
 			peers: HashMap::new(),
@@ -752,6 +798,11 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkBackend<B, H> for Litep2pNetworkBac
 								address.push(Protocol::P2p(litep2p::PeerId::from(peer).into()));
 							}
 
+							if self.ban_list.is_address_banned(&address.clone().into()) {
+								log::debug!(target: LOG_TARGET, "ignoring banned address ({address}) of {peer:?}");
+								continue
+							}
+
 							if self.litep2p.add_known_address(peer.into(), iter::once(address.clone())) == 0usize {
 								log::debug!(
 									target: LOG_TARGET,
@@ -781,6 +832,9 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkBackend<B, H> for Litep2pNetworkBac
 
 							let _ = handle.tx.unbounded_send(PeersetCommand::DisconnectPeer { peer });
 						}
+						NetworkServiceCommand::DisconnectBannedAddresses => {
+							self.disconnect_banned_addresses();
+						}
 						NetworkServiceCommand::SetReservedOnly {
 							protocol,
 							reserved_only,
@@ -810,7 +864,8 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkBackend<B, H> for Litep2pNetworkBac
 					Some(DiscoveryEvent::Discovered { addresses }) => {
 						// if at least one address was added for the peer, report the peer to `Peerstore`
 						for (peer, addresses) in Litep2pNetworkBackend::parse_addresses(addresses.into_iter()) {
-							if self.litep2p.add_known_address(peer.into(), addresses.clone().into_iter()) > 0 {
+							let addresses = self.unbanned_addresses(addresses);
+							if self.litep2p.add_known_address(peer.into(), addresses.into_iter()) > 0 {
 								self.peerstore_handle.add_known_peer(peer);
 							}
 						}
@@ -980,23 +1035,29 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkBackend<B, H> for Litep2pNetworkBac
 				},
 				event = self.litep2p.next_event() => match event {
 					Some(Litep2pEvent::ConnectionEstablished { peer, endpoint }) => {
-						let Some(metrics) = &self.metrics else {
-							continue;
-						};
-
-						let direction = match endpoint {
-							Endpoint::Dialer { .. } => "out",
-							Endpoint::Listener { .. } => {
-								// Increment incoming connections counter.
-								//
-								// Note: For litep2p these are represented by established negotiated connections,
-								// while for libp2p (legacy) these represent not-yet-negotiated connections.
-								metrics.incoming_connections_total.inc();
+						// `litep2p` doesn't allow refusing the connections before they are established,
+						// banned addresses are never dialed, while incoming connections are closed by
+						// the fatal reputation change
+						if self.ban_list.is_address_banned(&endpoint.address().clone().into()) {
+							log::debug!(target: LOG_TARGET, "{peer:?} connected from a banned address");
+							self.peerstore_handle.report_peer(peer.into(), BANNED_ADDRESS_RANGE);
+						}
 
-								"in"
-							},
-						};
-						metrics.connections_opened_total.with_label_values(&[direction]).inc();
+						if let Some(metrics) = &self.metrics {
+							let direction = match endpoint {
+								Endpoint::Dialer { .. } => "out",
+								Endpoint::Listener { .. } => {
+									// Increment incoming connections counter.
+									//
+									// Note: For litep2p these are represented by established negotiated connections,
+									// while for libp2p (legacy) these represent not-yet-negotiated connections.
+									metrics.incoming_connections_total.inc();
+
+									"in"
+								},
+							};
+							metrics.connections_opened_total.with_label_values(&[direction]).inc();
+						}
 
 						match self.peers.entry(peer) {
 							Entry::Vacant(entry) => {
@@ -1004,7 +1065,9 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkBackend<B, H> for Litep2pNetworkBac
 									endpoints: HashMap::from_iter([(endpoint.connection_id(), endpoint)]),
 									num_connections: 1usize,
 								});
-								metrics.distinct_peers_connections_opened_total.inc();
+								if let Some(metrics) = &self.metrics {
+									metrics.distinct_peers_connections_opened_total.inc();
+								}
 							}
 							Entry::Occupied(entry) => {
 								let entry = entry.into_mut();
@@ -1014,10 +1077,6 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkBackend<B, H> for Litep2pNetworkBac
 						}
 					}
 					Some(Litep2pEvent::ConnectionClosed { peer, connection_id }) => {
-						let Some(metrics) = &self.metrics else {
-							continue;
-						};
-
 						let Some(context) = self.peers.get_mut(&peer) else {
 							log::debug!(target: LOG_TARGET, "unknown peer disconnected: {peer:?} ({connection_id:?})");
 							continue
@@ -1038,11 +1097,15 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkBackend<B, H> for Litep2pNetworkBac
 							}
 						};
 
-						metrics.connections_closed_total.with_label_values(&[direction, "actively-closed"]).inc();
+						if let Some(metrics) = &self.metrics {
+							metrics.connections_closed_total.with_label_values(&[direction, "actively-closed"]).inc();
+						}
 
 						if context.num_connections == 0 {
 							self.peers.remove(&peer);
-							metrics.distinct_peers_connections_closed_total.inc();
+							if let Some(metrics) = &self.metrics {
+								metrics.distinct_peers_connections_closed_total.inc();
+							}
 						}
 					}
 					Some(Litep2pEvent::DialFailure { address, error }) => {
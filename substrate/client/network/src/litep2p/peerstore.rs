@@ -22,7 +22,7 @@
 //! such as their addresses, reputations, supported protocols etc.
 
 use crate::{
-	peer_store::{PeerStoreProvider, ProtocolHandle},
+	peer_store::{PeerReputation, PeerStoreProvider, ProtocolHandle, RECENT_REPUTATION_CHANGES},
 	service::{metrics::PeerStoreMetrics, traits::PeerStore},
 	ObservedRole, ReputationChange,
 };
@@ -34,7 +34,7 @@ use wasm_timer::Delay;
 use sc_network_types::PeerId;
 
 use std::{
-	collections::{HashMap, HashSet},
+	collections::{HashMap, HashSet, VecDeque},
 	sync::Arc,
 	time::{Duration, Instant},
 };
@@ -63,7 +63,7 @@ const INVERSE_DECREMENT: i32 = 200;
 const FORGET_AFTER: Duration = Duration::from_secs(3600);
 
 /// Peer information.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 struct PeerInfo {
 	/// Reputation of the peer.
 	reputation: i32,
@@ -73,17 +73,29 @@ struct PeerInfo {
 
 	/// Role of the peer, if known.
 	role: Option<ObservedRole>,
+
+	/// Reason of the explicit ban, if the peer was banned regardless of its reputation.
+	ban_reason: Option<String>,
+
+	/// Most recent reputation changes.
+	recent_changes: VecDeque<ReputationChange>,
 }
 
 impl Default for PeerInfo {
 	fn default() -> Self {
-		Self { reputation: 0i32, last_updated: Instant::now(), role: None }
+		Self {
+			reputation: 0i32,
+			last_updated: Instant::now(),
+			role: None,
+			ban_reason: None,
+			recent_changes: VecDeque::new(),
+		}
 	}
 }
 
 impl PeerInfo {
 	fn is_banned(&self) -> bool {
-		self.reputation < BANNED_THRESHOLD
+		self.ban_reason.is_some() || self.reputation < BANNED_THRESHOLD
 	}
 
 	fn add_reputation(&mut self, increment: i32) {
@@ -91,6 +103,13 @@ impl PeerInfo {
 		self.bump_last_updated();
 	}
 
+	fn record_change(&mut self, change: ReputationChange) {
+		if self.recent_changes.len() == RECENT_REPUTATION_CHANGES {
+			self.recent_changes.pop_front();
+		}
+		self.recent_changes.push_back(change);
+	}
+
 	fn decay_reputation(&mut self, seconds_passed: u64) {
 		// Note that decaying the reputation value happens "on its own",
 		// so we don't do `bump_last_updated()`.
@@ -137,10 +156,7 @@ impl PeerstoreHandle {
 
 	/// Add known peer to [`Peerstore`].
 	pub fn add_known_peer(&self, peer: PeerId) {
-		self.0
-			.lock()
-			.peers
-			.insert(peer, PeerInfo { reputation: 0i32, last_updated: Instant::now(), role: None });
+		self.0.lock().peers.insert(peer, PeerInfo::default());
 	}
 
 	pub fn peer_count(&self) -> usize {
@@ -166,7 +182,9 @@ impl PeerstoreHandle {
 			if info.is_banned() {
 				num_banned_peers += 1;
 			}
-			info.reputation != 0 || info.last_updated + FORGET_AFTER > now
+			info.ban_reason.is_some() ||
+				info.reputation != 0 ||
+				info.last_updated + FORGET_AFTER > now
 		});
 
 		if let Some(metrics) = &lock.metrics {
@@ -197,6 +215,7 @@ impl PeerStoreProvider for PeerstoreHandle {
 		let peer_info = lock.peers.entry(peer_id).or_default();
 		let was_banned = peer_info.is_banned();
 		peer_info.add_reputation(change.value);
+		peer_info.record_change(change);
 		let peer_reputation = peer_info.reputation;
 
 		log::trace!(
@@ -290,6 +309,44 @@ impl PeerStoreProvider for PeerstoreHandle {
 	fn add_known_peer(&self, peer: PeerId) {
 		self.0.lock().peers.entry(peer).or_default().last_updated = Instant::now();
 	}
+
+	/// Ban peer regardless of its reputation until it's unbanned.
+	fn ban_peer(&self, peer: PeerId, reason: String) {
+		log::info!(target: LOG_TARGET, "Peer {peer} is banned by the operator. Reason: {reason}.");
+
+		let mut lock = self.0.lock();
+		let peer_info = lock.peers.entry(peer).or_default();
+		peer_info.ban_reason = Some(reason);
+		peer_info.bump_last_updated();
+
+		lock.protocols.iter().for_each(|handle| handle.disconnect_peer(peer));
+	}
+
+	/// Lift the explicit ban of the peer.
+	fn unban_peer(&self, peer: PeerId) {
+		if let Some(peer_info) = self.0.lock().peers.get_mut(&peer) {
+			if peer_info.ban_reason.take().is_some() {
+				log::info!(target: LOG_TARGET, "Peer {peer} is unbanned by the operator.");
+				peer_info.bump_last_updated();
+			}
+		}
+	}
+
+	/// Get reputations of all known peers.
+	fn peer_reputations(&self) -> Vec<PeerReputation> {
+		self.0
+			.lock()
+			.peers
+			.iter()
+			.map(|(peer, info)| PeerReputation {
+				peer_id: *peer,
+				reputation: info.reputation,
+				is_banned: info.is_banned(),
+				ban_reason: info.ban_reason.clone(),
+				recent_changes: info.recent_changes.iter().copied().collect(),
+			})
+			.collect()
+	}
 }
 
 /// `Peerstore` handle for testing.
@@ -377,6 +434,7 @@ impl PeerStore for Peerstore {
 #[cfg(test)]
 mod tests {
 	use super::{PeerInfo, PeerStoreProvider, Peerstore};
+	use sc_network_common::types::ReputationChange;
 
 	#[test]
 	fn decaying_zero_reputation_yields_zero() {
@@ -478,4 +536,31 @@ mod tests {
 		assert_eq!(metrics.num_discovered.get(), 3);
 		assert_eq!(metrics.num_banned_peers.get(), 2);
 	}
+
+	#[test]
+	fn explicitly_banned_peers_stay_banned() {
+		let peer_a = sc_network_types::PeerId::random();
+		let peer_b = sc_network_types::PeerId::random();
+
+		let mut peerstore = Peerstore::new(vec![peer_a, peer_b], None);
+		let handle = peerstore.handle();
+
+		handle.report_peer(peer_a, ReputationChange::new(-1, "test"));
+		handle.ban_peer(peer_b, "spam".into());
+
+		// Explicit bans don't decay.
+		handle.progress_time(100_000);
+		assert!(!handle.is_banned(&peer_a));
+		assert!(handle.is_banned(&peer_b));
+		assert_eq!(handle.outgoing_candidates(2, Default::default()), vec![peer_a]);
+
+		let reputations = handle.peer_reputations();
+		let reputation_a = reputations.iter().find(|r| r.peer_id == peer_a).unwrap();
+		assert_eq!(reputation_a.recent_changes, vec![ReputationChange::new(-1, "test")]);
+		let reputation_b = reputations.iter().find(|r| r.peer_id == peer_b).unwrap();
+		assert_eq!(reputation_b.ban_reason.as_deref(), Some("spam"));
+
+		handle.unban_peer(peer_b);
+		assert!(!handle.is_banned(&peer_b));
+	}
 }
@@ -19,21 +19,24 @@
 //! `NetworkService` implementation for `litep2p`.
 
 use crate::{
+	ban_list::{BanList, BannedEntries},
+	bandwidth::BandwidthLimiter,
 	config::MultiaddrWithPeerId,
 	litep2p::shim::{
 		notification::{config::ProtocolControlHandle, peerset::PeersetCommand},
 		request_response::OutboundRequest,
 	},
 	network_state::NetworkState,
-	peer_store::PeerStoreProvider,
+	peer_store::{PeerReputation, PeerStoreProvider},
 	service::out_events,
-	Event, IfDisconnected, NetworkDHTProvider, NetworkEventStream, NetworkPeers, NetworkRequest,
-	NetworkSigner, NetworkStateInfo, NetworkStatus, NetworkStatusProvider, ProtocolName,
-	RequestFailure, Signature,
+	Event, IfDisconnected, NetworkBanList, NetworkDHTProvider, NetworkEventStream, NetworkPeers,
+	NetworkRequest, NetworkSigner, NetworkStateInfo, NetworkStatus, NetworkStatusProvider,
+	ProtocolName, RequestFailure, Signature,
 };
 
 use codec::DecodeAll;
 use futures::{channel::oneshot, stream::BoxStream};
+use ip_network::IpNetwork;
 use libp2p::identity::SigningError;
 use litep2p::{
 	addresses::PublicAddresses, crypto::ed25519::Keypair,
@@ -169,6 +172,9 @@ pub enum NetworkServiceCommand {
 		/// Sender for the events.
 		tx: out_events::Sender,
 	},
+
+	/// Disconnect the connected peers using an address from a banned range.
+	DisconnectBannedAddresses,
 }
 
 /// `NetworkService` implementation for `litep2p`.
@@ -186,6 +192,12 @@ pub struct Litep2pNetworkService {
 	/// Handle to `PeerStore`.
 	peer_store_handle: Arc<dyn PeerStoreProvider>,
 
+	/// Peers and address ranges banned by the node operator.
+	ban_list: BanList,
+
+	/// Upload and download rate limiter.
+	bandwidth_limiter: BandwidthLimiter,
+
 	/// Peerset handles.
 	peerset_handles: HashMap<ProtocolName, ProtocolControlHandle>,
 
@@ -209,6 +221,8 @@ impl Litep2pNetworkService {
 		keypair: Keypair,
 		cmd_tx: TracingUnboundedSender<NetworkServiceCommand>,
 		peer_store_handle: Arc<dyn PeerStoreProvider>,
+		ban_list: BanList,
+		bandwidth_limiter: BandwidthLimiter,
 		peerset_handles: HashMap<ProtocolName, ProtocolControlHandle>,
 		block_announce_protocol: ProtocolName,
 		request_response_protocols: HashMap<ProtocolName, TracingUnboundedSender<OutboundRequest>>,
@@ -220,6 +234,8 @@ impl Litep2pNetworkService {
 			keypair,
 			cmd_tx,
 			peer_store_handle,
+			ban_list,
+			bandwidth_limiter,
 			peerset_handles,
 			block_announce_protocol,
 			request_response_protocols,
@@ -336,6 +352,36 @@ impl NetworkStatusProvider for Litep2pNetworkService {
 	}
 }
 
+impl NetworkBanList for Litep2pNetworkService {
+	fn ban_peer(&self, peer_id: PeerId, reason: String) {
+		self.ban_list.ban_peer(peer_id, reason.clone());
+		self.peer_store_handle.ban_peer(peer_id, reason);
+	}
+
+	fn unban_peer(&self, peer_id: PeerId) {
+		self.ban_list.unban_peer(&peer_id);
+		self.peer_store_handle.unban_peer(peer_id);
+	}
+
+	fn ban_address_range(&self, range: IpNetwork) {
+		if self.ban_list.ban_address_range(range) {
+			let _ = self.cmd_tx.unbounded_send(NetworkServiceCommand::DisconnectBannedAddresses);
+		}
+	}
+
+	fn unban_address_range(&self, range: IpNetwork) {
+		self.ban_list.unban_address_range(&range);
+	}
+
+	fn banned_entries(&self) -> BannedEntries {
+		self.ban_list.entries()
+	}
+
+	fn peer_reputations(&self) -> Vec<PeerReputation> {
+		self.peer_store_handle.peer_reputations()
+	}
+}
+
 // Manual implementation to avoid extra boxing here
 // TODO: functions modifying peerset state could be modified to call peerset directly if the
 // `Multiaddr` only contains a `PeerId`
@@ -525,6 +571,11 @@ impl NetworkRequest for Litep2pNetworkService {
 	) {
 		match self.request_response_protocols.get(&protocol) {
 			Some(tx) => {
+				let sender = self.bandwidth_limiter.throttle_outbound_request(
+					&protocol,
+					request.len(),
+					sender,
+				);
 				let _ = tx.unbounded_send(OutboundRequest::new(
 					peer,
 					request,
@@ -19,6 +19,7 @@
 //! `litep2p` notification protocol configuration.
 
 use crate::{
+	bandwidth::{BandwidthLimiter, ProtocolBandwidthLimiter},
 	config::{MultiaddrWithPeerId, NonReservedPeerMode, NotificationHandshake, SetConfig},
 	litep2p::shim::notification::{
 		peerset::{Peerset, PeersetCommand},
@@ -73,6 +74,9 @@ pub struct NotificationProtocolConfig {
 
 	/// Handle for controlling the notification protocol.
 	pub handle: ProtocolControlHandle,
+
+	/// Bandwidth limiter of the protocol, shared with [`NotificationProtocol`].
+	bandwidth: ProtocolBandwidthLimiter,
 }
 
 impl NotificationProtocolConfig {
@@ -114,7 +118,14 @@ impl NotificationProtocolConfig {
 		// initialize the actual object implementing `NotificationService` and combine the
 		// `litep2p::NotificationHandle` with `Peerset` to implement a full and independent
 		// notification protocol runner
-		let protocol = NotificationProtocol::new(protocol_name.clone(), handle, peerset, metrics);
+		let bandwidth = ProtocolBandwidthLimiter::default();
+		let protocol = NotificationProtocol::new(
+			protocol_name.clone(),
+			handle,
+			peerset,
+			metrics,
+			bandwidth.clone(),
+		);
 
 		(
 			Self {
@@ -123,6 +134,7 @@ impl NotificationProtocolConfig {
 				set_config,
 				config,
 				handle: ProtocolControlHandle::new(peerset_tx, connected_peers),
+				bandwidth,
 			},
 			Box::new(protocol),
 		)
@@ -154,6 +166,11 @@ impl NotificationProtocolConfig {
 	pub fn max_notification_size(&self) -> usize {
 		self.max_notification_size
 	}
+
+	/// Set bandwidth limits of the protocol from the node-wide `limiter`.
+	pub(crate) fn set_bandwidth_limiter(&self, limiter: &BandwidthLimiter) {
+		self.bandwidth.install(limiter, &self.protocol_name);
+	}
 }
 
 impl NotificationConfig for NotificationProtocolConfig {
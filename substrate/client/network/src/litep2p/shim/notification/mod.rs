@@ -20,6 +20,7 @@
 //! with `NotificationService`.
 
 use crate::{
+	bandwidth::ProtocolBandwidthLimiter,
 	error::Error,
 	litep2p::shim::notification::peerset::{OpenResult, Peerset, PeersetNotificationCommand},
 	service::{
@@ -62,6 +63,9 @@ pub struct Litep2pMessageSink {
 
 	/// Notification metrics.
 	metrics: NotificationMetrics,
+
+	/// Bandwidth limiter of the protocol.
+	bandwidth: ProtocolBandwidthLimiter,
 }
 
 impl Litep2pMessageSink {
@@ -71,8 +75,9 @@ impl Litep2pMessageSink {
 		protocol: ProtocolName,
 		sink: NotificationSink,
 		metrics: NotificationMetrics,
+		bandwidth: ProtocolBandwidthLimiter,
 	) -> Self {
-		Self { protocol, peer, sink, metrics }
+		Self { protocol, peer, sink, metrics, bandwidth }
 	}
 }
 
@@ -82,6 +87,16 @@ impl MessageSink for Litep2pMessageSink {
 	fn send_sync_notification(&self, notification: Vec<u8>) {
 		let size = notification.len();
 
+		if !self.bandwidth.try_upload(size) {
+			log::trace!(
+				target: LOG_TARGET,
+				"{}: upload budget exceeded, dropping notification to {:?}",
+				self.protocol,
+				self.peer,
+			);
+			return
+		}
+
 		match self.sink.send_sync_notification(notification) {
 			Ok(_) => self.metrics.register_notification_sent(&self.protocol, size),
 			Err(error) => log::trace!(
@@ -99,6 +114,7 @@ impl MessageSink for Litep2pMessageSink {
 	/// Returns an error if the peer does not exist.
 	async fn send_async_notification(&self, notification: Vec<u8>) -> Result<(), Error> {
 		let size = notification.len();
+		self.bandwidth.upload(size).await;
 
 		match self.sink.send_async_notification(notification).await {
 			Ok(_) => {
@@ -142,6 +158,9 @@ pub struct NotificationProtocol {
 
 	/// Notification metrics.
 	metrics: NotificationMetrics,
+
+	/// Bandwidth limiter of the protocol.
+	bandwidth: ProtocolBandwidthLimiter,
 }
 
 impl fmt::Debug for NotificationProtocol {
@@ -160,12 +179,14 @@ impl NotificationProtocol {
 		handle: NotificationHandle,
 		peerset: Peerset,
 		metrics: NotificationMetrics,
+		bandwidth: ProtocolBandwidthLimiter,
 	) -> Self {
 		Self {
 			protocol,
 			handle,
 			peerset,
 			metrics,
+			bandwidth,
 			pending_cancels: HashSet::new(),
 			pending_validations: FuturesUnordered::new(),
 		}
@@ -201,6 +222,15 @@ impl NotificationService for NotificationProtocol {
 	fn send_sync_notification(&mut self, peer: &PeerId, notification: Vec<u8>) {
 		let size = notification.len();
 
+		if !self.bandwidth.try_upload(size) {
+			log::trace!(
+				target: LOG_TARGET,
+				"{}: upload budget exceeded, dropping notification to {peer:?}",
+				self.protocol,
+			);
+			return
+		}
+
 		if let Ok(_) = self.handle.send_sync_notification(peer.into(), notification) {
 			self.metrics.register_notification_sent(&self.protocol, size);
 		}
@@ -212,6 +242,7 @@ impl NotificationService for NotificationProtocol {
 		notification: Vec<u8>,
 	) -> Result<(), Error> {
 		let size = notification.len();
+		self.bandwidth.upload(size).await;
 
 		match self.handle.send_async_notification(peer.into(), notification).await {
 			Ok(_) => {
@@ -258,6 +289,7 @@ impl NotificationService for NotificationProtocol {
 				self.protocol.clone(),
 				sink,
 				self.metrics.clone(),
+				self.bandwidth.clone(),
 			));
 			sink
 		})
@@ -19,7 +19,7 @@
 //! Mocked components for tests.
 
 use crate::{
-	peer_store::{PeerStoreProvider, ProtocolHandle},
+	peer_store::{PeerReputation, PeerStoreProvider, ProtocolHandle},
 	ReputationChange,
 };
 
@@ -70,4 +70,16 @@ impl PeerStoreProvider for MockPeerStore {
 	fn add_known_peer(&self, _peer_id: PeerId) {
 		unimplemented!()
 	}
+
+	fn ban_peer(&self, _peer_id: PeerId, _reason: String) {
+		unimplemented!()
+	}
+
+	fn unban_peer(&self, _peer_id: PeerId) {
+		unimplemented!()
+	}
+
+	fn peer_reputations(&self) -> Vec<PeerReputation> {
+		Vec::new()
+	}
 }
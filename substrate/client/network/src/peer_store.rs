@@ -29,7 +29,7 @@ use prometheus_endpoint::Registry;
 use sc_network_common::{role::ObservedRole, types::ReputationChange};
 use std::{
 	cmp::{Ord, Ordering, PartialOrd},
-	collections::{hash_map::Entry, HashMap, HashSet},
+	collections::{hash_map::Entry, HashMap, HashSet, VecDeque},
 	fmt::Debug,
 	sync::Arc,
 	time::{Duration, Instant},
@@ -59,6 +59,8 @@ const INVERSE_DECREMENT: i32 = 200;
 /// Amount of time between the moment we last updated the [`PeerStore`] entry and the moment we
 /// remove it, once the reputation value reaches 0.
 const FORGET_AFTER: Duration = Duration::from_secs(3600);
+/// Number of most recent reputation changes remembered for every peer.
+pub const RECENT_REPUTATION_CHANGES: usize = 10;
 
 /// Trait describing the required functionality from a `Peerset` handle.
 pub trait ProtocolHandle: Debug + Send + Sync {
@@ -98,6 +100,30 @@ pub trait PeerStoreProvider: Debug + Send + Sync {
 
 	/// Add known peer.
 	fn add_known_peer(&self, peer_id: sc_network_types::PeerId);
+
+	/// Ban peer regardless of its reputation until [`PeerStoreProvider::unban_peer`] is called.
+	fn ban_peer(&self, peer_id: sc_network_types::PeerId, reason: String);
+
+	/// Lift the ban set by [`PeerStoreProvider::ban_peer`].
+	fn unban_peer(&self, peer_id: sc_network_types::PeerId);
+
+	/// Get reputations of all known peers.
+	fn peer_reputations(&self) -> Vec<PeerReputation>;
+}
+
+/// Reputation of a known peer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PeerReputation {
+	/// Peer ID.
+	pub peer_id: sc_network_types::PeerId,
+	/// Current reputation value.
+	pub reputation: i32,
+	/// Whether the peer is banned, either by reputation or by [`PeerStoreProvider::ban_peer`].
+	pub is_banned: bool,
+	/// Reason given to [`PeerStoreProvider::ban_peer`], if the peer was banned explicitly.
+	pub ban_reason: Option<String>,
+	/// Up to [`RECENT_REPUTATION_CHANGES`] most recent reputation changes, oldest first.
+	pub recent_changes: Vec<ReputationChange>,
 }
 
 /// Actual implementation of peer reputations and connection candidates provider.
@@ -154,9 +180,21 @@ impl PeerStoreProvider for PeerStoreHandle {
 	fn add_known_peer(&self, peer_id: sc_network_types::PeerId) {
 		self.inner.lock().add_known_peer(peer_id.into());
 	}
+
+	fn ban_peer(&self, peer_id: sc_network_types::PeerId, reason: String) {
+		self.inner.lock().ban_peer(peer_id.into(), reason);
+	}
+
+	fn unban_peer(&self, peer_id: sc_network_types::PeerId) {
+		self.inner.lock().unban_peer(&peer_id.into());
+	}
+
+	fn peer_reputations(&self) -> Vec<PeerReputation> {
+		self.inner.lock().peer_reputations()
+	}
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 struct PeerInfo {
 	/// Reputation of the peer.
 	reputation: i32,
@@ -166,11 +204,23 @@ struct PeerInfo {
 
 	/// Role of the peer, if known.
 	role: Option<ObservedRole>,
+
+	/// Reason of the explicit ban, if the peer was banned regardless of its reputation.
+	ban_reason: Option<String>,
+
+	/// Most recent reputation changes.
+	recent_changes: VecDeque<ReputationChange>,
 }
 
 impl Default for PeerInfo {
 	fn default() -> Self {
-		Self { reputation: 0, last_updated: Instant::now(), role: None }
+		Self {
+			reputation: 0,
+			last_updated: Instant::now(),
+			role: None,
+			ban_reason: None,
+			recent_changes: VecDeque::new(),
+		}
 	}
 }
 
@@ -197,7 +247,7 @@ impl PartialOrd for PeerInfo {
 
 impl PeerInfo {
 	fn is_banned(&self) -> bool {
-		self.reputation < BANNED_THRESHOLD
+		self.ban_reason.is_some() || self.reputation < BANNED_THRESHOLD
 	}
 
 	fn add_reputation(&mut self, increment: i32) {
@@ -205,6 +255,13 @@ impl PeerInfo {
 		self.bump_last_updated();
 	}
 
+	fn record_change(&mut self, change: ReputationChange) {
+		if self.recent_changes.len() == RECENT_REPUTATION_CHANGES {
+			self.recent_changes.pop_front();
+		}
+		self.recent_changes.push_back(change);
+	}
+
 	fn decay_reputation(&mut self, seconds_passed: u64) {
 		// Note that decaying the reputation value happens "on its own",
 		// so we don't do `bump_last_updated()`.
@@ -248,6 +305,8 @@ impl PeerStoreInner {
 	fn report_disconnect(&mut self, peer_id: PeerId) {
 		let peer_info = self.peers.entry(peer_id).or_default();
 		peer_info.add_reputation(DISCONNECT_REPUTATION_CHANGE);
+		peer_info
+			.record_change(ReputationChange::new(DISCONNECT_REPUTATION_CHANGE, "Disconnected"));
 
 		log::trace!(
 			target: LOG_TARGET,
@@ -262,6 +321,7 @@ impl PeerStoreInner {
 		let peer_info = self.peers.entry(peer_id).or_default();
 		let was_banned = peer_info.is_banned();
 		peer_info.add_reputation(change.value);
+		peer_info.record_change(change);
 
 		log::trace!(
 			target: LOG_TARGET,
@@ -337,12 +397,44 @@ impl PeerStoreInner {
 		self.peers.get(peer_id).map_or(None, |info| info.role)
 	}
 
+	fn ban_peer(&mut self, peer_id: PeerId, reason: String) {
+		log::info!(target: LOG_TARGET, "Peer {peer_id} is banned by the operator. Reason: {reason}.");
+
+		let peer_info = self.peers.entry(peer_id).or_default();
+		peer_info.ban_reason = Some(reason);
+		peer_info.bump_last_updated();
+
+		self.protocols.iter().for_each(|handle| handle.disconnect_peer(peer_id.into()));
+	}
+
+	fn unban_peer(&mut self, peer_id: &PeerId) {
+		if let Some(peer_info) = self.peers.get_mut(peer_id) {
+			if peer_info.ban_reason.take().is_some() {
+				log::info!(target: LOG_TARGET, "Peer {peer_id} is unbanned by the operator.");
+				peer_info.bump_last_updated();
+			}
+		}
+	}
+
+	fn peer_reputations(&self) -> Vec<PeerReputation> {
+		self.peers
+			.iter()
+			.map(|(peer_id, info)| PeerReputation {
+				peer_id: (*peer_id).into(),
+				reputation: info.reputation,
+				is_banned: info.is_banned(),
+				ban_reason: info.ban_reason.clone(),
+				recent_changes: info.recent_changes.iter().copied().collect(),
+			})
+			.collect()
+	}
+
 	fn outgoing_candidates(&self, count: usize, ignored: HashSet<PeerId>) -> Vec<PeerId> {
 		let mut candidates = self
 			.peers
 			.iter()
 			.filter_map(|(peer_id, info)| {
-				(!info.is_banned() && !ignored.contains(peer_id)).then_some((*peer_id, *info))
+				(!info.is_banned() && !ignored.contains(peer_id)).then_some((*peer_id, info))
 			})
 			.collect::<Vec<_>>();
 		let count = std::cmp::min(count, candidates.len());
@@ -370,7 +462,9 @@ impl PeerStoreInner {
 				num_banned_peers += 1;
 			}
 
-			info.reputation != 0 || info.last_updated + FORGET_AFTER > now
+			info.ban_reason.is_some() ||
+				info.reputation != 0 ||
+				info.last_updated + FORGET_AFTER > now
 		});
 
 		if let Some(metrics) = &self.metrics {
@@ -468,7 +562,8 @@ impl PeerStoreT for PeerStore {
 
 #[cfg(test)]
 mod tests {
-	use super::{PeerInfo, PeerStore, PeerStoreProvider};
+	use super::{PeerInfo, PeerStore, PeerStoreProvider, RECENT_REPUTATION_CHANGES};
+	use sc_network_common::types::ReputationChange;
 
 	#[test]
 	fn decaying_zero_reputation_yields_zero() {
@@ -570,4 +665,34 @@ mod tests {
 		assert_eq!(metrics.num_discovered.get(), 3);
 		assert_eq!(metrics.num_banned_peers.get(), 2);
 	}
+
+	#[test]
+	fn explicitly_banned_peers_stay_banned() {
+		let peer_a = sc_network_types::PeerId::random();
+		let peer_b = sc_network_types::PeerId::random();
+
+		let peerstore = PeerStore::new(vec![peer_a.into(), peer_b.into()], None);
+		let handle = peerstore.handle();
+
+		for _ in 0..RECENT_REPUTATION_CHANGES + 1 {
+			handle.report_peer(peer_a, ReputationChange::new(-1, "test"));
+		}
+		handle.ban_peer(peer_b, "spam".into());
+
+		// Explicit bans don't decay.
+		handle.inner.lock().progress_time(100_000);
+		assert!(!handle.is_banned(&peer_a));
+		assert!(handle.is_banned(&peer_b));
+		assert_eq!(handle.outgoing_candidates(2, Default::default()), vec![peer_a]);
+
+		let mut reputations = handle.peer_reputations();
+		reputations.sort_by_key(|reputation| reputation.peer_id != peer_a);
+		assert_eq!(reputations[0].recent_changes.len(), RECENT_REPUTATION_CHANGES);
+		assert_eq!(reputations[0].ban_reason, None);
+		assert!(reputations[1].is_banned);
+		assert_eq!(reputations[1].ban_reason.as_deref(), Some("spam"));
+
+		handle.unban_peer(peer_b);
+		assert!(!handle.is_banned(&peer_b));
+	}
 }
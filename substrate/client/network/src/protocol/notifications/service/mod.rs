@@ -19,6 +19,7 @@
 //! Notification service implementation.
 
 use crate::{
+	bandwidth::{BandwidthLimiter, ProtocolBandwidthLimiter},
 	error,
 	protocol::notifications::handler::NotificationsSink,
 	service::{
@@ -58,10 +59,11 @@ const COMMAND_QUEUE_SIZE: usize = 64;
 type Subscribers = Arc<Mutex<Vec<TracingUnboundedSender<InnerNotificationEvent>>>>;
 
 /// Type representing a distributable message sink.
-/// Detached message sink must carry the protocol name for registering metrics.
+/// Detached message sink must carry the protocol name for registering metrics and the bandwidth
+/// limiter of the protocol.
 ///
 /// See documentation for [`PeerContext`] for more details.
-type NotificationSink = Arc<Mutex<(NotificationsSink, ProtocolName)>>;
+type NotificationSink = Arc<Mutex<(NotificationsSink, ProtocolName, ProtocolBandwidthLimiter)>>;
 
 #[async_trait::async_trait]
 impl MessageSink for NotificationSink {
@@ -69,6 +71,11 @@ impl MessageSink for NotificationSink {
 	fn send_sync_notification(&self, notification: Vec<u8>) {
 		let sink = self.lock();
 
+		if !sink.2.try_upload(notification.len()) {
+			log::trace!(target: LOG_TARGET, "{}: upload budget exceeded, dropping notification", sink.1);
+			return
+		}
+
 		metrics::register_notification_sent(sink.0.metrics(), &sink.1, notification.len());
 		sink.0.send_sync_notification(notification);
 	}
@@ -83,6 +90,8 @@ impl MessageSink for NotificationSink {
 		// method is also used by `NetworkService` when sending notifications.
 		let notification_len = notification.len();
 		let sink = self.lock().clone();
+		sink.2.upload(notification_len).await;
+
 		let permit = sink
 			.0
 			.reserve_notification()
@@ -205,6 +214,9 @@ pub struct NotificationHandle {
 
 	/// Connected peers.
 	peers: HashMap<PeerId, PeerContext>,
+
+	/// Bandwidth limiter of the protocol.
+	bandwidth: ProtocolBandwidthLimiter,
 }
 
 impl NotificationHandle {
@@ -214,8 +226,9 @@ impl NotificationHandle {
 		tx: mpsc::Sender<NotificationCommand>,
 		rx: TracingUnboundedReceiver<InnerNotificationEvent>,
 		subscribers: Arc<Mutex<Vec<TracingUnboundedSender<InnerNotificationEvent>>>>,
+		bandwidth: ProtocolBandwidthLimiter,
 	) -> Self {
-		Self { protocol, tx, rx, subscribers, peers: HashMap::new() }
+		Self { protocol, tx, rx, subscribers, peers: HashMap::new(), bandwidth }
 	}
 }
 
@@ -234,6 +247,15 @@ impl NotificationService for NotificationHandle {
 	/// Send synchronous `notification` to `peer`.
 	fn send_sync_notification(&mut self, peer: &sc_network_types::PeerId, notification: Vec<u8>) {
 		if let Some(info) = self.peers.get(&((*peer).into())) {
+			if !self.bandwidth.try_upload(notification.len()) {
+				log::trace!(
+					target: LOG_TARGET,
+					"{}: upload budget exceeded, dropping notification to {peer}",
+					self.protocol,
+				);
+				return
+			}
+
 			metrics::register_notification_sent(
 				info.sink.metrics(),
 				&self.protocol,
@@ -257,6 +279,8 @@ impl NotificationService for NotificationHandle {
 			.ok_or_else(|| error::Error::PeerDoesntExist((*peer).into()))?
 			.sink;
 
+		self.bandwidth.upload(notification_len).await;
+
 		sink.reserve_notification()
 			.await
 			.map_err(|_| error::Error::ConnectionClosed)?
@@ -308,7 +332,11 @@ impl NotificationService for NotificationHandle {
 						peer,
 						PeerContext {
 							sink: sink.clone(),
-							shared_sink: Arc::new(Mutex::new((sink, self.protocol.clone()))),
+							shared_sink: Arc::new(Mutex::new((
+								sink,
+								self.protocol.clone(),
+								self.bandwidth.clone(),
+							))),
 						},
 					);
 					return Some(NotificationEvent::NotificationStreamOpened {
@@ -335,7 +363,8 @@ impl NotificationService for NotificationHandle {
 						),
 						Some(context) => {
 							context.sink = sink.clone();
-							*context.shared_sink.lock() = (sink.clone(), self.protocol.clone());
+							*context.shared_sink.lock() =
+								(sink.clone(), self.protocol.clone(), self.bandwidth.clone());
 						},
 					}
 				},
@@ -356,6 +385,7 @@ impl NotificationService for NotificationHandle {
 			rx: event_rx,
 			peers: self.peers.clone(),
 			subscribers: self.subscribers.clone(),
+			bandwidth: self.bandwidth.clone(),
 		}))
 	}
 
@@ -382,6 +412,9 @@ pub struct ProtocolHandlePair {
 	/// Subscribers of the notification protocol events.
 	subscribers: Subscribers,
 
+	/// Bandwidth limiter of the protocol, shared with [`NotificationHandle`].
+	bandwidth: ProtocolBandwidthLimiter,
+
 	// Receiver for notification commands received from the protocol implementation.
 	rx: mpsc::Receiver<NotificationCommand>,
 }
@@ -391,9 +424,10 @@ impl ProtocolHandlePair {
 	fn new(
 		protocol: ProtocolName,
 		subscribers: Subscribers,
+		bandwidth: ProtocolBandwidthLimiter,
 		rx: mpsc::Receiver<NotificationCommand>,
 	) -> Self {
-		Self { protocol, subscribers, rx }
+		Self { protocol, subscribers, bandwidth, rx }
 	}
 
 	/// Consume `self` and split [`ProtocolHandlePair`] into a handle which allows it to send events
@@ -402,7 +436,7 @@ impl ProtocolHandlePair {
 		self,
 	) -> (ProtocolHandle, Box<dyn Stream<Item = NotificationCommand> + Send + Unpin>) {
 		(
-			ProtocolHandle::new(self.protocol, self.subscribers),
+			ProtocolHandle::new(self.protocol, self.subscribers, self.bandwidth),
 			Box::new(ReceiverStream::new(self.rx)),
 		)
 	}
@@ -426,6 +460,9 @@ pub(crate) struct ProtocolHandle {
 
 	/// Prometheus metrics.
 	metrics: Option<NotificationMetrics>,
+
+	/// Bandwidth limiter of the protocol, shared with [`NotificationHandle`].
+	bandwidth: ProtocolBandwidthLimiter,
 }
 
 pub(crate) enum ValidationCallResult {
@@ -435,8 +472,19 @@ pub(crate) enum ValidationCallResult {
 
 impl ProtocolHandle {
 	/// Create new [`ProtocolHandle`].
-	fn new(protocol: ProtocolName, subscribers: Subscribers) -> Self {
-		Self { protocol, subscribers, num_peers: 0usize, metrics: None, delegate_to_peerset: false }
+	fn new(
+		protocol: ProtocolName,
+		subscribers: Subscribers,
+		bandwidth: ProtocolBandwidthLimiter,
+	) -> Self {
+		Self {
+			protocol,
+			subscribers,
+			num_peers: 0usize,
+			metrics: None,
+			delegate_to_peerset: false,
+			bandwidth,
+		}
 	}
 
 	/// Set metrics.
@@ -444,6 +492,11 @@ impl ProtocolHandle {
 		self.metrics = Some(metrics);
 	}
 
+	/// Set bandwidth limits of the protocol from the node-wide `limiter`.
+	pub fn set_bandwidth_limiter(&self, limiter: &BandwidthLimiter) {
+		self.bandwidth.install(limiter, &self.protocol);
+	}
+
 	/// Delegate validation to `Peerset`.
 	///
 	/// Protocols that do not do any validation themselves and only rely on `Peerset` handling
@@ -637,10 +690,17 @@ pub fn notification_service(
 	let (event_tx, event_rx) =
 		tracing_unbounded(metric_label_for_protocol(&protocol).leak(), 100_000);
 	let subscribers = Arc::new(Mutex::new(vec![event_tx]));
+	let bandwidth = ProtocolBandwidthLimiter::default();
 
 	(
-		ProtocolHandlePair::new(protocol.clone(), subscribers.clone(), cmd_rx),
-		Box::new(NotificationHandle::new(protocol.clone(), cmd_tx, event_rx, subscribers)),
+		ProtocolHandlePair::new(protocol.clone(), subscribers.clone(), bandwidth.clone(), cmd_rx),
+		Box::new(NotificationHandle::new(
+			protocol.clone(),
+			cmd_tx,
+			event_rx,
+			subscribers,
+			bandwidth,
+		)),
 	)
 }
 
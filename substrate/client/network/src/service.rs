@@ -28,6 +28,8 @@
 //! which is then processed by [`NetworkWorker::next_action`].
 
 use crate::{
+	ban_list::{BanList, BannedEntries, BANNED_ADDRESS_RANGE},
+	bandwidth::BandwidthLimiter,
 	behaviour::{self, Behaviour, BehaviourOut},
 	bitswap::BitswapRequestHandler,
 	config::{
@@ -40,17 +42,18 @@ use crate::{
 	network_state::{
 		NetworkState, NotConnectedPeer as NetworkStateNotConnectedPeer, Peer as NetworkStatePeer,
 	},
-	peer_store::{PeerStore, PeerStoreProvider},
+	peer_store::{PeerReputation, PeerStore, PeerStoreProvider},
 	protocol::{self, NotifsHandlerError, Protocol, Ready},
 	protocol_controller::{self, ProtoSetConfig, ProtocolController, SetId},
 	request_responses::{IfDisconnected, ProtocolConfig as RequestResponseConfig, RequestFailure},
 	service::{
 		signature::{Signature, SigningError},
 		traits::{
-			BandwidthSink, NetworkBackend, NetworkDHTProvider, NetworkEventStream, NetworkPeers,
-			NetworkRequest, NetworkService as NetworkServiceT, NetworkSigner, NetworkStateInfo,
-			NetworkStatus, NetworkStatusProvider, NotificationSender as NotificationSenderT,
-			NotificationSenderError, NotificationSenderReady as NotificationSenderReadyT,
+			BandwidthSink, NetworkBackend, NetworkBanList, NetworkDHTProvider, NetworkEventStream,
+			NetworkPeers, NetworkRequest, NetworkService as NetworkServiceT, NetworkSigner,
+			NetworkStateInfo, NetworkStatus, NetworkStatusProvider,
+			NotificationSender as NotificationSenderT, NotificationSenderError,
+			NotificationSenderReady as NotificationSenderReadyT,
 		},
 	},
 	transport,
@@ -61,6 +64,7 @@ use crate::{
 use codec::DecodeAll;
 use either::Either;
 use futures::{channel::oneshot, prelude::*};
+use ip_network::IpNetwork;
 #[allow(deprecated)]
 use libp2p::swarm::THandlerErr;
 use libp2p::{
@@ -154,6 +158,10 @@ pub struct NetworkService<B: BlockT + 'static, H: ExHashT> {
 	sync_protocol_handle: protocol_controller::ProtocolHandle,
 	/// Handle to `PeerStore`.
 	peer_store_handle: Arc<dyn PeerStoreProvider>,
+	/// Peers and address ranges banned by the node operator.
+	ban_list: BanList,
+	/// Upload and download rate limiter.
+	bandwidth_limiter: BandwidthLimiter,
 	/// Marker to pin the `H` generic. Serves no purpose except to not break backwards
 	/// compatibility.
 	_marker: PhantomData<H>,
@@ -331,6 +339,11 @@ where
 			fs::create_dir_all(path)?;
 		}
 
+		let ban_list = BanList::load(network_config.net_config_path.as_deref())?;
+		for (peer_id, reason) in ban_list.entries().peers {
+			peer_store_handle.ban_peer(peer_id, reason);
+		}
+
 		info!(
 			target: "sub-libp2p",
 			"🏷  Local node identity is: {}",
@@ -424,6 +437,10 @@ where
 			.into_iter()
 			.for_each(|controller| (params.executor)(controller.run().boxed()));
 
+		let (bandwidth_limiter, bandwidth_limiter_task) =
+			BandwidthLimiter::new(network_config.bandwidth_limits.clone());
+		(params.executor)(bandwidth_limiter_task);
+
 		// Protocol name to protocol id mapping. The first protocol is always block announce (sync)
 		// protocol, aka default (hardcoded) peer set.
 		let notification_protocol_ids: HashMap<ProtocolName, SetId> =
@@ -504,6 +521,9 @@ where
 			protocol_handles.clone(),
 			from_protocol_controllers,
 		)?;
+		notif_protocol_handles
+			.iter()
+			.for_each(|handle| handle.set_bandwidth_limiter(&bandwidth_limiter));
 
 		// Build the swarm.
 		let (mut swarm, bandwidth): (Swarm<Behaviour<B>>, _) = {
@@ -530,6 +550,7 @@ where
 					network_config.kademlia_disjoint_query_paths,
 				);
 				config.with_kademlia_replication_factor(network_config.kademlia_replication_factor);
+				config.with_ban_list(ban_list.clone());
 
 				match network_config.transport {
 					TransportConfig::MemoryOnly => {
@@ -555,7 +576,16 @@ where
 					user_agent,
 					local_public.into(),
 					discovery_config,
-					request_response_protocols,
+					request_response_protocols
+						.into_iter()
+						.map(|mut config| {
+							config.inbound_queue = bandwidth_limiter.throttle_inbound_requests(
+								&config.name,
+								config.inbound_queue.take(),
+							);
+							config
+						})
+						.collect(),
 					Arc::clone(&peer_store_handle),
 					external_addresses.clone(),
 					ConnectionLimits::default()
@@ -563,6 +593,7 @@ where
 						.with_max_established_incoming(Some(
 							crate::MAX_CONNECTIONS_ESTABLISHED_INCOMING,
 						)),
+					ban_list.clone(),
 				);
 
 				match result {
@@ -633,6 +664,8 @@ where
 			protocol_handles,
 			sync_protocol_handle,
 			peer_store_handle: Arc::clone(&peer_store_handle),
+			ban_list: ban_list.clone(),
+			bandwidth_limiter,
 			_marker: PhantomData,
 			_block: Default::default(),
 		});
@@ -648,6 +681,7 @@ where
 			boot_node_ids,
 			reported_invalid_boot_nodes: Default::default(),
 			peer_store_handle: Arc::clone(&peer_store_handle),
+			ban_list,
 			notif_protocol_handles,
 			_marker: Default::default(),
 			_block: Default::default(),
@@ -1010,6 +1044,40 @@ where
 	}
 }
 
+impl<B, H> NetworkBanList for NetworkService<B, H>
+where
+	B: BlockT + 'static,
+	H: ExHashT,
+{
+	fn ban_peer(&self, peer_id: sc_network_types::PeerId, reason: String) {
+		self.ban_list.ban_peer(peer_id, reason.clone());
+		self.peer_store_handle.ban_peer(peer_id, reason);
+	}
+
+	fn unban_peer(&self, peer_id: sc_network_types::PeerId) {
+		self.ban_list.unban_peer(&peer_id);
+		self.peer_store_handle.unban_peer(peer_id);
+	}
+
+	fn ban_address_range(&self, range: IpNetwork) {
+		if self.ban_list.ban_address_range(range) {
+			let _ = self.to_worker.unbounded_send(ServiceToWorkerMsg::DisconnectBannedAddresses);
+		}
+	}
+
+	fn unban_address_range(&self, range: IpNetwork) {
+		self.ban_list.unban_address_range(&range);
+	}
+
+	fn banned_entries(&self) -> BannedEntries {
+		self.ban_list.entries()
+	}
+
+	fn peer_reputations(&self) -> Vec<PeerReputation> {
+		self.peer_store_handle.peer_reputations()
+	}
+}
+
 #[async_trait::async_trait]
 impl<B, H> NetworkPeers for NetworkService<B, H>
 where
@@ -1243,6 +1311,7 @@ where
 		tx: oneshot::Sender<Result<(Vec<u8>, ProtocolName), RequestFailure>>,
 		connect: IfDisconnected,
 	) {
+		let tx = self.bandwidth_limiter.throttle_outbound_request(&protocol, request.len(), tx);
 		let _ = self.to_worker.unbounded_send(ServiceToWorkerMsg::Request {
 			target: target.into(),
 			protocol: protocol.into(),
@@ -1350,6 +1419,7 @@ enum ServiceToWorkerMsg {
 		pending_response: oneshot::Sender<Result<NetworkState, RequestFailure>>,
 	},
 	DisconnectPeer(PeerId, ProtocolName),
+	DisconnectBannedAddresses,
 }
 
 /// Main network worker. Must be polled in order for the network to advance.
@@ -1381,6 +1451,8 @@ where
 	reported_invalid_boot_nodes: HashSet<PeerId>,
 	/// Peer reputation store handle.
 	peer_store_handle: Arc<dyn PeerStoreProvider>,
+	/// Peers and address ranges banned by the node operator.
+	ban_list: BanList,
 	/// Notification protocol handles.
 	notif_protocol_handles: Vec<protocol::ProtocolHandle>,
 	/// Marker to pin the `H` generic. Serves no purpose except to not break backwards
@@ -1497,6 +1569,25 @@ where
 				.behaviour_mut()
 				.user_protocol_mut()
 				.disconnect_peer(&who, protocol_name),
+			ServiceToWorkerMsg::DisconnectBannedAddresses => self.disconnect_banned_addresses(),
+		}
+	}
+
+	/// Disconnect the open peers connected from a banned address range.
+	fn disconnect_banned_addresses(&mut self) {
+		let swarm = &mut self.network_service;
+		let open = swarm.behaviour_mut().user_protocol().open_peers().cloned().collect::<Vec<_>>();
+
+		for peer_id in open {
+			let Some(endpoint) =
+				swarm.behaviour_mut().node(&peer_id).and_then(|info| info.endpoint().cloned())
+			else {
+				continue
+			};
+
+			if self.ban_list.is_address_banned(&endpoint.get_remote_address().clone().into()) {
+				self.peer_store_handle.report_peer(peer_id.into(), BANNED_ADDRESS_RANGE);
+			}
 		}
 	}
 
@@ -21,10 +21,12 @@
 //! Traits defined by `sc-network`.
 
 use crate::{
+	ban_list::BannedEntries,
 	config::{IncomingRequest, MultiaddrWithPeerId, NotificationHandshake, Params, SetConfig},
 	error::{self, Error},
 	event::Event,
 	network_state::NetworkState,
+	peer_store::PeerReputation,
 	request_responses::{IfDisconnected, RequestFailure},
 	service::{metrics::NotificationMetrics, signature::Signature, PeerStoreProvider},
 	types::ProtocolName,
@@ -32,6 +34,7 @@ use crate::{
 };
 
 use futures::{channel::oneshot, Stream};
+use ip_network::IpNetwork;
 use prometheus_endpoint::Registry;
 
 use sc_client_api::BlockBackend;
@@ -60,6 +63,7 @@ pub trait NetworkService:
 	+ NetworkDHTProvider
 	+ NetworkStatusProvider
 	+ NetworkPeers
+	+ NetworkBanList
 	+ NetworkEventStream
 	+ NetworkStateInfo
 	+ NetworkRequest
@@ -74,6 +78,7 @@ impl<T> NetworkService for T where
 		+ NetworkDHTProvider
 		+ NetworkStatusProvider
 		+ NetworkPeers
+		+ NetworkBanList
 		+ NetworkEventStream
 		+ NetworkStateInfo
 		+ NetworkRequest
@@ -536,6 +541,59 @@ where
 	}
 }
 
+/// Provides runtime control over peers and address ranges banned by the node operator.
+///
+/// Unlike bans caused by reputation, these bans don't expire and are persisted across restarts.
+pub trait NetworkBanList {
+	/// Ban `peer_id`, disconnecting it from all protocols.
+	fn ban_peer(&self, peer_id: PeerId, reason: String);
+
+	/// Lift the ban of `peer_id`.
+	fn unban_peer(&self, peer_id: PeerId);
+
+	/// Ban all IP addresses in `range`, disconnecting the connected peers using one of them.
+	fn ban_address_range(&self, range: IpNetwork);
+
+	/// Lift the ban of `range`.
+	fn unban_address_range(&self, range: IpNetwork);
+
+	/// Get the banned peers and address ranges.
+	fn banned_entries(&self) -> BannedEntries;
+
+	/// Get reputations of all known peers along with their most recent reputation changes.
+	fn peer_reputations(&self) -> Vec<PeerReputation>;
+}
+
+impl<T> NetworkBanList for Arc<T>
+where
+	T: ?Sized,
+	T: NetworkBanList,
+{
+	fn ban_peer(&self, peer_id: PeerId, reason: String) {
+		T::ban_peer(self, peer_id, reason)
+	}
+
+	fn unban_peer(&self, peer_id: PeerId) {
+		T::unban_peer(self, peer_id)
+	}
+
+	fn ban_address_range(&self, range: IpNetwork) {
+		T::ban_address_range(self, range)
+	}
+
+	fn unban_address_range(&self, range: IpNetwork) {
+		T::unban_address_range(self, range)
+	}
+
+	fn banned_entries(&self) -> BannedEntries {
+		T::banned_entries(self)
+	}
+
+	fn peer_reputations(&self) -> Vec<PeerReputation> {
+		T::peer_reputations(self)
+	}
+}
+
 /// Provides access to network-level event stream.
 pub trait NetworkEventStream {
 	/// Returns a stream containing the events that happen on the network.
@@ -27,6 +27,7 @@ mod disconnected_peers;
 pub mod polkadot;
 pub mod state;
 pub mod state_sync;
+pub mod sync_progress;
 pub mod warp;
 
 use crate::{
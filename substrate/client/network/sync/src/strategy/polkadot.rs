@@ -26,6 +26,8 @@ use crate::{
 	strategy::{
 		chain_sync::{ChainSync, ChainSyncMode},
 		state::StateStrategy,
+		state_sync::{StateSync, StateSyncProvider},
+		sync_progress::{clear_progress, clear_state_progress, SyncProgressStore},
 		warp::{WarpSync, WarpSyncConfig},
 		StrategyKey, SyncingAction, SyncingStrategy,
 	},
@@ -43,6 +45,12 @@ use sp_blockchain::{Error as ClientError, HeaderBackend, HeaderMetadata};
 use sp_runtime::traits::{Block as BlockT, Header, NumberFor};
 use std::{any::Any, collections::HashMap, sync::Arc};
 
+/// Number of blocks the peers may be ahead of the target of the resumed state sync.
+///
+/// Peers keep the state of the last 256 blocks with the default pruning, so the state of the older
+/// target can't be downloaded anymore and warp sync is restarted.
+const MAX_RESUMED_STATE_AGE: u32 = 256;
+
 /// Corresponding `ChainSync` mode.
 fn chain_sync_mode(sync_mode: SyncMode) -> ChainSyncMode {
 	match sync_mode {
@@ -71,6 +79,18 @@ where
 	pub state_request_protocol_name: ProtocolName,
 	/// Block downloader
 	pub block_downloader: Arc<dyn BlockDownloader<Block>>,
+	/// Storage of the warp and state sync progress, used to resume them after a restart.
+	///
+	/// The downloaded state is stored until it's imported, see [`SyncProgressStore`].
+	pub progress_store: Option<Arc<dyn SyncProgressStore>>,
+}
+
+/// State sync resumed after a restart, along with the warp sync configuration to restart warp
+/// sync if the state sync target is too old.
+struct ResumedStateSync<B: BlockT> {
+	target_number: NumberFor<B>,
+	warp_sync_config: WarpSyncConfig<B>,
+	warp_sync_protocol_name: Option<ProtocolName>,
 }
 
 /// Proxy to specific syncing strategies used in Polkadot.
@@ -85,6 +105,8 @@ pub struct PolkadotSyncingStrategy<B: BlockT, Client> {
 	state: Option<StateStrategy<B>>,
 	/// `ChainSync` strategy.`
 	chain_sync: Option<ChainSync<B, Client>>,
+	/// Set while the state sync resumed after a restart is active.
+	resumed_state: Option<ResumedStateSync<B>>,
 	/// Connected peers and their best blocks used to seed a new strategy when switching to it in
 	/// `PolkadotSyncingStrategy::proceed_to_next`.
 	peer_best_blocks: HashMap<PeerId, (B::Hash, NumberFor<B>)>,
@@ -308,15 +330,16 @@ where
 	) -> Result<Vec<SyncingAction<B>>, ClientError> {
 		// This function presumes that strategies are executed serially and must be refactored once
 		// we have parallel strategies.
-		let actions: Vec<_> = if let Some(ref mut warp) = self.warp {
-			warp.actions(network_service).map(Into::into).collect()
+		let mut actions = self.discard_stale_state_sync();
+		if let Some(ref mut warp) = self.warp {
+			actions.extend(warp.actions(network_service).map(Into::into));
 		} else if let Some(ref mut state) = self.state {
-			state.actions(network_service).map(Into::into).collect()
+			actions.extend(state.actions(network_service).map(Into::into));
 		} else if let Some(ref mut chain_sync) = self.chain_sync {
-			chain_sync.actions(network_service)?
+			actions.extend(chain_sync.actions(network_service)?);
 		} else {
 			unreachable!("At least one syncing strategy is always active; qed")
-		};
+		}
 
 		if actions.iter().any(SyncingAction::is_finished) {
 			self.proceed_to_next()?;
@@ -355,18 +378,36 @@ where
 		if let SyncMode::Warp = config.mode {
 			let warp_sync_config = warp_sync_config
 				.expect("Warp sync configuration must be supplied in warp sync mode.");
-			let warp_sync = WarpSync::new(
+			if let Some((state, target_number)) = Self::resume_state_sync(&config, &client) {
+				let resumed_state =
+					ResumedStateSync { target_number, warp_sync_config, warp_sync_protocol_name };
+				return Ok(Self {
+					config,
+					client,
+					warp: None,
+					state: Some(state),
+					chain_sync: None,
+					resumed_state: Some(resumed_state),
+					peer_best_blocks: Default::default(),
+				})
+			}
+
+			let mut warp_sync = WarpSync::new(
 				client.clone(),
 				warp_sync_config,
 				warp_sync_protocol_name,
 				config.block_downloader.clone(),
 			);
+			if let Some(progress_store) = &config.progress_store {
+				warp_sync = warp_sync.with_progress_store(progress_store.clone());
+			}
 			Ok(Self {
 				config,
 				client,
 				warp: Some(warp_sync),
 				state: None,
 				chain_sync: None,
+				resumed_state: None,
 				peer_best_blocks: Default::default(),
 			})
 		} else {
@@ -386,11 +427,87 @@ where
 				warp: None,
 				state: None,
 				chain_sync: Some(chain_sync),
+				resumed_state: None,
 				peer_best_blocks: Default::default(),
 			})
 		}
 	}
 
+	/// Resume the state sync that was interrupted by a restart, if there's any. Returns the state
+	/// strategy with the number of the target block.
+	fn resume_state_sync(
+		config: &PolkadotSyncingStrategyConfig<B>,
+		client: &Arc<Client>,
+	) -> Option<(StateStrategy<B>, NumberFor<B>)> {
+		let progress_store = config.progress_store.as_ref()?;
+		if client.info().finalized_state.is_some() {
+			// the state has been imported, but the progress was not cleared
+			clear_progress::<B>(&**progress_store);
+			return None
+		}
+		let state_sync = StateSync::resume(client.clone(), progress_store.clone())?;
+		let target_number = state_sync.target_number();
+		info!(
+			target: LOG_TARGET,
+			"Resuming state sync of block #{} ({}).",
+			target_number,
+			state_sync.target_hash(),
+		);
+		let state = StateStrategy::new_with_provider(
+			Box::new(state_sync),
+			std::iter::empty(),
+			config.state_request_protocol_name.clone(),
+		);
+		Some((state, target_number))
+	}
+
+	/// Restart warp sync if most of the peers are too far ahead of the target of the resumed state
+	/// sync to still keep its state. The downloaded state is discarded, while warp sync continues
+	/// from the stored authority set. Returns the actions cancelling the pending state requests.
+	fn discard_stale_state_sync(&mut self) -> Vec<SyncingAction<B>> {
+		let Some(resumed_state) = self.resumed_state.take() else { return Vec::new() };
+		let max_number = resumed_state.target_number + MAX_RESUMED_STATE_AGE.into();
+		let ahead = self
+			.peer_best_blocks
+			.values()
+			.filter(|(_, best_number)| *best_number > max_number)
+			.count();
+		if ahead == 0 || ahead * 2 <= self.peer_best_blocks.len() {
+			self.resumed_state = Some(resumed_state);
+			return Vec::new()
+		}
+
+		info!(
+			target: LOG_TARGET,
+			"State of the resumed state sync target #{} is too old, restarting warp sync.",
+			resumed_state.target_number,
+		);
+		if let Some(progress_store) = &self.config.progress_store {
+			clear_state_progress::<B>(&**progress_store);
+		}
+		let mut warp_sync = WarpSync::new(
+			self.client.clone(),
+			resumed_state.warp_sync_config,
+			resumed_state.warp_sync_protocol_name,
+			self.config.block_downloader.clone(),
+		);
+		if let Some(progress_store) = &self.config.progress_store {
+			warp_sync = warp_sync.with_progress_store(progress_store.clone());
+		}
+		for (peer_id, (best_hash, best_number)) in &self.peer_best_blocks {
+			warp_sync.add_peer(*peer_id, *best_hash, *best_number);
+		}
+		self.state = None;
+		self.warp = Some(warp_sync);
+		self.peer_best_blocks
+			.keys()
+			.map(|peer_id| SyncingAction::CancelRequest {
+				peer_id: *peer_id,
+				key: StateStrategy::<B>::STRATEGY_KEY,
+			})
+			.collect()
+	}
+
 	/// Proceed with the next strategy if the active one finished.
 	pub fn proceed_to_next(&mut self) -> Result<(), ClientError> {
 		// The strategies are switched as `WarpSync` -> `StateStrategy` -> `ChainSync`.
@@ -401,12 +518,18 @@ where
 						target: LOG_TARGET,
 						"Warp sync is complete, continuing with state sync."
 					);
-					let state_sync = StateStrategy::new(
+					let mut state_sync = StateSync::new(
 						self.client.clone(),
 						res.target_header,
 						res.target_body,
 						res.target_justifications,
 						false,
+					);
+					if let Some(progress_store) = &self.config.progress_store {
+						state_sync = state_sync.with_progress_store(progress_store.clone());
+					}
+					let state_sync = StateStrategy::new_with_provider(
+						Box::new(state_sync),
 						self.peer_best_blocks
 							.iter()
 							.map(|(peer_id, (_, best_number))| (*peer_id, *best_number)),
@@ -422,6 +545,9 @@ where
 						target: LOG_TARGET,
 						"Warp sync failed. Continuing with full sync."
 					);
+					if let Some(progress_store) = &self.config.progress_store {
+						clear_progress::<B>(&**progress_store);
+					}
 					let chain_sync = match ChainSync::new(
 						chain_sync_mode(self.config.mode),
 						self.client.clone(),
@@ -447,11 +573,15 @@ where
 				},
 			}
 		} else if let Some(state) = &self.state {
+			self.resumed_state = None;
 			if state.is_succeeded() {
 				info!(target: LOG_TARGET, "State sync is complete, continuing with block sync.");
 			} else {
 				error!(target: LOG_TARGET, "State sync failed. Falling back to full sync.");
 			}
+			if let Some(progress_store) = &self.config.progress_store {
+				clear_progress::<B>(&**progress_store);
+			}
 			let chain_sync = match ChainSync::new(
 				chain_sync_mode(self.config.mode),
 				self.client.clone(),
@@ -20,11 +20,14 @@
 
 use crate::{
 	schema::v1::{KeyValueStateEntry, StateEntry, StateRequest, StateResponse},
+	strategy::sync_progress::{
+		load_state_progress, store_state_chunk, StateChunk, StateProgress, SyncProgressStore,
+	},
 	LOG_TARGET,
 };
 use codec::{Decode, Encode};
 use log::debug;
-use sc_client_api::{CompactProof, KeyValueStates, ProofProvider};
+use sc_client_api::{CompactProof, ProofProvider};
 use sc_consensus::ImportedState;
 use smallvec::SmallVec;
 use sp_core::storage::well_known_keys;
@@ -143,6 +146,10 @@ pub struct StateSync<B: BlockT, Client> {
 	metadata: StateSyncMetadata<B>,
 	state: HashMap<Vec<u8>, (Vec<(Vec<u8>, Vec<u8>)>, Vec<Vec<u8>>)>,
 	client: Arc<Client>,
+	/// Storage of the downloaded chunks, used to resume the download after a restart.
+	progress_store: Option<Arc<dyn SyncProgressStore>>,
+	/// Number of the chunks written to `progress_store`.
+	stored_chunks: u32,
 }
 
 impl<B, Client> StateSync<B, Client>
@@ -170,9 +177,55 @@ where
 				skip_proof,
 			},
 			state: HashMap::default(),
+			progress_store: None,
+			stored_chunks: 0,
 		}
 	}
 
+	/// Persist the downloaded chunks to `progress_store`, so the download may be resumed with
+	/// [`StateSync::resume`] after a restart.
+	pub fn with_progress_store(mut self, progress_store: Arc<dyn SyncProgressStore>) -> Self {
+		self.progress_store = Some(progress_store);
+		self
+	}
+
+	/// Resume the download persisted to `progress_store`, if there's any.
+	pub fn resume(client: Arc<Client>, progress_store: Arc<dyn SyncProgressStore>) -> Option<Self> {
+		let (progress, chunks) = load_state_progress::<B>(&*progress_store)?;
+		let mut state_sync = Self::new(
+			client,
+			progress.target_header,
+			progress.target_body,
+			progress.target_justifications,
+			progress.skip_proof,
+		)
+		.with_progress_store(progress_store);
+		for chunk in chunks {
+			for (state_root, key_values) in chunk {
+				state_sync.process_state_key_values(state_root, key_values);
+			}
+		}
+		state_sync.metadata.last_key = progress.last_key.into();
+		state_sync.metadata.imported_bytes = progress.imported_bytes;
+		state_sync.stored_chunks = progress.chunks;
+		Some(state_sync)
+	}
+
+	fn store_chunk(&mut self, encoded_chunk: &[u8]) {
+		let Some(progress_store) = &self.progress_store else { return };
+		self.stored_chunks += 1;
+		let progress = StateProgress::<B> {
+			target_header: self.metadata.target_header.clone(),
+			target_body: self.metadata.target_body.clone(),
+			target_justifications: self.metadata.target_justifications.clone(),
+			skip_proof: self.metadata.skip_proof,
+			last_key: self.metadata.last_key.to_vec(),
+			imported_bytes: self.metadata.imported_bytes,
+			chunks: self.stored_chunks,
+		};
+		store_state_chunk(&**progress_store, &progress, encoded_chunk);
+	}
+
 	fn process_state_key_values(
 		&mut self,
 		state_root: Vec<u8>,
@@ -205,13 +258,7 @@ where
 		}
 	}
 
-	fn process_state_verified(&mut self, values: KeyValueStates) {
-		for values in values.0 {
-			self.process_state_key_values(values.state_root, values.key_values);
-		}
-	}
-
-	fn process_state_unverified(&mut self, response: StateResponse) -> bool {
+	fn process_state_unverified(&mut self, response: StateResponse) -> (bool, StateChunk) {
 		let mut complete = true;
 		// if the trie is a child trie and one of its parent trie is empty,
 		// the parent cursor stays valid.
@@ -223,6 +270,7 @@ where
 		} else {
 			self.metadata.last_key.clear();
 		}
+		let mut chunk = StateChunk::new();
 		for state in response.entries {
 			debug!(
 				target: LOG_TARGET,
@@ -239,12 +287,12 @@ where
 			}
 
 			let KeyValueStateEntry { state_root, entries, complete: _ } = state;
-			self.process_state_key_values(
+			chunk.push((
 				state_root,
-				entries.into_iter().map(|StateEntry { key, value }| (key, value)),
-			);
+				entries.into_iter().map(|StateEntry { key, value }| (key, value)).collect(),
+			));
 		}
-		complete
+		(complete, chunk)
 	}
 }
 
@@ -263,7 +311,7 @@ where
 			debug!(target: LOG_TARGET, "Missing proof");
 			return ImportResult::BadResponse
 		}
-		let complete = if !self.metadata.skip_proof {
+		let (complete, chunk) = if !self.metadata.skip_proof {
 			debug!(target: LOG_TARGET, "Importing state from {} trie nodes", response.proof.len());
 			let proof_size = response.proof.len() as u64;
 			let proof = match CompactProof::decode(&mut response.proof.as_ref()) {
@@ -295,12 +343,20 @@ where
 				debug!(target: LOG_TARGET, "Error updating key cursor, depth: {}", completed);
 			};
 
-			self.process_state_verified(values);
 			self.metadata.imported_bytes += proof_size;
-			complete
+			let chunk = values.0.into_iter().map(|values| (values.state_root, values.key_values));
+			(complete, chunk.collect())
 		} else {
 			self.process_state_unverified(response)
 		};
+		// the last chunk is not stored, the state is imported right away
+		let encoded_chunk = (!complete && self.progress_store.is_some()).then(|| chunk.encode());
+		for (state_root, key_values) in chunk {
+			self.process_state_key_values(state_root, key_values);
+		}
+		if let Some(encoded_chunk) = encoded_chunk {
+			self.store_chunk(&encoded_chunk);
+		}
 		if complete {
 			self.metadata.complete = true;
 			let target_hash = self.metadata.target_hash();
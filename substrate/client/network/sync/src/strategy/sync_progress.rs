@@ -0,0 +1,276 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Persistence of the warp and state sync progress, used to resume them after a restart.
+//!
+//! Warp sync stores the authority set proved by the last verified warp proof fragment. State sync
+//! stores the target block, the key cursor and every downloaded state chunk, so the download
+//! continues from the last completed key. The progress is cleared once state sync is finished.
+//!
+//! The downloaded chunks are kept until the whole state is imported, so state sync temporarily
+//! takes about twice the size of the state on disk. The progress of the state sync target that is
+//! more than 256 blocks behind the peers is discarded, as its state is likely pruned by them.
+
+use crate::{
+	strategy::warp::{AuthorityList, SetId},
+	LOG_TARGET,
+};
+use codec::{Decode, Encode};
+use log::{debug, warn};
+use sc_client_api::AuxStore;
+use sp_runtime::{traits::Block as BlockT, Justifications};
+use std::fmt;
+
+/// Key of the warp proofs verification progress.
+const WARP_PROGRESS_KEY: &[u8] = b"sync_warp_progress";
+/// Key of the state download progress.
+const STATE_PROGRESS_KEY: &[u8] = b"sync_state_progress";
+/// Prefix of the keys of the downloaded state chunks.
+const STATE_CHUNK_PREFIX: &[u8] = b"sync_state_chunk";
+
+/// Storage of the sync progress that survives node restarts.
+///
+/// Implemented for any [`AuxStore`], e.g. the client.
+pub trait SyncProgressStore: Send + Sync {
+	/// Returns the value stored under `key`.
+	fn get(&self, key: &[u8]) -> Option<Vec<u8>>;
+	/// Atomically inserts and deletes values. Deletions occur after insertions.
+	fn update(&self, insert: &[(&[u8], &[u8])], delete: &[&[u8]]);
+}
+
+impl<T: AuxStore + Send + Sync> SyncProgressStore for T {
+	fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+		self.get_aux(key).unwrap_or_else(|e| {
+			warn!(target: LOG_TARGET, "Failed to read sync progress: {e}");
+			None
+		})
+	}
+
+	fn update(&self, insert: &[(&[u8], &[u8])], delete: &[&[u8]]) {
+		if let Err(e) = self.insert_aux(insert, delete) {
+			warn!(target: LOG_TARGET, "Failed to write sync progress: {e}");
+		}
+	}
+}
+
+impl fmt::Debug for dyn SyncProgressStore {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.write_str("SyncProgressStore")
+	}
+}
+
+/// Authority set proved by the verified warp proofs.
+#[derive(Encode, Decode, Debug, Clone, PartialEq)]
+pub(crate) struct WarpProgress<B: BlockT> {
+	pub set_id: SetId,
+	pub authorities: AuthorityList,
+	pub last_hash: B::Hash,
+}
+
+/// State download progress.
+#[derive(Encode, Decode, Debug, Clone, PartialEq)]
+pub(crate) struct StateProgress<B: BlockT> {
+	pub target_header: B::Header,
+	pub target_body: Option<Vec<B::Extrinsic>>,
+	pub target_justifications: Option<Justifications>,
+	pub skip_proof: bool,
+	pub last_key: Vec<Vec<u8>>,
+	pub imported_bytes: u64,
+	/// Number of the stored state chunks.
+	pub chunks: u32,
+}
+
+/// Downloaded key values, grouped by the state root of the trie. The top trie has an empty root.
+pub(crate) type StateChunk = Vec<(Vec<u8>, Vec<(Vec<u8>, Vec<u8>)>)>;
+
+fn chunk_key(index: u32) -> Vec<u8> {
+	(STATE_CHUNK_PREFIX, index).encode()
+}
+
+fn load<T: Decode>(store: &dyn SyncProgressStore, key: &[u8]) -> Option<T> {
+	let value = store.get(key)?;
+	match T::decode(&mut value.as_slice()) {
+		Ok(value) => Some(value),
+		Err(e) => {
+			warn!(target: LOG_TARGET, "Failed to decode sync progress: {e}");
+			None
+		},
+	}
+}
+
+/// Returns the stored warp proofs verification progress.
+pub(crate) fn load_warp_progress<B: BlockT>(
+	store: &dyn SyncProgressStore,
+) -> Option<WarpProgress<B>> {
+	load(store, WARP_PROGRESS_KEY)
+}
+
+/// Stores the warp proofs verification progress.
+pub(crate) fn store_warp_progress<B: BlockT>(
+	store: &dyn SyncProgressStore,
+	progress: &WarpProgress<B>,
+) {
+	store.update(&[(WARP_PROGRESS_KEY, &progress.encode()[..])], &[]);
+}
+
+/// Returns the stored state download progress along with the downloaded chunks.
+///
+/// Incomplete progress is cleared.
+pub(crate) fn load_state_progress<B: BlockT>(
+	store: &dyn SyncProgressStore,
+) -> Option<(StateProgress<B>, Vec<StateChunk>)> {
+	let progress: StateProgress<B> = load(store, STATE_PROGRESS_KEY)?;
+	let chunks = (0..progress.chunks)
+		.map(|index| load(store, &chunk_key(index)))
+		.collect::<Option<Vec<StateChunk>>>();
+	match chunks {
+		Some(chunks) => Some((progress, chunks)),
+		None => {
+			debug!(target: LOG_TARGET, "Stored state sync progress is incomplete, discarding it");
+			clear_progress::<B>(store);
+			None
+		},
+	}
+}
+
+/// Stores the next encoded state chunk, `progress` is expected to account for it.
+///
+/// Chunks are only removed with the whole progress, so the stored chunks grow up to the size of
+/// the downloaded state.
+pub(crate) fn store_state_chunk<B: BlockT>(
+	store: &dyn SyncProgressStore,
+	progress: &StateProgress<B>,
+	encoded_chunk: &[u8],
+) {
+	let Some(index) = progress.chunks.checked_sub(1) else { return };
+	store.update(
+		&[(&chunk_key(index)[..], encoded_chunk), (STATE_PROGRESS_KEY, &progress.encode()[..])],
+		&[],
+	);
+}
+
+/// Removes all stored warp and state sync progress.
+pub(crate) fn clear_progress<B: BlockT>(store: &dyn SyncProgressStore) {
+	clear_state_progress_with::<B>(store, &[WARP_PROGRESS_KEY]);
+}
+
+/// Removes the stored state sync progress, keeping the warp sync progress.
+pub(crate) fn clear_state_progress<B: BlockT>(store: &dyn SyncProgressStore) {
+	clear_state_progress_with::<B>(store, &[]);
+}
+
+// removes the state sync progress along with the other `keys`
+fn clear_state_progress_with<B: BlockT>(store: &dyn SyncProgressStore, keys: &[&[u8]]) {
+	let chunks = load::<StateProgress<B>>(store, STATE_PROGRESS_KEY).map_or(0, |p| p.chunks);
+	let chunk_keys = (0..chunks).map(chunk_key).collect::<Vec<_>>();
+	let mut delete = chunk_keys.iter().map(Vec::as_slice).collect::<Vec<_>>();
+	delete.push(STATE_PROGRESS_KEY);
+	delete.extend(keys);
+	store.update(&[], &delete);
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::{
+		schema::v1::{KeyValueStateEntry, StateEntry, StateResponse},
+		strategy::state_sync::{ImportResult, StateSync, StateSyncProvider},
+	};
+	use sc_client_api::HeaderBackend;
+	use std::sync::Arc;
+	use substrate_test_runtime_client::{
+		runtime::Block, DefaultTestClientBuilderExt, TestClientBuilder, TestClientBuilderExt,
+	};
+
+	fn state_response(keys: &[u8], complete: bool) -> StateResponse {
+		let entries = keys.iter().map(|key| StateEntry { key: vec![*key], value: vec![*key] });
+		StateResponse {
+			entries: vec![KeyValueStateEntry {
+				state_root: Vec::new(),
+				entries: entries.collect(),
+				complete,
+			}],
+			proof: Vec::new(),
+		}
+	}
+
+	#[test]
+	fn warp_progress_is_stored_and_cleared() {
+		let client = TestClientBuilder::new().build();
+		assert_eq!(load_warp_progress::<Block>(&client), None);
+
+		let progress = WarpProgress::<Block> {
+			set_id: 5,
+			authorities: Vec::new(),
+			last_hash: client.info().genesis_hash,
+		};
+		store_warp_progress(&client, &progress);
+		assert_eq!(load_warp_progress::<Block>(&client), Some(progress.clone()));
+
+		// the stale state sync progress is discarded without the warp sync progress
+		clear_state_progress::<Block>(&client);
+		assert_eq!(load_warp_progress::<Block>(&client), Some(progress));
+
+		clear_progress::<Block>(&client);
+		assert_eq!(load_warp_progress::<Block>(&client), None);
+	}
+
+	#[test]
+	fn state_sync_is_resumed() {
+		let client = Arc::new(TestClientBuilder::new().build());
+		let target_header = client.header(client.info().genesis_hash).unwrap().unwrap();
+		let mut state_sync = StateSync::new(client.clone(), target_header, None, None, true)
+			.with_progress_store(client.clone());
+		assert!(matches!(
+			state_sync.import(state_response(&[1, 2], false)),
+			ImportResult::Continue
+		));
+		assert!(matches!(state_sync.import(state_response(&[3], false)), ImportResult::Continue));
+		let imported_bytes = state_sync.progress().size;
+
+		// the download continues from the last key after a restart
+		let mut state_sync = StateSync::resume(client.clone(), client.clone()).unwrap();
+		assert_eq!(state_sync.next_request().start, vec![vec![3]]);
+		assert_eq!(state_sync.progress().size, imported_bytes);
+		let ImportResult::Import(_, _, imported_state, _, _) =
+			state_sync.import(state_response(&[4], true))
+		else {
+			panic!("State is expected to be complete")
+		};
+		let keys = imported_state.state.0[0].key_values.iter().map(|(key, _)| key[0]);
+		assert_eq!(keys.collect::<Vec<_>>(), vec![1, 2, 3, 4]);
+
+		// chunks are removed with the progress
+		clear_progress::<Block>(&*client);
+		assert!(StateSync::resume(client.clone(), client.clone()).is_none());
+		assert!(SyncProgressStore::get(&*client, &chunk_key(0)).is_none());
+	}
+
+	#[test]
+	fn incomplete_state_progress_is_discarded() {
+		let client = Arc::new(TestClientBuilder::new().build());
+		let target_header = client.header(client.info().genesis_hash).unwrap().unwrap();
+		let mut state_sync = StateSync::new(client.clone(), target_header, None, None, true)
+			.with_progress_store(client.clone());
+		assert!(matches!(state_sync.import(state_response(&[1], false)), ImportResult::Continue));
+
+		SyncProgressStore::update(&*client, &[], &[&chunk_key(0)]);
+		assert!(StateSync::resume(client.clone(), client.clone()).is_none());
+		assert!(SyncProgressStore::get(&*client, STATE_PROGRESS_KEY).is_none());
+	}
+}
@@ -24,8 +24,10 @@ use crate::{
 	block_relay_protocol::{BlockDownloader, BlockResponseError},
 	service::network::NetworkServiceHandle,
 	strategy::{
-		chain_sync::validate_blocks, disconnected_peers::DisconnectedPeers, StrategyKey,
-		SyncingAction,
+		chain_sync::validate_blocks,
+		disconnected_peers::DisconnectedPeers,
+		sync_progress::{load_warp_progress, store_warp_progress, SyncProgressStore, WarpProgress},
+		StrategyKey, SyncingAction,
 	},
 	types::{BadPeer, SyncState, SyncStatus},
 	LOG_TARGET,
@@ -213,6 +215,8 @@ pub struct WarpSync<B: BlockT, Client> {
 	block_downloader: Arc<dyn BlockDownloader<B>>,
 	actions: Vec<SyncingAction<B>>,
 	result: Option<WarpSyncResult<B>>,
+	/// Storage of the verified authority set, used to resume warp sync after a restart.
+	progress_store: Option<Arc<dyn SyncProgressStore>>,
 }
 
 impl<B, Client> WarpSync<B, Client>
@@ -248,6 +252,7 @@ where
 				block_downloader,
 				actions: vec![SyncingAction::Finished],
 				result: None,
+				progress_store: None,
 			}
 		}
 
@@ -268,9 +273,17 @@ where
 			block_downloader,
 			actions: Vec::new(),
 			result: None,
+			progress_store: None,
 		}
 	}
 
+	/// Persist the authority set proved by every verified warp proof to `progress_store` and
+	/// start from the persisted one, if there's any.
+	pub fn with_progress_store(mut self, progress_store: Arc<dyn SyncProgressStore>) -> Self {
+		self.progress_store = Some(progress_store);
+		self
+	}
+
 	/// Notify that a new peer has connected.
 	pub fn add_peer(&mut self, peer_id: PeerId, _best_hash: B::Hash, best_number: NumberFor<B>) {
 		self.peers.insert(peer_id, Peer { best_number, state: PeerState::Available });
@@ -320,10 +333,27 @@ where
 			return
 		}
 
+		let progress = self
+			.progress_store
+			.as_deref()
+			.and_then(load_warp_progress::<B>)
+			.unwrap_or_else(|| WarpProgress {
+				set_id: 0,
+				authorities: warp_sync_provider.current_authorities(),
+				last_hash: self.client.info().genesis_hash,
+			});
+		if progress.set_id != 0 {
+			debug!(
+				target: LOG_TARGET,
+				"Resuming warp sync from set_id={:?} ({}).",
+				progress.set_id,
+				progress.last_hash,
+			);
+		}
 		self.phase = Phase::WarpProof {
-			set_id: 0,
-			authorities: warp_sync_provider.current_authorities(),
-			last_hash: self.client.info().genesis_hash,
+			set_id: progress.set_id,
+			authorities: progress.authorities,
+			last_hash: progress.last_hash,
 			warp_sync_provider: Arc::clone(warp_sync_provider),
 		};
 		trace!(target: LOG_TARGET, "Started warp sync with {} peers.", self.peers.len());
@@ -408,6 +438,14 @@ where
 				*authorities = new_authorities;
 				*last_hash = new_last_hash;
 				self.total_proof_bytes += response.0.len() as u64;
+				if let Some(progress_store) = &self.progress_store {
+					let progress = WarpProgress::<B> {
+						set_id: *set_id,
+						authorities: authorities.clone(),
+						last_hash: *last_hash,
+					};
+					store_warp_progress(&**progress_store, &progress);
+				}
 			},
 			Ok(VerificationResult::Complete(new_set_id, _, header)) => {
 				log::debug!(
@@ -915,6 +915,7 @@ pub trait TestNetFactory: Default + Sized + Send {
 			metrics_registry: None,
 			state_request_protocol_name: state_request_protocol_config.name.clone(),
 			block_downloader: block_relay_params.downloader,
+			progress_store: None,
 		};
 		// Initialize syncing strategy.
 		let syncing_strategy = Box::new(
@@ -21,7 +21,10 @@ use std::{collections::HashSet, str::FromStr, sync::Arc, thread::sleep};
 use crate::NetworkProvider;
 use codec::{Decode, Encode};
 use futures::Future;
-pub use http::SharedClient;
+pub use http::{Body as HttpBody, HttpBackend, HttpBackendError, SharedClient};
+pub use http_mock::{MockHttpBackend, MockRequest, MockResponse};
+pub use http_policy::HttpPolicy;
+pub(crate) use http_policy::HttpSandbox;
 use sc_network::Multiaddr;
 use sc_network_types::PeerId;
 use sp_core::{
@@ -33,6 +36,8 @@ use sp_core::{
 };
 
 mod http;
+mod http_mock;
+mod http_policy;
 
 mod timestamp;
 
@@ -200,9 +205,10 @@ impl AsyncApi {
 	pub fn new(
 		network_provider: Arc<dyn NetworkProvider + Send + Sync>,
 		is_validator: bool,
-		shared_http_client: SharedClient,
+		http_backend: Arc<dyn HttpBackend>,
+		http_sandbox: Arc<HttpSandbox>,
 	) -> (Api, Self) {
-		let (http_api, http_worker) = http::http(shared_http_client);
+		let (http_api, http_worker) = http::http(http_backend, http_sandbox);
 
 		let api = Api { network_provider, is_validator, http: http_api };
 
@@ -328,7 +334,7 @@ mod tests {
 		let mock = Arc::new(TestNetwork());
 		let shared_client = SharedClient::new().unwrap();
 
-		AsyncApi::new(mock, false, shared_client)
+		AsyncApi::new(mock, false, Arc::new(shared_client), Default::default())
 	}
 
 	fn offchain_db() -> OffchainDb<LocalStorage> {
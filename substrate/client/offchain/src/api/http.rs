@@ -27,10 +27,10 @@
 //! (i.e.: the socket should continue being processed) in the background even if the runtime isn't
 //! actively calling any function.
 
-use crate::api::timestamp;
+use crate::api::{http_policy::HttpSandbox, timestamp};
 use bytes::buf::{Buf, Reader};
 use fnv::FnvHashMap;
-use futures::{channel::mpsc, future, prelude::*};
+use futures::{channel::mpsc, future, future::BoxFuture, prelude::*};
 use http_body_util::{combinators::BoxBody, StreamBody};
 use hyper::body::Body as _;
 use hyper_rustls::{HttpsConnector, HttpsConnectorBuilder};
@@ -71,8 +71,39 @@ impl SharedClient {
 	}
 }
 
+/// Error returned by an [`HttpBackend`].
+pub type HttpBackendError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Executes the HTTP requests started by offchain workers.
+///
+/// [`SharedClient`] sends them to the Internet, while [`MockHttpBackend`](super::MockHttpBackend)
+/// answers them with canned responses.
+pub trait HttpBackend: Send + Sync {
+	/// Send `request` and return the response.
+	fn request(
+		&self,
+		request: hyper::Request<Body>,
+	) -> BoxFuture<'static, Result<hyper::Response<Body>, HttpBackendError>>;
+}
+
+impl HttpBackend for SharedClient {
+	fn request(
+		&self,
+		request: hyper::Request<Body>,
+	) -> BoxFuture<'static, Result<hyper::Response<Body>, HttpBackendError>> {
+		self.0
+			.request(request)
+			.map_ok(|response| response.map(Body::new))
+			.map_err(Into::into)
+			.boxed()
+	}
+}
+
 /// Creates a pair of [`HttpApi`] and [`HttpWorker`].
-pub fn http(shared_client: SharedClient) -> (HttpApi, HttpWorker) {
+pub(crate) fn http(
+	backend: Arc<dyn HttpBackend>,
+	sandbox: Arc<HttpSandbox>,
+) -> (HttpApi, HttpWorker) {
 	let (to_worker, from_api) = tracing_unbounded("mpsc_ocw_to_worker", 100_000);
 	let (to_api, from_worker) = tracing_unbounded("mpsc_ocw_to_api", 100_000);
 
@@ -83,10 +114,10 @@ pub fn http(shared_client: SharedClient) -> (HttpApi, HttpWorker) {
 		// writing runtime code with hardcoded IDs.
 		next_id: HttpRequestId(rand::random::<u16>() % 2000),
 		requests: FnvHashMap::default(),
+		sandbox,
 	};
 
-	let engine =
-		HttpWorker { to_api, from_api, http_client: shared_client.0, requests: Vec::new() };
+	let engine = HttpWorker { to_api, from_api, http_client: backend, requests: Vec::new() };
 
 	(api, engine)
 }
@@ -105,6 +136,8 @@ pub struct HttpApi {
 	next_id: HttpRequestId,
 	/// List of HTTP requests in preparation or in progress.
 	requests: FnvHashMap<HttpRequestId, HttpApiRequest>,
+	/// Policy the requests must comply with.
+	sandbox: Arc<HttpSandbox>,
 }
 
 /// One active request within `HttpApi`.
@@ -119,7 +152,7 @@ enum HttpApiRequest {
 	/// A request has been dispatched but the worker notified us of an error. We report this
 	/// failure to the user as an `IoError` and remove the request from the list as soon as
 	/// possible.
-	Fail(client::Error),
+	Fail(HttpBackendError),
 }
 
 /// A request within `HttpApi` that has received a response.
@@ -141,6 +174,8 @@ struct HttpApiRequestRp {
 	/// Chunk that has been extracted from the channel and that is currently being read.
 	/// Reading data from the response should read from this field in priority.
 	current_read_chunk: Option<Reader<hyper::body::Bytes>>,
+	/// Number of bytes of the body received so far.
+	received_bytes: u64,
 }
 
 impl HttpApi {
@@ -156,6 +191,17 @@ impl HttpApi {
 		*request.method_mut() = hyper::Method::from_bytes(method.as_bytes()).map_err(|_| ())?;
 		*request.uri_mut() = hyper::Uri::from_maybe_shared(uri.to_owned()).map_err(|_| ())?;
 
+		if let Err(violation) = self.sandbox.start_request(request.uri()) {
+			tracing::debug!(
+				target: LOG_TARGET,
+				%method,
+				%uri,
+				%violation,
+				"Request rejected by the HTTP policy",
+			);
+			return Err(());
+		}
+
 		let new_id = self.next_id;
 		debug_assert!(!self.requests.contains_key(&new_id));
 		match self.next_id.0.checked_add(1) {
@@ -436,6 +482,15 @@ impl HttpApi {
 			match next_message {
 				Some(WorkerToApi::Response { id, status_code, headers, body }) => {
 					match self.requests.remove(&id) {
+						Some(HttpApiRequest::Dispatched(_))
+							if self.exceeds_max_response_size(content_length(&headers)) =>
+						{
+							tracing::debug!(target: LOG_TARGET, id = %id.0, "Response too large");
+							self.requests.insert(
+								id,
+								HttpApiRequest::Fail("response exceeds the maximum size".into()),
+							);
+						},
 						Some(HttpApiRequest::Dispatched(sending_body)) => {
 							self.requests.insert(
 								id,
@@ -445,6 +500,7 @@ impl HttpApi {
 									headers,
 									body: body.fuse(),
 									current_read_chunk: None,
+									received_bytes: 0,
 								}),
 							);
 						},
@@ -556,6 +612,11 @@ impl HttpApi {
 				match next_body {
 					Some(Ok(chunk)) =>
 						if let Ok(chunk) = chunk.into_data() {
+							response.received_bytes += chunk.len() as u64;
+							if self.exceeds_max_response_size(Some(response.received_bytes)) {
+								tracing::debug!(target: LOG_TARGET, id = %request_id.0, "Response too large");
+								return Err(HttpError::IoError);
+							}
 							response.current_read_chunk = Some(chunk.reader());
 						},
 					Some(Err(_)) => return Err(HttpError::IoError),
@@ -569,6 +630,19 @@ impl HttpApi {
 			}
 		}
 	}
+
+	/// Returns `true` if a response body of `size` bytes exceeds the size allowed by the policy.
+	fn exceeds_max_response_size(&self, size: Option<u64>) -> bool {
+		matches!((size, self.sandbox.max_response_size()), (Some(size), Some(max)) if size > max)
+	}
+}
+
+/// Returns the value of the `Content-Length` header, if any.
+fn content_length(headers: &hyper::HeaderMap) -> Option<u64> {
+	headers
+		.get(hyper::header::CONTENT_LENGTH)
+		.and_then(|value| value.to_str().ok())
+		.and_then(|value| value.parse().ok())
 }
 
 impl fmt::Debug for HttpApi {
@@ -628,7 +702,7 @@ enum WorkerToApi {
 		/// The ID that was passed to the worker.
 		id: HttpRequestId,
 		/// Error that happened.
-		error: client::Error,
+		error: HttpBackendError,
 	},
 }
 
@@ -639,7 +713,7 @@ pub struct HttpWorker {
 	/// Used to receive messages from the `HttpApi`.
 	from_api: TracingUnboundedReceiver<ApiToWorker>,
 	/// The engine that runs HTTP requests.
-	http_client: Arc<LazyClient>,
+	http_client: Arc<dyn HttpBackend>,
 	/// HTTP requests that are being worked on by the engine.
 	requests: Vec<(HttpRequestId, HttpWorkerRequest)>,
 }
@@ -647,7 +721,7 @@ pub struct HttpWorker {
 /// HTTP request being processed by the worker.
 enum HttpWorkerRequest {
 	/// Request has been dispatched and is waiting for a response from the Internet.
-	Dispatched(client::ResponseFuture),
+	Dispatched(BoxFuture<'static, Result<hyper::Response<Body>, HttpBackendError>>),
 	/// Progressively reading the body of the response and sending it to the channel.
 	ReadBody {
 		/// Body to read `Chunk`s from. Only used if the channel is ready to accept data.
@@ -673,7 +747,7 @@ impl Future for HttpWorker {
 			match request {
 				HttpWorkerRequest::Dispatched(mut future) => {
 					// Check for an HTTP response from the Internet.
-					let response = match Future::poll(Pin::new(&mut future), cx) {
+					let response = match future.poll_unpin(cx) {
 						Poll::Pending => {
 							me.requests.push((id, HttpWorkerRequest::Dispatched(future)));
 							continue;
@@ -697,10 +771,7 @@ impl Future for HttpWorker {
 						body: body_rx,
 					});
 
-					me.requests.push((
-						id,
-						HttpWorkerRequest::ReadBody { body: Body::new(body), tx: body_tx },
-					));
+					me.requests.push((id, HttpWorkerRequest::ReadBody { body, tx: body_tx }));
 					cx.waker().wake_by_ref(); // reschedule in order to poll the new future
 					continue;
 				},
@@ -772,7 +843,7 @@ impl fmt::Debug for HttpWorkerRequest {
 #[cfg(test)]
 mod tests {
 	use super::{
-		super::{tests::TestNetwork, AsyncApi},
+		super::{tests::TestNetwork, AsyncApi, HttpPolicy, MockHttpBackend, MockResponse},
 		*,
 	};
 	use crate::api::timestamp;
@@ -796,7 +867,7 @@ mod tests {
 		};
 		( $response:expr ) => {{
 			let hyper_client = SHARED_CLIENT.clone();
-			let (api, worker) = http(hyper_client.clone());
+			let (api, worker) = http(Arc::new(hyper_client.clone()), Default::default());
 
 			let (addr_tx, addr_rx) = std::sync::mpsc::channel();
 			std::thread::spawn(move || {
@@ -1130,7 +1201,8 @@ mod tests {
 
 		{
 			let mock = Arc::new(TestNetwork());
-			let (mut api, async_api) = AsyncApi::new(mock, false, shared_client.clone());
+			let (mut api, async_api) =
+				AsyncApi::new(mock, false, Arc::new(shared_client.clone()), Default::default());
 			api.timestamp();
 
 			futures::executor::block_on(async move {
@@ -1145,7 +1217,8 @@ mod tests {
 
 		{
 			let mock = Arc::new(TestNetwork());
-			let (mut api, async_api) = AsyncApi::new(mock, false, shared_client.clone());
+			let (mut api, async_api) =
+				AsyncApi::new(mock, false, Arc::new(shared_client.clone()), Default::default());
 			let id = api.http_request_start("lol", "nope", &[]).unwrap();
 			api.http_request_write_body(id, &[], None).unwrap();
 			futures::executor::block_on(async move {
@@ -1156,4 +1229,97 @@ mod tests {
 		// Check that the http client initialized, because it was used.
 		assert!(Lazy::into_value(Arc::try_unwrap(shared_client.0).unwrap()).is_ok());
 	}
+
+	// Returns an `HttpApi` whose worker sends the requests to `backend` in the background.
+	fn build_api_mock(backend: &MockHttpBackend, policy: HttpPolicy) -> HttpApi {
+		let (api, worker) = http(Arc::new(backend.clone()), Arc::new(HttpSandbox::new(policy)));
+		std::thread::spawn(move || futures::executor::block_on(worker));
+		api
+	}
+
+	#[test]
+	fn mock_backend_round_trip() {
+		let deadline = timestamp::now().add(Duration::from_millis(10_000));
+		let backend = MockHttpBackend::new();
+		backend.respond(
+			"POST",
+			"http://example.com/submit",
+			MockResponse::new(201, "created").with_header("x-test", "yes"),
+		);
+		let mut api = build_api_mock(&backend, Default::default());
+
+		let id = api.request_start("POST", "http://example.com/submit").unwrap();
+		api.request_add_header(id, "Content-Type", "text/plain").unwrap();
+		api.request_write_body(id, b"hello", Some(deadline)).unwrap();
+		api.request_write_body(id, &[], Some(deadline)).unwrap();
+		assert_eq!(
+			api.response_wait(&[id], Some(deadline)),
+			vec![HttpRequestStatus::Finished(201)]
+		);
+		assert!(api.response_headers(id).contains(&(b"x-test".to_vec(), b"yes".to_vec())));
+
+		let mut buf = vec![0; 64];
+		let n = api.response_read_body(id, &mut buf, Some(deadline)).unwrap();
+		assert_eq!(&buf[..n], b"created");
+
+		let id = api.request_start("GET", "http://example.com/missing").unwrap();
+		assert_eq!(
+			api.response_wait(&[id], Some(deadline)),
+			vec![HttpRequestStatus::Finished(404)]
+		);
+
+		let requests = backend.requests();
+		assert_eq!(requests.len(), 2);
+		assert_eq!(requests[0].method, "POST");
+		assert_eq!(requests[0].uri, "http://example.com/submit");
+		assert!(requests[0].headers.contains(&("content-type".into(), b"text/plain".to_vec())));
+		assert_eq!(requests[0].body, b"hello");
+		assert_eq!(requests[1].uri, "http://example.com/missing");
+	}
+
+	#[test]
+	fn http_policy_is_enforced() {
+		let deadline = timestamp::now().add(Duration::from_millis(10_000));
+		let backend = MockHttpBackend::new();
+		backend.respond("GET", "http://example.com/small", MockResponse::new(200, "ok"));
+		backend.respond("GET", "http://example.com/large", MockResponse::new(200, vec![0; 64]));
+		backend.respond(
+			"GET",
+			"http://example.com/chunked",
+			MockResponse::new(200, vec![0; 64]).with_header("content-length", "unknown"),
+		);
+		let mut api = build_api_mock(
+			&backend,
+			HttpPolicy {
+				allowed_hosts: Some(vec!["example.com".into()]),
+				max_requests_per_minute: None,
+				max_response_size: Some(16),
+			},
+		);
+
+		// Requests to other hosts are rejected before being sent.
+		assert_eq!(api.request_start("GET", "http://localhost/"), Err(()));
+
+		let id = api.request_start("GET", "http://example.com/small").unwrap();
+		assert_eq!(
+			api.response_wait(&[id], Some(deadline)),
+			vec![HttpRequestStatus::Finished(200)]
+		);
+		let mut buf = vec![0; 64];
+		assert_eq!(api.response_read_body(id, &mut buf, Some(deadline)), Ok(2));
+
+		// Responses announcing a body over the limit fail right away.
+		let id = api.request_start("GET", "http://example.com/large").unwrap();
+		assert_eq!(api.response_wait(&[id], Some(deadline)), vec![HttpRequestStatus::IoError]);
+
+		// Otherwise the body fails once it exceeds the limit.
+		let id = api.request_start("GET", "http://example.com/chunked").unwrap();
+		assert_eq!(
+			api.response_wait(&[id], Some(deadline)),
+			vec![HttpRequestStatus::Finished(200)]
+		);
+		assert_eq!(api.response_read_body(id, &mut buf, Some(deadline)), Err(HttpError::IoError));
+
+		assert_eq!(backend.requests().len(), 3);
+	}
 }
@@ -0,0 +1,160 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! In-memory [`HttpBackend`] answering offchain worker requests with canned responses.
+//!
+//! Meant for tests that run offchain workers without access to the Internet.
+
+use super::http::{Body, HttpBackend, HttpBackendError};
+use futures::{future::BoxFuture, FutureExt};
+use http_body_util::{BodyExt, Full};
+use parking_lot::Mutex;
+use std::sync::Arc;
+
+/// Response returned by [`MockHttpBackend`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MockResponse {
+	/// Status code.
+	pub status: u16,
+	/// Headers. `Content-Length` is added automatically if missing.
+	pub headers: Vec<(String, String)>,
+	/// Body.
+	pub body: Vec<u8>,
+}
+
+impl MockResponse {
+	/// Create a response with the given status code and body and no headers.
+	pub fn new(status: u16, body: impl Into<Vec<u8>>) -> Self {
+		Self { status, headers: Vec::new(), body: body.into() }
+	}
+
+	/// Add a header to the response.
+	pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+		self.headers.push((name.into(), value.into()));
+		self
+	}
+}
+
+/// Request received by [`MockHttpBackend`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MockRequest {
+	/// Method, e.g. `GET`.
+	pub method: String,
+	/// Full URI, e.g. `http://localhost/path`.
+	pub uri: String,
+	/// Headers, in the order they were added.
+	pub headers: Vec<(String, Vec<u8>)>,
+	/// Complete body.
+	pub body: Vec<u8>,
+}
+
+/// [`HttpBackend`] that records the requests it receives and answers them with the responses
+/// registered through [`MockHttpBackend::respond`].
+///
+/// Requests without a registered response are answered with `404 Not Found`. Clones share the
+/// same responses and recorded requests.
+#[derive(Debug, Clone, Default)]
+pub struct MockHttpBackend {
+	inner: Arc<Mutex<Inner>>,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+	/// Registered responses, keyed by method and URI.
+	responses: Vec<(String, String, MockResponse)>,
+	/// Requests received so far.
+	requests: Vec<MockRequest>,
+}
+
+impl MockHttpBackend {
+	/// Create new [`MockHttpBackend`] without any registered response.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Answer all requests with `method` to `uri` with `response`, replacing any response
+	/// previously registered for them.
+	///
+	/// `uri` is compared with the full URI of the request, so `http://localhost` doesn't match
+	/// requests to `http://localhost/`.
+	pub fn respond(&self, method: &str, uri: &str, response: MockResponse) {
+		let mut inner = self.inner.lock();
+		inner.responses.retain(|(m, u, _)| m != method || u != uri);
+		inner.responses.push((method.to_owned(), uri.to_owned(), response));
+	}
+
+	/// Requests received so far, in the order they were completely received.
+	pub fn requests(&self) -> Vec<MockRequest> {
+		self.inner.lock().requests.clone()
+	}
+}
+
+impl HttpBackend for MockHttpBackend {
+	fn request(
+		&self,
+		request: hyper::Request<Body>,
+	) -> BoxFuture<'static, Result<hyper::Response<Body>, HttpBackendError>> {
+		let inner = self.inner.clone();
+
+		async move {
+			// Like a real server, only answer once the whole request has been received.
+			let (head, body) = request.into_parts();
+			let body = body.collect().await?.to_bytes().to_vec();
+			let request = MockRequest {
+				method: head.method.to_string(),
+				uri: head.uri.to_string(),
+				headers: head
+					.headers
+					.iter()
+					.map(|(name, value)| (name.to_string(), value.as_bytes().to_vec()))
+					.collect(),
+				body,
+			};
+
+			let response = {
+				let mut inner = inner.lock();
+				let response = inner
+					.responses
+					.iter()
+					.find(|(method, uri, _)| *method == request.method && *uri == request.uri)
+					.map(|(_, _, response)| response.clone())
+					.unwrap_or_else(|| MockResponse::new(404, Vec::new()));
+				inner.requests.push(request);
+				response
+			};
+
+			let mut builder = hyper::Response::builder().status(response.status);
+			for (name, value) in &response.headers {
+				builder = builder.header(name, value);
+			}
+			if !response
+				.headers
+				.iter()
+				.any(|(name, _)| name.eq_ignore_ascii_case("content-length"))
+			{
+				builder = builder.header(hyper::header::CONTENT_LENGTH, response.body.len());
+			}
+
+			let body = Full::new(hyper::body::Bytes::from(response.body))
+				.map_err(|never| match never {})
+				.boxed();
+			Ok(builder.body(body)?)
+		}
+		.boxed()
+	}
+}
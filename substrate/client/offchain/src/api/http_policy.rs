@@ -0,0 +1,212 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Policy restricting the HTTP requests offchain workers may make.
+
+use parking_lot::Mutex;
+use serde::Deserialize;
+use std::{
+	collections::VecDeque,
+	fmt, fs, io,
+	num::NonZeroU32,
+	path::Path,
+	time::{Duration, Instant},
+};
+
+/// Window the request rate limit applies to.
+const RATE_LIMIT_WINDOW: Duration = Duration::from_secs(60);
+
+/// Restrictions on the HTTP requests made by offchain workers.
+///
+/// The default policy doesn't restrict anything.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct HttpPolicy {
+	/// Hosts requests may be sent to. `None` allows all hosts.
+	///
+	/// An entry of the form `*.example.com` allows all subdomains of `example.com`, but not
+	/// `example.com` itself.
+	pub allowed_hosts: Option<Vec<String>>,
+
+	/// Maximum number of requests that may be started per minute. `None` means unlimited.
+	pub max_requests_per_minute: Option<NonZeroU32>,
+
+	/// Maximum size of a response body in bytes. `None` means unlimited.
+	pub max_response_size: Option<u64>,
+}
+
+impl HttpPolicy {
+	/// Load the policy from the JSON file at `path`.
+	pub fn from_file(path: &Path) -> io::Result<Self> {
+		serde_json::from_slice(&fs::read(path)?)
+			.map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+	}
+
+	/// Returns `true` if requests to `host` are allowed.
+	pub fn is_host_allowed(&self, host: &str) -> bool {
+		let Some(allowed_hosts) = &self.allowed_hosts else { return true };
+		let host = host.trim_end_matches('.').to_ascii_lowercase();
+
+		allowed_hosts.iter().any(|allowed| {
+			let allowed = allowed.trim_end_matches('.').to_ascii_lowercase();
+
+			match allowed.strip_prefix("*.") {
+				Some(domain) => host
+					.strip_suffix(domain)
+					.is_some_and(|subdomain| subdomain.len() > 1 && subdomain.ends_with('.')),
+				None => host == allowed,
+			}
+		})
+	}
+}
+
+/// Reason for rejecting a request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum PolicyViolation {
+	/// The URI doesn't contain a host.
+	MissingHost,
+	/// The host isn't in [`HttpPolicy::allowed_hosts`].
+	HostNotAllowed(String),
+	/// [`HttpPolicy::max_requests_per_minute`] has been reached.
+	RateLimited,
+}
+
+impl fmt::Display for PolicyViolation {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			Self::MissingHost => write!(f, "URI has no host"),
+			Self::HostNotAllowed(host) => write!(f, "host {host} is not allowed"),
+			Self::RateLimited => write!(f, "request rate limit reached"),
+		}
+	}
+}
+
+/// Enforces an [`HttpPolicy`] across all offchain worker runs.
+#[derive(Debug, Default)]
+pub(crate) struct HttpSandbox {
+	/// Enforced policy.
+	policy: HttpPolicy,
+	/// Start times of the requests within the last [`RATE_LIMIT_WINDOW`].
+	recent_requests: Mutex<VecDeque<Instant>>,
+}
+
+impl HttpSandbox {
+	/// Create new [`HttpSandbox`].
+	pub fn new(policy: HttpPolicy) -> Self {
+		Self { policy, recent_requests: Default::default() }
+	}
+
+	/// Check whether a request to `uri` may be started, counting it against the rate limit if so.
+	pub fn start_request(&self, uri: &hyper::Uri) -> Result<(), PolicyViolation> {
+		if self.policy.allowed_hosts.is_some() {
+			let host = uri.host().ok_or(PolicyViolation::MissingHost)?;
+
+			if !self.policy.is_host_allowed(host) {
+				return Err(PolicyViolation::HostNotAllowed(host.to_owned()))
+			}
+		}
+
+		if let Some(max_requests) = self.policy.max_requests_per_minute {
+			let now = Instant::now();
+			let mut recent_requests = self.recent_requests.lock();
+
+			while recent_requests
+				.front()
+				.is_some_and(|started| now.duration_since(*started) >= RATE_LIMIT_WINDOW)
+			{
+				recent_requests.pop_front();
+			}
+			if recent_requests.len() >= max_requests.get() as usize {
+				return Err(PolicyViolation::RateLimited)
+			}
+			recent_requests.push_back(now);
+		}
+
+		Ok(())
+	}
+
+	/// Maximum size of a response body in bytes.
+	pub fn max_response_size(&self) -> Option<u64> {
+		self.policy.max_response_size
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn uri(uri: &str) -> hyper::Uri {
+		hyper::Uri::from_maybe_shared(uri.to_owned()).unwrap()
+	}
+
+	#[test]
+	fn hosts_are_matched() {
+		let policy = HttpPolicy {
+			allowed_hosts: Some(vec!["example.com".into(), "*.parity.io".into()]),
+			..Default::default()
+		};
+
+		assert!(policy.is_host_allowed("example.com"));
+		assert!(policy.is_host_allowed("EXAMPLE.com."));
+		assert!(!policy.is_host_allowed("api.example.com"));
+		assert!(policy.is_host_allowed("api.parity.io"));
+		assert!(policy.is_host_allowed("a.b.parity.io"));
+		assert!(!policy.is_host_allowed("parity.io"));
+		assert!(!policy.is_host_allowed("evilparity.io"));
+		assert!(HttpPolicy::default().is_host_allowed("anything"));
+	}
+
+	#[test]
+	fn requests_are_checked() {
+		let sandbox = HttpSandbox::new(HttpPolicy {
+			allowed_hosts: Some(vec!["localhost".into()]),
+			max_requests_per_minute: NonZeroU32::new(2),
+			max_response_size: None,
+		});
+
+		assert_eq!(
+			sandbox.start_request(&uri("http://example.com/")),
+			Err(PolicyViolation::HostNotAllowed("example.com".into())),
+		);
+		assert_eq!(sandbox.start_request(&uri("/path")), Err(PolicyViolation::MissingHost));
+		assert_eq!(sandbox.start_request(&uri("http://localhost:8080/")), Ok(()));
+		assert_eq!(sandbox.start_request(&uri("https://localhost/")), Ok(()));
+		assert_eq!(
+			sandbox.start_request(&uri("http://localhost/")),
+			Err(PolicyViolation::RateLimited)
+		);
+	}
+
+	#[test]
+	fn policy_is_parsed_from_json() {
+		let policy: HttpPolicy = serde_json::from_str(
+			r#"{"allowedHosts":["example.com"],"maxRequestsPerMinute":10,"maxResponseSize":1024}"#,
+		)
+		.unwrap();
+
+		assert_eq!(
+			policy,
+			HttpPolicy {
+				allowed_hosts: Some(vec!["example.com".into()]),
+				max_requests_per_minute: NonZeroU32::new(10),
+				max_response_size: Some(1024),
+			}
+		);
+		assert_eq!(serde_json::from_str::<HttpPolicy>("{}").unwrap(), HttpPolicy::default());
+	}
+}
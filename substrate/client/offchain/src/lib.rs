@@ -54,6 +54,10 @@ use threadpool::ThreadPool;
 
 mod api;
 
+pub use api::{
+	HttpBackend, HttpBackendError, HttpBody, HttpPolicy, MockHttpBackend, MockRequest,
+	MockResponse, SharedClient,
+};
 pub use sp_core::offchain::storage::OffchainDb;
 pub use sp_offchain::{OffchainWorkerApi, STORAGE_PREFIX};
 
@@ -110,6 +114,8 @@ pub struct OffchainWorkerOptions<RA, Block: traits::Block, Storage, CE> {
 	///
 	/// If not enabled, any http request will panic.
 	pub enable_http_requests: bool,
+	/// Restrictions on the http requests made by offchain workers.
+	pub http_policy: HttpPolicy,
 	/// Callback to create custom [`Extension`]s that should be registered for the
 	/// `offchain_worker` runtime call.
 	///
@@ -130,7 +136,8 @@ pub struct OffchainWorkerOptions<RA, Block: traits::Block, Storage, CE> {
 pub struct OffchainWorkers<RA, Block: traits::Block, Storage> {
 	runtime_api_provider: Arc<RA>,
 	thread_pool: Mutex<ThreadPool>,
-	shared_http_client: api::SharedClient,
+	http_backend: Arc<dyn HttpBackend>,
+	http_sandbox: Arc<api::HttpSandbox>,
 	enable_http_requests: bool,
 	keystore: Option<KeystorePtr>,
 	offchain_db: Option<OffchainDb<Storage>>,
@@ -151,6 +158,7 @@ impl<RA, Block: traits::Block, Storage> OffchainWorkers<RA, Block, Storage> {
 			network_provider,
 			is_validator,
 			enable_http_requests,
+			http_policy,
 			custom_extensions,
 		}: OffchainWorkerOptions<RA, Block, Storage, CE>,
 	) -> std::io::Result<Self> {
@@ -160,7 +168,8 @@ impl<RA, Block: traits::Block, Storage> OffchainWorkers<RA, Block, Storage> {
 				"offchain-worker".into(),
 				num_cpus::get(),
 			)),
-			shared_http_client: api::SharedClient::new()?,
+			http_backend: Arc::new(SharedClient::new()?),
+			http_sandbox: Arc::new(api::HttpSandbox::new(http_policy)),
 			enable_http_requests,
 			keystore,
 			offchain_db: offchain_db.map(OffchainDb::new),
@@ -170,6 +179,15 @@ impl<RA, Block: traits::Block, Storage> OffchainWorkers<RA, Block, Storage> {
 			custom_extensions: Box::new(custom_extensions),
 		})
 	}
+
+	/// Send the http requests of offchain workers to `backend` instead of the Internet.
+	///
+	/// The [`HttpPolicy`] is still enforced. Mostly useful to plug in a [`MockHttpBackend`] in
+	/// tests.
+	pub fn with_http_backend(mut self, backend: Arc<dyn HttpBackend>) -> Self {
+		self.http_backend = backend;
+		self
+	}
 }
 
 impl<RA, Block: traits::Block, Storage: offchain::OffchainStorage> fmt::Debug
@@ -246,7 +264,8 @@ where
 			let (api, runner) = api::AsyncApi::new(
 				self.network_provider.clone(),
 				self.is_validator,
-				self.shared_http_client.clone(),
+				self.http_backend.clone(),
+				self.http_sandbox.clone(),
 			);
 			tracing::debug!(target: LOG_TARGET, "Spawning offchain workers at {hash:?}");
 			let header = header.clone();
@@ -465,6 +484,7 @@ mod tests {
 			network_provider: network,
 			is_validator: false,
 			enable_http_requests: false,
+			http_policy: Default::default(),
 			custom_extensions: |_| Vec::new(),
 		})
 		.unwrap();
@@ -0,0 +1,49 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Database RPC errors.
+
+use jsonrpsee::types::error::{ErrorObject, ErrorObjectOwned};
+
+/// Database RPC Result type.
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Database RPC errors.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+	/// Failed to write the database snapshot.
+	#[error("Failed to write the database snapshot: {0}")]
+	SnapshotFailed(Box<dyn std::error::Error + Send>),
+	/// Call to an unsafe RPC was denied.
+	#[error(transparent)]
+	UnsafeRpcCalled(#[from] crate::policy::UnsafeRpcError),
+}
+
+/// Base error code for all database errors.
+const BASE_ERROR: i32 = crate::error::base::DB;
+
+impl From<Error> for ErrorObjectOwned {
+	fn from(e: Error) -> Self {
+		let msg = e.to_string();
+
+		match e {
+			Error::SnapshotFailed(_) => ErrorObject::owned(BASE_ERROR + 1, msg, None::<()>),
+			Error::UnsafeRpcCalled(e) => e.into(),
+		}
+	}
+}
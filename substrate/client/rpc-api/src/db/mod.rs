@@ -0,0 +1,39 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Substrate database API.
+
+pub mod error;
+
+use error::Error;
+use jsonrpsee::proc_macros::rpc;
+
+/// Substrate database API.
+///
+/// The API is meant for node operators only, so all methods are unsafe.
+#[rpc(client, server)]
+pub trait DbApi<Hash> {
+	/// Write a consistent copy of the node database to the new database at `path`, while the
+	/// node keeps running. Returns the hash of the best finalized block in the copy.
+	///
+	/// The `path` directory must be empty or missing. The copy may be restored with the
+	/// `restore` command, after the node is stopped. Only RocksDB databases are supported,
+	/// ParityDB databases are not.
+	#[method(name = "db_snapshot", with_extensions, blocking)]
+	fn snapshot(&self, path: String) -> Result<Hash, Error>;
+}
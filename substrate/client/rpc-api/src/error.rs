@@ -26,4 +26,5 @@ pub mod base {
 	pub const DEV: i32 = 6000;
 	pub const STATEMENT: i32 = 7000;
 	pub const MIXNET: i32 = 8000;
+	pub const DB: i32 = 9000;
 }
@@ -30,6 +30,7 @@ pub use policy::{check_if_safe, DenyUnsafe, UnsafeRpcError};
 pub mod author;
 pub mod chain;
 pub mod child_state;
+pub mod db;
 pub mod dev;
 pub mod mixnet;
 pub mod offchain;
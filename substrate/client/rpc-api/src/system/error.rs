@@ -36,6 +36,9 @@ pub enum Error {
 	/// Peer argument is malformatted.
 	#[error("{0}")]
 	MalformattedPeerArg(String),
+	/// Address range argument is malformatted.
+	#[error("{0}")]
+	MalformattedAddressRangeArg(String),
 	/// Call to an unsafe RPC was denied.
 	#[error(transparent)]
 	UnsafeRpcCalled(#[from] crate::policy::UnsafeRpcError),
@@ -50,6 +53,8 @@ const BASE_ERROR: i32 = crate::error::base::SYSTEM;
 const NOT_HEALTHY_ERROR: i32 = BASE_ERROR + 1;
 // Peer argument is malformatted.
 const MALFORMATTED_PEER_ARG_ERROR: i32 = BASE_ERROR + 2;
+// Address range argument is malformatted.
+const MALFORMATTED_ADDRESS_RANGE_ARG_ERROR: i32 = BASE_ERROR + 3;
 
 impl From<Error> for ErrorObjectOwned {
 	fn from(e: Error) -> ErrorObjectOwned {
@@ -58,6 +63,8 @@ impl From<Error> for ErrorObjectOwned {
 				ErrorObject::owned(NOT_HEALTHY_ERROR, e.to_string(), Some(h)),
 			Error::MalformattedPeerArg(e) =>
 				ErrorObject::owned(MALFORMATTED_PEER_ARG_ERROR, e, None::<()>),
+			Error::MalformattedAddressRangeArg(e) =>
+				ErrorObject::owned(MALFORMATTED_ADDRESS_RANGE_ARG_ERROR, e, None::<()>),
 			Error::UnsafeRpcCalled(e) => e.into(),
 			Error::Internal(e) =>
 				ErrorObjectOwned::owned(ErrorCode::InternalError.code(), e, None::<()>),
@@ -71,6 +71,52 @@ pub struct PeerInfo<Hash, Number> {
 	pub best_number: Number,
 }
 
+/// Peer banned by the node operator.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BannedPeer {
+	/// Peer ID
+	pub peer_id: String,
+	/// Reason given for the ban
+	pub reason: String,
+}
+
+/// Peers and address ranges banned by the node operator.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BanList {
+	/// Banned peers
+	pub peers: Vec<BannedPeer>,
+	/// Banned IP address ranges
+	pub address_ranges: Vec<String>,
+}
+
+/// Change of a peer's reputation.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReputationChange {
+	/// Reputation delta
+	pub value: i32,
+	/// Reason for the change
+	pub reason: String,
+}
+
+/// Reputation of a known peer.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PeerReputation {
+	/// Peer ID
+	pub peer_id: String,
+	/// Current reputation value
+	pub reputation: i32,
+	/// Is the peer banned, either by reputation or by the node operator
+	pub is_banned: bool,
+	/// Reason given by the node operator for the ban, if any
+	pub ban_reason: Option<String>,
+	/// Most recent reputation changes, oldest first
+	pub recent_changes: Vec<ReputationChange>,
+}
+
 /// The role the node is running as
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum NodeRole {
@@ -123,6 +169,21 @@ mod tests {
 		);
 	}
 
+	#[test]
+	fn should_serialize_peer_reputation() {
+		assert_eq!(
+			::serde_json::to_string(&PeerReputation {
+				peer_id: "2".into(),
+				reputation: -5,
+				is_banned: true,
+				ban_reason: Some("spam".into()),
+				recent_changes: vec![ReputationChange { value: -5, reason: "bad".into() }],
+			})
+			.unwrap(),
+			r#"{"peerId":"2","reputation":-5,"isBanned":true,"banReason":"spam","recentChanges":[{"value":-5,"reason":"bad"}]}"#,
+		);
+	}
+
 	#[test]
 	fn should_serialize_sync_state() {
 		assert_eq!(
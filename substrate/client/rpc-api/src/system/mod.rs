@@ -23,7 +23,10 @@ pub mod helpers;
 
 use jsonrpsee::{core::JsonValue, proc_macros::rpc};
 
-pub use self::helpers::{Health, NodeRole, PeerInfo, SyncState, SystemInfo};
+pub use self::helpers::{
+	BanList, BannedPeer, Health, NodeRole, PeerInfo, PeerReputation, ReputationChange, SyncState,
+	SystemInfo,
+};
 pub use error::Error;
 
 /// Substrate system RPC API
@@ -98,6 +101,35 @@ pub trait SystemApi<Hash, Number> {
 	#[method(name = "system_reservedPeers")]
 	async fn system_reserved_peers(&self) -> Result<Vec<String>, Error>;
 
+	/// Bans a peer until it's unbanned with `system_unbanPeer`, surviving restarts.
+	///
+	/// The string should encode only the PeerId e.g.
+	/// `QmSk5HQbn6LhUwDiNMseVUjuRYhEtYj4aUZ6WfWoGURpdV`.
+	#[method(name = "system_banPeer", with_extensions)]
+	async fn system_ban_peer(&self, peer_id: String, reason: Option<String>) -> Result<(), Error>;
+
+	/// Lifts the ban set by `system_banPeer`.
+	#[method(name = "system_unbanPeer", with_extensions)]
+	async fn system_unban_peer(&self, peer_id: String) -> Result<(), Error>;
+
+	/// Bans all peers connecting from an IP address range, e.g. `198.51.100.0/24`, surviving
+	/// restarts.
+	#[method(name = "system_banAddressRange", with_extensions)]
+	async fn system_ban_address_range(&self, range: String) -> Result<(), Error>;
+
+	/// Lifts the ban set by `system_banAddressRange`.
+	#[method(name = "system_unbanAddressRange", with_extensions)]
+	async fn system_unban_address_range(&self, range: String) -> Result<(), Error>;
+
+	/// Returns the peers and address ranges banned with `system_banPeer` and
+	/// `system_banAddressRange`.
+	#[method(name = "system_banList", with_extensions)]
+	async fn system_ban_list(&self) -> Result<BanList, Error>;
+
+	/// Returns the reputations of known peers along with their most recent reputation changes.
+	#[method(name = "system_peerReputations", with_extensions)]
+	async fn system_peer_reputations(&self) -> Result<Vec<PeerReputation>, Error>;
+
 	/// Returns the roles the node is running as.
 	#[method(name = "system_nodeRoles")]
 	async fn system_node_roles(&self) -> Result<Vec<NodeRole>, Error>;
@@ -81,6 +81,18 @@ pub trait ArchiveApi<Hash> {
 	#[method(name = "archive_unstable_hashByHeight")]
 	fn archive_unstable_hash_by_height(&self, height: u64) -> RpcResult<Vec<String>>;
 
+	/// Get the heights of finalized blocks from the `[from, to]` range, which state is
+	/// available.
+	///
+	/// Returns an array (possibly empty) of at most 1024 heights in ascending order. The state
+	/// of blocks at other heights has been pruned by the node.
+	///
+	/// # Unstable
+	///
+	/// This method is unstable and subject to change in the future.
+	#[method(name = "archive_unstable_availableStateHeights")]
+	fn archive_unstable_available_state_heights(&self, from: u64, to: u64) -> RpcResult<Vec<u64>>;
+
 	/// Call into the Runtime API at a specified block's state.
 	///
 	/// # Unstable
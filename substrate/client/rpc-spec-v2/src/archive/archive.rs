@@ -38,8 +38,8 @@ use jsonrpsee::{
 	PendingSubscriptionSink,
 };
 use sc_client_api::{
-	Backend, BlockBackend, BlockchainEvents, CallExecutor, ChildInfo, ExecutorProvider, StorageKey,
-	StorageProvider,
+	Backend, BlockBackend, BlockchainEvents, CallExecutor, ChildInfo, ExecutorProvider,
+	StateRetention, StorageKey, StorageProvider,
 };
 use sc_rpc::utils::Subscription;
 use sp_api::{CallApiAt, CallContext};
@@ -63,6 +63,9 @@ pub(crate) const LOG_TARGET: &str = "rpc-spec-v2::archive";
 /// its down buffer capacity per connection as well.
 const STORAGE_QUERY_BUF: usize = 16;
 
+/// The maximum number of heights returned by `archive_unstable_availableStateHeights`.
+const MAX_AVAILABLE_STATE_HEIGHTS: usize = 1024;
+
 /// An API for archive RPC calls.
 pub struct Archive<BE: Backend<Block>, Block: BlockT, Client> {
 	/// Substrate client.
@@ -90,6 +93,37 @@ impl<BE: Backend<Block>, Block: BlockT, Client> Archive<BE, Block, Client> {
 	}
 }
 
+impl<BE: Backend<Block>, Block: BlockT, Client: HeaderBackend<Block>> Archive<BE, Block, Client> {
+	/// Returns true if the state of the finalized block at given height is available.
+	fn is_state_available_at(&self, height: u64) -> bool {
+		let Ok(number) = U256::from(height).try_into() else { return false };
+		let Ok(Some(hash)) = self.client.hash(number) else { return false };
+		self.backend.have_state_at(hash, number)
+	}
+
+	/// Returns an error if the state of the block has been pruned.
+	///
+	/// Unknown blocks are not reported here, since the methods handle them differently.
+	fn ensure_state_available(&self, hash: Block::Hash) -> Result<(), ArchiveError> {
+		let Ok(Some(header)) = self.client.header(hash) else { return Ok(()) };
+		if self.backend.have_state_at(hash, *header.number()) {
+			return Ok(())
+		}
+
+		let mut message = format!("state of block {hash:?} (#{}) has been pruned", header.number());
+		if let StateRetention::Pruned { keep_last, keep_every } = self.backend.state_retention() {
+			message += &match keep_every {
+				Some(keep_every) => format!(
+					"; the state of every {keep_every}th block and of the last {keep_last} \
+					finalized blocks is kept"
+				),
+				None => format!("; the state of the last {keep_last} finalized blocks is kept"),
+			};
+		}
+		Err(ArchiveError::StatePruned(message))
+	}
+}
+
 /// Parse hex-encoded string parameter as raw bytes.
 ///
 /// If the parsing fails, returns an error propagated to the RPC method.
@@ -195,6 +229,39 @@ where
 		Ok(result)
 	}
 
+	fn archive_unstable_available_state_heights(&self, from: u64, to: u64) -> RpcResult<Vec<u64>> {
+		if from > to {
+			return Err(ArchiveError::InvalidParam(format!(
+				"Invalid block range: {from} is greater than {to}"
+			))
+			.into())
+		}
+
+		let finalized: u64 = self.client.info().finalized_number.saturated_into();
+		let to = to.min(finalized);
+		if from > to {
+			return Ok(vec![])
+		}
+
+		// only check heights which state may be kept by the backend
+		let heights: Box<dyn Iterator<Item = u64>> = match self.backend.state_retention() {
+			StateRetention::All => Box::new(from..=to),
+			StateRetention::Pruned { keep_last, keep_every } => {
+				let window_start = finalized.saturating_sub(keep_last as u64).max(from);
+				let kept = keep_every.filter(|n| *n > 0).map(|n| {
+					let n = n as u64;
+					(from.div_ceil(n) * n..window_start.min(to + 1)).step_by(n as usize)
+				});
+				Box::new(kept.into_iter().flatten().chain(window_start..=to))
+			},
+		};
+
+		Ok(heights
+			.filter(|height| self.is_state_available_at(*height))
+			.take(MAX_AVAILABLE_STATE_HEIGHTS)
+			.collect())
+	}
+
 	fn archive_unstable_call(
 		&self,
 		hash: Block::Hash,
@@ -202,6 +269,9 @@ where
 		call_parameters: String,
 	) -> RpcResult<MethodResult> {
 		let call_parameters = Bytes::from(parse_hex_param(call_parameters)?);
+		if let Err(error) = self.ensure_state_available(hash) {
+			return Ok(MethodResult::err(error.to_string()))
+		}
 
 		let result =
 			self.client
@@ -223,10 +293,16 @@ where
 	) {
 		let mut storage_client =
 			StorageSubscriptionClient::<Client, Block, BE>::new(self.client.clone());
+		let state_available = self.ensure_state_available(hash);
 
 		let fut = async move {
 			let Ok(mut sink) = pending.accept().await.map(Subscription::from) else { return };
 
+			if let Err(error) = state_available {
+				let _ = sink.send(&ArchiveStorageEvent::err(error.to_string())).await;
+				return
+			}
+
 			let items = match items
 				.into_iter()
 				.map(|query| {
@@ -179,6 +179,37 @@ async fn archive_finalized_height() {
 	assert_eq!(client_height, height);
 }
 
+#[tokio::test]
+async fn archive_available_state_heights() {
+	let (client, api) = setup_api();
+
+	let mut parent = (client.chain_info().genesis_hash, 0);
+	for _ in 0..3 {
+		let block = BlockBuilderBuilder::new(&*client)
+			.on_parent_block(parent.0)
+			.with_parent_block_number(parent.1)
+			.build()
+			.unwrap()
+			.build()
+			.unwrap()
+			.block;
+		client.import(BlockOrigin::Own, block.clone()).await.unwrap();
+		parent = (block.hash(), *block.header().number());
+	}
+	client.finalize_block(parent.0, None).unwrap();
+
+	// State of all finalized blocks is kept by the test backend.
+	let heights: Vec<u64> =
+		api.call("archive_unstable_availableStateHeights", [1, 10]).await.unwrap();
+	assert_eq!(heights, vec![1, 2, 3]);
+
+	let err = api
+		.call::<_, serde_json::Value>("archive_unstable_availableStateHeights", [2, 1])
+		.await
+		.unwrap_err();
+	assert_matches!(err, Error::JsonRpc(err) if err.code() == 3001 && err.message().contains("Invalid parameter"));
+}
+
 #[tokio::test]
 async fn archive_hash_by_height() {
 	let (client, api) = setup_api();
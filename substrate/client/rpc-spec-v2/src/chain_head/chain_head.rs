@@ -51,7 +51,14 @@ use sp_blockchain::{Error as BlockChainError, HeaderBackend, HeaderMetadata};
 use sp_core::{traits::CallContext, Bytes};
 use sp_rpc::list::ListOrValue;
 use sp_runtime::traits::Block as BlockT;
-use std::{marker::PhantomData, sync::Arc, time::Duration};
+use std::{
+	marker::PhantomData,
+	sync::{
+		atomic::{AtomicBool, Ordering},
+		Arc,
+	},
+	time::Duration,
+};
 use tokio::sync::mpsc;
 
 pub(crate) const LOG_TARGET: &str = "rpc-spec-v2";
@@ -79,6 +86,26 @@ pub struct ChainHeadConfig {
 	pub subscription_buffer_cap: usize,
 }
 
+/// Shared switch suspending the `chainHead_follow` subscriptions.
+///
+/// While suspended, new subscriptions are rejected and the running ones are stopped when they next
+/// pin a block, releasing the blocks they keep pinned and cached by the backend. Used to shed the
+/// load of the node, e.g. when it runs low on disk space.
+#[derive(Debug, Clone, Default)]
+pub struct ChainHeadSuspension(Arc<AtomicBool>);
+
+impl ChainHeadSuspension {
+	/// Suspend or resume the subscriptions.
+	pub fn set_suspended(&self, suspended: bool) {
+		self.0.store(suspended, Ordering::Relaxed);
+	}
+
+	/// Returns `true` if the subscriptions are suspended.
+	pub fn is_suspended(&self) -> bool {
+		self.0.load(Ordering::Relaxed)
+	}
+}
+
 /// Maximum pinned blocks across all connections.
 /// This number is large enough to consider immediate blocks.
 /// Note: This should never exceed the `PINNING_CACHE_SIZE` from client/db.
@@ -157,6 +184,12 @@ impl<BE: Backend<Block>, Block: BlockT, Client> ChainHead<BE, Block, Client> {
 			_phantom: PhantomData,
 		}
 	}
+
+	/// Suspend the subscriptions whenever `suspension` is set.
+	pub fn with_suspension(mut self, suspension: ChainHeadSuspension) -> Self {
+		self.subscriptions.set_suspension(suspension);
+		self
+	}
 }
 
 /// Helper to convert the `subscription ID` to a string.
@@ -26,7 +26,7 @@ mod error;
 mod inner;
 
 use crate::{
-	chain_head::chain_head::LOG_TARGET,
+	chain_head::chain_head::{ChainHeadSuspension, LOG_TARGET},
 	common::connections::{RegisteredConnection, ReservedConnection, RpcConnections},
 };
 
@@ -47,6 +47,9 @@ pub struct SubscriptionManagement<Block: BlockT, BE: Backend<Block>> {
 	/// For example, `chainHead_storage` cannot be called with a subscription ID that
 	/// was obtained from a different connection.
 	rpc_connections: RpcConnections,
+
+	/// Rejects new subscriptions and stops the running ones while set.
+	suspension: ChainHeadSuspension,
 }
 
 impl<Block: BlockT, BE: Backend<Block>> Clone for SubscriptionManagement<Block, BE> {
@@ -54,6 +57,7 @@ impl<Block: BlockT, BE: Backend<Block>> Clone for SubscriptionManagement<Block,
 		SubscriptionManagement {
 			inner: self.inner.clone(),
 			rpc_connections: self.rpc_connections.clone(),
+			suspension: self.suspension.clone(),
 		}
 	}
 }
@@ -75,9 +79,15 @@ impl<Block: BlockT, BE: Backend<Block>> SubscriptionManagement<Block, BE> {
 				backend,
 			))),
 			rpc_connections: RpcConnections::new(max_follow_subscriptions_per_connection),
+			suspension: Default::default(),
 		}
 	}
 
+	/// Suspend the subscriptions whenever `suspension` is set.
+	pub(crate) fn set_suspension(&mut self, suspension: ChainHeadSuspension) {
+		self.suspension = suspension;
+	}
+
 	/// Create a new instance from the inner state.
 	///
 	/// # Note
@@ -88,16 +98,21 @@ impl<Block: BlockT, BE: Backend<Block>> SubscriptionManagement<Block, BE> {
 		inner: Arc<RwLock<SubscriptionsInner<Block, BE>>>,
 		rpc_connections: RpcConnections,
 	) -> Self {
-		SubscriptionManagement { inner, rpc_connections }
+		SubscriptionManagement { inner, rpc_connections, suspension: Default::default() }
 	}
 
 	/// Reserve space for a subscriptions.
 	///
-	/// Fails if the connection ID is has reached the maximum number of active subscriptions.
+	/// Fails if the connection ID is has reached the maximum number of active subscriptions, or if
+	/// the subscriptions are suspended.
 	pub fn reserve_subscription(
 		&self,
 		connection_id: ConnectionId,
 	) -> Option<ReservedSubscription<Block, BE>> {
+		if self.suspension.is_suspended() {
+			return None
+		}
+
 		let reserved_token = self.rpc_connections.reserve_space(connection_id)?;
 
 		Some(ReservedSubscription {
@@ -130,12 +145,17 @@ impl<Block: BlockT, BE: Backend<Block>> SubscriptionManagement<Block, BE> {
 	/// Returns
 	/// - Ok(true) if the subscription did not previously contain this block
 	/// - Ok(false) if the subscription already contained this this
-	/// - Error if the backend failed to pin the block or the subscription ID is invalid
+	/// - Error if the backend failed to pin the block, the subscription ID is invalid or the
+	///   subscriptions are suspended
 	pub fn pin_block(
 		&self,
 		sub_id: &str,
 		hash: Block::Hash,
 	) -> Result<bool, SubscriptionManagementError> {
+		if self.suspension.is_suspended() {
+			return Err(SubscriptionManagementError::Custom("Subscriptions are suspended".into()))
+		}
+
 		let mut inner = self.inner.write();
 		inner.pin_block(sub_id, hash)
 	}
@@ -0,0 +1,63 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Implementation of the [`DbApiServer`] trait providing access to the node database.
+
+#[cfg(test)]
+mod tests;
+
+use jsonrpsee::Extensions;
+use sc_client_api::backend::Backend;
+use sc_rpc_api::{check_if_safe, db::error::Error};
+use sp_runtime::traits::Block as BlockT;
+use std::{
+	marker::{PhantomData, Send, Sync},
+	path::Path,
+	sync::Arc,
+};
+
+pub use sc_rpc_api::db::DbApiServer;
+
+/// The database API. All methods are unsafe.
+pub struct Db<Block: BlockT, BE> {
+	backend: Arc<BE>,
+	_phantom: PhantomData<Block>,
+}
+
+impl<Block: BlockT, BE> Db<Block, BE> {
+	/// Create a new database API.
+	pub fn new(backend: Arc<BE>) -> Self {
+		Self { backend, _phantom: PhantomData::default() }
+	}
+}
+
+impl<Block, BE> DbApiServer<Block::Hash> for Db<Block, BE>
+where
+	Block: BlockT + 'static,
+	BE: Backend<Block> + Send + Sync + 'static,
+{
+	fn snapshot(&self, ext: &Extensions, path: String) -> Result<Block::Hash, Error> {
+		check_if_safe(ext)?;
+
+		let (_, finalized_hash) = self
+			.backend
+			.snapshot(Path::new(&path))
+			.map_err(|e| Error::SnapshotFailed(Box::new(e)))?;
+		Ok(finalized_hash)
+	}
+}
@@ -0,0 +1,43 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use super::*;
+use crate::testing::{allow_unsafe, deny_unsafe};
+use assert_matches::assert_matches;
+use substrate_test_runtime_client::{prelude::*, runtime::Block};
+
+#[test]
+fn snapshot_is_unsupported_by_in_memory_database() {
+	let backend = TestClientBuilder::new().backend();
+	let db = Db::<Block, _>::new(backend);
+
+	assert_matches!(db.snapshot(&allow_unsafe(), "snapshot".into()), Err(Error::SnapshotFailed(_)));
+}
+
+#[test]
+fn db_calls_considered_unsafe() {
+	let backend = TestClientBuilder::new().backend();
+	let db = Db::<Block, _>::new(backend);
+
+	assert_matches!(
+		db.snapshot(&deny_unsafe(), "snapshot".into()),
+		Err(Error::UnsafeRpcCalled(e)) => {
+			assert_eq!(e.to_string(), "RPC call is unsafe to be called externally")
+		}
+	);
+}
@@ -30,6 +30,7 @@ pub use sc_rpc_api::DenyUnsafe;
 
 pub mod author;
 pub mod chain;
+pub mod db;
 pub mod dev;
 pub mod mixnet;
 pub mod offchain;
@@ -31,7 +31,10 @@ use sc_tracing::logging;
 use sc_utils::mpsc::TracingUnboundedSender;
 use sp_runtime::traits::{self, Header as HeaderT};
 
-pub use self::helpers::{Health, NodeRole, PeerInfo, SyncState, SystemInfo};
+pub use self::helpers::{
+	BanList, BannedPeer, Health, NodeRole, PeerInfo, PeerReputation, ReputationChange, SyncState,
+	SystemInfo,
+};
 pub use sc_rpc_api::system::*;
 
 /// System API implementation
@@ -59,6 +62,18 @@ pub enum Request<B: traits::Block> {
 	NetworkRemoveReservedPeer(String, oneshot::Sender<error::Result<()>>),
 	/// Must return the list of reserved peers
 	NetworkReservedPeers(oneshot::Sender<Vec<String>>),
+	/// Must return any potential parse error.
+	NetworkBanPeer(String, String, oneshot::Sender<error::Result<()>>),
+	/// Must return any potential parse error.
+	NetworkUnbanPeer(String, oneshot::Sender<error::Result<()>>),
+	/// Must return any potential parse error.
+	NetworkBanAddressRange(String, oneshot::Sender<error::Result<()>>),
+	/// Must return any potential parse error.
+	NetworkUnbanAddressRange(String, oneshot::Sender<error::Result<()>>),
+	/// Must return the banned peers and address ranges.
+	NetworkBanList(oneshot::Sender<BanList>),
+	/// Must return the reputations of known peers.
+	NetworkPeerReputations(oneshot::Sender<Vec<PeerReputation>>),
 	/// Must return the node role.
 	NodeRoles(oneshot::Sender<Vec<NodeRole>>),
 	/// Must return the state of the node syncing.
@@ -164,6 +179,77 @@ impl<B: traits::Block> SystemApiServer<B::Hash, <B::Header as HeaderT>::Number>
 		rx.await.map_err(|e| Error::Internal(e.to_string()))
 	}
 
+	async fn system_ban_peer(
+		&self,
+		ext: &Extensions,
+		peer_id: String,
+		reason: Option<String>,
+	) -> Result<(), Error> {
+		check_if_safe(ext)?;
+		let reason = reason.unwrap_or_else(|| "Banned by the node operator".into());
+		let (tx, rx) = oneshot::channel();
+		let _ = self.send_back.unbounded_send(Request::NetworkBanPeer(peer_id, reason, tx));
+		match rx.await {
+			Ok(Ok(())) => Ok(()),
+			Ok(Err(e)) => Err(e),
+			Err(e) => Err(Error::Internal(e.to_string())),
+		}
+	}
+
+	async fn system_unban_peer(&self, ext: &Extensions, peer_id: String) -> Result<(), Error> {
+		check_if_safe(ext)?;
+		let (tx, rx) = oneshot::channel();
+		let _ = self.send_back.unbounded_send(Request::NetworkUnbanPeer(peer_id, tx));
+		match rx.await {
+			Ok(Ok(())) => Ok(()),
+			Ok(Err(e)) => Err(e),
+			Err(e) => Err(Error::Internal(e.to_string())),
+		}
+	}
+
+	async fn system_ban_address_range(&self, ext: &Extensions, range: String) -> Result<(), Error> {
+		check_if_safe(ext)?;
+		let (tx, rx) = oneshot::channel();
+		let _ = self.send_back.unbounded_send(Request::NetworkBanAddressRange(range, tx));
+		match rx.await {
+			Ok(Ok(())) => Ok(()),
+			Ok(Err(e)) => Err(e),
+			Err(e) => Err(Error::Internal(e.to_string())),
+		}
+	}
+
+	async fn system_unban_address_range(
+		&self,
+		ext: &Extensions,
+		range: String,
+	) -> Result<(), Error> {
+		check_if_safe(ext)?;
+		let (tx, rx) = oneshot::channel();
+		let _ = self.send_back.unbounded_send(Request::NetworkUnbanAddressRange(range, tx));
+		match rx.await {
+			Ok(Ok(())) => Ok(()),
+			Ok(Err(e)) => Err(e),
+			Err(e) => Err(Error::Internal(e.to_string())),
+		}
+	}
+
+	async fn system_ban_list(&self, ext: &Extensions) -> Result<BanList, Error> {
+		check_if_safe(ext)?;
+		let (tx, rx) = oneshot::channel();
+		let _ = self.send_back.unbounded_send(Request::NetworkBanList(tx));
+		rx.await.map_err(|e| Error::Internal(e.to_string()))
+	}
+
+	async fn system_peer_reputations(
+		&self,
+		ext: &Extensions,
+	) -> Result<Vec<PeerReputation>, Error> {
+		check_if_safe(ext)?;
+		let (tx, rx) = oneshot::channel();
+		let _ = self.send_back.unbounded_send(Request::NetworkPeerReputations(tx));
+		rx.await.map_err(|e| Error::Internal(e.to_string()))
+	}
+
 	async fn system_node_roles(&self) -> Result<Vec<NodeRole>, Error> {
 		let (tx, rx) = oneshot::channel();
 		let _ = self.send_back.unbounded_send(Request::NodeRoles(tx));
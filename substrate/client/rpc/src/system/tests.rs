@@ -113,6 +113,43 @@ fn api<T: Into<Option<Status>>>(sync: T) -> RpcModule<System<Block>> {
 					let _ = sender
 						.send(vec!["QmSk5HQbn6LhUwDiNMseVUjuRYhEtYj4aUZ6WfWoGURpdV".to_string()]);
 				},
+				Request::NetworkBanPeer(peer, _reason, sender) |
+				Request::NetworkUnbanPeer(peer, sender) => {
+					let _ = match peer.parse::<PeerId>() {
+						Ok(_) => sender.send(Ok(())),
+						Err(s) =>
+							sender.send(Err(error::Error::MalformattedPeerArg(s.to_string()))),
+					};
+				},
+				Request::NetworkBanAddressRange(range, sender) |
+				Request::NetworkUnbanAddressRange(range, sender) => {
+					let _ = match range.parse::<sc_network::ban_list::IpNetwork>() {
+						Ok(_) => sender.send(Ok(())),
+						Err(e) => sender
+							.send(Err(error::Error::MalformattedAddressRangeArg(e.to_string()))),
+					};
+				},
+				Request::NetworkBanList(sender) => {
+					let _ = sender.send(BanList {
+						peers: vec![BannedPeer {
+							peer_id: "QmSk5HQbn6LhUwDiNMseVUjuRYhEtYj4aUZ6WfWoGURpdV".to_string(),
+							reason: "spam".to_string(),
+						}],
+						address_ranges: vec!["198.51.100.0/24".to_string()],
+					});
+				},
+				Request::NetworkPeerReputations(sender) => {
+					let _ = sender.send(vec![PeerReputation {
+						peer_id: "QmSk5HQbn6LhUwDiNMseVUjuRYhEtYj4aUZ6WfWoGURpdV".to_string(),
+						reputation: -10,
+						is_banned: false,
+						ban_reason: None,
+						recent_changes: vec![ReputationChange {
+							value: -10,
+							reason: "Bad block".to_string(),
+						}],
+					}]);
+				},
 				Request::NodeRoles(sender) => {
 					let _ = sender.send(vec![NodeRole::Authority]);
 				},
@@ -330,6 +367,47 @@ async fn system_network_remove_reserved() {
 		Err(RpcError::JsonRpc(err)) if err.message().contains("base-58 decode error: provided string contained invalid character '/' at byte 0")
 	);
 }
+#[tokio::test]
+async fn system_network_ban_peer() {
+	let _good: () = api(None)
+		.call("system_banPeer", ("QmSk5HQbn6LhUwDiNMseVUjuRYhEtYj4aUZ6WfWoGURpdV", "spam"))
+		.await
+		.expect("call with good peer id works");
+	let _good: () = api(None)
+		.call("system_unbanPeer", ["QmSk5HQbn6LhUwDiNMseVUjuRYhEtYj4aUZ6WfWoGURpdV"])
+		.await
+		.expect("call with good peer id works");
+
+	assert_matches!(
+		api(None).call::<_, ()>("system_banPeer", ["/ip4/198.51.100.19/tcp/30333"]).await,
+		Err(RpcError::JsonRpc(err)) if err.message().contains("base-58 decode error")
+	);
+}
+
+#[tokio::test]
+async fn system_network_ban_address_range() {
+	let _good: () = api(None)
+		.call("system_banAddressRange", ["198.51.100.0/24"])
+		.await
+		.expect("call with good address range works");
+
+	assert_matches!(
+		api(None).call::<_, ()>("system_unbanAddressRange", ["198.51.100.0"]).await,
+		Err(RpcError::JsonRpc(err)) if err.code() == 2003
+	);
+}
+
+#[tokio::test]
+async fn system_network_ban_list_and_reputations() {
+	let ban_list: BanList = api(None).call("system_banList", EmptyParams::new()).await.unwrap();
+	assert_eq!(ban_list.address_ranges, vec!["198.51.100.0/24".to_string()]);
+	assert_eq!(ban_list.peers[0].reason, "spam");
+
+	let reputations: Vec<PeerReputation> =
+		api(None).call("system_peerReputations", EmptyParams::new()).await.unwrap();
+	assert_eq!(reputations[0].recent_changes[0].reason, "Bad block");
+}
+
 #[tokio::test]
 async fn system_network_reserved_peers() {
 	let reserved_peers: Vec<String> =
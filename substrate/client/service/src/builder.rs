@@ -31,7 +31,7 @@ use log::info;
 use prometheus_endpoint::Registry;
 use sc_chain_spec::{get_extension, ChainSpec};
 use sc_client_api::{
-	execution_extensions::ExecutionExtensions, proof_provider::ProofProvider, BadBlocks,
+	execution_extensions::ExecutionExtensions, proof_provider::ProofProvider, AuxStore, BadBlocks,
 	BlockBackend, BlockchainEvents, ExecutorProvider, ForkBlocks, StorageProvider, UsageProvider,
 };
 use sc_client_db::{Backend, BlocksPruning, DatabaseSettings, PruningMode};
@@ -68,6 +68,7 @@ use sc_network_sync::{
 use sc_rpc::{
 	author::AuthorApiServer,
 	chain::ChainApiServer,
+	db::DbApiServer,
 	offchain::OffchainApiServer,
 	state::{ChildStateApiServer, StateApiServer},
 	system::SystemApiServer,
@@ -75,7 +76,7 @@ use sc_rpc::{
 };
 use sc_rpc_spec_v2::{
 	archive::ArchiveApiServer,
-	chain_head::ChainHeadApiServer,
+	chain_head::{ChainHeadApiServer, ChainHeadSuspension},
 	chain_spec::ChainSpecApiServer,
 	transaction::{TransactionApiServer, TransactionBroadcastApiServer},
 };
@@ -513,6 +514,7 @@ where
 	);
 
 	let rpc_id_provider = config.rpc.id_provider.take();
+	let chain_head_suspension = ChainHeadSuspension::default();
 
 	// jsonrpsee RPC
 	let gen_rpc_module = || {
@@ -529,6 +531,7 @@ where
 			config.blocks_pruning,
 			backend.clone(),
 			&*rpc_builder,
+			chain_head_suspension.clone(),
 		)
 	};
 
@@ -556,7 +559,8 @@ where
 		module
 	};
 
-	let in_memory_rpc_handle = RpcHandlers::new(Arc::new(in_memory_rpc), listen_addrs);
+	let in_memory_rpc_handle =
+		RpcHandlers::new(Arc::new(in_memory_rpc), listen_addrs, chain_head_suspension);
 
 	// Spawn informant task
 	spawn_handle.spawn(
@@ -676,6 +680,7 @@ pub fn gen_rpc_module<TBl, TBackend, TCl, TRpc, TExPool>(
 	blocks_pruning: BlocksPruning,
 	backend: Arc<TBackend>,
 	rpc_builder: &(dyn Fn(SubscriptionTaskExecutor) -> Result<RpcModule<TRpc>, Error>),
+	chain_head_suspension: ChainHeadSuspension,
 ) -> Result<RpcModule<()>, Error>
 where
 	TBl: BlockT,
@@ -741,6 +746,7 @@ where
 		// Defaults to sensible limits for the `ChainHead`.
 		sc_rpc_spec_v2::chain_head::ChainHeadConfig::default(),
 	)
+	.with_suspension(chain_head_suspension)
 	.into_rpc();
 
 	// Part of the RPC v2 spec.
@@ -779,6 +785,8 @@ where
 
 	let system = sc_rpc::system::System::new(system_info, system_rpc_tx).into_rpc();
 
+	let db = sc_rpc::db::Db::new(backend.clone()).into_rpc();
+
 	if let Some(storage) = backend.offchain_storage() {
 		let offchain = sc_rpc::offchain::Offchain::new(storage).into_rpc();
 
@@ -797,6 +805,7 @@ where
 	rpc_api.merge(chain).map_err(|e| Error::Application(e.into()))?;
 	rpc_api.merge(author).map_err(|e| Error::Application(e.into()))?;
 	rpc_api.merge(system).map_err(|e| Error::Application(e.into()))?;
+	rpc_api.merge(db).map_err(|e| Error::Application(e.into()))?;
 	rpc_api.merge(state).map_err(|e| Error::Application(e.into()))?;
 	rpc_api.merge(child_state).map_err(|e| Error::Application(e.into()))?;
 	// Additional [`RpcModule`]s defined in the node to fit the specific blockchain
@@ -857,6 +866,7 @@ where
 		+ BlockBackend<Block>
 		+ BlockIdTo<Block, Error = sp_blockchain::Error>
 		+ ProofProvider<Block>
+		+ AuxStore
 		+ HeaderBackend<Block>
 		+ BlockchainEvents<Block>
 		+ 'static,
@@ -1203,6 +1213,7 @@ where
 		+ BlockBackend<Block>
 		+ HeaderMetadata<Block, Error = sp_blockchain::Error>
 		+ ProofProvider<Block>
+		+ AuxStore
 		+ Send
 		+ Sync
 		+ 'static,
@@ -1316,6 +1327,7 @@ where
 		+ BlockBackend<Block>
 		+ HeaderMetadata<Block, Error = sp_blockchain::Error>
 		+ ProofProvider<Block>
+		+ AuxStore
 		+ Send
 		+ Sync
 		+ 'static,
@@ -1376,6 +1388,7 @@ where
 		metrics_registry: metrics_registry.cloned(),
 		state_request_protocol_name,
 		block_downloader,
+		progress_store: Some(client.clone()),
 	};
 	Ok(Box::new(PolkadotSyncingStrategy::new(
 		syncing_config,
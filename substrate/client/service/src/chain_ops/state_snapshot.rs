@@ -0,0 +1,436 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+use crate::error::Error;
+use codec::{Decode, Encode, IoReader as CodecIoReader};
+use futures::{future, prelude::*};
+use futures_timer::Delay;
+use log::{info, warn};
+use parking_lot::Mutex;
+use sc_client_api::{BlockBackend, HeaderBackend, StorageProvider};
+use sc_consensus::{
+	import_queue::{BlockImportError, BlockImportStatus, ImportQueue, IncomingBlock, Link},
+	ImportedState,
+};
+use sc_network_sync::strategy::warp::{
+	AuthorityList, EncodedProof, SetId, VerificationResult, WarpSyncProvider,
+};
+use sp_consensus::BlockOrigin;
+use sp_core::storage::{well_known_keys, ChildInfo};
+use sp_runtime::{
+	traits::{Block as BlockT, Header as HeaderT, NumberFor},
+	Justifications,
+};
+use sp_state_machine::{KeyValueStates, KeyValueStorageLevel};
+use std::{
+	collections::BTreeMap,
+	io::{Read, Write},
+	pin::Pin,
+	sync::Arc,
+	task::Poll,
+	time::Duration,
+};
+
+/// Magic bytes every state snapshot starts with.
+const SNAPSHOT_MAGIC: [u8; 4] = *b"sstt";
+
+/// Version of the state snapshot format.
+const SNAPSHOT_VERSION: u32 = 1;
+
+/// Maximum number of key/value pairs written in a single snapshot entry.
+const SNAPSHOT_CHUNK_SIZE: usize = 4096;
+
+/// Number of milliseconds to wait until next poll of the import queue.
+const DELAY_TIME: u64 = 200;
+
+/// Entry of the key/value stream following the snapshot header.
+#[derive(Encode, Decode)]
+enum SnapshotEntry {
+	/// Key/value pairs of the top trie.
+	Top(Vec<(Vec<u8>, Vec<u8>)>),
+	/// Key/value pairs of the default child trie stored under the given prefixed key.
+	Child(Vec<u8>, Vec<(Vec<u8>, Vec<u8>)>),
+	/// End of the stream.
+	End,
+}
+
+/// A state snapshot read from a file.
+pub struct StateSnapshot<B: BlockT> {
+	/// Header of the block the state belongs to.
+	pub header: B::Header,
+	/// Justifications of the block, if they were stored at export time.
+	pub justifications: Option<Justifications>,
+	/// Encoded warp sync proofs, proving the finality of the block starting from the genesis
+	/// authorities. Empty if the snapshot was exported without a warp sync provider.
+	pub warp_proof: Vec<Vec<u8>>,
+	/// State keys and values.
+	pub state: KeyValueStates,
+}
+
+fn write_entry(output: &mut impl Write, entry: &SnapshotEntry) -> Result<(), Error> {
+	output.write_all(&entry.encode()).map_err(Into::into)
+}
+
+/// Verify the warp sync proof `fragment` against the current authorities, updating them if the
+/// proof is valid. Returns the header of the block if its finality is proved.
+fn verify_warp_proof_fragment<B: BlockT>(
+	warp_sync_provider: &dyn WarpSyncProvider<B>,
+	fragment: &EncodedProof,
+	set_id: &mut SetId,
+	authorities: &mut AuthorityList,
+	last_hash: &mut B::Hash,
+) -> Result<Option<B::Header>, Error> {
+	let result = warp_sync_provider
+		.verify(fragment, *set_id, std::mem::take(authorities))
+		.map_err(|e| Error::Other(format!("Invalid warp sync proof: {e}")))?;
+	match result {
+		VerificationResult::Partial(new_set_id, new_authorities, new_last_hash) => {
+			if new_last_hash == *last_hash {
+				return Err(Error::Other("Warp sync proof doesn't make any progress".into()))
+			}
+			*set_id = new_set_id;
+			*authorities = new_authorities;
+			*last_hash = new_last_hash;
+			Ok(None)
+		},
+		VerificationResult::Complete(_, _, header) => Ok(Some(header)),
+	}
+}
+
+/// Generate the warp sync proofs of the last block with a finality proof, starting from the
+/// genesis block. Returns the encoded proofs along with the header of the block.
+fn generate_warp_proof<B: BlockT>(
+	warp_sync_provider: &dyn WarpSyncProvider<B>,
+	genesis_hash: B::Hash,
+) -> Result<(Vec<Vec<u8>>, B::Header), Error> {
+	let mut set_id = 0;
+	let mut authorities = warp_sync_provider.current_authorities();
+	let mut last_hash = genesis_hash;
+	let mut proofs = Vec::new();
+	loop {
+		// the proofs are limited in size, the next one starts at the last proved block
+		let fragment = warp_sync_provider
+			.generate(last_hash)
+			.map_err(|e| Error::Other(format!("Error generating warp sync proof: {e}")))?;
+		let header = verify_warp_proof_fragment(
+			warp_sync_provider,
+			&fragment,
+			&mut set_id,
+			&mut authorities,
+			&mut last_hash,
+		)?;
+		proofs.push(fragment.0);
+		if let Some(header) = header {
+			return Ok((proofs, header))
+		}
+	}
+}
+
+/// Verify that the warp sync proof of the snapshot proves the finality of its block, starting from
+/// the genesis authorities of `warp_sync_provider`, like a warp sync does.
+pub fn verify_state_snapshot_finality<B: BlockT>(
+	snapshot: &StateSnapshot<B>,
+	genesis_hash: B::Hash,
+	warp_sync_provider: &dyn WarpSyncProvider<B>,
+) -> Result<(), Error> {
+	let mut set_id = 0;
+	let mut authorities = warp_sync_provider.current_authorities();
+	let mut last_hash = genesis_hash;
+	for (index, fragment) in snapshot.warp_proof.iter().enumerate() {
+		let header = verify_warp_proof_fragment(
+			warp_sync_provider,
+			&EncodedProof(fragment.clone()),
+			&mut set_id,
+			&mut authorities,
+			&mut last_hash,
+		)?;
+		match header {
+			Some(header) if index + 1 < snapshot.warp_proof.len() =>
+				return Err(Error::Other(format!(
+					"Warp sync proof of the state snapshot continues after block {:?}",
+					header.hash()
+				))),
+			Some(header) if header.hash() != snapshot.header.hash() =>
+				return Err(Error::Other(format!(
+					"Warp sync proof of the state snapshot proves block {:?}, expected {:?}",
+					header.hash(),
+					snapshot.header.hash(),
+				))),
+			Some(_) => return Ok(()),
+			None => (),
+		}
+	}
+	Err(Error::Other("Warp sync proof of the state snapshot is missing or incomplete".into()))
+}
+
+/// Export the state at a finalized block as a state snapshot.
+///
+/// The snapshot consists of the block header, its justifications and the warp sync proof of its
+/// finality, followed by a stream of the top and child trie key/value pairs. It can be loaded
+/// into another node with [`import_state_snapshot`].
+///
+/// With `warp_sync_provider`, the state is exported at the last block with a finality proof, which
+/// is also the default block to export. Without the provider, the snapshot has no warp sync proof,
+/// so its finality can't be verified on import, and the last finalized block is exported by
+/// default.
+pub fn export_state_snapshot<B, BA, C>(
+	client: Arc<C>,
+	hash: Option<B::Hash>,
+	warp_sync_provider: Option<&dyn WarpSyncProvider<B>>,
+	mut output: impl Write,
+) -> Result<(), Error>
+where
+	C: HeaderBackend<B> + BlockBackend<B> + StorageProvider<B, BA>,
+	B: BlockT,
+	BA: sc_client_api::backend::Backend<B>,
+{
+	let info = client.info();
+	let (warp_proof, header) = match warp_sync_provider {
+		Some(warp_sync_provider) => {
+			let (warp_proof, header) = generate_warp_proof(warp_sync_provider, info.genesis_hash)?;
+			if hash.map_or(false, |hash| hash != header.hash()) {
+				return Err(Error::Other(format!(
+					"Finality of block #{} ({:?}) is proved last, state snapshots can only be \
+					taken at this block",
+					header.number(),
+					header.hash(),
+				)))
+			}
+			(warp_proof, header)
+		},
+		None => {
+			let hash = hash.unwrap_or(info.finalized_hash);
+			let header = client
+				.header(hash)?
+				.ok_or_else(|| Error::Other(format!("Unknown block {hash:?}")))?;
+			warn!("No warp sync provider, the snapshot of block {hash:?} has no finality proof");
+			(Vec::new(), header)
+		},
+	};
+	let hash = header.hash();
+	if *header.number() > info.finalized_number {
+		return Err(Error::Other(format!(
+			"Block #{} is not finalized, state snapshots can only be taken at finalized blocks",
+			header.number()
+		)))
+	}
+
+	let justifications = client.justifications(hash)?;
+	output.write_all(
+		&(SNAPSHOT_MAGIC, SNAPSHOT_VERSION, &header, &justifications, &warp_proof).encode(),
+	)?;
+
+	let mut keys = 0usize;
+	let mut pairs = Vec::new();
+	let mut child_roots = Vec::new();
+	for (key, value) in client.storage_pairs(hash, None, None)? {
+		// Child tries are written after the top trie, their roots are recomputed on import.
+		if key.0.starts_with(well_known_keys::DEFAULT_CHILD_STORAGE_KEY_PREFIX) {
+			child_roots.push(key.0);
+			continue
+		}
+
+		pairs.push((key.0, value.0));
+		if pairs.len() == SNAPSHOT_CHUNK_SIZE {
+			keys += pairs.len();
+			write_entry(&mut output, &SnapshotEntry::Top(std::mem::take(&mut pairs)))?;
+		}
+	}
+	if !pairs.is_empty() {
+		keys += pairs.len();
+		write_entry(&mut output, &SnapshotEntry::Top(std::mem::take(&mut pairs)))?;
+	}
+
+	for prefixed_key in child_roots {
+		let child_info = ChildInfo::new_default(
+			&prefixed_key[well_known_keys::DEFAULT_CHILD_STORAGE_KEY_PREFIX.len()..],
+		);
+		for child_key in client.child_storage_keys(hash, child_info.clone(), None, None)? {
+			if let Some(child_value) = client.child_storage(hash, &child_info, &child_key)? {
+				pairs.push((child_key.0, child_value.0));
+			}
+			if pairs.len() == SNAPSHOT_CHUNK_SIZE {
+				keys += pairs.len();
+				let entry = SnapshotEntry::Child(prefixed_key.clone(), std::mem::take(&mut pairs));
+				write_entry(&mut output, &entry)?;
+			}
+		}
+		if !pairs.is_empty() {
+			keys += pairs.len();
+			let entry = SnapshotEntry::Child(prefixed_key, std::mem::take(&mut pairs));
+			write_entry(&mut output, &entry)?;
+		}
+	}
+
+	write_entry(&mut output, &SnapshotEntry::End)?;
+	output.flush()?;
+
+	info!("Exported {} keys of state at #{} ({hash:?})", keys, header.number());
+	Ok(())
+}
+
+/// Read a state snapshot produced by [`export_state_snapshot`].
+///
+/// Neither the state nor the warp sync proof are verified here. The proof is verified by
+/// [`import_state_snapshot`], while the client checks the state against the state root of the
+/// header when the snapshot is imported.
+pub fn read_state_snapshot<B: BlockT>(input: impl Read) -> Result<StateSnapshot<B>, Error> {
+	let mut reader = CodecIoReader(input);
+	let decode_error =
+		|what: &str, e: codec::Error| Error::Other(format!("Error decoding snapshot {what}: {e}"));
+
+	let (magic, version) =
+		<([u8; 4], u32)>::decode(&mut reader).map_err(|e| decode_error("header", e))?;
+	if magic != SNAPSHOT_MAGIC {
+		return Err(Error::Other("Input is not a state snapshot".into()))
+	}
+	if version != SNAPSHOT_VERSION {
+		return Err(Error::Other(format!("Unsupported state snapshot version {version}")))
+	}
+	let (header, justifications, warp_proof) =
+		<(B::Header, Option<Justifications>, Vec<Vec<u8>>)>::decode(&mut reader)
+			.map_err(|e| decode_error("header", e))?;
+
+	let mut top = Vec::new();
+	let mut children = BTreeMap::<Vec<u8>, Vec<_>>::new();
+	loop {
+		match SnapshotEntry::decode(&mut reader).map_err(|e| decode_error("entry", e))? {
+			SnapshotEntry::Top(mut pairs) => top.append(&mut pairs),
+			SnapshotEntry::Child(prefixed_key, mut pairs) => {
+				if !well_known_keys::is_default_child_storage_key(&prefixed_key) {
+					return Err(Error::Other("Invalid child storage key in snapshot".into()))
+				}
+				children.entry(prefixed_key).or_default().append(&mut pairs);
+			},
+			SnapshotEntry::End => break,
+		}
+	}
+
+	let mut levels = vec![KeyValueStorageLevel {
+		state_root: Vec::new(),
+		parent_storage_keys: Vec::new(),
+		key_values: top,
+	}];
+	levels.extend(children.into_iter().map(|(prefixed_key, key_values)| KeyValueStorageLevel {
+		state_root: Vec::new(),
+		parent_storage_keys: vec![prefixed_key],
+		key_values,
+	}));
+
+	Ok(StateSnapshot { header, justifications, warp_proof, state: KeyValueStates(levels) })
+}
+
+/// Import a state snapshot produced by [`export_state_snapshot`].
+///
+/// The block is passed to the import queue the same way state sync does, so that consensus
+/// engines can pick up their state. The client refuses the import if the state does not match
+/// the state root of the header.
+///
+/// With `warp_sync_provider`, the warp sync proof of the snapshot must prove the finality of its
+/// block, like a warp sync does. Without it, the snapshot block is trusted.
+pub fn import_state_snapshot<B, IQ, C>(
+	client: Arc<C>,
+	mut import_queue: IQ,
+	warp_sync_provider: Option<Arc<dyn WarpSyncProvider<B>>>,
+	input: impl Read + Send + 'static,
+) -> Pin<Box<dyn Future<Output = Result<(), Error>> + Send>>
+where
+	C: HeaderBackend<B> + Send + Sync + 'static,
+	B: BlockT,
+	IQ: ImportQueue<B> + 'static,
+{
+	struct WaitLink {
+		result: Mutex<Option<Result<(), String>>>,
+	}
+
+	impl<B: BlockT> Link<B> for WaitLink {
+		fn blocks_processed(
+			&self,
+			_imported: usize,
+			_num_expected_blocks: usize,
+			results: Vec<(Result<BlockImportStatus<NumberFor<B>>, BlockImportError>, B::Hash)>,
+		) {
+			for (result, hash) in results {
+				*self.result.lock() = Some(result.map(|_| ()).map_err(|e| {
+					format!("There was an error importing state of block {hash:?}: {e}")
+				}));
+			}
+		}
+	}
+
+	let snapshot = match read_state_snapshot::<B>(input) {
+		Ok(snapshot) => snapshot,
+		Err(e) => return future::ready(Err(e)).boxed(),
+	};
+	let info = client.info();
+	match warp_sync_provider {
+		Some(warp_sync_provider) => {
+			let verified =
+				verify_state_snapshot_finality(&snapshot, info.genesis_hash, &*warp_sync_provider);
+			if let Err(e) = verified {
+				return future::ready(Err(e)).boxed()
+			}
+		},
+		None => warn!("Importing state snapshot without verifying its finality"),
+	}
+	let StateSnapshot { header, justifications, warp_proof: _, state } = snapshot;
+	let hash = header.hash();
+	let number = *header.number();
+
+	let finalized_number = info.finalized_number;
+	if finalized_number >= number {
+		return future::ready(Err(Error::Other(format!(
+			"Database is already finalized at #{finalized_number}, not importing state at #{number}"
+		))))
+		.boxed()
+	}
+
+	info!("Importing state of block #{number} ({hash:?})");
+	import_queue.service_ref().import_blocks(
+		BlockOrigin::NetworkInitialSync,
+		vec![IncomingBlock::<B> {
+			hash,
+			header: Some(header),
+			body: None,
+			indexed_body: None,
+			justifications,
+			origin: None,
+			allow_missing_state: true,
+			import_existing: true,
+			skip_execution: true,
+			state: Some(ImportedState { block: hash, state }),
+		}],
+	);
+
+	let link = WaitLink { result: Mutex::new(None) };
+	let mut delay = Delay::new(Duration::from_millis(DELAY_TIME));
+	future::poll_fn(move |cx| loop {
+		import_queue.poll_actions(cx, &link);
+		if let Some(result) = link.result.lock().take() {
+			if result.is_ok() {
+				info!("🎉 Imported state of block #{number}. Best: #{}", client.info().best_number);
+			}
+			return Poll::Ready(result.map_err(Error::Other))
+		}
+
+		match Pin::new(&mut delay).poll(cx) {
+			Poll::Pending => return Poll::Pending,
+			Poll::Ready(()) => delay.reset(Duration::from_millis(DELAY_TIME)),
+		}
+	})
+	.boxed()
+}
@@ -82,7 +82,10 @@ use std::{
 	collections::{HashMap, HashSet},
 	marker::PhantomData,
 	path::PathBuf,
-	sync::Arc,
+	sync::{
+		atomic::{AtomicBool, Ordering},
+		Arc,
+	},
 };
 
 use super::call_executor::LocalCallExecutor;
@@ -111,6 +114,8 @@ where
 	importing_block: RwLock<Option<Block::Hash>>,
 	block_rules: BlockRules<Block>,
 	config: ClientConfig<Block>,
+	// Set while offchain indexing is suspended, e.g. because the node runs low on disk space.
+	offchain_indexing_suspended: AtomicBool,
 	telemetry: Option<TelemetryHandle>,
 	unpin_worker_sender: TracingUnboundedSender<UnpinWorkerMessage<Block>>,
 	code_provider: CodeProvider<Block, B, E>,
@@ -418,6 +423,7 @@ where
 			importing_block: Default::default(),
 			block_rules: BlockRules::new(fork_blocks, bad_blocks),
 			config,
+			offchain_indexing_suspended: AtomicBool::new(false),
 			telemetry,
 			unpin_worker_sender,
 			code_provider,
@@ -425,6 +431,14 @@ where
 		})
 	}
 
+	/// Stop or resume writing the offchain indexing data of imported blocks.
+	///
+	/// Has no effect if the offchain indexing API isn't enabled. Data of the blocks imported while
+	/// suspended is lost.
+	pub fn set_offchain_indexing_suspended(&self, suspended: bool) {
+		self.offchain_indexing_suspended.store(suspended, Ordering::Relaxed);
+	}
+
 	/// returns a reference to the block import notification sinks
 	/// useful for test environments.
 	pub fn import_notification_sinks(&self) -> &NotificationSinks<BlockImportNotification<Block>> {
@@ -600,7 +614,9 @@ where
 						let (main_sc, child_sc, offchain_sc, tx, _, tx_index) =
 							storage_changes.into_inner();
 
-						if self.config.offchain_indexing_api {
+						if self.config.offchain_indexing_api &&
+							!self.offchain_indexing_suspended.load(Ordering::Relaxed)
+						{
 							operation.op.update_offchain_storage(offchain_sc)?;
 						}
 
@@ -33,6 +33,7 @@ pub use sc_network::{
 	},
 	Multiaddr,
 };
+pub use sc_offchain::HttpPolicy as OffchainHttpPolicy;
 pub use sc_rpc_server::{
 	IpNetwork, RpcEndpoint, RpcMethods, SubscriptionIdProvider as RpcSubscriptionIdProvider,
 };
@@ -42,7 +43,7 @@ use sp_core::crypto::SecretString;
 use std::{
 	io, iter,
 	net::SocketAddr,
-	num::NonZeroU32,
+	num::{NonZeroU32, NonZeroUsize},
 	path::{Path, PathBuf},
 };
 use tempfile::TempDir;
@@ -70,6 +71,10 @@ pub struct Configuration {
 	///
 	/// If `None` is given the cache is disabled.
 	pub trie_cache_maximum_size: Option<usize>,
+	/// Number of threads preparing the verification of blocks ahead of their import.
+	///
+	/// If `None` is given blocks are verified sequentially.
+	pub import_verification_threads: Option<NonZeroUsize>,
 	/// State pruning settings.
 	pub state_pruning: Option<PruningMode>,
 	/// Number of blocks to keep in the db.
@@ -154,6 +159,8 @@ pub struct OffchainWorkerConfig {
 	pub enabled: bool,
 	/// allow writes from the runtime to the offchain worker database.
 	pub indexing_enabled: bool,
+	/// Restrictions on the HTTP requests made by offchain workers.
+	pub http_policy: OffchainHttpPolicy,
 }
 
 /// Configuration of the Prometheus endpoint.
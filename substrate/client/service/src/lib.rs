@@ -43,12 +43,13 @@ use jsonrpsee::RpcModule;
 use log::{debug, error, warn};
 use sc_client_api::{blockchain::HeaderBackend, BlockBackend, BlockchainEvents, ProofProvider};
 use sc_network::{
-	config::MultiaddrWithPeerId, service::traits::NetworkService, NetworkBackend, NetworkBlock,
-	NetworkPeers, NetworkStateInfo,
+	ban_list::IpNetwork, config::MultiaddrWithPeerId, service::traits::NetworkService,
+	NetworkBackend, NetworkBanList, NetworkBlock, NetworkPeers, NetworkStateInfo,
 };
 use sc_network_sync::SyncingService;
 use sc_network_types::PeerId;
 use sc_rpc_server::Server;
+use sc_rpc_spec_v2::chain_head::ChainHeadSuspension;
 use sc_utils::mpsc::TracingUnboundedReceiver;
 use sp_blockchain::HeaderMetadata;
 use sp_consensus::SyncOracle;
@@ -114,12 +115,19 @@ pub struct RpcHandlers {
 	// This can be used to introspect the port the RPC server is listening on. SDK consumers are
 	// depending on this and it should be supported even if in-memory query support is removed.
 	listen_addresses: Vec<Multiaddr>,
+
+	// Suspends the `chainHead_follow` subscriptions of all the RPC modules.
+	chain_head_suspension: ChainHeadSuspension,
 }
 
 impl RpcHandlers {
 	/// Create PRC handlers instance.
-	pub fn new(rpc_module: Arc<RpcModule<()>>, listen_addresses: Vec<Multiaddr>) -> Self {
-		Self { rpc_module, listen_addresses }
+	pub fn new(
+		rpc_module: Arc<RpcModule<()>>,
+		listen_addresses: Vec<Multiaddr>,
+		chain_head_suspension: ChainHeadSuspension,
+	) -> Self {
+		Self { rpc_module, listen_addresses, chain_head_suspension }
 	}
 
 	/// Starts an RPC query.
@@ -153,6 +161,13 @@ impl RpcHandlers {
 	pub fn listen_addresses(&self) -> &[Multiaddr] {
 		&self.listen_addresses[..]
 	}
+
+	/// Stop the `chainHead_follow` subscriptions and reject new ones, or accept them again.
+	///
+	/// Stopped subscriptions release the blocks they keep pinned and cached by the backend.
+	pub fn set_chain_head_suspended(&self, suspended: bool) {
+		self.chain_head_suspension.set_suspended(suspended);
+	}
 }
 
 /// An incomplete set of chain components, but enough to run the chain ops subcommands.
@@ -348,6 +363,91 @@ pub async fn build_system_rpc_future<
 				let _ =
 					sender.send(reserved_peers.iter().map(|peer_id| peer_id.to_base58()).collect());
 			},
+			sc_rpc::system::Request::NetworkBanPeer(peer_id, reason, sender) => {
+				let _ = match peer_id.parse::<PeerId>() {
+					Ok(peer_id) => {
+						network_service.ban_peer(peer_id, reason);
+						sender.send(Ok(()))
+					},
+					Err(e) => sender.send(Err(sc_rpc::system::error::Error::MalformattedPeerArg(
+						e.to_string(),
+					))),
+				};
+			},
+			sc_rpc::system::Request::NetworkUnbanPeer(peer_id, sender) => {
+				let _ = match peer_id.parse::<PeerId>() {
+					Ok(peer_id) => {
+						network_service.unban_peer(peer_id);
+						sender.send(Ok(()))
+					},
+					Err(e) => sender.send(Err(sc_rpc::system::error::Error::MalformattedPeerArg(
+						e.to_string(),
+					))),
+				};
+			},
+			sc_rpc::system::Request::NetworkBanAddressRange(range, sender) => {
+				let _ = match range.parse::<IpNetwork>() {
+					Ok(range) => {
+						network_service.ban_address_range(range);
+						sender.send(Ok(()))
+					},
+					Err(e) => sender.send(Err(
+						sc_rpc::system::error::Error::MalformattedAddressRangeArg(e.to_string()),
+					)),
+				};
+			},
+			sc_rpc::system::Request::NetworkUnbanAddressRange(range, sender) => {
+				let _ = match range.parse::<IpNetwork>() {
+					Ok(range) => {
+						network_service.unban_address_range(range);
+						sender.send(Ok(()))
+					},
+					Err(e) => sender.send(Err(
+						sc_rpc::system::error::Error::MalformattedAddressRangeArg(e.to_string()),
+					)),
+				};
+			},
+			sc_rpc::system::Request::NetworkBanList(sender) => {
+				let entries = network_service.banned_entries();
+
+				let _ = sender.send(sc_rpc::system::BanList {
+					peers: entries
+						.peers
+						.into_iter()
+						.map(|(peer_id, reason)| sc_rpc::system::BannedPeer {
+							peer_id: peer_id.to_base58(),
+							reason,
+						})
+						.collect(),
+					address_ranges: entries
+						.address_ranges
+						.iter()
+						.map(|range| range.to_string())
+						.collect(),
+				});
+			},
+			sc_rpc::system::Request::NetworkPeerReputations(sender) => {
+				let reputations = network_service
+					.peer_reputations()
+					.into_iter()
+					.map(|peer| sc_rpc::system::PeerReputation {
+						peer_id: peer.peer_id.to_base58(),
+						reputation: peer.reputation,
+						is_banned: peer.is_banned,
+						ban_reason: peer.ban_reason,
+						recent_changes: peer
+							.recent_changes
+							.into_iter()
+							.map(|change| sc_rpc::system::ReputationChange {
+								value: change.value,
+								reason: change.reason.to_string(),
+							})
+							.collect(),
+					})
+					.collect();
+
+				let _ = sender.send(reputations);
+			},
 			sc_rpc::system::Request::NodeRoles(sender) => {
 				use sc_rpc::system::NodeRole;
 
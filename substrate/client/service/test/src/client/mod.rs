@@ -27,9 +27,16 @@ use sc_client_api::{
 use sc_client_db::{Backend, BlocksPruning, DatabaseSettings, DatabaseSource, PruningMode};
 use sc_consensus::{
 	BlockCheckParams, BlockImport, BlockImportParams, ForkChoiceStrategy, ImportResult,
+	ImportedState, StateAction, StorageChanges,
 };
 use sc_executor::WasmExecutor;
-use sc_service::client::{new_with_backend, Client, LocalCallExecutor};
+use sc_network_sync::strategy::warp::{
+	AuthorityList, EncodedProof, SetId, VerificationResult, WarpSyncProvider,
+};
+use sc_service::{
+	chain_ops::{export_state_snapshot, read_state_snapshot, verify_state_snapshot_finality},
+	client::{new_with_backend, Client, LocalCallExecutor},
+};
 use sp_api::ProvideRuntimeApi;
 use sp_consensus::{BlockOrigin, Error as ConsensusError, SelectChain};
 use sp_core::{testing::TaskExecutor, traits::CallContext, H256};
@@ -38,8 +45,10 @@ use sp_runtime::{
 	traits::{BlakeTwo256, Block as BlockT, Header as HeaderT},
 	ConsensusEngineId, Justifications, StateVersion,
 };
-use sp_state_machine::{backend::Backend as _, InMemoryBackend, OverlayedChanges, StateMachine};
-use sp_storage::{ChildInfo, StorageKey};
+use sp_state_machine::{
+	backend::Backend as _, InMemoryBackend, KeyValueStates, OverlayedChanges, StateMachine,
+};
+use sp_storage::{ChildInfo, StorageData, StorageKey};
 use std::{collections::HashSet, sync::Arc};
 use substrate_test_runtime::TestAPI;
 use substrate_test_runtime_client::{
@@ -1097,6 +1106,126 @@ fn import_with_justification() {
 	assert!(matches!(finality_notifications.try_recv().unwrap_err(), TryRecvError::Empty));
 }
 
+/// Proves the finality of the target block with a proof containing its header.
+struct TestWarpSyncProvider {
+	target: Header,
+}
+
+impl WarpSyncProvider<Block> for TestWarpSyncProvider {
+	fn generate(
+		&self,
+		_start: H256,
+	) -> Result<EncodedProof, Box<dyn std::error::Error + Send + Sync>> {
+		Ok(EncodedProof(self.target.encode()))
+	}
+
+	fn verify(
+		&self,
+		proof: &EncodedProof,
+		set_id: SetId,
+		authorities: AuthorityList,
+	) -> Result<VerificationResult<Block>, Box<dyn std::error::Error + Send + Sync>> {
+		let header = Header::decode(&mut &proof.0[..])?;
+		if header != self.target {
+			return Err("Unexpected block".into())
+		}
+		Ok(VerificationResult::Complete(set_id, authorities, header))
+	}
+
+	fn current_authorities(&self) -> AuthorityList {
+		Vec::new()
+	}
+}
+
+#[test]
+fn state_snapshot_is_imported() {
+	let child_info = ChildInfo::new_default(b"child");
+	let builder = || {
+		TestClientBuilder::new().add_extra_child_storage(
+			&child_info,
+			b"key".to_vec(),
+			vec![1u8; 32],
+		)
+	};
+	let client = Arc::new(builder().build());
+
+	// G -> A1
+	let justification = Justifications::from((TEST_ENGINE_ID, vec![1, 2, 3]));
+	let a1 = BlockBuilderBuilder::new(&*client)
+		.on_parent_block(client.chain_info().genesis_hash)
+		.with_parent_block_number(0)
+		.build()
+		.unwrap()
+		.build()
+		.unwrap()
+		.block;
+	block_on(client.import_justified(BlockOrigin::Own, a1.clone(), justification.clone())).unwrap();
+
+	let warp_sync_provider = TestWarpSyncProvider { target: a1.header.clone() };
+	let mut encoded = Vec::new();
+	export_state_snapshot(client.clone(), None, Some(&warp_sync_provider), &mut encoded).unwrap();
+	// only the block proved by the warp sync proof is exported
+	let genesis_hash = client.chain_info().genesis_hash;
+	assert!(export_state_snapshot(
+		client.clone(),
+		Some(genesis_hash),
+		Some(&warp_sync_provider),
+		Vec::new()
+	)
+	.is_err());
+
+	// the warp sync proof is verified
+	let mut snapshot = read_state_snapshot::<Block>(&encoded[..]).unwrap();
+	assert_eq!(snapshot.warp_proof, vec![a1.header.encode()]);
+	assert!(verify_state_snapshot_finality(&snapshot, genesis_hash, &warp_sync_provider).is_ok());
+	let genesis_header = client.header(genesis_hash).unwrap().unwrap();
+	let other_provider = TestWarpSyncProvider { target: genesis_header };
+	assert!(verify_state_snapshot_finality(&snapshot, genesis_hash, &other_provider).is_err());
+	snapshot.warp_proof.clear();
+	assert!(verify_state_snapshot_finality(&snapshot, genesis_hash, &warp_sync_provider).is_err());
+
+	let import = |state: fn(KeyValueStates) -> KeyValueStates| {
+		let target = builder().build();
+		let snapshot = read_state_snapshot::<Block>(&encoded[..]).unwrap();
+		assert_eq!(snapshot.header, a1.header);
+		assert_eq!(snapshot.justifications, Some(justification.clone()));
+
+		let mut import_params =
+			BlockImportParams::new(BlockOrigin::NetworkInitialSync, snapshot.header);
+		import_params.justifications = snapshot.justifications;
+		import_params.state_action =
+			StateAction::ApplyChanges(StorageChanges::Import(ImportedState {
+				block: a1.hash(),
+				state: state(snapshot.state),
+			}));
+		import_params.finalized = true;
+		import_params.fork_choice = Some(ForkChoiceStrategy::Custom(true));
+		import_params.import_existing = true;
+		block_on(target.import_block(import_params)).map(|_| target)
+	};
+
+	// The snapshot state matches the state root of the header.
+	let target = import(|state| state).unwrap();
+	assert_eq!(target.chain_info().finalized_hash, a1.hash());
+	assert_eq!(
+		target.storage(a1.hash(), &StorageKey(b":code".to_vec())).unwrap(),
+		client.storage(a1.hash(), &StorageKey(b":code".to_vec())).unwrap(),
+	);
+	assert_eq!(
+		target
+			.child_storage(a1.hash(), &child_info, &StorageKey(b"key".to_vec()))
+			.unwrap(),
+		Some(StorageData(vec![1u8; 32])),
+	);
+
+	// A tampered snapshot is rejected.
+	assert!(import(|mut state| {
+		state.0[0].key_values[0].1.push(0);
+		state
+	})
+	.is_err());
+}
+
 #[test]
 fn importing_diverged_finalized_block_should_trigger_reorg() {
 	let client = substrate_test_runtime_client::new();
@@ -46,7 +46,7 @@ mod pruning;
 #[cfg(test)]
 mod test;
 
-use codec::Codec;
+use codec::{Codec, Decode, Encode};
 use log::trace;
 use noncanonical::NonCanonicalOverlay;
 use parking_lot::RwLock;
@@ -62,6 +62,8 @@ const PRUNING_MODE: &[u8] = b"mode";
 const PRUNING_MODE_ARCHIVE: &[u8] = b"archive";
 const PRUNING_MODE_ARCHIVE_CANON: &[u8] = b"archive_canonical";
 const PRUNING_MODE_CONSTRAINED: &[u8] = b"constrained";
+const PRUNING_MODE_ARCHIVE_SPARSE: &[u8] = b"archive_sparse";
+const PRUNING_KEEP_EVERY: &[u8] = b"keep_every";
 pub(crate) const DEFAULT_MAX_BLOCK_CONSTRAINT: u32 = 256;
 
 /// Database value type.
@@ -145,6 +147,8 @@ pub enum StateDbError {
 	BlockUnavailable,
 	/// Block record is missing from the pruning window
 	BlockMissing,
+	/// Sparse archive requires the database with reference counting, it can't count them in memory
+	SparseArchiveUnsupported,
 }
 
 impl<E> From<StateDbError> for Error<E> {
@@ -196,6 +200,10 @@ impl fmt::Debug for StateDbError {
 				write!(f, "Trying to get a block record from db while it is not commit to db yet")
 			},
 			Self::BlockMissing => write!(f, "Block record is missing from the pruning window"),
+			Self::SparseArchiveUnsupported => write!(
+				f,
+				"Sparse archive pruning mode requires the database with reference counting (ParityDb)"
+			),
 		}
 	}
 }
@@ -235,6 +243,14 @@ pub enum PruningMode {
 	ArchiveAll,
 	/// Canonicalization discards non-canonical nodes. All the canonical nodes are kept in the DB.
 	ArchiveCanonical,
+	/// Keep the state of every `keep_every`th canonical block and maintain a pruning window of
+	/// `max_blocks` for all other blocks. Requires the database with reference counting.
+	ArchiveSparse {
+		/// The state of blocks with numbers divisible by `keep_every` is never pruned.
+		keep_every: u32,
+		/// Maximum blocks in the pruning window.
+		max_blocks: u32,
+	},
 }
 
 impl PruningMode {
@@ -243,11 +259,26 @@ impl PruningMode {
 		PruningMode::Constrained(Constraints { max_blocks: Some(n) })
 	}
 
+	/// Create a mode that keeps the state of every `keep_every`th block and of the last
+	/// `max_blocks` blocks.
+	pub fn sparse_archive(keep_every: u32, max_blocks: u32) -> PruningMode {
+		PruningMode::ArchiveSparse { keep_every, max_blocks }
+	}
+
 	/// Is this an archive (either ArchiveAll or ArchiveCanonical) pruning mode?
 	pub fn is_archive(&self) -> bool {
 		match *self {
 			PruningMode::ArchiveAll | PruningMode::ArchiveCanonical => true,
-			PruningMode::Constrained(_) => false,
+			PruningMode::Constrained(_) | PruningMode::ArchiveSparse { .. } => false,
+		}
+	}
+
+	/// Returns the size of the pruning window, if blocks are pruned.
+	pub fn max_blocks(&self) -> Option<u32> {
+		match *self {
+			PruningMode::Constrained(Constraints { max_blocks }) => Some(max_blocks.unwrap_or(0)),
+			PruningMode::ArchiveSparse { max_blocks, .. } => Some(max_blocks),
+			PruningMode::ArchiveAll | PruningMode::ArchiveCanonical => None,
 		}
 	}
 
@@ -257,6 +288,7 @@ impl PruningMode {
 			PruningMode::ArchiveAll => PRUNING_MODE_ARCHIVE,
 			PruningMode::ArchiveCanonical => PRUNING_MODE_ARCHIVE_CANON,
 			PruningMode::Constrained(_) => PRUNING_MODE_CONSTRAINED,
+			PruningMode::ArchiveSparse { .. } => PRUNING_MODE_ARCHIVE_SPARSE,
 		}
 	}
 
@@ -305,6 +337,7 @@ pub struct StateDbSync<BlockHash: Hash, Key: Hash, D: MetaDb> {
 	pruning: Option<RefWindow<BlockHash, Key, D>>,
 	pinned: HashMap<BlockHash, u32>,
 	ref_counting: bool,
+	max_blocks_override: Option<u32>,
 }
 
 impl<BlockHash: Hash, Key: Hash, D: MetaDb> StateDbSync<BlockHash, Key, D> {
@@ -319,10 +352,25 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> StateDbSync<BlockHash, Key, D> {
 		let pruning: Option<RefWindow<BlockHash, Key, D>> = match mode {
 			PruningMode::Constrained(Constraints { max_blocks }) =>
 				Some(RefWindow::new(db, max_blocks.unwrap_or(0), ref_counting)?),
+			PruningMode::ArchiveSparse { keep_every, max_blocks } => {
+				// `ref_counting` is set if the database doesn't count references itself, while
+				// the sparse archive releases the references of the kept nodes selectively
+				if ref_counting {
+					return Err(StateDbError::SparseArchiveUnsupported.into())
+				}
+				Some(RefWindow::new_sparse(db, max_blocks, keep_every)?)
+			},
 			PruningMode::ArchiveAll | PruningMode::ArchiveCanonical => None,
 		};
 
-		Ok(StateDbSync { mode, non_canonical, pruning, pinned: Default::default(), ref_counting })
+		Ok(StateDbSync {
+			mode,
+			non_canonical,
+			pruning,
+			pinned: Default::default(),
+			ref_counting,
+			max_blocks_override: None,
+		})
 	}
 
 	fn insert_block(
@@ -338,7 +386,9 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> StateDbSync<BlockHash, Key, D> {
 				// write changes immediately
 				Ok(CommitSet { data: changeset, meta: Default::default() })
 			},
-			PruningMode::Constrained(_) | PruningMode::ArchiveCanonical => self
+			PruningMode::Constrained(_) |
+			PruningMode::ArchiveCanonical |
+			PruningMode::ArchiveSparse { .. } => self
 				.non_canonical
 				.insert(hash, number, parent_hash, changeset)
 				.map_err(Into::into),
@@ -380,7 +430,9 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> StateDbSync<BlockHash, Key, D> {
 	fn is_pruned(&self, hash: &BlockHash, number: u64) -> IsPruned {
 		match self.mode {
 			PruningMode::ArchiveAll => IsPruned::NotPruned,
-			PruningMode::ArchiveCanonical | PruningMode::Constrained(_) => {
+			PruningMode::ArchiveCanonical |
+			PruningMode::Constrained(_) |
+			PruningMode::ArchiveSparse { .. } => {
 				if self
 					.non_canonical
 					.last_canonicalized_block_number()
@@ -407,12 +459,19 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> StateDbSync<BlockHash, Key, D> {
 		}
 	}
 
+	/// Returns the size of the pruning window, taking the override into account.
+	fn max_blocks(&self) -> Option<u32> {
+		self.mode
+			.max_blocks()
+			.map(|max_blocks| self.max_blocks_override.map_or(max_blocks, |o| max_blocks.min(o)))
+	}
+
 	fn prune(&mut self, commit: &mut CommitSet<Key>) -> Result<(), Error<D::Error>> {
-		if let (&mut Some(ref mut pruning), PruningMode::Constrained(constraints)) =
-			(&mut self.pruning, &self.mode)
+		if let (Some(max_blocks), &mut Some(ref mut pruning)) =
+			(self.max_blocks(), &mut self.pruning)
 		{
 			loop {
-				if pruning.window_size() <= constraints.max_blocks.unwrap_or(0) as u64 {
+				if pruning.window_size() <= max_blocks as u64 {
 					break
 				}
 
@@ -442,16 +501,18 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> StateDbSync<BlockHash, Key, D> {
 	fn revert_one(&mut self) -> Option<CommitSet<Key>> {
 		match self.mode {
 			PruningMode::ArchiveAll => Some(CommitSet::default()),
-			PruningMode::ArchiveCanonical | PruningMode::Constrained(_) =>
-				self.non_canonical.revert_one(),
+			PruningMode::ArchiveCanonical |
+			PruningMode::Constrained(_) |
+			PruningMode::ArchiveSparse { .. } => self.non_canonical.revert_one(),
 		}
 	}
 
 	fn remove(&mut self, hash: &BlockHash) -> Option<CommitSet<Key>> {
 		match self.mode {
 			PruningMode::ArchiveAll => Some(CommitSet::default()),
-			PruningMode::ArchiveCanonical | PruningMode::Constrained(_) =>
-				self.non_canonical.remove(hash),
+			PruningMode::ArchiveCanonical |
+			PruningMode::Constrained(_) |
+			PruningMode::ArchiveSparse { .. } => self.non_canonical.remove(hash),
 		}
 	}
 
@@ -461,7 +522,9 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> StateDbSync<BlockHash, Key, D> {
 	{
 		match self.mode {
 			PruningMode::ArchiveAll => Ok(()),
-			PruningMode::ArchiveCanonical | PruningMode::Constrained(_) => {
+			PruningMode::ArchiveCanonical |
+			PruningMode::Constrained(_) |
+			PruningMode::ArchiveSparse { .. } => {
 				let have_block = self.non_canonical.have_block(hash) ||
 					self.pruning.as_ref().map_or_else(
 						|| hint(),
@@ -531,6 +594,9 @@ pub struct StateDb<BlockHash: Hash, Key: Hash, D: MetaDb> {
 
 impl<BlockHash: Hash, Key: Hash, D: MetaDb> StateDb<BlockHash, Key, D> {
 	/// Create an instance of [`StateDb`].
+	///
+	/// `ref_counting` must be set if the database doesn't count references to the nodes itself, so
+	/// the references are counted in memory.
 	pub fn open(
 		db: D,
 		requested_mode: Option<PruningMode>,
@@ -563,6 +629,11 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> StateDb<BlockHash, Key, D> {
 			let value = selected_mode.id().to_owned();
 
 			cs.meta.inserted.push((key, value));
+			if let PruningMode::ArchiveSparse { keep_every, .. } = selected_mode {
+				cs.meta
+					.inserted
+					.push((to_meta_key(PRUNING_KEEP_EVERY, &()), keep_every.encode()));
+			}
 
 			cs
 		} else {
@@ -579,6 +650,15 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> StateDb<BlockHash, Key, D> {
 		self.db.read().mode.clone()
 	}
 
+	/// Temporarily shrink the pruning window to at most `max_blocks` blocks, or restore the
+	/// window of the pruning mode if `None` is given.
+	///
+	/// The override isn't persisted and only applies to pruning modes with a window. Blocks
+	/// falling out of the smaller window are pruned when the next block is canonicalized.
+	pub fn set_max_blocks_override(&self, max_blocks: Option<u32>) {
+		self.db.write().max_blocks_override = max_blocks;
+	}
+
 	/// Add a new non-canonical block.
 	pub fn insert_block(
 		&self,
@@ -655,11 +735,31 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> StateDb<BlockHash, Key, D> {
 	/// Reset in-memory changes to the last disk-backed state.
 	pub fn reset(&self, db: D) -> Result<(), Error<D::Error>> {
 		let mut state_db = self.db.write();
+		let max_blocks_override = state_db.max_blocks_override;
 		*state_db = StateDbSync::new(state_db.mode.clone(), state_db.ref_counting, db)?;
+		state_db.max_blocks_override = max_blocks_override;
 		Ok(())
 	}
 }
 
+/// Converts the journals of the database that doesn't support reference counting, so they may be
+/// used with the database that does.
+///
+/// The database without reference counting stores each node once, while the reference counting
+/// database keeps a reference per insertion. Deletions of nodes, re-inserted by the later blocks of
+/// the pruning window, are dropped from the journals, like the memory queue does on load. Node keys
+/// are mapped with `convert_key`. Returns the records that should replace the journals in the
+/// converted database.
+pub fn convert_journals_to_ref_counted<BlockHash: Hash, Key: Hash, D: MetaDb>(
+	db: &D,
+	convert_key: impl Fn(Key) -> Key,
+) -> Result<Vec<(Vec<u8>, DBValue)>, Error<D::Error>> {
+	let mut records = Vec::new();
+	noncanonical::convert_journals::<BlockHash, Key, D>(db, &convert_key, &mut records)?;
+	pruning::convert_journals::<BlockHash, Key, D>(db, &convert_key, &mut records)?;
+	Ok(records)
+}
+
 /// The result return by `StateDb::is_pruned`
 #[derive(Debug, PartialEq, Eq)]
 pub enum IsPruned {
@@ -674,7 +774,18 @@ pub enum IsPruned {
 fn fetch_stored_pruning_mode<D: MetaDb>(db: &D) -> Result<Option<PruningMode>, Error<D::Error>> {
 	let meta_key_mode = to_meta_key(PRUNING_MODE, &());
 	if let Some(stored_mode) = db.get_meta(&meta_key_mode).map_err(Error::Db)? {
-		if let Some(mode) = PruningMode::from_id(&stored_mode) {
+		if stored_mode == PRUNING_MODE_ARCHIVE_SPARSE {
+			let meta_key_keep_every = to_meta_key(PRUNING_KEEP_EVERY, &());
+			let keep_every = match db.get_meta(&meta_key_keep_every).map_err(Error::Db)? {
+				Some(keep_every) => u32::decode(&mut keep_every.as_slice())?,
+				None =>
+					return Err(StateDbError::Metadata(
+						"Sparse archive does not have KEEP_EVERY stored in its meta-data".into(),
+					)
+					.into()),
+			};
+			Ok(Some(PruningMode::sparse_archive(keep_every, DEFAULT_MAX_BLOCK_CONSTRAINT)))
+		} else if let Some(mode) = PruningMode::from_id(&stored_mode) {
 			Ok(Some(mode))
 		} else {
 			Err(StateDbError::Metadata(format!(
@@ -698,6 +809,11 @@ fn choose_pruning_mode(
 			Ok(PruningMode::ArchiveCanonical),
 		(PruningMode::Constrained(_), PruningMode::Constrained(requested)) =>
 			Ok(PruningMode::Constrained(requested)),
+		// the period can't be changed, because it defines which nodes are kept
+		(
+			PruningMode::ArchiveSparse { keep_every: stored_keep_every, .. },
+			PruningMode::ArchiveSparse { keep_every, max_blocks },
+		) if stored_keep_every == keep_every => Ok(PruningMode::ArchiveSparse { keep_every, max_blocks }),
 		(stored, requested) => Err(StateDbError::IncompatiblePruningModes { requested, stored }),
 	}
 }
@@ -705,8 +821,9 @@ fn choose_pruning_mode(
 #[cfg(test)]
 mod tests {
 	use crate::{
+		convert_journals_to_ref_counted,
 		test::{make_changeset, make_db, TestDb},
-		Constraints, Error, IsPruned, PruningMode, StateDb, StateDbError,
+		ChangeSet, CommitSet, Constraints, Error, IsPruned, PruningMode, StateDb, StateDbError,
 	};
 	use sp_core::H256;
 
@@ -847,6 +964,127 @@ mod tests {
 		assert!(db.data_eq(&make_db(&[1, 21, 3, 921, 922, 93, 94])));
 	}
 
+	#[test]
+	fn max_blocks_override_shrinks_pruning_window() {
+		let (mut db, sdb) =
+			make_test_db(PruningMode::Constrained(Constraints { max_blocks: Some(2) }));
+		sdb.set_max_blocks_override(Some(1));
+		db.commit(&sdb.canonicalize_block(&H256::from_low_u64_be(4)).unwrap());
+		assert_eq!(sdb.is_pruned(&H256::from_low_u64_be(21), 2), IsPruned::Pruned);
+		assert_eq!(sdb.is_pruned(&H256::from_low_u64_be(3), 3), IsPruned::Pruned);
+		assert_eq!(sdb.is_pruned(&H256::from_low_u64_be(4), 4), IsPruned::NotPruned);
+
+		// the window of the pruning mode applies again once the override is lifted
+		sdb.set_max_blocks_override(None);
+		db.commit(
+			&sdb.insert_block(
+				&H256::from_low_u64_be(5),
+				5,
+				&H256::from_low_u64_be(4),
+				make_changeset(&[5], &[]),
+			)
+			.unwrap(),
+		);
+		db.commit(&sdb.canonicalize_block(&H256::from_low_u64_be(5)).unwrap());
+		assert_eq!(sdb.is_pruned(&H256::from_low_u64_be(4), 4), IsPruned::NotPruned);
+		assert_eq!(sdb.is_pruned(&H256::from_low_u64_be(5), 5), IsPruned::NotPruned);
+	}
+
+	#[test]
+	fn sparse_archive_keeps_every_nth_block() {
+		let mode = PruningMode::sparse_archive(2, 0);
+		let mut db = make_db(&[91]);
+		let (state_db_init, mut state_db) =
+			StateDb::open(db.clone(), Some(mode.clone()), false, true).unwrap();
+		db.commit(&state_db_init);
+
+		// every block replaces the node, inserted by its parent
+		let changes = [(1, 91), (2, 1), (3, 2), (4, 3)];
+		for (number, (inserted, deleted)) in (1u64..).zip(changes) {
+			if number == 4 {
+				// insertions since the last kept block are restored after restart
+				state_db = StateDb::open(db.clone(), Some(mode.clone()), false, false).unwrap().1;
+			}
+			let hash = H256::from_low_u64_be(number);
+			let parent_hash = H256::from_low_u64_be(number - 1);
+			db.commit(
+				&state_db
+					.insert_block(
+						&hash,
+						number,
+						&parent_hash,
+						make_changeset(&[inserted], &[deleted]),
+					)
+					.unwrap(),
+			);
+			db.commit(&state_db.canonicalize_block(&hash).unwrap());
+		}
+
+		// only nodes of kept blocks 0, 2 and 4 are left
+		assert!(db.data_eq(&make_db(&[91, 2, 4])));
+		assert_eq!(state_db.is_pruned(&H256::from_low_u64_be(1), 1), IsPruned::Pruned);
+		assert_eq!(state_db.is_pruned(&H256::from_low_u64_be(2), 2), IsPruned::MaybePruned);
+		assert_eq!(state_db.is_pruned(&H256::from_low_u64_be(3), 3), IsPruned::Pruned);
+		// journals of blocks 1..=4 are removed
+		assert_eq!(db.meta_len(), 4);
+
+		// the period is restored from the database
+		let state_db: StateDb<H256, H256, TestDb> =
+			StateDb::open(db.clone(), None, false, false).unwrap().1;
+		assert_eq!(state_db.pruning_mode(), PruningMode::sparse_archive(2, 256));
+		// the period can't be changed
+		assert!(StateDb::<H256, H256, TestDb>::open(
+			db.clone(),
+			Some(PruningMode::sparse_archive(3, 0)),
+			false,
+			false
+		)
+		.is_err());
+		// databases without reference counting are not supported
+		assert!(StateDb::<H256, H256, TestDb>::open(make_db(&[]), Some(mode), true, true).is_err());
+	}
+
+	#[test]
+	fn journals_are_converted_to_ref_counted() {
+		let mode = PruningMode::blocks_pruning(2);
+		let mut db = make_db(&[91]);
+		let (state_db_init, state_db) =
+			StateDb::open(db.clone(), Some(mode.clone()), true, true).unwrap();
+		db.commit(&state_db_init);
+
+		// block 1 deletes the node, block 2 inserts it back and block 3 is not canonicalized
+		let changes = [(1, vec![1], vec![91]), (2, vec![91], vec![]), (3, vec![3], vec![1])];
+		for (number, inserted, deleted) in changes {
+			let hash = H256::from_low_u64_be(number);
+			db.commit(
+				&state_db
+					.insert_block(
+						&hash,
+						number,
+						&H256::from_low_u64_be(number - 1),
+						make_changeset(&inserted, &deleted),
+					)
+					.unwrap(),
+			);
+			if number < 3 {
+				db.commit(&state_db.canonicalize_block(&hash).unwrap());
+			}
+		}
+
+		let records = convert_journals_to_ref_counted::<H256, H256, _>(&db, |key| key).unwrap();
+		db.commit(&CommitSet {
+			data: ChangeSet::default(),
+			meta: ChangeSet { inserted: records, deleted: Vec::new() },
+		});
+
+		// the journal of block 1 doesn't delete the re-inserted node anymore
+		let state_db: StateDb<H256, H256, TestDb> =
+			StateDb::open(db.clone(), Some(mode), false, false).unwrap().1;
+		db.commit(&state_db.canonicalize_block(&H256::from_low_u64_be(3)).unwrap());
+		assert_eq!(state_db.is_pruned(&H256::from_low_u64_be(1), 1), IsPruned::Pruned);
+		assert!(db.data_eq(&make_db(&[91, 1, 3])));
+	}
+
 	#[test]
 	fn detects_incompatible_mode() {
 		let mut db = make_db(&[]);
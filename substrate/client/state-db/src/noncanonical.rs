@@ -81,6 +81,45 @@ fn to_journal_key(block: u64, index: u64) -> Vec<u8> {
 	to_meta_key(NON_CANONICAL_JOURNAL, &(block, index))
 }
 
+/// Re-encodes the journal records with the node keys mapped by `convert_key`.
+pub(crate) fn convert_journals<BlockHash: Hash, Key: Hash, D: MetaDb>(
+	db: &D,
+	convert_key: &impl Fn(Key) -> Key,
+	records: &mut Vec<(Vec<u8>, DBValue)>,
+) -> Result<(), Error<D::Error>> {
+	let last_canonicalized = db.get_meta(&to_meta_key(LAST_CANONICAL, &())).map_err(Error::Db)?;
+	let mut block = match last_canonicalized {
+		Some(buffer) => <(BlockHash, u64)>::decode(&mut buffer.as_slice())?.1 + 1,
+		None => return Ok(()),
+	};
+	loop {
+		let mut found = false;
+		for index in 0..MAX_BLOCKS_PER_LEVEL {
+			let journal_key = to_journal_key(block, index);
+			if let Some(record) = db.get_meta(&journal_key).map_err(Error::Db)? {
+				let record: JournalRecord<BlockHash, Key> = Decode::decode(&mut record.as_slice())?;
+				let record = JournalRecord {
+					hash: record.hash,
+					parent_hash: record.parent_hash,
+					inserted: record
+						.inserted
+						.into_iter()
+						.map(|(key, value)| (convert_key(key), value))
+						.collect(),
+					deleted: record.deleted.into_iter().map(convert_key).collect(),
+				};
+				records.push((journal_key, record.encode()));
+				found = true;
+			}
+		}
+		if !found {
+			break
+		}
+		block += 1;
+	}
+	Ok(())
+}
+
 #[cfg_attr(test, derive(PartialEq, Debug))]
 struct BlockOverlay<BlockHash: Hash, Key: Hash> {
 	hash: BlockHash,
@@ -23,10 +23,14 @@
 //! If a node is re-inserted into the window it gets removed from
 //! the death list.
 //! The changes are journaled in the DB.
+//!
+//! In the sparse archive mode the state of every `keep_every`th block is never pruned. Deleted
+//! nodes are only removed from the database if they have been inserted after the last kept
+//! block, so journals of pruned blocks are kept until the next kept block is pruned.
 
 use crate::{
-	noncanonical::LAST_CANONICAL, to_meta_key, CommitSet, Error, Hash, MetaDb, StateDbError,
-	DEFAULT_MAX_BLOCK_CONSTRAINT, LOG_TARGET,
+	noncanonical::LAST_CANONICAL, to_meta_key, CommitSet, DBValue, Error, Hash, MetaDb,
+	StateDbError, DEFAULT_MAX_BLOCK_CONSTRAINT, LOG_TARGET,
 };
 use codec::{Decode, Encode};
 use log::trace;
@@ -42,6 +46,65 @@ pub struct RefWindow<BlockHash: Hash, Key: Hash, D: MetaDb> {
 	queue: DeathRowQueue<BlockHash, Key, D>,
 	/// Block number that is next to be pruned.
 	base: u64,
+	/// Sparse archive state, if the state of every `keep_every`th block is kept.
+	sparse: Option<SparseArchive<Key>>,
+}
+
+/// Nodes inserted by pruned blocks since the last block, which state is kept by the sparse
+/// archive. All other nodes are part of the kept state and are never deleted.
+struct SparseArchive<Key: Hash> {
+	/// The state of every `keep_every`th block is kept.
+	keep_every: u64,
+	/// Number of insertions of every node since the last kept block.
+	inserted: HashMap<Key, u32>,
+}
+
+impl<Key: Hash> SparseArchive<Key> {
+	/// Returns true if the state of the block with given number is kept.
+	fn is_kept(&self, number: u64) -> bool {
+		number % self.keep_every == 0
+	}
+
+	/// Note insertions of the pruned block.
+	fn note_inserted(&mut self, inserted: impl IntoIterator<Item = Key>) {
+		for key in inserted {
+			*self.inserted.entry(key).or_default() += 1;
+		}
+	}
+
+	/// Returns true if the node has been inserted since the last kept block and may be deleted.
+	fn forget(&mut self, key: &Key) -> bool {
+		match self.inserted.get_mut(key) {
+			Some(count) if *count > 1 => {
+				*count -= 1;
+				true
+			},
+			Some(_) => {
+				self.inserted.remove(key);
+				true
+			},
+			None => false,
+		}
+	}
+
+	/// Prune the block. Adds changes to `commit`.
+	fn prune(
+		&mut self,
+		number: u64,
+		pruned: DeathRow<impl Hash, Key>,
+		commit: &mut CommitSet<Key>,
+	) {
+		let DeathRow { deleted, inserted, .. } = pruned;
+		commit.data.deleted.extend(deleted.into_iter().filter(|key| self.forget(key)));
+		if self.is_kept(number) {
+			// the new segment starts, journals of the previous one are not needed anymore
+			self.inserted.clear();
+			let first = number.saturating_sub(self.keep_every - 1);
+			commit.meta.deleted.extend((first..=number).map(to_journal_key));
+		} else {
+			self.note_inserted(inserted);
+		}
+	}
 }
 
 /// `DeathRowQueue` used to keep track of blocks in the pruning window, there are two flavors:
@@ -136,7 +199,11 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> DeathRowQueue<BlockHash, Key, D> {
 				// cache.
 				if num == base + cache.len() as u64 && cache.len() < *cache_capacity {
 					trace!(target: LOG_TARGET, "Adding to DB backed cache {:?} (#{})", hash, num);
-					cache.push_back(DeathRow { hash, deleted: deleted.into_iter().collect() });
+					cache.push_back(DeathRow {
+						hash,
+						deleted: deleted.into_iter().collect(),
+						inserted,
+					});
 				}
 				*last = Some(num);
 			},
@@ -152,7 +219,11 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> DeathRowQueue<BlockHash, Key, D> {
 				for k in deleted.iter() {
 					death_index.insert(k.clone(), imported_block);
 				}
-				death_rows.push_back(DeathRow { hash, deleted: deleted.into_iter().collect() });
+				death_rows.push_back(DeathRow {
+					hash,
+					deleted: deleted.into_iter().collect(),
+					inserted: Vec::new(),
+				});
 			},
 		}
 	}
@@ -255,8 +326,8 @@ fn load_death_row_from_db<BlockHash: Hash, Key: Hash, D: MetaDb>(
 	let journal_key = to_journal_key(block);
 	match db.get_meta(&journal_key).map_err(Error::Db)? {
 		Some(record) => {
-			let JournalRecord { hash, deleted, .. } = Decode::decode(&mut record.as_slice())?;
-			Ok(Some(DeathRow { hash, deleted: deleted.into_iter().collect() }))
+			let JournalRecord { hash, inserted, deleted } = Decode::decode(&mut record.as_slice())?;
+			Ok(Some(DeathRow { hash, deleted: deleted.into_iter().collect(), inserted }))
 		},
 		None => Ok(None),
 	}
@@ -266,6 +337,9 @@ fn load_death_row_from_db<BlockHash: Hash, Key: Hash, D: MetaDb>(
 struct DeathRow<BlockHash: Hash, Key: Hash> {
 	hash: BlockHash,
 	deleted: HashSet<Key>,
+	/// Keys inserted by the block. Only tracked by the database-backed queue of the sparse
+	/// archive.
+	inserted: Vec<Key>,
 }
 
 #[derive(Encode, Decode, Default)]
@@ -279,6 +353,34 @@ fn to_journal_key(block: u64) -> Vec<u8> {
 	to_meta_key(PRUNING_JOURNAL, &block)
 }
 
+/// Reads the pruning journals written by the memory queue and re-encodes them for the
+/// database-backed queue, with the node keys mapped by `convert_key`.
+pub(crate) fn convert_journals<BlockHash: Hash, Key: Hash, D: MetaDb>(
+	db: &D,
+	convert_key: &impl Fn(Key) -> Key,
+	records: &mut Vec<(Vec<u8>, DBValue)>,
+) -> Result<(), Error<D::Error>> {
+	let base = match db.get_meta(&to_meta_key(LAST_PRUNED, &())).map_err(Error::Db)? {
+		Some(buffer) => u64::decode(&mut buffer.as_slice())? + 1,
+		None => 0,
+	};
+	// the memory queue drops deletions of the re-inserted keys on import, the database-backed
+	// queue expects them to be dropped from the journal
+	if let DeathRowQueue::Mem { death_rows, .. } =
+		DeathRowQueue::<BlockHash, Key, D>::new_mem(db, base)?
+	{
+		for (number, row) in (base..).zip(death_rows) {
+			let record = JournalRecord {
+				hash: row.hash,
+				inserted: Vec::new(),
+				deleted: row.deleted.into_iter().map(convert_key).collect::<Vec<_>>(),
+			};
+			records.push((to_journal_key(number), record.encode()));
+		}
+	}
+	Ok(())
+}
+
 /// The result return by `RefWindow::have_block`
 #[derive(Debug, PartialEq, Eq)]
 pub enum HaveBlock {
@@ -347,7 +449,38 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> RefWindow<BlockHash, Key, D> {
 			DeathRowQueue::new_db_backed(db, base, last, window_size)?
 		};
 
-		Ok(RefWindow { queue, base })
+		Ok(RefWindow { queue, base, sparse: None })
+	}
+
+	/// Create the pruning window of the sparse archive, which keeps the state of every
+	/// `keep_every`th block. Requires the database with reference counting.
+	pub fn new_sparse(
+		db: D,
+		window_size: u32,
+		keep_every: u32,
+	) -> Result<RefWindow<BlockHash, Key, D>, Error<D::Error>> {
+		let mut window = Self::new(db, window_size, false)?;
+		let mut sparse =
+			SparseArchive { keep_every: keep_every.max(1) as u64, inserted: Default::default() };
+
+		// replay the blocks that have been pruned since the last kept block
+		if let DeathRowQueue::DbBacked { ref db, .. } = window.queue {
+			let last_pruned = window.base.checked_sub(1);
+			if let Some(last_pruned) = last_pruned.filter(|number| !sparse.is_kept(*number)) {
+				let first = last_pruned - last_pruned % sparse.keep_every + 1;
+				for number in first..=last_pruned {
+					if let Some(row) = load_death_row_from_db::<BlockHash, Key, D>(db, number)? {
+						for key in row.deleted.iter() {
+							sparse.forget(key);
+						}
+						sparse.note_inserted(row.inserted);
+					}
+				}
+			}
+		}
+
+		window.sparse = Some(sparse);
+		Ok(window)
 	}
 
 	pub fn window_size(&self) -> u64 {
@@ -374,6 +507,10 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> RefWindow<BlockHash, Key, D> {
 
 	// Check if a block is in the pruning window and not be pruned yet
 	pub fn have_block(&self, hash: &BlockHash, number: u64) -> HaveBlock {
+		// the sparse archive keeps the state of pruned block if it is canonical
+		if number < self.base && self.sparse.as_ref().map_or(false, |s| s.is_kept(number)) {
+			return HaveBlock::Maybe
+		}
 		// if the queue is empty or the block number exceed the pruning window, we definitely
 		// do not have this block
 		if self.is_empty() || number < self.base || number >= self.base + self.window_size() {
@@ -387,9 +524,14 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> RefWindow<BlockHash, Key, D> {
 		if let Some(pruned) = self.queue.pop_front(self.base)? {
 			trace!(target: LOG_TARGET, "Pruning {:?} ({} deleted)", pruned.hash, pruned.deleted.len());
 			let index = self.base;
-			commit.data.deleted.extend(pruned.deleted.into_iter());
+			match self.sparse {
+				Some(ref mut sparse) => sparse.prune(index, pruned, commit),
+				None => {
+					commit.data.deleted.extend(pruned.deleted.into_iter());
+					commit.meta.deleted.push(to_journal_key(self.base));
+				},
+			}
 			commit.meta.inserted.push((to_meta_key(LAST_PRUNED, &()), index.encode()));
-			commit.meta.deleted.push(to_journal_key(self.base));
 			self.base += 1;
 			Ok(())
 		} else {
@@ -424,7 +566,7 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> RefWindow<BlockHash, Key, D> {
 			commit.data.inserted.len(),
 			commit.data.deleted.len(),
 		);
-		let inserted = if matches!(self.queue, DeathRowQueue::Mem { .. }) {
+		let inserted = if matches!(self.queue, DeathRowQueue::Mem { .. }) || self.sparse.is_some() {
 			commit.data.inserted.iter().map(|(k, _)| k.clone()).collect()
 		} else {
 			Default::default()
@@ -907,4 +1049,35 @@ mod tests {
 			assert_eq!(HaveBlock::Yes, pruning.have_block(&block, block));
 		}
 	}
+
+	#[test]
+	fn sparse_archive_replays_pruned_blocks_after_restart() {
+		fn prune(
+			pruning: &mut RefWindow<u64, H256, TestDb>,
+			db: &mut TestDb,
+			number: u64,
+			inserted: &[u64],
+			deleted: &[u64],
+		) -> Vec<H256> {
+			let mut commit = make_commit(inserted, deleted);
+			pruning.note_canonical(&number, number, &mut commit).unwrap();
+			push_last_canonicalized(number, &mut commit);
+			pruning.prune_one(&mut commit).unwrap();
+			db.commit(&commit);
+			commit.data.deleted
+		}
+
+		let mut db = make_db(&[91]);
+		let mut pruning = RefWindow::new_sparse(db.clone(), 0, 4).unwrap();
+
+		// node 91 of the kept state of block 0 gets a second reference from block 1, which is
+		// released by block 2
+		assert!(prune(&mut pruning, &mut db, 0, &[], &[]).is_empty());
+		assert!(prune(&mut pruning, &mut db, 1, &[91], &[]).is_empty());
+		assert_eq!(prune(&mut pruning, &mut db, 2, &[], &[91]), vec![H256::from_low_u64_be(91)]);
+
+		// the reference of the kept state is not released after restart
+		let mut pruning = RefWindow::new_sparse(db.clone(), 0, 4).unwrap();
+		assert!(prune(&mut pruning, &mut db, 3, &[], &[91]).is_empty());
+	}
 }
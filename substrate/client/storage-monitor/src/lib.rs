@@ -16,10 +16,23 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
-use clap::Args;
+//! Monitors the space available for the database and degrades the node when it runs low.
+//!
+//! The available space is compared against three thresholds, from the highest to the lowest:
+//! - below the warning threshold, alerts are emitted through logs, metrics and telemetry;
+//! - below the degradation threshold, the configured [`DegradationAction`]s are taken to slow down
+//!   the growth of the database;
+//! - below the shutdown threshold, the node is terminated.
+//!
+//! A level is only left once the available space exceeds its threshold by 10%, so that the node
+//! doesn't flap between levels.
+
+use clap::{Args, ValueEnum};
+use prometheus_endpoint::{register, Gauge, PrometheusError, Registry, U64};
+use sc_telemetry::{telemetry, TelemetryHandle, SUBSTRATE_INFO};
 use sp_core::traits::SpawnEssentialNamed;
 use std::{
-	io,
+	fmt, io,
 	path::{Path, PathBuf},
 	time::Duration,
 };
@@ -36,6 +49,30 @@ pub enum Error {
 	IOError(#[from] io::Error),
 	#[error("Out of storage space: available {0}MiB, required {1}MiB")]
 	StorageOutOfSpace(u64, u64),
+	#[error("Degradation threshold {0}MiB must be greater than the threshold {1}MiB")]
+	InvalidDegradationThreshold(u64, u64),
+}
+
+/// Action taken while the available space is below the degradation threshold.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum DegradationAction {
+	/// Shrink the state and blocks pruning windows to `--db-storage-degraded-pruning` blocks.
+	AggressivePruning,
+	/// Stop writing offchain indexing data of imported blocks.
+	DisableOffchainIndexing,
+	/// Stop the `chainHead_follow` RPC subscriptions and reject new ones, releasing the blocks
+	/// they keep pinned and cached by the database.
+	DisableRpcCaches,
+}
+
+impl fmt::Display for DegradationAction {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			Self::AggressivePruning => write!(f, "aggressive-pruning"),
+			Self::DisableOffchainIndexing => write!(f, "disable-offchain-indexing"),
+			Self::DisableRpcCaches => write!(f, "disable-rpc-caches"),
+		}
+	}
 }
 
 /// Parameters used to create the storage monitor.
@@ -46,13 +83,142 @@ pub struct StorageMonitorParams {
 	/// If available space for DB storage drops below the given threshold, node will
 	/// be gracefully terminated.
 	///
-	/// If `0` is given monitoring will be disabled.
+	/// If `0` is given, the node is never terminated.
 	#[arg(long = "db-storage-threshold", value_name = "MiB", default_value_t = 1024)]
 	pub threshold: u64,
 
 	/// How often available space is polled.
 	#[arg(long = "db-storage-polling-period", value_name = "SECONDS", default_value_t = 5, value_parser = clap::value_parser!(u32).range(1..))]
 	pub polling_period: u32,
+
+	/// Available space on database storage below which alerts are emitted.
+	///
+	/// Alerts are logged, reported through the `substrate_storage_monitor_status` metric and
+	/// sent to telemetry. If `0` is given, no alert is emitted before degrading the node.
+	#[arg(long = "db-storage-warning-threshold", value_name = "MiB", default_value_t = 0)]
+	pub warning_threshold: u64,
+
+	/// Available space on database storage below which the node degrades.
+	///
+	/// While degraded, the actions given by `--db-storage-degradation-action` are taken. Must be
+	/// greater than `--db-storage-threshold`. If `0` is given, the node is never degraded.
+	#[arg(long = "db-storage-degradation-threshold", value_name = "MiB", default_value_t = 0)]
+	pub degradation_threshold: u64,
+
+	/// Action taken while the node is degraded.
+	///
+	/// Can be passed multiple times, or as a comma separated list.
+	#[arg(
+		long = "db-storage-degradation-action",
+		value_name = "ACTION",
+		value_enum,
+		value_delimiter = ','
+	)]
+	pub degradation_actions: Vec<DegradationAction>,
+
+	/// Number of blocks whose state and body are kept by the `aggressive-pruning` action.
+	///
+	/// Only shrinks the configured pruning windows, archive nodes keep everything.
+	#[arg(long = "db-storage-degraded-pruning", value_name = "BLOCKS", default_value_t = 64, value_parser = clap::value_parser!(u32).range(1..))]
+	pub degraded_pruning: u32,
+}
+
+/// Status of the storage, from the healthiest to the most critical.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum StorageStatus {
+	/// Available space is above all thresholds.
+	Healthy,
+	/// Available space is below the warning threshold.
+	Low,
+	/// Available space is below the degradation threshold.
+	Degraded,
+	/// Available space is below the shutdown threshold.
+	Exhausted,
+}
+
+/// Callbacks executing the [`DegradationAction`]s, provided by the node.
+///
+/// Each callback is called with `true` when the node enters the degraded mode, and with `false`
+/// when it leaves it.
+#[derive(Default)]
+pub struct DegradationHandlers {
+	handlers: Vec<(DegradationAction, Box<dyn Fn(bool) + Send + Sync>)>,
+}
+
+impl DegradationHandlers {
+	/// Create new [`DegradationHandlers`] without any handler.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Register the callback executing `action`.
+	pub fn with(
+		mut self,
+		action: DegradationAction,
+		handler: impl Fn(bool) + Send + Sync + 'static,
+	) -> Self {
+		self.handlers.push((action, Box::new(handler)));
+		self
+	}
+
+	/// Only keep the handlers of the `enabled` actions, warning about the unsupported ones.
+	fn retain_enabled(&mut self, enabled: &[DegradationAction]) {
+		self.handlers.retain(|(action, _)| enabled.contains(action));
+
+		for action in enabled {
+			if !self.handlers.iter().any(|(a, _)| a == action) {
+				log::warn!(
+					target: LOG_TARGET,
+					"Degradation action `{action}` is not supported by this node, ignoring it",
+				);
+			}
+		}
+	}
+
+	/// Call all handlers.
+	fn set_degraded(&self, degraded: bool) {
+		for (action, handler) in &self.handlers {
+			log::info!(
+				target: LOG_TARGET,
+				"{} degradation action `{action}`",
+				if degraded { "Starting" } else { "Stopping" },
+			);
+			handler(degraded);
+		}
+	}
+}
+
+impl fmt::Debug for DegradationHandlers {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_list().entries(self.handlers.iter().map(|(action, _)| action)).finish()
+	}
+}
+
+/// Storage monitor metrics.
+struct Metrics {
+	available_space: Gauge<U64>,
+	status: Gauge<U64>,
+}
+
+impl Metrics {
+	fn register(registry: &Registry) -> std::result::Result<Self, PrometheusError> {
+		Ok(Self {
+			available_space: register(
+				Gauge::new(
+					"substrate_storage_monitor_available_space",
+					"Available space on database storage in MiB",
+				)?,
+				registry,
+			)?,
+			status: register(
+				Gauge::new(
+					"substrate_storage_monitor_status",
+					"Status of database storage: 0 healthy, 1 low, 2 degraded, 3 exhausted",
+				)?,
+				registry,
+			)?,
+		})
+	}
 }
 
 /// Storage monitor service: checks the available space for the filesystem for given path.
@@ -61,8 +227,20 @@ pub struct StorageMonitorService {
 	path: PathBuf,
 	/// number of megabytes that shall be free on the filesystem for watched path
 	threshold: u64,
+	/// number of megabytes below which alerts are emitted
+	warning_threshold: u64,
+	/// number of megabytes below which the node degrades
+	degradation_threshold: u64,
 	/// storage space polling period
 	polling_period: Duration,
+	/// current status of the storage
+	status: StorageStatus,
+	/// callbacks executing the enabled degradation actions
+	degradation_handlers: DegradationHandlers,
+	/// metrics, if prometheus is enabled
+	metrics: Option<Metrics>,
+	/// telemetry handle, if telemetry is enabled
+	telemetry: Option<TelemetryHandle>,
 }
 
 impl StorageMonitorService {
@@ -71,8 +249,14 @@ impl StorageMonitorService {
 		parameters: StorageMonitorParams,
 		path: PathBuf,
 		spawner: &impl SpawnEssentialNamed,
+		prometheus_registry: Option<&Registry>,
+		telemetry: Option<TelemetryHandle>,
+		mut degradation_handlers: DegradationHandlers,
 	) -> Result<()> {
-		if parameters.threshold == 0 {
+		if parameters.threshold == 0 &&
+			parameters.warning_threshold == 0 &&
+			parameters.degradation_threshold == 0
+		{
 			log::info!(
 				target: LOG_TARGET,
 				"StorageMonitorService: threshold `0` given, storage monitoring disabled",
@@ -84,12 +268,31 @@ impl StorageMonitorService {
 				path.display()
 			);
 
+			Self::check_thresholds(&parameters)?;
 			Self::check_free_space(&path, parameters.threshold)?;
 
+			degradation_handlers.retain_enabled(&parameters.degradation_actions);
+			let metrics = prometheus_registry.and_then(|registry| {
+				Metrics::register(registry)
+					.map_err(|err| {
+						log::warn!(
+							target: LOG_TARGET,
+							"Failed to register storage monitor prometheus metrics: {err}",
+						)
+					})
+					.ok()
+			});
+
 			let storage_monitor_service = StorageMonitorService {
 				path,
 				threshold: parameters.threshold,
+				warning_threshold: parameters.warning_threshold,
+				degradation_threshold: parameters.degradation_threshold,
 				polling_period: Duration::from_secs(parameters.polling_period.into()),
+				status: StorageStatus::Healthy,
+				degradation_handlers,
+				metrics,
+				telemetry,
 			};
 
 			spawner.spawn_essential(
@@ -104,13 +307,116 @@ impl StorageMonitorService {
 
 	/// Main monitoring loop, intended to be spawned as essential task. Quits if free space drop
 	/// below threshold.
-	async fn run(self) {
+	async fn run(mut self) {
 		loop {
+			match Self::free_space(&self.path) {
+				Ok(available_space) => {
+					self.update_status(available_space);
+					if self.status == StorageStatus::Exhausted {
+						break
+					}
+				},
+				Err(e) => {
+					log::error!(target: LOG_TARGET, "Could not read available space: {e:?}.");
+					break
+				},
+			}
 			tokio::time::sleep(self.polling_period).await;
-			if Self::check_free_space(&self.path, self.threshold).is_err() {
-				break
+		}
+	}
+
+	/// Returns the status of the storage for the given amount of available space in MiB.
+	fn status_for(&self, available_space: u64) -> StorageStatus {
+		let below = |threshold: u64, status: StorageStatus| {
+			// Levels already reached are only left with some margin.
+			let threshold = if self.status >= status {
+				threshold.saturating_add(threshold / 10)
+			} else {
+				threshold
 			};
+			available_space < threshold
+		};
+
+		if below(self.threshold, StorageStatus::Exhausted) {
+			StorageStatus::Exhausted
+		} else if below(self.degradation_threshold, StorageStatus::Degraded) {
+			StorageStatus::Degraded
+		} else if below(self.warning_threshold, StorageStatus::Low) {
+			StorageStatus::Low
+		} else {
+			StorageStatus::Healthy
+		}
+	}
+
+	/// Updates the status of the storage, and reports and acts on its changes.
+	fn update_status(&mut self, available_space: u64) {
+		log::trace!(
+			target: LOG_TARGET,
+			"free: {available_space}, status: {:?}, thresholds: {}/{}/{}.",
+			self.status,
+			self.warning_threshold,
+			self.degradation_threshold,
+			self.threshold,
+		);
+
+		let status = self.status_for(available_space);
+		if let Some(metrics) = &self.metrics {
+			metrics.available_space.set(available_space);
+			metrics.status.set(status as u64);
+		}
+		if status == self.status {
+			return
+		}
+
+		let path = self.path.display();
+		match status {
+			StorageStatus::Healthy => log::info!(
+				target: LOG_TARGET,
+				"Available space {available_space}MiB for path `{path}` is back to normal",
+			),
+			StorageStatus::Low => log::warn!(
+				target: LOG_TARGET,
+				"Available space {available_space}MiB for path `{path}` is running low",
+			),
+			StorageStatus::Degraded => log::warn!(
+				target: LOG_TARGET,
+				"Available space {available_space}MiB for path `{path}` dropped below degradation threshold: {}MiB, degrading the node",
+				self.degradation_threshold,
+			),
+			StorageStatus::Exhausted => log::error!(
+				target: LOG_TARGET,
+				"Available space {available_space}MiB for path `{path}` dropped below threshold: {}MiB , terminating...",
+				self.threshold,
+			),
+		}
+		telemetry!(
+			self.telemetry;
+			SUBSTRATE_INFO;
+			"storage_monitor.status";
+			"status" => ?status,
+			"available_space" => available_space,
+		);
+
+		let was_degraded = self.status >= StorageStatus::Degraded;
+		let is_degraded = status >= StorageStatus::Degraded;
+		if was_degraded != is_degraded {
+			self.degradation_handlers.set_degraded(is_degraded);
+		}
+
+		self.status = status;
+	}
+
+	/// Checks that the node degrades before it is terminated.
+	fn check_thresholds(parameters: &StorageMonitorParams) -> Result<()> {
+		if parameters.degradation_threshold != 0 &&
+			parameters.degradation_threshold <= parameters.threshold
+		{
+			return Err(Error::InvalidDegradationThreshold(
+				parameters.degradation_threshold,
+				parameters.threshold,
+			))
 		}
+		Ok(())
 	}
 
 	/// Returns free space in MiB, or error if statvfs failed.
@@ -143,3 +449,106 @@ impl StorageMonitorService {
 		}
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::sync::{Arc, Mutex};
+
+	/// Service with the 100/1000/2000MiB thresholds, recording the calls of the degradation
+	/// handler.
+	fn service() -> (StorageMonitorService, Arc<Mutex<Vec<bool>>>) {
+		let calls = Arc::new(Mutex::new(Vec::new()));
+		let recorded = calls.clone();
+		let degradation_handlers = DegradationHandlers::new()
+			.with(DegradationAction::AggressivePruning, move |degraded| {
+				recorded.lock().unwrap().push(degraded)
+			});
+		let service = StorageMonitorService {
+			path: PathBuf::new(),
+			threshold: 100,
+			warning_threshold: 2000,
+			degradation_threshold: 1000,
+			polling_period: Duration::from_secs(1),
+			status: StorageStatus::Healthy,
+			degradation_handlers,
+			metrics: None,
+			telemetry: None,
+		};
+		(service, calls)
+	}
+
+	#[test]
+	fn status_follows_thresholds() {
+		let (service, _) = service();
+
+		assert_eq!(service.status_for(2000), StorageStatus::Healthy);
+		assert_eq!(service.status_for(1999), StorageStatus::Low);
+		assert_eq!(service.status_for(1000), StorageStatus::Low);
+		assert_eq!(service.status_for(999), StorageStatus::Degraded);
+		assert_eq!(service.status_for(100), StorageStatus::Degraded);
+		assert_eq!(service.status_for(99), StorageStatus::Exhausted);
+	}
+
+	#[test]
+	fn levels_are_left_with_margin() {
+		let (mut service, _) = service();
+
+		service.update_status(999);
+		assert_eq!(service.status, StorageStatus::Degraded);
+		service.update_status(1099);
+		assert_eq!(service.status, StorageStatus::Degraded);
+		service.update_status(1100);
+		assert_eq!(service.status, StorageStatus::Low);
+		service.update_status(2199);
+		assert_eq!(service.status, StorageStatus::Low);
+		service.update_status(2200);
+		assert_eq!(service.status, StorageStatus::Healthy);
+
+		// levels are entered without margin
+		service.update_status(1999);
+		assert_eq!(service.status, StorageStatus::Low);
+	}
+
+	#[test]
+	fn degradation_handlers_are_called_on_enter_and_exit() {
+		let (mut service, calls) = service();
+
+		service.update_status(1500);
+		assert!(calls.lock().unwrap().is_empty());
+
+		service.update_status(900);
+		service.update_status(950);
+		assert_eq!(*calls.lock().unwrap(), vec![true]);
+
+		// terminating keeps the node degraded
+		service.update_status(50);
+		assert_eq!(*calls.lock().unwrap(), vec![true]);
+
+		service.update_status(1500);
+		assert_eq!(*calls.lock().unwrap(), vec![true, false]);
+
+		// jumping straight to termination degrades the node first
+		service.update_status(3000);
+		service.update_status(50);
+		assert_eq!(*calls.lock().unwrap(), vec![true, false, true]);
+	}
+
+	#[test]
+	fn degradation_threshold_must_exceed_threshold() {
+		let parameters = |threshold, degradation_threshold| StorageMonitorParams {
+			threshold,
+			degradation_threshold,
+			..Default::default()
+		};
+
+		assert!(StorageMonitorService::check_thresholds(&parameters(1024, 0)).is_ok());
+		assert!(StorageMonitorService::check_thresholds(&parameters(1024, 2048)).is_ok());
+		assert!(StorageMonitorService::check_thresholds(&parameters(0, 2048)).is_ok());
+		assert!(matches!(
+			StorageMonitorService::check_thresholds(&parameters(1024, 1024)),
+			Err(Error::InvalidDegradationThreshold(1024, 1024))
+		));
+		assert!(StorageMonitorService::check_thresholds(&parameters(2048, 1024)).is_err());
+	}
+}
@@ -33,3 +33,8 @@ impl std::error::Error for DatabaseError {
 
 /// A specialized `Result` type for database operations.
 pub type Result<T> = std::result::Result<T, DatabaseError>;
+
+/// Returns an error for operations that are not supported by the database.
+pub fn unsupported(message: &str) -> DatabaseError {
+	DatabaseError(Box::new(std::io::Error::new(std::io::ErrorKind::Unsupported, message)))
+}
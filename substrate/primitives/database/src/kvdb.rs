@@ -17,10 +17,25 @@
 
 /// A wrapper around `kvdb::Database` that implements `sp_database::Database` trait
 use ::kvdb::{DBTransaction, KeyValueDB};
+use parking_lot::RwLock;
+use std::path::Path;
 
 use crate::{error, Change, ColumnId, Database, Transaction};
 
-struct DbAdapter<D: KeyValueDB + 'static>(D);
+/// Maximal number of entries that are written to the snapshot database in a single transaction.
+const SNAPSHOT_BATCH_SIZE: usize = 16 * 1024;
+
+/// Opens an empty database at given path, where the snapshot is written to.
+pub type SnapshotTarget<D> = Box<dyn Fn(&Path) -> std::io::Result<D> + Send + Sync>;
+
+struct DbAdapter<D: KeyValueDB + 'static> {
+	db: D,
+	/// Number of database columns and the snapshot target, if snapshots are supported.
+	snapshots: Option<(u32, SnapshotTarget<D>)>,
+	/// Commits are holding the read lock, snapshots are holding the write lock while the
+	/// snapshot iterators are created.
+	commit_lock: RwLock<()>,
+}
 
 fn handle_err<T>(result: std::io::Result<T>) -> T {
 	match result {
@@ -37,7 +52,31 @@ where
 	D: KeyValueDB + 'static,
 	H: Clone + AsRef<[u8]>,
 {
-	std::sync::Arc::new(DbAdapter(db))
+	std::sync::Arc::new(DbAdapter { db, snapshots: None, commit_lock: RwLock::new(()) })
+}
+
+/// Wrap RocksDb database into a trait object that implements `sp_database::Database` and
+/// supports online snapshots.
+///
+/// The snapshot copies all `columns` of the database into the database, opened by `target`.
+/// Iterators of the database must read from the database state at the moment they are created
+/// (RocksDB iterators read from the implicit snapshot of the database). Commits are only blocked
+/// while the iterators of all columns are created, so the copy is consistent, but the database
+/// stays writable while it is being copied.
+pub fn as_database_with_snapshots<D, H>(
+	db: D,
+	columns: u32,
+	target: SnapshotTarget<D>,
+) -> std::sync::Arc<dyn Database<H>>
+where
+	D: KeyValueDB + 'static,
+	H: Clone + AsRef<[u8]>,
+{
+	std::sync::Arc::new(DbAdapter {
+		db,
+		snapshots: Some((columns, target)),
+		commit_lock: RwLock::new(()),
+	})
 }
 
 impl<D: KeyValueDB> DbAdapter<D> {
@@ -46,7 +85,7 @@ impl<D: KeyValueDB> DbAdapter<D> {
 		// Add a key suffix for the counter
 		let mut counter_key = key.to_vec();
 		counter_key.push(0);
-		Ok(match self.0.get(col, &counter_key).map_err(|e| error::DatabaseError(Box::new(e)))? {
+		Ok(match self.db.get(col, &counter_key).map_err(|e| error::DatabaseError(Box::new(e)))? {
 			Some(data) => {
 				let mut counter_data = [0; 4];
 				if data.len() != 4 {
@@ -66,6 +105,7 @@ impl<D: KeyValueDB> DbAdapter<D> {
 
 impl<D: KeyValueDB, H: Clone + AsRef<[u8]>> Database<H> for DbAdapter<D> {
 	fn commit(&self, transaction: Transaction<H>) -> error::Result<()> {
+		let _commit_guard = self.commit_lock.read();
 		let mut tx = DBTransaction::new();
 		for change in transaction.0.into_iter() {
 			match change {
@@ -105,14 +145,40 @@ impl<D: KeyValueDB, H: Clone + AsRef<[u8]>> Database<H> for DbAdapter<D> {
 				},
 			}
 		}
-		self.0.write(tx).map_err(|e| error::DatabaseError(Box::new(e)))
+		self.db.write(tx).map_err(|e| error::DatabaseError(Box::new(e)))
 	}
 
 	fn get(&self, col: ColumnId, key: &[u8]) -> Option<Vec<u8>> {
-		handle_err(self.0.get(col, key))
+		handle_err(self.db.get(col, key))
 	}
 
 	fn contains(&self, col: ColumnId, key: &[u8]) -> bool {
-		handle_err(self.0.has_key(col, key))
+		handle_err(self.db.has_key(col, key))
+	}
+
+	fn snapshot(&self, path: &Path) -> error::Result<()> {
+		let Some((columns, target)) = &self.snapshots else {
+			return Err(error::unsupported("snapshots are not supported by the database"))
+		};
+
+		let snapshot = target(path).map_err(|e| error::DatabaseError(Box::new(e)))?;
+		let iterators: Vec<_> = {
+			let _commit_guard = self.commit_lock.write();
+			(0..*columns).map(|col| self.db.iter(col)).collect()
+		};
+		for (col, iterator) in (0..*columns).zip(iterators) {
+			let mut tx = DBTransaction::new();
+			for entry in iterator {
+				let (key, value) = entry.map_err(|e| error::DatabaseError(Box::new(e)))?;
+				tx.put_vec(col, &key, value);
+				if tx.ops.len() >= SNAPSHOT_BATCH_SIZE {
+					snapshot
+						.write(std::mem::take(&mut tx))
+						.map_err(|e| error::DatabaseError(Box::new(e)))?;
+				}
+			}
+			snapshot.write(tx).map_err(|e| error::DatabaseError(Box::new(e)))?;
+		}
+		Ok(())
 	}
 }
@@ -21,7 +21,7 @@ pub mod error;
 mod kvdb;
 mod mem;
 
-pub use crate::kvdb::as_database;
+pub use crate::kvdb::{as_database, as_database_with_snapshots, SnapshotTarget};
 pub use mem::MemDb;
 
 /// An identifier for a column.
@@ -117,6 +117,15 @@ pub trait Database<H: Clone + AsRef<[u8]>>: Send + Sync {
 	///
 	/// Not all database implementations use a prefix for keys, so this function may be a noop.
 	fn sanitize_key(&self, _key: &mut Vec<u8>) {}
+
+	/// Write a consistent copy of the whole database to the new database at `path`.
+	///
+	/// The database stays usable while the snapshot is taken. Returns an error if snapshots are
+	/// not supported by the database. Only RocksDB databases support snapshots, ParityDB and
+	/// in-memory databases do not.
+	fn snapshot(&self, _path: &std::path::Path) -> error::Result<()> {
+		Err(error::unsupported("snapshots are not supported by the database"))
+	}
 }
 
 impl<H> std::fmt::Debug for dyn Database<H> {
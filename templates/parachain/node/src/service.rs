@@ -298,6 +298,7 @@ pub async fn start_parachain_node(
 				network_provider: Arc::new(network.clone()),
 				is_validator: parachain_config.role.is_authority(),
 				enable_http_requests: false,
+				http_policy: parachain_config.offchain_worker.http_policy.clone(),
 				custom_extensions: move |_| vec![],
 			})?;
 		task_manager.spawn_handle().spawn(
@@ -385,6 +386,8 @@ pub async fn start_parachain_node(
 		relay_chain_slot_duration,
 		recovery_handle: Box::new(overseer_handle.clone()),
 		sync_service: sync_service.clone(),
+		pov_recovery_config: collator_options.pov_recovery_config,
+		prometheus_registry: prometheus_registry.as_ref(),
 	})?;
 
 	if validator {
@@ -103,7 +103,7 @@ pub type Executive = frame_executive::Executive<
 	Block,
 	frame_system::ChainContext<Runtime>,
 	Runtime,
-	AllPalletsWithSystem,
+	cumulus_primitives_storage_weight_reclaim::StorageWeightReclaimHooks<AllPalletsWithSystem>,
 	Migrations,
 >;
 
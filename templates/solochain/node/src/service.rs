@@ -112,6 +112,7 @@ pub fn new_partial(config: &Configuration) -> Result<Service, ServiceError> {
 			check_for_equivocation: Default::default(),
 			telemetry: telemetry.as_ref().map(|x| x.handle()),
 			compatibility_mode: Default::default(),
+			verification_threads: config.import_verification_threads,
 		})?;
 
 	Ok(sc_service::PartialComponents {
@@ -195,6 +196,7 @@ pub fn new_full<
 				)),
 				network_provider: Arc::new(network.clone()),
 				enable_http_requests: true,
+				http_policy: config.offchain_worker.http_policy.clone(),
 				custom_extensions: |_| vec![],
 			})?;
 		task_manager.spawn_handle().spawn(
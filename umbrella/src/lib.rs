@@ -733,6 +733,10 @@ pub use pallet_xcm_bridge_hub;
 #[cfg(feature = "pallet-xcm-bridge-hub-router")]
 pub use pallet_xcm_bridge_hub_router;
 
+/// A pallet limiting the rate at which origins can execute XCM programs.
+#[cfg(feature = "pallet-xcm-rate-limiter")]
+pub use pallet_xcm_rate_limiter;
+
 /// Logic which is common to all parachain runtimes.
 #[cfg(feature = "parachains-common")]
 pub use parachains_common;
@@ -1532,6 +1536,10 @@ pub use tracing_gum;
 #[cfg(feature = "tracing-gum-proc-macro")]
 pub use tracing_gum_proc_macro;
 
+/// Multi-hop dry-run of XCM programs across chains.
+#[cfg(feature = "xcm-dry-run")]
+pub use xcm_dry_run;
+
 /// Test kit to emulate XCM program execution.
 #[cfg(feature = "xcm-emulator")]
 pub use xcm_emulator;